@@ -0,0 +1,200 @@
+//! `orchestra agent add|list|remove` — manage a codebase's subagents.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clap::{Args, Subcommand};
+
+use orchestra_core::{
+    registry,
+    types::{AgentConfig, Codebase, ProjectName},
+};
+use orchestra_renderer::{user_template_dir_at, Renderer, TemplateContext};
+use orchestra_sync::layout::resolve_managed_path;
+
+use crate::output::OutputOptions;
+
+/// Manage subagents registered for a codebase.
+#[derive(Subcommand, Debug)]
+pub enum AgentCommand {
+    /// Register a subagent and create its entry-point file.
+    Add(AgentAddArgs),
+
+    /// List the subagents registered for a codebase.
+    List {
+        /// Codebase whose subagents should be listed.
+        codebase: String,
+    },
+
+    /// Deregister a subagent. Its entry-point file is left in place.
+    Remove {
+        /// Codebase the subagent belongs to.
+        codebase: String,
+
+        /// Id of the subagent to remove.
+        agent_id: String,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct AgentAddArgs {
+    /// Codebase to add the subagent to.
+    pub codebase: String,
+
+    /// Subagent id (letters, digits, `-` and `_`).
+    pub agent_id: String,
+
+    /// Entry-point file, relative to the codebase root. Defaults to
+    /// `AGENT/<agent-id>.md`.
+    #[arg(long, value_name = "PATH")]
+    pub entry_point: Option<PathBuf>,
+
+    /// Skill the subagent uses. Repeatable.
+    #[arg(long = "skill", value_name = "SKILL")]
+    pub skills: Vec<String>,
+}
+
+pub fn run(cmd: AgentCommand, out: OutputOptions) -> Result<()> {
+    let home = super::home_dir()?;
+    match cmd {
+        AgentCommand::Add(args) => add(&home, args, out),
+        AgentCommand::List { codebase } => list(&home, &codebase, out),
+        AgentCommand::Remove { codebase, agent_id } => remove(&home, &codebase, &agent_id),
+    }
+}
+
+fn add(home: &Path, args: AgentAddArgs, out: OutputOptions) -> Result<()> {
+    super::validate_id("subagent", &args.agent_id)?;
+    let (project, mut codebase) = find_codebase(home, &args.codebase)?;
+    if registered_agents(&codebase).any(|agent| agent.agent_id == args.agent_id) {
+        bail!(
+            "subagent '{}' is already registered for '{}'",
+            args.agent_id,
+            codebase.name
+        );
+    }
+
+    let entry_point = args
+        .entry_point
+        .unwrap_or_else(|| PathBuf::from("AGENT").join(format!("{}.md", args.agent_id)));
+    let entry_file = resolve_managed_path(&codebase.path, &entry_point)
+        .context("the entry point must be inside the codebase")?;
+
+    let Some(target) = codebase.projects.first_mut() else {
+        bail!(
+            "codebase '{}' has no projects to attach a subagent to",
+            codebase.name
+        );
+    };
+    target.agents.push(AgentConfig {
+        agent_id: args.agent_id.clone(),
+        entry_point: entry_point.clone(),
+        skills: (!args.skills.is_empty()).then(|| args.skills.clone()),
+    });
+    codebase.updated_at = Utc::now();
+
+    let created = !entry_file.exists();
+    if !created {
+        if !out.quiet() {
+            println!("  kept existing entry point {}", entry_file.display());
+        }
+    } else {
+        let renderer = Renderer::cached(Some(&user_template_dir_at(home)))
+            .context("failed to initialise renderer")?;
+        let ctx = TemplateContext::from_codebase(&codebase);
+        let content = renderer
+            .render_subagent_entry(&ctx, &args.agent_id, &args.skills)
+            .context("failed to render subagent entry point")?;
+        if let Some(parent) = entry_file.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(&entry_file, content)
+            .with_context(|| format!("failed to write {}", entry_file.display()))?;
+        if !out.quiet() {
+            println!("  created {}", entry_file.display());
+        }
+    }
+
+    if let Err(err) = registry::save_codebase_at(home, &project, &codebase) {
+        // Leave no entry point behind for a subagent that was not registered.
+        if created {
+            let _ = fs::remove_file(&entry_file);
+        }
+        return Err(err)
+            .with_context(|| format!("failed to save registry for '{}'", codebase.name));
+    }
+    println!(
+        "Added subagent '{}' to '{}'. Run `orchestra sync {}` to list it in agent files.",
+        args.agent_id, codebase.name, codebase.name
+    );
+    Ok(())
+}
+
+fn list(home: &Path, codebase_name: &str, out: OutputOptions) -> Result<()> {
+    let (_, codebase) = find_codebase(home, codebase_name)?;
+    let mut agents = registered_agents(&codebase).peekable();
+    if agents.peek().is_none() {
+        println!("No subagents registered for '{}'.", codebase.name);
+        if !out.quiet() {
+            println!("Run: orchestra agent add {} <agent-id>", codebase.name);
+        }
+        return Ok(());
+    }
+
+    for agent in agents {
+        print!("  {}  {}", agent.agent_id, agent.entry_point.display());
+        match &agent.skills {
+            Some(skills) if !skills.is_empty() => println!("  [{}]", skills.join(", ")),
+            _ => println!(),
+        }
+    }
+    Ok(())
+}
+
+fn remove(home: &Path, codebase_name: &str, agent_id: &str) -> Result<()> {
+    let (project, mut codebase) = find_codebase(home, codebase_name)?;
+    let mut removed = None;
+    for group in &mut codebase.projects {
+        if let Some(index) = group.agents.iter().position(|a| a.agent_id == agent_id) {
+            removed = Some(group.agents.remove(index));
+            break;
+        }
+    }
+    let Some(removed) = removed else {
+        bail!(
+            "no subagent '{}' is registered for '{}'",
+            agent_id,
+            codebase.name
+        );
+    };
+    codebase.updated_at = Utc::now();
+
+    registry::save_codebase_at(home, &project, &codebase)
+        .with_context(|| format!("failed to save registry for '{}'", codebase.name))?;
+    println!(
+        "Removed subagent '{}' from '{}'. Its entry point {} was left in place.",
+        agent_id,
+        codebase.name,
+        removed.entry_point.display()
+    );
+    Ok(())
+}
+
+pub(crate) fn find_codebase(home: &Path, codebase_name: &str) -> Result<(ProjectName, Codebase)> {
+    registry::list_codebases_at(home)
+        .context("failed to load registry — run `orchestra init` first")?
+        .into_iter()
+        .find(|(_, codebase)| codebase.name.0 == codebase_name)
+        .with_context(|| {
+            format!(
+                "codebase '{codebase_name}' is not registered. Use `orchestra project list` to see registered codebases."
+            )
+        })
+}
+
+fn registered_agents(codebase: &Codebase) -> impl Iterator<Item = &AgentConfig> {
+    codebase.projects.iter().flat_map(|project| project.agents.iter())
+}
@@ -0,0 +1,151 @@
+//! `orchestra command set|list|unset` — manage the build, test, and lint
+//! commands rendered into a codebase's agent files.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clap::Subcommand;
+
+use orchestra_core::registry;
+
+use super::agent::find_codebase;
+use crate::output::OutputOptions;
+
+/// Manage the project commands listed in agent files.
+#[derive(Subcommand, Debug)]
+pub enum CommandCommand {
+    /// Add or replace a named command, e.g. `test "cargo test --workspace"`.
+    Set {
+        /// Codebase the command belongs to.
+        codebase: String,
+
+        /// Command name (letters, digits, `-` and `_`), e.g. `build`, `test`, `lint`.
+        name: String,
+
+        /// Shell command to run, quoted as one argument.
+        run: String,
+    },
+
+    /// List the commands registered for a codebase.
+    List {
+        /// Codebase whose commands should be listed.
+        codebase: String,
+    },
+
+    /// Remove a named command.
+    Unset {
+        /// Codebase the command belongs to.
+        codebase: String,
+
+        /// Name of the command to remove.
+        name: String,
+    },
+}
+
+pub fn run(cmd: CommandCommand, out: OutputOptions) -> Result<()> {
+    let home = super::home_dir()?;
+    match cmd {
+        CommandCommand::Set {
+            codebase,
+            name,
+            run,
+        } => set(&home, &codebase, &name, &run),
+        CommandCommand::List { codebase } => list(&home, &codebase, out),
+        CommandCommand::Unset { codebase, name } => unset(&home, &codebase, &name),
+    }
+}
+
+fn set(home: &Path, codebase_name: &str, name: &str, run: &str) -> Result<()> {
+    validate_command_name(name)?;
+    if run.trim().is_empty() {
+        bail!("command '{name}' cannot be empty");
+    }
+    let (project, mut codebase) = find_codebase(home, codebase_name)?;
+
+    // A command already defined by a later project is replaced in place;
+    // new commands go on the first project.
+    let target = match codebase
+        .projects
+        .iter()
+        .position(|p| p.commands.contains_key(name))
+    {
+        Some(index) => &mut codebase.projects[index],
+        None => match codebase.projects.first_mut() {
+            Some(first) => first,
+            None => bail!(
+                "codebase '{}' has no projects to attach a command to",
+                codebase.name
+            ),
+        },
+    };
+    target.commands.insert(name.to_string(), run.trim().to_string());
+    codebase.updated_at = Utc::now();
+
+    registry::save_codebase_at(home, &project, &codebase)
+        .with_context(|| format!("failed to save registry for '{}'", codebase.name))?;
+    println!(
+        "Set command '{}' for '{}'. Run `orchestra sync {}` to update agent files.",
+        name, codebase.name, codebase.name
+    );
+    Ok(())
+}
+
+fn list(home: &Path, codebase_name: &str, out: OutputOptions) -> Result<()> {
+    let (_, codebase) = find_codebase(home, codebase_name)?;
+    let mut commands: Vec<(&String, &String)> = codebase
+        .projects
+        .iter()
+        .flat_map(|p| p.commands.iter())
+        .collect();
+    if commands.is_empty() {
+        println!("No commands registered for '{}'.", codebase.name);
+        if !out.quiet() {
+            println!(
+                "Run: orchestra command set {} test \"<command>\"",
+                codebase.name
+            );
+        }
+        return Ok(());
+    }
+
+    commands.sort_by_key(|(name, _)| *name);
+    commands.dedup_by_key(|(name, _)| *name);
+    let width = commands.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, run) in commands {
+        println!("  {name:<width$}  {run}");
+    }
+    Ok(())
+}
+
+fn unset(home: &Path, codebase_name: &str, name: &str) -> Result<()> {
+    let (project, mut codebase) = find_codebase(home, codebase_name)?;
+    let mut removed = false;
+    for group in &mut codebase.projects {
+        removed |= group.commands.remove(name).is_some();
+    }
+    if !removed {
+        bail!(
+            "no command '{}' is registered for '{}'",
+            name,
+            codebase.name
+        );
+    }
+    codebase.updated_at = Utc::now();
+
+    registry::save_codebase_at(home, &project, &codebase)
+        .with_context(|| format!("failed to save registry for '{}'", codebase.name))?;
+    println!("Removed command '{}' from '{}'.", name, codebase.name);
+    Ok(())
+}
+
+fn validate_command_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        bail!("invalid command name '{name}'; use letters, digits, '-' and '_'");
+    }
+    Ok(())
+}
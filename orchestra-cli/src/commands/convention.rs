@@ -0,0 +1,114 @@
+//! `orchestra convention set-agent|reset-agent` — replace or restore the
+//! built-in convention bullets rendered into one agent's files.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clap::Subcommand;
+
+use orchestra_core::registry;
+use orchestra_renderer::AgentKind;
+
+use super::agent::find_codebase;
+
+/// Manage per-agent convention bullets.
+#[derive(Subcommand, Debug)]
+pub enum ConventionCommand {
+    /// Replace an agent's built-in convention bullets with the lines of a file.
+    SetAgent {
+        /// Codebase the conventions belong to.
+        codebase: String,
+
+        /// Agent whose bullets are replaced, e.g. `claude`, `cursor`, `roo`.
+        agent: AgentKind,
+
+        /// File with one convention per line. Blank lines are skipped and a
+        /// leading `- ` is dropped.
+        #[arg(long)]
+        file: PathBuf,
+    },
+
+    /// Go back to an agent's built-in convention bullets.
+    ResetAgent {
+        /// Codebase the conventions belong to.
+        codebase: String,
+
+        /// Agent whose override is removed.
+        agent: AgentKind,
+    },
+}
+
+pub fn run(cmd: ConventionCommand) -> Result<()> {
+    let home = super::home_dir()?;
+    match cmd {
+        ConventionCommand::SetAgent {
+            codebase,
+            agent,
+            file,
+        } => set_agent(&home, &codebase, agent, &file),
+        ConventionCommand::ResetAgent { codebase, agent } => reset_agent(&home, &codebase, agent),
+    }
+}
+
+fn set_agent(home: &Path, codebase_name: &str, agent: AgentKind, file: &Path) -> Result<()> {
+    let text =
+        fs::read_to_string(file).with_context(|| format!("failed to read {}", file.display()))?;
+    let bullets = parse_bullets(&text);
+    if bullets.is_empty() {
+        bail!(
+            "{} has no conventions; use `orchestra convention reset-agent` to restore the defaults",
+            file.display()
+        );
+    }
+    let (project, mut codebase) = find_codebase(home, codebase_name)?;
+    let count = bullets.len();
+    codebase
+        .agent_conventions
+        .insert(agent.name().to_string(), bullets);
+    codebase.updated_at = Utc::now();
+
+    registry::save_codebase_at(home, &project, &codebase)
+        .with_context(|| format!("failed to save registry for '{}'", codebase.name))?;
+    println!(
+        "Set {} convention(s) for {} in '{}'. Run `orchestra sync {}` to update agent files.",
+        count, agent, codebase.name, codebase.name
+    );
+    Ok(())
+}
+
+fn reset_agent(home: &Path, codebase_name: &str, agent: AgentKind) -> Result<()> {
+    let (project, mut codebase) = find_codebase(home, codebase_name)?;
+    if codebase.agent_conventions.remove(agent.name()).is_none() {
+        bail!(
+            "'{}' has no convention override for {}",
+            codebase.name,
+            agent
+        );
+    }
+    codebase.updated_at = Utc::now();
+
+    registry::save_codebase_at(home, &project, &codebase)
+        .with_context(|| format!("failed to save registry for '{}'", codebase.name))?;
+    println!(
+        "Restored the built-in conventions for {} in '{}'.",
+        agent, codebase.name
+    );
+    Ok(())
+}
+
+/// One convention per non-blank line, without a leading `- ` or `* ` marker.
+fn parse_bullets(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .map(|line| {
+            line.strip_prefix("- ")
+                .or_else(|| line.strip_prefix("* "))
+                .unwrap_or(line)
+                .trim()
+        })
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
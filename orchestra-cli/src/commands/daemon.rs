@@ -1,28 +1,78 @@
-//! `orchestra daemon` — background watcher lifecycle and launchd management.
+//! `orchestra daemon` — background watcher lifecycle and launchd/systemd
+//! management.
 
 use std::collections::VecDeque;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::time::Duration;
 
 use anyhow::{Context, Result};
+use chrono::Utc;
 use clap::{Args, Subcommand};
 
-use orchestra_daemon::paths::{socket_path, stderr_log_path, stdout_log_path};
+use orchestra_daemon::events;
+use orchestra_daemon::paths::{socket_path, stderr_log_path, stdout_log_path, DAEMON_LABEL};
+use orchestra_daemon::state::{self, ProcessState};
 use orchestra_daemon::{
-    install_launchd, request_status, request_stop, start_blocking, uninstall_launchd, DaemonError,
+    daemon_version_warning, install_service, installed_service, launchd_status, request_status,
+    request_stop, start_blocking, uninstall_service, wait_for_shutdown, DaemonError,
+    DaemonOptions, LaunchdServiceState, LaunchdState, DEFAULT_RESYNC_INTERVAL, SERVICE_MANAGER,
+    SHUTDOWN_TIMEOUT,
 };
 
+/// `DEFAULT_RESYNC_INTERVAL` in whole hours, as the CLI takes it.
+pub(super) const DEFAULT_RESYNC_HOURS: u64 = DEFAULT_RESYNC_INTERVAL.as_secs() / (60 * 60);
+
+/// Longest `--resync-interval` accepted, a year; anything longer would
+/// overflow the daemon's timer.
+const MAX_RESYNC_HOURS: u64 = 365 * 24;
+
 #[derive(Subcommand, Debug)]
 pub enum DaemonCommand {
     /// Run daemon in foreground (watcher + socket server).
-    Start,
-    /// Request graceful daemon shutdown over Unix socket.
-    Stop,
+    Start {
+        /// Skip syncing never-synced and stale codebases at startup.
+        #[arg(long)]
+        no_startup_sync: bool,
+
+        /// Re-sync every codebase this many hours apart, at most 8760; 0
+        /// disables it.
+        #[arg(
+            long,
+            value_name = "HOURS",
+            default_value_t = DEFAULT_RESYNC_HOURS,
+            value_parser = clap::value_parser!(u64).range(..=MAX_RESYNC_HOURS)
+        )]
+        resync_interval: u64,
+    },
+    /// Request graceful daemon shutdown over Unix socket and wait for it to exit.
+    Stop {
+        /// Return as soon as the daemon acknowledges the request.
+        #[arg(long)]
+        no_wait: bool,
+    },
+    /// Stop the daemon, wait for it to exit, then start it in the foreground.
+    Restart,
     /// Query daemon runtime status over Unix socket.
     Status,
-    /// Install and bootstrap launchd agent.
-    Install,
-    /// Boot out and remove launchd agent.
+    /// Install and start the launchd agent (macOS) or systemd user unit
+    /// (Linux).
+    Install {
+        /// Have the managed daemon skip its startup sync.
+        #[arg(long)]
+        no_startup_sync: bool,
+
+        /// Hours between the managed daemon's full re-syncs, at most 8760;
+        /// 0 disables them.
+        #[arg(
+            long,
+            value_name = "HOURS",
+            default_value_t = DEFAULT_RESYNC_HOURS,
+            value_parser = clap::value_parser!(u64).range(..=MAX_RESYNC_HOURS)
+        )]
+        resync_interval: u64,
+    },
+    /// Stop and remove the launchd agent or systemd user unit.
     Uninstall,
     /// Print recent daemon log lines.
     Logs(DaemonLogsArgs),
@@ -37,53 +87,122 @@ pub struct DaemonLogsArgs {
     /// Show only stderr log file.
     #[arg(long)]
     pub stderr_only: bool,
+
+    /// Show the structured event log (`events.jsonl`) instead, one JSON
+    /// object per line.
+    #[arg(long, conflicts_with = "stderr_only")]
+    pub events: bool,
+
+    /// Only show events for this codebase.
+    #[arg(long, requires = "events")]
+    pub codebase: Option<String>,
+
+    /// Only show events newer than this age, e.g. `30m`, `12h`, `7d`.
+    #[arg(long, requires = "events", value_parser = super::history::parse_since)]
+    pub since: Option<chrono::Duration>,
 }
 
 pub fn run(command: DaemonCommand) -> Result<()> {
-    let home = dirs::home_dir().context("could not determine home directory")?;
+    let home = super::home_dir()?;
 
     match command {
-        DaemonCommand::Start => {
-            start_blocking(&home).context("daemon exited with error")?;
+        DaemonCommand::Start {
+            no_startup_sync,
+            resync_interval,
+        } => {
+            let options = DaemonOptions {
+                startup_sync: !no_startup_sync,
+                resync_interval: (resync_interval > 0)
+                    .then(|| Duration::from_secs(resync_interval * 60 * 60)),
+            };
+            start_blocking(&home, options).context("daemon exited with error")?;
         }
-        DaemonCommand::Stop => match request_stop(&home) {
-            Ok(()) => println!("daemon stop requested"),
+        DaemonCommand::Stop { no_wait } => match request_stop(&home) {
+            Ok(stop) if no_wait => println!("daemon stop requested{}", drain_note(&stop)),
+            Ok(stop) => {
+                wait_for_shutdown(&home, SHUTDOWN_TIMEOUT)
+                    .context("daemon acknowledged stop but did not exit")?;
+                println!("daemon stopped{}", drain_note(&stop));
+            }
             Err(DaemonError::DaemonNotRunning { .. }) => {
                 println!("daemon is not running");
             }
             Err(err) => return Err(err).context("failed to stop daemon"),
         },
-        DaemonCommand::Status => match request_status(&home) {
-            Ok(status) => {
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&status)
-                        .context("failed to render daemon status JSON")?
-                );
+        DaemonCommand::Restart => restart(&home)?,
+        DaemonCommand::Status => {
+            let launchd = launchd_state(&home);
+            let mut process = None;
+            let (running, mut payload) = match request_status(&home) {
+                Ok(status) => {
+                    if let Some(warning) = daemon_version_warning(&status) {
+                        eprintln!("⚠  {warning}");
+                    }
+                    (
+                        true,
+                        serde_json::to_value(&status)
+                            .context("failed to render daemon status JSON")?,
+                    )
+                }
+                Err(DaemonError::DaemonNotRunning { .. }) => {
+                    let state = state::inspect_at(&home);
+                    process = Some(state);
+                    (
+                        false,
+                        serde_json::json!({
+                            "running": false,
+                            "socket": socket_path(&home).display().to_string(),
+                            "process": state,
+                        }),
+                    )
+                }
+                Err(err) => return Err(err).context("failed to query daemon status"),
+            };
+            if let Some(object) = payload.as_object_mut() {
+                let value = match &launchd {
+                    Some(state) => serde_json::to_value(state)
+                        .context("failed to render daemon status JSON")?,
+                    None => serde_json::Value::Null,
+                };
+                object.insert("launchd".to_string(), value);
             }
-            Err(DaemonError::DaemonNotRunning { .. }) => {
-                let payload = serde_json::json!({
-                    "running": false,
-                    "socket": socket_path(&home).display().to_string(),
-                });
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&payload)
-                        .context("failed to render daemon status JSON")?
-                );
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&payload)
+                    .context("failed to render daemon status JSON")?
+            );
+            if let Some(warning) = process.and_then(process_warning) {
+                eprintln!("⚠  {warning}");
+            }
+            if let Some(state) = &launchd {
+                reconcile_launchd(running, state);
             }
-            Err(err) => return Err(err).context("failed to query daemon status"),
-        },
-        DaemonCommand::Install => {
-            let path = install_launchd(&home).context("failed to install launchd service")?;
-            println!("installed launchd service: {}", path.display());
+        }
+        DaemonCommand::Install {
+            no_startup_sync,
+            resync_interval,
+        } => {
+            let mut start_args = Vec::new();
+            if no_startup_sync {
+                start_args.push("--no-startup-sync".to_string());
+            }
+            if resync_interval != DEFAULT_RESYNC_HOURS {
+                start_args.push(format!("--resync-interval={resync_interval}"));
+            }
+            let start_args: Vec<&str> = start_args.iter().map(String::as_str).collect();
+            let path = install_service(&home, &start_args)
+                .with_context(|| format!("failed to install {SERVICE_MANAGER} service"))?;
+            println!("installed {SERVICE_MANAGER} service: {}", path.display());
         }
         DaemonCommand::Uninstall => {
-            uninstall_launchd(&home).context("failed to uninstall launchd service")?;
-            println!("uninstalled launchd service");
+            uninstall_service(&home)
+                .with_context(|| format!("failed to uninstall {SERVICE_MANAGER} service"))?;
+            println!("uninstalled {SERVICE_MANAGER} service");
         }
         DaemonCommand::Logs(args) => {
-            if args.stderr_only {
+            if args.events {
+                print_events(&home, &args)?;
+            } else if args.stderr_only {
                 print_tail(&stderr_log_path(&home), args.lines)
                     .context("failed to read daemon stderr log")?;
             } else {
@@ -98,6 +217,117 @@ pub fn run(command: DaemonCommand) -> Result<()> {
     Ok(())
 }
 
+/// How the daemon's sync queue drained on stop, e.g. " (finished 1 in-flight
+/// sync request, refused 2 queued)". Empty when nothing was outstanding or
+/// the daemon did not report counts.
+fn drain_note(stop: &serde_json::Value) -> String {
+    let count = |key| stop.get(key).and_then(serde_json::Value::as_u64).unwrap_or(0);
+    let (drained, rejected) = (count("drained"), count("rejected"));
+    if drained + rejected == 0 {
+        return String::new();
+    }
+    let requests = if drained == 1 { "request" } else { "requests" };
+    format!(" (finished {drained} in-flight sync {requests}, refused {rejected} queued)")
+}
+
+fn restart(home: &std::path::Path) -> Result<()> {
+    match request_stop(home) {
+        Ok(stop) => {
+            wait_for_shutdown(home, SHUTDOWN_TIMEOUT)
+                .context("daemon acknowledged stop but did not exit")?;
+            println!("daemon stopped{}", drain_note(&stop));
+        }
+        Err(DaemonError::DaemonNotRunning { .. }) => println!("daemon was not running"),
+        Err(err) => return Err(err).context("failed to stop daemon"),
+    }
+
+    // launchd and systemd keep the service alive and relaunch it by
+    // themselves; starting a second copy here would race it for the socket.
+    if let Some(service) = installed_service(home) {
+        println!(
+            "{SERVICE_MANAGER} manages the daemon ({}) and will start it again.",
+            service.display()
+        );
+        println!("Check it with `orchestra daemon status`.");
+        return Ok(());
+    }
+
+    println!("starting daemon in the foreground");
+    start_blocking(home, DaemonOptions::default()).context("daemon exited with error")
+}
+
+/// What launchd thinks of the service, or `None` off macOS. A failing
+/// `launchctl` is reported as a warning rather than failing `daemon status`.
+fn launchd_state(home: &std::path::Path) -> Option<LaunchdState> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    match launchd_status(home) {
+        Ok(state) => Some(state),
+        Err(err) => {
+            eprintln!("⚠  could not query launchd: {err}");
+            None
+        }
+    }
+}
+
+/// Why a daemon that does not answer on its socket is not simply stopped.
+fn process_warning(process: ProcessState) -> Option<String> {
+    match process {
+        ProcessState::Stopped => None,
+        ProcessState::StaleSocket { pid: Some(pid) } => Some(format!(
+            "the daemon socket is present but process {pid} is dead; the daemon crashed \
+             or was killed — the next `orchestra daemon start` removes the stale socket"
+        )),
+        ProcessState::StaleSocket { pid: None } => Some(
+            "the daemon socket is present but no daemon process is recorded; \
+             the next `orchestra daemon start` removes the stale socket"
+                .to_string(),
+        ),
+        ProcessState::StalePidFile { pid } => Some(format!(
+            "the last daemon (pid {pid}) exited without shutting down cleanly"
+        )),
+        ProcessState::Unresponsive { pid } => Some(format!(
+            "daemon process {pid} is alive but not answering on its socket; \
+             it may still be starting or be stuck"
+        )),
+    }
+}
+
+/// Explains disagreements between the socket and launchd on stderr, keeping
+/// stdout as plain JSON.
+fn reconcile_launchd(socket_up: bool, launchd: &LaunchdState) {
+    match (socket_up, &launchd.state) {
+        (false, LaunchdServiceState::Running | LaunchdServiceState::Waiting) => eprintln!(
+            "⚠  launchd has the service loaded ({launchd}) but the socket is not answering; \
+             it may be restarting or crash-looping — see `orchestra daemon logs --stderr-only`"
+        ),
+        (true, LaunchdServiceState::NotLoaded) if launchd.installed => eprintln!(
+            "⚠  the daemon is running but launchd has not loaded {DAEMON_LABEL}; \
+             it will not restart on crash or login — run `orchestra daemon install` again"
+        ),
+        _ => {}
+    }
+}
+
+/// Print the last `args.lines` events matching the `--codebase` and
+/// `--since` filters.
+fn print_events(home: &std::path::Path, args: &DaemonLogsArgs) -> Result<()> {
+    let cutoff = args
+        .since
+        .map(|age| (Utc::now() - age).timestamp().max(0) as u64);
+    let matching = events::read_at(home, args.codebase.as_deref(), cutoff)
+        .context("failed to read daemon event log")?;
+    if matching.is_empty() {
+        println!("No daemon events found.");
+        return Ok(());
+    }
+    for event in &matching[matching.len().saturating_sub(args.lines)..] {
+        println!("{}", serde_json::to_string(event)?);
+    }
+    Ok(())
+}
+
 fn print_tail(path: &std::path::Path, lines: usize) -> Result<()> {
     if !path.exists() {
         println!("log file not found: {}", path.display());
@@ -0,0 +1,150 @@
+//! `orchestra detect <path> [--json] [--save <codebase>]` — what Orchestra
+//! thinks a directory is, optionally recorded in the registry.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use clap::Args;
+
+use orchestra_core::{registry, types::StackDetection};
+use orchestra_detector::{
+    detect_stack_detailed, indicator_files, Confidence, DetectedStack, DetectionReport,
+    DetectorOptions,
+};
+use orchestra_sync::detection::indicator_digest;
+
+use super::agent::find_codebase;
+
+/// Arguments for `orchestra detect`.
+#[derive(Args, Debug)]
+pub struct DetectArgs {
+    /// Directory to inspect (default with `--save`: the codebase root).
+    #[arg(required_unless_present = "save")]
+    pub path: Option<PathBuf>,
+
+    /// Print the result as JSON.
+    #[arg(long)]
+    pub json: bool,
+
+    /// Write the result into this registered codebase's project type,
+    /// framework and detection record.
+    #[arg(long, value_name = "CODEBASE")]
+    pub save: Option<String>,
+}
+
+impl DetectArgs {
+    pub fn run(self) -> Result<()> {
+        let home = super::home_dir()?;
+        let target = match &self.save {
+            Some(name) => Some(find_codebase(&home, name)?),
+            None => None,
+        };
+        let path = match (&self.path, &target) {
+            (Some(path), _) => path.clone(),
+            (None, Some((_, codebase))) => codebase.path.clone(),
+            (None, None) => unreachable!("clap requires a path without --save"),
+        };
+
+        let (stack, warnings) = detect_stack_detailed(&path, &DetectorOptions::default())
+            .map_err(|err| anyhow!("{err}"))?;
+        for warning in &warnings {
+            eprintln!("⚠  {warning}");
+        }
+
+        if self.json {
+            let report = DetectionReport::new(&path, stack.clone());
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).context("failed to serialize detect JSON")?
+            );
+        } else {
+            print_stack(&stack, &indicator_files(&path));
+        }
+
+        let Some((project_name, mut codebase)) = target else {
+            return Ok(());
+        };
+        if !same_dir(&path, &codebase.path) {
+            bail!(
+                "'{}' is not the root of codebase '{}' ({})",
+                path.display(),
+                codebase.name,
+                codebase.path.display()
+            );
+        }
+        let Some(project) = codebase.projects.first_mut() else {
+            bail!(
+                "codebase '{}' has no project to record the stack on",
+                codebase.name
+            );
+        };
+        project.project_type = stack.project_type.clone();
+        project.framework = stack.framework.clone();
+        project.detection = Some(stack_detection(&path, &stack, false));
+        codebase.updated_at = Utc::now();
+        registry::save_codebase_at(&home, &project_name, &codebase)
+            .context("failed to save detected stack")?;
+        if !self.json {
+            println!("✓ Saved to codebase '{}'", codebase.name);
+        }
+        Ok(())
+    }
+}
+
+/// What to record in the registry about `stack`, detected at `path`: the
+/// confidence, the time, and a digest of the root indicator files so a
+/// later change to them can be flagged.
+pub(crate) fn stack_detection(
+    path: &Path,
+    stack: &DetectedStack,
+    overridden: bool,
+) -> StackDetection {
+    let indicators = indicator_files(path);
+    StackDetection {
+        confidence: confidence_label(&stack.confidence).to_string(),
+        overridden,
+        detected_at: Some(Utc::now()),
+        indicators_sha256: Some(indicator_digest(path, &indicators)),
+        indicators,
+        language: Some(stack.primary_language.clone()),
+        package_manager: stack.package_manager.clone(),
+    }
+}
+
+pub(crate) fn confidence_label(confidence: &Confidence) -> &'static str {
+    match confidence {
+        Confidence::High => "high",
+        Confidence::Medium => "medium",
+    }
+}
+
+fn print_stack(stack: &DetectedStack, indicators: &[String]) {
+    println!("Language:    {}", stack.primary_language);
+    println!(
+        "Framework:   {}",
+        stack.framework.as_deref().unwrap_or("none identified")
+    );
+    if let Some(variant) = &stack.variant {
+        println!("Variant:     {variant}");
+    }
+    if let Some(detail) = &stack.detail {
+        println!("Found in:    {detail}");
+    }
+    if let Some(manager) = &stack.package_manager {
+        println!("Packages:    {manager}");
+    }
+    println!("Type:        {}", stack.project_type);
+    println!("Confidence:  {}", confidence_label(&stack.confidence));
+    if !indicators.is_empty() {
+        println!("Indicators:  {}", indicators.join(", "));
+    }
+}
+
+/// Whether `a` and `b` name the same directory.
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
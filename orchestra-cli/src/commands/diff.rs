@@ -1,38 +1,228 @@
-//! `orchestra diff <codebase>` — show unified diffs for what sync would write.
+//! `orchestra diff <codebase>|--all|--project <name> [--context <N>] [--stat]`
+//! — show unified diffs for what sync would write.
 
+use std::io::{self, IsTerminal};
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
 use clap::Args;
+use colored::{ColoredString, Colorize};
 
-use orchestra_sync::diff_codebase;
+use orchestra_sync::{
+    diff_all_with_context, diff_codebase_with_context, DiffCodebaseResult, FileDiff,
+    DEFAULT_CONTEXT_LINES,
+};
+
+use super::plural;
+use crate::output::OutputOptions;
 
 /// Arguments for `orchestra diff`.
 #[derive(Args, Debug)]
 pub struct DiffArgs {
-    /// Codebase name to diff.
-    pub codebase: String,
+    /// Codebase name to diff (omit when using `--all` or `--project`).
+    #[arg(required_unless_present_any = ["all", "project"])]
+    pub codebase: Option<String>,
+
+    /// Diff every registered codebase.
+    #[arg(long, conflicts_with_all = ["codebase", "project"])]
+    pub all: bool,
+
+    /// Diff every codebase in this registry project.
+    #[arg(long, value_name = "NAME", conflicts_with = "codebase")]
+    pub project: Option<String>,
+
+    /// Lines of unchanged context shown around each hunk.
+    #[arg(long, short = 'U', value_name = "N", default_value_t = DEFAULT_CONTEXT_LINES)]
+    pub context: usize,
+
+    /// Print only the lines added and removed per file, without hunks.
+    #[arg(long)]
+    pub stat: bool,
 }
 
 impl DiffArgs {
-    pub fn run(self) -> Result<()> {
-        let home: PathBuf = dirs::home_dir().context("could not determine home directory")?;
+    /// With `--all` or `--project`, the global `--verbose` also lists
+    /// codebases that would not change; `--quiet` prints only the totals.
+    pub fn run(self, out: OutputOptions) -> Result<()> {
+        let home: PathBuf = super::home_dir()?;
 
-        let result = diff_codebase(&self.codebase, &home)
-            .with_context(|| format!("diff failed for '{}'", self.codebase))?;
+        let Some(name) = self.codebase.as_deref() else {
+            let results = diff_all_with_context(&home, self.project.as_deref(), self.context)
+                .context("diff failed")?;
+            return self.print_many(&results, out);
+        };
+
+        let result = diff_codebase_with_context(name, &home, self.context)
+            .with_context(|| format!("diff failed for '{name}'"))?;
+        if !out.quiet() {
+            super::print_budget_overruns(&result.codebase_name, &result.budget_overruns);
+        }
 
         if result.diffs.is_empty() {
             println!("No differences for '{}'.", result.codebase_name);
             return Ok(());
         }
+        if out.quiet() {
+            println!(
+                "'{}' — {}, +{}/-{} lines",
+                result.codebase_name,
+                plural(result.diffs.len(), "file"),
+                result.added(),
+                result.removed()
+            );
+            return Ok(());
+        }
+        print_diffs(&result, self.stat);
+        Ok(())
+    }
+
+    fn print_many(&self, results: &[DiffCodebaseResult], out: OutputOptions) -> Result<()> {
+        if results.is_empty() {
+            match &self.project {
+                Some(project) => println!("No codebases registered under project '{project}'."),
+                None => println!("No codebases registered. Run `orchestra init` first."),
+            }
+            return Ok(());
+        }
 
-        for diff in result.diffs {
-            print!("{}", diff.unified_diff);
-            if !diff.unified_diff.ends_with('\n') {
-                println!();
+        let changed: Vec<&DiffCodebaseResult> =
+            results.iter().filter(|result| !result.diffs.is_empty()).collect();
+        if !out.quiet() {
+            for result in results {
+                super::print_budget_overruns(&result.codebase_name, &result.budget_overruns);
+                if result.diffs.is_empty() {
+                    if out.verbose() {
+                        println!("=== {} — no differences", result.codebase_name);
+                    }
+                    continue;
+                }
+                println!(
+                    "=== {} — {}, +{}/-{} lines",
+                    result.codebase_name,
+                    plural(result.diffs.len(), "file"),
+                    result.added(),
+                    result.removed()
+                );
+                print_diffs(result, self.stat);
             }
         }
 
+        let files: usize = changed.iter().map(|result| result.diffs.len()).sum();
+        let added: usize = changed.iter().map(|result| result.added()).sum();
+        let removed: usize = changed.iter().map(|result| result.removed()).sum();
+        println!(
+            "{} of {} would change, {}, +{added}/-{removed} lines",
+            changed.len(),
+            plural(results.len(), "codebase"),
+            plural(files, "file")
+        );
         Ok(())
     }
 }
+
+/// Each file's hunks, preceded by its `+N/-M` line on a terminal, or only
+/// that line when `stat_only`. Piped output stays a plain unified diff.
+fn print_diffs(result: &DiffCodebaseResult, stat_only: bool) {
+    let stat_lines = stat_only || io::stdout().is_terminal();
+    for diff in &result.diffs {
+        if stat_lines {
+            println!("{}", file_stat_line(diff));
+        }
+        if stat_only {
+            continue;
+        }
+        let unified = colorize_unified_diff(&diff.unified_diff);
+        print!("{unified}");
+        if !unified.ends_with('\n') {
+            println!();
+        }
+    }
+}
+
+/// `CLAUDE.md  +3/-1`, bold like the file headers.
+fn file_stat_line(diff: &FileDiff) -> String {
+    format!(
+        "{}  {}/{}",
+        diff.relative_path.display().to_string().bold(),
+        format!("+{}", diff.added).green(),
+        format!("-{}", diff.removed).red()
+    )
+}
+
+/// Color a unified diff line by line: file headers bold, hunk headers cyan,
+/// additions green and removals red. `colored` leaves the text plain when
+/// stdout is not a terminal or `NO_COLOR` is set, so piped output is the
+/// diff exactly as rendered.
+pub fn colorize_unified_diff(diff: &str) -> String {
+    diff.split_inclusive('\n')
+        .map(|line| {
+            let (text, newline) = match line.strip_suffix('\n') {
+                Some(text) => (text, "\n"),
+                None => (line, ""),
+            };
+            format!("{}{newline}", style_diff_line(text))
+        })
+        .collect()
+}
+
+fn style_diff_line(line: &str) -> ColoredString {
+    if line.starts_with("--- ") || line.starts_with("+++ ") {
+        line.bold()
+    } else if line.starts_with("@@") {
+        line.cyan()
+    } else if line.starts_with('+') {
+        line.green()
+    } else if line.starts_with('-') {
+        line.red()
+    } else {
+        line.normal()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use colored::{Color, Style};
+
+    use super::*;
+
+    const DIFF: &str = "--- a/CLAUDE.md\n+++ b/CLAUDE.md\n@@ -1,2 +1,2 @@\n # Title\n-old\n+new\n";
+
+    #[test]
+    fn diff_lines_are_styled_by_kind() {
+        let lines: Vec<ColoredString> = DIFF.lines().map(style_diff_line).collect();
+        assert_eq!(lines[0].style, Style::default().bold());
+        assert_eq!(lines[1].style, Style::default().bold());
+        assert_eq!(lines[2].fgcolor, Some(Color::Cyan));
+        assert_eq!(lines[3].fgcolor, None);
+        assert_eq!(lines[4].fgcolor, Some(Color::Red));
+        assert_eq!(lines[5].fgcolor, Some(Color::Green));
+    }
+
+    /// Clears the `colored` override when dropped, so other tests see the
+    /// default again even if an assertion fails.
+    struct ColorOverride;
+
+    impl ColorOverride {
+        fn set(enabled: bool) -> Self {
+            colored::control::set_override(enabled);
+            ColorOverride
+        }
+    }
+
+    impl Drop for ColorOverride {
+        fn drop(&mut self) {
+            colored::control::unset_override();
+        }
+    }
+
+    #[test]
+    fn uncolored_diff_is_unchanged() {
+        let _plain = ColorOverride::set(false);
+        assert_eq!(colorize_unified_diff(DIFF), DIFF);
+        let no_trailing_newline = DIFF.trim_end();
+        assert_eq!(
+            colorize_unified_diff(no_trailing_newline),
+            no_trailing_newline
+        );
+    }
+}
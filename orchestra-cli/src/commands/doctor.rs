@@ -1,16 +1,33 @@
 //! `orchestra doctor` — broad health diagnostics.
+//!
+//! Every check is a standalone function returning one or more
+//! [`DoctorFinding`]s. Findings that carry a safe remediation are applied by
+//! `--fix`; the command exits non-zero while any error-severity finding remains.
 
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use clap::Args;
 use colored::Colorize;
 use serde::Serialize;
 
-use orchestra_core::registry;
-use orchestra_daemon::{paths::socket_path, request_status, DaemonError};
-use orchestra_renderer::engine::{guide_path, pilot_path};
-use orchestra_sync::{managed_agent_paths, staleness};
+use orchestra_core::{
+    paths, registry,
+    types::{Codebase, CodebaseName, ProjectName},
+};
+use orchestra_daemon::{
+    daemon_version_warning,
+    paths::{launchd_plist_path, logs_dir, projects_root, socket_path},
+    request_status, DaemonError,
+};
+use orchestra_sync::{
+    hash_store, layout,
+    log_rotation::{rotate_if_needed, MAX_LOG_BYTES, MAX_ROTATED_FILES},
+    staleness,
+};
+
+use crate::output::OutputOptions;
 
 const REPO: &str = "Chris-Miracle/orch";
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -20,151 +37,109 @@ pub struct DoctorArgs {
     /// Emit machine-readable JSON.
     #[arg(long)]
     pub json: bool,
+
+    /// Apply safe remediations (remove stale socket, delete orphaned hash
+    /// stores, tighten registry permissions, rotate oversized logs, store
+    /// relative or symlinked codebase paths as their real path).
+    #[arg(long)]
+    pub fix: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    Ok,
+    Warn,
+    Error,
+}
+
+/// A safe remediation `--fix` may apply for a finding.
+#[derive(Debug, Clone)]
+enum Fix {
+    RemoveFile(PathBuf),
+    #[cfg_attr(not(unix), allow(dead_code))]
+    Chmod(PathBuf, u32),
+    RotateLog(PathBuf),
+    /// Rewrite a codebase's registered path to its canonical form.
+    CanonicalizePath {
+        home: PathBuf,
+        project: ProjectName,
+        codebase: CodebaseName,
+        path: PathBuf,
+    },
 }
 
 #[derive(Debug, Clone, Serialize)]
-struct DoctorCheck {
+struct DoctorFinding {
     name: String,
-    status: String,
-    detail: String,
+    severity: Severity,
+    message: String,
+    fixable: bool,
+    #[serde(skip)]
+    fix: Option<Fix>,
+}
+
+impl DoctorFinding {
+    fn new(name: &str, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            severity,
+            message: message.into(),
+            fixable: false,
+            fix: None,
+        }
+    }
+
+    fn ok(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, Severity::Ok, message)
+    }
+
+    fn warn(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, Severity::Warn, message)
+    }
+
+    fn error(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, Severity::Error, message)
+    }
+
+    fn with_fix(mut self, fix: Fix) -> Self {
+        self.fixable = true;
+        self.fix = Some(fix);
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
 struct DoctorReport {
     version: String,
-    checks: Vec<DoctorCheck>,
+    checks: Vec<DoctorFinding>,
 }
 
 impl DoctorArgs {
-    pub fn run(self) -> Result<()> {
-        let home: PathBuf = dirs::home_dir().context("could not determine home directory")?;
-        let mut checks = Vec::new();
+    pub fn run(self, out: OutputOptions) -> Result<()> {
+        let home: PathBuf = super::home_dir()?;
 
-        checks.push(version_check());
-        checks.push(path_check());
-        checks.push(daemon_socket_check(&home));
+        let mut checks = vec![version_check(), path_check()];
+        checks.extend(registry_permissions_check(&home));
+        checks.extend(daemon_socket_check(&home));
         checks.push(daemon_check(&home));
-
-        let codebases_res = registry::list_codebases_at(&home);
-        match codebases_res {
-            Ok(codebases) => {
-                checks.push(DoctorCheck {
-                    name: "registry integrity".into(),
-                    status: "pass".into(),
-                    detail: format!("{} codebase entries loaded", codebases.len()),
-                });
-
-                let mut missing_paths = Vec::new();
-                let mut missing_pilot = Vec::new();
-                let mut missing_guide = Vec::new();
-                let mut stale = 0usize;
-                let mut current = 0usize;
-                let mut other = 0usize;
-                let mut missing_managed = Vec::new();
-
-                for (project, codebase) in &codebases {
-                    if !codebase.path.exists() {
-                        missing_paths.push(format!("{}:{}", project.0, codebase.path.display()));
-                    }
-
-                    let pilot = pilot_path(&codebase.path);
-                    if !pilot.exists() {
-                        missing_pilot.push(format!("{}:{}", project.0, codebase.name.0));
-                    }
-
-                    let guide = guide_path(&codebase.path);
-                    if !guide.exists() {
-                        missing_guide.push(format!("{}:{}", project.0, codebase.name.0));
-                    }
-
-                    if let Ok(signal) = staleness::check(&home, project, codebase) {
-                        match signal {
-                            orchestra_sync::StalenessSignal::Current => current += 1,
-                            orchestra_sync::StalenessSignal::Stale { .. } => stale += 1,
-                            _ => other += 1,
-                        }
-                    }
-
-                    let mut expected = managed_agent_paths(&[(project.clone(), codebase.clone())]);
-                    expected.push(guide_path(&codebase.path));
-                    expected.push(pilot_path(&codebase.path));
-                    let missing = expected
-                        .into_iter()
-                        .filter(|p| !p.exists())
-                        .count();
-                    if missing > 0 {
-                        missing_managed.push(format!("{}:{} missing {}", project.0, codebase.name.0, missing));
-                    }
-                }
-
-                if missing_paths.is_empty() {
-                    checks.push(DoctorCheck {
-                        name: "codebase paths".into(),
-                        status: "pass".into(),
-                        detail: "all registered codebase paths exist".into(),
-                    });
-                } else {
-                    checks.push(DoctorCheck {
-                        name: "codebase paths".into(),
-                        status: "warn".into(),
-                        detail: format!("missing: {}", missing_paths.join(", ")),
-                    });
-                }
-
-                if missing_pilot.is_empty() {
-                    checks.push(DoctorCheck {
-                        name: "pilot.md presence".into(),
-                        status: "pass".into(),
-                        detail: "all codebases have orchestra/pilot.md".into(),
-                    });
-                } else {
-                    checks.push(DoctorCheck {
-                        name: "pilot.md presence".into(),
-                        status: "warn".into(),
-                        detail: format!("missing: {}", missing_pilot.join(", ")),
-                    });
-                }
-
-                if missing_guide.is_empty() {
-                    checks.push(DoctorCheck {
-                        name: "guide presence".into(),
-                        status: "pass".into(),
-                        detail: "all codebases have orchestra/.guide.md".into(),
-                    });
-                } else {
-                    checks.push(DoctorCheck {
-                        name: "guide presence".into(),
-                        status: "warn".into(),
-                        detail: format!("missing: {}", missing_guide.join(", ")),
-                    });
-                }
-
-                checks.push(DoctorCheck {
-                    name: "staleness summary".into(),
-                    status: if stale == 0 { "pass".into() } else { "warn".into() },
-                    detail: format!("current: {current}, stale: {stale}, other: {other}"),
-                });
-
-                if missing_managed.is_empty() {
-                    checks.push(DoctorCheck {
-                        name: "managed files presence".into(),
-                        status: "pass".into(),
-                        detail: "all expected managed files are present".into(),
-                    });
-                } else {
-                    checks.push(DoctorCheck {
-                        name: "managed files presence".into(),
-                        status: "warn".into(),
-                        detail: missing_managed.join(", "),
-                    });
-                }
-            }
-            Err(err) => {
-                checks.push(DoctorCheck {
-                    name: "registry integrity".into(),
-                    status: "fail".into(),
-                    detail: err.to_string(),
-                });
+        checks.push(launchd_plist_check(&home));
+        checks.extend(log_size_check(&home));
+
+        let (codebases, registry_findings) = registry_integrity_check(&home);
+        let registry_complete = registry_findings
+            .iter()
+            .all(|finding| finding.severity == Severity::Ok);
+        checks.extend(registry_findings);
+        checks.extend(codebase_paths_check(&home, &codebases));
+        checks.extend(registry_names_check(&codebases));
+        checks.extend(hash_store_check(&home, &codebases, registry_complete));
+        checks.extend(hash_integrity_check(&home, &codebases));
+        checks.extend(agent_files_checks(&home, &codebases));
+
+        if self.fix {
+            for finding in &mut checks {
+                apply_fix(finding);
             }
         }
 
@@ -178,70 +153,131 @@ impl DoctorArgs {
                 "{}",
                 serde_json::to_string_pretty(&report).context("failed to serialize doctor JSON")?
             );
-            return Ok(());
+        } else if out.quiet() {
+            print_summary(&report);
+        } else {
+            print_human(&report, self.fix);
         }
 
-        print_human(&report);
+        if report
+            .checks
+            .iter()
+            .any(|finding| finding.severity == Severity::Error)
+        {
+            std::process::exit(1);
+        }
         Ok(())
     }
 }
 
-fn daemon_socket_check(home: &std::path::Path) -> DoctorCheck {
-    let socket = socket_path(home);
-    if socket.exists() {
-        DoctorCheck {
-            name: "daemon socket".into(),
-            status: "pass".into(),
-            detail: socket.display().to_string(),
+fn print_human(report: &DoctorReport, fixed: bool) {
+    println!("Orchestra Doctor — v{}", report.version);
+    for check in &report.checks {
+        let icon = match check.severity {
+            Severity::Ok => "✓".green().bold().to_string(),
+            Severity::Warn => "⚠".yellow().bold().to_string(),
+            Severity::Error => "✗".red().bold().to_string(),
+        };
+        println!("  {} {}: {}", icon, check.name, check.message);
+    }
+
+    let fixable = report.checks.iter().filter(|c| c.fixable).count();
+    if !fixed && fixable > 0 {
+        println!();
+        println!("{fixable} finding(s) can be fixed with `orchestra doctor --fix`.");
+    }
+}
+
+/// The single line `doctor --quiet` prints.
+fn print_summary(report: &DoctorReport) {
+    let count = |severity: Severity| {
+        report
+            .checks
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .count()
+    };
+    println!(
+        "Orchestra Doctor — {} checks, {} warnings, {} errors",
+        report.checks.len(),
+        count(Severity::Warn),
+        count(Severity::Error)
+    );
+}
+
+/// Apply the finding's remediation, marking it resolved on success.
+fn apply_fix(finding: &mut DoctorFinding) {
+    let Some(fix) = finding.fix.take() else {
+        return;
+    };
+
+    let outcome = match &fix {
+        Fix::RemoveFile(path) => fs::remove_file(path),
+        Fix::Chmod(path, mode) => set_mode(path, *mode),
+        Fix::RotateLog(path) => {
+            rotate_if_needed(path, MAX_LOG_BYTES, MAX_ROTATED_FILES).map(|_| ())
         }
-    } else {
-        DoctorCheck {
-            name: "daemon socket".into(),
-            status: "warn".into(),
-            detail: format!("missing: {}", socket.display()),
+        Fix::CanonicalizePath {
+            home,
+            project,
+            codebase,
+            path,
+        } => set_codebase_path(home, project, codebase, path),
+    };
+
+    match outcome {
+        Ok(()) => {
+            finding.severity = Severity::Ok;
+            finding.message = format!("fixed: {}", finding.message);
+            finding.fixable = false;
+        }
+        Err(err) => {
+            finding.message = format!("{} (fix failed: {err})", finding.message);
+            finding.fix = Some(fix);
         }
     }
 }
 
-fn print_human(report: &DoctorReport) {
-    println!("Orchestra Doctor — v{}", report.version);
-    for check in &report.checks {
-        let icon = match check.status.as_str() {
-            "pass" => "✓".green().bold().to_string(),
-            "warn" => "⚠".yellow().bold().to_string(),
-            _ => "✗".red().bold().to_string(),
-        };
-        println!("  {} {}: {}", icon, check.name, check.detail);
-    }
+fn set_codebase_path(
+    home: &Path,
+    project: &ProjectName,
+    name: &CodebaseName,
+    path: &Path,
+) -> std::io::Result<()> {
+    let mut codebase =
+        registry::load_codebase_at(home, project, name).map_err(std::io::Error::other)?;
+    codebase.path = path.to_path_buf();
+    codebase.updated_at = chrono::Utc::now();
+    registry::save_codebase_at(home, project, &codebase).map_err(std::io::Error::other)
 }
 
-fn version_check() -> DoctorCheck {
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> std::io::Result<()> {
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Installation checks
+// ---------------------------------------------------------------------------
+
+fn version_check() -> DoctorFinding {
+    const NAME: &str = "version update";
     match fetch_latest_release_tag() {
         Ok(Some(tag)) => {
             if tag.contains(CURRENT_VERSION) {
-                DoctorCheck {
-                    name: "version update".into(),
-                    status: "pass".into(),
-                    detail: format!("running latest ({})", tag),
-                }
+                DoctorFinding::ok(NAME, format!("running latest ({})", tag))
             } else {
-                DoctorCheck {
-                    name: "version update".into(),
-                    status: "warn".into(),
-                    detail: format!("newer release available: {}", tag),
-                }
+                DoctorFinding::warn(NAME, format!("newer release available: {}", tag))
             }
         }
-        Ok(None) => DoctorCheck {
-            name: "version update".into(),
-            status: "warn".into(),
-            detail: "no release tag found from GitHub API".into(),
-        },
-        Err(err) => DoctorCheck {
-            name: "version update".into(),
-            status: "warn".into(),
-            detail: format!("could not check release API: {err}"),
-        },
+        Ok(None) => DoctorFinding::warn(NAME, "no release tag found from GitHub API"),
+        Err(err) => DoctorFinding::warn(NAME, format!("could not check release API: {err}")),
     }
 }
 
@@ -261,24 +297,14 @@ fn fetch_latest_release_tag() -> Result<Option<String>> {
         .map(|s| s.to_string()))
 }
 
-fn path_check() -> DoctorCheck {
-    let path_var = std::env::var_os("PATH");
-    let current_exe = std::env::current_exe().ok();
-
-    let Some(path_var) = path_var else {
-        return DoctorCheck {
-            name: "binary in PATH".into(),
-            status: "warn".into(),
-            detail: "PATH is not set".into(),
-        };
+fn path_check() -> DoctorFinding {
+    const NAME: &str = "binary in PATH";
+    let Some(path_var) = std::env::var_os("PATH") else {
+        return DoctorFinding::warn(NAME, "PATH is not set");
     };
 
-    let Some(exe) = current_exe else {
-        return DoctorCheck {
-            name: "binary in PATH".into(),
-            status: "warn".into(),
-            detail: "could not resolve current executable".into(),
-        };
+    let Ok(exe) = std::env::current_exe() else {
+        return DoctorFinding::warn(NAME, "could not resolve current executable");
     };
 
     let parent = exe.parent().map(|p| p.to_path_buf());
@@ -286,42 +312,494 @@ fn path_check() -> DoctorCheck {
         .any(|candidate| Some(candidate) == parent);
 
     if in_path {
-        DoctorCheck {
-            name: "binary in PATH".into(),
-            status: "pass".into(),
-            detail: exe.display().to_string(),
-        }
+        DoctorFinding::ok(NAME, exe.display().to_string())
     } else {
-        DoctorCheck {
-            name: "binary in PATH".into(),
-            status: "warn".into(),
-            detail: format!("{} is not in PATH entries", exe.display()),
-        }
+        DoctorFinding::warn(NAME, format!("{} is not in PATH entries", exe.display()))
+    }
+}
+
+/// The registry root and every project directory should be mode `0700`.
+fn registry_permissions_check(home: &Path) -> Vec<DoctorFinding> {
+    const NAME: &str = "registry permissions";
+    let root = projects_root(home);
+    if !root.is_dir() {
+        return vec![DoctorFinding::warn(
+            NAME,
+            format!("registry directory missing: {} (run `orchestra init`)", root.display()),
+        )];
+    }
+
+    let mut dirs = vec![root.clone()];
+    if let Ok(entries) = fs::read_dir(&root) {
+        let mut projects: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .collect();
+        projects.sort();
+        dirs.extend(projects);
+    }
+
+    let findings: Vec<DoctorFinding> = dirs
+        .into_iter()
+        .filter_map(|dir| {
+            let mode = dir_mode(&dir)?;
+            (mode & 0o077 != 0).then(|| {
+                DoctorFinding::warn(
+                    NAME,
+                    format!("{} has mode {:o}, expected 700", dir.display(), mode),
+                )
+                .with_fix(Fix::Chmod(dir, 0o700))
+            })
+        })
+        .collect();
+
+    if findings.is_empty() {
+        vec![DoctorFinding::ok(NAME, format!("{} is private", root.display()))]
+    } else {
+        findings
     }
 }
 
-fn daemon_check(home: &std::path::Path) -> DoctorCheck {
+#[cfg(unix)]
+fn dir_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).ok().map(|m| m.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn dir_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+/// The daemon socket must either accept connections or not exist at all.
+#[cfg(unix)]
+fn daemon_socket_check(home: &Path) -> Vec<DoctorFinding> {
+    const NAME: &str = "daemon socket";
+    let socket = socket_path(home);
+    if !socket.exists() {
+        return vec![DoctorFinding::ok(NAME, "absent (daemon not running)")];
+    }
+
+    match std::os::unix::net::UnixStream::connect(&socket) {
+        Ok(_) => vec![DoctorFinding::ok(NAME, socket.display().to_string())],
+        Err(err) => vec![DoctorFinding::warn(
+            NAME,
+            format!("stale socket {} ({err})", socket.display()),
+        )
+        .with_fix(Fix::RemoveFile(socket))],
+    }
+}
+
+#[cfg(not(unix))]
+fn daemon_socket_check(home: &Path) -> Vec<DoctorFinding> {
+    let _ = socket_path(home);
+    vec![DoctorFinding::ok("daemon socket", "not applicable on this platform")]
+}
+
+fn daemon_check(home: &Path) -> DoctorFinding {
+    const NAME: &str = "daemon status";
     match request_status(home) {
         Ok(status) => {
             let running = status
                 .get("running")
                 .and_then(|v| v.as_bool())
                 .unwrap_or(true);
-            DoctorCheck {
-            name: "daemon status".into(),
-                status: if running { "pass".into() } else { "warn".into() },
-                detail: format!("running: {}", running),
+            if let Some(warning) = daemon_version_warning(&status) {
+                return DoctorFinding::warn(NAME, warning);
+            }
+            let finding = if running { DoctorFinding::ok } else { DoctorFinding::warn };
+            finding(NAME, format!("running: {}", running))
+        }
+        Err(DaemonError::DaemonNotRunning { .. }) => {
+            DoctorFinding::warn(NAME, "daemon is not running")
+        }
+        Err(err) => DoctorFinding::warn(NAME, format!("unable to query daemon: {err}")),
+    }
+}
+
+/// An installed launchd plist should launch the binary that is running now.
+fn launchd_plist_check(home: &Path) -> DoctorFinding {
+    const NAME: &str = "launchd plist";
+    let plist = launchd_plist_path(home);
+    if !plist.exists() {
+        return DoctorFinding::ok(NAME, "not installed");
+    }
+
+    let contents = match fs::read_to_string(&plist) {
+        Ok(contents) => contents,
+        Err(err) => {
+            return DoctorFinding::warn(NAME, format!("cannot read {}: {err}", plist.display()))
+        }
+    };
+    let Some(program) = plist_program(&contents) else {
+        return DoctorFinding::warn(
+            NAME,
+            format!("{} has no ProgramArguments entry", plist.display()),
+        );
+    };
+
+    let Ok(exe) = std::env::current_exe() else {
+        return DoctorFinding::warn(NAME, "could not resolve current executable");
+    };
+    let canonical = |p: &Path| fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    if canonical(Path::new(&program)) == canonical(&exe) {
+        DoctorFinding::ok(NAME, format!("launches {program}"))
+    } else {
+        DoctorFinding::warn(
+            NAME,
+            format!(
+                "plist launches {program} but current binary is {} (run `orchestra daemon install`)",
+                exe.display()
+            ),
+        )
+    }
+}
+
+/// First `<string>` inside the plist's `ProgramArguments` array.
+fn plist_program(contents: &str) -> Option<String> {
+    let (_, after_key) = contents.split_once("<key>ProgramArguments</key>")?;
+    let (_, after_open) = after_key.split_once("<string>")?;
+    let (program, _) = after_open.split_once("</string>")?;
+    Some(program.trim().to_string())
+}
+
+/// Log files under `~/.orchestra/logs` should stay below the rotation cap.
+fn log_size_check(home: &Path) -> Vec<DoctorFinding> {
+    const NAME: &str = "log sizes";
+    let dir = logs_dir(home);
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return vec![DoctorFinding::ok(NAME, "no log directory")];
+    };
+
+    let mut logs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|ext| ext == "log" || ext == "jsonl")
+        })
+        .collect();
+    logs.sort();
+
+    let findings: Vec<DoctorFinding> = logs
+        .into_iter()
+        .filter_map(|path| {
+            let size = fs::metadata(&path).ok()?.len();
+            (size > MAX_LOG_BYTES).then(|| {
+                DoctorFinding::warn(
+                    NAME,
+                    format!(
+                        "{} is {size} bytes (limit {MAX_LOG_BYTES})",
+                        path.display()
+                    ),
+                )
+                .with_fix(Fix::RotateLog(path))
+            })
+        })
+        .collect();
+
+    if findings.is_empty() {
+        vec![DoctorFinding::ok(NAME, "all logs under the rotation size")]
+    } else {
+        findings
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Registry checks
+// ---------------------------------------------------------------------------
+
+/// Parse every codebase YAML individually so one bad file does not hide the rest.
+fn registry_integrity_check(home: &Path) -> (Vec<(ProjectName, Codebase)>, Vec<DoctorFinding>) {
+    const NAME: &str = "registry integrity";
+    let listing = match registry::list_codebases_lossy_at(home) {
+        Ok(listing) => listing,
+        Err(err) => {
+            let message = format!("cannot read {}: {err}", projects_root(home).display());
+            return (Vec::new(), vec![DoctorFinding::error(NAME, message)]);
+        }
+    };
+    let codebases = listing.codebases;
+    let mut findings: Vec<DoctorFinding> = listing
+        .errors
+        .iter()
+        .map(|failed| DoctorFinding::error(NAME, failed.to_string()))
+        .collect();
+
+    if findings.is_empty() {
+        findings.push(DoctorFinding::ok(
+            NAME,
+            format!("{} codebase entries loaded", codebases.len()),
+        ));
+    }
+    (codebases, findings)
+}
+
+/// Registered paths must exist. Entries from before `init` canonicalized its
+/// path may be relative or go through a symlink; `--fix` stores the real path.
+fn codebase_paths_check(home: &Path, codebases: &[(ProjectName, Codebase)]) -> Vec<DoctorFinding> {
+    const NAME: &str = "codebase paths";
+    let mut findings = Vec::new();
+    for (project, codebase) in codebases {
+        let Ok(canonical) = codebase.path.canonicalize() else {
+            findings.push(DoctorFinding::error(
+                NAME,
+                format!(
+                    "{}:{} path no longer exists: {}",
+                    project.0,
+                    codebase.name.0,
+                    codebase.path.display()
+                ),
+            ));
+            continue;
+        };
+        if canonical == codebase.path {
+            continue;
+        }
+        let message = format!(
+            "{}:{} path {} resolves to {}",
+            project.0,
+            codebase.name.0,
+            codebase.path.display(),
+            canonical.display()
+        );
+        // A relative path resolved against doctor's cwd is only trusted when
+        // it lands on a directory named after the codebase.
+        let trusted = codebase.path.is_absolute()
+            || canonical.file_name() == Some(codebase.name.0.as_ref());
+        let finding = DoctorFinding::warn(NAME, message);
+        findings.push(if trusted {
+            finding.with_fix(Fix::CanonicalizePath {
+                home: home.to_path_buf(),
+                project: project.clone(),
+                codebase: codebase.name.clone(),
+                path: canonical,
+            })
+        } else {
+            finding
+        });
+    }
+
+    if findings.is_empty() {
+        vec![DoctorFinding::ok(NAME, "all registered codebase paths exist")]
+    } else {
+        findings
+    }
+}
+
+/// Entries registered before names were validated still load, but a name
+/// with a separator or leading dot can break lookups; suggest renaming them.
+fn registry_names_check(codebases: &[(ProjectName, Codebase)]) -> Vec<DoctorFinding> {
+    const NAME: &str = "registry names";
+    let mut findings = Vec::new();
+    let mut seen_projects = Vec::new();
+    for (project, codebase) in codebases {
+        if !seen_projects.contains(&project) {
+            seen_projects.push(project);
+            if let Err(err) = registry::validate_name(&project.0) {
+                findings.push(DoctorFinding::warn(NAME, format!("project {err}")));
             }
         }
-        Err(DaemonError::DaemonNotRunning { .. }) => DoctorCheck {
-            name: "daemon status".into(),
-            status: "warn".into(),
-            detail: "daemon is not running".into(),
+        if let Err(err) = registry::validate_name(&codebase.name.0) {
+            findings.push(DoctorFinding::warn(
+                NAME,
+                format!("{}: codebase {err}", project.0),
+            ));
+        }
+    }
+
+    if findings.is_empty() {
+        vec![DoctorFinding::ok(NAME, "all project and codebase names are valid")]
+    } else {
+        findings
+    }
+}
+
+/// Hash stores must parse and belong to a registered codebase.
+///
+/// Orphan detection is skipped when the registry failed to load completely,
+/// so a malformed YAML never gets its hash store deleted by `--fix`.
+fn hash_store_check(
+    home: &Path,
+    codebases: &[(ProjectName, Codebase)],
+    registry_complete: bool,
+) -> Vec<DoctorFinding> {
+    const NAME: &str = "hash stores";
+    let dir = paths::dirs_at(home).hashes_dir;
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return vec![DoctorFinding::ok(NAME, "no hash stores yet")];
+    };
+
+    let mut stores: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    stores.sort();
+
+    let mut findings = Vec::new();
+    for path in &stores {
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let registered = codebases.iter().any(|(_, cb)| cb.name.0 == name);
+
+        if !registered && registry_complete {
+            findings.push(
+                DoctorFinding::warn(
+                    NAME,
+                    format!("orphaned hash store for unregistered codebase '{name}'"),
+                )
+                .with_fix(Fix::RemoveFile(path.clone())),
+            );
+        } else if let Err(err) = hash_store::read_at(home, name) {
+            findings.push(DoctorFinding::error(
+                NAME,
+                format!(
+                    "{} does not parse: {err}; the next sync sets it aside and rebuilds it",
+                    path.display()
+                ),
+            ));
+        }
+    }
+
+    if findings.is_empty() {
+        vec![DoctorFinding::ok(
+            NAME,
+            format!("{} hash store(s) valid", stores.len()),
+        )]
+    } else {
+        findings
+    }
+}
+
+/// Every hash recorded for a codebase must match the file on disk. A
+/// mismatch is a file edited since the last sync, or a store that no longer
+/// describes what sync wrote; the next sync resolves either.
+fn hash_integrity_check(home: &Path, codebases: &[(ProjectName, Codebase)]) -> Vec<DoctorFinding> {
+    const NAME: &str = "hash integrity";
+    let mut findings = Vec::new();
+    let mut verified = 0usize;
+    for (_, codebase) in codebases {
+        let name = &codebase.name.0;
+        if !hash_store::store_path_at(home, name).exists() {
+            continue;
+        }
+        // Stores that do not parse are reported by the hash stores check.
+        let Ok(mismatches) = hash_store::verify_at(home, name) else {
+            continue;
+        };
+        verified += 1;
+        if mismatches.is_empty() {
+            continue;
+        }
+        let files: Vec<String> = mismatches
+            .iter()
+            .map(|mismatch| {
+                let path = layout::display_relative(&mismatch.path, codebase);
+                match mismatch.actual {
+                    Some(_) => path.display().to_string(),
+                    None => format!("{} (missing)", path.display()),
+                }
+            })
+            .collect();
+        findings.push(DoctorFinding::warn(
+            NAME,
+            format!(
+                "{} file(s) in '{name}' differ from their recorded hash: {}; run `orchestra sync {name}` to rewrite them",
+                files.len(),
+                files.join(", ")
+            ),
+        ));
+    }
+
+    if findings.is_empty() {
+        vec![DoctorFinding::ok(
+            NAME,
+            format!("recorded hashes match for {verified} codebase(s)"),
+        )]
+    } else {
+        findings
+    }
+}
+
+/// Presence of orchestra control files and managed agent files, plus staleness.
+fn agent_files_checks(home: &Path, codebases: &[(ProjectName, Codebase)]) -> Vec<DoctorFinding> {
+    let mut missing_pilot = Vec::new();
+    let mut missing_guide = Vec::new();
+    let mut missing_managed = Vec::new();
+    let mut stale = 0usize;
+    let mut current = 0usize;
+    let mut other = 0usize;
+
+    for (project, codebase) in codebases.iter().filter(|(_, cb)| cb.path.exists()) {
+        let label = format!("{}:{}", project.0, codebase.name.0);
+        if !layout::managed_pilot_path(codebase).exists() {
+            missing_pilot.push(label.clone());
+        }
+        if !layout::managed_guide_path(codebase).exists() {
+            missing_guide.push(label.clone());
+        }
+
+        // Loading a store that does not parse would set it aside; leave that
+        // to the next sync and count the codebase as neither stale nor current.
+        if hash_store::read_at(home, &codebase.name.0).is_err() {
+            other += 1;
+        } else if let Ok(signal) = staleness::check(home, project, codebase, false) {
+            match signal {
+                orchestra_sync::StalenessSignal::Current => current += 1,
+                orchestra_sync::StalenessSignal::Stale { .. } => stale += 1,
+                _ => other += 1,
+            }
+        }
+
+        let missing = layout::all_managed_paths(codebase)
+            .into_iter()
+            .filter(|p| !p.exists())
+            .count();
+        if missing > 0 {
+            missing_managed.push(format!("{label} missing {missing}"));
+        }
+    }
+
+    let presence = |name: &str, missing: Vec<String>, ok: &str| {
+        if missing.is_empty() {
+            DoctorFinding::ok(name, ok)
+        } else {
+            DoctorFinding::warn(name, format!("missing: {}", missing.join(", ")))
+        }
+    };
+
+    vec![
+        presence(
+            "pilot.md presence",
+            missing_pilot,
+            "all codebases have orchestra/pilot.md",
+        ),
+        presence(
+            "guide presence",
+            missing_guide,
+            "all codebases have orchestra/.guide.md",
+        ),
+        if stale == 0 {
+            DoctorFinding::ok(
+                "staleness summary",
+                format!("current: {current}, stale: {stale}, other: {other}"),
+            )
+        } else {
+            DoctorFinding::warn(
+                "staleness summary",
+                format!("current: {current}, stale: {stale}, other: {other}"),
+            )
         },
-        Err(err) => DoctorCheck {
-            name: "daemon status".into(),
-            status: "warn".into(),
-            detail: format!("unable to query daemon: {err}"),
+        if missing_managed.is_empty() {
+            DoctorFinding::ok(
+                "managed files presence",
+                "all expected managed files are present",
+            )
+        } else {
+            DoctorFinding::warn("managed files presence", missing_managed.join(", "))
         },
-    }
+    ]
 }
@@ -0,0 +1,62 @@
+//! `orchestra export` — bundle the registry into one YAML document.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use orchestra_sync::bundle;
+
+/// Arguments for `orchestra export`.
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// File to write the bundle to, or `-` for stdout.
+    #[arg(long, short, value_name = "FILE", default_value = "-")]
+    pub output: PathBuf,
+
+    /// Also bundle each codebase's hash store.
+    #[arg(long)]
+    pub include_hashes: bool,
+}
+
+impl ExportArgs {
+    pub fn run(self) -> Result<()> {
+        let home: PathBuf = super::home_dir()?;
+
+        let bundle = bundle::export_at(&home, self.include_hashes)
+            .context("failed to export registry")?;
+        let yaml = bundle.to_yaml().context("failed to serialize bundle")?;
+
+        if self.output == Path::new("-") {
+            std::io::stdout()
+                .write_all(yaml.as_bytes())
+                .context("failed to write bundle to stdout")?;
+            return Ok(());
+        }
+
+        fs::write(&self.output, yaml)
+            .with_context(|| format!("failed to write {}", self.output.display()))?;
+        // The bundle holds the same data as the registry, so keep it as private.
+        set_private(&self.output)
+            .with_context(|| format!("failed to restrict {}", self.output.display()))?;
+        eprintln!(
+            "Exported {} codebase(s) to {}",
+            bundle.codebases.len(),
+            self.output.display()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn set_private(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(not(unix))]
+fn set_private(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,155 @@
+//! `orchestra global edit|show` — manage instructions rendered into every
+//! codebase from `~/.orchestra/global.yaml`.
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+
+use orchestra_core::global;
+
+use crate::output::OutputOptions;
+
+/// Written when `global edit` creates the file.
+const GLOBAL_TEMPLATE: &str = "\
+# Instructions rendered into every codebase's agent files.
+# Run `orchestra sync --all` after editing.
+conventions: []
+# conventions:
+#   - Never commit secrets or credentials
+skills: []
+# skills:
+#   - id: security-review
+#     description: Check changes against the security checklist
+# architecture_notes: |
+#   Services talk to each other over the internal gRPC mesh.
+";
+
+/// Manage the global instruction layer.
+#[derive(Subcommand, Debug)]
+pub enum GlobalCommand {
+    /// Open `~/.orchestra/global.yaml` in `$VISUAL` or `$EDITOR`, creating it
+    /// if needed.
+    Edit,
+
+    /// Show the global conventions, skills and architecture notes.
+    Show,
+}
+
+pub fn run(cmd: GlobalCommand, out: OutputOptions) -> Result<()> {
+    let home = super::home_dir()?;
+    match cmd {
+        GlobalCommand::Edit => edit(&home, out),
+        GlobalCommand::Show => show(&home, out),
+    }
+}
+
+fn edit(home: &Path, out: OutputOptions) -> Result<()> {
+    let path = global::path_at(home);
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&path, GLOBAL_TEMPLATE)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+    }
+
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| default_editor().to_string());
+    let status = editor_command(&editor, &path)
+        .status()
+        .with_context(|| format!("failed to run editor '{editor}'"))?;
+    if !status.success() {
+        bail!("editor '{editor}' exited with {status}");
+    }
+
+    global::load_at(home).with_context(|| {
+        format!(
+            "{} does not parse; fix it with `orchestra global edit`",
+            path.display()
+        )
+    })?;
+    println!("Saved {}.", path.display());
+    if !out.quiet() {
+        println!("Run: orchestra sync --all");
+    }
+    Ok(())
+}
+
+fn show(home: &Path, out: OutputOptions) -> Result<()> {
+    let global = global::load_at(home).context("failed to load global instructions")?;
+    if global.is_empty() {
+        println!("No global instructions.");
+        if !out.quiet() {
+            println!("Run: orchestra global edit");
+        }
+        return Ok(());
+    }
+
+    println!("Global instructions ({}):", global::path_at(home).display());
+    if !global.conventions.is_empty() {
+        println!("\nConventions:");
+        for convention in &global.conventions {
+            println!("  - {convention}");
+        }
+    }
+    if !global.skills.is_empty() {
+        println!("\nSkills:");
+        let width = global
+            .skills
+            .iter()
+            .map(|skill| skill.id.len())
+            .max()
+            .unwrap_or(0);
+        for skill in &global.skills {
+            println!("  {:<width$}  {}", skill.id, skill.description);
+        }
+    }
+    if let Some(notes) = global
+        .architecture_notes
+        .as_deref()
+        .map(str::trim)
+        .filter(|notes| !notes.is_empty())
+    {
+        println!("\nArchitecture notes:");
+        for line in notes.lines() {
+            println!("  {line}");
+        }
+    }
+    Ok(())
+}
+
+/// The editor command line runs through the shell, so values such as
+/// `code --wait` work.
+#[cfg(unix)]
+fn editor_command(editor: &str, path: &Path) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("sh")
+        .arg(path);
+    cmd
+}
+
+#[cfg(windows)]
+fn editor_command(editor: &str, path: &Path) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C")
+        .arg(format!("{editor} \"{}\"", path.display()));
+    cmd
+}
+
+#[cfg(unix)]
+fn default_editor() -> &'static str {
+    "vi"
+}
+
+#[cfg(windows)]
+fn default_editor() -> &'static str {
+    "notepad"
+}
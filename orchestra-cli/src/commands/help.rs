@@ -0,0 +1,246 @@
+//! `orchestra help [<topic> | <command>...]`
+//!
+//! Topics are Markdown files under `orchestra-cli/topics/`, embedded at build
+//! time and rendered for the terminal: headings in bold, code in cyan. Any
+//! other argument is looked up as a command, so `orchestra help daemon start`
+//! prints the same text as `orchestra daemon start --help`. A topic wins over
+//! a command of the same name, so `orchestra help daemon` prints the topic
+//! and ends by pointing at `orchestra daemon --help`.
+
+use anyhow::{bail, Result};
+use clap::{Args, CommandFactory};
+use colored::Colorize;
+
+use orchestra_sync::status::SignalKind;
+
+use crate::Cli;
+
+/// Show help for a topic or command.
+#[derive(Args, Debug)]
+pub struct HelpArgs {
+    /// A topic such as `signals`, or a command such as `daemon start`.
+    #[arg(value_name = "TOPIC|COMMAND")]
+    pub topic: Vec<String>,
+}
+
+/// A page for `orchestra help <name>`.
+#[derive(Debug)]
+pub struct Topic {
+    pub name: &'static str,
+    pub summary: &'static str,
+    source: &'static str,
+}
+
+/// Every topic, in the order `orchestra help` lists them.
+pub const TOPICS: &[Topic] = &[
+    Topic {
+        name: "signals",
+        summary: "What each `orchestra status` signal means",
+        source: include_str!("../../topics/signals.md"),
+    },
+    Topic {
+        name: "layout",
+        summary: "Where Orchestra keeps its registry and generated files",
+        source: include_str!("../../topics/layout.md"),
+    },
+    Topic {
+        name: "daemon",
+        summary: "What the background daemon does and how to run it",
+        source: include_str!("../../topics/daemon.md"),
+    },
+    Topic {
+        name: "templates",
+        summary: "Overriding the templates generated files are rendered from",
+        source: include_str!("../../topics/templates.md"),
+    },
+    Topic {
+        name: "exit-codes",
+        summary: "Exit codes for scripts and CI",
+        source: include_str!("../../topics/exit-codes.md"),
+    },
+];
+
+/// Replaced in `signals.md` by one line per [`SignalKind`].
+const SIGNALS_PLACEHOLDER: &str = "{signals}";
+
+impl Topic {
+    pub fn find(name: &str) -> Option<&'static Topic> {
+        TOPICS.iter().find(|topic| topic.name == name)
+    }
+
+    /// The topic's Markdown, with the sections generated from code filled in.
+    pub fn markdown(&self) -> String {
+        self.source.replace(SIGNALS_PLACEHOLDER, &signal_list())
+    }
+}
+
+/// One list item per signal, in precedence order, so the topic always
+/// matches what `orchestra status` prints.
+fn signal_list() -> String {
+    SignalKind::all()
+        .iter()
+        .map(|kind| {
+            format!(
+                "- `{}` (`{}`): {}",
+                kind.label(),
+                kind.key(),
+                kind.description()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl HelpArgs {
+    pub fn run(self) -> Result<()> {
+        if let [name] = self.topic.as_slice() {
+            if let Some(topic) = Topic::find(name) {
+                print!("{}", render_markdown(&topic.markdown()));
+                if Cli::command().find_subcommand(name).is_some() {
+                    println!();
+                    println!(
+                        "{}",
+                        render_inline(&format!(
+                            "For the `{name}` command's options, run `orchestra {name} --help`."
+                        ))
+                    );
+                }
+                return Ok(());
+            }
+        }
+
+        let mut command = Cli::command();
+        // Gives subcommands their full `orchestra daemon start` usage line.
+        command.build();
+        if self.topic.is_empty() {
+            command.print_help()?;
+            println!();
+            print_topic_list();
+            return Ok(());
+        }
+        for name in &self.topic {
+            let Some(sub) = command.find_subcommand(name).cloned() else {
+                bail!(
+                    "no help topic or command '{}'; topics: {}",
+                    self.topic.join(" "),
+                    topic_names()
+                );
+            };
+            command = sub;
+        }
+        command.print_help()?;
+        Ok(())
+    }
+}
+
+fn topic_names() -> String {
+    TOPICS
+        .iter()
+        .map(|topic| topic.name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_topic_list() {
+    println!("{}", "Help topics:".bold());
+    let width = TOPICS
+        .iter()
+        .map(|topic| topic.name.len())
+        .max()
+        .unwrap_or(0);
+    for topic in TOPICS {
+        println!("  {:<width$}  {}", topic.name, render_inline(topic.summary));
+    }
+    println!();
+    println!("Run `orchestra help <topic>` to read one.");
+}
+
+/// Render `markdown` for the terminal: `#` headings in bold, code spans in
+/// cyan, and fenced blocks indented with their fences dropped. Shell
+/// comments inside a fence are dimmed. Colors are left out when stdout is
+/// not a terminal or `NO_COLOR` is set.
+pub fn render_markdown(markdown: &str) -> String {
+    let mut out = String::new();
+    let mut in_fence = false;
+    for line in markdown.lines() {
+        if line.starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            if line.trim_start().starts_with('#') {
+                out.push_str(&format!("    {}", line.dimmed()));
+            } else if !line.is_empty() {
+                out.push_str(&format!("    {}", line.cyan()));
+            }
+        } else if let Some(heading) = heading_text(line) {
+            out.push_str(&heading.bold().to_string());
+        } else {
+            out.push_str(&render_inline(line));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn heading_text(line: &str) -> Option<&str> {
+    let text = line.trim_start_matches('#');
+    (text.len() < line.len())
+        .then(|| text.strip_prefix(' '))
+        .flatten()
+}
+
+/// Code spans in cyan without their backticks. A line with an unmatched
+/// backtick is left as it is.
+fn render_inline(line: &str) -> String {
+    if line.matches('`').count() % 2 == 1 {
+        return line.to_string();
+    }
+    line.split('`')
+        .enumerate()
+        .map(|(i, part)| {
+            if i % 2 == 1 {
+                part.cyan().to_string()
+            } else {
+                part.to_string()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_topic_renders_non_empty() {
+        colored::control::set_override(false);
+        for topic in TOPICS {
+            let text = render_markdown(&topic.markdown());
+            assert!(!text.trim().is_empty(), "topic '{}' is empty", topic.name);
+            assert!(!text.contains("```"), "topic '{}' kept a fence", topic.name);
+            assert!(!text.contains(SIGNALS_PLACEHOLDER));
+        }
+    }
+
+    #[test]
+    fn signals_topic_mentions_every_signal() {
+        let text = Topic::find("signals").unwrap().markdown();
+        for kind in SignalKind::all() {
+            assert!(text.contains(kind.key()), "missing {}", kind.key());
+            assert!(text.contains(kind.label()), "missing {}", kind.label());
+            assert!(text.contains(kind.description()));
+        }
+    }
+
+    #[test]
+    fn markdown_loses_its_markup_without_color() {
+        colored::control::set_override(false);
+        let text =
+            render_markdown("# Title\n\nRun `orchestra sync`.\n\n```sh\n# comment\nls\n```\n");
+        assert_eq!(
+            text,
+            "Title\n\nRun orchestra sync.\n\n    # comment\n    ls\n"
+        );
+    }
+}
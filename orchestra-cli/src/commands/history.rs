@@ -0,0 +1,128 @@
+//! `orchestra history` — past sync runs from the sync history journal.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use clap::Args;
+use tabled::{settings::Style, Table, Tabled};
+
+use orchestra_sync::{history, staleness::format_datetime_age};
+
+/// Arguments for `orchestra history`.
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    /// Codebase whose sync history should be shown.
+    pub codebase: String,
+
+    /// Only show syncs newer than this age, e.g. `30m`, `12h`, `7d`, `2w`.
+    #[arg(long, value_parser = parse_since)]
+    pub since: Option<Duration>,
+}
+
+#[derive(Tabled)]
+struct HistoryTableRow {
+    #[tabled(rename = "when")]
+    when: String,
+    #[tabled(rename = "age")]
+    age: String,
+    #[tabled(rename = "source")]
+    source: String,
+    #[tabled(rename = "written")]
+    written: usize,
+    #[tabled(rename = "unchanged")]
+    unchanged: usize,
+}
+
+impl HistoryArgs {
+    pub fn run(self) -> Result<()> {
+        let home: PathBuf = super::home_dir()?;
+
+        let cutoff: Option<DateTime<Utc>> = self.since.map(|age| Utc::now() - age);
+        let entries = history::read_at(&home, Some(&self.codebase), cutoff)
+            .context("failed to read sync history")?;
+
+        if entries.is_empty() {
+            println!("No sync history for '{}'.", self.codebase);
+            return Ok(());
+        }
+
+        let rows: Vec<HistoryTableRow> = entries
+            .into_iter()
+            .rev()
+            .map(|entry| HistoryTableRow {
+                when: entry.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+                age: format_datetime_age(entry.timestamp),
+                source: entry.source.to_string(),
+                written: entry.written,
+                unchanged: entry.unchanged,
+            })
+            .collect();
+
+        println!("Sync history for '{}' ({} runs)", self.codebase, rows.len());
+        let mut table = Table::new(rows);
+        table.with(Style::rounded());
+        println!("{table}");
+        Ok(())
+    }
+}
+
+/// Parse a positive relative age such as `7d` into a [`Duration`].
+pub(crate) fn parse_since(raw: &str) -> Result<Duration> {
+    let raw = raw.trim();
+    let Some((unit_at, unit)) = raw.char_indices().last() else {
+        bail!("invalid --since value ''; expected e.g. 7d");
+    };
+    let amount: i64 = raw[..unit_at]
+        .parse()
+        .with_context(|| format!("invalid --since value '{raw}'; expected e.g. 7d"))?;
+    if amount <= 0 {
+        bail!("--since must be a positive age, got '{raw}'");
+    }
+    let duration = match unit {
+        's' => Duration::try_seconds(amount),
+        'm' => Duration::try_minutes(amount),
+        'h' => Duration::try_hours(amount),
+        'd' => Duration::try_days(amount),
+        'w' => Duration::try_weeks(amount),
+        _ => bail!("invalid --since unit in '{raw}'; expected one of s, m, h, d, w"),
+    };
+    // Callers subtract the age from now, which must not overflow either.
+    duration
+        .filter(|age| Utc::now().checked_sub_signed(*age).is_some())
+        .with_context(|| format!("--since value '{raw}' is too large"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn since_accepts_each_unit() {
+        assert_eq!(parse_since("30s").unwrap(), Duration::seconds(30));
+        assert_eq!(parse_since("12h").unwrap(), Duration::hours(12));
+        assert_eq!(parse_since(" 2w ").unwrap(), Duration::weeks(2));
+    }
+
+    #[test]
+    fn since_rejects_bad_values_without_panicking() {
+        for raw in [
+            "",
+            "d",
+            "7",
+            "7y",
+            "7é",
+            "é",
+            "0d",
+            "-3h",
+            "99999999999999d",
+        ] {
+            assert!(parse_since(raw).is_err(), "accepted '{raw}'");
+        }
+        let err = parse_since("99999999999999d").unwrap_err().to_string();
+        assert!(err.contains("too large"), "{err}");
+        let err = parse_since("0d").unwrap_err().to_string();
+        assert!(err.contains("positive"), "{err}");
+    }
+}
+
@@ -0,0 +1,168 @@
+//! `orchestra hook set|list|unset` — manage the commands run before and
+//! after a codebase is synced.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clap::{Subcommand, ValueEnum};
+
+use orchestra_core::{registry, types::SyncHooks};
+
+use super::agent::find_codebase;
+use crate::output::OutputOptions;
+
+/// Which sync hook to change.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum HookArg {
+    /// Runs before rendering; a failure aborts the sync.
+    PreSync,
+    /// Runs after a sync that wrote files; a failure leaves the files written.
+    PostSync,
+}
+
+impl HookArg {
+    fn label(self) -> &'static str {
+        match self {
+            Self::PreSync => "pre_sync",
+            Self::PostSync => "post_sync",
+        }
+    }
+
+    fn slot(self, hooks: &mut SyncHooks) -> &mut Option<String> {
+        match self {
+            Self::PreSync => &mut hooks.pre_sync,
+            Self::PostSync => &mut hooks.post_sync,
+        }
+    }
+}
+
+/// Manage per-codebase sync hooks.
+#[derive(Subcommand, Debug)]
+pub enum HookCommand {
+    /// Set a hook, e.g. `post-sync "git add -A && git commit -m 'agent files'"`.
+    Set {
+        /// Codebase the hook belongs to.
+        codebase: String,
+
+        /// Which hook to set.
+        hook: HookArg,
+
+        /// Shell command to run from the codebase root, quoted as one argument.
+        run: String,
+
+        /// Also run this codebase's hooks when the daemon syncs it.
+        #[arg(long)]
+        allow_in_daemon: bool,
+    },
+
+    /// Show the hooks registered for a codebase.
+    List {
+        /// Codebase whose hooks should be listed.
+        codebase: String,
+    },
+
+    /// Remove a hook.
+    Unset {
+        /// Codebase the hook belongs to.
+        codebase: String,
+
+        /// Which hook to remove.
+        hook: HookArg,
+    },
+}
+
+pub fn run(cmd: HookCommand, out: OutputOptions) -> Result<()> {
+    let home = super::home_dir()?;
+    match cmd {
+        HookCommand::Set {
+            codebase,
+            hook,
+            run,
+            allow_in_daemon,
+        } => set(&home, &codebase, hook, &run, allow_in_daemon),
+        HookCommand::List { codebase } => list(&home, &codebase, out),
+        HookCommand::Unset { codebase, hook } => unset(&home, &codebase, hook),
+    }
+}
+
+fn set(
+    home: &Path,
+    codebase_name: &str,
+    hook: HookArg,
+    run: &str,
+    allow_in_daemon: bool,
+) -> Result<()> {
+    if run.trim().is_empty() {
+        bail!("{} hook cannot be empty", hook.label());
+    }
+    let (project, mut codebase) = find_codebase(home, codebase_name)?;
+
+    let hooks = codebase.hooks.get_or_insert_with(SyncHooks::default);
+    *hook.slot(hooks) = Some(run.trim().to_string());
+    hooks.allow_in_daemon |= allow_in_daemon;
+    codebase.updated_at = Utc::now();
+
+    registry::save_codebase_at(home, &project, &codebase)
+        .with_context(|| format!("failed to save registry for '{}'", codebase.name))?;
+    println!("Set {} hook for '{}'.", hook.label(), codebase.name);
+    Ok(())
+}
+
+fn list(home: &Path, codebase_name: &str, out: OutputOptions) -> Result<()> {
+    let (_, codebase) = find_codebase(home, codebase_name)?;
+    let hooks = match codebase.hooks.as_ref().filter(|hooks| !hooks.is_empty()) {
+        Some(hooks) => hooks,
+        None => {
+            println!("No hooks registered for '{}'.", codebase.name);
+            if !out.quiet() {
+                println!(
+                    "Run: orchestra hook set {} post-sync \"<command>\"",
+                    codebase.name
+                );
+            }
+            return Ok(());
+        }
+    };
+
+    for (label, run) in [
+        ("pre_sync", &hooks.pre_sync),
+        ("post_sync", &hooks.post_sync),
+    ] {
+        if let Some(run) = run {
+            println!("  {label:<9}  {run}");
+        }
+    }
+    let daemon = if hooks.allow_in_daemon {
+        "also run by the daemon"
+    } else {
+        "skipped by the daemon"
+    };
+    println!("  ({daemon})");
+    Ok(())
+}
+
+fn unset(home: &Path, codebase_name: &str, hook: HookArg) -> Result<()> {
+    let (project, mut codebase) = find_codebase(home, codebase_name)?;
+    let removed = codebase
+        .hooks
+        .as_mut()
+        .and_then(|hooks| hook.slot(hooks).take())
+        .is_some();
+    if !removed {
+        bail!(
+            "no {} hook is registered for '{}'",
+            hook.label(),
+            codebase.name
+        );
+    }
+    if codebase.hooks.as_ref().is_some_and(SyncHooks::is_empty) {
+        codebase.hooks = None;
+    }
+    codebase.updated_at = Utc::now();
+
+    registry::save_codebase_at(home, &project, &codebase)
+        .with_context(|| format!("failed to save registry for '{}'", codebase.name))?;
+    println!("Removed {} hook from '{}'.", hook.label(), codebase.name);
+    Ok(())
+}
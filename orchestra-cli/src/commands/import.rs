@@ -0,0 +1,77 @@
+//! `orchestra import` — load a bundle written by `orchestra export`.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use orchestra_sync::bundle::{self, ImportMode, PathRemap, RegistryBundle};
+
+use crate::output::OutputOptions;
+
+/// Arguments for `orchestra import`.
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// Bundle file to import, or `-` for stdin.
+    pub file: PathBuf,
+
+    /// Rewrite a path prefix, e.g. `/Users/ana=/home/ana`. Repeatable.
+    #[arg(long = "remap-path", value_name = "OLD=NEW")]
+    pub remap_path: Vec<PathRemap>,
+
+    /// Keep whichever copy of an existing codebase was updated most recently.
+    #[arg(long, conflicts_with = "replace")]
+    pub merge: bool,
+
+    /// Overwrite existing codebases with the bundle's copy.
+    #[arg(long)]
+    pub replace: bool,
+}
+
+impl ImportArgs {
+    pub fn run(self, out: OutputOptions) -> Result<()> {
+        let home: PathBuf = super::home_dir()?;
+
+        let raw = if self.file == Path::new("-") {
+            let mut raw = String::new();
+            std::io::stdin()
+                .read_to_string(&mut raw)
+                .context("failed to read bundle from stdin")?;
+            raw
+        } else {
+            std::fs::read_to_string(&self.file)
+                .with_context(|| format!("failed to read {}", self.file.display()))?
+        };
+        let bundle = RegistryBundle::from_yaml(&raw).context("failed to parse bundle")?;
+
+        let mode = if self.merge {
+            ImportMode::Merge
+        } else if self.replace {
+            ImportMode::Replace
+        } else {
+            ImportMode::Strict
+        };
+        let report = bundle::import_at(&home, bundle, &self.remap_path, mode)
+            .context("failed to import bundle")?;
+
+        if !out.quiet() {
+            for name in &report.added {
+                println!("  added     {name}");
+            }
+            for name in &report.replaced {
+                println!("  replaced  {name}");
+            }
+            for name in &report.kept {
+                println!("  kept      {name} (local copy is newer)");
+            }
+        }
+        println!(
+            "Imported bundle ({} added, {} replaced, {} kept)",
+            report.added.len(),
+            report.replaced.len(),
+            report.kept.len()
+        );
+        Ok(())
+    }
+}
@@ -1,44 +1,160 @@
-//! `orchestra init <path> --project <name> [--type ...] [--detect]`
+//! `orchestra init <path> --project <name> [--type ...] [--detect [--yes] [--detect-strict]] [--output-root <dir>] [--force-overlap] [--no-manifest] [--seed-commands]`
+//! `orchestra init --from-git <url> --project <name> [--dest <dir>] [--no-sync] [...]`
 
-use std::path::PathBuf;
+use std::collections::btree_map::Entry;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
 use clap::Args;
 
-use orchestra_core::{registry, types::ProjectName};
+use orchestra_core::{
+    registry,
+    types::{Codebase, ProjectName, ProjectType, StackDetection},
+    RegistryError,
+};
+use orchestra_detector::{
+    detect_stack, detect_stack_detailed, Confidence, DetectedStack, DetectorOptions,
+};
 
 use super::super::ProjectTypeArg;
+use super::detect::{confidence_label, stack_detection};
+use super::sync::SyncArgs;
+use crate::gitclone;
+use crate::output::OutputOptions;
 
 /// Initialize a codebase in the Orchestra registry.
 #[derive(Args, Debug)]
 pub struct InitArgs {
     /// Absolute or relative path to the codebase root directory.
-    pub path: PathBuf,
+    #[arg(required_unless_present = "from_git", conflicts_with = "from_git")]
+    pub path: Option<PathBuf>,
+
+    /// Clone this repository (ssh or https URL) and register the clone.
+    /// An existing destination is registered without cloning.
+    #[arg(long, value_name = "URL")]
+    pub from_git: Option<String>,
+
+    /// Directory to clone into as `<dest>/<repo-name>` (default: the
+    /// current directory).
+    #[arg(long, value_name = "DIR", requires = "from_git")]
+    pub dest: Option<PathBuf>,
+
+    /// Skip the first sync after `--from-git` registers the clone.
+    #[arg(long, requires = "from_git")]
+    pub no_sync: bool,
 
     /// Project group name (e.g. "copnow", "myapp"). Creates
     /// ~/.orchestra/projects/<project>/<codebase>.yaml
-    #[arg(long, short = 'p')]
+    #[arg(long, short = 'p', value_parser = super::parse_name)]
     pub project: String,
 
-    /// Project category: backend | frontend | mobile | ml.
+    /// Project category: backend | frontend | mobile | ml | infra.
     #[arg(long = "type", short = 't', value_name = "TYPE")]
     pub project_type: Option<ProjectTypeArg>,
 
-    /// Auto-detect project type from directory contents (Phase 02+; currently a no-op).
+    /// Detect the project type from the directory contents. On a terminal
+    /// the detected stack is shown for confirmation first.
     #[arg(long, conflicts_with = "project_type")]
     pub detect: bool,
+
+    /// With `--detect`, accept the detected type without prompting.
+    #[arg(long, short = 'y', requires = "detect")]
+    pub yes: bool,
+
+    /// Detect without prompting, and fail instead of guessing when no
+    /// framework was identified (medium confidence). Implies `--detect`.
+    #[arg(long, conflicts_with = "project_type")]
+    pub detect_strict: bool,
+
+    /// Write generated agent files under this directory instead of the
+    /// codebase itself (out-of-tree mode). Created if missing.
+    #[arg(long, value_name = "DIR")]
+    pub output_root: Option<PathBuf>,
+
+    /// Register the path even if it contains, or lies inside, an already
+    /// registered codebase (intentional monorepo setups).
+    #[arg(long)]
+    pub force_overlap: bool,
+
+    /// Do not write `.orchestra-manifest.json` on sync.
+    #[arg(long)]
+    pub no_manifest: bool,
+
+    /// Pre-fill build/test/lint commands from the detected stack (e.g.
+    /// `cargo test --workspace` for Rust). Edit them with `orchestra command`.
+    #[arg(long)]
+    pub seed_commands: bool,
 }
 
 impl InitArgs {
-    pub fn run(self) -> Result<()> {
-        let project_type = self.project_type.map(|p| p.into());
-        let path = self
-            .path
+    pub fn run(self, out: OutputOptions) -> Result<()> {
+        let (path, cloned) = match &self.from_git {
+            Some(url) => (clone_destination(url, self.dest.clone(), out)?, true),
+            None => (
+                self.path.clone().context("provide a path or --from-git")?,
+                false,
+            ),
+        };
+        let codebase = self.register(&path, out)?;
+
+        if cloned && !self.no_sync {
+            SyncArgs {
+                codebase: Some(codebase.name.0.clone()),
+                all: false,
+                dry_run: false,
+                agent: None,
+                no_backup: false,
+                enforce_budget: false,
+                report: None,
+                fail_on_change: false,
+                watch: false,
+                force: false,
+            }
+            .run(out)?;
+        }
+        Ok(())
+    }
+
+    /// Register `path` with every setting the flags ask for, and print
+    /// where the entry was saved. Registering a path again returns its
+    /// existing entry.
+    pub(super) fn register(&self, path: &Path, out: OutputOptions) -> Result<Codebase> {
+        let path = path
             .canonicalize()
-            .with_context(|| format!("cannot resolve path '{}'", self.path.display()))?;
+            .with_context(|| format!("cannot resolve path '{}'", path.display()))?;
+
+        let output_root = self
+            .output_root
+            .as_ref()
+            .map(|dir| {
+                std::fs::create_dir_all(dir)
+                    .and_then(|_| dir.canonicalize())
+                    .with_context(|| format!("cannot resolve output root '{}'", dir.display()))
+            })
+            .transpose()?;
 
+        let detected = if self.detect || self.detect_strict {
+            Some(self.detect_project_type(&path, out)?)
+        } else {
+            None
+        };
+        let project_type = match &detected {
+            Some(detected) => Some(detected.project_type.clone()),
+            None => self.project_type.clone().map(|p| p.into()),
+        };
+
+        let home = super::home_dir()?;
         let project = self.project.clone();
-        let codebase = registry::init(path.clone(), ProjectName::from(self.project), project_type)
+        let project_name = ProjectName::from(project.clone());
+        let init = if self.force_overlap {
+            registry::init_allow_overlap_at
+        } else {
+            registry::init_at
+        };
+        let mut codebase = init(path.clone(), project_name.clone(), project_type, &home)
+            .map_err(overlap_hint)
             .with_context(|| {
                 format!(
                     "failed to init '{}' under project '{}'",
@@ -47,14 +163,344 @@ impl InitArgs {
                 )
             })?;
 
+        if let Some(root) = output_root {
+            if codebase.output_root.as_ref() != Some(&root) {
+                codebase.output_root = Some(root);
+                codebase.updated_at = Utc::now();
+                registry::save_codebase_at(&home, &project_name, &codebase)
+                    .context("failed to save output root")?;
+            }
+        }
+
+        if let Some(detected) = detected {
+            if let Some(target) = codebase.projects.first_mut() {
+                if target.framework.is_none() {
+                    target.framework = detected.framework;
+                }
+                target.detection = Some(detected.detection);
+                codebase.updated_at = Utc::now();
+                registry::save_codebase_at(&home, &project_name, &codebase)
+                    .context("failed to save detected stack")?;
+            }
+        }
+
+        if self.no_manifest && codebase.manifest != Some(false) {
+            codebase.manifest = Some(false);
+            codebase.updated_at = Utc::now();
+            registry::save_codebase_at(&home, &project_name, &codebase)
+                .context("failed to save manifest setting")?;
+        }
+
+        if self.seed_commands {
+            seed_commands(&project_name, &mut codebase, out)?;
+        }
+
         println!(
             "✓ Registered '{}' under project '{}'",
             codebase.name, project
         );
-        println!(
-            "  Saved to: ~/.orchestra/projects/{}/{}.yaml",
-            project, codebase.name
+        if !out.quiet() {
+            let saved = registry::codebase_path_at(&home, &project_name, &codebase.name);
+            println!("  Saved to: {}", saved.display());
+            if let Some(root) = &codebase.output_root {
+                println!("  Generated files: {}", root.display());
+            }
+        }
+
+        Ok(codebase)
+    }
+
+    /// Detect the stack at `path` and settle on a project type: confirmed
+    /// at a prompt on a terminal, accepted as is with `--yes` or when
+    /// output is piped, refused at medium confidence with `--detect-strict`.
+    pub(super) fn detect_project_type(
+        &self,
+        path: &Path,
+        out: OutputOptions,
+    ) -> Result<DetectedType> {
+        let (stack, warnings) = detect_stack_detailed(path, &DetectorOptions::default())
+            .map_err(|err| anyhow!("{err}\n  Pass --type to choose the project type."))?;
+        for warning in &warnings {
+            eprintln!("⚠  {warning}");
+        }
+
+        let medium = stack.confidence == Confidence::Medium;
+        if self.detect_strict && medium {
+            bail!(
+                "detected {}, but no framework was identified\n  \
+                 Pass --type to choose the project type.",
+                describe_stack(&stack)
+            );
+        }
+
+        let interactive = !self.yes && !self.detect_strict && io::stdout().is_terminal();
+        let choice = if interactive {
+            confirm_detected_type(&stack, &mut io::stdin().lock(), &mut io::stdout())?
+        } else {
+            if !out.quiet() {
+                println!("Detected stack: {}", describe_stack(&stack));
+            }
+            if medium {
+                eprintln!("⚠  No framework identified; check the project type or pass --type.");
+            }
+            DetectChoice::Accept
+        };
+
+        let (project_type, overridden) = match choice {
+            DetectChoice::Accept => (stack.project_type.clone(), false),
+            DetectChoice::Override(project_type) => (project_type, true),
+            DetectChoice::Abort => bail!("init aborted; nothing was registered"),
+        };
+        Ok(DetectedType {
+            project_type,
+            detection: stack_detection(path, &stack, overridden),
+            framework: stack.framework,
+        })
+    }
+}
+
+/// The project type `--detect` settled on and what to record about it.
+pub(super) struct DetectedType {
+    pub(super) project_type: ProjectType,
+    pub(super) framework: Option<String>,
+    detection: StackDetection,
+}
+
+/// The answer to the `--detect` confirmation prompt.
+#[derive(Debug, PartialEq, Eq)]
+enum DetectChoice {
+    Accept,
+    Override(ProjectType),
+    Abort,
+}
+
+/// Show `stack` on `output` and ask whether to use its project type, pick
+/// another one, or abort. End of input aborts.
+fn confirm_detected_type(
+    stack: &DetectedStack,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> Result<DetectChoice> {
+    writeln!(output, "Detected stack: {}", describe_stack(stack))?;
+    if stack.confidence == Confidence::Medium {
+        writeln!(
+            output,
+            "  No framework identified; the project type is a guess."
+        )?;
+    }
+    loop {
+        let Some(answer) = prompt_line(input, output, "Use this project type? [Y/n/abort]: ")?
+        else {
+            return Ok(DetectChoice::Abort);
+        };
+        match answer.as_str() {
+            "" | "y" | "yes" => return Ok(DetectChoice::Accept),
+            "n" | "no" | "change" | "c" => break,
+            "a" | "abort" | "q" | "quit" => return Ok(DetectChoice::Abort),
+            _ => writeln!(output, "Please answer y, n or abort.")?,
+        }
+    }
+    loop {
+        let Some(answer) = prompt_line(
+            input,
+            output,
+            "Project type (backend|frontend|mobile|ml|infra): ",
+        )?
+        else {
+            return Ok(DetectChoice::Abort);
+        };
+        match answer.parse::<ProjectTypeArg>() {
+            Ok(ProjectTypeArg(project_type)) if project_type == stack.project_type => {
+                return Ok(DetectChoice::Accept)
+            }
+            Ok(ProjectTypeArg(project_type)) => return Ok(DetectChoice::Override(project_type)),
+            Err(_) if answer == "abort" => return Ok(DetectChoice::Abort),
+            Err(_) => writeln!(
+                output,
+                "Please enter one of: backend, frontend, mobile, ml, infra"
+            )?,
+        }
+    }
+}
+
+/// Print `message` and read one trimmed, lowercased answer; `None` at end
+/// of input.
+pub(super) fn prompt_line(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    message: &str,
+) -> Result<Option<String>> {
+    write!(output, "{message}")?;
+    output.flush().context("failed to flush stdout")?;
+    let mut line = String::new();
+    if input
+        .read_line(&mut line)
+        .context("failed to read user input")?
+        == 0
+    {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_ascii_lowercase()))
+}
+
+/// `Rust / Axum -> backend (confidence: high)`.
+fn describe_stack(stack: &DetectedStack) -> String {
+    format!(
+        "{}{} -> {} (confidence: {})",
+        stack.primary_language,
+        stack
+            .variant
+            .as_ref()
+            .or(stack.framework.as_ref())
+            .map(|f| format!(" / {f}"))
+            .unwrap_or_default(),
+        stack.project_type,
+        confidence_label(&stack.confidence)
+    )
+}
+
+/// Clone `url` into `<dest>/<repo-name>` and return that directory. An
+/// existing directory is returned as is, without cloning.
+fn clone_destination(url: &str, dest: Option<PathBuf>, out: OutputOptions) -> Result<PathBuf> {
+    let Some(name) = gitclone::repo_name(url) else {
+        bail!("cannot tell the repository name from '{url}'");
+    };
+    let parent = match dest {
+        Some(dest) => dest,
+        None => std::env::current_dir().context("cannot determine current directory")?,
+    };
+    let target = parent.join(&name);
+    if target.exists() {
+        eprintln!(
+            "'{}' already exists; registering it without cloning",
+            target.display()
+        );
+        return Ok(target);
+    }
+
+    std::fs::create_dir_all(&parent)
+        .with_context(|| format!("cannot create '{}'", parent.display()))?;
+    gitclone::clone(url, &target)?;
+    if !out.quiet() {
+        println!("✓ Cloned '{url}' into '{}'", target.display());
+    }
+    Ok(target)
+}
+
+/// Fill the first project's empty command slots from the detected stack.
+fn seed_commands(
+    project_name: &ProjectName,
+    codebase: &mut Codebase,
+    out: OutputOptions,
+) -> Result<()> {
+    let defaults = match detect_stack(&codebase.path) {
+        Ok(stack) => stack.default_commands(),
+        Err(err) => {
+            eprintln!("  No commands seeded: {err}");
+            return Ok(());
+        }
+    };
+    let Some(target) = codebase.projects.first_mut() else {
+        return Ok(());
+    };
+    let mut seeded = 0;
+    for (name, run) in defaults {
+        if let Entry::Vacant(slot) = target.commands.entry(name) {
+            slot.insert(run);
+            seeded += 1;
+        }
+    }
+    if seeded == 0 {
+        if !out.quiet() {
+            println!("  No commands seeded for this stack");
+        }
+        return Ok(());
+    }
+    codebase.updated_at = Utc::now();
+    registry::save_codebase_at(&super::home_dir()?, project_name, codebase)
+        .context("failed to save seeded commands")?;
+    if out.quiet() {
+        return Ok(());
+    }
+    println!("  Seeded {seeded} command(s); review with `orchestra command list {}`", codebase.name);
+    Ok(())
+}
+
+/// Point overlapping-path errors at `--force-overlap`.
+pub(crate) fn overlap_hint(err: RegistryError) -> anyhow::Error {
+    match err {
+        RegistryError::OverlappingPath { .. } => anyhow!(
+            "{err}\n  Pass --force-overlap if both codebases should manage overlapping paths."
+        ),
+        other => other.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stack(confidence: Confidence) -> DetectedStack {
+        let framework = (confidence == Confidence::High).then(|| "Express".to_string());
+        DetectedStack {
+            primary_language: "TypeScript".to_string(),
+            framework,
+            project_type: ProjectType::Backend,
+            confidence,
+            detail: None,
+            variant: None,
+            package_manager: None,
+        }
+    }
+
+    /// Run the prompt against `answers`, returning the choice and the
+    /// transcript.
+    fn confirm(stack: &DetectedStack, answers: &str) -> (DetectChoice, String) {
+        let mut output = Vec::new();
+        let choice =
+            confirm_detected_type(stack, &mut answers.as_bytes(), &mut output).expect("prompt");
+        (choice, String::from_utf8(output).expect("utf-8"))
+    }
+
+    #[test]
+    fn enter_accepts_the_detected_type() {
+        let (choice, transcript) = confirm(&stack(Confidence::High), "\n");
+        assert_eq!(choice, DetectChoice::Accept);
+        assert!(
+            transcript.contains("TypeScript / Express -> backend (confidence: high)"),
+            "{transcript}"
+        );
+        assert!(!transcript.contains("is a guess"), "{transcript}");
+    }
+
+    #[test]
+    fn no_then_type_overrides_after_a_bad_answer() {
+        let (choice, transcript) = confirm(&stack(Confidence::Medium), "n\nweb\nMobile\n");
+        assert_eq!(choice, DetectChoice::Override(ProjectType::Mobile));
+        assert!(transcript.contains("(confidence: medium)"), "{transcript}");
+        assert!(transcript.contains("is a guess"), "{transcript}");
+        assert!(transcript.contains("Please enter one of"), "{transcript}");
+    }
+
+    #[test]
+    fn choosing_the_detected_type_again_is_not_an_override() {
+        let (choice, _) = confirm(&stack(Confidence::Medium), "change\nbackend\n");
+        assert_eq!(choice, DetectChoice::Accept);
+    }
+
+    #[test]
+    fn abort_or_end_of_input_aborts() {
+        assert_eq!(
+            confirm(&stack(Confidence::High), "abort\n").0,
+            DetectChoice::Abort
+        );
+        assert_eq!(
+            confirm(&stack(Confidence::High), "maybe\n").0,
+            DetectChoice::Abort
+        );
+        assert_eq!(
+            confirm(&stack(Confidence::High), "n\n").0,
+            DetectChoice::Abort
         );
-        Ok(())
     }
 }
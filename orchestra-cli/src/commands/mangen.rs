@@ -0,0 +1,83 @@
+//! `orchestra mangen <dir>` — write a man page for every command.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Args, Command, CommandFactory};
+use clap_mangen::Man;
+
+use crate::output::OutputOptions;
+use crate::Cli;
+
+/// Write man pages for every command to a directory.
+#[derive(Args, Debug)]
+pub struct MangenArgs {
+    /// Directory to write the pages to; created if missing.
+    pub dir: PathBuf,
+}
+
+impl MangenArgs {
+    pub fn run(self, out: OutputOptions) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create '{}'", self.dir.display()))?;
+        let pages = write_man_pages(&self.dir)?;
+        if out.verbose() {
+            for page in &pages {
+                println!("  {}", page.display());
+            }
+        }
+        println!("Wrote {} man pages to {}", pages.len(), self.dir.display());
+        Ok(())
+    }
+}
+
+/// Write `orchestra.1` and a page for each subcommand, named after its
+/// path such as `orchestra-daemon-start.1`. Returns the files written.
+pub fn write_man_pages(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut root = Cli::command();
+    // Names each subcommand's page and usage after its full path.
+    root.build();
+    let mut pages = Vec::new();
+    write_pages(&root, dir, &mut pages)?;
+    Ok(pages)
+}
+
+fn write_pages(command: &Command, dir: &Path, pages: &mut Vec<PathBuf>) -> Result<()> {
+    let page = Man::new(command.clone())
+        .generate_to(dir)
+        .with_context(|| format!("failed to write a man page to '{}'", dir.display()))?;
+    pages.push(page);
+    for sub in command.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        write_pages(sub, dir, pages)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn page_names(command: &Command, prefix: &str, names: &mut Vec<String>) {
+        let name = format!("{prefix}{}", command.get_name());
+        names.push(format!("{name}.1"));
+        for sub in command.get_subcommands() {
+            page_names(sub, &format!("{name}-"), names);
+        }
+    }
+
+    #[test]
+    fn writes_a_page_per_subcommand() {
+        let dir = TempDir::new().unwrap();
+        let pages = write_man_pages(dir.path()).unwrap();
+
+        let mut expected = Vec::new();
+        page_names(&Cli::command(), "", &mut expected);
+        assert!(expected.contains(&"orchestra-daemon-start.1".to_string()));
+        for name in &expected {
+            assert!(dir.path().join(name).is_file(), "missing {name}");
+        }
+        assert_eq!(pages.len(), expected.len());
+    }
+}
@@ -1,11 +1,139 @@
+pub mod agent;
+pub mod command;
+pub mod convention;
 pub mod daemon;
+pub mod detect;
 pub mod doctor;
 pub mod diff;
+pub mod export;
+pub mod global;
+pub mod help;
+pub mod history;
+pub mod hook;
+pub mod import;
 pub mod init;
+pub mod mangen;
 pub mod offboard;
 pub mod onboard;
+pub mod output;
 pub mod project;
 pub mod reset;
+pub mod restore;
+pub mod serve;
+pub mod setup;
+pub mod skill;
 pub mod status;
 pub mod sync;
+pub mod task;
+pub mod template;
 pub mod update;
+pub mod upgrade_templates;
+pub mod which;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use orchestra_core::paths::{self, OrchestraDirs};
+use orchestra_core::types::{Codebase, ProjectName};
+use orchestra_core::{registry, RegistryError};
+use orchestra_renderer::{BudgetOverrun, Trim};
+
+/// Resolve where Orchestra keeps its files for this run and install the
+/// result for the user's home, so every `_at(home)` call below finds it.
+///
+/// Without a home directory, `$ORCHESTRA_HOME` or `--home` can still place
+/// everything; that root then stands in for the home.
+pub fn install_dirs(explicit: Option<&Path>) {
+    let home = dirs::home_dir().or_else(|| {
+        std::env::var_os(paths::ORCHESTRA_HOME_ENV)
+            .filter(|root| !root.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| explicit.map(Path::to_path_buf))
+            .map(|root| std::path::absolute(&root).unwrap_or(root))
+    });
+    if let Some(home) = home {
+        let dirs = OrchestraDirs::resolve(&home, explicit);
+        paths::install(&home, dirs);
+    }
+}
+
+/// The home every command passes to the library, which maps it to the
+/// directories chosen by [`install_dirs`]; usually the user's home directory.
+pub fn home_dir() -> Result<PathBuf> {
+    paths::installed_home()
+        .or_else(dirs::home_dir)
+        .context("could not determine home directory; pass --home <dir>")
+}
+
+/// Clap value parser for project and codebase names, so a name the registry
+/// would refuse is rejected before anything is written.
+pub fn parse_name(name: &str) -> Result<String, String> {
+    match registry::validate_name(name) {
+        Ok(()) => Ok(name.to_string()),
+        Err(RegistryError::InvalidName { reason, .. }) => Err(reason),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Check a subagent or skill id: letters, digits, `-` and `_`. `kind` names
+/// the id in the error, e.g. "skill".
+pub fn validate_id(kind: &str, id: &str) -> Result<()> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        bail!("invalid {kind} id '{id}'; use letters, digits, '-' and '_'");
+    }
+    Ok(())
+}
+
+/// `count` and `noun`, with an `s` unless `count` is 1: `1 codebase`,
+/// `3 files`.
+pub fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{count} {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+/// Warn on stderr about agent files over their token budget, and what
+/// enforcement trimmed from them.
+pub fn print_budget_overruns(codebase: &str, overruns: &[BudgetOverrun]) {
+    for overrun in overruns {
+        let over = format!(
+            "'{codebase}': {} is ~{} tokens, over {}'s {}-token budget",
+            overrun.path.display(),
+            overrun.tokens,
+            overrun.agent,
+            overrun.budget
+        );
+        match overrun.trim {
+            Trim::None => eprintln!(
+                "⚠  {over}; sync with --enforce-budget, or set `enforce_budget: true`, to trim it"
+            ),
+            trim if overrun.fits() => eprintln!(
+                "✂  {over}; trimmed {} to ~{} tokens",
+                trim.label(),
+                overrun.rendered_tokens
+            ),
+            trim => eprintln!(
+                "⚠  {over}; still ~{} tokens after trimming {}",
+                overrun.rendered_tokens,
+                trim.label()
+            ),
+        }
+    }
+}
+
+/// Every readable codebase, warning on stderr about each registry file that
+/// could not be read or parsed rather than failing on it.
+pub fn list_codebases_or_warn(home: &Path) -> Result<Vec<(ProjectName, Codebase)>> {
+    let listing = registry::list_codebases_lossy_at(home)
+        .context("failed to load registry — run `orchestra init` first")?;
+    for failed in &listing.errors {
+        eprintln!("⚠  Skipping unreadable registry file: {failed}");
+    }
+    Ok(listing.codebases)
+}
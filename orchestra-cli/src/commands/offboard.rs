@@ -6,9 +6,19 @@ use std::path::PathBuf;
 use anyhow::{Context, Result};
 use clap::Args;
 
-use orchestra_core::{registry, types::ProjectName};
+use orchestra_core::{
+    registry,
+    types::{Codebase, ProjectName},
+};
 use orchestra_renderer::engine::{backup_dir, legacy_orchestra_dirs, orchestra_dir, AgentKind};
-use orchestra_sync::{load_backup_manifest, restore_from_backup};
+use orchestra_sync::{
+    layout::{
+        all_managed_paths, check_codebase_root, contained_path, output_root, resolve_managed_path,
+    },
+    load_backup_manifest, restore_from_backup,
+};
+
+use crate::output::OutputOptions;
 
 /// Arguments for `orchestra offboard`.
 #[derive(Args, Debug)]
@@ -30,8 +40,8 @@ pub struct OffboardArgs {
 }
 
 impl OffboardArgs {
-    pub fn run(self) -> Result<()> {
-        let home = dirs::home_dir().context("could not determine home directory")?;
+    pub fn run(self, out: OutputOptions) -> Result<()> {
+        let home = super::home_dir()?;
         let raw_path = self.path.unwrap_or_else(|| PathBuf::from("."));
         let codebase_path = raw_path
             .canonicalize()
@@ -61,36 +71,43 @@ impl OffboardArgs {
                     codebase_path.display()
                 )
             })?;
+        check_codebase_root(&codebase)?;
+
+        // The plan backs the confirmation prompt, so `--quiet --yes` drops it.
+        if !(out.quiet() && self.yes) {
+            if self.recent {
+                println!(
+                    "Preparing to revert recent onboarding for '{}' under project '{}'.",
+                    codebase.name, project_key
+                );
+            } else {
+                println!(
+                    "Preparing to offboard '{}' under project '{}'.",
+                    codebase.name, project_key
+                );
+            }
+            println!();
+
+            // Show what will happen
+            let backup_manifest = backup_dir(&codebase.path).join("manifest.json");
+            if backup_manifest.exists() {
+                println!("  ✓ Backup found — pre-onboard files will be restored.");
+            } else {
+                eprintln!(
+                    "  ⚠ No backup found at orchestra/backup/ — original files cannot be restored."
+                );
+            }
 
-        if self.recent {
+            // Count managed files that will be removed
+            let managed = managed_files_to_remove(&codebase);
             println!(
-                "Preparing to revert recent onboarding for '{}' under project '{}'.",
-                codebase.name, project_key
+                "  ✓ {} Orchestra-managed agent files will be removed.",
+                managed.len()
             );
-        } else {
-            println!("Preparing to offboard '{}' under project '{}'.", codebase.name, project_key);
+            println!("  ✓ orchestra/ controls and backup directories will be removed.");
+            println!("  ✓ Codebase will be deregistered from the registry.");
+            println!();
         }
-        println!();
-
-        // Show what will happen
-        let backup_manifest = backup_dir(&codebase_path).join("manifest.json");
-        if backup_manifest.exists() {
-            println!("  ✓ Backup found — pre-onboard files will be restored.");
-        } else {
-            println!("  ⚠ No backup found at orchestra/backup/ — original files cannot be restored.");
-        }
-
-        // Count managed files that will be removed
-        let protected = protected_restore_paths(&codebase_path);
-        let managed: Vec<PathBuf> = managed_cleanup_paths(&codebase_path)
-            .into_iter()
-            .filter(|p| !protected.iter().any(|protected| protected == p))
-            .filter(|p: &PathBuf| p.exists())
-            .collect();
-        println!("  ✓ {} Orchestra-managed agent files will be removed.", managed.len());
-        println!("  ✓ orchestra/ controls and backup directories will be removed.");
-        println!("  ✓ Codebase will be deregistered from the registry.");
-        println!();
 
         if !self.yes {
             let confirm = prompt("Proceed with offboard? This cannot be undone. [y/N]: ")?;
@@ -100,39 +117,16 @@ impl OffboardArgs {
             }
         }
 
-        // Step 1: Restore from backup
-        if backup_manifest.exists() {
-            let num_restored = restore_from_backup(&codebase_path)
-                .context("failed to restore files from backup")?;
-            println!("Restored {} files from backup.", num_restored);
-        }
-
-        // Step 2: Remove Orchestra-managed agent files
-        let mut removed_count = 0usize;
-        for path in managed {
-            if path.exists() {
-                if let Err(e) = std::fs::remove_file(&path) {
-                    eprintln!("  Warning: could not remove {}: {}", path.display(), e);
-                } else {
-                    removed_count += 1;
-                }
+        let purged = purge_managed_files(&codebase)?;
+        if !out.quiet() {
+            if let Some(num_restored) = purged.restored {
+                println!("Restored {} files from backup.", num_restored);
             }
-        }
-        // Remove any now-empty agent directories that Orchestra created
-        prune_empty_agent_dirs(&codebase_path);
-        println!("Removed {} Orchestra-managed files.", removed_count);
-
-        // Step 3: Remove project-local Orchestra directories
-        let project_orchestra_dir = orchestra_dir(&codebase_path);
-        if project_orchestra_dir.exists() {
-            std::fs::remove_dir_all(&project_orchestra_dir)
-                .with_context(|| format!("failed to remove {}", project_orchestra_dir.display()))?;
-            println!("Removed orchestra/ directory.");
-        }
-        for legacy_dir in legacy_orchestra_dirs(&codebase_path) {
-            if legacy_dir.exists() {
-                std::fs::remove_dir_all(&legacy_dir)
-                    .with_context(|| format!("failed to remove {}", legacy_dir.display()))?;
+            println!("Removed {} Orchestra-managed files.", purged.removed_files);
+            if purged.removed_orchestra_dir {
+                println!("Removed orchestra/ directory.");
+            }
+            for _ in 0..purged.removed_legacy_dirs {
                 println!("Removed legacy orchestra/ directory.");
             }
         }
@@ -140,22 +134,125 @@ impl OffboardArgs {
         // Step 4: Deregister from global registry
         registry::remove_codebase_at(&home, &project_key, &codebase.name)
             .context("failed to deregister codebase from registry")?;
-        println!("Deregistered '{}' from registry.", codebase.name);
-
-        println!();
+        if !out.quiet() {
+            println!("Deregistered '{}' from registry.", codebase.name);
+            println!();
+        }
         println!("✓ Offboarded '{}' successfully.", codebase.name);
-        println!("  Your codebase is back to its pre-Orchestra state.");
+        if !out.quiet() {
+            println!("  Your codebase is back to its pre-Orchestra state.");
+        }
 
         Ok(())
     }
 }
 
-fn managed_cleanup_paths(codebase_root: &std::path::Path) -> Vec<PathBuf> {
-    let mut paths = Vec::new();
-    for agent in AgentKind::all() {
-        paths.extend(agent.output_paths(codebase_root));
-        paths.extend(agent.legacy_output_paths(codebase_root));
+/// What [`purge_managed_files`] did to one codebase.
+pub(super) struct PurgedFiles {
+    /// Files restored from the pre-onboard backup, if there was one.
+    pub restored: Option<usize>,
+    pub removed_files: usize,
+    pub removed_orchestra_dir: bool,
+    pub removed_legacy_dirs: usize,
+}
+
+/// Orchestra-managed agent files of `codebase`, in the tree or under its
+/// output root, that exist and will not be restored from the backup.
+pub(super) fn managed_files_to_remove(codebase: &Codebase) -> Vec<PathBuf> {
+    let protected = protected_restore_paths(&codebase.path);
+    managed_cleanup_paths(codebase)
+        .into_iter()
+        .filter(|p| !protected.iter().any(|protected| protected == p))
+        .filter(|p: &PathBuf| p.exists())
+        .collect()
+}
+
+/// Everything [`purge_managed_files`] deletes under the codebase: the
+/// managed files, the manifest among them, and the `orchestra/` directories.
+pub(super) fn purge_targets(codebase: &Codebase) -> Result<Vec<PathBuf>> {
+    check_codebase_root(codebase)?;
+    let codebase_root = codebase.path.as_path();
+    let mut targets = managed_files_to_remove(codebase);
+    targets.extend(
+        std::iter::once(orchestra_dir(codebase_root))
+            .chain(legacy_orchestra_dirs(codebase_root))
+            .filter(|dir| dir.exists()),
+    );
+    Ok(targets)
+}
+
+/// Return the codebase to its pre-onboard state: restore the backup, remove
+/// managed agent files and the `orchestra/` directories. The registry is
+/// left alone. A codebase at `/` or a home directory is refused untouched.
+pub(super) fn purge_managed_files(codebase: &Codebase) -> Result<PurgedFiles> {
+    check_codebase_root(codebase)?;
+    let codebase_root = codebase.path.as_path();
+    let managed = managed_files_to_remove(codebase);
+
+    // Step 1: Restore from backup
+    let restored = if backup_dir(codebase_root).join("manifest.json").exists() {
+        Some(restore_from_backup(codebase_root).context("failed to restore files from backup")?)
+    } else {
+        None
+    };
+
+    // Step 2: Remove Orchestra-managed agent files
+    let mut removed_files = 0usize;
+    for path in managed {
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                eprintln!("  Warning: could not remove {}: {}", path.display(), e);
+            } else {
+                removed_files += 1;
+            }
+        }
+    }
+    // Remove any now-empty agent directories that Orchestra created
+    prune_empty_agent_dirs(codebase_root);
+    if output_root(codebase) != codebase_root {
+        prune_empty_agent_dirs(output_root(codebase));
     }
+
+    // Step 3: Remove project-local Orchestra directories
+    let project_orchestra_dir = orchestra_dir(codebase_root);
+    let removed_orchestra_dir = project_orchestra_dir.exists();
+    if removed_orchestra_dir {
+        std::fs::remove_dir_all(&project_orchestra_dir)
+            .with_context(|| format!("failed to remove {}", project_orchestra_dir.display()))?;
+    }
+    let mut removed_legacy_dirs = 0usize;
+    for legacy_dir in legacy_orchestra_dirs(codebase_root) {
+        if legacy_dir.exists() {
+            std::fs::remove_dir_all(&legacy_dir)
+                .with_context(|| format!("failed to remove {}", legacy_dir.display()))?;
+            removed_legacy_dirs += 1;
+        }
+    }
+
+    Ok(PurgedFiles {
+        restored,
+        removed_files,
+        removed_orchestra_dir,
+        removed_legacy_dirs,
+    })
+}
+
+/// Every file a sync writes, the manifest among them, resolves against the
+/// output root; legacy outputs resolve against the codebase, which is where
+/// older versions wrote them.
+fn managed_cleanup_paths(codebase: &Codebase) -> Vec<PathBuf> {
+    let codebase_root = codebase.path.as_path();
+    let mut paths = all_managed_paths(codebase);
+    // A symlinked agent directory can point out of the tree; leave what it
+    // points at alone.
+    paths.retain(|path| contained_path(codebase, path).is_ok());
+
+    let mut legacy: Vec<PathBuf> = AgentKind::all()
+        .iter()
+        .flat_map(|agent| agent.legacy_output_paths(codebase_root))
+        .collect();
+    legacy.retain(|path| resolve_managed_path(codebase_root, path).is_ok());
+    paths.extend(legacy);
     paths.sort();
     paths.dedup();
     paths
@@ -206,6 +303,8 @@ fn prune_empty_agent_dirs(codebase_root: &std::path::Path) {
         ".agent/skills/orchestra-sync",
         ".agent/skills",
         ".agent",
+        ".roo/rules",
+        ".roo",
     ];
 
     // Prune leaf-first so parent dirs can be caught in subsequent iterations
@@ -221,7 +320,7 @@ fn prune_empty_agent_dirs(codebase_root: &std::path::Path) {
     }
 }
 
-fn prompt(message: &str) -> Result<String> {
+pub(super) fn prompt(message: &str) -> Result<String> {
     print!("{message}");
     io::stdout().flush().context("failed to flush stdout")?;
     let mut input = String::new();
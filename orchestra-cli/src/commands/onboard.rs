@@ -1,5 +1,6 @@
 //! `orchestra onboard` — interactive onboarding and bootstrap.
 
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::{self, Write};
 use std::path::PathBuf;
@@ -11,12 +12,15 @@ use orchestra_core::{
     registry,
     types::{ProjectName, ProjectType, Task, TaskId, TaskStatus},
 };
-use orchestra_detector::{detect_stack, scan_agent_files, AgentFileHit};
+use orchestra_detector::{detect_stack_detailed, scan_agent_files, AgentFileHit, DetectorOptions};
 use orchestra_renderer::engine::{backup_dir, control_dir, guide_path, orchestra_dir, pilot_path};
 use orchestra_sync::{
     backup_agent_files, pipeline, BackupItem, SyncScope,
 };
 
+use super::init::overlap_hint;
+use crate::output::OutputOptions;
+
 const IMPORT_BLOCK_START: &str = "<!-- orchestra:import ";
 const IMPORT_BLOCK_END: &str = "<!-- /orchestra:import -->";
 
@@ -58,7 +62,7 @@ pub struct OnboardArgs {
     pub path: Option<PathBuf>,
 
     /// Project group name. If omitted, interactive prompt is used.
-    #[arg(long, short = 'p')]
+    #[arg(long, short = 'p', value_parser = super::parse_name)]
     pub project: Option<String>,
 
     /// Accept detected/default project type without prompt.
@@ -69,14 +73,19 @@ pub struct OnboardArgs {
     #[arg(long)]
     pub force: bool,
 
+    /// Register the path even if it contains, or lies inside, an already
+    /// registered codebase (intentional monorepo setups).
+    #[arg(long)]
+    pub force_overlap: bool,
+
     /// Migration mode for existing agent files: "prompt" (recommended) or "mechanical".
     ///
-    /// - prompt:     Generates a one-shot setup prompt you paste into your agent chat.
-    ///               The agent reads official docs, migrates your skills/subagents/rules,
-    ///               and sets up pilot.md as the master orchestrator. (recommended)
+    /// - prompt: Generates a one-shot setup prompt you paste into your agent chat.
+    ///   The agent reads official docs, migrates your skills/subagents/rules,
+    ///   and sets up pilot.md as the master orchestrator. (recommended)
     ///
     /// - mechanical: Orchestra preserves all your existing agent files in-place and merges
-    ///               discovered conventions and notes into the registry automatically.
+    ///   discovered conventions and notes into the registry automatically.
     #[arg(long)]
     pub migrate: Option<String>,
 
@@ -86,8 +95,8 @@ pub struct OnboardArgs {
 }
 
 impl OnboardArgs {
-    pub fn run(self) -> Result<()> {
-        let home = dirs::home_dir().context("could not determine home directory")?;
+    pub fn run(self, out: OutputOptions) -> Result<()> {
+        let home = super::home_dir()?;
         let raw_path = self.path.unwrap_or_else(|| PathBuf::from("."));
         let codebase_path = raw_path
             .canonicalize()
@@ -99,21 +108,29 @@ impl OnboardArgs {
                     "Already onboarded as '{}' under project '{}'.",
                     cb.name, project
                 );
-                println!("Use --force to re-run onboarding workflow.");
+                if !out.quiet() {
+                    println!("Use --force to re-run onboarding workflow.");
+                }
                 return Ok(());
             }
         }
 
-        let project_type = resolve_project_type(&codebase_path, self.yes)?;
+        let (project_type, framework) = resolve_project_type(&codebase_path, self.yes, out)?;
         let project_name = resolve_project_name(self.project, &home, &codebase_path)?;
         let project_key = ProjectName::from(project_name.clone());
 
-        let mut codebase = registry::init_at(
+        let init = if self.force_overlap {
+            registry::init_allow_overlap_at
+        } else {
+            registry::init_at
+        };
+        let mut codebase = init(
             codebase_path.clone(),
             project_key.clone(),
             Some(project_type),
             &home,
         )
+        .map_err(overlap_hint)
         .with_context(|| {
             format!(
                 "failed to init '{}' under project '{}'",
@@ -121,13 +138,22 @@ impl OnboardArgs {
                 project_name
             )
         })?;
+        if let Some(project) = codebase.projects.first_mut() {
+            if project.framework.is_none() && framework.is_some() {
+                project.framework = framework;
+                registry::save_codebase_at(&home, &project_key, &codebase)
+                    .context("failed saving detected framework to registry")?;
+            }
+        }
 
         // Scan for existing agent files
         let hits = scan_agent_files(&codebase_path).context("failed to scan existing agent files")?;
         let migrate = resolve_migrate_mode(self.migrate.as_deref(), self.yes, !hits.is_empty())?;
 
         if !hits.is_empty() {
-            println!("Found {} existing agent file/folder entries.", hits.len());
+            if !out.quiet() {
+                println!("Found {} existing agent file/folder entries.", hits.len());
+            }
 
             // Extract conventions/notes from legacy files and merge into registry
             let (legacy_conventions, legacy_notes, legacy_tasks) = extract_legacy_hints(&hits);
@@ -158,11 +184,13 @@ impl OnboardArgs {
             let manifest = backup_agent_files(&codebase_path, &backup_items)
                 .context("failed while backing up existing agent files")?;
 
-            println!(
-                "Backed up {} file entries to {}",
-                manifest.files.len(),
-                backup_dir(&codebase_path).display()
-            );
+            if !out.quiet() {
+                println!(
+                    "Backed up {} file entries to {}",
+                    manifest.files.len(),
+                    backup_dir(&codebase_path).display()
+                );
+            }
 
             // Deletion is optional and happens only after a successful sync + import pass.
         }
@@ -175,14 +203,20 @@ impl OnboardArgs {
         }
 
         // Run the sync pipeline
-        let mut results = pipeline::run(&home, SyncScope::Codebase(codebase.name.0.clone()), false)
+        let mut results = pipeline::run(&home, SyncScope::Codebase(codebase.name.0.clone()), false, None)
             .with_context(|| format!("sync failed for '{}'", codebase.name))?;
 
-        if let Some(result) = results.pop() {
+        if let Some(result) = results.pop().filter(|_| !out.quiet()) {
             let changed = result
                 .writes
                 .iter()
-                .filter(|w| !matches!(w, orchestra_sync::WriteResult::Unchanged { .. }))
+                .filter(|w| {
+                    matches!(
+                        w,
+                        orchestra_sync::WriteResult::Written { .. }
+                            | orchestra_sync::WriteResult::WouldWrite { .. }
+                    )
+                })
                 .count();
             println!("Synced '{}' ({} file updates).", result.codebase_name, changed);
         }
@@ -190,17 +224,21 @@ impl OnboardArgs {
         if !hits.is_empty() {
             let imported = import_existing_agent_files(&codebase_path, &hits)
                 .context("failed to import existing agent files into orchestra/controls")?;
-            println!(
-                "Imported {} existing file entries into {}",
-                imported,
-                control_dir(&codebase_path).display()
-            );
+            if !out.quiet() {
+                println!(
+                    "Imported {} existing file entries into {}",
+                    imported,
+                    control_dir(&codebase_path).display()
+                );
+            }
 
             if self.delete {
                 let deleted = delete_original_agent_files(&hits)
                     .context("failed to delete legacy agent files after onboarding")?;
-                println!("Deleted {} original agent file/folder entries.", deleted);
-                println!("  orchestra/pilot.md is now the single source of truth for agent direction.");
+                if !out.quiet() {
+                    println!("Deleted {} original agent file/folder entries.", deleted);
+                    println!("  orchestra/pilot.md is now the single source of truth for agent direction.");
+                }
             }
         }
 
@@ -208,17 +246,28 @@ impl OnboardArgs {
             "✓ Onboarded '{}' under project '{}'.",
             codebase.name, project_name
         );
-        println!("  Pilot entrypoint: {}", pilot_path(&codebase.path).display());
-        println!("  Control folder: {}", control_dir(&codebase.path).display());
+        if !out.quiet() {
+            println!(
+                "  Pilot entrypoint: {}",
+                pilot_path(&codebase.path).display()
+            );
+            println!(
+                "  Control folder: {}",
+                control_dir(&codebase.path).display()
+            );
+        }
 
-        // Present migration options to the user
+        // Present migration options to the user; the prompt is kept under
+        // --quiet since it is what the user pastes into their agent.
         if !hits.is_empty() {
-            println!();
             match migrate {
                 MigrateMode::Prompt => {
+                    println!();
                     print_migration_prompt(&codebase_path, &hits, &codebase.name.0, self.delete);
                 }
+                MigrateMode::Mechanical if out.quiet() => {}
                 MigrateMode::Mechanical => {
+                    println!();
                     println!("────────────────────────────────────────────────────────────");
                     println!("  Mechanical migration complete.");
                     println!();
@@ -294,8 +343,8 @@ r#"─────────────────────────
 
 You are setting up Orchestra — the control harness that keeps all agent files,
 rules, subagents, skills, and task context in sync across Claude, Cursor,
-Copilot, Codex, Windsurf, Gemini, Cline, and Antigravity. Your goal is to
-finish onboarding without losing any user content or context.
+Copilot, Codex, Windsurf, Gemini, Cline, Antigravity, Roo Code, and Zed.
+Your goal is to finish onboarding without losing any user content or context.
 
 ## What Orchestra did
 
@@ -333,6 +382,8 @@ Orchestra successfully onboarded this codebase and:
      - Gemini: https://developers.google.com/gemini-api/
      - Cline: https://github.com/cline/cline
      - Antigravity: https://docs.antigravity.dev/
+     - Roo Code: https://docs.roocode.com/features/custom-instructions
+     - Zed: https://zed.dev/docs/ai/rules
      - Identify all custom content: skills, subagent definitions, rules,
          custom instructions, conventions, memory entries, persona definitions,
          task-routing notes, and workflow rules.
@@ -545,6 +596,8 @@ fn should_import_into_guide(relative: &std::path::Path) -> bool {
         ".cursorrules",
         ".windsurfrules",
         ".clinerules",
+        ".roorules",
+        ".rules",
     ];
 
     !reserved.contains(&name) && is_text_mergeable(relative)
@@ -582,6 +635,10 @@ fn normalize_import_relative_path(relative: &std::path::Path) -> PathBuf {
         return PathBuf::from(".clinerules/orchestra.md");
     }
 
+    if relative == std::path::Path::new(".roorules") {
+        return PathBuf::from(".roo/rules/orchestra.md");
+    }
+
     if let Some(stripped) = strip_leading_component(relative, "cursor") {
         return PathBuf::from(".cursor").join(stripped);
     }
@@ -725,30 +782,50 @@ fn imported_conflict_path(destination: &std::path::Path) -> PathBuf {
 // Project type resolution
 // ---------------------------------------------------------------------------
 
-fn resolve_project_type(codebase_path: &std::path::Path, auto_yes: bool) -> Result<ProjectType> {
-    let detected = detect_stack(codebase_path).ok();
+/// Pick the project type, confirming the detected one unless `auto_yes`.
+/// Also returns the detected framework, if any.
+fn resolve_project_type(
+    codebase_path: &std::path::Path,
+    auto_yes: bool,
+    out: OutputOptions,
+) -> Result<(ProjectType, Option<String>)> {
+    let (detected, warnings) =
+        match detect_stack_detailed(codebase_path, &DetectorOptions::default()) {
+            Ok((stack, warnings)) => (Some(stack), warnings),
+            Err(_) => (None, Vec::new()),
+        };
+    for warning in &warnings {
+        eprintln!("⚠  {warning}");
+    }
     let mut selected = detected
         .as_ref()
         .map(|d| d.project_type.clone())
         .unwrap_or(ProjectType::Backend);
+    let framework = detected.as_ref().and_then(|d| d.framework.clone());
 
-    if let Some(stack) = detected {
-        println!(
-            "Detected stack: {}{} -> {}",
+    match detected {
+        Some(_) if auto_yes && out.quiet() => {}
+        Some(stack) => println!(
+            "Detected stack: {}{}{} -> {}",
             stack.primary_language,
             stack
-                .framework
+                .variant
                 .as_ref()
+                .or(stack.framework.as_ref())
                 .map(|f| format!(" / {f}"))
                 .unwrap_or_default(),
+            stack
+                .detail
+                .as_ref()
+                .map(|d| format!(" (in {d})"))
+                .unwrap_or_default(),
             stack.project_type
-        );
-    } else {
-        println!("Could not detect stack confidently; defaulting to backend.");
+        ),
+        None => eprintln!("Could not detect stack confidently; defaulting to backend."),
     }
 
     if auto_yes {
-        return Ok(selected);
+        return Ok((selected, framework));
     }
 
     let confirm = prompt("Use this project type? [Y/n/change]: ")?;
@@ -757,18 +834,19 @@ fn resolve_project_type(codebase_path: &std::path::Path, auto_yes: bool) -> Resu
         selected = prompt_project_type()?;
     }
 
-    Ok(selected)
+    Ok((selected, framework))
 }
 
 fn prompt_project_type() -> Result<ProjectType> {
     loop {
-        let input = prompt("Project type (backend|frontend|mobile|ml): ")?;
+        let input = prompt("Project type (backend|frontend|mobile|ml|infra): ")?;
         match input.trim().to_ascii_lowercase().as_str() {
             "backend" => return Ok(ProjectType::Backend),
             "frontend" => return Ok(ProjectType::Frontend),
             "mobile" => return Ok(ProjectType::Mobile),
             "ml" => return Ok(ProjectType::Ml),
-            _ => println!("Please enter one of: backend, frontend, mobile, ml"),
+            "infra" => return Ok(ProjectType::Infra),
+            _ => println!("Please enter one of: backend, frontend, mobile, ml, infra"),
         }
     }
 }
@@ -809,14 +887,12 @@ fn resolve_project_name(
     );
     println!("Enter an existing project name to attach, or a new name to create one.");
 
-    loop {
-        let input = prompt(&format!("Project name [{}]: ", default_name))?;
-        let trimmed = input.trim();
-        if trimmed.is_empty() {
-            return Ok(default_name.clone());
-        }
-        return Ok(trimmed.to_string());
+    let input = prompt(&format!("Project name [{}]: ", default_name))?;
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Ok(default_name.clone());
     }
+    Ok(trimmed.to_string())
 }
 
 fn prompt(message: &str) -> Result<String> {
@@ -980,8 +1056,10 @@ fn merge_legacy_tasks(codebase: &mut orchestra_core::types::Codebase, tasks: &[L
             description: None,
             subtasks: vec![],
             notes: vec![format!("[{}] imported from legacy agent files during onboarding", now.format("%Y-%m-%dT%H:%M:%SZ"))],
+            assigned_agent: None,
             created_at: now,
             updated_at: now,
+            extra: BTreeMap::new(),
         });
         next_index += 1;
     }
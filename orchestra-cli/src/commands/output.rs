@@ -0,0 +1,160 @@
+//! `orchestra output add|list|remove` — manage extra files rendered from
+//! user templates alongside a codebase's agent files.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clap::Subcommand;
+
+use orchestra_core::{registry, types::ExtraOutput};
+use orchestra_renderer::user_template_dir_at;
+use orchestra_sync::layout;
+
+use super::agent::find_codebase;
+use crate::output::OutputOptions;
+
+/// Manage per-codebase extra outputs.
+#[derive(Subcommand, Debug)]
+pub enum OutputCommand {
+    /// Render a user template to a file in the codebase on every sync, e.g.
+    /// `ai-context.md.tera docs/ai-context.md`.
+    Add {
+        /// Codebase the output belongs to.
+        codebase: String,
+
+        /// Template name under `~/.orchestra/templates/`.
+        template: String,
+
+        /// Output file, relative to the codebase root.
+        output_path: PathBuf,
+    },
+
+    /// Show the extra outputs registered for a codebase.
+    List {
+        /// Codebase whose outputs should be listed.
+        codebase: String,
+    },
+
+    /// Stop rendering an extra output. The file already written is kept.
+    Remove {
+        /// Codebase the output belongs to.
+        codebase: String,
+
+        /// Output file, as given to `output add`.
+        output_path: PathBuf,
+    },
+}
+
+pub fn run(cmd: OutputCommand, out: OutputOptions) -> Result<()> {
+    let home = super::home_dir()?;
+    match cmd {
+        OutputCommand::Add {
+            codebase,
+            template,
+            output_path,
+        } => add(&home, &codebase, &template, output_path),
+        OutputCommand::List { codebase } => list(&home, &codebase, out),
+        OutputCommand::Remove {
+            codebase,
+            output_path,
+        } => remove(&home, &codebase, &output_path),
+    }
+}
+
+fn add(home: &Path, codebase_name: &str, template: &str, output_path: PathBuf) -> Result<()> {
+    let template = template.trim();
+    if template.is_empty() {
+        bail!("template name cannot be empty");
+    }
+    let (project, mut codebase) = find_codebase(home, codebase_name)?;
+    let output = ExtraOutput {
+        template: template.to_string(),
+        output_path,
+    };
+
+    let Some(target) = layout::extra_output_path(&codebase, &output) else {
+        bail!(
+            "output path '{}' must be a relative path inside the codebase",
+            output.output_path.display()
+        );
+    };
+    if layout::all_managed_paths(&codebase).contains(&target) {
+        bail!(
+            "'{}' is already managed by Orchestra for '{}'",
+            output.output_path.display(),
+            codebase.name
+        );
+    }
+    if !user_template_dir_at(home).join(template).is_file() {
+        eprintln!(
+            "⚠  no template '{template}' in {} yet; sync will fail until it exists",
+            user_template_dir_at(home).display()
+        );
+    }
+
+    codebase.extra_outputs.push(output);
+    codebase.updated_at = Utc::now();
+    registry::save_codebase_at(home, &project, &codebase)
+        .with_context(|| format!("failed to save registry for '{}'", codebase.name))?;
+    println!(
+        "Added extra output {} for '{}'.",
+        layout::display_relative(&target, &codebase).display(),
+        codebase.name
+    );
+    Ok(())
+}
+
+fn list(home: &Path, codebase_name: &str, out: OutputOptions) -> Result<()> {
+    let (_, codebase) = find_codebase(home, codebase_name)?;
+    if codebase.extra_outputs.is_empty() {
+        println!("No extra outputs registered for '{}'.", codebase.name);
+        if !out.quiet() {
+            println!(
+                "Run: orchestra output add {} <template> <output-path>",
+                codebase.name
+            );
+        }
+        return Ok(());
+    }
+
+    let width = codebase
+        .extra_outputs
+        .iter()
+        .map(|output| output.output_path.display().to_string().len())
+        .max()
+        .unwrap_or(0);
+    for output in &codebase.extra_outputs {
+        println!(
+            "  {:<width$}  ← {}",
+            output.output_path.display().to_string(),
+            output.template
+        );
+    }
+    Ok(())
+}
+
+fn remove(home: &Path, codebase_name: &str, output_path: &Path) -> Result<()> {
+    let (project, mut codebase) = find_codebase(home, codebase_name)?;
+    let before = codebase.extra_outputs.len();
+    codebase
+        .extra_outputs
+        .retain(|output| output.output_path != output_path);
+    if codebase.extra_outputs.len() == before {
+        bail!(
+            "no extra output '{}' is registered for '{}'",
+            output_path.display(),
+            codebase.name
+        );
+    }
+    codebase.updated_at = Utc::now();
+
+    registry::save_codebase_at(home, &project, &codebase)
+        .with_context(|| format!("failed to save registry for '{}'", codebase.name))?;
+    println!(
+        "Removed extra output {} from '{}'.",
+        output_path.display(),
+        codebase.name
+    );
+    Ok(())
+}
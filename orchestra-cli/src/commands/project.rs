@@ -1,54 +1,196 @@
-//! `orchestra project list` and `orchestra project add <name>`
+//! `orchestra project list`, `orchestra project add <name>` and
+//! `orchestra project remove <name>`
+
+use std::path::PathBuf;
 
 use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
+use serde::Serialize;
 
 use orchestra_core::{
-    registry,
-    types::{CodebaseName, ProjectName},
+    paths,
+    registry::{self, RemoveProjectMode},
+    types::{Codebase, CodebaseName, ProjectName, ProjectType, TaskStatus},
+    RegistryError,
 };
+use orchestra_sync::{hash_store, status::TaskCounts};
 
 use super::super::ProjectTypeArg;
+use crate::output::OutputOptions;
 
 /// Manage codebases within the active registry.
 #[derive(Subcommand, Debug)]
 pub enum ProjectCommand {
     /// List all registered codebases grouped by project.
-    List,
+    List(ListArgs),
 
     /// Add a new codebase to a project directory.
     Add(AddArgs),
+
+    /// Remove a project from the registry.
+    Remove(RemoveArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    /// Emit machine-readable JSON.
+    #[arg(long)]
+    pub json: bool,
+
+    /// Only list codebases in this project.
+    #[arg(long = "project", short = 'p')]
+    pub project: Option<String>,
 }
 
 #[derive(Args, Debug)]
 pub struct AddArgs {
     /// Codebase name (e.g. "payments", "dashboard").
+    #[arg(value_parser = super::parse_name)]
     pub name: String,
 
     /// Project group to add the codebase under.
     /// If omitted and only one project exists, that project is used automatically.
-    #[arg(long = "project", short = 'p')]
+    #[arg(long = "project", short = 'p', value_parser = super::parse_name)]
     pub project: Option<String>,
 
-    /// Project category: backend | frontend | mobile | ml. Defaults to backend.
+    /// Project category: backend | frontend | mobile | ml | infra. Defaults to backend.
     #[arg(long = "type", short = 't', value_name = "TYPE")]
     pub project_type: Option<ProjectTypeArg>,
 }
 
-pub fn run(cmd: ProjectCommand) -> Result<()> {
+#[derive(Args, Debug)]
+pub struct RemoveArgs {
+    /// Project to remove.
+    pub name: String,
+
+    /// Also deregister every codebase in the project and delete its hash store.
+    #[arg(long)]
+    pub cascade: bool,
+
+    /// With --cascade, also offboard each codebase on disk: restore its
+    /// backup and delete its Orchestra-managed files.
+    #[arg(long, requires = "cascade")]
+    pub purge_files: bool,
+
+    /// Skip confirmation prompt.
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+}
+
+pub fn run(cmd: ProjectCommand, out: OutputOptions) -> Result<()> {
     match cmd {
-        ProjectCommand::List => list(),
+        ProjectCommand::List(args) => list(args, out),
         ProjectCommand::Add(args) => add(args),
+        ProjectCommand::Remove(args) => remove(args, out),
+    }
+}
+
+/// One project in `project list --json`. Field names are stable.
+#[derive(Debug, Serialize)]
+struct ProjectListing {
+    project: String,
+    codebases: Vec<CodebaseListing>,
+}
+
+#[derive(Debug, Serialize)]
+struct CodebaseListing {
+    name: String,
+    path: PathBuf,
+    /// `false` for a registration whose directory is gone.
+    path_exists: bool,
+    projects: Vec<SubprojectListing>,
+}
+
+#[derive(Debug, Serialize)]
+struct SubprojectListing {
+    name: String,
+    #[serde(rename = "type")]
+    project_type: ProjectType,
+    task_counts: TaskCountListing,
+    agent_count: usize,
+}
+
+/// Tasks per status, keyed like the `tasks` object of `orchestra status
+/// --json`; every key is present, zero or not.
+#[derive(Debug, Serialize)]
+struct TaskCountListing {
+    #[serde(flatten)]
+    counts: TaskCounts,
+    /// Statuses this version does not recognise, e.g. written by a newer
+    /// orchestra.
+    unknown: usize,
+}
+
+impl CodebaseListing {
+    fn new(codebase: &Codebase) -> Self {
+        let projects = codebase
+            .projects
+            .iter()
+            .map(|project| {
+                let task_counts = TaskCountListing {
+                    counts: TaskCounts::of_tasks(&project.tasks),
+                    unknown: project
+                        .tasks
+                        .iter()
+                        .filter(|task| matches!(task.status, TaskStatus::Unknown(_)))
+                        .count(),
+                };
+                SubprojectListing {
+                    name: project.name.0.clone(),
+                    project_type: project.project_type.clone(),
+                    task_counts,
+                    agent_count: project.agents.len(),
+                }
+            })
+            .collect();
+        CodebaseListing {
+            name: codebase.name.0.clone(),
+            path: codebase.path.clone(),
+            path_exists: codebase.path.exists(),
+            projects,
+        }
     }
 }
 
-fn list() -> Result<()> {
-    let codebases = registry::list_codebases()
+fn list(args: ListArgs, out: OutputOptions) -> Result<()> {
+    let home = super::home_dir()?;
+    let mut codebases = super::list_codebases_or_warn(&home)?;
+    let mut projects = registry::list_project_names_at(&home)
         .context("failed to load registry — run `orchestra init` first")?;
+    if let Some(name) = &args.project {
+        if !projects.iter().any(|project| &project.0 == name) {
+            return Err(RegistryError::ProjectNotFound { name: name.clone() })
+                .context("run `orchestra project list` to see registered projects");
+        }
+        projects.retain(|project| &project.0 == name);
+        codebases.retain(|(project, _)| &project.0 == name);
+    }
+
+    if args.json {
+        // Empty projects are listed too, so `--project` always finds its own.
+        let listing: Vec<ProjectListing> = projects
+            .iter()
+            .map(|project| ProjectListing {
+                project: project.0.clone(),
+                codebases: codebases
+                    .iter()
+                    .filter(|(owner, _)| owner == project)
+                    .map(|(_, codebase)| CodebaseListing::new(codebase))
+                    .collect(),
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&listing).context("failed to serialize project list")?
+        );
+        return Ok(());
+    }
 
     if codebases.is_empty() {
         println!("No codebases registered.");
-        println!("Run: orchestra init <path> --project <name>");
+        if !out.quiet() {
+            println!("Run: orchestra init <path> --project <name>");
+        }
         return Ok(());
     }
 
@@ -69,11 +211,13 @@ fn list() -> Result<()> {
 }
 
 fn add(args: AddArgs) -> Result<()> {
+    let home = super::home_dir()?;
     // Resolve which project to add to
     let project = match args.project {
         Some(p) => ProjectName::from(p),
         None => {
-            let projects = registry::list_project_names().context("failed to read project list")?;
+            let projects =
+                registry::list_project_names_at(&home).context("failed to read project list")?;
             match projects.len() {
                 0 => {
                     return Err(anyhow::anyhow!(
@@ -93,7 +237,8 @@ fn add(args: AddArgs) -> Result<()> {
     };
 
     let project_type = args.project_type.unwrap_or_default().into();
-    let codebase = registry::add_codebase(
+    let codebase = registry::add_codebase_at(
+        &home,
         &project,
         CodebaseName::from(args.name.clone()),
         project_type,
@@ -108,3 +253,108 @@ fn add(args: AddArgs) -> Result<()> {
     println!("✓ Added '{}' to project '{}'", codebase.name, project);
     Ok(())
 }
+
+fn remove(args: RemoveArgs, out: OutputOptions) -> Result<()> {
+    let home = super::home_dir()?;
+    let project = ProjectName::from(args.name);
+    let names = registry::project_codebase_names_at(&home, &project)
+        .context("run `orchestra project list` to see registered projects")?;
+    if !args.cascade && !names.is_empty() {
+        return Err(RegistryError::ProjectNotEmpty {
+            name: project.0.clone(),
+            codebases: names.iter().map(|name| name.0.clone()).collect(),
+        })
+        .context("pass --cascade to remove them too");
+    }
+
+    // --purge-files needs each codebase's path, so its YAML must load.
+    let codebases: Vec<Codebase> = if args.purge_files {
+        names
+            .iter()
+            .map(|name| registry::load_codebase_at(&home, &project, name))
+            .collect::<Result<_, _>>()
+            .context("cannot purge files for a codebase whose registry entry does not load")?
+    } else {
+        Vec::new()
+    };
+
+    // Refuse an unsafe codebase before printing or deleting anything.
+    let purge_targets = codebases
+        .iter()
+        .map(super::offboard::purge_targets)
+        .collect::<Result<Vec<_>>>()?;
+
+    // The plan backs the confirmation prompt, so `--quiet --yes` drops it.
+    if !(out.quiet() && args.yes) {
+        println!("Removing project '{}' will delete:", project);
+        println!(
+            "  {}",
+            paths::dirs_at(&home)
+                .registry_root
+                .join(&project.0)
+                .display()
+        );
+        for name in &names {
+            println!("  codebase '{}':", name);
+            println!(
+                "    {}",
+                registry::codebase_path_at(&home, &project, name).display()
+            );
+            let store = hash_store::store_path_at(&home, &name.0);
+            if store.exists() {
+                println!("    {}", store.display());
+            }
+        }
+        for (codebase, targets) in codebases.iter().zip(&purge_targets) {
+            println!(
+                "  files in {} ('{}'):",
+                codebase.path.display(),
+                codebase.name
+            );
+            for path in targets {
+                println!("    {}", path.display());
+            }
+        }
+        println!();
+    }
+
+    if !args.yes {
+        let confirm = super::offboard::prompt("Proceed? This cannot be undone. [y/N]: ")?;
+        if !matches!(confirm.trim().to_ascii_lowercase().as_str(), "y" | "yes") {
+            println!("Project removal cancelled.");
+            return Ok(());
+        }
+    }
+
+    for codebase in &codebases {
+        if !codebase.path.is_dir() {
+            eprintln!(
+                "  Warning: {} no longer exists; skipping its files.",
+                codebase.path.display()
+            );
+            continue;
+        }
+        let purged = super::offboard::purge_managed_files(codebase)
+            .with_context(|| format!("failed to purge files for '{}'", codebase.name))?;
+        if !out.quiet() {
+            println!(
+                "Removed {} Orchestra-managed files from '{}'.",
+                purged.removed_files, codebase.name
+            );
+        }
+    }
+
+    let mode = if args.cascade {
+        RemoveProjectMode::Cascade
+    } else {
+        RemoveProjectMode::RefuseIfNotEmpty
+    };
+    let removed = registry::remove_project_at(&home, &project, mode)
+        .with_context(|| format!("failed to remove project '{}'", project))?;
+    println!(
+        "✓ Removed project '{}' and {} codebase(s) from the registry.",
+        project,
+        removed.len()
+    );
+    Ok(())
+}
@@ -1,13 +1,13 @@
 //! `orchestra reset` — wipe Orchestra and all its managed files, then reinstall clean.
 
-use std::path::PathBuf;
-
 use anyhow::{Context, Result};
 use clap::Args;
 
-use orchestra_core::registry;
-use orchestra_renderer::engine::{backup_dir, legacy_orchestra_dirs, orchestra_dir, AgentKind};
-use orchestra_sync::{load_backup_manifest, restore_from_backup};
+use orchestra_core::{paths, registry};
+use orchestra_renderer::engine::{backup_dir, legacy_orchestra_dirs, orchestra_dir};
+use orchestra_sync::{layout::check_codebase_root, restore_from_backup};
+
+use crate::output::OutputOptions;
 
 /// Arguments for `orchestra reset`.
 #[derive(Args, Debug)]
@@ -22,7 +22,7 @@ pub struct ResetArgs {
 }
 
 impl ResetArgs {
-    pub fn run(self) -> Result<()> {
+    pub fn run(self, out: OutputOptions) -> Result<()> {
         if !self.confirm {
             eprintln!("orchestra reset requires --confirm to prevent accidental data loss.");
             eprintln!();
@@ -37,58 +37,76 @@ impl ResetArgs {
             std::process::exit(1);
         }
 
-        let home = dirs::home_dir().context("could not determine home directory")?;
+        let home = super::home_dir()?;
+        // Progress lines; warnings and the final line are printed regardless.
+        let progress = |line: &str| {
+            if !out.quiet() {
+                println!("{line}");
+            }
+        };
 
-        println!("🎼 Orchestra Reset");
-        println!();
+        progress("🎼 Orchestra Reset");
+        progress("");
 
         // Collect all codebases before we destroy the registry
         let codebases = registry::list_codebases_at(&home)
             .unwrap_or_default();
 
         if codebases.is_empty() {
-            println!("  No registered codebases found.");
+            progress("  No registered codebases found.");
         } else {
-            println!("  Found {} registered codebase(s):", codebases.len());
+            progress(&format!(
+                "  Found {} registered codebase(s):",
+                codebases.len()
+            ));
             for (project, cb) in &codebases {
-                println!("    • {} / {} ({})", project, cb.name, cb.path.display());
+                progress(&format!(
+                    "    • {} / {} ({})",
+                    project,
+                    cb.name,
+                    cb.path.display()
+                ));
             }
-            println!();
+            progress("");
 
             for (project, codebase) in &codebases {
                 let codebase_path = &codebase.path;
 
                 if !codebase_path.exists() {
-                    println!("  Skipping '{}' — path no longer exists.", codebase.name);
+                    progress(&format!(
+                        "  Skipping '{}' — path no longer exists.",
+                        codebase.name
+                    ));
                     continue;
                 }
+                if let Err(e) = check_codebase_root(codebase) {
+                    eprintln!("  ⚠ Skipping '{}': {}", codebase.name, e);
+                    continue;
+                }
+
+                progress(&format!("  Processing '{}'...", codebase.name));
 
-                println!("  Processing '{}'...", codebase.name);
+                let managed = super::offboard::managed_files_to_remove(codebase);
 
                 // Optionally restore backups
                 if self.restore_backups {
                     let backup_manifest = backup_dir(codebase_path).join("manifest.json");
                     if backup_manifest.exists() {
                         match restore_from_backup(codebase_path) {
-                            Ok(n) => println!("    ✓ Restored {} files from backup.", n),
+                            Ok(n) => progress(&format!("    ✓ Restored {} files from backup.", n)),
                             Err(e) => eprintln!("    ⚠ Could not restore backups: {}", e),
                         }
                     }
                 }
 
                 // Remove Orchestra-managed agent files
-                let protected = protected_restore_paths(codebase_path);
                 let mut removed = 0usize;
-                for path in managed_cleanup_paths(codebase_path)
-                    .into_iter()
-                    .filter(|p| !protected.iter().any(|protected| protected == p))
-                    .filter(|p: &PathBuf| p.exists())
-                {
+                for path in managed {
                     if std::fs::remove_file(&path).is_ok() {
                         removed += 1;
                     }
                 }
-                println!("    ✓ Removed {} managed agent files.", removed);
+                progress(&format!("    ✓ Removed {} managed agent files.", removed));
 
                 // Remove current and legacy Orchestra directories inside codebase
                 let orch_dir = orchestra_dir(codebase_path);
@@ -96,7 +114,7 @@ impl ResetArgs {
                     if let Err(e) = std::fs::remove_dir_all(&orch_dir) {
                         eprintln!("    ⚠ Could not remove orchestra/: {}", e);
                     } else {
-                        println!("    ✓ Removed orchestra/.");
+                        progress("    ✓ Removed orchestra/.");
                     }
                 }
                 for legacy_dir in legacy_orchestra_dirs(codebase_path) {
@@ -104,7 +122,7 @@ impl ResetArgs {
                         if let Err(e) = std::fs::remove_dir_all(&legacy_dir) {
                             eprintln!("    ⚠ Could not remove legacy orchestra/: {}", e);
                         } else {
-                            println!("    ✓ Removed legacy orchestra/.");
+                            progress("    ✓ Removed legacy orchestra/.");
                         }
                     }
                 }
@@ -113,53 +131,36 @@ impl ResetArgs {
             }
         }
 
-        println!();
+        progress("");
 
-        // Wipe ~/.orchestra/ — the global registry  
-        let orchestra_home = home.join(".orchestra");
-        if orchestra_home.exists() {
-            std::fs::remove_dir_all(&orchestra_home)
-                .with_context(|| format!("failed to remove {}", orchestra_home.display()))?;
-            println!("  ✓ Removed ~/.orchestra/ (registry, hashes, channel).");
-        } else {
-            println!("  ~/.orchestra/ not found — nothing to wipe.");
+        // Wipe ~/.orchestra/ (or the resolved data and state dirs) — the global registry
+        let dirs = paths::dirs_at(&home);
+        let mut roots = vec![dirs.data_root];
+        if dirs.state_root != roots[0] {
+            roots.push(dirs.state_root);
+        }
+        for root in roots {
+            if root.exists() {
+                std::fs::remove_dir_all(&root)
+                    .with_context(|| format!("failed to remove {}", root.display()))?;
+                progress(&format!("  ✓ Removed {}", root.display()));
+            } else {
+                progress(&format!(
+                    "  {} not found — nothing to wipe.",
+                    root.display()
+                ));
+            }
         }
 
-        println!();
+        progress("");
         println!("✓ Orchestra has been fully reset.");
-        println!();
-        println!("  To set up a fresh installation:");
-        println!("    orchestra onboard");
-        println!(
-            "  To reinstall the latest version:\n    curl -fsSL https://raw.githubusercontent.com/Chris-Miracle/orch/main/install.sh | sh"
+        progress("");
+        progress("  To set up a fresh installation:");
+        progress("    orchestra onboard");
+        progress(
+            "  To reinstall the latest version:\n    curl -fsSL https://raw.githubusercontent.com/Chris-Miracle/orch/main/install.sh | sh",
         );
 
         Ok(())
     }
 }
-
-fn managed_cleanup_paths(codebase_root: &std::path::Path) -> Vec<PathBuf> {
-    let mut paths = Vec::new();
-    for agent in AgentKind::all() {
-        paths.extend(agent.output_paths(codebase_root));
-        paths.extend(agent.legacy_output_paths(codebase_root));
-    }
-    paths.sort();
-    paths.dedup();
-    paths
-}
-
-fn protected_restore_paths(codebase_root: &std::path::Path) -> Vec<PathBuf> {
-    let Ok(Some(manifest)) = load_backup_manifest(codebase_root) else {
-        return Vec::new();
-    };
-
-    let mut paths: Vec<PathBuf> = manifest
-        .files
-        .into_iter()
-        .map(|entry| codebase_root.join(entry.original_path))
-        .collect();
-    paths.sort();
-    paths.dedup();
-    paths
-}
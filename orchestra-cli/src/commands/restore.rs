@@ -0,0 +1,97 @@
+//! `orchestra restore <codebase> [<file>] [--list] [--at <stamp>]` — bring back
+//! local edits that a sync overwrote.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use tabled::{settings::Style, Table, Tabled};
+
+use orchestra_sync::{edit_backup, staleness::format_datetime_age};
+
+use crate::output::OutputOptions;
+
+/// Arguments for `orchestra restore`.
+#[derive(Args, Debug)]
+pub struct RestoreArgs {
+    /// Codebase whose backups to list or restore.
+    pub codebase: String,
+
+    /// Managed file to restore, relative to the output root
+    /// (e.g. `orchestra/controls/CLAUDE.md`) or just its name when unique.
+    #[arg(required_unless_present = "list")]
+    pub file: Option<String>,
+
+    /// List backups (of `<file>` only, when given) instead of restoring.
+    #[arg(long)]
+    pub list: bool,
+
+    /// Restore the backup with this stamp (from `--list`) instead of the
+    /// newest one.
+    #[arg(long, value_name = "STAMP", conflicts_with = "list")]
+    pub at: Option<String>,
+}
+
+#[derive(Tabled)]
+struct BackupTableRow {
+    #[tabled(rename = "file")]
+    file: String,
+    #[tabled(rename = "stamp")]
+    stamp: String,
+    #[tabled(rename = "age")]
+    age: String,
+}
+
+impl RestoreArgs {
+    pub fn run(self, out: OutputOptions) -> Result<()> {
+        let home: PathBuf = super::home_dir()?;
+
+        if self.list {
+            return self.print_list(&home);
+        }
+
+        let file = self.file.as_deref().unwrap_or_default();
+        let restored = edit_backup::restore_at(&home, &self.codebase, file, self.at.as_deref())
+            .with_context(|| format!("failed to restore '{file}' for '{}'", self.codebase))?;
+        println!(
+            "✓ Restored {} from the backup taken {}",
+            restored.target.display(),
+            restored.backup.taken_at.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        if !out.quiet() {
+            println!(
+                "  The next sync overwrites it again; add `<!-- orchestra:ignore -->` to keep it."
+            );
+        }
+        Ok(())
+    }
+
+    fn print_list(&self, home: &std::path::Path) -> Result<()> {
+        let backups = edit_backup::list_at(home, &self.codebase)
+            .with_context(|| format!("failed to list backups for '{}'", self.codebase))?;
+        let rows: Vec<BackupTableRow> = backups
+            .iter()
+            .rev()
+            .filter(|backup| match &self.file {
+                Some(file) => backup.file == *file || backup.file.ends_with(&format!("/{file}")),
+                None => true,
+            })
+            .map(|backup| BackupTableRow {
+                file: backup.file.clone(),
+                stamp: backup.stamp(),
+                age: format_datetime_age(backup.taken_at),
+            })
+            .collect();
+
+        if rows.is_empty() {
+            println!("No backups for '{}'.", self.codebase);
+            return Ok(());
+        }
+
+        println!("Backups for '{}' ({})", self.codebase, rows.len());
+        let mut table = Table::new(rows);
+        table.with(Style::rounded());
+        println!("{table}");
+        Ok(())
+    }
+}
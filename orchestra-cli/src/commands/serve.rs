@@ -0,0 +1,24 @@
+//! `orchestra serve --stdio` — daemon protocol over stdin/stdout for editors.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use orchestra_daemon::serve_stdio;
+
+/// Arguments for `orchestra serve`.
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// Read newline-delimited JSON requests from stdin and answer on stdout.
+    /// Requests run in this process; no daemon is needed.
+    #[arg(long, required = true)]
+    pub stdio: bool,
+}
+
+impl ServeArgs {
+    pub fn run(self) -> Result<()> {
+        let home: PathBuf = super::home_dir()?;
+        serve_stdio(&home).context("stdio server failed")
+    }
+}
@@ -0,0 +1,199 @@
+//! `orchestra setup <path> --project <name> [--type <TYPE> | --detect [--yes]] [--dry-run] [--daemon]`
+
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use orchestra_core::{
+    registry,
+    types::{CodebaseName, ProjectName},
+};
+
+use super::super::ProjectTypeArg;
+use super::daemon::{DaemonCommand, DEFAULT_RESYNC_HOURS};
+use super::init::{prompt_line, InitArgs};
+use super::sync::SyncArgs;
+use crate::output::OutputOptions;
+
+/// Register a codebase, sync it, and show what to do next.
+#[derive(Args, Debug)]
+pub struct SetupArgs {
+    /// Absolute or relative path to the codebase root directory.
+    pub path: PathBuf,
+
+    /// Project group name (e.g. "copnow", "myapp").
+    #[arg(long, short = 'p', value_parser = super::parse_name)]
+    pub project: String,
+
+    /// Project category: backend | frontend | mobile | ml | infra.
+    #[arg(long = "type", short = 't', value_name = "TYPE")]
+    pub project_type: Option<ProjectTypeArg>,
+
+    /// Detect the project type from the directory contents. On a terminal
+    /// the detected stack is shown for confirmation first.
+    #[arg(long, conflicts_with = "project_type")]
+    pub detect: bool,
+
+    /// With `--detect`, accept the detected type without prompting.
+    #[arg(long, short = 'y', requires = "detect")]
+    pub yes: bool,
+
+    /// Show what would be registered without writing anything.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// After syncing, offer to install the daemon as a launchd agent
+    /// (macOS).
+    #[arg(long, conflicts_with = "dry_run")]
+    pub daemon: bool,
+}
+
+impl SetupArgs {
+    /// `orchestra init` followed by `orchestra sync` for the new codebase.
+    /// A failed sync leaves the codebase registered, as running the two
+    /// commands would.
+    pub fn run(self, out: OutputOptions) -> Result<()> {
+        let init = self.init_args();
+        if self.dry_run {
+            return self.preview(&init, out);
+        }
+
+        let codebase = init.register(&self.path, out)?;
+        let name = codebase.name.0.clone();
+        SyncArgs {
+            codebase: Some(name.clone()),
+            all: false,
+            dry_run: false,
+            agent: None,
+            no_backup: false,
+            enforce_budget: false,
+            report: None,
+            fail_on_change: false,
+            watch: false,
+            force: false,
+        }
+        .run(out)
+        .with_context(|| {
+            format!("'{name}' stays registered; run `orchestra sync {name}` once this is fixed")
+        })?;
+
+        let daemon_installed = self.daemon && offer_daemon_install(out)?;
+        if !out.quiet() {
+            print_next_steps(&name, daemon_installed);
+        }
+        Ok(())
+    }
+
+    fn init_args(&self) -> InitArgs {
+        InitArgs {
+            path: Some(self.path.clone()),
+            from_git: None,
+            dest: None,
+            no_sync: false,
+            project: self.project.clone(),
+            project_type: self.project_type.clone(),
+            detect: self.detect,
+            yes: self.yes,
+            detect_strict: false,
+            output_root: None,
+            force_overlap: false,
+            no_manifest: false,
+            seed_commands: false,
+        }
+    }
+
+    /// `--dry-run`: detect the project type if asked and show where the
+    /// registry entry and generated files would go.
+    fn preview(&self, init: &InitArgs, out: OutputOptions) -> Result<()> {
+        let path = self
+            .path
+            .canonicalize()
+            .with_context(|| format!("cannot resolve path '{}'", self.path.display()))?;
+        let project_type = if self.detect {
+            init.detect_project_type(&path, out)?.project_type
+        } else {
+            self.project_type
+                .clone()
+                .map(Into::into)
+                .unwrap_or_default()
+        };
+
+        let home = super::home_dir()?;
+        let project = ProjectName::from(self.project.clone());
+        let name = CodebaseName::from(codebase_name(&path));
+        let saved = registry::codebase_path_at(&home, &project, &name);
+        if saved.exists() {
+            println!(
+                "'{}' is already registered under project '{}'; setup would sync it",
+                name, self.project
+            );
+        } else {
+            println!(
+                "Would register '{}' under project '{}' as {}",
+                name, self.project, project_type
+            );
+        }
+        if !out.quiet() {
+            println!("  Registry entry: {}", saved.display());
+            println!("  Generated files: {}", path.join("orchestra").display());
+        }
+        println!("Nothing was written (dry run).");
+        Ok(())
+    }
+}
+
+/// The name `init` gives the codebase at `path`: its directory name.
+fn codebase_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// On macOS, ask whether to run `orchestra daemon install` and run it on
+/// yes; elsewhere, or off a terminal, only say how. Returns whether the
+/// daemon was installed.
+fn offer_daemon_install(out: OutputOptions) -> Result<bool> {
+    if !cfg!(target_os = "macos") {
+        println!(
+            "--daemon installs the launchd agent on macOS only; \
+             run `orchestra daemon install` to set up the systemd user unit."
+        );
+        return Ok(false);
+    }
+    if !io::stdin().is_terminal() {
+        if !out.quiet() {
+            println!("Run `orchestra daemon install` to keep agent files in sync automatically.");
+        }
+        return Ok(false);
+    }
+    let answer = prompt_line(
+        &mut io::stdin().lock(),
+        &mut io::stdout(),
+        "Install the Orchestra daemon as a launchd agent now? [Y/n]: ",
+    )?;
+    if !matches!(answer.as_deref(), Some("" | "y" | "yes")) {
+        return Ok(false);
+    }
+    super::daemon::run(DaemonCommand::Install {
+        no_startup_sync: false,
+        resync_interval: DEFAULT_RESYNC_HOURS,
+    })?;
+    Ok(true)
+}
+
+fn print_next_steps(codebase: &str, daemon_installed: bool) {
+    println!();
+    println!("Next steps:");
+    println!(
+        "  • Add tasks and conventions: write `task-added \"<title>\"` or \
+         `convention-added \"<text>\"` in an `<!-- orchestra:update -->` block \
+         of any generated agent file"
+    );
+    println!("  • Import open issues: orchestra task import {codebase} --from-json issues.json");
+    if !daemon_installed {
+        println!("  • Keep agent files in sync automatically: orchestra daemon install");
+    }
+}
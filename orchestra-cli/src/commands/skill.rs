@@ -0,0 +1,93 @@
+//! `orchestra skill list|add` — manage the shared skills catalog.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+
+use orchestra_renderer::skills::{self, SkillEntry};
+
+use crate::output::OutputOptions;
+
+/// Manage skill descriptions in `~/.orchestra/skills/`.
+#[derive(Subcommand, Debug)]
+pub enum SkillCommand {
+    /// List catalogued skills.
+    List,
+
+    /// Add a skill to the catalog.
+    Add(SkillAddArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct SkillAddArgs {
+    /// Skill id (letters, digits, `-` and `_`), as used in `--skill`.
+    pub id: String,
+
+    /// Description rendered into agent files.
+    #[arg(long)]
+    pub description: String,
+
+    /// Heading of the catalog file. Defaults to the id.
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Overwrite an existing catalog entry.
+    #[arg(long)]
+    pub force: bool,
+}
+
+pub fn run(cmd: SkillCommand, out: OutputOptions) -> Result<()> {
+    let home = super::home_dir()?;
+    match cmd {
+        SkillCommand::List => list(&home, out),
+        SkillCommand::Add(args) => add(&home, args),
+    }
+}
+
+fn list(home: &Path, out: OutputOptions) -> Result<()> {
+    let catalog = skills::load_catalog(home).context("failed to load skills catalog")?;
+    if catalog.is_empty() {
+        println!("No skills catalogued.");
+        if !out.quiet() {
+            println!("Run: orchestra skill add <id> --description <text>");
+        }
+        return Ok(());
+    }
+
+    let width = catalog.keys().map(String::len).max().unwrap_or(0);
+    for entry in catalog.values() {
+        println!("  {:<width$}  {}", entry.id, entry.description);
+    }
+    Ok(())
+}
+
+fn add(home: &Path, args: SkillAddArgs) -> Result<()> {
+    super::validate_id("skill", &args.id)?;
+    let description = args.description.trim();
+    if description.is_empty() {
+        bail!("skill description must not be empty");
+    }
+    let path = skills::entry_path_at(home, &args.id);
+    if path.exists() && !args.force {
+        bail!(
+            "skill '{}' is already catalogued at {}; pass --force to overwrite",
+            args.id,
+            path.display()
+        );
+    }
+
+    let entry = SkillEntry {
+        title: args.title.unwrap_or_else(|| args.id.clone()),
+        id: args.id,
+        description: description.to_string(),
+    };
+    let path = skills::save_entry_at(home, &entry)
+        .with_context(|| format!("failed to write skill '{}'", entry.id))?;
+    println!(
+        "Catalogued skill '{}' at {}. Run `orchestra sync --all` to update agent files.",
+        entry.id,
+        path.display()
+    );
+    Ok(())
+}
@@ -1,21 +1,29 @@
 //! `orchestra status` — staleness and sync visibility.
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal, Write as _};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
-use clap::Args;
+use anyhow::{bail, Context, Result};
+use clap::{Args, ValueEnum};
 use colored::Colorize;
-use serde::Serialize;
+use crossterm::{
+    cursor::MoveTo,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{self, Clear, ClearType},
+};
 use tabled::{settings::Style, Table, Tabled};
 
-use orchestra_core::{registry, types::TaskStatus};
-use orchestra_sync::{
-    hash_store,
-    staleness::{check, format_datetime_age},
-    StalenessSignal,
+use orchestra_daemon::{request_status_report, DaemonError};
+use orchestra_sync::status::{
+    build_report, AgentStatus, CodebaseStatus, ProjectRollup, SignalKind, StatusReport,
 };
 
+use super::plural;
+use crate::output::OutputOptions;
+
 /// Arguments for `orchestra status`.
 #[derive(Args, Debug)]
 pub struct StatusArgs {
@@ -23,73 +31,285 @@ pub struct StatusArgs {
     #[arg(long)]
     pub project: Option<String>,
 
-    /// Emit machine-readable JSON.
-    #[arg(long)]
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = StatusFormat::Table)]
+    pub format: StatusFormat,
+
+    /// Emit machine-readable JSON (alias for `--format json`).
+    #[arg(long, conflicts_with = "format")]
     pub json: bool,
+
+    /// Hash every managed file instead of trusting unchanged size and mtime.
+    #[arg(long)]
+    pub deep: bool,
+
+    /// Show only this codebase, broken down per agent.
+    #[arg(long, value_name = "CODEBASE")]
+    pub detailed: Option<String>,
+
+    /// Ask the running daemon for the report; checks locally when the daemon
+    /// is not running. Markdown output, which lists changed files, is always
+    /// checked locally.
+    #[arg(long, conflicts_with_all = ["deep", "detailed"])]
+    pub via_daemon: bool,
+
+    /// Redraw the table until `q` or Ctrl-C, marking rows whose status
+    /// changed since the previous refresh.
+    #[arg(long, conflicts_with_all = ["json", "format"])]
+    pub watch: bool,
+
+    /// Seconds between `--watch` refreshes.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 2,
+        requires = "watch",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub interval: u64,
+}
+
+/// Output formats for `orchestra status`.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StatusFormat {
+    /// Colored table for the terminal.
+    #[default]
+    Table,
+    /// Machine-readable JSON.
+    Json,
+    /// GitHub-flavored markdown, for pasting into PRs and notes.
+    Markdown,
 }
 
 impl StatusArgs {
-    pub fn run(self) -> Result<()> {
-        let home: PathBuf = dirs::home_dir().context("could not determine home directory")?;
+    pub fn run(self, out: OutputOptions) -> Result<()> {
+        let home: PathBuf = super::home_dir()?;
+        if self.watch {
+            return self.watch(&home);
+        }
 
-        let mut codebases = registry::list_codebases_at(&home)
-            .context("failed to load registry — run `orchestra init` first")?;
+        let format = if self.json {
+            StatusFormat::Json
+        } else {
+            self.format
+        };
+        let daemon_report = match format {
+            StatusFormat::Markdown => None,
+            StatusFormat::Table | StatusFormat::Json => self.daemon_report(&home),
+        };
+        let report = match daemon_report {
+            Some(report) => report,
+            None => self.local_report(&home)?,
+        };
+        match format {
+            StatusFormat::Table => print_table(report, out),
+            StatusFormat::Json => print_json(&report)?,
+            StatusFormat::Markdown => print!("{}", render_markdown(report)),
+        }
+        Ok(())
+    }
+
+    fn local_report(&self, home: &Path) -> Result<StatusReport> {
+        let mut codebases = super::list_codebases_or_warn(home)?;
         if let Some(project_filter) = self.project.as_ref() {
             codebases.retain(|(project, _)| project.0 == *project_filter);
         }
+        if let Some(name) = self.detailed.as_ref() {
+            codebases.retain(|(_, codebase)| codebase.name.0 == *name);
+            if codebases.is_empty() {
+                bail!("codebase '{name}' is not registered");
+            }
+        }
 
-        let report = build_report(&home, &codebases)?;
-        if self.json {
-            print_json(report)?;
-            return Ok(());
+        build_report(home, &codebases, self.deep, self.detailed.is_some())
+            .context("status check failed")
+    }
+
+    /// The report from the running daemon with `--via-daemon`, or `None` to
+    /// compute it locally.
+    fn daemon_report(&self, home: &Path) -> Option<StatusReport> {
+        if !self.via_daemon {
+            return None;
         }
+        match self.fetch_daemon_report(home) {
+            Ok(report) => Some(report),
+            Err(err) => {
+                match err.downcast_ref::<DaemonError>() {
+                    Some(DaemonError::DaemonNotRunning { .. }) => {
+                        eprintln!("⚠  the daemon is not running; checking locally")
+                    }
+                    _ => eprintln!("⚠  daemon status failed ({err:#}); checking locally"),
+                }
+                None
+            }
+        }
+    }
 
-        print_table(report);
-        Ok(())
+    fn fetch_daemon_report(&self, home: &Path) -> Result<StatusReport> {
+        let data = request_status_report(home)?;
+        let mut report = serde_json::from_value::<StatusReport>(data)
+            .context("daemon sent an unexpected status report")?;
+        if let Some(project) = self.project.as_ref() {
+            report.retain_project(project);
+        }
+        Ok(report)
+    }
+
+    /// `status --watch`: redraw the table every `--interval` seconds until
+    /// `q` or Ctrl-C. Asks the daemon when one is running, which keeps each
+    /// refresh cheap, and checks locally otherwise.
+    fn watch(&self, home: &Path) -> Result<()> {
+        if !io::stdout().is_terminal() {
+            bail!("`status --watch` needs a terminal; run `orchestra status` instead");
+        }
+        let interval = Duration::from_secs(self.interval);
+        let use_daemon = !self.deep && self.detailed.is_none();
+        let mut previous: Option<StatusReport> = None;
+        loop {
+            let report = if use_daemon {
+                self.fetch_daemon_report(home)
+                    .or_else(|_| self.local_report(home))?
+            } else {
+                self.local_report(home)?
+            };
+            let rows = annotate_changes(previous.as_ref(), &report);
+            let mut stdout = io::stdout();
+            execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+            print!("{}", render_watch(&report, &rows, self.interval));
+            stdout.flush()?;
+            previous = Some(report);
+            if quit_requested(interval)? {
+                return Ok(());
+            }
+        }
     }
 }
 
-#[derive(Debug, Clone)]
-struct CodebaseStatus {
+/// Keeps the terminal in raw mode until dropped.
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> Result<Self> {
+        terminal::enable_raw_mode().context("failed to read keys from the terminal")?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Wait up to `timeout` for `q`, Esc or Ctrl-C, reading keys in raw mode.
+fn quit_requested(timeout: Duration) -> Result<bool> {
+    let _raw = RawMode::enable()?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() || !event::poll(left)? {
+            return Ok(false);
+        }
+        if let Event::Key(key) = event::read()? {
+            let ctrl_c =
+                key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            if key.kind == KeyEventKind::Press
+                && (ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc))
+            {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+/// One codebase in the `status --watch` table.
+#[derive(Debug, Clone, PartialEq, Eq, Tabled)]
+struct WatchRow {
+    /// The status differs from the previous refresh, or the codebase is new.
+    #[tabled(rename = "", display_with = "change_marker")]
+    changed: bool,
+    #[tabled(rename = "project")]
     project: String,
+    #[tabled(rename = "codebase")]
     codebase: String,
-    signal: StalenessSignal,
+    #[tabled(rename = "status", display_with = "signal_label")]
+    status: SignalKind,
+    #[tabled(rename = "detail")]
     detail: String,
-    last_sync_age: String,
-    last_sync_at: Option<String>,
+    #[tabled(rename = "last sync")]
+    last_sync: String,
+    #[tabled(rename = "active tasks")]
     active_tasks: usize,
 }
 
-#[derive(Debug, Clone)]
-struct StatusReport {
-    project_count: usize,
-    stale_count: usize,
-    needs_sync_count: usize,
-    codebases: Vec<CodebaseStatus>,
+fn change_marker(changed: &bool) -> String {
+    if *changed { "*" } else { "" }.to_string()
 }
 
-#[derive(Serialize)]
-struct StatusReportJson {
-    summary: StatusSummaryJson,
-    codebases: Vec<CodebaseStatusJson>,
+fn signal_label(kind: &SignalKind) -> String {
+    kind.label().to_string()
 }
 
-#[derive(Serialize)]
-struct StatusSummaryJson {
-    projects: usize,
-    codebases: usize,
-    stale: usize,
+/// The rows of `current`, grouped by project, each marked as changed when
+/// `previous` had the codebase with another status or not at all. Nothing
+/// is marked on the first refresh.
+fn annotate_changes(previous: Option<&StatusReport>, current: &StatusReport) -> Vec<WatchRow> {
+    let mut rows: Vec<WatchRow> = current
+        .codebases
+        .iter()
+        .map(|row| WatchRow {
+            changed: previous.is_some_and(|previous| {
+                !previous
+                    .codebases
+                    .iter()
+                    .any(|old| old.codebase == row.codebase && old.status == row.status)
+            }),
+            project: row.project.clone(),
+            codebase: row.codebase.clone(),
+            status: row.status,
+            detail: row.detail.clone(),
+            last_sync: row.last_sync_age.clone(),
+            active_tasks: row.active_tasks,
+        })
+        .collect();
+    rows.sort_by(|a, b| a.project.cmp(&b.project));
+    rows
 }
 
-#[derive(Serialize)]
-struct CodebaseStatusJson {
-    project: String,
-    codebase: String,
-    status: String,
-    detail: String,
-    last_sync_age: String,
-    last_sync_at: Option<String>,
-    active_tasks: usize,
+/// One `--watch` frame: the summary line, the table with changed rows
+/// highlighted, and how to quit.
+fn render_watch(report: &StatusReport, rows: &[WatchRow], interval: u64) -> String {
+    let mut frame = format!(
+        "Orchestra v{} | {} | {} | {} stale\n",
+        env!("CARGO_PKG_VERSION"),
+        plural(report.summary.projects, "project"),
+        plural(report.summary.codebases, "codebase"),
+        report.summary.stale,
+    );
+    if rows.is_empty() {
+        frame.push_str("No codebases registered.\n");
+    } else {
+        let mut table = Table::new(rows);
+        table.with(Style::rounded());
+        for line in table.to_string().lines() {
+            // Changed rows are the ones whose first cell holds the marker.
+            if line.starts_with("│ * ") {
+                frame.push_str(&line.bright_white().bold().to_string());
+            } else {
+                frame.push_str(line);
+            }
+            frame.push('\n');
+        }
+    }
+    frame.push_str(&format!(
+        "{}\n",
+        format!(
+            "Refreshed {} · every {interval}s · * changed since the last refresh · q to quit",
+            chrono::Local::now().format("%H:%M:%S")
+        )
+        .bright_black()
+    ));
+    frame
 }
 
 #[derive(Tabled)]
@@ -106,120 +326,48 @@ struct StatusTableRow {
     active_tasks: usize,
 }
 
-fn build_report(
-    home: &Path,
-    codebases: &[(
-        orchestra_core::types::ProjectName,
-        orchestra_core::types::Codebase,
-    )],
-) -> Result<StatusReport> {
-    let project_count = codebases
-        .iter()
-        .map(|(project, _)| project.0.clone())
-        .collect::<BTreeSet<_>>()
-        .len();
-
-    let mut rows = Vec::new();
-    for (project, codebase) in codebases {
-        let signal = check(home, project, codebase)
-            .with_context(|| format!("status check failed for '{}'", codebase.name))?;
-        let active_tasks = count_active_tasks(codebase);
-        let (last_sync_at, last_sync_age) = load_last_sync(home, &codebase.name.0)
-            .with_context(|| format!("failed to load hash store for '{}'", codebase.name))?;
-
-        rows.push(CodebaseStatus {
-            project: project.0.clone(),
-            codebase: codebase.name.0.clone(),
-            detail: signal_detail(&signal),
-            signal,
-            last_sync_age,
-            last_sync_at,
-            active_tasks,
-        });
-    }
-
-    let stale_count = rows
-        .iter()
-        .filter(|r| matches!(r.signal, StalenessSignal::Stale { .. }))
-        .count();
-    let needs_sync_count = rows
-        .iter()
-        .filter(|r| !matches!(r.signal, StalenessSignal::Current))
-        .count();
-
-    Ok(StatusReport {
-        project_count,
-        stale_count,
-        needs_sync_count,
-        codebases: rows,
-    })
-}
-
-fn load_last_sync(home: &Path, codebase_name: &str) -> Result<(Option<String>, String)> {
-    let path = hash_store::store_path_at(home, codebase_name);
-    if !path.exists() {
-        return Ok((None, "never".to_string()));
-    }
-    let store = hash_store::load_at(home, codebase_name)?;
-    if store.files.is_empty() {
-        return Ok((None, "never".to_string()));
-    }
-    let iso = Some(store.synced_at.to_rfc3339());
-    let age = format_datetime_age(store.synced_at);
-    Ok((iso, age))
-}
-
-fn count_active_tasks(codebase: &orchestra_core::types::Codebase) -> usize {
-    codebase
-        .projects
-        .iter()
-        .flat_map(|project| project.tasks.iter())
-        .filter(|task| !matches!(task.status, TaskStatus::Done))
-        .count()
+#[derive(Tabled)]
+struct AgentTableRow {
+    #[tabled(rename = "agent")]
+    agent: String,
+    #[tabled(rename = "status")]
+    status: String,
+    #[tabled(rename = "detail")]
+    detail: String,
 }
 
-fn print_json(report: StatusReport) -> Result<()> {
-    let payload = StatusReportJson {
-        summary: StatusSummaryJson {
-            projects: report.project_count,
-            codebases: report.codebases.len(),
-            stale: report.stale_count,
-        },
-        codebases: report
-            .codebases
-            .into_iter()
-            .map(|row| CodebaseStatusJson {
-                project: row.project,
-                codebase: row.codebase,
-                status: signal_key(&row.signal).to_string(),
-                detail: row.detail,
-                last_sync_age: row.last_sync_age,
-                last_sync_at: row.last_sync_at,
-                active_tasks: row.active_tasks,
-            })
-            .collect(),
-    };
+fn print_json(report: &StatusReport) -> Result<()> {
     println!(
         "{}",
-        serde_json::to_string_pretty(&payload).context("failed to serialize status JSON")?
+        serde_json::to_string_pretty(report).context("failed to serialize status JSON")?
     );
     Ok(())
 }
 
-fn print_table(report: StatusReport) {
+fn print_table(report: StatusReport, out: OutputOptions) {
     println!(
-        "Orchestra v{} | {} projects | {} codebases | {} stale",
+        "Orchestra v{} | {} | {} | {} stale",
         env!("CARGO_PKG_VERSION"),
-        report.project_count,
-        report.codebases.len(),
-        report.stale_count,
+        plural(report.summary.projects, "project"),
+        plural(report.summary.codebases, "codebase"),
+        report.summary.stale,
     );
+    if out.quiet() {
+        return;
+    }
 
     if report.codebases.is_empty() {
         println!("No codebases registered.");
         return;
     }
 
+    let needs_sync_count = report.needs_sync_count();
+    let outdated_detection: Vec<String> = report
+        .codebases
+        .iter()
+        .filter(|row| row.detection_outdated)
+        .map(|row| row.codebase.clone())
+        .collect();
     let separator = "■".repeat(67).bright_black().to_string();
     let mut grouped = BTreeMap::<String, Vec<CodebaseStatus>>::new();
     for row in report.codebases {
@@ -229,22 +377,30 @@ fn print_table(report: StatusReport) {
     println!("{separator}");
     println!(
         "Indicators: {} CURRENT  {} STALE  {} MODIFIED  {} ORPHAN  {} NEVER SYNCED",
-        signal_indicator(&StalenessSignal::Current),
-        signal_indicator(&StalenessSignal::Stale {
-            reason: String::new(),
-        }),
-        signal_indicator(&StalenessSignal::Modified { files: Vec::new() }),
-        signal_indicator(&StalenessSignal::Orphan { files: Vec::new() }),
-        signal_indicator(&StalenessSignal::NeverSynced),
+        signal_indicator(SignalKind::Current),
+        signal_indicator(SignalKind::Stale),
+        signal_indicator(SignalKind::Modified),
+        signal_indicator(SignalKind::Orphan),
+        signal_indicator(SignalKind::NeverSynced),
     );
     println!("{separator}");
+    let rollups: BTreeMap<String, ProjectRollup> = report
+        .projects
+        .into_iter()
+        .map(|rollup| (rollup.project.clone(), rollup))
+        .collect();
     for (project, rows) in grouped {
         println!("{}", project.to_uppercase().bold());
+        if let Some(rollup) = rollups.get(&project) {
+            println!("{}", rollup.summary_line().bright_black());
+        }
+        let breakdowns: Vec<Vec<AgentStatus>> =
+            rows.iter().filter_map(|row| row.agents.clone()).collect();
         let table_rows: Vec<StatusTableRow> = rows
             .into_iter()
             .map(|row| StatusTableRow {
                 codebase: row.codebase,
-                status: signal_label(&row.signal).to_string(),
+                status: row.status.label().to_string(),
                 detail: row.detail,
                 last_sync: row.last_sync_age,
                 active_tasks: row.active_tasks,
@@ -253,66 +409,275 @@ fn print_table(report: StatusReport) {
         let mut table = Table::new(table_rows);
         table.with(Style::rounded());
         println!("{table}");
+        for agents in breakdowns {
+            print_agent_table(&agents);
+        }
         println!("{separator}");
     }
 
-    if report.needs_sync_count > 0 {
+    if needs_sync_count > 0 {
         println!("Run 'orchestra sync --all' to update stale codebases.");
     }
+    for codebase in outdated_detection {
+        println!(
+            "⚠  Stack metadata may be outdated for '{codebase}': its manifest changed since \
+             detection. Run 'orchestra detect --save {codebase}' to refresh it."
+        );
+    }
 }
 
-fn signal_key(signal: &StalenessSignal) -> &'static str {
-    match signal {
-        StalenessSignal::NeverSynced => "never_synced",
-        StalenessSignal::Current => "current",
-        StalenessSignal::Stale { .. } => "stale",
-        StalenessSignal::Modified { .. } => "modified",
-        StalenessSignal::Orphan { .. } => "orphan",
-    }
+/// Per-agent breakdown printed under the codebase table by `--detailed`.
+fn print_agent_table(agents: &[AgentStatus]) {
+    let rows: Vec<AgentTableRow> = agents
+        .iter()
+        .map(|entry| AgentTableRow {
+            agent: entry.agent.clone(),
+            status: entry.status.label().to_string(),
+            detail: entry.detail.clone(),
+        })
+        .collect();
+    let mut table = Table::new(rows);
+    table.with(Style::rounded());
+    println!("{table}");
 }
 
-fn signal_label(signal: &StalenessSignal) -> &'static str {
-    match signal {
-        StalenessSignal::NeverSynced => "NEVER SYNCED",
-        StalenessSignal::Current => "CURRENT",
-        StalenessSignal::Stale { .. } => "STALE",
-        StalenessSignal::Modified { .. } => "MODIFIED",
-        StalenessSignal::Orphan { .. } => "ORPHAN",
+/// Render the report as one GitHub-flavored table per project, followed by
+/// a collapsible list of the modified and orphaned files behind each row.
+fn render_markdown(report: StatusReport) -> String {
+    let mut out = format!(
+        "**Orchestra v{} | {} | {} | {} stale**\n",
+        env!("CARGO_PKG_VERSION"),
+        plural(report.summary.projects, "project"),
+        plural(report.summary.codebases, "codebase"),
+        report.summary.stale,
+    );
+    if report.codebases.is_empty() {
+        out.push_str("\nNo codebases registered.\n");
+        return out;
     }
-}
 
-fn signal_indicator(signal: &StalenessSignal) -> String {
-    match signal {
-        StalenessSignal::NeverSynced => "■".bright_black().bold().to_string(),
-        StalenessSignal::Current => "■".green().bold().to_string(),
-        StalenessSignal::Stale { .. } => "■".yellow().bold().to_string(),
-        StalenessSignal::Modified { .. } => "■".red().bold().to_string(),
-        StalenessSignal::Orphan { .. } => "■".magenta().bold().to_string(),
+    let mut grouped = BTreeMap::<String, Vec<CodebaseStatus>>::new();
+    for row in report.codebases {
+        grouped.entry(row.project.clone()).or_default().push(row);
+    }
+
+    for (project, rows) in grouped {
+        out.push_str(&format!("\n### {}\n\n", markdown_cell(&project)));
+        out.push_str("| codebase | status | detail | last sync | active tasks |\n");
+        out.push_str("| --- | --- | --- | --- | --- |\n");
+        for row in &rows {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                markdown_cell(&row.codebase),
+                row.status.label(),
+                markdown_cell(&row.detail),
+                markdown_cell(&row.last_sync_age),
+                row.active_tasks,
+            ));
+        }
+
+        let with_files: Vec<&CodebaseStatus> =
+            rows.iter().filter(|row| !row.files.is_empty()).collect();
+        if with_files.is_empty() {
+            continue;
+        }
+        let file_count: usize = with_files.iter().map(|row| row.files.len()).sum();
+        out.push_str(&format!(
+            "\n<details>\n<summary>Changed files ({file_count})</summary>\n\n"
+        ));
+        for row in with_files {
+            out.push_str(&format!("**{}** ({})\n\n", row.codebase, row.status.key()));
+            for file in &row.files {
+                out.push_str(&format!("- `{file}`\n"));
+            }
+            out.push('\n');
+        }
+        out.push_str("</details>\n");
     }
+    out
+}
+
+/// Escape a value for use inside a markdown table cell.
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|")
 }
 
-fn signal_detail(signal: &StalenessSignal) -> String {
-    match signal {
-        StalenessSignal::NeverSynced => "no hash store entries".to_string(),
-        StalenessSignal::Current => "up to date".to_string(),
-        StalenessSignal::Stale { reason } => reason.clone(),
-        StalenessSignal::Modified { files } => format!("{} edited", summarize_files(files)),
-        StalenessSignal::Orphan { files } => format!("{} untracked", summarize_files(files)),
+fn signal_indicator(kind: SignalKind) -> String {
+    match kind {
+        SignalKind::NeverSynced => "■".bright_black().bold().to_string(),
+        SignalKind::Current => "■".green().bold().to_string(),
+        SignalKind::Stale => "■".yellow().bold().to_string(),
+        SignalKind::Modified => "■".red().bold().to_string(),
+        SignalKind::Orphan => "■".magenta().bold().to_string(),
     }
 }
 
-fn summarize_files(files: &[PathBuf]) -> String {
-    if files.is_empty() {
-        return "unknown file".to_string();
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orchestra_sync::{
+        status::{signal_detail, signal_files, StatusSummary},
+        StalenessSignal,
+    };
+
+    fn row(project: &str, codebase: &str, signal: StalenessSignal, age: &str) -> CodebaseStatus {
+        CodebaseStatus {
+            project: project.to_string(),
+            codebase: codebase.to_string(),
+            status: SignalKind::of(&signal),
+            detail: signal_detail(&signal),
+            files: signal_files(&signal)
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect(),
+            ignored: Vec::new(),
+            last_sync_age: age.to_string(),
+            last_sync_at: None,
+            active_tasks: 1,
+            detection_outdated: false,
+            agents: None,
+        }
     }
 
-    let mut names: Vec<String> = files
-        .iter()
-        .take(2)
-        .map(|path| path.display().to_string())
-        .collect();
-    if files.len() > names.len() {
-        names.push(format!("+{} more", files.len() - names.len()));
+    fn fixture() -> StatusReport {
+        let codebases = vec![
+            row("copnow", "current_api", StalenessSignal::Current, "5m ago"),
+            row(
+                "copnow",
+                "stale_api",
+                StalenessSignal::Stale {
+                    reason: "registry changed 2m ago".to_string(),
+                },
+                "1h ago",
+            ),
+            row(
+                "copnow",
+                "modified_api",
+                StalenessSignal::Modified {
+                    files: vec![
+                        PathBuf::from("orchestra/controls/CLAUDE.md"),
+                        PathBuf::from("AGENTS.md"),
+                    ],
+                },
+                "3h ago",
+            ),
+            row(
+                "infra",
+                "orphan_api",
+                StalenessSignal::Orphan {
+                    files: vec![PathBuf::from(".cursor/rules/old|name.mdc")],
+                },
+                "2d ago",
+            ),
+            row("infra", "never_api", StalenessSignal::NeverSynced, "never"),
+        ];
+        StatusReport {
+            summary: StatusSummary {
+                projects: 2,
+                codebases: 5,
+                stale: 1,
+            },
+            codebases,
+            projects: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn markdown_report_matches_golden_output() {
+        let expected = format!(
+            "**Orchestra v{} | 2 projects | 5 codebases | 1 stale**
+
+### copnow
+
+| codebase | status | detail | last sync | active tasks |
+| --- | --- | --- | --- | --- |
+| current_api | CURRENT | up to date | 5m ago | 1 |
+| stale_api | STALE | registry changed 2m ago | 1h ago | 1 |
+| modified_api | MODIFIED | orchestra/controls/CLAUDE.md, AGENTS.md edited | 3h ago | 1 |
+
+<details>
+<summary>Changed files (2)</summary>
+
+**modified_api** (modified)
+
+- `orchestra/controls/CLAUDE.md`
+- `AGENTS.md`
+
+</details>
+
+### infra
+
+| codebase | status | detail | last sync | active tasks |
+| --- | --- | --- | --- | --- |
+| orphan_api | ORPHAN | .cursor/rules/old\\|name.mdc untracked | 2d ago | 1 |
+| never_api | NEVER SYNCED | no hash store entries | never | 1 |
+
+<details>
+<summary>Changed files (1)</summary>
+
+**orphan_api** (orphan)
+
+- `.cursor/rules/old|name.mdc`
+
+</details>
+",
+            env!("CARGO_PKG_VERSION")
+        );
+        assert_eq!(render_markdown(fixture()), expected);
+    }
+
+    #[test]
+    fn watch_marks_rows_whose_status_changed() {
+        let first = fixture();
+        let rows = annotate_changes(None, &first);
+        assert!(rows.iter().all(|row| !row.changed), "nothing changed yet");
+        assert!(rows.windows(2).all(|w| w[0].project <= w[1].project));
+
+        let mut second = fixture();
+        second.codebases[0].status = SignalKind::Stale;
+        second.codebases[1].last_sync_age = "just now".to_string();
+        second.codebases.push(row(
+            "copnow",
+            "new_api",
+            StalenessSignal::NeverSynced,
+            "never",
+        ));
+        let changed: Vec<String> = annotate_changes(Some(&first), &second)
+            .into_iter()
+            .filter(|row| row.changed)
+            .map(|row| row.codebase)
+            .collect();
+        assert_eq!(changed, vec!["current_api", "new_api"]);
+
+        let unchanged = annotate_changes(Some(&second), &second);
+        assert!(unchanged.iter().all(|row| !row.changed));
+    }
+
+    #[test]
+    fn watch_frame_puts_the_marker_in_the_first_cell() {
+        let first = fixture();
+        let mut second = fixture();
+        second.codebases[2].status = SignalKind::Current;
+        let frame = render_watch(&second, &annotate_changes(Some(&first), &second), 2);
+        let marked: Vec<&str> = frame.lines().filter(|line| line.contains("│ * ")).collect();
+        assert_eq!(marked.len(), 1, "{frame}");
+        assert!(marked[0].contains("modified_api"));
+        assert!(frame.contains("every 2s"));
+    }
+
+    #[test]
+    fn markdown_report_without_codebases() {
+        let report = StatusReport {
+            summary: StatusSummary::default(),
+            codebases: Vec::new(),
+            projects: Vec::new(),
+        };
+        let markdown = render_markdown(report);
+        assert!(markdown.ends_with("\nNo codebases registered.\n"));
+        assert!(!markdown.contains('\u{1b}'), "markdown must not contain ANSI escapes");
     }
-    names.join(", ")
 }
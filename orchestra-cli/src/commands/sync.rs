@@ -1,16 +1,40 @@
 //! `orchestra sync` — render and write per-agent files for a codebase.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
 use clap::Args;
+use colored::Colorize;
+use orchestra_renderer::AgentKind;
 use orchestra_sync::{
-    pipeline::{self, SyncScope},
-    WriteResult,
+    pipeline::{self, CodebaseOutcome, SyncProgress, SyncScope},
+    SyncCodebaseResult, SyncError, SyncReport, UnknownAssignee, WriteResult,
 };
 
+use super::plural;
+use crate::output::OutputOptions;
+
+/// Exit code of a `--fail-on-change` run that wrote, or would write, files.
+const EXIT_CHANGED: i32 = 2;
+
+/// Exit code of an `--all` or `--report` run in which some, but not all,
+/// codebases failed.
+const EXIT_PARTIAL_FAILURE: i32 = 3;
+
 /// Arguments for `orchestra sync`.
+///
+/// Exit codes: 0 on success; 1 when the sync failed (every codebase, or a
+/// `post_sync` hook); 2 with `--fail-on-change` when files were written, or
+/// would be with `--dry-run`; 3 when some codebases of a multi-codebase run
+/// failed and the rest synced.
 #[derive(Args, Debug)]
+#[command(after_help = "Exit codes:\n  \
+    0  synced (nothing changed, with --fail-on-change)\n  \
+    1  sync failed: every codebase, or a post_sync hook\n  \
+    2  --fail-on-change: files were written, or would be with --dry-run\n  \
+    3  --all or --report: some codebases failed, the rest synced")]
 pub struct SyncArgs {
     /// Name of the codebase to sync (omit when using `--all`).
     pub codebase: Option<String>,
@@ -22,69 +46,697 @@ pub struct SyncArgs {
     /// Show what would be written without actually writing any files.
     #[arg(long)]
     pub dry_run: bool,
+
+    /// Only sync one agent's files: claude, cursor, windsurf, copilot, codex,
+    /// gemini, cline, antigravity, roo or zed.
+    #[arg(long, value_name = "AGENT")]
+    pub agent: Option<AgentKind>,
+
+    /// Overwrite locally modified files without saving a copy to
+    /// ~/.orchestra/backups/ first.
+    #[arg(long)]
+    pub no_backup: bool,
+
+    /// Trim agent files that are over their token budget: drop task
+    /// descriptions, then tasks past the first 5, then the skills section,
+    /// until they fit.
+    #[arg(long)]
+    pub enforce_budget: bool,
+
+    /// Write a JSON report of the run to PATH: every codebase's files and
+    /// their state, or why it failed. Failing codebases no longer stop the
+    /// run; the command still exits non-zero.
+    #[arg(long, value_name = "PATH", conflicts_with = "watch")]
+    pub report: Option<PathBuf>,
+
+    /// Exit with code 2 when any file was written, or would be with
+    /// `--dry-run`, after printing the summary. For CI checks that generated
+    /// files are up to date.
+    #[arg(long, conflicts_with = "watch")]
+    pub fail_on_change: bool,
+
+    /// Stay in the foreground and re-sync the codebase whenever its registry
+    /// file changes, until Ctrl-C.
+    #[arg(
+        long,
+        requires = "codebase",
+        conflicts_with_all = ["all", "dry_run", "agent", "no_backup", "enforce_budget"]
+    )]
+    pub watch: bool,
+
+    /// With `--watch`, start even though the daemon is running for this home.
+    #[arg(long, requires = "watch")]
+    pub force: bool,
 }
 
 impl SyncArgs {
-    pub fn run(self) -> Result<()> {
-        let home: PathBuf = dirs::home_dir().context("could not determine home directory")?;
+    pub fn run(self, out: OutputOptions) -> Result<()> {
+        let home: PathBuf = super::home_dir()?;
+
+        if let Some(report) = &self.report {
+            let scope = match &self.codebase {
+                Some(name) if !self.all => SyncScope::Codebase(name.clone()),
+                None if self.all => SyncScope::All,
+                _ => bail!("provide a codebase name or use --all"),
+            };
+            return self.run_with_report(&home, scope, report, out);
+        }
 
         if self.all {
-            let results =
-                pipeline::run(&home, SyncScope::All, self.dry_run).context("sync --all failed")?;
-            for r in &results {
-                print_results(&r.codebase_name, &r.writes, self.dry_run);
-            }
-            if results.is_empty() {
+            let started = Instant::now();
+            let outcomes = self
+                .run_each(&home, SyncScope::All, out)
+                .context("sync --all failed")?;
+            if outcomes.is_empty() {
                 println!("No codebases registered. Run `orchestra init` first.");
+                return Ok(());
             }
+            let total = outcomes.len();
+            let (results, failed) = print_outcomes(outcomes, started.elapsed(), self.dry_run, out);
+            self.finish(&results, failed, total)
         } else {
             let name = self
                 .codebase
                 .clone()
                 .context("provide a codebase name or use --all")?;
-            let mut results = pipeline::run(&home, SyncScope::Codebase(name.clone()), self.dry_run)
+            if self.watch && !self.force && daemon_is_running(&home) {
+                bail!(
+                    "the Orchestra daemon is running and already syncs '{name}' on change; \
+                     stop it with `orchestra daemon stop` or pass --force"
+                );
+            }
+            let started = Instant::now();
+            let results: Vec<SyncCodebaseResult> = self
+                .run_each(&home, SyncScope::Codebase(name.clone()), out)
+                .and_then(|outcomes| outcomes.into_iter().map(|o| o.result).collect())
                 .with_context(|| format!("sync failed for '{name}'"))?;
-            if let Some(result) = results.pop() {
-                print_results(&result.codebase_name, &result.writes, self.dry_run);
+            let outcomes: Vec<SyncOutcome> = results.iter().map(SyncOutcome::Synced).collect();
+            print_sync_output(&outcomes, started.elapsed(), self.dry_run, out);
+            print_warnings(&results, out);
+            if !self.watch {
+                return self.finish(&results, 0, results.len());
             }
+            post_sync_failures(&results)?;
+            watch(&home, &name, out)
         }
+    }
+
+    /// [`pipeline::run_each`] for `scope`: every codebase is synced, even
+    /// when one before it fails.
+    fn run_each(
+        &self,
+        home: &Path,
+        scope: SyncScope,
+        out: OutputOptions,
+    ) -> Result<Vec<CodebaseOutcome>, SyncError> {
+        pipeline::run_each(
+            home,
+            scope,
+            self.dry_run,
+            self.agent,
+            !self.no_backup,
+            self.enforce_budget,
+            |event| print_progress(&event, out),
+        )
+    }
 
+    /// End a run whose output has been printed: an error when every one of
+    /// `total` codebases or a hook failed, otherwise exit with
+    /// [`EXIT_PARTIAL_FAILURE`] when some codebases failed, or with
+    /// [`EXIT_CHANGED`] when `--fail-on-change` saw a write.
+    fn finish(&self, results: &[SyncCodebaseResult], failed: usize, total: usize) -> Result<()> {
+        if failed > 0 && failed == total {
+            bail!("sync failed for {failed} codebase(s)");
+        }
+        let hooks = post_sync_failures(results);
+        if failed > 0 {
+            if let Err(err) = &hooks {
+                eprintln!("Error: {err}");
+            }
+            exit_with(
+                EXIT_PARTIAL_FAILURE,
+                &format!(
+                    "sync failed for {failed} codebase(s); the other {} synced",
+                    total - failed
+                ),
+            );
+        }
+        hooks?;
+        if self.fail_on_change && results.iter().any(has_changes) {
+            exit_with(EXIT_CHANGED, "generated files changed (--fail-on-change)");
+        }
         Ok(())
     }
+
+    /// `sync --report`: sync every codebase in `scope` even when some fail,
+    /// write the report, then fail if any codebase or hook did.
+    fn run_with_report(
+        &self,
+        home: &Path,
+        scope: SyncScope,
+        report_path: &Path,
+        out: OutputOptions,
+    ) -> Result<()> {
+        let started_at = Utc::now();
+        let started = Instant::now();
+        let outcomes = self
+            .run_each(home, scope.clone(), out)
+            .context("sync failed")?;
+        let report = SyncReport::new(
+            home,
+            scope,
+            self.dry_run,
+            self.agent.map(|agent| agent.to_string()),
+            started_at,
+            started.elapsed(),
+            &outcomes,
+        );
+
+        let total = outcomes.len();
+        let (results, failed) = print_outcomes(outcomes, started.elapsed(), self.dry_run, out);
+
+        write_report(report_path, &report)?;
+        if !out.quiet() {
+            println!("Report written to {}", report_path.display());
+        }
+        self.finish(&results, failed, total)
+    }
 }
 
-fn print_results(codebase_name: &str, writes: &[WriteResult], dry_run: bool) {
-    let prefix = if dry_run { "[dry-run] " } else { "" };
-    let written: Vec<_> = writes
+/// Print the outcomes of a keep-going run, failures to stderr first, and
+/// return the synced results with the number of failed codebases.
+fn print_outcomes(
+    outcomes: Vec<CodebaseOutcome>,
+    elapsed: Duration,
+    dry_run: bool,
+    out: OutputOptions,
+) -> (Vec<SyncCodebaseResult>, usize) {
+    for outcome in &outcomes {
+        if let Err(err) = &outcome.result {
+            eprintln!("✗  '{}': {err}", outcome.name);
+        }
+    }
+    let printed: Vec<SyncOutcome> = outcomes
         .iter()
-        .filter(|r| {
-            matches!(
-                r,
-                WriteResult::Written { .. } | WriteResult::WouldWrite { .. }
-            )
+        .map(|outcome| match &outcome.result {
+            Ok(result) => SyncOutcome::Synced(result),
+            Err(_) => SyncOutcome::Failed(&outcome.name),
         })
         .collect();
-    let unchanged: Vec<_> = writes
-        .iter()
-        .filter(|r| matches!(r, WriteResult::Unchanged { .. }))
+    print_sync_output(&printed, elapsed, dry_run, out);
+    let failed = outcomes.iter().filter(|o| o.result.is_err()).count();
+    let results: Vec<SyncCodebaseResult> = outcomes
+        .into_iter()
+        .filter_map(|outcome| outcome.result.ok())
         .collect();
+    print_warnings(&results, out);
+    (results, failed)
+}
+
+/// Whether a sync wrote a file, or a dry run would have.
+fn has_changes(result: &SyncCodebaseResult) -> bool {
+    result.writes.iter().any(|write| {
+        matches!(
+            write,
+            WriteResult::Written { .. } | WriteResult::WouldWrite { .. }
+        )
+    })
+}
+
+/// Report `message` the way a failed command does and exit with `code`.
+fn exit_with(code: i32, message: &str) -> ! {
+    use std::io::Write;
+
+    let _ = std::io::stdout().flush();
+    eprintln!("Error: {message}");
+    std::process::exit(code)
+}
 
-    if written.is_empty() && unchanged.is_empty() {
-        println!("{prefix}✓ '{codebase_name}' — nothing to do");
+fn write_report(path: &Path, report: &SyncReport) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let mut json = serde_json::to_string_pretty(report).context("failed to serialize report")?;
+    json.push('\n');
+    std::fs::write(path, json)
+        .with_context(|| format!("failed to write report to {}", path.display()))
+}
+
+/// Whether a daemon is listening on this home's socket.
+#[cfg(unix)]
+fn daemon_is_running(home: &Path) -> bool {
+    std::os::unix::net::UnixStream::connect(orchestra_daemon::paths::socket_path(home)).is_ok()
+}
+
+#[cfg(not(unix))]
+fn daemon_is_running(_home: &Path) -> bool {
+    false
+}
+
+/// `sync --watch`: re-sync `name` on every change to its registry file,
+/// printing a line per sync, until Ctrl-C.
+#[cfg(unix)]
+fn watch(home: &Path, name: &str, out: OutputOptions) -> Result<()> {
+    use orchestra_daemon::Watch;
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .context("failed to start the watch runtime")?;
+    if !out.quiet() {
+        println!("Watching '{name}' for registry changes (Ctrl-C to stop)…");
+    }
+    runtime
+        .block_on(Watch::new(home).only_codebase(name).run_until(
+            async {
+                let _ = tokio::signal::ctrl_c().await;
+            },
+            |event| print_watch_event(&event),
+        ))
+        .with_context(|| format!("watching '{name}' failed"))?;
+    if !out.quiet() {
+        println!("Stopped watching '{name}'.");
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn watch(_home: &Path, _name: &str, _out: OutputOptions) -> Result<()> {
+    bail!("sync --watch is not supported on Windows")
+}
+
+#[cfg(unix)]
+fn print_watch_event(event: &orchestra_daemon::SyncEvent) {
+    use orchestra_daemon::SyncEvent;
+
+    let now = chrono::Local::now().format("%H:%M:%S");
+    match event {
+        SyncEvent::SyncCompleted(summary) => println!(
+            "[{now}] ✓ '{}' synced ({} written, {} unchanged)",
+            summary.target, summary.written, summary.unchanged
+        ),
+        SyncEvent::SyncFailed { target, error } => {
+            eprintln!("[{now}] ✗ '{target}': {error} (retrying)")
+        }
+        SyncEvent::RegistryInvalid { path, error } => eprintln!(
+            "[{now}] ⚠  {} does not parse; syncing resumes once it is fixed\n{error}",
+            path.display()
+        ),
+        SyncEvent::RegistryRemoved { path } => {
+            eprintln!(
+                "[{now}] ⚠  {} was removed from the registry",
+                path.display()
+            )
+        }
+        SyncEvent::WatchDegraded { unwatched_dirs: 0 } => {
+            eprintln!("[{now}] ✓ every directory is watched again")
+        }
+        SyncEvent::WatchDegraded { unwatched_dirs } => eprintln!(
+            "[{now}] ⚠  {unwatched_dirs} directories cannot be watched (inotify watch limit reached); \
+             raise fs.inotify.max_user_watches, retrying"
+        ),
+        SyncEvent::RegistryChanged { .. } => {}
+    }
+}
+
+/// One stderr line per codebase while `sync --all` runs, so a long run shows
+/// where it is. Per-file lines are added with `--verbose`; `--quiet` drops both.
+fn print_progress(event: &SyncProgress, out: OutputOptions) {
+    if out.quiet() {
         return;
     }
+    match event {
+        SyncProgress::CodebaseStarted { name, index, total } if *total > 1 => {
+            eprintln!("[{index}/{total}] syncing '{name}'…");
+        }
+        SyncProgress::FileWritten { path } if out.verbose() => {
+            eprintln!("  ✎  {}", path.display());
+        }
+        _ => {}
+    }
+}
+
+/// Report failed `post_sync` hooks; the files they followed stay written.
+fn post_sync_failures(results: &[SyncCodebaseResult]) -> Result<()> {
+    let mut failed = 0;
+    for result in results {
+        if let Some(err) = &result.post_sync_error {
+            eprintln!("✗  '{}': {err}", result.codebase_name);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        bail!("post_sync hook failed for {failed} codebase(s); synced files were kept");
+    }
+    Ok(())
+}
+
+/// One codebase's part of a sync run, as [`render_sync_output`] sees it.
+enum SyncOutcome<'a> {
+    Synced(&'a SyncCodebaseResult),
+    /// The codebase failed; its error has already gone to stderr.
+    Failed(&'a str),
+}
+
+/// Per-outcome file counts for one or more sync results.
+#[derive(Default)]
+struct WriteCounts {
+    written: usize,
+    unchanged: usize,
+    ignored: usize,
+}
+
+impl WriteCounts {
+    fn of(writes: &[WriteResult]) -> Self {
+        let mut counts = Self::default();
+        counts.add(writes);
+        counts
+    }
 
-    println!(
-        "{prefix}✓ '{codebase_name}' synced ({} written, {} unchanged)",
-        written.len(),
-        unchanged.len()
+    fn add(&mut self, writes: &[WriteResult]) {
+        for r in writes {
+            match r {
+                WriteResult::Written { .. } | WriteResult::WouldWrite { .. } => self.written += 1,
+                WriteResult::Unchanged { .. } => self.unchanged += 1,
+                WriteResult::Ignored { .. } => self.ignored += 1,
+            }
+        }
+    }
+}
+
+/// Render a sync run: one colored header per codebase with its changed
+/// files indented below (unchanged ones too with `verbose`), then the
+/// totals line.
+fn render_sync_output(
+    outcomes: &[SyncOutcome<'_>],
+    elapsed: Duration,
+    dry_run: bool,
+    verbose: bool,
+) -> String {
+    let mut out = String::new();
+    for outcome in outcomes {
+        let result = match outcome {
+            SyncOutcome::Synced(result) => result,
+            SyncOutcome::Failed(name) => {
+                out.push_str(&format!(
+                    "{} {}  {}\n",
+                    "■".red().bold(),
+                    name.bold(),
+                    "failed".red()
+                ));
+                continue;
+            }
+        };
+        let counts = WriteCounts::of(&result.writes);
+        let (indicator, label) = if counts.written == 0 {
+            ("■".green().bold(), "unchanged".green())
+        } else if dry_run {
+            (
+                "■".cyan().bold(),
+                format!("dry-run: {} would change", counts.written).cyan(),
+            )
+        } else {
+            (
+                "■".yellow().bold(),
+                format!("{} written", plural(counts.written, "file")).yellow(),
+            )
+        };
+        out.push_str(&format!(
+            "{indicator} {}  {label}\n",
+            result.codebase_name.bold()
+        ));
+        for line in result.writes.iter().filter_map(|r| write_line(r, verbose)) {
+            out.push_str(&line);
+        }
+    }
+    out.push_str(&render_totals(outcomes, elapsed, dry_run));
+    out.push('\n');
+    out
+}
+
+/// The lines listed under a codebase header for one write, if any.
+fn write_line(write: &WriteResult, verbose: bool) -> Option<String> {
+    let line = match write {
+        WriteResult::Written { path, backed_up } => {
+            let mut line = format!("  ✎  {}\n", path.display());
+            if let Some(backup) = backed_up {
+                let name = path.file_name().unwrap_or(path.as_os_str());
+                line.push_str(&format!(
+                    "     overwrote locally-modified {} — previous version saved to {}\n",
+                    name.to_string_lossy(),
+                    backup.display()
+                ));
+            }
+            line
+        }
+        WriteResult::WouldWrite { path } => format!("  ~  {}\n", path.display()),
+        WriteResult::Ignored { path } => {
+            format!("  ⊘  {}  ignored (user-managed)\n", path.display())
+        }
+        WriteResult::Unchanged { path } if verbose => {
+            format!(
+                "  {}  {}\n",
+                "·".bright_black(),
+                path.display().to_string().bright_black()
+            )
+        }
+        WriteResult::Unchanged { .. } => return None,
+    };
+    Some(line)
+}
+
+/// The closing line of a sync run, and all `--quiet` prints, e.g.
+/// `3 codebases changed, 14 files written, 102 unchanged, 1.2s`.
+fn render_totals(outcomes: &[SyncOutcome<'_>], elapsed: Duration, dry_run: bool) -> String {
+    let mut counts = WriteCounts::default();
+    let mut changed = 0;
+    let mut failed = 0;
+    for outcome in outcomes {
+        match outcome {
+            SyncOutcome::Synced(result) => {
+                let before = counts.written;
+                counts.add(&result.writes);
+                if counts.written > before {
+                    changed += 1;
+                }
+            }
+            SyncOutcome::Failed(_) => failed += 1,
+        }
+    }
+
+    let mut line = if dry_run {
+        format!(
+            "dry-run: {} would change, {} would be written",
+            plural(changed, "codebase"),
+            plural(counts.written, "file")
+        )
+    } else {
+        format!(
+            "{} changed, {} written",
+            plural(changed, "codebase"),
+            plural(counts.written, "file")
+        )
+    };
+    line.push_str(&format!(", {} unchanged", counts.unchanged));
+    if counts.ignored > 0 {
+        line.push_str(&format!(", {} ignored", counts.ignored));
+    }
+    if failed > 0 {
+        line.push_str(&format!(", {failed} failed"));
+    }
+    line.push_str(&format!(", {:.1}s", elapsed.as_secs_f64()));
+    line.bold().to_string()
+}
+
+/// Print a sync run: the full grouped output, or only its totals line with
+/// `--quiet`.
+fn print_sync_output(
+    outcomes: &[SyncOutcome<'_>],
+    elapsed: Duration,
+    dry_run: bool,
+    out: OutputOptions,
+) {
+    if out.quiet() {
+        println!("{}", render_totals(outcomes, elapsed, dry_run));
+    } else {
+        print!(
+            "{}",
+            render_sync_output(outcomes, elapsed, dry_run, out.verbose())
+        );
+    }
+}
+
+/// The stderr warnings that follow a synced codebase's output.
+fn print_warnings(results: &[SyncCodebaseResult], out: OutputOptions) {
+    if out.quiet() {
+        return;
+    }
+    for result in results {
+        print_previous_locations(&result.previous_locations);
+        print_uncatalogued_skills(&result.uncatalogued_skills);
+        super::print_budget_overruns(&result.codebase_name, &result.budget_overruns);
+        print_unknown_assignees(&result.codebase_name, &result.unknown_assignees);
+    }
+}
+
+fn print_previous_locations(paths: &[PathBuf]) {
+    if paths.is_empty() {
+        return;
+    }
+    eprintln!("⚠  Generated files remain at a previous output location (not removed):");
+    for path in paths {
+        eprintln!("  ✗  {}", path.display());
+    }
+}
+
+fn print_uncatalogued_skills(ids: &[String]) {
+    if ids.is_empty() {
+        return;
+    }
+    eprintln!(
+        "⚠  Skills missing from the catalog (rendered as their id): {}",
+        ids.join(", ")
     );
+    eprintln!("   Add them with `orchestra skill add <id> --description <text>`.");
+}
 
-    for r in writes {
-        match r {
-            WriteResult::Written { path } => println!("  ✎  {}", path.display()),
-            WriteResult::WouldWrite { path } => println!("  ~  {}", path.display()),
-            WriteResult::Unchanged { path } => println!("  ·  {}", path.display()),
+fn print_unknown_assignees(codebase: &str, unknown: &[UnknownAssignee]) {
+    if unknown.is_empty() {
+        return;
+    }
+    eprintln!("⚠  Tasks in '{codebase}' assigned to an unknown agent (listed in no agent file):");
+    for task in unknown {
+        eprintln!("  ✗  {} → {}", task.task_id, task.assigned_agent);
+    }
+    eprintln!("   Reassign them with `orchestra task assign {codebase} <task> <agent>`.");
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, writes: Vec<WriteResult>) -> SyncCodebaseResult {
+        SyncCodebaseResult {
+            codebase_name: name.to_string(),
+            writes,
+            previous_locations: Vec::new(),
+            uncatalogued_skills: Vec::new(),
+            budget_overruns: Vec::new(),
+            unknown_assignees: Vec::new(),
+            post_sync_error: None,
         }
     }
+
+    fn written(path: &str) -> WriteResult {
+        WriteResult::Written {
+            path: PathBuf::from(path),
+            backed_up: None,
+        }
+    }
+
+    fn unchanged(path: &str) -> WriteResult {
+        WriteResult::Unchanged {
+            path: PathBuf::from(path),
+        }
+    }
+
+    fn render(outcomes: &[SyncOutcome<'_>], dry_run: bool, verbose: bool) -> String {
+        colored::control::set_override(false);
+        render_sync_output(outcomes, Duration::from_millis(1234), dry_run, verbose)
+    }
+
+    #[test]
+    fn written_and_unchanged_codebases_are_grouped() {
+        let api = result(
+            "api",
+            vec![
+                written("api/CLAUDE.md"),
+                WriteResult::Written {
+                    path: PathBuf::from("api/AGENTS.md"),
+                    backed_up: Some(PathBuf::from("backups/AGENTS.md.1")),
+                },
+                unchanged("api/GEMINI.md"),
+            ],
+        );
+        let web = result("web", vec![unchanged("web/CLAUDE.md")]);
+        let outcomes = [SyncOutcome::Synced(&api), SyncOutcome::Synced(&web)];
+
+        assert_eq!(
+            render(&outcomes, false, false),
+            "■ api  2 files written\n\
+             \x20 ✎  api/CLAUDE.md\n\
+             \x20 ✎  api/AGENTS.md\n\
+             \x20    overwrote locally-modified AGENTS.md — previous version saved to backups/AGENTS.md.1\n\
+             ■ web  unchanged\n\
+             1 codebase changed, 2 files written, 2 unchanged, 1.2s\n"
+        );
+    }
+
+    #[test]
+    fn verbose_lists_unchanged_files() {
+        let api = result(
+            "api",
+            vec![written("api/CLAUDE.md"), unchanged("api/GEMINI.md")],
+        );
+
+        assert_eq!(
+            render(&[SyncOutcome::Synced(&api)], false, true),
+            "■ api  1 file written\n\
+             \x20 ✎  api/CLAUDE.md\n\
+             \x20 ·  api/GEMINI.md\n\
+             1 codebase changed, 1 file written, 1 unchanged, 1.2s\n"
+        );
+    }
+
+    #[test]
+    fn dry_run_reports_what_would_change() {
+        let api = result(
+            "api",
+            vec![
+                WriteResult::WouldWrite {
+                    path: PathBuf::from("api/CLAUDE.md"),
+                },
+                WriteResult::Ignored {
+                    path: PathBuf::from("api/AGENTS.md"),
+                },
+            ],
+        );
+
+        assert_eq!(
+            render(&[SyncOutcome::Synced(&api)], true, false),
+            "■ api  dry-run: 1 would change\n\
+             \x20 ~  api/CLAUDE.md\n\
+             \x20 ⊘  api/AGENTS.md  ignored (user-managed)\n\
+             dry-run: 1 codebase would change, 1 file would be written, 0 unchanged, 1 ignored, 1.2s\n"
+        );
+    }
+
+    #[test]
+    fn failed_codebases_are_listed_and_counted() {
+        let api = result("api", vec![unchanged("api/CLAUDE.md")]);
+        let outcomes = [SyncOutcome::Synced(&api), SyncOutcome::Failed("web")];
+
+        assert_eq!(
+            render(&outcomes, false, false),
+            "■ api  unchanged\n\
+             ■ web  failed\n\
+             0 codebases changed, 0 files written, 1 unchanged, 1 failed, 1.2s\n"
+        );
+    }
+
+    #[test]
+    fn quiet_prints_only_the_totals() {
+        colored::control::set_override(false);
+        let api = result("api", vec![written("api/CLAUDE.md")]);
+
+        assert_eq!(
+            render_totals(&[SyncOutcome::Synced(&api)], Duration::from_secs(2), false),
+            "1 codebase changed, 1 file written, 0 unchanged, 2.0s"
+        );
+    }
 }
@@ -0,0 +1,360 @@
+//! `orchestra task import` — keep a codebase's tasks in step with an
+//! external tracker.
+//! `orchestra task assign` — route a task to one agent's files.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use clap::Subcommand;
+use serde::Deserialize;
+
+use orchestra_core::{
+    registry,
+    types::{Codebase, Task, TaskId, TaskStatus},
+};
+
+use orchestra_renderer::AgentKind;
+
+use super::agent::find_codebase;
+use crate::output::OutputOptions;
+
+/// Prefix of the ids given to tasks imported from GitHub issues.
+const GITHUB_ID_PREFIX: &str = "gh-";
+
+/// Manage a codebase's tasks.
+#[derive(Subcommand, Debug)]
+pub enum TaskCommand {
+    /// Import GitHub issues, e.g. from
+    /// `gh issue list --state all --json number,title,state,body,labels`.
+    Import {
+        /// Codebase the tasks belong to.
+        codebase: String,
+
+        /// JSON file holding an array of issues.
+        #[arg(long, value_name = "FILE")]
+        from_json: PathBuf,
+
+        /// Label that marks an open issue as in progress.
+        #[arg(long, value_name = "LABEL", default_value = "in progress")]
+        in_progress_label: String,
+
+        /// Label that marks an open issue as blocked.
+        #[arg(long, value_name = "LABEL", default_value = "blocked")]
+        blocked_label: String,
+
+        /// Remove previously imported `gh-*` tasks whose issue is not in FILE.
+        #[arg(long)]
+        prune: bool,
+    },
+    /// Assign a task to one agent, so only that agent's files list it.
+    Assign {
+        /// Codebase the task belongs to.
+        codebase: String,
+
+        /// Task id, e.g. `T-001` or `gh-42`.
+        task: String,
+
+        /// Agent the task is for, e.g. `claude`, `cursor`, `roo`.
+        #[arg(required_unless_present = "clear")]
+        agent: Option<String>,
+
+        /// Remove the assignment, so every agent's files list the task again.
+        #[arg(long, conflicts_with = "agent")]
+        clear: bool,
+    },
+}
+
+pub fn run(cmd: TaskCommand, out: OutputOptions) -> Result<()> {
+    let home = super::home_dir()?;
+    match cmd {
+        TaskCommand::Import {
+            codebase,
+            from_json,
+            in_progress_label,
+            blocked_label,
+            prune,
+        } => {
+            let labels = LabelRules {
+                in_progress: in_progress_label,
+                blocked: blocked_label,
+            };
+            import(&home, &codebase, &from_json, &labels, prune, out)
+        }
+        TaskCommand::Assign {
+            codebase,
+            task,
+            agent,
+            clear: _,
+        } => assign(&home, &codebase, &task, agent, out),
+    }
+}
+
+/// Set or, with `agent` unset, clear a task's `assigned_agent`. Agent names
+/// are stored in their lowercase form; any other name is kept as given,
+/// with a warning that no agent file will list the task.
+fn assign(
+    home: &Path,
+    codebase_name: &str,
+    task_id: &str,
+    agent: Option<String>,
+    out: OutputOptions,
+) -> Result<()> {
+    let assigned = match agent.as_deref().map(str::trim) {
+        Some("") => bail!("agent name must not be empty; use --clear to remove an assignment"),
+        Some(name) => Some(match name.parse::<AgentKind>() {
+            Ok(agent) => agent.name().to_string(),
+            Err(_) => {
+                eprintln!(
+                    "⚠  '{name}' is not an agent Orchestra renders; no agent file will list {task_id}."
+                );
+                name.to_string()
+            }
+        }),
+        None => None,
+    };
+
+    let (project, mut codebase) = find_codebase(home, codebase_name)?;
+    let task = codebase
+        .projects
+        .iter_mut()
+        .flat_map(|project| project.tasks.iter_mut())
+        .find(|task| task.id.0 == task_id)
+        .with_context(|| format!("task '{task_id}' not found in '{codebase_name}'"))?;
+    if task.assigned_agent == assigned {
+        match &assigned {
+            Some(agent) => println!("{task_id} is already assigned to {agent}."),
+            None => println!("{task_id} is not assigned to an agent."),
+        }
+        return Ok(());
+    }
+    let now = Utc::now();
+    task.assigned_agent = assigned.clone();
+    task.updated_at = now;
+    codebase.updated_at = now;
+
+    registry::save_codebase_at(home, &project, &codebase)
+        .with_context(|| format!("failed to save registry for '{}'", codebase.name))?;
+    match assigned {
+        Some(agent) => println!("Assigned {task_id} in '{}' to {agent}.", codebase.name),
+        None => println!(
+            "Cleared the assignment of {task_id} in '{}'; every agent's files list it.",
+            codebase.name
+        ),
+    }
+    if !out.quiet() {
+        println!(
+            "Run `orchestra sync {}` to update agent files.",
+            codebase.name
+        );
+    }
+    Ok(())
+}
+
+/// One issue as `gh issue list --json` or the GitHub REST API returns it.
+#[derive(Debug, Deserialize)]
+struct Issue {
+    number: u64,
+    title: String,
+    state: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    labels: Vec<IssueLabel>,
+}
+
+/// `gh` and the REST API give labels as objects; plain strings are accepted
+/// for hand-written files.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum IssueLabel {
+    Named { name: String },
+    Plain(String),
+}
+
+impl IssueLabel {
+    fn name(&self) -> &str {
+        match self {
+            IssueLabel::Named { name } | IssueLabel::Plain(name) => name,
+        }
+    }
+}
+
+/// Which labels move an open issue out of `pending`.
+struct LabelRules {
+    in_progress: String,
+    blocked: String,
+}
+
+impl Issue {
+    fn task_id(&self) -> TaskId {
+        TaskId(format!("{GITHUB_ID_PREFIX}{}", self.number))
+    }
+
+    /// Closed issues are done; an open one is blocked or in progress when it
+    /// carries that label (blocked wins) and pending otherwise.
+    fn status(&self, rules: &LabelRules) -> TaskStatus {
+        if self.state.eq_ignore_ascii_case("closed") {
+            return TaskStatus::Done;
+        }
+        let has_label = |wanted: &str| {
+            self.labels
+                .iter()
+                .any(|label| label.name().eq_ignore_ascii_case(wanted))
+        };
+        if has_label(&rules.blocked) {
+            TaskStatus::Blocked
+        } else if has_label(&rules.in_progress) {
+            TaskStatus::InProgress
+        } else {
+            TaskStatus::Pending
+        }
+    }
+
+    fn description(&self) -> Option<String> {
+        self.body
+            .as_deref()
+            .map(str::trim)
+            .filter(|body| !body.is_empty())
+            .map(str::to_string)
+    }
+}
+
+/// What an import did to the codebase's tasks.
+#[derive(Debug, Default)]
+struct ImportSummary {
+    added: usize,
+    updated: usize,
+    unchanged: usize,
+    pruned: usize,
+}
+
+impl ImportSummary {
+    fn changed(&self) -> bool {
+        self.added + self.updated + self.pruned > 0
+    }
+}
+
+fn import(
+    home: &Path,
+    codebase_name: &str,
+    file: &Path,
+    labels: &LabelRules,
+    prune: bool,
+    out: OutputOptions,
+) -> Result<()> {
+    let json = std::fs::read_to_string(file)
+        .with_context(|| format!("failed to read {}", file.display()))?;
+    let issues: Vec<Issue> = serde_json::from_str(&json)
+        .with_context(|| format!("{} is not a JSON array of issues", file.display()))?;
+    let mut seen = BTreeSet::new();
+    for issue in &issues {
+        if !seen.insert(issue.number) {
+            bail!(
+                "issue #{} appears more than once in {}",
+                issue.number,
+                file.display()
+            );
+        }
+    }
+
+    let (project, mut codebase) = find_codebase(home, codebase_name)?;
+    if codebase.projects.is_empty() {
+        bail!(
+            "codebase '{}' has no projects to attach tasks to",
+            codebase.name
+        );
+    }
+    let summary = apply_issues(&mut codebase, &issues, labels, prune, Utc::now());
+
+    if summary.changed() {
+        registry::save_codebase_at(home, &project, &codebase)
+            .with_context(|| format!("failed to save registry for '{}'", codebase.name))?;
+    }
+    println!(
+        "Imported {} issue(s) into '{}': {} added, {} updated, {} unchanged, {} pruned.",
+        issues.len(),
+        codebase.name,
+        summary.added,
+        summary.updated,
+        summary.unchanged,
+        summary.pruned
+    );
+    if summary.changed() && !out.quiet() {
+        println!(
+            "Run `orchestra sync {}` to update agent files.",
+            codebase.name
+        );
+    }
+    Ok(())
+}
+
+/// Merge `issues` into the codebase's tasks. An issue whose `gh-<number>`
+/// task already exists updates it in place, keeping its `created_at`,
+/// subtasks and notes; new ones are added to the first project.
+fn apply_issues(
+    codebase: &mut Codebase,
+    issues: &[Issue],
+    labels: &LabelRules,
+    prune: bool,
+    now: DateTime<Utc>,
+) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+    for issue in issues {
+        let id = issue.task_id();
+        let title = issue.title.trim().to_string();
+        let status = issue.status(labels);
+        let description = issue.description();
+
+        let existing = codebase
+            .projects
+            .iter_mut()
+            .flat_map(|project| project.tasks.iter_mut())
+            .find(|task| task.id == id);
+        match existing {
+            Some(task) => {
+                if task.title == title && task.status == status && task.description == description {
+                    summary.unchanged += 1;
+                    continue;
+                }
+                task.title = title;
+                task.status = status;
+                task.description = description;
+                task.updated_at = now;
+                summary.updated += 1;
+            }
+            None => {
+                codebase.projects[0].tasks.push(Task {
+                    id,
+                    title,
+                    status,
+                    description,
+                    subtasks: Vec::new(),
+                    notes: Vec::new(),
+                    assigned_agent: None,
+                    created_at: now,
+                    updated_at: now,
+                    extra: BTreeMap::new(),
+                });
+                summary.added += 1;
+            }
+        }
+    }
+
+    if prune {
+        let imported: HashSet<TaskId> = issues.iter().map(Issue::task_id).collect();
+        for project in &mut codebase.projects {
+            let before = project.tasks.len();
+            project.tasks.retain(|task| {
+                !task.id.0.starts_with(GITHUB_ID_PREFIX) || imported.contains(&task.id)
+            });
+            summary.pruned += before - project.tasks.len();
+        }
+    }
+
+    if summary.changed() {
+        codebase.updated_at = now;
+    }
+    summary
+}
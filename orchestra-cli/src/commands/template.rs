@@ -0,0 +1,116 @@
+//! `orchestra template check|list` — validate and inspect the templates sync
+//! renders, including user overrides in `~/.orchestra/templates/`.
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use tabled::{settings::Style, Table, Tabled};
+
+use orchestra_renderer::{
+    check_templates, user_template_dir_at, TemplateContext, TemplateEngine, TemplateOrigin,
+};
+
+use super::agent::find_codebase;
+use crate::output::OutputOptions;
+
+/// Validate and inspect agent file templates.
+#[derive(Subcommand, Debug)]
+pub enum TemplateCommand {
+    /// Parse and render every template without writing any files.
+    Check {
+        /// Render against this codebase's registry data instead of a
+        /// built-in example.
+        #[arg(long, value_name = "NAME")]
+        codebase: Option<String>,
+    },
+
+    /// List the templates in use and whether each is embedded or overridden.
+    List,
+}
+
+#[derive(Tabled)]
+struct TemplateTableRow {
+    #[tabled(rename = "template")]
+    name: String,
+    #[tabled(rename = "source")]
+    source: String,
+}
+
+pub fn run(cmd: TemplateCommand, out: OutputOptions) -> Result<()> {
+    let home = super::home_dir()?;
+    match cmd {
+        TemplateCommand::Check { codebase } => check(&home, codebase.as_deref(), out),
+        TemplateCommand::List => list(&home),
+    }
+}
+
+fn origin_label(origin: &TemplateOrigin) -> String {
+    match origin {
+        TemplateOrigin::Embedded => "embedded".to_string(),
+        TemplateOrigin::Override(path) => format!("override ({})", path.display()),
+    }
+}
+
+fn check(home: &Path, codebase_name: Option<&str>, out: OutputOptions) -> Result<()> {
+    let ctx = match codebase_name {
+        Some(name) => {
+            let (_, codebase) = find_codebase(home, name)?;
+            TemplateContext::from_codebase(&codebase)
+        }
+        None => TemplateContext::example(),
+    };
+
+    let checks = check_templates(Some(&user_template_dir_at(home)), &ctx)
+        .context("failed to load templates")?;
+    let mut failed = 0;
+    for check in &checks {
+        match &check.error {
+            None if out.quiet() => {}
+            None => println!("  ✓ {}", check.name),
+            Some(error) => {
+                failed += 1;
+                println!("  ✗ {} [{}]", check.name, origin_label(&check.origin));
+                for line in error.lines() {
+                    println!("      {line}");
+                }
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{failed} of {} templates failed to render", checks.len());
+    }
+    println!("✓ All {} templates rendered", checks.len());
+    Ok(())
+}
+
+fn list(home: &Path) -> Result<()> {
+    let engine = TemplateEngine::new(Some(&user_template_dir_at(home))).context(
+        "failed to load templates — run `orchestra template check` to find the broken one",
+    )?;
+    let mut rows: Vec<TemplateTableRow> = engine
+        .origins()
+        .iter()
+        .map(|(name, origin)| TemplateTableRow {
+            name: name.clone(),
+            source: origin_label(origin),
+        })
+        .collect();
+    rows.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let overridden = engine
+        .origins()
+        .values()
+        .filter(|origin| matches!(origin, TemplateOrigin::Override(_)))
+        .count();
+    let count = rows.len();
+    let mut table = Table::new(rows);
+    table.with(Style::rounded());
+
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "Templates ({count}, {overridden} overridden)")
+        .and_then(|()| writeln!(stdout, "{table}"))
+        .context("failed to write the template list")
+}
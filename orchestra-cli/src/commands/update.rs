@@ -11,8 +11,11 @@ use std::process::Command;
 use anyhow::{bail, Context, Result};
 use clap::Args;
 use colored::Colorize;
+use orchestra_core::paths;
 use serde::Deserialize;
 
+use crate::output::OutputOptions;
+
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const REPO: &str = "Chris-Miracle/orch";
 
@@ -68,9 +71,9 @@ struct Asset {
 // Entry point
 // ---------------------------------------------------------------------------
 
-pub fn run(args: UpdateArgs) -> Result<()> {
-    let home = dirs::home_dir().context("could not determine home directory")?;
-    let channel_file = home.join(".orchestra").join("channel");
+pub fn run(args: UpdateArgs, out: OutputOptions) -> Result<()> {
+    let home = super::home_dir()?;
+    let channel_file = paths::dirs_at(&home).data_root.join("channel");
 
     // 1. Resolve effective channel
     let channel: String = if args.stable {
@@ -96,8 +99,10 @@ pub fn run(args: UpdateArgs) -> Result<()> {
         .map(str::to_string)
         .unwrap_or_else(|| format!("v{CURRENT_VERSION}"));
 
-    println!("\n  channel     {}", channel.bold());
-    println!("  installed   {}", installed_tag.dimmed());
+    if !out.quiet() {
+        println!("\n  channel     {}", channel.bold());
+        println!("  installed   {}", installed_tag.dimmed());
+    }
 
     // 4. Fetch latest release for channel
     let release =
@@ -115,12 +120,14 @@ pub fn run(args: UpdateArgs) -> Result<()> {
         return Ok(());
     }
 
-    println!(
-        "\n  {} Update available: {} → {}\n",
-        "→".cyan().bold(),
-        installed_tag.dimmed(),
-        latest_tag.green().bold()
-    );
+    if !out.quiet() {
+        println!(
+            "\n  {} Update available: {} → {}\n",
+            "→".cyan().bold(),
+            installed_tag.dimmed(),
+            latest_tag.green().bold()
+        );
+    }
 
     // 6. Find downloadable asset for this architecture
     let asset = release
@@ -141,12 +148,14 @@ pub fn run(args: UpdateArgs) -> Result<()> {
         .context("could not resolve executable path")?;
 
     // 8. Download, extract, and replace
-    println!("  Downloading {}...", asset.name);
+    if !out.quiet() {
+        println!("  Downloading {}...", asset.name);
+    }
     download_and_install(&asset.browser_download_url, &install_path)
         .context("update failed")?;
 
     // 9. Persist updated channel tag (so next `orchestra update` knows what's installed)
-    let tag_file = home.join(".orchestra").join("installed_tag");
+    let tag_file = paths::dirs_at(&home).data_root.join("installed_tag");
     let _ = fs::write(&tag_file, latest_tag);
 
     println!(
@@ -175,8 +184,9 @@ fn read_channel_from_disk(channel_file: &Path) -> String {
 
 fn save_channel(channel_file: &Path, channel: &str) -> Result<()> {
     let dir = channel_file.parent().expect("channel file has parent dir");
-    fs::create_dir_all(dir).context("could not create ~/.orchestra directory")?;
-    fs::write(channel_file, channel).context("could not write ~/.orchestra/channel")?;
+    fs::create_dir_all(dir).with_context(|| format!("could not create {}", dir.display()))?;
+    fs::write(channel_file, channel)
+        .with_context(|| format!("could not write {}", channel_file.display()))?;
     Ok(())
 }
 
@@ -0,0 +1,145 @@
+//! `orchestra upgrade-templates [--apply] [--codebase <name>|--all]` — show,
+//! then write, the changes a new template set makes to generated files.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+
+use orchestra_sync::{
+    pipeline::{self, SyncScope},
+    upgrade::{self, TemplateUpgrade},
+    WriteResult,
+};
+
+use super::plural;
+use crate::output::OutputOptions;
+
+/// Arguments for `orchestra upgrade-templates`.
+#[derive(Args, Debug)]
+pub struct UpgradeTemplatesArgs {
+    /// Write the template changes instead of showing them.
+    #[arg(long)]
+    pub apply: bool,
+
+    /// Only this codebase.
+    #[arg(long, value_name = "NAME")]
+    pub codebase: Option<String>,
+
+    /// Every registered codebase (the default).
+    #[arg(long, conflicts_with = "codebase")]
+    pub all: bool,
+}
+
+impl UpgradeTemplatesArgs {
+    /// `--quiet` leaves out the diffs and written paths.
+    pub fn run(self, out: OutputOptions) -> Result<()> {
+        let home = super::home_dir()?;
+        let plans = match self.codebase.as_deref() {
+            Some(name) => vec![upgrade::plan_codebase(name, &home)
+                .with_context(|| format!("failed to check templates for '{name}'"))?],
+            None => upgrade::plan_all(&home).context("failed to check templates")?,
+        };
+        if plans.is_empty() {
+            println!("No codebases registered. Run `orchestra init` first.");
+            return Ok(());
+        }
+
+        if self.apply {
+            apply(&home, &plans, out)
+        } else {
+            show(&plans, out);
+            Ok(())
+        }
+    }
+}
+
+fn show(plans: &[TemplateUpgrade], out: OutputOptions) {
+    for plan in plans {
+        if plan.templates_unchanged {
+            println!(
+                "'{}': templates unchanged since last sync.",
+                plan.codebase_name
+            );
+            continue;
+        }
+        if plan.changes.is_empty() {
+            println!("'{}': no template changes.", plan.codebase_name);
+            continue;
+        }
+        let added: usize = plan.changes.iter().map(|c| c.diff.added).sum();
+        let removed: usize = plan.changes.iter().map(|c| c.diff.removed).sum();
+        println!(
+            "=== {} — {}, +{added}/-{removed} lines",
+            plan.codebase_name,
+            plural(plan.changes.len(), "file")
+        );
+        if !out.quiet() {
+            for change in &plan.changes {
+                print!("{}", change.diff.unified_diff);
+                if !change.diff.unified_diff.ends_with('\n') {
+                    println!();
+                }
+            }
+        }
+        println!("Affected: {}", affected(plan));
+    }
+
+    let changed = plans.iter().filter(|plan| !plan.changes.is_empty()).count();
+    if changed > 0 {
+        println!(
+            "{changed} of {} {} template changes. Run `orchestra upgrade-templates --apply` to write them.",
+            plural(plans.len(), "codebase"),
+            if changed == 1 { "has" } else { "have" }
+        );
+    }
+}
+
+/// The affected agents, then the shared files, comma-separated.
+fn affected(plan: &TemplateUpgrade) -> String {
+    let mut names: Vec<String> = plan.agents().iter().map(|a| a.to_string()).collect();
+    if plan.shared_changed() {
+        names.push("shared files".to_string());
+    }
+    names.join(", ")
+}
+
+fn apply(home: &Path, plans: &[TemplateUpgrade], out: OutputOptions) -> Result<()> {
+    let mut failed = 0;
+    for plan in plans {
+        let name = &plan.codebase_name;
+        if plan.templates_unchanged {
+            println!("'{name}': templates unchanged since last sync.");
+            continue;
+        }
+        let outcomes =
+            pipeline::run_template_upgrade(home, SyncScope::Codebase(name.clone()), |_| {})
+                .context("template upgrade failed")?;
+        for outcome in outcomes {
+            match outcome.result {
+                Ok(result) => {
+                    let written: Vec<&Path> = result
+                        .writes
+                        .iter()
+                        .filter(|write| matches!(write, WriteResult::Written { .. }))
+                        .map(WriteResult::path)
+                        .collect();
+                    println!("'{name}': updated {}.", plural(written.len(), "file"));
+                    if !out.quiet() {
+                        for path in written {
+                            println!("  ✎  {}", path.display());
+                        }
+                    }
+                }
+                Err(err) => {
+                    eprintln!("✗  '{name}': {err}");
+                    failed += 1;
+                }
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("template upgrade failed for {failed} codebase(s)");
+    }
+    Ok(())
+}
@@ -0,0 +1,75 @@
+//! `orchestra which <path> [--json]` — whether a file is generated by
+//! Orchestra, for which codebase and agent, and how current it is.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use serde_json::json;
+
+use orchestra_sync::{
+    find_managed_at, layout,
+    staleness::format_datetime_age,
+    status::{signal_detail, SignalKind},
+};
+
+/// Arguments for `orchestra which`.
+#[derive(Args, Debug)]
+pub struct WhichArgs {
+    /// File to look up; need not exist yet.
+    pub path: PathBuf,
+
+    /// Print the result as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl WhichArgs {
+    pub fn run(self) -> Result<()> {
+        let home = super::home_dir()?;
+        let Some(found) =
+            find_managed_at(&home, &self.path).context("failed to look up managed files")?
+        else {
+            bail!(
+                "'{}' is not generated by Orchestra for any registered codebase",
+                self.path.display()
+            );
+        };
+
+        let codebase = &found.codebase.name.0;
+        let agent = found.agent.map(|agent| agent.name());
+        let status = SignalKind::of(&found.signal).key();
+        if self.json {
+            let report = json!({
+                "path": found.path,
+                "relative_path": layout::display_relative(&found.path, &found.codebase),
+                "project": found.project.0,
+                "codebase": codebase,
+                "agent": agent,
+                "status": status,
+                "detail": signal_detail(&found.signal),
+                "last_synced": found.last_synced,
+            });
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).context("failed to serialize which JSON")?
+            );
+            return Ok(());
+        }
+
+        println!("{} is generated by Orchestra", found.path.display());
+        println!("  Codebase:  {codebase} (project '{}')", found.project.0);
+        println!("  Agent:     {}", agent.unwrap_or("none (shared file)"));
+        println!("  Status:    {status} — {}", signal_detail(&found.signal));
+        match found.last_synced {
+            Some(at) => println!(
+                "  Last sync: {} ({} ago)",
+                at.format("%Y-%m-%d %H:%M:%S UTC"),
+                format_datetime_age(at)
+            ),
+            None => println!("  Last sync: never"),
+        }
+        println!("Edits are overwritten; change the registry and run `orchestra sync {codebase}`.");
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+//! Cloning a repository for `orchestra init --from-git`.
+//!
+//! Runs `git clone` through the binary named by `ORCHESTRA_GIT` (default
+//! `git`), so tests can substitute a fake.
+
+use std::ffi::OsString;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+/// Environment variable naming the git executable to run.
+pub const GIT_ENV: &str = "ORCHESTRA_GIT";
+
+/// The repository name in a clone URL: `repo` for
+/// `git@github.com:org/repo.git`, `https://github.com/org/repo` and local
+/// paths such as `/srv/git/repo.git/`.
+pub fn repo_name(url: &str) -> Option<String> {
+    let trimmed = url.trim().trim_end_matches(['/', '\\']);
+    let last = trimmed.rsplit(['/', '\\', ':']).next()?;
+    let name = last.strip_suffix(".git").unwrap_or(last);
+    (!name.is_empty() && name != "." && name != "..").then(|| name.to_string())
+}
+
+/// Clone `url` into `dest`, which must not exist yet. A failed clone is
+/// reported with git's own error output.
+pub fn clone(url: &str, dest: &Path) -> Result<()> {
+    let git = std::env::var_os(GIT_ENV).unwrap_or_else(|| OsString::from("git"));
+    let output = Command::new(&git)
+        .arg("clone")
+        .arg("--")
+        .arg(url)
+        .arg(dest)
+        .output()
+        .with_context(|| format!("failed to run '{}'", git.to_string_lossy()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "git clone of '{url}' failed ({}):\n{}",
+            output.status,
+            stderr.trim_end()
+        );
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repo_name_handles_ssh_https_and_local_urls() {
+        for (url, expected) in [
+            ("git@github.com:org/repo.git", Some("repo")),
+            ("git@github.com:repo", Some("repo")),
+            ("ssh://git@github.com/org/repo.git", Some("repo")),
+            ("https://github.com/org/repo.git", Some("repo")),
+            ("https://github.com/org/repo", Some("repo")),
+            ("https://github.com/org/repo/", Some("repo")),
+            ("https://gitlab.com/group/sub/my.repo.git", Some("my.repo")),
+            ("/srv/git/repo.git/", Some("repo")),
+            ("C:\\git\\repo.git", Some("repo")),
+            (".git", None),
+            ("", None),
+        ] {
+            assert_eq!(repo_name(url).as_deref(), expected, "{url}");
+        }
+    }
+}
@@ -3,50 +3,133 @@
 //! # Usage
 //!
 //! ```text
-//! orchestra init <path> --project <name> [--type backend|frontend|mobile|ml] [--detect]
+//! orchestra init <path> --project <name> [--type backend|frontend|mobile|ml|infra] [--detect]
+//! orchestra init --from-git <url> --project <name> [--dest <dir>] [--no-sync]
+//! orchestra detect <path> [--json] [--save <codebase>]
 //! orchestra project list
 //! orchestra project add <name> [--type ...]
-//! orchestra sync <codebase> [--dry-run]
-//! orchestra sync --all [--dry-run]
+//! orchestra project remove <name> [--cascade [--purge-files]] [--yes]
+//! orchestra agent add <codebase> <agent-id> [--entry-point <path>] [--skill <s>]...
+//! orchestra agent list|remove <codebase> ...
+//! orchestra command set <codebase> <name> <command>
+//! orchestra command list|unset <codebase> ...
+//! orchestra convention set-agent <codebase> <agent> --file <bullets.txt>
+//! orchestra convention reset-agent <codebase> <agent>
+//! orchestra hook set <codebase> pre-sync|post-sync <command> [--allow-in-daemon]
+//! orchestra hook list|unset <codebase> ...
+//! orchestra output add <codebase> <template> <output-path>
+//! orchestra output list|remove <codebase> ...
+//! orchestra global edit|show
+//! orchestra skill list
+//! orchestra skill add <id> --description <text> [--title <title>]
+//! orchestra task import <codebase> --from-json <file> [--prune]
+//! orchestra task assign <codebase> <task> <agent>|--clear
+//! orchestra sync <codebase> [--dry-run] [--no-backup]
+//! orchestra sync --all [--dry-run] [--no-backup]
+//! orchestra sync <codebase> --watch [--force]
+//! orchestra restore <codebase> [<file>] [--list] [--at <stamp>]
 //! orchestra status [--project <name>] [--json]
+//! orchestra history <codebase> [--since 7d]
 //! orchestra diff <codebase>
-//! orchestra daemon start|stop|status|install|uninstall|logs
+//! orchestra diff --all|--project <name>
+//! orchestra which <path> [--json]
+//! orchestra template check [--codebase <name>]
+//! orchestra template list
+//! orchestra upgrade-templates [--apply] [--codebase <name>|--all]
+//! orchestra export [--output <file>|-] [--include-hashes]
+//! orchestra import <file> [--remap-path OLD=NEW] [--merge|--replace]
+//! orchestra daemon start|stop|restart|status|install|uninstall|logs
+//! orchestra serve --stdio
+//! orchestra help [<topic> | <command>...]
+//! orchestra mangen <dir>
 //! ```
+//!
+//! Every command accepts `--home <dir>` to use a data directory other than
+//! `~/.orchestra`; see [`orchestra_core::paths`] for the full resolution order.
+//! `-q/--quiet` and `-v/--verbose` are global too; see [`output`].
 
 mod commands;
+mod gitclone;
+mod output;
 
 use std::fmt;
+use std::io;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 
 use commands::{
-    daemon::DaemonCommand, doctor::DoctorArgs, diff::DiffArgs, init::InitArgs,
-    offboard::OffboardArgs, onboard::OnboardArgs, project::ProjectCommand, reset::ResetArgs,
-    status::StatusArgs, sync::SyncArgs, update::UpdateArgs,
+    agent::AgentCommand, command::CommandCommand, convention::ConventionCommand,
+    daemon::DaemonCommand, detect::DetectArgs,
+    doctor::DoctorArgs,
+    diff::DiffArgs, export::ExportArgs, global::GlobalCommand, help::HelpArgs,
+    history::HistoryArgs,
+    hook::HookCommand,
+    import::ImportArgs, init::InitArgs, mangen::MangenArgs,
+    offboard::OffboardArgs, onboard::OnboardArgs, output::OutputCommand, project::ProjectCommand,
+    reset::ResetArgs,
+    restore::RestoreArgs, serve::ServeArgs, setup::SetupArgs, skill::SkillCommand, status::StatusArgs, sync::SyncArgs,
+    task::TaskCommand, template::TemplateCommand, update::UpdateArgs,
+    upgrade_templates::UpgradeTemplatesArgs, which::WhichArgs,
 };
 use orchestra_core::types::ProjectType;
+use output::OutputOptions;
 
 const ALL_COMMANDS_HELP: &str = "
 All available commands:
     init
+    detect
     project list
     project add
+    project remove
+    agent add
+    agent list
+    agent remove
+    command set
+    command list
+    command unset
+    convention set-agent
+    convention reset-agent
+    hook set
+    hook list
+    hook unset
+    output add
+    output list
+    output remove
+    global edit
+    global show
+    skill list
+    skill add
+    task import
+    task assign
     sync
     onboard
     offboard
     status
+    history
     diff
+    which
+    restore
+    template check
+    template list
+    upgrade-templates
+    export
+    import
     doctor
     daemon start
     daemon stop
+    daemon restart
     daemon status
     daemon install
     daemon uninstall
     daemon logs
+    serve
     update
     reset
+    help
+    mangen
 ";
 
 // ---------------------------------------------------------------------------
@@ -60,8 +143,22 @@ All available commands:
     about = "Manage AI coding agent files across multiple codebases",
     long_about = None,
     after_help = ALL_COMMANDS_HELP,
+    disable_help_subcommand = true,
 )]
 struct Cli {
+    /// Keep Orchestra's registry, hashes, and logs in this directory instead
+    /// of `~/.orchestra` (`$ORCHESTRA_HOME` takes precedence).
+    #[arg(long, global = true, value_name = "DIR")]
+    home: Option<PathBuf>,
+
+    /// Print only errors and a one-line summary.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Print more detail, including debug logs on stderr.
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -71,12 +168,66 @@ enum Commands {
     /// Initialize a codebase in the Orchestra registry.
     Init(InitArgs),
 
+    /// Register a codebase, sync it, and show what to do next.
+    Setup(SetupArgs),
+
+    /// Detect a directory's language, framework and project type.
+    Detect(DetectArgs),
+
     /// Manage projects within the active registry.
     Project {
         #[command(subcommand)]
         command: ProjectCommand,
     },
 
+    /// Manage subagents registered for a codebase.
+    Agent {
+        #[command(subcommand)]
+        command: AgentCommand,
+    },
+
+    /// Manage the build, test, and lint commands listed in agent files.
+    Command {
+        #[command(subcommand)]
+        command: CommandCommand,
+    },
+
+    /// Replace the built-in convention bullets rendered for one agent.
+    Convention {
+        #[command(subcommand)]
+        command: ConventionCommand,
+    },
+
+    /// Manage commands run before and after a codebase is synced.
+    Hook {
+        #[command(subcommand)]
+        command: HookCommand,
+    },
+
+    /// Manage extra files rendered from user templates for a codebase.
+    Output {
+        #[command(subcommand)]
+        command: OutputCommand,
+    },
+
+    /// Manage instructions rendered into every codebase.
+    Global {
+        #[command(subcommand)]
+        command: GlobalCommand,
+    },
+
+    /// Manage the shared skills catalog.
+    Skill {
+        #[command(subcommand)]
+        command: SkillCommand,
+    },
+
+    /// Import or assign a codebase's tasks.
+    Task {
+        #[command(subcommand)]
+        command: TaskCommand,
+    },
+
     /// Render and write per-agent instruction files for a codebase.
     Sync(SyncArgs),
 
@@ -89,15 +240,42 @@ enum Commands {
     /// Show staleness status across registered codebases.
     Status(StatusArgs),
 
-    /// Show unified diff of what sync would write for a codebase.
+    /// Show past sync runs for a codebase from the sync history journal.
+    History(HistoryArgs),
+
+    /// Show unified diff of what sync would write for one or more codebases.
     Diff(DiffArgs),
 
-    /// Manage Orchestra background daemon and launchd integration.
+    /// Show whether a file is generated by Orchestra, and for which codebase.
+    Which(WhichArgs),
+
+    /// List or restore local edits that a sync overwrote.
+    Restore(RestoreArgs),
+
+    /// Validate templates (including overrides) or list where each comes from.
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommand,
+    },
+
+    /// Show or apply the changes new templates make to generated files.
+    UpgradeTemplates(UpgradeTemplatesArgs),
+
+    /// Bundle the registry into one YAML document for another machine.
+    Export(ExportArgs),
+
+    /// Load a registry bundle written by `orchestra export`.
+    Import(ImportArgs),
+
+    /// Manage Orchestra background daemon and launchd/systemd integration.
     Daemon {
         #[command(subcommand)]
         command: DaemonCommand,
     },
 
+    /// Answer daemon protocol requests over stdio (for editor integrations).
+    Serve(ServeArgs),
+
     /// Run broader system and registry health checks.
     Doctor(DoctorArgs),
 
@@ -106,6 +284,12 @@ enum Commands {
 
     /// Wipe Orchestra and all its managed files (requires --confirm).
     Reset(ResetArgs),
+
+    /// Show help for a topic (signals, layout, daemon, ...) or a command.
+    Help(HelpArgs),
+
+    /// Write man pages for every command to a directory.
+    Mangen(MangenArgs),
 }
 
 // ---------------------------------------------------------------------------
@@ -125,8 +309,9 @@ impl FromStr for ProjectTypeArg {
             "frontend" => Ok(Self(ProjectType::Frontend)),
             "mobile" => Ok(Self(ProjectType::Mobile)),
             "ml" => Ok(Self(ProjectType::Ml)),
+            "infra" => Ok(Self(ProjectType::Infra)),
             other => Err(format!(
-                "unknown project type '{other}'; expected: backend, frontend, mobile, ml"
+                "unknown project type '{other}'; expected: backend, frontend, mobile, ml, infra"
             )),
         }
     }
@@ -150,17 +335,53 @@ impl From<ProjectTypeArg> for ProjectType {
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    match cli.command {
-        Commands::Init(args) => args.run(),
-        Commands::Project { command } => commands::project::run(command),
-        Commands::Sync(args) => args.run(),
-        Commands::Onboard(args) => args.run(),
-        Commands::Offboard(args) => args.run(),
-        Commands::Status(args) => args.run(),
-        Commands::Diff(args) => args.run(),
+    commands::install_dirs(cli.home.as_deref());
+    let out = OutputOptions::from_flags(cli.quiet, cli.verbose);
+    out.init_logging();
+    let result = match cli.command {
+        Commands::Init(args) => args.run(out),
+        Commands::Setup(args) => args.run(out),
+        Commands::Detect(args) => args.run(),
+        Commands::Project { command } => commands::project::run(command, out),
+        Commands::Agent { command } => commands::agent::run(command, out),
+        Commands::Command { command } => commands::command::run(command, out),
+        Commands::Convention { command } => commands::convention::run(command),
+        Commands::Hook { command } => commands::hook::run(command, out),
+        Commands::Output { command } => commands::output::run(command, out),
+        Commands::Global { command } => commands::global::run(command, out),
+        Commands::Skill { command } => commands::skill::run(command, out),
+        Commands::Task { command } => commands::task::run(command, out),
+        Commands::Sync(args) => args.run(out),
+        Commands::Onboard(args) => args.run(out),
+        Commands::Offboard(args) => args.run(out),
+        Commands::Status(args) => args.run(out),
+        Commands::History(args) => args.run(),
+        Commands::Diff(args) => args.run(out),
+        Commands::Which(args) => args.run(),
+        Commands::Restore(args) => args.run(out),
+        Commands::Template { command } => commands::template::run(command, out),
+        Commands::UpgradeTemplates(args) => args.run(out),
+        Commands::Export(args) => args.run(),
+        Commands::Import(args) => args.run(out),
         Commands::Daemon { command } => commands::daemon::run(command),
-        Commands::Doctor(args) => args.run(),
-        Commands::Update(args) => commands::update::run(args),
-        Commands::Reset(args) => args.run(),
+        Commands::Serve(args) => args.run(),
+        Commands::Doctor(args) => args.run(out),
+        Commands::Update(args) => commands::update::run(args, out),
+        Commands::Reset(args) => args.run(out),
+        Commands::Help(args) => args.run(),
+        Commands::Mangen(args) => args.run(out),
+    };
+    // Piped into `head`, the reader may go away early; that is not an error.
+    match result {
+        Err(err) if is_broken_pipe(&err) => Ok(()),
+        result => result,
     }
 }
+
+fn is_broken_pipe(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|err| err.kind() == io::ErrorKind::BrokenPipe)
+    })
+}
@@ -0,0 +1,61 @@
+//! How much the CLI prints, chosen once by the global `-q`/`-v` flags.
+//!
+//! Data a command was asked for (tables, JSON, diffs) always goes to stdout.
+//! Progress lines are dropped by `--quiet`; warnings and errors go to stderr.
+
+/// Output level selected on the command line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Errors plus a one-line summary per command.
+    Quiet,
+    /// The usual human-readable output.
+    #[default]
+    Normal,
+    /// Normal output plus debug logs from the libraries on stderr.
+    Verbose,
+}
+
+/// Output settings passed to every command that prints progress.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutputOptions {
+    pub verbosity: Verbosity,
+}
+
+impl OutputOptions {
+    /// Options for the `--quiet` and `--verbose` flags (clap keeps them exclusive).
+    pub fn from_flags(quiet: bool, verbose: bool) -> Self {
+        let verbosity = if quiet {
+            Verbosity::Quiet
+        } else if verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        };
+        Self { verbosity }
+    }
+
+    pub fn quiet(&self) -> bool {
+        self.verbosity == Verbosity::Quiet
+    }
+
+    pub fn verbose(&self) -> bool {
+        self.verbosity == Verbosity::Verbose
+    }
+
+    /// With `--verbose`, send Orchestra's `debug` logs to stderr so they never
+    /// mix with data on stdout. `RUST_LOG` overrides the filter.
+    pub fn init_logging(&self) {
+        use tracing_subscriber::{fmt, EnvFilter};
+
+        if !self.verbose() {
+            return;
+        }
+        let filter = EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| EnvFilter::new("warn,orchestra=debug"));
+        let _ = fmt()
+            .with_env_filter(filter)
+            .with_target(false)
+            .with_writer(std::io::stderr)
+            .try_init();
+    }
+}
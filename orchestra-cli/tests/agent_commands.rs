@@ -0,0 +1,166 @@
+mod common;
+
+use std::fs;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{CodebaseName, ProjectName},
+};
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+fn registered_agent_ids(home: &TempDir) -> Vec<String> {
+    let codebase = registry::load_codebase_at(
+        home.path(),
+        &ProjectName::from("copnow"),
+        &CodebaseName::from("copnow_api"),
+    )
+    .expect("load codebase");
+    codebase
+        .projects
+        .iter()
+        .flat_map(|project| project.agents.iter())
+        .map(|agent| agent.agent_id.clone())
+        .collect()
+}
+
+#[test]
+fn agent_add_registers_agent_and_writes_entry_point() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["agent", "add", "copnow_api", "migrations", "--skill", "sql"])
+        .assert()
+        .success()
+        .stdout(contains("Added subagent 'migrations'"));
+
+    assert_eq!(registered_agent_ids(&home), ["migrations"]);
+    let yaml = fs::read_to_string(registry::codebase_path_at(
+        home.path(),
+        &ProjectName::from("copnow"),
+        &CodebaseName::from("copnow_api"),
+    ))
+    .expect("read registry yaml");
+    assert!(yaml.contains("agent_id: migrations"), "{yaml}");
+    assert!(yaml.contains("entry_point: AGENT/migrations.md"), "{yaml}");
+
+    let entry = fs::read_to_string(codebase_dir.join("AGENT/migrations.md")).expect("entry point");
+    assert!(entry.contains("# migrations"));
+    assert!(entry.contains("Subagent for `copnow_api`"));
+    assert!(entry.contains("## Stack"));
+    assert!(entry.contains("- sql"));
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    let claude_md = fs::read_to_string(codebase_dir.join("orchestra/controls/CLAUDE.md"))
+        .expect("CLAUDE.md");
+    let delegation = claude_md
+        .split("## Subagent Delegation")
+        .nth(1)
+        .expect("subagent section");
+    assert!(
+        delegation.contains("- **migrations** — `AGENT/migrations.md` (skills: sql)"),
+        "{delegation}"
+    );
+
+    orchestra_cmd(home.path())
+        .args(["agent", "list", "copnow_api"])
+        .assert()
+        .success()
+        .stdout(contains("migrations  AGENT/migrations.md  [sql]"));
+}
+
+#[test]
+fn agent_add_rejects_duplicate_ids() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["agent", "add", "copnow_api", "migrations"])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["agent", "add", "copnow_api", "migrations", "--entry-point", "docs/other.md"])
+        .assert()
+        .failure()
+        .stderr(contains("already registered"));
+
+    assert_eq!(registered_agent_ids(&home), ["migrations"]);
+}
+
+#[test]
+fn agent_add_rejects_an_entry_point_outside_the_codebase() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    let outside = workspace.path().join("outside.md");
+
+    for entry_point in [outside.to_str().unwrap(), "../outside.md"] {
+        orchestra_cmd(home.path())
+            .args(["agent", "add", "copnow_api", "escaper", "--entry-point"])
+            .arg(entry_point)
+            .assert()
+            .failure()
+            .stderr(contains("must be inside the codebase"));
+    }
+
+    assert!(!outside.exists());
+    assert!(registered_agent_ids(&home).is_empty());
+}
+
+#[test]
+fn agent_add_keeps_an_existing_entry_point_file() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+    let entry = codebase_dir.join("docs/reviewer.md");
+    fs::create_dir_all(entry.parent().unwrap()).unwrap();
+    fs::write(&entry, "hand written\n").unwrap();
+
+    orchestra_cmd(home.path())
+        .args(["agent", "add", "copnow_api", "reviewer", "--entry-point", "docs/reviewer.md"])
+        .assert()
+        .success()
+        .stdout(contains("kept existing entry point"));
+
+    assert_eq!(fs::read_to_string(&entry).unwrap(), "hand written\n");
+}
+
+#[test]
+fn agent_remove_deregisters_but_keeps_entry_point() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["agent", "add", "copnow_api", "migrations"])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["agent", "remove", "copnow_api", "migrations"])
+        .assert()
+        .success();
+
+    assert!(registered_agent_ids(&home).is_empty());
+    assert!(codebase_dir.join("AGENT/migrations.md").exists());
+
+    orchestra_cmd(home.path())
+        .args(["agent", "remove", "copnow_api", "migrations"])
+        .assert()
+        .failure()
+        .stderr(contains("no subagent 'migrations'"));
+    orchestra_cmd(home.path())
+        .args(["agent", "list", "copnow_api"])
+        .assert()
+        .success()
+        .stdout(contains("No subagents registered"));
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use orchestra_renderer::{default_conventions, AgentKind};
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+fn sync(home: &TempDir) {
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+}
+
+fn controls(codebase_dir: &Path, file: &str) -> String {
+    fs::read_to_string(codebase_dir.join("orchestra/controls").join(file))
+        .unwrap_or_else(|e| panic!("read {file}: {e}"))
+}
+
+#[test]
+fn set_agent_replaces_only_that_agents_conventions() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+    sync(&home);
+    let agents_before = controls(&codebase_dir, "AGENTS.md");
+
+    let bullets = workspace.path().join("bullets.txt");
+    fs::write(
+        &bullets,
+        "- Add dependencies when they save real work.\n\n- Squash commits before review.\n",
+    )
+    .expect("write bullets");
+    orchestra_cmd(home.path())
+        .args(["convention", "set-agent", "copnow_api", "claude", "--file"])
+        .arg(&bullets)
+        .assert()
+        .success()
+        .stdout(contains("Set 2 convention(s) for claude in 'copnow_api'"));
+    sync(&home);
+
+    let claude = controls(&codebase_dir, "CLAUDE.md");
+    assert!(
+        claude.contains(
+            "## Conventions\n\n- Add dependencies when they save real work.\n- Squash commits before review.\n"
+        ),
+        "override missing from CLAUDE.md:\n{claude}"
+    );
+    assert!(!claude.contains(default_conventions(AgentKind::Claude)[1]));
+    assert!(claude.contains("## Project Overview"));
+    assert_eq!(controls(&codebase_dir, "AGENTS.md"), agents_before);
+
+    orchestra_cmd(home.path())
+        .args(["convention", "reset-agent", "copnow_api", "claude"])
+        .assert()
+        .success();
+    sync(&home);
+    let claude = controls(&codebase_dir, "CLAUDE.md");
+    for default in default_conventions(AgentKind::Claude) {
+        assert!(
+            claude.contains(default),
+            "default missing after reset: {default}"
+        );
+    }
+}
+
+#[test]
+fn set_agent_rejects_unknown_agents_and_empty_files() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    let empty = workspace.path().join("empty.txt");
+    fs::write(&empty, "\n  \n").expect("write empty file");
+
+    orchestra_cmd(home.path())
+        .args(["convention", "set-agent", "copnow_api", "claude", "--file"])
+        .arg(&empty)
+        .assert()
+        .failure()
+        .stderr(contains("has no conventions"));
+    orchestra_cmd(home.path())
+        .args(["convention", "set-agent", "copnow_api", "vim", "--file"])
+        .arg(&empty)
+        .assert()
+        .failure()
+        .stderr(contains("unknown agent"));
+}
@@ -0,0 +1,35 @@
+//! Fixtures shared by the CLI integration tests. Each test binary uses only
+//! some of them.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use orchestra_core::{
+    registry,
+    types::{ProjectName, ProjectType},
+};
+use tempfile::TempDir;
+
+/// The `orchestra` binary, keeping its data in `home/.orchestra`.
+pub fn orchestra_cmd(home: &Path) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("orchestra"));
+    cmd.arg("--home").arg(home.join(".orchestra"));
+    cmd
+}
+
+/// Register `workspace/<codebase_name>` as a backend codebase in project
+/// `copnow` and return its directory.
+pub fn init_codebase(home: &TempDir, workspace: &TempDir, codebase_name: &str) -> PathBuf {
+    let codebase_dir = workspace.path().join(codebase_name);
+    fs::create_dir_all(&codebase_dir).expect("create codebase dir");
+    registry::init_at(
+        codebase_dir.clone(),
+        ProjectName::from("copnow"),
+        Some(ProjectType::Backend),
+        home.path(),
+    )
+    .expect("init codebase");
+    codebase_dir
+}
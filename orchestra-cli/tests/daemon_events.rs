@@ -0,0 +1,94 @@
+mod common;
+
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+use common::orchestra_cmd;
+
+fn seed_events(home: &TempDir) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock")
+        .as_secs();
+    let logs = home.path().join(".orchestra").join("logs");
+    fs::create_dir_all(&logs).expect("logs dir");
+    let lines = [
+        format!(r#"{{"at_unix":{},"event":"daemon_started"}}"#, now - 7200),
+        format!(
+            r#"{{"at_unix":{},"event":"sync_failed","codebases":["copnow_api"],"detail":{{"error":"old failure"}}}}"#,
+            now - 7200
+        ),
+        format!(r#"{{"at_unix":{now},"event":"sync_started","codebases":["worker_api"]}}"#),
+        "{truncated".to_string(),
+        format!(
+            r#"{{"at_unix":{now},"event":"sync_failed","codebases":["copnow_api"],"detail":{{"error":"new failure"}}}}"#
+        ),
+    ];
+    fs::write(logs.join("events.jsonl"), lines.join("\n") + "\n").expect("seed event log");
+}
+
+fn events_output(home: &TempDir, filters: &[&str]) -> Vec<String> {
+    let output = orchestra_cmd(home.path())
+        .args(["daemon", "logs", "--events"])
+        .args(filters)
+        .output()
+        .expect("run daemon logs");
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout)
+        .expect("utf8 stdout")
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn events_query_filters_by_codebase_and_age() {
+    let home = TempDir::new().expect("home");
+    seed_events(&home);
+
+    assert_eq!(
+        events_output(&home, &[]).len(),
+        4,
+        "the corrupt line is skipped"
+    );
+
+    let copnow = events_output(&home, &["--codebase", "copnow_api"]);
+    assert_eq!(copnow.len(), 2, "{copnow:?}");
+    assert!(copnow.iter().all(|line| line.contains("\"copnow_api\"")));
+    assert!(copnow[0].contains("old failure") && copnow[1].contains("new failure"));
+
+    let recent = events_output(&home, &["--codebase", "copnow_api", "--since", "1h"]);
+    assert_eq!(recent.len(), 1, "{recent:?}");
+    assert!(recent[0].contains("new failure"));
+}
+
+#[test]
+fn events_filters_require_events_flag() {
+    let home = TempDir::new().expect("home");
+    orchestra_cmd(home.path())
+        .args(["daemon", "logs", "--codebase", "copnow_api"])
+        .assert()
+        .failure()
+        .stderr(contains("--events"));
+    orchestra_cmd(home.path())
+        .args(["daemon", "logs", "--events"])
+        .assert()
+        .success()
+        .stdout(contains("No daemon events found."));
+}
+
+#[test]
+fn events_reject_invalid_since_without_panicking() {
+    let home = TempDir::new().expect("home");
+    for since in ["7é", "99999999999999d", "0d"] {
+        orchestra_cmd(home.path())
+            .args(["daemon", "logs", "--events", "--since", since])
+            .assert()
+            .code(2)
+            .stderr(contains("--since"));
+    }
+}
@@ -0,0 +1,83 @@
+//! `orchestra daemon install` against a real systemd user manager.
+//!
+//! systemd only reads units from the real user's `~/.config/systemd/user`,
+//! so this test installs there (with a throwaway `ORCHESTRA_HOME`) and only
+//! runs when `ORCHESTRA_SYSTEMD_TESTS=1` is set.
+#![cfg(target_os = "linux")]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use tempfile::TempDir;
+
+const GATE_ENV: &str = "ORCHESTRA_SYSTEMD_TESTS";
+const UNIT: &str = "orchestra-daemon.service";
+
+fn orchestra_cmd(data: &Path) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("orchestra"));
+    cmd.env("ORCHESTRA_HOME", data);
+    cmd
+}
+
+fn unit_path() -> PathBuf {
+    let home = std::env::var_os("HOME").expect("HOME");
+    PathBuf::from(home).join(".config/systemd/user").join(UNIT)
+}
+
+fn is_active() -> bool {
+    Command::new("systemctl")
+        .args(["--user", "is-active", "--quiet", UNIT])
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Uninstalls on drop so a failed assertion does not leave the unit behind.
+struct Installed<'a>(&'a Path);
+
+impl Drop for Installed<'_> {
+    fn drop(&mut self) {
+        let _ = orchestra_cmd(self.0).args(["daemon", "uninstall"]).status();
+    }
+}
+
+#[test]
+fn install_starts_and_uninstall_removes_the_user_unit() {
+    if std::env::var(GATE_ENV).as_deref() != Ok("1") {
+        eprintln!("skipping: set {GATE_ENV}=1 on a machine with a systemd user session");
+        return;
+    }
+    let data = TempDir::new().expect("data dir");
+
+    let output = orchestra_cmd(data.path())
+        .args(["daemon", "install", "--no-startup-sync"])
+        .output()
+        .expect("run orchestra");
+    assert!(output.status.success(), "{output:?}");
+    let installed = Installed(data.path());
+    let unit = std::fs::read_to_string(unit_path()).expect("unit written");
+    assert!(unit.contains("--no-startup-sync"), "{unit}");
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let status = orchestra_cmd(data.path())
+            .args(["daemon", "status"])
+            .output()
+            .expect("run orchestra");
+        if String::from_utf8_lossy(&status.stdout).contains("\"running\": true") {
+            break;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "daemon never answered: {status:?}"
+        );
+        sleep(Duration::from_millis(200));
+    }
+    assert!(is_active());
+
+    drop(installed);
+    assert!(!unit_path().exists());
+    assert!(!is_active());
+}
@@ -0,0 +1,218 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{ProjectName, ProjectType},
+};
+use tempfile::TempDir;
+
+/// Runs the binary with no data-dir overrides inherited from the caller.
+fn orchestra_cmd(home: &Path) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("orchestra"));
+    cmd.env("HOME", home)
+        .env("USERPROFILE", home)
+        .env_remove("ORCHESTRA_HOME")
+        .env_remove("XDG_DATA_HOME")
+        .env_remove("XDG_STATE_HOME");
+    cmd
+}
+
+fn make_codebase(workspace: &TempDir) -> PathBuf {
+    let codebase_dir = workspace.path().join("copnow_api");
+    fs::create_dir_all(&codebase_dir).expect("create codebase dir");
+    codebase_dir
+}
+
+/// `init`, `sync` and `status` through the CLI, with `configure` adding the
+/// data-dir override under test.
+fn init_sync_status(home: &Path, codebase_dir: &Path, configure: impl Fn(&mut Command)) {
+    let mut init = orchestra_cmd(home);
+    configure(&mut init);
+    init.arg("init")
+        .arg(codebase_dir)
+        .args(["--project", "copnow"])
+        .assert()
+        .success();
+
+    let mut sync = orchestra_cmd(home);
+    configure(&mut sync);
+    sync.args(["sync", "copnow_api"]).assert().success();
+
+    let mut status = orchestra_cmd(home);
+    configure(&mut status);
+    status
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(contains("copnow_api"));
+}
+
+fn assert_data_under(root: &Path) {
+    assert!(root.join("projects/copnow/copnow_api.yaml").is_file());
+    assert!(root.join("hashes/copnow_api.json").is_file());
+    assert!(root.join("logs/sync-history.jsonl").is_file());
+}
+
+#[test]
+fn orchestra_home_env_holds_all_data() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = make_codebase(&workspace);
+    let data = home.path().join("Sync/orchestra");
+
+    init_sync_status(home.path(), &codebase_dir, |cmd| {
+        cmd.env("ORCHESTRA_HOME", &data);
+    });
+
+    assert_data_under(&data);
+    assert!(!home.path().join(".orchestra").exists());
+    assert!(codebase_dir.join("orchestra/controls/CLAUDE.md").exists());
+
+    // Without the override the legacy registry is empty.
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn home_flag_is_used_when_env_is_unset() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = make_codebase(&workspace);
+    let data = home.path().join("custom");
+
+    init_sync_status(home.path(), &codebase_dir, |cmd| {
+        cmd.arg("--home").arg(&data);
+    });
+
+    assert_data_under(&data);
+    assert!(!home.path().join(".orchestra").exists());
+}
+
+#[test]
+fn home_flag_leaves_the_home_directory_untouched() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = make_codebase(&workspace);
+    let data = workspace.path().join("team-registry");
+
+    init_sync_status(home.path(), &codebase_dir, |cmd| {
+        cmd.arg("--home").arg(&data);
+    });
+    orchestra_cmd(home.path())
+        .arg("--home")
+        .arg(&data)
+        .args(["diff", "copnow_api"])
+        .assert()
+        .success();
+
+    assert_data_under(&data);
+    let written: Vec<_> = fs::read_dir(home.path()).expect("read home").collect();
+    assert!(written.is_empty(), "home was written to: {written:?}");
+}
+
+#[test]
+fn home_flag_works_without_a_home_directory() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = make_codebase(&workspace);
+    let data = workspace.path().join("team-registry");
+
+    init_sync_status(home.path(), &codebase_dir, |cmd| {
+        cmd.env_remove("HOME")
+            .env_remove("USERPROFILE")
+            .arg("--home")
+            .arg(&data);
+    });
+    orchestra_cmd(home.path())
+        .env_remove("HOME")
+        .env_remove("USERPROFILE")
+        .arg("--home")
+        .arg(&data)
+        .args(["project", "add", "copnow_web", "--project", "copnow"])
+        .assert()
+        .success();
+
+    assert_data_under(&data);
+    assert!(data.join("projects/copnow/copnow_web.yaml").is_file());
+}
+
+#[test]
+fn orchestra_home_env_beats_home_flag() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = make_codebase(&workspace);
+    let env_root = home.path().join("from-env");
+    let flag_root = home.path().join("from-flag");
+
+    init_sync_status(home.path(), &codebase_dir, |cmd| {
+        cmd.env("ORCHESTRA_HOME", &env_root)
+            .arg("--home")
+            .arg(&flag_root);
+    });
+
+    assert_data_under(&env_root);
+    assert!(!flag_root.exists());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn xdg_dirs_split_data_and_state() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = make_codebase(&workspace);
+    let data = home.path().join("xdg-data");
+    let state = home.path().join("xdg-state");
+
+    init_sync_status(home.path(), &codebase_dir, |cmd| {
+        cmd.env("XDG_DATA_HOME", &data)
+            .env("XDG_STATE_HOME", &state);
+    });
+
+    assert!(data
+        .join("orchestra/projects/copnow/copnow_api.yaml")
+        .is_file());
+    assert!(data.join("orchestra/hashes/copnow_api.json").is_file());
+    assert!(state.join("orchestra/logs/sync-history.jsonl").is_file());
+    assert!(!home.path().join(".orchestra").exists());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn existing_legacy_dir_is_preferred_over_missing_xdg_dir() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = make_codebase(&workspace);
+    registry::init_at(
+        codebase_dir,
+        ProjectName::from("copnow"),
+        Some(ProjectType::Backend),
+        home.path(),
+    )
+    .expect("init legacy registry");
+    let data = home.path().join("xdg-data");
+
+    orchestra_cmd(home.path())
+        .env("XDG_DATA_HOME", &data)
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .env("XDG_DATA_HOME", &data)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(contains("copnow_api"));
+
+    assert!(home
+        .path()
+        .join(".orchestra/hashes/copnow_api.json")
+        .is_file());
+    assert!(!data.join("orchestra").exists());
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use serde_json::Value;
+
+use orchestra_core::{
+    registry,
+    types::{ProjectName, ProjectType},
+    Project,
+};
+use tempfile::TempDir;
+
+use common::orchestra_cmd;
+
+/// A Rust crate depending on Axum.
+fn axum_crate(workspace: &TempDir) -> PathBuf {
+    let dir = workspace.path().join("api");
+    fs::create_dir_all(&dir).expect("create codebase dir");
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"api\"\n\n[dependencies]\naxum = \"0.7\"\n",
+    )
+    .expect("write Cargo.toml");
+    dir
+}
+
+/// Register `dir` as `api` with the wrong project type.
+fn register_as_frontend(home: &TempDir, dir: &Path) {
+    registry::init_at(
+        dir.to_path_buf(),
+        ProjectName::from("acme"),
+        Some(ProjectType::Frontend),
+        home.path(),
+    )
+    .expect("init codebase");
+}
+
+fn registered_project(home: &TempDir) -> Project {
+    let (_, codebase) = registry::list_codebases_at(home.path())
+        .expect("list")
+        .remove(0);
+    codebase.projects[0].clone()
+}
+
+fn status_json(home: &TempDir) -> Value {
+    let output = orchestra_cmd(home.path())
+        .args(["status", "--json"])
+        .output()
+        .expect("run orchestra");
+    assert!(output.status.success(), "{output:?}");
+    serde_json::from_slice(&output.stdout).expect("json stdout")
+}
+
+#[test]
+fn json_reports_the_detected_stack() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let dir = axum_crate(&workspace);
+
+    let output = orchestra_cmd(home.path())
+        .arg("detect")
+        .arg(&dir)
+        .arg("--json")
+        .output()
+        .expect("run orchestra");
+    assert!(output.status.success(), "{output:?}");
+    let json: Value = serde_json::from_slice(&output.stdout).expect("json stdout");
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "path": dir,
+            "primary_language": "Rust",
+            "framework": "Axum",
+            "variant": null,
+            "project_type": "backend",
+            "confidence": "high",
+            "detail": null,
+            "package_manager": null,
+            "indicators": ["Cargo.toml"],
+        })
+    );
+    assert!(!home.path().join(".orchestra").exists());
+
+    orchestra_cmd(home.path())
+        .arg("detect")
+        .arg(workspace.path())
+        .assert()
+        .failure()
+        .stderr(contains("no known indicator file found"));
+}
+
+#[test]
+fn save_updates_the_registry_and_status_flags_later_manifest_changes() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let dir = axum_crate(&workspace);
+    register_as_frontend(&home, &dir);
+
+    orchestra_cmd(home.path())
+        .arg("detect")
+        .arg(&dir)
+        .assert()
+        .success()
+        .stdout(contains("Framework:   Axum"));
+    assert_eq!(
+        registered_project(&home).project_type,
+        ProjectType::Frontend
+    );
+
+    orchestra_cmd(home.path())
+        .args(["detect", "--save", "api"])
+        .assert()
+        .success()
+        .stdout(contains("✓ Saved to codebase 'api'"));
+    let project = registered_project(&home);
+    assert_eq!(project.project_type, ProjectType::Backend);
+    assert_eq!(project.framework.as_deref(), Some("Axum"));
+    let detection = project.detection.expect("detection recorded");
+    assert_eq!(detection.indicators, ["Cargo.toml"]);
+    assert!(detection.detected_at.is_some());
+    assert!(status_json(&home)["codebases"][0]
+        .get("detection_outdated")
+        .is_none());
+
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"api\"\n\n[dependencies]\nactix-web = \"4\"\n",
+    )
+    .expect("edit Cargo.toml");
+    assert_eq!(
+        status_json(&home)["codebases"][0]["detection_outdated"],
+        true
+    );
+    orchestra_cmd(home.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(contains("Stack metadata may be outdated for 'api'"));
+
+    orchestra_cmd(home.path())
+        .args(["detect", "--save", "api"])
+        .assert()
+        .success();
+    assert_eq!(
+        registered_project(&home).framework.as_deref(),
+        Some("Actix Web")
+    );
+    assert!(status_json(&home)["codebases"][0]
+        .get("detection_outdated")
+        .is_none());
+}
+
+#[test]
+fn save_refuses_a_directory_other_than_the_codebase_root() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let dir = axum_crate(&workspace);
+    register_as_frontend(&home, &dir);
+    let other = workspace.path().join("other");
+    fs::create_dir_all(&other).expect("create other dir");
+    fs::write(other.join("go.mod"), "module example.com/other\n").expect("write go.mod");
+
+    orchestra_cmd(home.path())
+        .arg("detect")
+        .arg(&other)
+        .args(["--save", "api"])
+        .assert()
+        .failure()
+        .stderr(contains("is not the root of codebase 'api'"));
+    assert_eq!(
+        registered_project(&home).project_type,
+        ProjectType::Frontend
+    );
+}
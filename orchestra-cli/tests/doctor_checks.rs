@@ -1,7 +1,10 @@
 use std::path::PathBuf;
 use std::process::Command;
 
-use orchestra_core::{registry, types::ProjectName};
+use orchestra_core::{
+    registry,
+    types::{CodebaseName, ProjectName},
+};
 use tempfile::TempDir;
 
 fn orchestra_bin_path() -> PathBuf {
@@ -79,3 +82,214 @@ fn doctor_json_contains_expected_checks() {
     assert!(names.contains(&"guide presence"));
     assert!(names.contains(&"staleness summary"));
 }
+
+fn run_doctor(home: &std::path::Path, extra: &[&str]) -> (bool, serde_json::Value) {
+    let output = Command::new(orchestra_bin_path())
+        .env("HOME", home)
+        .env("USERPROFILE", home)
+        .args(["doctor", "--json"])
+        .args(extra)
+        .output()
+        .expect("run doctor");
+    let json = serde_json::from_slice(&output.stdout).expect("json");
+    (output.status.success(), json)
+}
+
+fn findings<'a>(report: &'a serde_json::Value, name: &str) -> Vec<&'a serde_json::Value> {
+    report["checks"]
+        .as_array()
+        .expect("checks array")
+        .iter()
+        .filter(|c| c["name"] == name)
+        .collect()
+}
+
+#[cfg(unix)]
+#[test]
+fn doctor_flags_and_fixes_stale_socket() {
+    let home = TempDir::new().expect("home");
+    let socket = home.path().join(".orchestra").join("daemon.sock");
+    std::fs::create_dir_all(socket.parent().unwrap()).expect("run dir");
+    // Bind then drop: the socket file remains but nothing is listening.
+    drop(std::os::unix::net::UnixListener::bind(&socket).expect("bind"));
+
+    let (_, report) = run_doctor(home.path(), &[]);
+    let socket_findings = findings(&report, "daemon socket");
+    assert_eq!(socket_findings[0]["severity"], "warn");
+    assert_eq!(socket_findings[0]["fixable"], true);
+    assert!(socket.exists(), "doctor without --fix must not touch the socket");
+
+    let (_, report) = run_doctor(home.path(), &["--fix"]);
+    assert_eq!(findings(&report, "daemon socket")[0]["severity"], "ok");
+    assert!(!socket.exists(), "--fix removes the stale socket");
+}
+
+#[test]
+fn doctor_flags_and_fixes_orphaned_hash_store() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase = workspace.path().join("kept_app");
+    std::fs::create_dir_all(&codebase).expect("mkdir codebase");
+    registry::init_at(codebase, ProjectName::from("acme"), None, home.path()).expect("init");
+
+    let hashes = home.path().join(".orchestra").join("hashes");
+    std::fs::create_dir_all(&hashes).expect("hashes dir");
+    let payload = r#"{"synced_at":"2024-01-01T00:00:00Z","files":{}}"#;
+    std::fs::write(hashes.join("kept_app.json"), payload).expect("kept store");
+    std::fs::write(hashes.join("ghost_app.json"), payload).expect("orphan store");
+
+    let (_, report) = run_doctor(home.path(), &[]);
+    let hash_findings = findings(&report, "hash stores");
+    assert_eq!(hash_findings.len(), 1);
+    assert_eq!(hash_findings[0]["severity"], "warn");
+    assert!(hash_findings[0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("ghost_app"));
+
+    run_doctor(home.path(), &["--fix"]);
+    assert!(!hashes.join("ghost_app.json").exists(), "orphan removed");
+    assert!(hashes.join("kept_app.json").exists(), "registered store kept");
+}
+
+#[test]
+fn doctor_reports_files_that_differ_from_their_recorded_hash() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase = workspace.path().join("hashed_app");
+    std::fs::create_dir_all(&codebase).expect("mkdir codebase");
+    registry::init_at(
+        codebase.clone(),
+        ProjectName::from("acme"),
+        None,
+        home.path(),
+    )
+    .expect("init");
+    let synced = Command::new(orchestra_bin_path())
+        .env("HOME", home.path())
+        .env("USERPROFILE", home.path())
+        .args(["sync", "hashed_app"])
+        .output()
+        .expect("run sync");
+    assert!(synced.status.success(), "{synced:?}");
+
+    let (_, report) = run_doctor(home.path(), &[]);
+    assert_eq!(findings(&report, "hash integrity")[0]["severity"], "ok");
+
+    std::fs::write(codebase.join("orchestra/controls/AGENTS.md"), "edited\n")
+        .expect("edit AGENTS.md");
+    let (_, report) = run_doctor(home.path(), &[]);
+    let integrity = findings(&report, "hash integrity");
+    assert_eq!(integrity.len(), 1);
+    assert_eq!(integrity[0]["severity"], "warn");
+    let message = integrity[0]["message"].as_str().unwrap();
+    assert!(message.contains("1 file(s) in 'hashed_app'"), "{message}");
+    assert!(message.contains("AGENTS.md"), "{message}");
+}
+
+#[test]
+fn doctor_reports_a_corrupt_hash_store_without_moving_it() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase = workspace.path().join("torn_app");
+    std::fs::create_dir_all(&codebase).expect("mkdir codebase");
+    registry::init_at(codebase, ProjectName::from("acme"), None, home.path()).expect("init");
+
+    let hashes = home.path().join(".orchestra").join("hashes");
+    std::fs::create_dir_all(&hashes).expect("hashes dir");
+    std::fs::write(hashes.join("torn_app.json"), "").expect("empty store");
+
+    let (ok, report) = run_doctor(home.path(), &[]);
+    assert!(!ok);
+    let hash_findings = findings(&report, "hash stores");
+    assert_eq!(hash_findings[0]["severity"], "error");
+    assert!(hash_findings[0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("the next sync sets it aside"));
+    assert!(
+        hashes.join("torn_app.json").exists(),
+        "doctor changes nothing"
+    );
+}
+
+#[test]
+fn doctor_fails_on_missing_codebase_path() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase = workspace.path().join("vanished_app");
+    std::fs::create_dir_all(&codebase).expect("mkdir codebase");
+    registry::init_at(codebase.clone(), ProjectName::from("acme"), None, home.path())
+        .expect("init");
+    std::fs::remove_dir_all(&codebase).expect("remove codebase");
+
+    let (success, report) = run_doctor(home.path(), &["--fix"]);
+    assert!(!success, "error findings must produce a non-zero exit");
+    let path_findings = findings(&report, "codebase paths");
+    assert_eq!(path_findings[0]["severity"], "error");
+    assert!(path_findings[0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("vanished_app"));
+}
+
+#[cfg(unix)]
+#[test]
+fn doctor_fix_canonicalizes_symlinked_codebase_paths() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let real = workspace.path().join("linked_app");
+    std::fs::create_dir_all(&real).expect("mkdir codebase");
+    let project = ProjectName::from("acme");
+    let mut codebase =
+        registry::init_at(real.clone(), project.clone(), None, home.path()).expect("init");
+    // Registrations from before init canonicalized kept the path as typed.
+    let link = workspace.path().join("link");
+    std::os::unix::fs::symlink(&real, &link).expect("symlink");
+    codebase.path = link;
+    registry::save_codebase_at(home.path(), &project, &codebase).expect("save");
+
+    let (_, report) = run_doctor(home.path(), &[]);
+    let path_findings = findings(&report, "codebase paths");
+    assert_eq!(path_findings[0]["severity"], "warn");
+    assert_eq!(path_findings[0]["fixable"], true);
+    assert!(path_findings[0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("resolves to"));
+
+    let (_, report) = run_doctor(home.path(), &["--fix"]);
+    assert_eq!(findings(&report, "codebase paths")[0]["severity"], "ok");
+    let fixed =
+        registry::load_codebase_at(home.path(), &project, &CodebaseName::from("linked_app"))
+            .expect("load");
+    assert_eq!(fixed.path, real.canonicalize().unwrap());
+}
+
+#[test]
+fn doctor_warns_about_names_registered_before_validation() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase = workspace.path().join("legacy_app");
+    std::fs::create_dir_all(&codebase).expect("mkdir codebase");
+    let project = ProjectName::from("acme");
+
+    registry::init_at(codebase, project.clone(), None, home.path()).expect("init");
+    let (_, report) = run_doctor(home.path(), &[]);
+    assert_eq!(findings(&report, "registry names")[0]["severity"], "ok");
+
+    // Older builds saved whatever name they were given.
+    let (_, mut legacy) = registry::list_codebases_at(home.path())
+        .expect("list")
+        .remove(0);
+    legacy.name = CodebaseName::from(".legacy_app");
+    registry::save_codebase_at(home.path(), &project, &legacy).expect("save");
+
+    let (_, report) = run_doctor(home.path(), &[]);
+    let name_findings = findings(&report, "registry names");
+    assert_eq!(name_findings.len(), 1);
+    assert_eq!(name_findings[0]["severity"], "warn");
+    let message = name_findings[0]["message"].as_str().unwrap();
+    assert!(message.contains(".legacy_app"), "{message}");
+    assert!(message.contains("start with a dot"), "{message}");
+}
@@ -76,7 +76,7 @@ fn dry_run_sync_reports_files_and_writes_nothing() {
     );
     let stdout = String::from_utf8(output.stdout).unwrap();
 
-    assert!(stdout.contains("[dry-run]"), "missing dry-run prefix");
+    assert!(stdout.contains("dry-run:"), "missing dry-run header");
     assert!(stdout.contains("CLAUDE.md"), "missing CLAUDE.md in output");
     assert!(stdout.contains("settings.json"), "missing settings.json in output");
 
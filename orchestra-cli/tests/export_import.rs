@@ -0,0 +1,169 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use assert_cmd::prelude::*;
+use chrono::{Duration, Utc};
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{CodebaseName, ProjectName},
+};
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+fn export_to(home: &Path, bundle: &Path, extra: &[&str]) {
+    orchestra_cmd(home)
+        .arg("export")
+        .arg("--output")
+        .arg(bundle)
+        .args(extra)
+        .assert()
+        .success();
+}
+
+fn load(home: &Path, name: &str) -> orchestra_core::types::Codebase {
+    registry::load_codebase_at(home, &ProjectName::from("copnow"), &CodebaseName::from(name))
+        .expect("load codebase")
+}
+
+#[test]
+fn export_then_import_into_fresh_home_round_trips() {
+    let laptop = TempDir::new().expect("laptop");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&laptop, &workspace, "copnow_api");
+    init_codebase(&laptop, &workspace, "copnow_web");
+
+    let scratch = TempDir::new().expect("scratch");
+    let bundle = scratch.path().join("registry.yaml");
+    export_to(laptop.path(), &bundle, &[]);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&bundle).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+    }
+
+    let desktop = TempDir::new().expect("desktop");
+    orchestra_cmd(desktop.path())
+        .arg("import")
+        .arg(&bundle)
+        .assert()
+        .success()
+        .stdout(contains("2 added, 0 replaced, 0 kept"));
+
+    orchestra_cmd(desktop.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(contains("copnow_api"))
+        .stdout(contains("copnow_web"));
+    assert_eq!(
+        load(desktop.path(), "copnow_api"),
+        load(laptop.path(), "copnow_api")
+    );
+}
+
+#[test]
+fn import_remaps_codebase_path_prefix() {
+    let laptop = TempDir::new().expect("laptop");
+    let old_workspace = TempDir::new().expect("old workspace");
+    init_codebase(&laptop, &old_workspace, "copnow_api");
+
+    let scratch = TempDir::new().expect("scratch");
+    let bundle = scratch.path().join("registry.yaml");
+    export_to(laptop.path(), &bundle, &["--include-hashes"]);
+
+    let desktop = TempDir::new().expect("desktop");
+    let new_workspace = TempDir::new().expect("new workspace");
+    let remap = format!(
+        "{}={}",
+        old_workspace.path().display(),
+        new_workspace.path().display()
+    );
+    orchestra_cmd(desktop.path())
+        .arg("import")
+        .arg(&bundle)
+        .args(["--remap-path", &remap])
+        .assert()
+        .success();
+
+    assert_eq!(
+        load(desktop.path(), "copnow_api").path,
+        new_workspace.path().join("copnow_api")
+    );
+}
+
+#[test]
+fn import_refuses_existing_codebases_without_merge_or_replace() {
+    let laptop = TempDir::new().expect("laptop");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&laptop, &workspace, "copnow_api");
+
+    let scratch = TempDir::new().expect("scratch");
+    let bundle = scratch.path().join("registry.yaml");
+    export_to(laptop.path(), &bundle, &[]);
+
+    let desktop = TempDir::new().expect("desktop");
+    init_codebase(&desktop, &workspace, "copnow_api");
+    let before = load(desktop.path(), "copnow_api");
+
+    orchestra_cmd(desktop.path())
+        .arg("import")
+        .arg(&bundle)
+        .assert()
+        .failure()
+        .stderr(contains("already registered: copnow_api"));
+    assert_eq!(load(desktop.path(), "copnow_api"), before);
+
+    orchestra_cmd(desktop.path())
+        .arg("import")
+        .arg(&bundle)
+        .arg("--replace")
+        .assert()
+        .success()
+        .stdout(contains("0 added, 1 replaced, 0 kept"));
+    assert_eq!(
+        load(desktop.path(), "copnow_api"),
+        load(laptop.path(), "copnow_api")
+    );
+}
+
+#[test]
+fn merge_keeps_the_most_recently_updated_copy() {
+    let laptop = TempDir::new().expect("laptop");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&laptop, &workspace, "copnow_api");
+    init_codebase(&laptop, &workspace, "copnow_web");
+
+    let scratch = TempDir::new().expect("scratch");
+    let bundle = scratch.path().join("registry.yaml");
+    export_to(laptop.path(), &bundle, &[]);
+
+    // Desktop's api is newer than the bundle; its web is older.
+    let desktop = TempDir::new().expect("desktop");
+    let project = ProjectName::from("copnow");
+    for (name, offset) in [("copnow_api", 1), ("copnow_web", -1)] {
+        init_codebase(&desktop, &workspace, name);
+        let mut codebase = load(desktop.path(), name);
+        codebase.updated_at = Utc::now() + Duration::days(offset);
+        codebase.notes.push(format!("desktop {name}"));
+        registry::save_codebase_at(desktop.path(), &project, &codebase).expect("save");
+    }
+
+    orchestra_cmd(desktop.path())
+        .arg("import")
+        .arg(&bundle)
+        .arg("--merge")
+        .assert()
+        .success()
+        .stdout(contains("kept      copnow_api"))
+        .stdout(contains("0 added, 1 replaced, 1 kept"));
+
+    assert_eq!(load(desktop.path(), "copnow_api").notes, ["desktop copnow_api"]);
+    assert!(load(desktop.path(), "copnow_web").notes.is_empty());
+}
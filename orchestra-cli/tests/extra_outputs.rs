@@ -0,0 +1,123 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{CodebaseName, ExtraOutput, ProjectName},
+};
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+fn registered_outputs(home: &TempDir) -> Vec<ExtraOutput> {
+    registry::load_codebase_at(
+        home.path(),
+        &ProjectName::from("copnow"),
+        &CodebaseName::from("copnow_api"),
+    )
+    .expect("load codebase")
+    .extra_outputs
+}
+
+#[test]
+fn output_add_list_and_remove_update_registry() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+    let templates = home.path().join(".orchestra/templates");
+    fs::create_dir_all(&templates).expect("templates dir");
+    fs::write(
+        templates.join("ai-context.md.tera"),
+        "# {{ codebase_name }}\n",
+    )
+    .expect("write template");
+
+    orchestra_cmd(home.path())
+        .args([
+            "output",
+            "add",
+            "copnow_api",
+            "ai-context.md.tera",
+            "docs/ai-context.md",
+        ])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Added extra output docs/ai-context.md for 'copnow_api'",
+        ));
+    assert_eq!(
+        registered_outputs(&home),
+        vec![ExtraOutput {
+            template: "ai-context.md.tera".to_string(),
+            output_path: PathBuf::from("docs/ai-context.md"),
+        }]
+    );
+
+    orchestra_cmd(home.path())
+        .args(["output", "list", "copnow_api"])
+        .assert()
+        .success()
+        .stdout(contains("docs/ai-context.md  ← ai-context.md.tera"));
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(codebase_dir.join("docs/ai-context.md")).expect("extra output"),
+        "# copnow_api\n"
+    );
+
+    orchestra_cmd(home.path())
+        .args(["output", "remove", "copnow_api", "docs/ai-context.md"])
+        .assert()
+        .success()
+        .stdout(contains("Removed extra output docs/ai-context.md"));
+    assert!(registered_outputs(&home).is_empty());
+    orchestra_cmd(home.path())
+        .args(["output", "remove", "copnow_api", "docs/ai-context.md"])
+        .assert()
+        .failure()
+        .stderr(contains("no extra output 'docs/ai-context.md'"));
+}
+
+#[test]
+fn output_add_rejects_paths_outside_the_codebase_and_managed_files() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["output", "add", "copnow_api", "x.md.tera", "../escape.md"])
+        .assert()
+        .failure()
+        .stderr(contains("must be a relative path inside the codebase"));
+    orchestra_cmd(home.path())
+        .args([
+            "output",
+            "add",
+            "copnow_api",
+            "x.md.tera",
+            "orchestra/controls/AGENTS.md",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("already managed by Orchestra"));
+    assert!(registered_outputs(&home).is_empty());
+
+    orchestra_cmd(home.path())
+        .args(["output", "add", "copnow_api", "x.md.tera", "docs/x.md"])
+        .assert()
+        .success()
+        .stderr(contains("no template 'x.md.tera'"));
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .failure()
+        .stderr(contains("extra output 'docs/x.md' (template 'x.md.tera')"));
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use std::fs;
+use std::thread::sleep;
+use std::time::Duration;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::global;
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+fn write_global(home: &TempDir, contents: &str) {
+    let path = global::path_at(home.path());
+    fs::create_dir_all(path.parent().expect("parent")).expect("create data dir");
+    fs::write(path, contents).expect("write global.yaml");
+}
+
+fn statuses(home: &TempDir) -> Vec<(String, String)> {
+    let assert = orchestra_cmd(home.path())
+        .args(["status", "--json"])
+        .assert()
+        .success();
+    let payload: serde_json::Value =
+        serde_json::from_slice(&assert.get_output().stdout).expect("status json");
+    payload["codebases"]
+        .as_array()
+        .expect("codebases array")
+        .iter()
+        .map(|row| {
+            (
+                row["codebase"].as_str().expect("codebase").to_string(),
+                row["status"].as_str().expect("status").to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn global_conventions_render_into_every_codebase_and_edits_mark_them_stale() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let api = init_codebase(&home, &workspace, "copnow_api");
+    let web = init_codebase(&home, &workspace, "copnow_web");
+    write_global(
+        &home,
+        "conventions:\n  - Never commit secrets\narchitecture_notes: Services talk over gRPC.\n",
+    );
+
+    orchestra_cmd(home.path())
+        .args(["sync", "--all"])
+        .assert()
+        .success();
+    for dir in [&api, &web] {
+        let claude =
+            fs::read_to_string(dir.join("orchestra/controls/CLAUDE.md")).expect("CLAUDE.md");
+        assert!(claude.contains("- Never commit secrets"), "{claude}");
+        assert!(claude.contains("Services talk over gRPC."), "{claude}");
+    }
+    assert!(statuses(&home)
+        .iter()
+        .all(|(_, status)| status == "current"));
+
+    sleep(Duration::from_millis(1100));
+    write_global(
+        &home,
+        "conventions:\n  - Never commit secrets\n  - Sign off commits\n",
+    );
+    let statuses = statuses(&home);
+    assert_eq!(statuses.len(), 2);
+    assert!(
+        statuses.iter().all(|(_, status)| status == "stale"),
+        "{statuses:?}"
+    );
+}
+
+#[test]
+fn global_show_reports_missing_file_as_empty() {
+    let home = TempDir::new().expect("home");
+
+    orchestra_cmd(home.path())
+        .args(["global", "show"])
+        .assert()
+        .success()
+        .stdout(contains("No global instructions."));
+
+    write_global(
+        &home,
+        "conventions:\n  - Never commit secrets\nskills:\n  - id: security-review\n    description: Check the security checklist\n",
+    );
+    orchestra_cmd(home.path())
+        .args(["global", "show"])
+        .assert()
+        .success()
+        .stdout(contains("- Never commit secrets"))
+        .stdout(contains("security-review  Check the security checklist"));
+}
+
+#[cfg(unix)]
+#[test]
+fn global_edit_creates_the_file_and_rejects_invalid_yaml() {
+    let home = TempDir::new().expect("home");
+    let script = home.path().join("fake-editor.sh");
+    fs::write(
+        &script,
+        "#!/bin/sh\necho 'conventions: [Sign off commits]' > \"$1\"\n",
+    )
+    .expect("write editor");
+
+    orchestra_cmd(home.path())
+        .env("VISUAL", "")
+        .env("EDITOR", format!("sh {}", script.display()))
+        .args(["global", "edit"])
+        .assert()
+        .success()
+        .stdout(contains("Saved"));
+    assert_eq!(
+        global::load_at(home.path()).expect("load").conventions,
+        vec!["Sign off commits".to_string()]
+    );
+
+    fs::write(
+        &script,
+        "#!/bin/sh\necho 'conventions: [unclosed' > \"$1\"\n",
+    )
+    .expect("write editor");
+    orchestra_cmd(home.path())
+        .env("VISUAL", "")
+        .env("EDITOR", format!("sh {}", script.display()))
+        .args(["global", "edit"])
+        .assert()
+        .failure()
+        .stderr(contains("does not parse"));
+}
@@ -18,13 +18,23 @@ fn help_lists_all_available_commands() {
 
     for expected in [
         "init",
+        "detect",
         "project list",
         "project add",
+        "project remove",
+        "convention set-agent",
+        "skill list",
+        "skill add",
+        "task import",
+        "task assign",
         "sync",
         "status",
         "diff",
+        "which",
+        "upgrade-templates",
         "daemon start",
         "daemon stop",
+        "daemon restart",
         "daemon status",
         "daemon install",
         "daemon uninstall",
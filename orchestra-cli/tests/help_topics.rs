@@ -0,0 +1,82 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+use orchestra_sync::status::SignalKind;
+
+fn orchestra_command() -> Command {
+    assert_cmd::cargo::cargo_bin_cmd!("orchestra")
+}
+
+#[test]
+fn help_lists_topics_and_renders_them() {
+    orchestra_command()
+        .arg("help")
+        .assert()
+        .success()
+        .stdout(contains("Usage: orchestra"))
+        .stdout(contains("Help topics:"))
+        .stdout(contains("exit-codes"));
+
+    let output = orchestra_command()
+        .args(["help", "signals"])
+        .assert()
+        .success()
+        .stdout(contains("Staleness signals"))
+        .stdout(contains("```").not())
+        .get_output()
+        .stdout
+        .clone();
+    let text = String::from_utf8(output).expect("utf8 topic");
+    for kind in SignalKind::all() {
+        assert!(text.contains(kind.label()), "missing {}", kind.label());
+    }
+}
+
+#[test]
+fn help_falls_back_to_command_help() {
+    orchestra_command()
+        .args(["help", "daemon", "start"])
+        .assert()
+        .success()
+        .stdout(contains("Usage: orchestra daemon start"));
+
+    // The `daemon` topic shadows the command, and says where its help is.
+    orchestra_command()
+        .args(["help", "daemon"])
+        .assert()
+        .success()
+        .stdout(contains("orchestra daemon --help"));
+
+    orchestra_command()
+        .args(["help", "nope"])
+        .assert()
+        .failure()
+        .stderr(contains("no help topic or command 'nope'"));
+}
+
+#[test]
+fn mangen_writes_a_page_per_command() {
+    let dir = TempDir::new().expect("out dir");
+    let out = dir.path().join("man1");
+
+    orchestra_command()
+        .arg("mangen")
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout(contains("man pages to"));
+
+    for page in [
+        "orchestra.1",
+        "orchestra-sync.1",
+        "orchestra-daemon.1",
+        "orchestra-daemon-start.1",
+        "orchestra-template-check.1",
+        "orchestra-help.1",
+        "orchestra-mangen.1",
+    ] {
+        assert!(out.join(page).is_file(), "missing {page}");
+    }
+}
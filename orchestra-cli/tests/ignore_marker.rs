@@ -0,0 +1,94 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use assert_cmd::prelude::*;
+use chrono::Utc;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{CodebaseName, ProjectName, Task, TaskId, TaskStatus},
+};
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+fn status_json(home: &TempDir) -> serde_json::Value {
+    let output = orchestra_cmd(home.path())
+        .args(["status", "--json"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).expect("stdout utf8");
+    let payload: serde_json::Value = serde_json::from_str(&stdout).expect("parse status json");
+    payload["codebases"][0].clone()
+}
+
+const HAND_WRITTEN: &str = "<!-- orchestra:ignore -->\n# Hand-maintained instructions\n";
+
+#[test]
+fn marked_file_is_left_alone_until_unmarked() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+
+    let target = codebase_dir.join("orchestra/controls/CLAUDE.md");
+    let rendered = fs::read_to_string(&target).expect("rendered CLAUDE.md");
+    fs::write(&target, HAND_WRITTEN).expect("hand-maintain CLAUDE.md");
+
+    for _ in 0..2 {
+        orchestra_cmd(home.path())
+            .args(["sync", "copnow_api"])
+            .assert()
+            .success()
+            .stdout(contains("CLAUDE.md  ignored (user-managed)"));
+        assert_eq!(fs::read_to_string(&target).expect("read"), HAND_WRITTEN);
+    }
+
+    let row = status_json(&home);
+    assert_eq!(row["status"], "current", "{row}");
+    assert_eq!(row["ignored"], serde_json::json!(["orchestra/controls/CLAUDE.md"]));
+
+    // A registry change still leaves the marked file out of `diff`.
+    let project = ProjectName::from("copnow");
+    let mut codebase =
+        registry::load_codebase_at(home.path(), &project, &CodebaseName::from("copnow_api"))
+            .expect("load codebase");
+    let now = Utc::now();
+    codebase.projects[0].tasks.push(Task {
+        id: TaskId::from("T-042"),
+        title: "ignore-marker-sentinel".to_owned(),
+        status: TaskStatus::Pending,
+        description: None,
+        subtasks: vec![],
+        notes: vec![],
+        assigned_agent: None,
+        created_at: now,
+        updated_at: now,
+        extra: BTreeMap::new(),
+    });
+    codebase.updated_at = now;
+    registry::save_codebase_at(home.path(), &project, &codebase).expect("save codebase");
+    let diff = orchestra_cmd(home.path())
+        .args(["diff", "copnow_api"])
+        .assert()
+        .success();
+    let diff = String::from_utf8(diff.get_output().stdout.clone()).expect("stdout utf8");
+    assert!(diff.contains("ignore-marker-sentinel"), "{diff}");
+    assert!(!diff.contains("controls/CLAUDE.md"), "{diff}");
+
+    fs::write(&target, "# Hand-maintained instructions\n").expect("remove marker");
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    let resumed = fs::read_to_string(&target).expect("read");
+    assert_ne!(resumed, rendered, "registry change is rendered");
+    assert!(resumed.contains("ignore-marker-sentinel"), "{resumed}");
+    assert!(status_json(&home).get("ignored").is_none());
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use std::fs;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::{registry, types::ProjectType, Project, StackDetection};
+use tempfile::TempDir;
+
+use common::orchestra_cmd;
+
+/// A codebase directory whose `package.json` has `dependencies`.
+fn node_codebase(workspace: &TempDir, name: &str, dependencies: &str) -> std::path::PathBuf {
+    let dir = workspace.path().join(name);
+    fs::create_dir_all(&dir).expect("create codebase dir");
+    fs::write(
+        dir.join("package.json"),
+        format!(r#"{{"name":"{name}","dependencies":{{{dependencies}}}}}"#),
+    )
+    .expect("write package.json");
+    dir
+}
+
+fn registered_project(home: &TempDir, name: &str) -> Project {
+    let (_, codebase) = registry::list_codebases_at(home.path())
+        .expect("list")
+        .into_iter()
+        .find(|(_, codebase)| codebase.name.0 == name)
+        .expect("codebase registered");
+    codebase.projects[0].clone()
+}
+
+#[test]
+fn piped_detect_accepts_and_records_the_detected_stack() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let dir = node_codebase(&workspace, "web", r#""next":"14""#);
+
+    orchestra_cmd(home.path())
+        .args([
+            "init",
+            dir.to_str().unwrap(),
+            "--project",
+            "acme",
+            "--detect",
+        ])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Detected stack: JavaScript / Next.js -> frontend (confidence: high)",
+        ));
+
+    let project = registered_project(&home, "web");
+    assert_eq!(project.project_type, ProjectType::Frontend);
+    assert_eq!(project.framework.as_deref(), Some("Next.js"));
+    let detection: StackDetection = project.detection.expect("detection recorded");
+    assert_eq!(detection.confidence, "high");
+    assert!(!detection.overridden);
+    assert!(detection.detected_at.is_some());
+    assert_eq!(detection.indicators, ["package.json"]);
+    assert!(detection.indicators_sha256.is_some());
+}
+
+#[test]
+fn detect_strict_refuses_medium_confidence() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let dir = node_codebase(&workspace, "tooling", "");
+
+    orchestra_cmd(home.path())
+        .args([
+            "init",
+            dir.to_str().unwrap(),
+            "-p",
+            "acme",
+            "--detect-strict",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("no framework was identified"))
+        .stderr(contains("Pass --type"));
+    assert!(registry::list_codebases_at(home.path())
+        .expect("list")
+        .is_empty());
+
+    orchestra_cmd(home.path())
+        .args([
+            "init",
+            dir.to_str().unwrap(),
+            "-p",
+            "acme",
+            "--detect",
+            "--yes",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("No framework identified"));
+    let project = registered_project(&home, "tooling");
+    assert_eq!(project.project_type, ProjectType::Backend);
+    assert_eq!(
+        project.detection.map(|detection| detection.confidence),
+        Some("medium".to_string())
+    );
+}
+
+#[test]
+fn yes_requires_detect() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let dir = node_codebase(&workspace, "api", "");
+
+    orchestra_cmd(home.path())
+        .args(["init", dir.to_str().unwrap(), "-p", "acme", "--yes"])
+        .assert()
+        .failure()
+        .stderr(contains("--detect"));
+}
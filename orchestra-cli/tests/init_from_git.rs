@@ -0,0 +1,137 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{CodebaseName, ProjectName},
+};
+use tempfile::TempDir;
+
+use common::orchestra_cmd;
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=orchestra",
+            "-c",
+            "user.email=orchestra@example.com",
+        ])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .expect("run git");
+    assert!(status.success(), "git {args:?} failed");
+}
+
+/// A bare repository at `<workspace>/remote/widget.git` with one commit.
+fn bare_repo(workspace: &TempDir) -> PathBuf {
+    let src = workspace.path().join("src");
+    fs::create_dir_all(&src).expect("create src");
+    git(&src, &["init", "-q"]);
+    fs::write(src.join("Cargo.toml"), "[package]\nname = \"widget\"\n").expect("write");
+    git(&src, &["add", "-A"]);
+    git(&src, &["commit", "-q", "-m", "initial"]);
+
+    let remote = workspace.path().join("remote");
+    fs::create_dir_all(&remote).expect("create remote");
+    git(&remote, &["clone", "-q", "--bare", "../src", "widget.git"]);
+    remote.join("widget.git")
+}
+
+#[test]
+fn from_git_clones_registers_and_syncs() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let url = bare_repo(&workspace);
+    let dest = workspace.path().join("checkouts");
+
+    orchestra_cmd(home.path())
+        .args(["init", "--from-git"])
+        .arg(&url)
+        .args(["--project", "acme", "--type", "backend", "--dest"])
+        .arg(&dest)
+        .assert()
+        .success()
+        .stdout(contains("Cloned"));
+
+    let clone = dest.join("widget");
+    assert!(clone.join("Cargo.toml").is_file());
+    let codebase = registry::load_codebase_at(
+        home.path(),
+        &ProjectName::from("acme"),
+        &CodebaseName::from("widget"),
+    )
+    .expect("codebase registered");
+    assert_eq!(codebase.path, clone.canonicalize().unwrap());
+    assert!(clone.join("orchestra/controls/CLAUDE.md").is_file());
+}
+
+#[test]
+fn from_git_no_sync_registers_without_writing_agent_files() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let url = bare_repo(&workspace);
+    let dest = workspace.path().join("checkouts");
+
+    orchestra_cmd(home.path())
+        .args(["init", "--from-git"])
+        .arg(&url)
+        .args(["--project", "acme", "--no-sync", "--dest"])
+        .arg(&dest)
+        .assert()
+        .success();
+
+    let clone = dest.join("widget");
+    assert!(clone.join("Cargo.toml").is_file());
+    assert!(!clone.join("orchestra").exists());
+}
+
+#[test]
+fn from_git_existing_destination_skips_clone() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let dest = workspace.path().join("checkouts");
+    fs::create_dir_all(dest.join("widget")).expect("create existing dir");
+
+    orchestra_cmd(home.path())
+        .env("ORCHESTRA_GIT", workspace.path().join("no-such-git"))
+        .args(["init", "--from-git", "git@example.com:acme/widget.git"])
+        .args(["--project", "acme", "--no-sync", "--dest"])
+        .arg(&dest)
+        .assert()
+        .success()
+        .stderr(contains("already exists; registering it without cloning"));
+
+    registry::load_codebase_at(
+        home.path(),
+        &ProjectName::from("acme"),
+        &CodebaseName::from("widget"),
+    )
+    .expect("codebase registered");
+}
+
+#[test]
+fn from_git_clone_failure_reports_git_stderr() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let missing = workspace.path().join("missing.git");
+
+    orchestra_cmd(home.path())
+        .args(["init", "--from-git"])
+        .arg(&missing)
+        .args(["--project", "acme", "--dest"])
+        .arg(workspace.path())
+        .assert()
+        .failure()
+        .stderr(contains("git clone of"))
+        .stderr(contains("does not exist"));
+
+    assert!(!workspace.path().join("missing").exists());
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use std::fs;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+use common::orchestra_cmd;
+
+#[test]
+fn init_inside_registered_codebase_requires_force_overlap() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let repo = workspace.path().join("acme");
+    let web = repo.join("apps").join("web");
+    fs::create_dir_all(&web).expect("create apps/web");
+
+    orchestra_cmd(home.path())
+        .arg("init")
+        .arg(&repo)
+        .args(["--project", "acme"])
+        .assert()
+        .success();
+
+    orchestra_cmd(home.path())
+        .arg("init")
+        .arg(&web)
+        .args(["--project", "acme"])
+        .assert()
+        .failure()
+        .stderr(contains("overlaps codebase 'acme'"))
+        .stderr(contains("--force-overlap"));
+    assert!(!home.path().join(".orchestra/projects/acme/web.yaml").exists());
+
+    orchestra_cmd(home.path())
+        .arg("init")
+        .arg(&web)
+        .args(["--project", "acme", "--force-overlap"])
+        .assert()
+        .success()
+        .stdout(contains("Registered 'web'"));
+}
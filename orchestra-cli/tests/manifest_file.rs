@@ -0,0 +1,80 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use assert_cmd::prelude::*;
+
+use orchestra_core::{
+    registry,
+    types::{CodebaseName, ProjectName},
+};
+use tempfile::TempDir;
+
+use common::orchestra_cmd;
+
+fn init(home: &Path, codebase_dir: &Path, extra: &[&str]) {
+    fs::create_dir_all(codebase_dir).expect("create codebase dir");
+    orchestra_cmd(home)
+        .arg("init")
+        .arg(codebase_dir)
+        .args(["--project", "copnow"])
+        .args(extra)
+        .assert()
+        .success();
+}
+
+#[test]
+fn sync_writes_a_stable_manifest() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = workspace.path().join("copnow_api");
+    init(home.path(), &codebase_dir, &[]);
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+
+    let manifest_path = codebase_dir.join(".orchestra-manifest.json");
+    let first = fs::read_to_string(&manifest_path).expect("manifest written");
+    let manifest: serde_json::Value = serde_json::from_str(&first).expect("parse manifest");
+    let paths: Vec<&str> = manifest["files"]
+        .as_array()
+        .expect("files array")
+        .iter()
+        .map(|entry| entry["path"].as_str().expect("path"))
+        .collect();
+    assert!(paths.contains(&"orchestra/controls/CLAUDE.md"), "{paths:?}");
+    assert!(paths.contains(&"orchestra/pilot.md"), "{paths:?}");
+    assert!(!paths.contains(&".orchestra-manifest.json"), "{paths:?}");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&manifest_path).expect("read"), first);
+}
+
+#[test]
+fn no_manifest_flag_turns_it_off() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = workspace.path().join("copnow_web");
+    init(home.path(), &codebase_dir, &["--no-manifest"]);
+
+    let codebase = registry::load_codebase_at(
+        home.path(),
+        &ProjectName::from("copnow"),
+        &CodebaseName::from("copnow_web"),
+    )
+    .expect("load codebase");
+    assert_eq!(codebase.manifest, Some(false));
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_web"])
+        .assert()
+        .success();
+    assert!(codebase_dir.join("orchestra/pilot.md").exists());
+    assert!(!codebase_dir.join(".orchestra-manifest.json").exists());
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use predicates::str::contains;
+
+use tempfile::TempDir;
+
+use common::init_codebase;
+
+fn orchestra_cmd(home: &Path) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("orchestra"));
+    cmd.env("HOME", home)
+        .env("USERPROFILE", home)
+        .env_remove("RUST_LOG");
+    cmd
+}
+
+fn stdout_of(cmd: &mut Command) -> String {
+    let output = cmd.output().expect("run orchestra");
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).expect("utf-8 stdout")
+}
+
+#[test]
+fn quiet_sync_prints_one_summary_line() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    init_codebase(&home, &workspace, "copnow_web");
+
+    let single = stdout_of(orchestra_cmd(home.path()).args(["--quiet", "sync", "copnow_api"]));
+    assert_eq!(single.lines().count(), 1, "{single}");
+    assert!(single.contains("1 codebase changed"), "{single}");
+
+    let all = stdout_of(orchestra_cmd(home.path()).args(["sync", "--all", "-q"]));
+    assert_eq!(all.lines().count(), 1, "{all}");
+    // copnow_api was synced above, so only copnow_web changes.
+    assert!(all.contains("1 codebase changed"), "{all}");
+}
+
+#[test]
+fn sync_all_reports_progress_per_codebase_on_stderr() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    init_codebase(&home, &workspace, "copnow_web");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "--all"])
+        .assert()
+        .success()
+        .stderr(contains("[1/2] syncing 'copnow_api'"))
+        .stderr(contains("[2/2] syncing 'copnow_web'"))
+        .stdout(contains("syncing").not());
+
+    orchestra_cmd(home.path())
+        .args(["sync", "--all", "--quiet"])
+        .assert()
+        .success()
+        .stderr(contains("syncing").not());
+}
+
+#[test]
+fn verbose_logs_go_to_stderr_and_json_stays_parseable() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["--verbose", "sync", "copnow_api"])
+        .assert()
+        .success()
+        .stderr(contains("unchanged:"))
+        .stdout(contains("unchanged:").not());
+
+    let json = stdout_of(orchestra_cmd(home.path()).args(["status", "--json", "--verbose"]));
+    let parsed: serde_json::Value = serde_json::from_str(&json).expect("status JSON parses");
+    assert_eq!(parsed["codebases"][0]["codebase"], "copnow_api");
+}
+
+#[test]
+fn quiet_and_verbose_conflict() {
+    let home = TempDir::new().expect("home");
+    orchestra_cmd(home.path())
+        .args(["-q", "-v", "status"])
+        .assert()
+        .failure()
+        .stderr(contains("cannot be used with"));
+}
+
+#[test]
+fn quiet_drops_hints_but_keeps_results() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+
+    let loud = stdout_of(orchestra_cmd(home.path()).args(["agent", "list", "copnow_api"]));
+    assert!(loud.contains("Run: orchestra agent add"), "{loud}");
+    let quiet = stdout_of(orchestra_cmd(home.path()).args(["-q", "agent", "list", "copnow_api"]));
+    assert!(!quiet.contains("Run:"), "{quiet}");
+
+    let reset = stdout_of(orchestra_cmd(home.path()).args(["--quiet", "reset", "--confirm"]));
+    assert_eq!(reset.trim(), "✓ Orchestra has been fully reset.");
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{CodebaseName, ProjectName},
+};
+use tempfile::TempDir;
+
+use common::orchestra_cmd;
+
+fn status_of(home: &Path, codebase: &str) -> String {
+    let output = orchestra_cmd(home)
+        .args(["status", "--json"])
+        .assert()
+        .success();
+    let payload: serde_json::Value =
+        serde_json::from_slice(&output.get_output().stdout).expect("parse status json");
+    payload["codebases"]
+        .as_array()
+        .expect("codebases array")
+        .iter()
+        .find(|row| row["codebase"].as_str() == Some(codebase))
+        .and_then(|row| row["status"].as_str())
+        .expect("codebase row")
+        .to_string()
+}
+
+#[test]
+fn sync_writes_under_output_root_and_leaves_codebase_untouched() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = workspace.path().join("oot_api");
+    let output_root = workspace.path().join("generated").join("oot_api");
+    fs::create_dir_all(&codebase_dir).expect("create codebase dir");
+    fs::write(codebase_dir.join("main.rs"), "fn main() {}\n").expect("seed source");
+
+    orchestra_cmd(home.path())
+        .arg("init")
+        .arg(&codebase_dir)
+        .args(["--project", "copnow", "--output-root"])
+        .arg(&output_root)
+        .assert()
+        .success();
+
+    orchestra_cmd(home.path())
+        .args(["sync", "oot_api"])
+        .assert()
+        .success();
+
+    assert!(output_root.join("orchestra/controls/CLAUDE.md").exists());
+    assert!(output_root.join("orchestra/pilot.md").exists());
+
+    let entries: Vec<_> = fs::read_dir(&codebase_dir)
+        .expect("read codebase dir")
+        .map(|e| e.expect("entry").file_name())
+        .collect();
+    assert_eq!(entries, ["main.rs"], "codebase tree must stay untouched");
+
+    assert_eq!(status_of(home.path(), "oot_api"), "current");
+}
+
+#[test]
+fn switching_output_root_warns_about_previous_location() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = workspace.path().join("moving_api");
+    fs::create_dir_all(&codebase_dir).expect("create codebase dir");
+    let project = ProjectName::from("copnow");
+    registry::init_at(codebase_dir.clone(), project.clone(), None, home.path())
+        .expect("init codebase");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "moving_api"])
+        .assert()
+        .success();
+    let old_claude = codebase_dir.join("orchestra/controls/CLAUDE.md");
+    assert!(old_claude.exists());
+
+    let output_root = workspace.path().join("out");
+    let mut codebase =
+        registry::load_codebase_at(home.path(), &project, &CodebaseName::from("moving_api"))
+            .expect("load codebase");
+    codebase.output_root = Some(output_root.clone());
+    registry::save_codebase_at(home.path(), &project, &codebase).expect("save codebase");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "moving_api"])
+        .assert()
+        .success()
+        .stderr(contains("previous output location"))
+        .stderr(contains(old_claude.display().to_string()));
+
+    assert!(output_root.join("orchestra/controls/CLAUDE.md").exists());
+    assert!(old_claude.exists(), "old files are left for the user to remove");
+}
+
+#[test]
+fn offboard_removes_files_under_output_root() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = workspace.path().join("oot_api");
+    let output_root = workspace.path().join("generated").join("oot_api");
+    fs::create_dir_all(&codebase_dir).expect("create codebase dir");
+
+    orchestra_cmd(home.path())
+        .arg("init")
+        .arg(&codebase_dir)
+        .args(["--project", "copnow", "--output-root"])
+        .arg(&output_root)
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["sync", "oot_api"])
+        .assert()
+        .success();
+    let claude = output_root.join("orchestra/controls/CLAUDE.md");
+    let pilot = output_root.join("orchestra/pilot.md");
+    let manifest = output_root.join(".orchestra-manifest.json");
+    assert!(claude.exists() && pilot.exists() && manifest.exists());
+
+    orchestra_cmd(home.path())
+        .arg("offboard")
+        .arg(&codebase_dir)
+        .arg("--yes")
+        .assert()
+        .success();
+
+    assert!(!claude.exists(), "agent output is removed");
+    assert!(!pilot.exists(), "pilot under the output root is removed");
+    assert!(!manifest.exists(), "out-of-tree manifest is removed");
+}
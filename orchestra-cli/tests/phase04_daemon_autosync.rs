@@ -58,10 +58,20 @@ struct DaemonProcess {
 
 impl DaemonProcess {
     fn start(binary: PathBuf, home: PathBuf) -> Self {
+        Self::spawn(binary, home, &["start"])
+    }
+
+    /// Start without the startup sync, for tests that count watcher syncs.
+    fn start_without_startup_sync(binary: PathBuf, home: PathBuf) -> Self {
+        Self::spawn(binary, home, &["start", "--no-startup-sync"])
+    }
+
+    fn spawn(binary: PathBuf, home: PathBuf, daemon_args: &[&str]) -> Self {
         let child = Command::new(&binary)
             .env("HOME", &home)
             .env("USERPROFILE", &home)
-            .args(["daemon", "start"])
+            .arg("daemon")
+            .args(daemon_args)
             .stdin(Stdio::null())
             .stdout(Stdio::null())
             .stderr(Stdio::null())
@@ -199,3 +209,289 @@ fn registry_change_triggers_auto_sync() {
 
     daemon.stop();
 }
+
+fn daemon_status(binary: &Path, home: &Path) -> Option<serde_json::Value> {
+    let output = Command::new(binary)
+        .env("HOME", home)
+        .env("USERPROFILE", home)
+        .args(["daemon", "status"])
+        .output()
+        .ok()?;
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+fn daemon_metrics(binary: &Path, home: &Path) -> Option<serde_json::Value> {
+    daemon_status(binary, home)?.get("metrics").cloned()
+}
+
+fn daemon_codebase_status(binary: &Path, home: &Path, name: &str) -> Option<serde_json::Value> {
+    daemon_status(binary, home)?["codebases"]
+        .as_array()?
+        .iter()
+        .find(|cb| cb["name"] == name)
+        .cloned()
+}
+
+#[test]
+fn watcher_triggered_sync_increments_metrics() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = workspace.path().join("metrics_api");
+    std::fs::create_dir_all(&codebase_dir).expect("mkdir codebase");
+
+    let project = ProjectName::from("copnow");
+    let mut codebase = registry::init_at(
+        codebase_dir.clone(),
+        project.clone(),
+        Some(ProjectType::Backend),
+        home.path(),
+    )
+    .expect("init codebase");
+
+    let binary = orchestra_bin_path();
+    let mut daemon = DaemonProcess::start_without_startup_sync(binary.clone(), home.path().to_path_buf());
+    assert!(
+        wait_until(Duration::from_secs(5), || daemon_running(
+            &binary,
+            home.path()
+        )),
+        "daemon did not report running state in time",
+    );
+
+    let baseline = daemon_metrics(&binary, home.path()).expect("metrics in status payload");
+    assert_eq!(baseline["syncs_started"], 0);
+
+    codebase.projects[0].name = ProjectName::from("metrics-sentinel");
+    registry::save_codebase_at(home.path(), &project, &codebase).expect("save codebase");
+
+    let counted = wait_until(Duration::from_secs(10), || {
+        daemon_metrics(&binary, home.path()).is_some_and(|m| {
+            m["events_received"].as_u64().unwrap_or(0) > 0
+                && m["syncs_started"].as_u64().unwrap_or(0) > 0
+                && m["files_written"].as_u64().unwrap_or(0) > 0
+                && !m["recent_sync_durations_ms"]
+                    .as_array()
+                    .is_none_or(|d| d.is_empty())
+        })
+    });
+    assert!(counted, "watcher-triggered sync did not update daemon metrics");
+
+    daemon.stop();
+}
+
+#[test]
+fn failed_watcher_sync_surfaces_last_error_until_next_valid_save() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = workspace.path().join("retry_api");
+    std::fs::create_dir_all(&codebase_dir).expect("mkdir codebase");
+
+    let project = ProjectName::from("copnow");
+    let mut codebase = registry::init_at(
+        codebase_dir.clone(),
+        project.clone(),
+        Some(ProjectType::Backend),
+        home.path(),
+    )
+    .expect("init codebase");
+    let yaml_path = registry::codebase_path_at(home.path(), &project, &codebase.name);
+
+    let binary = orchestra_bin_path();
+    let mut daemon = DaemonProcess::start(binary.clone(), home.path().to_path_buf());
+    assert!(
+        wait_until(Duration::from_secs(5), || daemon_running(
+            &binary,
+            home.path()
+        )),
+        "daemon did not report running state in time",
+    );
+
+    std::fs::write(&yaml_path, "name: retry_api\npath: [unclosed\n").expect("corrupt yaml");
+    let failed = wait_until(Duration::from_secs(10), || {
+        daemon_codebase_status(&binary, home.path(), "retry_api").is_some_and(|cb| {
+            cb["last_error"].is_string() && cb["consecutive_failures"].as_u64() >= Some(1)
+        })
+    });
+    assert!(failed, "status payload did not report the failed sync");
+
+    codebase.projects[0].name = ProjectName::from("retry-sentinel");
+    registry::save_codebase_at(home.path(), &project, &codebase).expect("save valid codebase");
+    let recovered = wait_until(Duration::from_secs(10), || {
+        daemon_codebase_status(&binary, home.path(), "retry_api").is_some_and(|cb| {
+            cb["last_error"].is_null() && cb["consecutive_failures"] == 0
+        })
+    });
+    assert!(recovered, "valid save did not clear the last sync error");
+
+    daemon.stop();
+}
+
+#[test]
+fn deleting_codebase_yaml_drops_it_from_daemon_status() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let project = ProjectName::from("copnow");
+
+    for name in ["kept_api", "dropped_api"] {
+        let codebase_dir = workspace.path().join(name);
+        std::fs::create_dir_all(&codebase_dir).expect("mkdir codebase");
+        registry::init_at(
+            codebase_dir,
+            project.clone(),
+            Some(ProjectType::Backend),
+            home.path(),
+        )
+        .expect("init codebase");
+    }
+    let yaml_path =
+        registry::codebase_path_at(home.path(), &project, &CodebaseName::from("dropped_api"));
+
+    let binary = orchestra_bin_path();
+    let mut daemon = DaemonProcess::start_without_startup_sync(binary.clone(), home.path().to_path_buf());
+    assert!(
+        wait_until(Duration::from_secs(5), || daemon_codebase_status(
+            &binary,
+            home.path(),
+            "dropped_api"
+        )
+        .is_some()),
+        "daemon did not report the registered codebase in time",
+    );
+
+    std::fs::remove_file(&yaml_path).expect("delete codebase yaml");
+    let dropped = wait_until(Duration::from_secs(10), || {
+        daemon_status(&binary, home.path()).is_some_and(|status| {
+            let names: Vec<&str> = status["codebases"]
+                .as_array()
+                .map(|cbs| cbs.iter().filter_map(|cb| cb["name"].as_str()).collect())
+                .unwrap_or_default();
+            names == ["kept_api"]
+        })
+    });
+    assert!(dropped, "deleted codebase is still listed in daemon status");
+    assert_eq!(
+        daemon_metrics(&binary, home.path()).expect("metrics")["syncs_started"],
+        0,
+        "a deletion must not trigger a sync"
+    );
+
+    daemon.stop();
+}
+
+#[test]
+fn stop_waits_until_socket_is_released() {
+    let home = TempDir::new().expect("home");
+    let binary = orchestra_bin_path();
+    let mut daemon = DaemonProcess::start(binary.clone(), home.path().to_path_buf());
+    assert!(
+        wait_until(Duration::from_secs(5), || daemon_running(
+            &binary,
+            home.path()
+        )),
+        "daemon did not report running state in time",
+    );
+
+    let output = Command::new(&binary)
+        .env("HOME", home.path())
+        .env("USERPROFILE", home.path())
+        .args(["daemon", "stop"])
+        .output()
+        .expect("run daemon stop");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("daemon stopped"));
+
+    let socket = orchestra_daemon::paths::socket_path(home.path());
+    assert!(
+        !socket.exists() || std::os::unix::net::UnixStream::connect(&socket).is_err(),
+        "socket still accepts connections after stop returned"
+    );
+
+    daemon.stop();
+}
+
+#[test]
+fn restart_replaces_running_daemon() {
+    let home = TempDir::new().expect("home");
+    let binary = orchestra_bin_path();
+    let mut first = DaemonProcess::start(binary.clone(), home.path().to_path_buf());
+    assert!(
+        wait_until(Duration::from_secs(5), || daemon_running(
+            &binary,
+            home.path()
+        )),
+        "daemon did not report running state in time",
+    );
+
+    let mut restarted = DaemonProcess::spawn(binary.clone(), home.path().to_path_buf(), &["restart"]);
+    let replaced = wait_until(Duration::from_secs(10), || {
+        matches!(first.child.try_wait(), Ok(Some(_))) && daemon_running(&binary, home.path())
+    });
+    assert!(replaced, "restart did not stop the old daemon and start a new one");
+    assert!(
+        matches!(restarted.child.try_wait(), Ok(None)),
+        "restarted daemon should keep running in the foreground"
+    );
+
+    restarted.stop();
+    first.stop();
+}
+
+#[test]
+fn status_via_daemon_prints_the_local_status() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    for (project, name) in [
+        ("copnow", "copnow_api"),
+        ("copnow", "copnow_web"),
+        ("infra", "tools"),
+    ] {
+        let codebase_dir = workspace.path().join(name);
+        std::fs::create_dir_all(&codebase_dir).expect("mkdir codebase");
+        registry::init_at(
+            codebase_dir,
+            ProjectName::from(project),
+            Some(ProjectType::Backend),
+            home.path(),
+        )
+        .expect("init codebase");
+    }
+
+    let binary = orchestra_bin_path();
+    let mut daemon =
+        DaemonProcess::start_without_startup_sync(binary.clone(), home.path().to_path_buf());
+    assert!(
+        wait_until(Duration::from_secs(5), || daemon_running(
+            &binary,
+            home.path()
+        )),
+        "daemon did not report running state in time",
+    );
+
+    let status = |args: &[&str]| {
+        let output = Command::new(&binary)
+            .env("HOME", home.path())
+            .env("USERPROFILE", home.path())
+            .arg("status")
+            .args(args)
+            .output()
+            .expect("run status");
+        assert!(output.status.success(), "status {args:?} failed");
+        output
+    };
+    for args in [&["--json"][..], &[], &["--json", "--project", "infra"]] {
+        let local = status(args);
+        let via_daemon = status(&[args, &["--via-daemon"]].concat());
+        assert_eq!(
+            String::from_utf8_lossy(&via_daemon.stdout),
+            String::from_utf8_lossy(&local.stdout),
+            "status {args:?} differs when answered by the daemon",
+        );
+        assert!(
+            via_daemon.stderr.is_empty(),
+            "daemon did not answer: {}",
+            String::from_utf8_lossy(&via_daemon.stderr)
+        );
+    }
+
+    daemon.stop();
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use std::thread::sleep;
@@ -166,8 +167,10 @@ fn phase05_writeback_end_to_end_propagation() {
         description: Some("should be auto-completed by writeback".to_owned()),
         subtasks: vec![],
         notes: vec![],
+        assigned_agent: None,
         created_at: now,
         updated_at: now,
+        extra: BTreeMap::new(),
     });
     codebase.updated_at = now;
     registry::save_codebase_at(home.path(), &project, &codebase).expect("save codebase with task");
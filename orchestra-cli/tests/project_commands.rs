@@ -0,0 +1,266 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Stdio;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::ProjectName,
+};
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+fn set_command(home: &TempDir, name: &str, run: &str) {
+    orchestra_cmd(home.path())
+        .args(["command", "set", "copnow_api", name, run])
+        .assert()
+        .success();
+}
+
+#[test]
+fn set_commands_render_sorted_into_agent_files() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+    set_command(&home, "test", "cargo test --workspace");
+    set_command(&home, "lint", "cargo clippy --workspace");
+    set_command(&home, "build", "cargo build");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    let claude = codebase_dir.join("orchestra/controls/CLAUDE.md");
+    let first = fs::read_to_string(&claude).expect("read CLAUDE.md");
+    let build = first.find("**build:** `cargo build`").expect("build listed");
+    let lint = first.find("**lint:** `cargo clippy --workspace`").expect("lint listed");
+    let test = first.find("**test:** `cargo test --workspace`").expect("test listed");
+    assert!(build < lint && lint < test, "commands not sorted:\n{first}");
+    assert!(!first.contains("Refer to the README or project root"));
+    let agents = fs::read_to_string(codebase_dir.join("orchestra/controls/AGENTS.md"))
+        .expect("read AGENTS.md");
+    assert!(agents.contains("**test:** `cargo test --workspace`"));
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&claude).expect("read CLAUDE.md"), first);
+
+    orchestra_cmd(home.path())
+        .args(["command", "list", "copnow_api"])
+        .assert()
+        .success()
+        .stdout(contains("build  cargo build"));
+}
+
+#[test]
+fn unset_removes_the_command() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    set_command(&home, "test", "pytest");
+
+    orchestra_cmd(home.path())
+        .args(["command", "unset", "copnow_api", "test"])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["command", "list", "copnow_api"])
+        .assert()
+        .success()
+        .stdout(contains("No commands registered"));
+    orchestra_cmd(home.path())
+        .args(["command", "unset", "copnow_api", "test"])
+        .assert()
+        .failure()
+        .stderr(contains("no command 'test'"));
+}
+
+#[test]
+fn invalid_command_name_is_rejected() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["command", "set", "copnow_api", "run tests", "pytest"])
+        .assert()
+        .failure()
+        .stderr(contains("invalid command name"));
+}
+
+#[test]
+fn init_seed_commands_uses_detected_stack() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = workspace.path().join("engine");
+    fs::create_dir_all(&codebase_dir).expect("create codebase dir");
+    fs::write(
+        codebase_dir.join("Cargo.toml"),
+        "[package]\nname = \"engine\"\n",
+    )
+    .expect("write Cargo.toml");
+
+    orchestra_cmd(home.path())
+        .arg("init")
+        .arg(&codebase_dir)
+        .args(["--project", "tools", "--seed-commands"])
+        .assert()
+        .success()
+        .stdout(contains("Seeded 3 command(s)"));
+    orchestra_cmd(home.path())
+        .args(["command", "list", "engine"])
+        .assert()
+        .success()
+        .stdout(contains("cargo test --workspace"));
+}
+
+#[test]
+fn unsafe_names_are_rejected_before_touching_the_registry() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["project", "add", "../../etc", "--project", "copnow"])
+        .assert()
+        .failure()
+        .stderr(contains("name cannot contain a path separator"));
+    orchestra_cmd(home.path())
+        .args(["project", "add", "payments", "--project", ".hidden"])
+        .assert()
+        .failure()
+        .stderr(contains("name cannot start with a dot"));
+    orchestra_cmd(home.path())
+        .arg("init")
+        .arg(workspace.path().join("copnow_api"))
+        .args(["--project", "project"])
+        .assert()
+        .failure()
+        .stderr(contains("reserved"));
+
+    let projects = registry::list_project_names_at(home.path()).expect("projects");
+    assert_eq!(projects, vec![ProjectName::from("copnow")]);
+    assert_eq!(registry::list_codebases_at(home.path()).expect("list").len(), 1);
+}
+
+#[test]
+fn project_remove_refuses_non_empty_and_unknown_projects() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["project", "remove", "copnow", "--yes"])
+        .assert()
+        .failure()
+        .stderr(contains("pass --cascade"))
+        .stderr(contains("still has 1 codebase(s): copnow_api"));
+    orchestra_cmd(home.path())
+        .args(["project", "remove", "nope", "--cascade", "--yes"])
+        .assert()
+        .failure()
+        .stderr(contains("no project named 'nope'"));
+    orchestra_cmd(home.path())
+        .args(["project", "remove", "copnow", "--cascade"])
+        .stdin(Stdio::null())
+        .assert()
+        .success()
+        .stdout(contains("copnow_api.yaml"))
+        .stdout(contains("Project removal cancelled."));
+
+    assert_eq!(registry::list_codebases_at(home.path()).expect("list").len(), 1);
+}
+
+#[test]
+fn project_remove_cascade_purges_registry_hashes_and_files() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+    fs::write(codebase_dir.join("main.rs"), "fn main() {}\n").expect("write source");
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    let store = home.path().join(".orchestra/hashes/copnow_api.json");
+    assert!(store.exists());
+    assert!(codebase_dir.join("orchestra/controls/CLAUDE.md").exists());
+
+    orchestra_cmd(home.path())
+        .args([
+            "project",
+            "remove",
+            "copnow",
+            "--cascade",
+            "--purge-files",
+            "--yes",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("copnow_api.json"))
+        .stdout(contains("orchestra/"))
+        .stdout(contains(".orchestra-manifest.json"))
+        .stdout(contains("Removed project 'copnow' and 1 codebase(s)"));
+
+    assert!(registry::list_project_names_at(home.path())
+        .expect("projects")
+        .is_empty());
+    assert!(!store.exists());
+    let left: Vec<_> = fs::read_dir(&codebase_dir)
+        .expect("read codebase dir")
+        .map(|entry| entry.expect("entry").file_name())
+        .collect();
+    assert_eq!(left, ["main.rs"], "Orchestra files left behind: {left:?}");
+}
+
+#[test]
+fn project_remove_purge_refuses_a_codebase_at_the_filesystem_root() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    let project = ProjectName::from("copnow");
+    let mut codebase = registry::load_codebase_at(home.path(), &project, &"copnow_api".into())
+        .expect("load codebase");
+    codebase.path = PathBuf::from("/");
+    registry::save_codebase_at(home.path(), &project, &codebase).expect("save codebase");
+
+    orchestra_cmd(home.path())
+        .args([
+            "project",
+            "remove",
+            "copnow",
+            "--cascade",
+            "--purge-files",
+            "--yes",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("filesystem root"));
+
+    assert_eq!(registry::list_codebases_at(home.path()).expect("list").len(), 1);
+}
+
+#[test]
+fn project_remove_cascade_keeps_files_without_purge() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+
+    orchestra_cmd(home.path())
+        .args(["project", "remove", "copnow", "--cascade", "--yes"])
+        .assert()
+        .success();
+
+    assert!(registry::list_codebases_at(home.path()).expect("list").is_empty());
+    assert!(codebase_dir.join("orchestra/controls/CLAUDE.md").exists());
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use assert_cmd::prelude::*;
+use chrono::Utc;
+use predicates::str::contains;
+use serde_json::Value;
+
+use orchestra_core::{
+    registry,
+    types::{AgentConfig, ProjectName, ProjectType, Task, TaskId, TaskStatus},
+};
+use tempfile::TempDir;
+
+use common::orchestra_cmd;
+
+fn init_codebase(home: &TempDir, workspace: &TempDir, project: &str, name: &str) {
+    let codebase_dir = workspace.path().join(name);
+    fs::create_dir_all(&codebase_dir).expect("create codebase dir");
+    registry::init_at(
+        codebase_dir,
+        ProjectName::from(project),
+        Some(ProjectType::Frontend),
+        home.path(),
+    )
+    .expect("init codebase");
+}
+
+fn list_json(home: &TempDir, extra: &[&str]) -> Value {
+    let output = orchestra_cmd(home.path())
+        .args(["project", "list", "--json"])
+        .args(extra)
+        .output()
+        .expect("run orchestra");
+    assert!(output.status.success(), "{output:?}");
+    serde_json::from_slice(&output.stdout).expect("json stdout")
+}
+
+/// Two projects: `acme` with `acme_web` (four tasks, one in a status this
+/// version does not know, and an agent) and
+/// `copnow` with `copnow_api`, whose directory has been deleted.
+fn two_projects(home: &TempDir, workspace: &TempDir) {
+    init_codebase(home, workspace, "acme", "acme_web");
+    init_codebase(home, workspace, "copnow", "copnow_api");
+    fs::remove_dir_all(workspace.path().join("copnow_api")).expect("remove copnow_api");
+
+    let acme = ProjectName::from("acme");
+    let (_, mut web) = registry::list_codebases_at(home.path())
+        .expect("list")
+        .into_iter()
+        .find(|(project, _)| project == &acme)
+        .expect("acme_web");
+    let now = Utc::now();
+    for (id, status) in [
+        ("t1", TaskStatus::Pending),
+        ("t2", TaskStatus::Done),
+        ("t3", TaskStatus::InProgress),
+        ("t4", TaskStatus::Unknown("archived".to_string())),
+    ] {
+        web.projects[0].tasks.push(Task {
+            id: TaskId::from(id),
+            title: id.to_string(),
+            status,
+            description: None,
+            subtasks: Vec::new(),
+            notes: Vec::new(),
+            assigned_agent: None,
+            created_at: now,
+            updated_at: now,
+            extra: BTreeMap::new(),
+        });
+    }
+    web.projects[0].agents.push(AgentConfig {
+        agent_id: "reviewer".to_string(),
+        entry_point: "AGENT/reviewer.md".into(),
+        skills: None,
+    });
+    registry::save_codebase_at(home.path(), &acme, &web).expect("save acme_web");
+}
+
+#[test]
+fn json_lists_projects_codebases_and_dangling_paths() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    two_projects(&home, &workspace);
+
+    let json = list_json(&home, &[]);
+    let projects = json.as_array().expect("array");
+    let names: Vec<&str> = projects
+        .iter()
+        .map(|p| p["project"].as_str().expect("project name"))
+        .collect();
+    assert_eq!(names, ["acme", "copnow"]);
+
+    let web = &projects[0]["codebases"][0];
+    assert_eq!(web["name"], "acme_web");
+    assert_eq!(
+        web["path"].as_str().expect("path"),
+        workspace
+            .path()
+            .join("acme_web")
+            .canonicalize()
+            .unwrap()
+            .to_str()
+            .unwrap()
+    );
+    assert_eq!(web["path_exists"], true);
+    let project = &web["projects"][0];
+    assert_eq!(project["name"], "acme_web");
+    assert_eq!(project["type"], "frontend");
+    assert_eq!(project["agent_count"], 1);
+    assert_eq!(
+        project["task_counts"],
+        serde_json::json!({
+            "pending": 1, "in_progress": 1, "blocked": 0, "done": 1, "cancelled": 0,
+            "unknown": 1
+        })
+    );
+
+    let api = &projects[1]["codebases"][0];
+    assert_eq!(api["name"], "copnow_api");
+    assert_eq!(api["path_exists"], false);
+    assert_eq!(api["projects"][0]["agent_count"], 0);
+}
+
+#[test]
+fn project_filter_limits_both_outputs() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    two_projects(&home, &workspace);
+
+    let json = list_json(&home, &["--project", "copnow"]);
+    let projects = json.as_array().expect("array");
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0]["project"], "copnow");
+
+    let output = orchestra_cmd(home.path())
+        .args(["project", "list", "-p", "acme"])
+        .output()
+        .expect("run orchestra");
+    let stdout = String::from_utf8(output.stdout).expect("utf-8");
+    assert!(stdout.contains("Project: acme"), "{stdout}");
+    assert!(!stdout.contains("copnow"), "{stdout}");
+
+    orchestra_cmd(home.path())
+        .args(["project", "list", "--json", "--project", "nope"])
+        .assert()
+        .failure()
+        .stderr(contains("no project named 'nope'"));
+}
+
+#[test]
+fn empty_registry_is_an_empty_json_array() {
+    let home = TempDir::new().expect("home");
+
+    let output = orchestra_cmd(home.path())
+        .args(["project", "list", "--json"])
+        .output()
+        .expect("run orchestra");
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        String::from_utf8(output.stdout).expect("utf-8").trim(),
+        "[]"
+    );
+
+    orchestra_cmd(home.path())
+        .args(["project", "list"])
+        .assert()
+        .success()
+        .stdout(contains("Run: orchestra init <path> --project <name>"));
+}
@@ -62,6 +62,7 @@ fn offboard_recent_restores_original_files_and_removes_orchestra_dir() {
         "onboard failed: {}",
         String::from_utf8_lossy(&onboard.stderr)
     );
+    assert!(codebase.join(".orchestra-manifest.json").exists());
 
     let offboard = Command::new(&binary)
         .env("HOME", home.path())
@@ -78,6 +79,7 @@ fn offboard_recent_restores_original_files_and_removes_orchestra_dir() {
         String::from_utf8_lossy(&offboard.stderr)
     );
 
+    assert!(!codebase.join(".orchestra-manifest.json").exists());
     assert!(!codebase.join("orchestra").exists());
     assert_eq!(
         std::fs::read_to_string(codebase.join("CLAUDE.md")).expect("read restored claude"),
@@ -119,6 +121,7 @@ fn reset_restore_backups_removes_project_orchestra_dirs_and_global_state() {
         "onboard failed: {}",
         String::from_utf8_lossy(&onboard.stderr)
     );
+    assert!(codebase.join(".orchestra-manifest.json").exists());
 
     let reset = Command::new(&binary)
         .env("HOME", home.path())
@@ -134,6 +137,7 @@ fn reset_restore_backups_removes_project_orchestra_dirs_and_global_state() {
         String::from_utf8_lossy(&reset.stderr)
     );
 
+    assert!(!codebase.join(".orchestra-manifest.json").exists());
     assert!(!codebase.join("orchestra").exists());
     assert_eq!(
         std::fs::read_to_string(codebase.join("CLAUDE.md")).expect("read restored file"),
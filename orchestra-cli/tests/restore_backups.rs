@@ -0,0 +1,71 @@
+mod common;
+
+use std::fs;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+fn backup_count(home: &TempDir) -> usize {
+    orchestra_sync::edit_backup::list_at(home.path(), "copnow_api")
+        .expect("list backups")
+        .len()
+}
+
+#[test]
+fn overwritten_edit_can_be_listed_and_restored() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+
+    let claude = codebase_dir.join("orchestra/controls/CLAUDE.md");
+    fs::write(&claude, "# my notes\n").expect("hand-edit CLAUDE.md");
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "overwrote locally-modified CLAUDE.md — previous version saved to",
+        ));
+    assert_ne!(fs::read_to_string(&claude).expect("read"), "# my notes\n");
+    assert_eq!(backup_count(&home), 1);
+
+    orchestra_cmd(home.path())
+        .args(["restore", "copnow_api", "--list"])
+        .assert()
+        .success()
+        .stdout(contains("orchestra/controls/CLAUDE.md"));
+
+    orchestra_cmd(home.path())
+        .args(["restore", "copnow_api", "CLAUDE.md"])
+        .assert()
+        .success()
+        .stdout(contains("Restored"));
+    assert_eq!(fs::read_to_string(&claude).expect("read"), "# my notes\n");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--no-backup"])
+        .assert()
+        .success();
+    assert_eq!(backup_count(&home), 1, "--no-backup skips the copy");
+}
+
+#[test]
+fn restore_without_backups_fails() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["restore", "copnow_api", "CLAUDE.md"])
+        .assert()
+        .failure()
+        .stderr(contains("no backup of 'CLAUDE.md'"));
+}
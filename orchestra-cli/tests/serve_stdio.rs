@@ -0,0 +1,63 @@
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use orchestra_core::{
+    registry,
+    types::{ProjectName, ProjectType},
+};
+use tempfile::TempDir;
+
+fn serve(home: &TempDir, input: &str) -> Vec<serde_json::Value> {
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin!("orchestra"))
+        .args(["serve", "--stdio"])
+        .env("HOME", home.path())
+        .env("USERPROFILE", home.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("spawn orchestra serve");
+    child
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all(input.as_bytes())
+        .expect("write requests");
+    let output = child.wait_with_output().expect("wait for serve");
+    assert!(output.status.success(), "serve exited with {}", output.status);
+
+    String::from_utf8(output.stdout)
+        .expect("stdout utf8")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("response json"))
+        .collect()
+}
+
+#[test]
+fn serve_stdio_answers_requests_without_a_daemon() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = workspace.path().join("copnow_api");
+    fs::create_dir_all(&codebase_dir).expect("create codebase dir");
+    registry::init_at(
+        codebase_dir.clone(),
+        ProjectName::from("copnow"),
+        Some(ProjectType::Backend),
+        home.path(),
+    )
+    .expect("init codebase");
+
+    let responses = serve(
+        &home,
+        "{\"cmd\":\"sync\",\"codebase\":\"copnow_api\"}\n\
+         not json\n\
+         {\"cmd\":\"status\"}\n",
+    );
+
+    assert_eq!(responses.len(), 3, "{responses:?}");
+    assert_eq!(responses[0]["ok"], true, "{}", responses[0]);
+    assert!(codebase_dir.join("orchestra/controls/CLAUDE.md").exists());
+    assert_eq!(responses[1]["ok"], false);
+    assert_eq!(responses[2]["data"]["codebases"][0]["name"], "copnow_api");
+    assert_eq!(responses[2]["data"]["codebases"][0]["status"], "current");
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{CodebaseName, ProjectName, ProjectType},
+};
+use tempfile::TempDir;
+
+use common::orchestra_cmd;
+
+fn next_app(workspace: &TempDir) -> PathBuf {
+    let repo = workspace.path().join("web");
+    fs::create_dir_all(&repo).expect("create repo");
+    fs::write(
+        repo.join("package.json"),
+        r#"{"dependencies": {"next": "14.0.0", "react": "18.0.0"}}"#,
+    )
+    .expect("write package.json");
+    repo
+}
+
+fn registry_file(home: &TempDir) -> PathBuf {
+    home.path().join(".orchestra/projects/copnow/web.yaml")
+}
+
+#[test]
+fn setup_registers_and_syncs_in_one_step() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let repo = next_app(&workspace);
+
+    orchestra_cmd(home.path())
+        .arg("setup")
+        .arg(&repo)
+        .args(["--project", "copnow", "--detect", "--yes"])
+        .assert()
+        .success()
+        .stdout(contains("✓ Registered 'web' under project 'copnow'"))
+        .stdout(contains("Next steps:"))
+        .stdout(contains("orchestra daemon install"));
+
+    assert!(registry_file(&home).is_file());
+    assert!(repo.join("orchestra/controls/CLAUDE.md").is_file());
+    let codebase = registry::load_codebase_at(
+        home.path(),
+        &ProjectName::from("copnow"),
+        &CodebaseName::from("web"),
+    )
+    .expect("load codebase");
+    assert_eq!(codebase.projects[0].project_type, ProjectType::Frontend);
+    assert_eq!(codebase.projects[0].framework.as_deref(), Some("Next.js"));
+
+    // Init is idempotent, so setting up again only re-syncs.
+    orchestra_cmd(home.path())
+        .arg("setup")
+        .arg(&repo)
+        .args(["--project", "copnow", "--type", "frontend"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn setup_dry_run_writes_nothing() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let repo = next_app(&workspace);
+
+    orchestra_cmd(home.path())
+        .arg("setup")
+        .arg(&repo)
+        .args(["--project", "copnow", "--detect", "--yes", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Would register 'web' under project 'copnow' as frontend",
+        ))
+        .stdout(contains("Nothing was written"));
+
+    assert!(!registry_file(&home).exists());
+    assert!(!repo.join("orchestra").exists());
+}
+
+#[test]
+fn failed_sync_keeps_the_codebase_registered() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let repo = next_app(&workspace);
+    // Generated files go under `orchestra/`, which cannot be a directory.
+    fs::write(repo.join("orchestra"), "in the way").expect("block output dir");
+
+    orchestra_cmd(home.path())
+        .arg("setup")
+        .arg(&repo)
+        .args(["--project", "copnow", "--type", "frontend"])
+        .assert()
+        .failure()
+        .stderr(contains("'web' stays registered; run `orchestra sync web`"));
+
+    assert!(registry_file(&home).is_file());
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use std::fs;
+
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use predicates::str::contains;
+
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+#[test]
+fn catalog_description_is_rendered_into_claude_md() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+    let description = "Write reversible migrations and never edit an applied one.";
+
+    orchestra_cmd(home.path())
+        .args(["skill", "add", "sql", "--title", "SQL", "--description", description])
+        .assert()
+        .success()
+        .stdout(contains("Catalogued skill 'sql'"));
+    let catalog_file = home.path().join(".orchestra/skills/sql.md");
+    assert_eq!(
+        fs::read_to_string(&catalog_file).expect("catalog file"),
+        format!("# SQL\n\n{description}\n")
+    );
+
+    orchestra_cmd(home.path())
+        .args(["skill", "list"])
+        .assert()
+        .success()
+        .stdout(contains("sql").and(contains(description)));
+
+    orchestra_cmd(home.path())
+        .args(["agent", "add", "copnow_api", "migrations", "--skill", "sql"])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success()
+        .stderr(contains("missing from the catalog").not());
+
+    let claude_md = fs::read_to_string(codebase_dir.join("orchestra/controls/CLAUDE.md"))
+        .expect("CLAUDE.md");
+    assert!(claude_md.contains(&format!("- {description}")), "{claude_md}");
+}
+
+#[test]
+fn uncatalogued_skill_renders_id_and_warns() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["agent", "add", "copnow_api", "migrations", "--skill", "graphql"])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success()
+        .stderr(contains("Skills missing from the catalog").and(contains("graphql")));
+
+    let claude_md = fs::read_to_string(codebase_dir.join("orchestra/controls/CLAUDE.md"))
+        .expect("CLAUDE.md");
+    assert!(claude_md.contains("- graphql"), "{claude_md}");
+}
+
+#[test]
+fn skill_add_refuses_to_overwrite_without_force() {
+    let home = TempDir::new().expect("home");
+
+    orchestra_cmd(home.path())
+        .args(["skill", "add", "rust", "--description", "Idiomatic Rust."])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["skill", "add", "rust", "--description", "Other."])
+        .assert()
+        .failure()
+        .stderr(contains("already catalogued"));
+    orchestra_cmd(home.path())
+        .args(["skill", "add", "rust", "--description", "Other.", "--force"])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["skill", "add", "../escape", "--description", "Nope."])
+        .assert()
+        .failure()
+        .stderr(contains("invalid skill id"));
+}
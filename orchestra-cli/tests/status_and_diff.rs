@@ -1,11 +1,13 @@
+mod common;
+
 use std::collections::{BTreeSet, HashMap};
 use std::fs;
-use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::path::PathBuf;
 use std::thread::sleep;
 use std::time::Duration;
 
 use assert_cmd::prelude::*;
+use predicates::prelude::*;
 use predicates::str::contains;
 
 use orchestra_core::{
@@ -14,11 +16,7 @@ use orchestra_core::{
 };
 use tempfile::TempDir;
 
-fn orchestra_cmd(home: &Path) -> Command {
-    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("orchestra"));
-    cmd.env("HOME", home).env("USERPROFILE", home);
-    cmd
-}
+use common::orchestra_cmd;
 
 fn init_codebase(
     home: &TempDir,
@@ -85,6 +83,122 @@ fn diff_accuracy_on_registry_change() {
     );
 }
 
+#[test]
+fn diff_all_summarises_only_changed_codebases() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let copnow = ProjectName::from("copnow");
+    let tools = ProjectName::from("tools");
+    init_codebase(&home, &workspace, &copnow, "copnow_api");
+    init_codebase(&home, &workspace, &copnow, "copnow_web");
+    init_codebase(&home, &workspace, &tools, "tools_cli");
+    orchestra_cmd(home.path())
+        .args(["sync", "--all"])
+        .assert()
+        .success();
+
+    let codebase_name = CodebaseName::from("copnow_web");
+    let mut codebase =
+        registry::load_codebase_at(home.path(), &copnow, &codebase_name).expect("load codebase");
+    codebase.projects[0].name = ProjectName::from("renamed-web");
+    registry::save_codebase_at(home.path(), &copnow, &codebase).expect("save codebase");
+
+    let assert = orchestra_cmd(home.path())
+        .args(["diff", "--all"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("stdout utf8");
+
+    assert!(stdout.contains("=== copnow_web"), "changed codebase missing:\n{stdout}");
+    assert!(!stdout.contains("=== copnow_api"));
+    assert!(!stdout.contains("=== tools_cli"));
+    let files = stdout.lines().filter(|line| line.starts_with("--- a/")).count();
+    let added = stdout
+        .lines()
+        .filter(|line| line.starts_with('+') && !line.starts_with("+++ b/"))
+        .count();
+    let removed = stdout
+        .lines()
+        .filter(|line| line.starts_with('-') && !line.starts_with("--- a/"))
+        .count();
+    assert!(files > 1 && added > 0 && removed > 0);
+    let summary = format!(
+        "1 of 3 codebases would change, {files} files, +{added}/-{removed} lines"
+    );
+    assert!(stdout.contains(&summary), "expected '{summary}' in:\n{stdout}");
+
+    orchestra_cmd(home.path())
+        .args(["diff", "--project", "tools", "--verbose"])
+        .assert()
+        .success()
+        .stdout(contains("=== tools_cli — no differences"))
+        .stdout(contains("0 of 1 codebase would change, 0 files, +0/-0 lines"));
+}
+
+#[test]
+fn diff_stat_lists_per_file_counts_without_hunks() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let project = ProjectName::from("copnow");
+    init_codebase(&home, &workspace, &project, "copnow_api");
+    sync_codebase_via_cli(&home, "copnow_api");
+
+    let codebase_name = CodebaseName::from("copnow_api");
+    let mut codebase =
+        registry::load_codebase_at(home.path(), &project, &codebase_name).expect("load codebase");
+    codebase.projects[0].name = ProjectName::from("renamed-api");
+    registry::save_codebase_at(home.path(), &project, &codebase).expect("save codebase");
+
+    let assert = orchestra_cmd(home.path())
+        .args(["diff", "copnow_api", "--stat"])
+        .assert()
+        .success()
+        .stdout(contains("orchestra/controls/CLAUDE.md  +"));
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("stdout utf8");
+    assert!(!stdout.contains("@@"), "--stat printed hunks:\n{stdout}");
+    assert!(
+        !stdout.contains("\x1b["),
+        "piped output is colored:\n{stdout}"
+    );
+
+    let hunk_lengths = |context: &str| -> usize {
+        let assert = orchestra_cmd(home.path())
+            .args(["diff", "copnow_api", "--context", context])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("stdout utf8");
+        stdout.lines().filter(|line| line.starts_with(' ')).count()
+    };
+    assert_eq!(hunk_lengths("0"), 0);
+    assert!(hunk_lengths("5") > hunk_lengths("1"));
+}
+
+#[test]
+fn piped_diff_is_a_plain_unified_diff() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let project = ProjectName::from("copnow");
+    init_codebase(&home, &workspace, &project, "copnow_api");
+    sync_codebase_via_cli(&home, "copnow_api");
+
+    let codebase_name = CodebaseName::from("copnow_api");
+    let mut codebase =
+        registry::load_codebase_at(home.path(), &project, &codebase_name).expect("load codebase");
+    codebase.projects[0].name = ProjectName::from("renamed-api");
+    registry::save_codebase_at(home.path(), &project, &codebase).expect("save codebase");
+
+    let assert = orchestra_cmd(home.path())
+        .args(["diff", "copnow_api"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("stdout utf8");
+    assert!(
+        stdout.starts_with("--- "),
+        "piped diff starts with a header line:\n{stdout}"
+    );
+    assert!(!stdout.contains("CLAUDE.md  +"), "{stdout}");
+}
+
 #[test]
 fn status_json_includes_all_codebases_with_expected_staleness_and_schema() {
     let home = TempDir::new().expect("home");
@@ -134,7 +248,7 @@ fn status_json_includes_all_codebases_with_expected_staleness_and_schema() {
         .keys()
         .cloned()
         .collect();
-    let expected_top: BTreeSet<String> = ["summary", "codebases"]
+    let expected_top: BTreeSet<String> = ["summary", "codebases", "projects"]
         .into_iter()
         .map(str::to_string)
         .collect();
@@ -228,3 +342,234 @@ fn sync_repairs_modified_managed_files() {
         .expect("repair_api row");
     assert_eq!(repair_row["status"].as_str(), Some("current"));
 }
+
+#[test]
+fn status_deep_catches_mtime_preserving_edit() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let project = ProjectName::from("copnow");
+
+    let codebase_dir = init_codebase(&home, &workspace, &project, "deep_api");
+    sync_codebase_via_cli(&home, "deep_api");
+
+    let target = codebase_dir.join("orchestra/controls/CLAUDE.md");
+    let original = fs::read_to_string(&target).expect("read baseline");
+    let mtime = filetime::FileTime::from_last_modification_time(
+        &fs::metadata(&target).expect("metadata"),
+    );
+    let edited = original.replacen("deep_api", "peed_api", 1);
+    assert_ne!(edited, original);
+    fs::write(&target, edited).expect("edit");
+    filetime::set_file_mtime(&target, mtime).expect("restore mtime");
+
+    let status_of = |args: &[&str]| {
+        let output = orchestra_cmd(home.path()).args(args).assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone()).expect("stdout utf8");
+        let payload: serde_json::Value = serde_json::from_str(&stdout).expect("parse status json");
+        payload["codebases"][0]["status"]
+            .as_str()
+            .expect("status")
+            .to_string()
+    };
+    assert_eq!(status_of(&["status", "--json"]), "current");
+    assert_eq!(status_of(&["status", "--json", "--deep"]), "modified");
+}
+
+#[test]
+fn status_detailed_breaks_down_staleness_per_agent() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let project = ProjectName::from("copnow");
+
+    let codebase_dir = init_codebase(&home, &workspace, &project, "rules_api");
+    init_codebase(&home, &workspace, &project, "other_api");
+    sync_codebase_via_cli(&home, "rules_api");
+    fs::write(
+        codebase_dir.join("orchestra/controls/.cursor/rules/orchestra.mdc"),
+        "manual rules\n",
+    )
+    .expect("modify cursor rules");
+
+    let assert = orchestra_cmd(home.path())
+        .args(["status", "--json", "--detailed", "rules_api"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("stdout utf8");
+    let payload: serde_json::Value = serde_json::from_str(&stdout).expect("parse status json");
+    let rows = payload["codebases"].as_array().expect("codebases array");
+    assert_eq!(rows.len(), 1, "--detailed shows only the named codebase");
+    assert_eq!(rows[0]["status"].as_str(), Some("modified"));
+
+    let agents: HashMap<String, String> = rows[0]["agents"]
+        .as_array()
+        .expect("agents array")
+        .iter()
+        .map(|entry| {
+            (
+                entry["agent"].as_str().expect("agent").to_string(),
+                entry["status"].as_str().expect("status").to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(agents.get("cursor").map(String::as_str), Some("modified"));
+    assert_eq!(agents.get("claude").map(String::as_str), Some("current"));
+    let flagged: Vec<&String> = agents
+        .iter()
+        .filter(|(_, status)| *status != "current")
+        .map(|(agent, _)| agent)
+        .collect();
+    assert_eq!(flagged, ["cursor"]);
+
+    orchestra_cmd(home.path())
+        .args(["status", "--detailed", "rules_api"])
+        .assert()
+        .success()
+        .stdout(contains("cursor"))
+        .stdout(contains(
+            "orchestra/controls/.cursor/rules/orchestra.mdc edited",
+        ));
+    orchestra_cmd(home.path())
+        .args(["status", "--detailed", "missing_api"])
+        .assert()
+        .failure()
+        .stderr(contains("codebase 'missing_api' is not registered"));
+}
+
+#[test]
+fn status_markdown_format_lists_modified_files() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let project = ProjectName::from("copnow");
+
+    let codebase_dir = init_codebase(&home, &workspace, &project, "notes_api");
+    sync_codebase_via_cli(&home, "notes_api");
+    fs::write(
+        codebase_dir.join("orchestra/controls/CLAUDE.md"),
+        "manual local change\n",
+    )
+    .expect("modify file");
+
+    let assert = orchestra_cmd(home.path())
+        .args(["status", "--format", "markdown"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("stdout utf8");
+    assert!(stdout.contains("| 1 project | 1 codebase | 0 stale**"), "stdout: {stdout}");
+    assert!(stdout.contains("### copnow"), "stdout: {stdout}");
+    assert!(stdout.contains("| notes_api | MODIFIED |"), "stdout: {stdout}");
+    assert!(stdout.contains("- `orchestra/controls/CLAUDE.md`"), "stdout: {stdout}");
+    assert!(!stdout.contains('\u{1b}'), "markdown must not contain ANSI escapes");
+
+    orchestra_cmd(home.path())
+        .args(["status", "--json", "--format", "table"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn status_via_daemon_checks_locally_when_the_daemon_is_not_running() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, &ProjectName::from("copnow"), "copnow_api");
+    init_codebase(&home, &workspace, &ProjectName::from("infra"), "infra_tools");
+
+    let local = orchestra_cmd(home.path())
+        .args(["status", "--json"])
+        .output()
+        .expect("run status");
+    assert!(local.status.success());
+
+    orchestra_cmd(home.path())
+        .args(["status", "--json", "--via-daemon"])
+        .assert()
+        .success()
+        .stdout(String::from_utf8(local.stdout).expect("stdout utf8"))
+        .stderr(contains("daemon is not running; checking locally"));
+
+    orchestra_cmd(home.path())
+        .args(["status", "--via-daemon", "--deep"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn status_watch_refuses_output_that_is_not_a_terminal() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(
+        &home,
+        &workspace,
+        &ProjectName::from("copnow"),
+        "copnow_api",
+    );
+
+    orchestra_cmd(home.path())
+        .args(["status", "--watch", "--interval", "1"])
+        .assert()
+        .failure()
+        .stderr(contains("needs a terminal; run `orchestra status` instead"));
+    orchestra_cmd(home.path())
+        .args(["status", "--interval", "5"])
+        .assert()
+        .failure()
+        .stderr(contains("--watch"));
+    orchestra_cmd(home.path())
+        .args(["status", "--watch", "--json"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn misspelled_codebase_suggests_the_registered_name() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, &ProjectName::from("copnow"), "payments");
+
+    for command in ["sync", "diff"] {
+        orchestra_cmd(home.path())
+            .args([command, "paymnets"])
+            .assert()
+            .failure()
+            .stderr(contains(
+                "codebase 'paymnets' is not registered; did you mean 'payments'?",
+            ))
+            .stderr(contains("registry not found").not());
+    }
+}
+
+#[test]
+fn corrupt_registry_file_is_reported_without_hiding_other_codebases() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let project = ProjectName::from("copnow");
+    init_codebase(&home, &workspace, &project, "copnow_api");
+    let stray = home.path().join(".orchestra/projects/copnow/backup.yaml");
+    fs::write(&stray, "projects: [unclosed\n").expect("write stray yaml");
+
+    orchestra_cmd(home.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(contains("copnow_api"))
+        .stderr(contains("Skipping unreadable registry file"))
+        .stderr(contains("backup.yaml"));
+    orchestra_cmd(home.path())
+        .args(["project", "list"])
+        .assert()
+        .success()
+        .stdout(contains("copnow_api"))
+        .stderr(contains("backup.yaml"));
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["sync", "backup"])
+        .assert()
+        .failure()
+        .stderr(contains("failed to parse registry"));
+    orchestra_cmd(home.path())
+        .args(["doctor", "--json"])
+        .assert()
+        .stdout(contains("backup.yaml"));
+}
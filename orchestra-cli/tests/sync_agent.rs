@@ -0,0 +1,91 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+fn controls(codebase_dir: &Path) -> PathBuf {
+    codebase_dir.join("orchestra").join("controls")
+}
+
+#[test]
+fn sync_agent_writes_only_that_agents_files() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--agent", "claude"])
+        .assert()
+        .success()
+        .stdout(contains("CLAUDE.md"))
+        .stdout(contains("4 files written, 0 unchanged"));
+
+    let controls = controls(&codebase_dir);
+    assert!(controls.join("CLAUDE.md").exists());
+    assert!(!controls.join("AGENTS.md").exists());
+    assert!(!controls.join("GEMINI.md").exists());
+    assert!(!controls.join(".cursor").exists());
+    assert!(!codebase_dir.join("orchestra").join("pilot.md").exists());
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--agent", "claude"])
+        .assert()
+        .success()
+        .stdout(contains("0 files written, 4 unchanged"));
+    assert!(!controls.join("AGENTS.md").exists());
+}
+
+#[test]
+fn sync_agent_dry_run_writes_nothing() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--agent", "codex", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(contains("dry-run:"))
+        .stdout(contains("AGENTS.md"));
+
+    assert!(!controls(&codebase_dir).exists());
+}
+
+#[test]
+fn sync_agent_keeps_other_agents_hash_entries() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--agent", "claude"])
+        .assert()
+        .success();
+
+    // Every other agent's outputs are still tracked, so a full sync is a no-op.
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success()
+        .stdout(contains("0 files written,"));
+}
+
+#[test]
+fn sync_rejects_unknown_agent() {
+    let home = TempDir::new().expect("home");
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--agent", "vim"])
+        .assert()
+        .failure()
+        .stderr(contains("unknown agent 'vim'"));
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+/// The registered path becomes a file, so nothing can be written under it.
+fn break_codebase(codebase_dir: &Path) {
+    fs::remove_dir_all(codebase_dir).expect("remove codebase dir");
+    fs::write(codebase_dir, "not a directory").expect("replace with file");
+}
+
+#[test]
+fn fail_on_change_exits_2_until_files_are_current() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--dry-run", "--fail-on-change"])
+        .assert()
+        .code(2)
+        .stdout(contains("dry-run: 1 codebase would change"))
+        .stderr(contains("generated files changed (--fail-on-change)"));
+    assert!(!codebase_dir.join("orchestra/pilot.md").exists());
+
+    orchestra_cmd(home.path())
+        .args(["sync", "--all", "--fail-on-change"])
+        .assert()
+        .code(2)
+        .stdout(contains("1 codebase changed"));
+    assert!(codebase_dir.join("orchestra/pilot.md").exists());
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--dry-run", "--fail-on-change"])
+        .assert()
+        .code(0)
+        .stdout(contains("dry-run: 0 codebases would change"));
+    orchestra_cmd(home.path())
+        .args(["sync", "--all", "--fail-on-change"])
+        .assert()
+        .code(0);
+}
+
+#[test]
+fn fail_on_change_writes_the_report_before_exiting() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    let report_path = workspace.path().join("sync.json");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "--all", "--dry-run", "--fail-on-change", "--report"])
+        .arg(&report_path)
+        .assert()
+        .code(2)
+        .stdout(contains("Report written to"));
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).expect("report written"))
+            .expect("report is JSON");
+    assert_eq!(report["codebases"][0]["status"], "synced");
+}
+
+#[test]
+fn sync_all_with_some_failures_exits_3_and_syncs_the_rest() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    // The broken codebase sorts first, so a run that stopped at the first
+    // failure would never reach the other one.
+    let broken = init_codebase(&home, &workspace, "copnow_admin");
+    let api = init_codebase(&home, &workspace, "copnow_api");
+    break_codebase(&broken);
+
+    orchestra_cmd(home.path())
+        .args(["sync", "--all"])
+        .assert()
+        .code(3)
+        .stdout(contains("copnow_admin  failed"))
+        .stdout(contains("1 codebase changed"))
+        .stdout(contains("1 failed"))
+        .stderr(contains("✗  'copnow_admin'"))
+        .stderr(contains(
+            "sync failed for 1 codebase(s); the other 1 synced",
+        ));
+    assert!(api.join("orchestra/pilot.md").exists());
+
+    // A partial failure outranks --fail-on-change.
+    fs::remove_file(api.join("orchestra/pilot.md")).expect("remove pilot");
+    orchestra_cmd(home.path())
+        .args(["sync", "--all", "--fail-on-change"])
+        .assert()
+        .code(3)
+        .stdout(contains("1 codebase changed"));
+}
+
+#[test]
+fn total_failure_exits_1() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let api = init_codebase(&home, &workspace, "copnow_api");
+    let web = init_codebase(&home, &workspace, "copnow_web");
+    break_codebase(&api);
+    break_codebase(&web);
+
+    orchestra_cmd(home.path())
+        .args(["sync", "--all", "--fail-on-change"])
+        .assert()
+        .code(1)
+        .stdout(contains("2 failed"))
+        .stderr(contains("sync failed for 2 codebase(s)"));
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .code(1)
+        .stderr(contains("sync failed for 'copnow_api'"));
+}
+
+#[test]
+fn help_documents_the_exit_codes() {
+    let home = TempDir::new().expect("home");
+    orchestra_cmd(home.path())
+        .args(["sync", "--help"])
+        .assert()
+        .success()
+        .stdout(contains("Exit codes:"))
+        .stdout(contains("--fail-on-change"))
+        .stdout(contains("3  --all or --report: some codebases failed"));
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use std::fs;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+fn journal_lines(home: &TempDir) -> Vec<String> {
+    let path = home
+        .path()
+        .join(".orchestra")
+        .join("logs")
+        .join("sync-history.jsonl");
+    fs::read_to_string(path)
+        .expect("read journal")
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn two_syncs_produce_two_journal_lines() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+
+    for _ in 0..2 {
+        orchestra_cmd(home.path())
+            .args(["sync", "copnow_api"])
+            .assert()
+            .success();
+    }
+
+    let lines = journal_lines(&home);
+    assert_eq!(lines.len(), 2, "one journal line per sync run");
+    for line in &lines {
+        let entry: serde_json::Value = serde_json::from_str(line).expect("valid JSON line");
+        assert_eq!(entry["codebase"], "copnow_api");
+        assert_eq!(entry["source"], "cli");
+    }
+}
+
+#[test]
+fn history_lists_only_requested_codebase() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    init_codebase(&home, &workspace, "copnow_web");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "--all"])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+
+    orchestra_cmd(home.path())
+        .args(["history", "copnow_api", "--since", "7d"])
+        .assert()
+        .success()
+        .stdout(contains("Sync history for 'copnow_api' (2 runs)"));
+
+    orchestra_cmd(home.path())
+        .args(["history", "copnow_web"])
+        .assert()
+        .success()
+        .stdout(contains("(1 runs)"));
+}
+
+#[test]
+fn history_since_excludes_old_runs_and_skips_corrupt_lines() {
+    let home = TempDir::new().expect("home");
+    let journal = home.path().join(".orchestra").join("logs");
+    fs::create_dir_all(&journal).expect("logs dir");
+    fs::write(
+        journal.join("sync-history.jsonl"),
+        concat!(
+            r#"{"timestamp":"2020-01-01T00:00:00Z","codebase":"copnow_api","source":"daemon","written":3,"unchanged":0,"paths":[]}"#,
+            "\n",
+            "{truncated\n",
+        ),
+    )
+    .expect("seed journal");
+
+    orchestra_cmd(home.path())
+        .args(["history", "copnow_api"])
+        .assert()
+        .success()
+        .stdout(contains("(1 runs)"))
+        .stdout(contains("daemon"));
+
+    orchestra_cmd(home.path())
+        .args(["history", "copnow_api", "--since", "7d"])
+        .assert()
+        .success()
+        .stdout(contains("No sync history for 'copnow_api'."));
+}
+
+#[test]
+fn history_rejects_invalid_since() {
+    let home = TempDir::new().expect("home");
+    orchestra_cmd(home.path())
+        .args(["history", "copnow_api", "--since", "7y"])
+        .assert()
+        .failure()
+        .stderr(contains("--since"));
+    for since in ["7é", "99999999999999d", "0d"] {
+        orchestra_cmd(home.path())
+            .args(["history", "copnow_api", "--since", since])
+            .assert()
+            .code(2)
+            .stderr(contains("--since"));
+    }
+}
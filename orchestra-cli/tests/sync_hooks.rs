@@ -0,0 +1,101 @@
+mod common;
+
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{CodebaseName, ProjectName, SyncHooks},
+};
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+fn registered_hooks(home: &TempDir) -> Option<SyncHooks> {
+    registry::load_codebase_at(
+        home.path(),
+        &ProjectName::from("copnow"),
+        &CodebaseName::from("copnow_api"),
+    )
+    .expect("load codebase")
+    .hooks
+}
+
+#[test]
+fn hook_set_list_and_unset_update_registry() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["hook", "set", "copnow_api", "post-sync", "git add -A"])
+        .assert()
+        .success()
+        .stdout(contains("Set post_sync hook for 'copnow_api'"));
+    orchestra_cmd(home.path())
+        .args([
+            "hook",
+            "set",
+            "copnow_api",
+            "pre-sync",
+            "true",
+            "--allow-in-daemon",
+        ])
+        .assert()
+        .success();
+    assert_eq!(
+        registered_hooks(&home),
+        Some(SyncHooks {
+            pre_sync: Some("true".into()),
+            post_sync: Some("git add -A".into()),
+            allow_in_daemon: true,
+        })
+    );
+
+    orchestra_cmd(home.path())
+        .args(["hook", "list", "copnow_api"])
+        .assert()
+        .success()
+        .stdout(contains("post_sync  git add -A"))
+        .stdout(contains("also run by the daemon"));
+
+    for hook in ["pre-sync", "post-sync"] {
+        orchestra_cmd(home.path())
+            .args(["hook", "unset", "copnow_api", hook])
+            .assert()
+            .success();
+    }
+    assert_eq!(registered_hooks(&home), None);
+    orchestra_cmd(home.path())
+        .args(["hook", "unset", "copnow_api", "post-sync"])
+        .assert()
+        .failure()
+        .stderr(contains("no post_sync hook is registered"));
+}
+
+#[cfg(unix)]
+#[test]
+fn failing_post_sync_hook_fails_sync_but_keeps_files() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args([
+            "hook",
+            "set",
+            "copnow_api",
+            "post-sync",
+            "echo 'commit refused' >&2; exit 2",
+        ])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .failure()
+        .stderr(contains("post_sync hook failed"))
+        .stderr(contains("commit refused"));
+    assert!(codebase_dir.join("orchestra/controls/CLAUDE.md").exists());
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use std::fs;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+#[test]
+fn sync_all_report_records_synced_and_failed_codebases() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    let broken = init_codebase(&home, &workspace, "copnow_web");
+    // The registered path is now a file, so nothing can be written under it.
+    fs::remove_dir_all(&broken).expect("remove codebase dir");
+    fs::write(&broken, "not a directory").expect("replace with file");
+    let report_path = workspace.path().join("reports/sync.json");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "--all", "--report"])
+        .arg(&report_path)
+        .assert()
+        .failure()
+        .stdout(contains("copnow_api  "))
+        .stdout(contains("copnow_web  failed"))
+        .stdout(contains("1 codebase changed"))
+        .stderr(contains("✗  'copnow_web'"))
+        .stderr(contains("sync failed for 1 codebase(s)"));
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).expect("report written"))
+            .expect("report is JSON");
+    assert!(report["orchestra_version"].is_string());
+    assert!(report["started_at"].is_string());
+    assert!(report["duration_ms"].is_u64());
+    assert_eq!(report["scope"], serde_json::json!({"kind": "all"}));
+    assert_eq!(report["dry_run"], false);
+
+    let codebases = report["codebases"].as_array().expect("codebases array");
+    assert_eq!(codebases.len(), 2);
+    let api = &codebases[0];
+    assert_eq!(api["codebase"], "copnow_api");
+    assert_eq!(api["status"], "synced");
+    let files = api["files"].as_array().expect("files array");
+    assert!(files
+        .iter()
+        .any(|file| file["path"] == "orchestra/controls/CLAUDE.md" && file["state"] == "written"));
+    assert!(files
+        .iter()
+        .all(|file| !file["path"].as_str().unwrap().starts_with('/')));
+
+    let web = &codebases[1];
+    assert_eq!(web["codebase"], "copnow_web");
+    assert_eq!(web["status"], "failed");
+    assert!(!web["error"].as_str().expect("error message").is_empty());
+}
+
+#[test]
+fn sync_report_for_one_codebase_marks_unchanged_files() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    let report_path = workspace.path().join("sync.json");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--report"])
+        .arg(&report_path)
+        .assert()
+        .success()
+        .stdout(contains("Report written to"));
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).expect("report written"))
+            .expect("report is JSON");
+    assert_eq!(
+        report["scope"],
+        serde_json::json!({"kind": "codebase", "name": "copnow_api"})
+    );
+    let files = report["codebases"][0]["files"]
+        .as_array()
+        .expect("files array");
+    assert!(!files.is_empty());
+    assert!(files.iter().all(|file| file["state"] == "unchanged"));
+}
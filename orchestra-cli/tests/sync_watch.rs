@@ -0,0 +1,127 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{CodebaseName, ProjectName},
+};
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+fn add_convention(home: &TempDir, name: &str, convention: &str) {
+    let project = ProjectName::from("copnow");
+    let mut codebase = registry::load_codebase_at(home.path(), &project, &CodebaseName::from(name))
+        .expect("load codebase");
+    codebase.conventions.push(convention.to_string());
+    registry::save_codebase_at(home.path(), &project, &codebase).expect("save codebase");
+}
+
+#[test]
+fn watch_resyncs_on_registry_change_and_stops_on_interrupt() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+    let claude_md = codebase_dir.join("orchestra/controls/CLAUDE.md");
+
+    let mut child = orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--watch"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn watch");
+    let stdout = child.stdout.take().expect("stdout");
+    let (lines_tx, lines) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let _ = lines_tx.send(line);
+        }
+    });
+
+    let banner = lines
+        .iter()
+        .find(|line| line.contains("Watching"))
+        .expect("watch banner");
+    assert!(banner.contains("'copnow_api'"), "{banner}");
+    assert!(claude_md.exists(), "initial sync ran before watching");
+    // Let the watcher register before editing.
+    sleep(Duration::from_millis(500));
+
+    add_convention(&home, "copnow_api", "watch-sentinel");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !fs::read_to_string(&claude_md).is_ok_and(|c| c.contains("watch-sentinel")) {
+        assert!(Instant::now() < deadline, "registry change was not synced");
+        sleep(Duration::from_millis(50));
+    }
+
+    let status = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .expect("send SIGINT");
+    assert!(status.success());
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let exit = loop {
+        if let Some(exit) = child.try_wait().expect("wait") {
+            break exit;
+        }
+        assert!(Instant::now() < deadline, "watch did not stop on Ctrl-C");
+        sleep(Duration::from_millis(50));
+    };
+    assert!(exit.success(), "{exit:?}");
+
+    let rest: Vec<String> = lines.iter().collect();
+    assert!(
+        rest.iter()
+            .any(|line| line.contains("✓ 'copnow_api' synced")),
+        "{rest:?}"
+    );
+    assert!(
+        rest.iter()
+            .any(|line| line.contains("Stopped watching 'copnow_api'")),
+        "{rest:?}"
+    );
+}
+
+#[test]
+fn watch_refuses_to_start_while_the_daemon_is_running() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+
+    let socket = orchestra_daemon::paths::socket_path(home.path());
+    fs::create_dir_all(socket.parent().expect("socket dir")).expect("socket dir");
+    let _listener = std::os::unix::net::UnixListener::bind(&socket).expect("bind socket");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--watch"])
+        .assert()
+        .failure()
+        .stderr(contains("daemon is running"))
+        .stderr(contains("--force"));
+}
+
+#[test]
+fn watch_needs_a_single_codebase() {
+    let home = TempDir::new().expect("home");
+    orchestra_cmd(home.path())
+        .args(["sync", "--all", "--watch"])
+        .assert()
+        .failure();
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--force"])
+        .assert()
+        .failure()
+        .stderr(contains("--watch"));
+}
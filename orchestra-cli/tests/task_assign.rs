@@ -0,0 +1,137 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use assert_cmd::prelude::*;
+use chrono::Utc;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{Codebase, CodebaseName, ProjectName, ProjectType, Task, TaskId, TaskStatus},
+};
+use tempfile::TempDir;
+
+use common::orchestra_cmd;
+
+fn load(home: &TempDir) -> Codebase {
+    registry::load_codebase_at(
+        home.path(),
+        &ProjectName::from("copnow"),
+        &CodebaseName::from("copnow_api"),
+    )
+    .expect("load codebase")
+}
+
+/// A codebase with the pending tasks T-1, T-2 and T-3, none assigned.
+fn init_codebase(home: &TempDir, workspace: &TempDir) -> PathBuf {
+    let codebase_dir = workspace.path().join("copnow_api");
+    fs::create_dir_all(&codebase_dir).expect("create codebase dir");
+    registry::init_at(
+        codebase_dir.clone(),
+        ProjectName::from("copnow"),
+        Some(ProjectType::Backend),
+        home.path(),
+    )
+    .expect("init codebase");
+    let mut codebase = load(home);
+    let now = Utc::now();
+    codebase.projects[0].tasks = (1..=3)
+        .map(|i| Task {
+            id: TaskId::from(format!("T-{i}")),
+            title: format!("Task {i}"),
+            status: TaskStatus::Pending,
+            description: None,
+            subtasks: Vec::new(),
+            notes: Vec::new(),
+            assigned_agent: None,
+            created_at: now,
+            updated_at: now,
+            extra: BTreeMap::new(),
+        })
+        .collect();
+    registry::save_codebase_at(home.path(), &ProjectName::from("copnow"), &codebase)
+        .expect("save tasks");
+    codebase_dir
+}
+
+fn assigned_agent(home: &TempDir, id: &str) -> Option<String> {
+    load(home).projects[0]
+        .tasks
+        .iter()
+        .find(|task| task.id.0 == id)
+        .and_then(|task| task.assigned_agent.clone())
+}
+
+#[test]
+fn assigned_tasks_are_listed_only_in_their_agents_files() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace);
+
+    orchestra_cmd(home.path())
+        .args(["task", "assign", "copnow_api", "T-2", "claude"])
+        .assert()
+        .success()
+        .stdout(contains("Assigned T-2 in 'copnow_api' to claude."));
+    orchestra_cmd(home.path())
+        .args(["task", "assign", "copnow_api", "T-3", "Cursor"])
+        .assert()
+        .success();
+    assert_eq!(assigned_agent(&home, "T-3").as_deref(), Some("cursor"));
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    let controls = codebase_dir.join("orchestra/controls");
+    let claude = fs::read_to_string(controls.join("CLAUDE.md")).expect("CLAUDE.md");
+    let cursor =
+        fs::read_to_string(controls.join(".cursor/rules/orchestra.mdc")).expect("cursor rules");
+    assert!(claude.contains("| T-1 |") && claude.contains("| T-2 |"));
+    assert!(!claude.contains("| T-3 |"));
+    assert!(cursor.contains("| T-1 |") && cursor.contains("| T-3 |"));
+    assert!(!cursor.contains("| T-2 |"));
+
+    orchestra_cmd(home.path())
+        .args(["task", "assign", "copnow_api", "T-2", "--clear"])
+        .assert()
+        .success();
+    assert_eq!(assigned_agent(&home, "T-2"), None);
+}
+
+#[test]
+fn assignment_to_an_unknown_agent_warns_at_sync() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace);
+
+    orchestra_cmd(home.path())
+        .args(["task", "assign", "copnow_api", "T-1", "alice"])
+        .assert()
+        .success()
+        .stderr(contains("'alice' is not an agent Orchestra renders"));
+    assert_eq!(assigned_agent(&home, "T-1").as_deref(), Some("alice"));
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success()
+        .stderr(contains("assigned to an unknown agent"))
+        .stderr(contains("T-1 → alice"));
+}
+
+#[test]
+fn assigning_a_missing_task_fails() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace);
+
+    orchestra_cmd(home.path())
+        .args(["task", "assign", "copnow_api", "T-9", "claude"])
+        .assert()
+        .failure()
+        .stderr(contains("task 'T-9' not found in 'copnow_api'"));
+}
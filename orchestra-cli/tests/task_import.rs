@@ -0,0 +1,206 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use assert_cmd::prelude::*;
+use chrono::Utc;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{Codebase, CodebaseName, ProjectName, Task, TaskId, TaskStatus},
+};
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+fn load(home: &TempDir) -> Codebase {
+    registry::load_codebase_at(
+        home.path(),
+        &ProjectName::from("copnow"),
+        &CodebaseName::from("copnow_api"),
+    )
+    .expect("load codebase")
+}
+
+fn task(codebase: &Codebase, id: &str) -> Option<Task> {
+    codebase
+        .projects
+        .iter()
+        .flat_map(|project| project.tasks.iter())
+        .find(|task| task.id.0 == id)
+        .cloned()
+}
+
+fn write_issues(workspace: &TempDir, json: &str) -> PathBuf {
+    let path = workspace.path().join("issues.json");
+    fs::write(&path, json).expect("write issues");
+    path
+}
+
+fn import(home: &TempDir, file: &Path, extra: &[&str]) -> String {
+    let output = orchestra_cmd(home.path())
+        .args(["task", "import", "copnow_api", "--from-json"])
+        .arg(file)
+        .args(extra)
+        .output()
+        .expect("run orchestra");
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).expect("utf-8 stdout")
+}
+
+const ISSUES: &str = r#"[
+  {"number": 12, "title": "Add login", "state": "OPEN", "body": "Use OAuth.", "labels": []},
+  {"number": 13, "title": "Fix cache", "state": "OPEN", "body": "", "labels": [{"name": "In Progress"}]},
+  {"number": 14, "title": "Upgrade db", "state": "OPEN", "body": null, "labels": [{"name": "blocked"}]},
+  {"number": 15, "title": "Old bug", "state": "CLOSED", "body": "done", "labels": []}
+]"#;
+
+#[test]
+fn import_maps_issues_to_tasks() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    let file = write_issues(&workspace, ISSUES);
+
+    let stdout = import(&home, &file, &[]);
+    assert!(
+        stdout.contains("4 added, 0 updated, 0 unchanged, 0 pruned"),
+        "{stdout}"
+    );
+
+    let codebase = load(&home);
+    let login = task(&codebase, "gh-12").expect("gh-12 imported");
+    assert_eq!(login.title, "Add login");
+    assert_eq!(login.status, TaskStatus::Pending);
+    assert_eq!(login.description.as_deref(), Some("Use OAuth."));
+    let cache = task(&codebase, "gh-13").expect("gh-13 imported");
+    assert_eq!(cache.status, TaskStatus::InProgress);
+    assert_eq!(cache.description, None);
+    assert_eq!(
+        task(&codebase, "gh-14").expect("gh-14").status,
+        TaskStatus::Blocked
+    );
+    assert_eq!(
+        task(&codebase, "gh-15").expect("gh-15").status,
+        TaskStatus::Done
+    );
+}
+
+#[test]
+fn reimporting_the_same_file_changes_nothing() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    let file = write_issues(&workspace, ISSUES);
+
+    import(&home, &file, &[]);
+    let first = load(&home);
+    let stdout = import(&home, &file, &[]);
+    assert!(
+        stdout.contains("0 added, 0 updated, 4 unchanged, 0 pruned"),
+        "{stdout}"
+    );
+
+    let second = load(&home);
+    assert_eq!(second.updated_at, first.updated_at);
+    let tasks: usize = second.projects.iter().map(|p| p.tasks.len()).sum();
+    assert_eq!(tasks, 4, "re-import must not duplicate tasks");
+}
+
+#[test]
+fn reimport_updates_changed_issues_and_keeps_created_at() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    let file = write_issues(&workspace, ISSUES);
+    import(&home, &file, &[]);
+    let before = task(&load(&home), "gh-12").expect("gh-12");
+
+    let file = write_issues(
+        &workspace,
+        r#"[{"number": 12, "title": "Add login", "state": "OPEN", "body": "Use OAuth.",
+             "labels": [{"name": "wip"}]}]"#,
+    );
+    let stdout = import(&home, &file, &["--in-progress-label", "wip"]);
+    assert!(stdout.contains("0 added, 1 updated"), "{stdout}");
+
+    let after = task(&load(&home), "gh-12").expect("gh-12");
+    assert_eq!(after.status, TaskStatus::InProgress);
+    assert_eq!(after.created_at, before.created_at);
+    assert!(after.updated_at > before.updated_at);
+
+    let file = write_issues(
+        &workspace,
+        r#"[{"number": 12, "title": "Add login", "state": "closed", "body": "Use OAuth."}]"#,
+    );
+    import(&home, &file, &[]);
+    assert_eq!(
+        task(&load(&home), "gh-12").expect("gh-12").status,
+        TaskStatus::Done
+    );
+}
+
+#[test]
+fn prune_removes_only_missing_imported_tasks() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    let mut codebase = load(&home);
+    let now = Utc::now();
+    codebase.projects[0].tasks.push(Task {
+        id: TaskId::from("local-1"),
+        title: "Written by hand".to_string(),
+        status: TaskStatus::Pending,
+        description: None,
+        subtasks: Vec::new(),
+        notes: Vec::new(),
+        assigned_agent: None,
+        created_at: now,
+        updated_at: now,
+        extra: BTreeMap::new(),
+    });
+    registry::save_codebase_at(home.path(), &ProjectName::from("copnow"), &codebase)
+        .expect("save codebase");
+    import(&home, &write_issues(&workspace, ISSUES), &[]);
+
+    let file = write_issues(
+        &workspace,
+        r#"[{"number": 12, "title": "Add login", "state": "OPEN", "body": "Use OAuth."}]"#,
+    );
+    let stdout = import(&home, &file, &[]);
+    assert!(stdout.contains("0 pruned"), "without --prune: {stdout}");
+    assert!(task(&load(&home), "gh-13").is_some());
+
+    let stdout = import(&home, &file, &["--prune"]);
+    assert!(stdout.contains("1 unchanged, 3 pruned"), "{stdout}");
+    let codebase = load(&home);
+    assert!(task(&codebase, "gh-12").is_some());
+    assert!(task(&codebase, "gh-13").is_none());
+    assert!(task(&codebase, "gh-15").is_none());
+    assert!(
+        task(&codebase, "local-1").is_some(),
+        "hand-written tasks stay"
+    );
+}
+
+#[test]
+fn duplicate_issue_numbers_are_rejected() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    init_codebase(&home, &workspace, "copnow_api");
+    let file = write_issues(
+        &workspace,
+        r#"[{"number": 1, "title": "a", "state": "OPEN"},
+            {"number": 1, "title": "b", "state": "OPEN"}]"#,
+    );
+
+    orchestra_cmd(home.path())
+        .args(["task", "import", "copnow_api", "--from-json"])
+        .arg(&file)
+        .assert()
+        .failure()
+        .stderr(contains("issue #1 appears more than once"));
+}
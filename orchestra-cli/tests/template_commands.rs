@@ -0,0 +1,101 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{ProjectName, ProjectType},
+};
+use tempfile::TempDir;
+
+use common::orchestra_cmd;
+
+fn write_override(home: &TempDir, name: &str, content: &str) -> PathBuf {
+    let path = home.path().join(".orchestra/templates").join(name);
+    fs::create_dir_all(path.parent().expect("parent")).expect("create template dir");
+    fs::write(&path, content).expect("write override");
+    path
+}
+
+#[test]
+fn check_passes_with_embedded_templates() {
+    let home = TempDir::new().expect("home");
+
+    orchestra_cmd(home.path())
+        .args(["template", "check"])
+        .assert()
+        .success()
+        .stdout(contains("claude/claude.md.tera"))
+        .stdout(contains("templates rendered"));
+}
+
+#[test]
+fn check_reports_broken_override_by_file() {
+    let home = TempDir::new().expect("home");
+    let broken = write_override(&home, "claude/claude.md.tera", "# {{ codebase_name\n");
+
+    orchestra_cmd(home.path())
+        .args(["template", "check"])
+        .assert()
+        .failure()
+        .stdout(contains("✗ claude/claude.md.tera"))
+        .stdout(contains(broken.display().to_string()))
+        .stderr(contains("1 of"));
+}
+
+#[test]
+fn check_renders_against_a_registered_codebase() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = workspace.path().join("copnow_api");
+    fs::create_dir_all(&codebase_dir).expect("create codebase dir");
+    registry::init_at(
+        codebase_dir,
+        ProjectName::from("copnow"),
+        Some(ProjectType::Backend),
+        home.path(),
+    )
+    .expect("init codebase");
+
+    orchestra_cmd(home.path())
+        .args(["template", "check", "--codebase", "copnow_api"])
+        .assert()
+        .success();
+
+    orchestra_cmd(home.path())
+        .args(["template", "check", "--codebase", "missing"])
+        .assert()
+        .failure()
+        .stderr(contains("not registered"));
+}
+
+#[test]
+fn list_marks_overridden_templates() {
+    let home = TempDir::new().expect("home");
+    let custom = write_override(&home, "zed/rules.tera", "# Custom rules\n");
+
+    orchestra_cmd(home.path())
+        .args(["template", "list"])
+        .assert()
+        .success()
+        .stdout(contains("1 overridden"))
+        .stdout(contains(format!("override ({})", custom.display())))
+        .stdout(contains("embedded"));
+}
+
+#[test]
+fn list_exits_cleanly_when_the_reader_is_gone() {
+    let home = TempDir::new().expect("home");
+    let (reader, writer) = std::io::pipe().expect("pipe");
+    drop(reader);
+
+    orchestra_cmd(home.path())
+        .args(["template", "list"])
+        .stdout(writer)
+        .assert()
+        .success();
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use assert_cmd::prelude::*;
+use chrono::Utc;
+use predicates::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{CodebaseName, ProjectName, Task, TaskId, TaskStatus},
+};
+use tempfile::TempDir;
+
+use common::{init_codebase, orchestra_cmd};
+
+/// Register 40 active tasks with long descriptions, all rendered, and set
+/// `enforce_budget` as given.
+fn add_long_tasks(home: &TempDir, enforce_budget: Option<bool>) {
+    let project = ProjectName::from("copnow");
+    let mut codebase =
+        registry::load_codebase_at(home.path(), &project, &CodebaseName::from("copnow_api"))
+            .expect("load codebase");
+    let now = Utc::now();
+    codebase.projects[0].tasks = (0..40)
+        .map(|i| Task {
+            id: TaskId::from(format!("T-{i:03}")),
+            title: format!("Task number {i}"),
+            status: TaskStatus::Pending,
+            description: Some("Long description of the work to be done. ".repeat(15)),
+            subtasks: vec![],
+            notes: vec![],
+            assigned_agent: None,
+            created_at: now,
+            updated_at: now,
+            extra: BTreeMap::new(),
+        })
+        .collect();
+    codebase.max_rendered_tasks = Some(40);
+    codebase.enforce_budget = enforce_budget;
+    codebase.updated_at = now;
+    registry::save_codebase_at(home.path(), &project, &codebase).expect("save codebase");
+}
+
+fn cursor_rules(codebase_dir: &Path) -> String {
+    fs::read_to_string(codebase_dir.join("orchestra/controls/.cursor/rules/orchestra.mdc"))
+        .expect("read cursor rules")
+}
+
+#[test]
+fn oversized_output_warns_and_enforcement_trims_it() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+    add_long_tasks(&home, None);
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success()
+        .stderr(contains("orchestra.mdc is ~"))
+        .stderr(contains("over cursor's 3000-token budget"))
+        .stderr(contains("--enforce-budget"));
+    // Nothing changed since the sync, so diff skips rendering but still warns.
+    orchestra_cmd(home.path())
+        .args(["diff", "copnow_api"])
+        .assert()
+        .success()
+        .stderr(contains("over cursor's 3000-token budget"));
+    let untrimmed = cursor_rules(&codebase_dir);
+    assert!(untrimmed.contains("Long description of the work"));
+    assert!(untrimmed.chars().count() > 3000 * 4);
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--enforce-budget"])
+        .assert()
+        .success()
+        .stderr(contains("✂  'copnow_api': "))
+        .stderr(contains("trimmed task descriptions to ~"));
+    let trimmed = cursor_rules(&codebase_dir);
+    assert!(trimmed.chars().count() <= 3000 * 4);
+    assert!(!trimmed.contains("Long description of the work"));
+    assert!(trimmed.contains("trimmed to fit this agent's token budget"));
+    assert!(trimmed.contains("| T-000 | Task number 0 | pending | … |"));
+
+    // With the registry setting, diff renders the same trimmed files.
+    add_long_tasks(&home, Some(true));
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    orchestra_cmd(home.path())
+        .args(["diff", "copnow_api"])
+        .assert()
+        .success()
+        .stdout(contains("No differences for 'copnow_api'."));
+    assert_eq!(cursor_rules(&codebase_dir), trimmed);
+}
+
+#[test]
+fn output_within_budget_is_untouched_by_enforcement() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_codebase(&home, &workspace, "copnow_api");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success()
+        .stderr(contains("budget").not());
+    let claude = codebase_dir.join("orchestra/controls/CLAUDE.md");
+    let before = fs::read(&claude).expect("read CLAUDE.md");
+
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api", "--enforce-budget"])
+        .assert()
+        .success()
+        .stdout(contains("0 codebases changed"))
+        .stderr(contains("budget").not());
+    assert_eq!(fs::read(&claude).expect("read CLAUDE.md"), before);
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use predicates::str::contains;
+
+use orchestra_core::{
+    registry,
+    types::{ProjectName, ProjectType},
+};
+use orchestra_renderer::AgentKind;
+use tempfile::TempDir;
+
+use common::orchestra_cmd;
+
+fn init_and_sync(home: &TempDir, workspace: &TempDir) -> PathBuf {
+    let codebase_dir = workspace.path().join("copnow_api");
+    fs::create_dir_all(&codebase_dir).expect("create codebase dir");
+    registry::init_at(
+        codebase_dir.clone(),
+        ProjectName::from("copnow"),
+        Some(ProjectType::Backend),
+        home.path(),
+    )
+    .expect("init codebase");
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    codebase_dir
+}
+
+/// Stand-in for a binary upgrade: an override replaces the cline template.
+fn change_cline_template(home: &TempDir) {
+    let template = AgentKind::Cline.template_names()[0];
+    let path = home.path().join(".orchestra/templates").join(template);
+    fs::create_dir_all(path.parent().unwrap()).expect("mkdir templates");
+    fs::write(&path, "upgraded cline rules\n").expect("write override");
+}
+
+#[test]
+fn shows_then_applies_template_changes() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_and_sync(&home, &workspace);
+    let cline = codebase_dir.join("orchestra/controls/.clinerules/orchestra.md");
+    let before = fs::read_to_string(&cline).expect("read cline");
+
+    orchestra_cmd(home.path())
+        .arg("upgrade-templates")
+        .assert()
+        .success()
+        .stdout(contains(
+            "'copnow_api': templates unchanged since last sync.",
+        ));
+
+    change_cline_template(&home);
+    orchestra_cmd(home.path())
+        .args(["upgrade-templates", "--codebase", "copnow_api"])
+        .assert()
+        .success()
+        .stdout(contains("=== copnow_api — 1 file,"))
+        .stdout(contains("+upgraded cline rules"))
+        .stdout(contains("Affected: cline"))
+        .stdout(contains("CLAUDE.md").not());
+    assert_eq!(fs::read_to_string(&cline).expect("read cline"), before);
+
+    orchestra_cmd(home.path())
+        .args(["upgrade-templates", "--all", "--apply"])
+        .assert()
+        .success()
+        // The cline rules, plus the manifest listing their new digest.
+        .stdout(contains("'copnow_api': updated 2 files."))
+        .stdout(contains(".clinerules/orchestra.md"))
+        .stdout(contains(".orchestra-manifest.json"));
+    assert_eq!(
+        fs::read_to_string(&cline).expect("read cline"),
+        "upgraded cline rules\n"
+    );
+
+    orchestra_cmd(home.path())
+        .arg("upgrade-templates")
+        .assert()
+        .success()
+        .stdout(contains("templates unchanged since last sync"));
+}
+
+#[test]
+fn apply_leaves_locally_edited_files_alone() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let codebase_dir = init_and_sync(&home, &workspace);
+    let cline = codebase_dir.join("orchestra/controls/.clinerules/orchestra.md");
+    fs::write(&cline, "hand-written rules\n").expect("edit cline");
+    change_cline_template(&home);
+
+    orchestra_cmd(home.path())
+        .arg("upgrade-templates")
+        .assert()
+        .success()
+        .stdout(contains("'copnow_api': no template changes."));
+    orchestra_cmd(home.path())
+        .args(["upgrade-templates", "--apply"])
+        .assert()
+        .success()
+        .stdout(contains("'copnow_api': updated 0 files."));
+    assert_eq!(
+        fs::read_to_string(&cline).expect("read cline"),
+        "hand-written rules\n"
+    );
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use serde_json::Value;
+
+use orchestra_core::{
+    registry,
+    types::{ProjectName, ProjectType},
+};
+use orchestra_sync::layout;
+use tempfile::TempDir;
+
+use common::orchestra_cmd;
+
+/// Register and sync `copnow_api` under project `copnow`; returns its
+/// canonical directory.
+fn synced_codebase(home: &TempDir, workspace: &TempDir) -> PathBuf {
+    let dir = workspace.path().join("copnow_api");
+    fs::create_dir_all(&dir).expect("create codebase dir");
+    registry::init_at(
+        dir.clone(),
+        ProjectName::from("copnow"),
+        Some(ProjectType::Backend),
+        home.path(),
+    )
+    .expect("init codebase");
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success();
+    dir.canonicalize().expect("canonical dir")
+}
+
+#[test]
+fn every_markdown_output_carries_the_provenance_header() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    synced_codebase(&home, &workspace);
+
+    let (_, codebase) = registry::list_codebases_at(home.path())
+        .expect("list")
+        .remove(0);
+    let header = format!(
+        "Generated by Orchestra v{} — codebase 'copnow_api', project 'copnow' — do not edit; \
+         run `orchestra sync copnow_api`",
+        env!("CARGO_PKG_VERSION")
+    );
+    let mut outputs: Vec<PathBuf> = layout::managed_output_paths(&codebase)
+        .into_iter()
+        .map(|(_, path)| path)
+        .collect();
+    outputs.push(layout::managed_guide_path(&codebase));
+    outputs.push(layout::managed_pilot_path(&codebase));
+
+    let mut checked = 0;
+    for path in outputs {
+        let content = fs::read_to_string(&path).expect("read output");
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let json: Value = serde_json::from_str(&content).expect("valid JSON");
+            assert_eq!(json["_comment"], header.as_str(), "{}", path.display());
+        } else {
+            assert!(
+                content.contains(&format!("<!-- {header} -->")),
+                "{} has no provenance header:\n{content}",
+                path.display()
+            );
+        }
+        checked += 1;
+    }
+    assert!(checked > 20, "only {checked} outputs checked");
+
+    // The header is stable, so a second sync rewrites nothing.
+    orchestra_cmd(home.path())
+        .args(["sync", "copnow_api"])
+        .assert()
+        .success()
+        .stdout(contains("0 files written"));
+}
+
+#[test]
+fn which_resolves_managed_files_and_rejects_others() {
+    let home = TempDir::new().expect("home");
+    let workspace = TempDir::new().expect("workspace");
+    let dir = synced_codebase(&home, &workspace);
+
+    let output = orchestra_cmd(home.path())
+        .current_dir(&dir)
+        .args(["which", "orchestra/controls/CLAUDE.md", "--json"])
+        .output()
+        .expect("run orchestra");
+    assert!(output.status.success(), "{output:?}");
+    let json: Value = serde_json::from_slice(&output.stdout).expect("json stdout");
+    assert_eq!(json["codebase"], "copnow_api");
+    assert_eq!(json["project"], "copnow");
+    assert_eq!(json["agent"], "claude");
+    assert_eq!(json["status"], "current");
+    assert_eq!(json["relative_path"], "orchestra/controls/CLAUDE.md");
+
+    fs::write(dir.join("orchestra/pilot.md"), "edited\n").expect("edit pilot");
+    orchestra_cmd(home.path())
+        .arg("which")
+        .arg(dir.join("orchestra/pilot.md"))
+        .assert()
+        .success()
+        .stdout(contains("Codebase:  copnow_api (project 'copnow')"))
+        .stdout(contains("Agent:     none (shared file)"))
+        .stdout(contains("Status:    modified"));
+
+    fs::write(dir.join("README.md"), "# api\n").expect("write readme");
+    orchestra_cmd(home.path())
+        .arg("which")
+        .arg(dir.join("README.md"))
+        .assert()
+        .failure()
+        .stderr(contains("is not generated by Orchestra"));
+}
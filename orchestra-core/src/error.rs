@@ -23,6 +23,17 @@ pub enum RegistryError {
         source: serde_yaml::Error,
     },
 
+    /// YAML parse error on load with a known location, carrying an annotated
+    /// snippet of the offending lines.
+    #[error("failed to parse registry at {path} (line {line}, column {column}): {message}\n{snippet}")]
+    ParseDetailed {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        snippet: String,
+        message: String,
+    },
+
     /// `dirs::home_dir()` returned `None` — cannot locate `~/.orchestra/`.
     #[error("cannot determine home directory; set $HOME or equivalent")]
     HomeNotFound,
@@ -30,4 +41,70 @@ pub enum RegistryError {
     /// The registry YAML file did not exist at the expected path.
     #[error("registry not found at {path}")]
     RegistryNotFound { path: PathBuf },
+
+    /// The registry file was written by a newer orchestra than this build.
+    #[error(
+        "registry at {path} uses schema v{found}, but this orchestra supports up to v{supported}; upgrade orchestra to read it"
+    )]
+    UnsupportedSchema {
+        path: PathBuf,
+        found: u32,
+        supported: u32,
+    },
+
+    /// The requested codebase path is, contains, or sits inside a path that
+    /// another codebase is already registered at.
+    #[error(
+        "{} overlaps codebase '{codebase}' registered at {}",
+        requested.display(),
+        existing.display()
+    )]
+    OverlappingPath {
+        codebase: String,
+        existing: PathBuf,
+        requested: PathBuf,
+    },
+
+    /// The path passed to `init` could not be resolved, usually because it
+    /// does not exist.
+    #[error("cannot resolve codebase path {}: {source}", path.display())]
+    PathNotFound {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// The resolved codebase path is already registered under another name.
+    #[error(
+        "{} is already registered as codebase '{codebase}' in project '{project}'",
+        path.display()
+    )]
+    DuplicatePath {
+        project: String,
+        codebase: String,
+        path: PathBuf,
+    },
+
+    /// A project or codebase name cannot be used as a registry file name.
+    #[error("invalid name '{name}': {reason}")]
+    InvalidName { name: String, reason: String },
+
+    /// No project directory exists under the registry root.
+    #[error("no project named '{name}'")]
+    ProjectNotFound { name: String },
+
+    /// The project still has codebases and the removal was not cascading.
+    #[error("project '{name}' still has {} codebase(s): {}", codebases.len(), codebases.join(", "))]
+    ProjectNotEmpty {
+        name: String,
+        codebases: Vec<String>,
+    },
+
+    /// A schema migration could not upgrade the registry file.
+    #[error("failed to migrate registry at {path} from schema v{from}: {message}")]
+    Migration {
+        path: PathBuf,
+        from: u32,
+        message: String,
+    },
 }
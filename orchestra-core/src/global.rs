@@ -0,0 +1,119 @@
+//! Org-wide instructions rendered into every codebase.
+//!
+//! `~/.orchestra/global.yaml` holds baseline conventions, skills and
+//! architecture notes that apply to every registered codebase. Sync merges
+//! them into each codebase's template context, global entries first. A
+//! missing file means no global instructions.
+//!
+//! ```yaml
+//! conventions:
+//!   - Never commit secrets
+//! skills:
+//!   - id: security-review
+//!     description: Check changes against the security checklist
+//! architecture_notes: Services talk over the internal gRPC mesh.
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::RegistryError;
+use crate::paths;
+use crate::registry::parse_error;
+use crate::types::Skill;
+
+/// File name of the global context under the data root.
+pub const GLOBAL_FILE: &str = "global.yaml";
+
+/// Instructions shared by every codebase.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GlobalContext {
+    pub conventions: Vec<String>,
+    pub skills: Vec<Skill>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub architecture_notes: Option<String>,
+}
+
+impl GlobalContext {
+    /// Whether there is nothing to render.
+    pub fn is_empty(&self) -> bool {
+        self.conventions.is_empty()
+            && self.skills.is_empty()
+            && self
+                .architecture_notes
+                .as_deref()
+                .is_none_or(|notes| notes.trim().is_empty())
+    }
+}
+
+/// `~/.orchestra/global.yaml` — pure, no I/O.
+pub fn path_at(home: &Path) -> PathBuf {
+    paths::dirs_at(home).data_root.join(GLOBAL_FILE)
+}
+
+/// Load the global context. A missing or empty file is the empty default.
+pub fn load_at(home: &Path) -> Result<GlobalContext, RegistryError> {
+    let path = path_at(home);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(GlobalContext::default()),
+        Err(e) => return Err(e.into()),
+    };
+    if contents.trim().is_empty() {
+        return Ok(GlobalContext::default());
+    }
+    serde_yaml::from_str(&contents).map_err(|e| parse_error(&path, &contents, e))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_global(home: &Path, contents: &str) {
+        let path = path_at(home);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn missing_or_empty_file_is_the_default() {
+        let home = TempDir::new().unwrap();
+        assert_eq!(load_at(home.path()).unwrap(), GlobalContext::default());
+
+        write_global(home.path(), "\n");
+        let global = load_at(home.path()).unwrap();
+        assert!(global.is_empty());
+    }
+
+    #[test]
+    fn loads_conventions_skills_and_notes() {
+        let home = TempDir::new().unwrap();
+        write_global(
+            home.path(),
+            "conventions:\n  - Never commit secrets\nskills:\n  - id: security-review\n    description: Check the security checklist\narchitecture_notes: Services talk over gRPC.\n",
+        );
+        let global = load_at(home.path()).unwrap();
+        assert_eq!(global.conventions, vec!["Never commit secrets".to_string()]);
+        assert_eq!(global.skills[0].id, "security-review");
+        assert_eq!(
+            global.architecture_notes.as_deref(),
+            Some("Services talk over gRPC.")
+        );
+        assert!(!global.is_empty());
+    }
+
+    #[test]
+    fn malformed_file_reports_its_path() {
+        let home = TempDir::new().unwrap();
+        write_global(home.path(), "conventions: [unclosed\n");
+        let err = load_at(home.path()).unwrap_err().to_string();
+        assert!(err.contains(GLOBAL_FILE), "{err}");
+    }
+}
@@ -4,13 +4,18 @@
 //! - [`types`] — newtypes and domain structs
 //! - [`error`] — [`RegistryError`]
 //! - [`registry`] — load / save / init
+//! - [`paths`] — where Orchestra's own files live
+//! - [`global`] — instructions shared by every codebase
 
 pub mod error;
+pub mod global;
+pub mod paths;
 pub mod registry;
 pub mod types;
 
 pub use error::RegistryError;
+pub use global::GlobalContext;
 pub use types::{
-    AgentConfig, Codebase, CodebaseName, Project, ProjectName, ProjectType, Registry, Skill,
-    Subtask, Task, TaskId, TaskStatus,
+    AgentConfig, Codebase, CodebaseName, ExtraOutput, Project, ProjectName, ProjectType, Registry, Skill,
+    StackDetection, Subtask, SyncHooks, Task, TaskId, TaskStatus,
 };
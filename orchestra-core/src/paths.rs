@@ -0,0 +1,270 @@
+//! Where Orchestra keeps its own files.
+//!
+//! # Resolution order
+//!
+//! [`OrchestraDirs::resolve`] picks the first of:
+//!
+//! 1. `$ORCHESTRA_HOME` — one directory holding everything.
+//! 2. The CLI's `--home <dir>` flag — same layout as `$ORCHESTRA_HOME`.
+//! 3. On Linux, `$XDG_DATA_HOME/orchestra` (registry, hashes, backups, …) and
+//!    `$XDG_STATE_HOME/orchestra` (logs, run dir, socket) when either variable
+//!    is set — unless `~/.orchestra` already exists and the XDG data directory
+//!    does not, so existing installs keep working.
+//! 4. The legacy `~/.orchestra`.
+//!
+//! # Threading
+//!
+//! Library functions keep taking `home: &Path` and look their directories up
+//! with [`dirs_at`]. The binaries resolve once at startup and [`install`] the
+//! result for the real home; any other home (a test's `TempDir`) gets the
+//! legacy layout beneath it, so tests never depend on the environment.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable that overrides every other location.
+pub const ORCHESTRA_HOME_ENV: &str = "ORCHESTRA_HOME";
+
+/// Directory name of the legacy layout under the user's home.
+pub const LEGACY_DIR_NAME: &str = ".orchestra";
+
+/// Directory name used beneath the XDG base directories.
+const XDG_DIR_NAME: &str = "orchestra";
+
+/// Resolved locations of Orchestra's data and state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrchestraDirs {
+    /// Persistent data: hashes, backups, skills, templates, channel.
+    pub data_root: PathBuf,
+    /// Runtime state: logs, locks, run dir, daemon socket.
+    pub state_root: PathBuf,
+    /// `<data_root>/projects` — one directory per registry project.
+    pub registry_root: PathBuf,
+    /// `<data_root>/hashes` — per-codebase hash stores.
+    pub hashes_dir: PathBuf,
+    /// `<state_root>/logs` — daemon logs and sync journals.
+    pub logs_dir: PathBuf,
+    /// `<state_root>/run` — daemon pid and runtime files.
+    pub run_dir: PathBuf,
+}
+
+impl OrchestraDirs {
+    /// Data and state share one root, as with `~/.orchestra` or `$ORCHESTRA_HOME`.
+    pub fn single(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        Self::split(root.clone(), root)
+    }
+
+    /// Data and state in separate roots, as with the XDG layout.
+    pub fn split(data_root: impl Into<PathBuf>, state_root: impl Into<PathBuf>) -> Self {
+        let data_root = data_root.into();
+        let state_root = state_root.into();
+        Self {
+            registry_root: data_root.join("projects"),
+            hashes_dir: data_root.join("hashes"),
+            logs_dir: state_root.join("logs"),
+            run_dir: state_root.join("run"),
+            data_root,
+            state_root,
+        }
+    }
+
+    /// `<home>/.orchestra` for everything.
+    pub fn legacy(home: &Path) -> Self {
+        Self::single(home.join(LEGACY_DIR_NAME))
+    }
+
+    /// Resolve from the process environment; `explicit` is the `--home` flag.
+    pub fn resolve(home: &Path, explicit: Option<&Path>) -> Self {
+        Self::resolve_with(home, explicit, cfg!(target_os = "linux"), |key| {
+            std::env::var_os(key)
+        })
+    }
+
+    /// [`resolve`](Self::resolve) with the environment and platform injected.
+    pub fn resolve_with(
+        home: &Path,
+        explicit: Option<&Path>,
+        use_xdg: bool,
+        env: impl Fn(&str) -> Option<OsString>,
+    ) -> Self {
+        let var = |key: &str| {
+            env(key)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+
+        if let Some(root) = var(ORCHESTRA_HOME_ENV) {
+            return Self::single(absolute(root));
+        }
+        if let Some(root) = explicit {
+            return Self::single(absolute(root.to_path_buf()));
+        }
+
+        let legacy = Self::legacy(home);
+        if !use_xdg {
+            return legacy;
+        }
+        // Relative XDG paths are invalid per the spec and are ignored.
+        let data_home = var("XDG_DATA_HOME").filter(|path| path.is_absolute());
+        let state_home = var("XDG_STATE_HOME").filter(|path| path.is_absolute());
+        if data_home.is_none() && state_home.is_none() {
+            return legacy;
+        }
+
+        let xdg = Self::split(
+            data_home
+                .unwrap_or_else(|| home.join(".local").join("share"))
+                .join(XDG_DIR_NAME),
+            state_home
+                .unwrap_or_else(|| home.join(".local").join("state"))
+                .join(XDG_DIR_NAME),
+        );
+        if legacy.data_root.is_dir() && !xdg.data_root.exists() {
+            return legacy;
+        }
+        xdg
+    }
+
+    /// Whether this is the legacy layout under `home`.
+    pub fn is_legacy(&self, home: &Path) -> bool {
+        *self == Self::legacy(home)
+    }
+}
+
+fn absolute(path: PathBuf) -> PathBuf {
+    std::path::absolute(&path).unwrap_or(path)
+}
+
+static INSTALLED: OnceLock<(PathBuf, OrchestraDirs)> = OnceLock::new();
+
+/// Use `dirs` whenever [`dirs_at`] is asked about `home` for the rest of the
+/// process. Only the first call takes effect.
+pub fn install(home: &Path, dirs: OrchestraDirs) {
+    let _ = INSTALLED.set((home.to_path_buf(), dirs));
+}
+
+/// The home [`install`] was called with, if it has been.
+pub fn installed_home() -> Option<PathBuf> {
+    INSTALLED.get().map(|(home, _)| home.clone())
+}
+
+/// Directories for `home`: the [`install`]ed ones when `home` is the home they
+/// were installed for, otherwise the legacy layout under `home`.
+pub fn dirs_at(home: &Path) -> OrchestraDirs {
+    match INSTALLED.get() {
+        Some((installed_home, dirs)) if installed_home == home => dirs.clone(),
+        _ => OrchestraDirs::legacy(home),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn resolve(
+        home: &Path,
+        explicit: Option<&Path>,
+        use_xdg: bool,
+        vars: &[(&str, &Path)],
+    ) -> OrchestraDirs {
+        let vars: HashMap<String, OsString> = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.as_os_str().to_owned()))
+            .collect();
+        OrchestraDirs::resolve_with(home, explicit, use_xdg, |key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn defaults_to_legacy_without_overrides() {
+        let home = TempDir::new().unwrap();
+        let dirs = resolve(home.path(), None, true, &[]);
+        assert!(dirs.is_legacy(home.path()));
+        assert_eq!(dirs.registry_root, home.path().join(".orchestra/projects"));
+    }
+
+    #[test]
+    fn env_var_beats_flag_and_xdg() {
+        let home = TempDir::new().unwrap();
+        let custom = home.path().join("synced");
+        let flag = home.path().join("flag");
+        let dirs = resolve(
+            home.path(),
+            Some(&flag),
+            true,
+            &[
+                (ORCHESTRA_HOME_ENV, &custom),
+                ("XDG_DATA_HOME", &home.path().join("data")),
+            ],
+        );
+        assert_eq!(dirs, OrchestraDirs::single(&custom));
+        assert_eq!(dirs.hashes_dir, custom.join("hashes"));
+    }
+
+    #[test]
+    fn flag_beats_xdg() {
+        let home = TempDir::new().unwrap();
+        let flag = home.path().join("flag");
+        let dirs = resolve(
+            home.path(),
+            Some(&flag),
+            true,
+            &[("XDG_DATA_HOME", &home.path().join("data"))],
+        );
+        assert_eq!(dirs, OrchestraDirs::single(&flag));
+    }
+
+    #[test]
+    fn xdg_splits_data_and_state() {
+        let home = TempDir::new().unwrap();
+        let data = home.path().join("data");
+        let state = home.path().join("state");
+        let dirs = resolve(
+            home.path(),
+            None,
+            true,
+            &[("XDG_DATA_HOME", &data), ("XDG_STATE_HOME", &state)],
+        );
+        assert_eq!(dirs.registry_root, data.join("orchestra/projects"));
+        assert_eq!(dirs.logs_dir, state.join("orchestra/logs"));
+        assert_eq!(dirs.run_dir, state.join("orchestra/run"));
+    }
+
+    #[test]
+    fn xdg_is_ignored_off_linux() {
+        let home = TempDir::new().unwrap();
+        let dirs = resolve(
+            home.path(),
+            None,
+            false,
+            &[("XDG_DATA_HOME", &home.path().join("data"))],
+        );
+        assert!(dirs.is_legacy(home.path()));
+    }
+
+    #[test]
+    fn existing_legacy_dir_is_preferred_over_missing_xdg_dir() {
+        let home = TempDir::new().unwrap();
+        let data = home.path().join("data");
+        std::fs::create_dir_all(home.path().join(".orchestra/projects")).unwrap();
+        let dirs = resolve(home.path(), None, true, &[("XDG_DATA_HOME", &data)]);
+        assert!(dirs.is_legacy(home.path()));
+
+        std::fs::create_dir_all(data.join("orchestra")).unwrap();
+        let dirs = resolve(home.path(), None, true, &[("XDG_DATA_HOME", &data)]);
+        assert_eq!(dirs.data_root, data.join("orchestra"));
+    }
+
+    #[test]
+    fn uninstalled_home_gets_legacy_layout() {
+        let home = TempDir::new().unwrap();
+        assert!(dirs_at(home.path()).is_legacy(home.path()));
+    }
+}
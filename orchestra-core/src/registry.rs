@@ -3,11 +3,13 @@
 //! # Storage layout
 //!
 //! ```text
-//! ~/.orchestra/
+//! ~/.orchestra/               (or wherever `paths::dirs_at` resolves)
 //!   projects/
 //!     <project_name>/
 //!       project.yaml          (index — mode 0600, created on first init)
 //!       <codebase_name>.yaml  (one file per codebase — mode 0600)
+//!   hashes/
+//!     <codebase_name>.json    (hash store, written by orchestra-sync)
 //! ```
 //!
 //! # API pattern
@@ -17,13 +19,24 @@
 //! - `fn(…)` — derives home from `dirs::home_dir()`, delegates to `_at`
 //!
 //! Tests must NEVER call the no-arg wrappers; always use `_at`.
+//!
+//! # Schema versions
+//!
+//! Codebase files carry a `schema_version`. Loading an older file runs the
+//! [`migrate`] steps and rewrites it in place; a newer file is rejected.
+
+pub mod migrate;
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use chrono::Utc;
 
 use crate::error::RegistryError;
-use crate::types::{Codebase, CodebaseName, Project, ProjectName, ProjectType};
+use crate::paths;
+use crate::types::{
+    Codebase, CodebaseName, Project, ProjectName, ProjectType, TaskStatus, CURRENT_SCHEMA_VERSION,
+};
 
 // ---------------------------------------------------------------------------
 // 1. Path helpers
@@ -33,7 +46,7 @@ use crate::types::{Codebase, CodebaseName, Project, ProjectName, ProjectType};
 ///
 /// Creates the directory (mode `0700`) if it does not yet exist.
 pub fn project_dir_at(home: &Path, project: &ProjectName) -> Result<PathBuf, RegistryError> {
-    let dir = home.join(".orchestra").join("projects").join(&project.0);
+    let dir = paths::dirs_at(home).registry_root.join(&project.0);
     if !dir.exists() {
         std::fs::create_dir_all(&dir)?;
         set_dir_permissions(&dir)?;
@@ -52,15 +65,15 @@ pub fn codebase_path_at(
     project: &ProjectName,
     codebase: &CodebaseName,
 ) -> PathBuf {
-    home.join(".orchestra")
-        .join("projects")
+    paths::dirs_at(home)
+        .registry_root
         .join(&project.0)
         .join(format!("{}.yaml", codebase.0))
 }
 
 /// Lists the names of all project directories under `<home>/.orchestra/projects/`.
 pub fn list_project_names_at(home: &Path) -> Result<Vec<ProjectName>, RegistryError> {
-    let dir = home.join(".orchestra").join("projects");
+    let dir = paths::dirs_at(home).registry_root;
     if !dir.exists() {
         return Ok(vec![]);
     }
@@ -85,7 +98,11 @@ pub fn list_project_names() -> Result<Vec<ProjectName>, RegistryError> {
 /// Load a single codebase from `<home>/.orchestra/projects/<project>/<codebase>.yaml`.
 ///
 /// Returns `RegistryError::RegistryNotFound` if absent,
-/// `RegistryError::Parse` (with path + line context) if malformed YAML.
+/// `RegistryError::ParseDetailed` (with line, column and an annotated snippet)
+/// if malformed YAML, falling back to `RegistryError::Parse` when serde_yaml
+/// reports no location,
+/// `RegistryError::UnsupportedSchema` if written by a newer orchestra.
+/// Files on an older schema are migrated and rewritten before returning.
 pub fn load_codebase_at(
     home: &Path,
     project: &ProjectName,
@@ -95,8 +112,7 @@ pub fn load_codebase_at(
     if !path.exists() {
         return Err(RegistryError::RegistryNotFound { path });
     }
-    let contents = std::fs::read_to_string(&path)?;
-    serde_yaml::from_str(&contents).map_err(|e| RegistryError::Parse { path, source: e })
+    read_codebase_file(&path)
 }
 
 /// `load_codebase_at` convenience wrapper.
@@ -110,13 +126,52 @@ pub fn load_codebase(
 /// Walk `<home>/.orchestra/projects/*/*.yaml` and return all codebases grouped
 /// by project. Results are sorted deterministically (project name, then codebase name).
 ///
-/// Skips `project.yaml` index files.
+/// Skips `project.yaml` index files. Fails on the first file that cannot be
+/// read or parsed; [`list_codebases_lossy_at`] lists the rest instead.
 pub fn list_codebases_at(
     home: &Path,
 ) -> Result<Vec<(ProjectName, Codebase)>, RegistryError> {
-    let projects_dir = home.join(".orchestra").join("projects");
+    let listing = list_codebases_lossy_at(home)?;
+    match listing.errors.into_iter().next() {
+        Some(failed) => Err(failed.error),
+        None => Ok(listing.codebases),
+    }
+}
+
+/// A registry file (or project directory) that could not be read or parsed.
+#[derive(Debug)]
+pub struct RegistryFileError {
+    pub path: PathBuf,
+    pub error: RegistryError,
+}
+
+impl std::fmt::Display for RegistryFileError {
+    /// The error, led by the path unless the error already names it.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.error {
+            RegistryError::Io(err) => write!(f, "cannot read {}: {err}", self.path.display()),
+            error => write!(f, "{error}"),
+        }
+    }
+}
+
+/// Every codebase that could be loaded, plus the files that could not.
+#[derive(Debug, Default)]
+pub struct RegistryListing {
+    /// Sorted as by [`list_codebases_at`].
+    pub codebases: Vec<(ProjectName, Codebase)>,
+    pub errors: Vec<RegistryFileError>,
+}
+
+/// [`list_codebases_at`], but a file that fails to read or parse is recorded
+/// in [`RegistryListing::errors`] and the walk goes on, so one corrupt or
+/// stray YAML file does not hide every other codebase. Only a failure to
+/// read the projects directory itself is an error.
+pub fn list_codebases_lossy_at(home: &Path) -> Result<RegistryListing, RegistryError> {
+    let projects_dir = paths::dirs_at(home).registry_root;
+    let mut listing = RegistryListing::default();
     if !projects_dir.exists() {
-        return Ok(vec![]);
+        return Ok(listing);
     }
 
     let mut project_entries: Vec<_> = std::fs::read_dir(&projects_dir)?
@@ -125,13 +180,19 @@ pub fn list_codebases_at(
         .collect();
     project_entries.sort_by_key(|e| e.file_name());
 
-    let mut result = Vec::new();
     for proj_entry in project_entries {
         let project_name = ProjectName::from(proj_entry.file_name().to_string_lossy().into_owned());
 
-        let mut file_entries: Vec<_> = std::fs::read_dir(proj_entry.path())?
-            .filter_map(|e| e.ok())
-            .collect();
+        let mut file_entries: Vec<_> = match std::fs::read_dir(proj_entry.path()) {
+            Ok(entries) => entries.filter_map(|e| e.ok()).collect(),
+            Err(err) => {
+                listing.errors.push(RegistryFileError {
+                    path: proj_entry.path(),
+                    error: err.into(),
+                });
+                continue;
+            }
+        };
         file_entries.sort_by_key(|e| e.file_name());
 
         for file_entry in file_entries {
@@ -140,14 +201,14 @@ pub fn list_codebases_at(
             if !name.ends_with(".yaml") || name == "project.yaml" {
                 continue;
             }
-            let contents = std::fs::read_to_string(file_entry.path())?;
-            let codebase: Codebase = serde_yaml::from_str(&contents).map_err(|e| {
-                RegistryError::Parse { path: file_entry.path(), source: e }
-            })?;
-            result.push((project_name.clone(), codebase));
+            let path = file_entry.path();
+            match read_codebase_file(&path) {
+                Ok(codebase) => listing.codebases.push((project_name.clone(), codebase)),
+                Err(error) => listing.errors.push(RegistryFileError { path, error }),
+            }
         }
     }
-    Ok(result)
+    Ok(listing)
 }
 
 /// `list_codebases_at` convenience wrapper.
@@ -207,20 +268,99 @@ fn scaffold_project_index(home: &Path, project: &ProjectName) -> Result<(), Regi
 }
 
 // ---------------------------------------------------------------------------
-// 5. Init
+// 5. Name validation
+// ---------------------------------------------------------------------------
+
+/// Longest project or codebase name accepted, in characters.
+pub const MAX_NAME_LEN: usize = 100;
+
+/// Check that `name` can be used as a project or codebase name.
+///
+/// Names become file and directory names under the registry (`<name>.yaml`,
+/// hash stores, lock files), so they must be non-empty, at most
+/// [`MAX_NAME_LEN`] characters, contain no path separator or NUL, not start
+/// with a dot, and not be the reserved `project` (the project index file).
+///
+/// Only new entries are checked; existing registry files still load.
+pub fn validate_name(name: &str) -> Result<(), RegistryError> {
+    let reason = if name.is_empty() {
+        "name cannot be empty".to_string()
+    } else if name.chars().count() > MAX_NAME_LEN {
+        format!("name is longer than {MAX_NAME_LEN} characters")
+    } else if name.contains(['/', '\\']) {
+        "name cannot contain a path separator".to_string()
+    } else if name.contains('\0') {
+        "name cannot contain a NUL character".to_string()
+    } else if name.starts_with('.') {
+        "name cannot start with a dot".to_string()
+    } else if name == "project" {
+        "'project' is reserved for the project index file".to_string()
+    } else {
+        return Ok(());
+    };
+    Err(RegistryError::InvalidName {
+        name: name.to_string(),
+        reason,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// 6. Init
 // ---------------------------------------------------------------------------
 
 /// Register a codebase at `codebase_path` under `project_name`.
 ///
 /// Creates `<home>/.orchestra/projects/<project_name>/<codebase_name>.yaml`.
 /// Idempotent: if the file already exists, loads and returns it unchanged.
+///
+/// `codebase_path` is canonicalized first, so relative paths and symlinks are
+/// stored as the absolute real path and the codebase is named after the real
+/// directory. Returns `RegistryError::PathNotFound` if it cannot be resolved
+/// and `RegistryError::DuplicatePath` if another codebase is already
+/// registered at the same real path; neither writes anything.
+///
+/// Returns `RegistryError::OverlappingPath` if `codebase_path` equals, contains,
+/// or lies inside the path of another registered codebase; use
+/// [`init_allow_overlap_at`] to register it anyway. Returns
+/// `RegistryError::InvalidName` if the project name or the directory name
+/// fails [`validate_name`].
 pub fn init_at(
     codebase_path: PathBuf,
     project_name: ProjectName,
     project_type: Option<ProjectType>,
     home: &Path,
+) -> Result<Codebase, RegistryError> {
+    init_inner(codebase_path, project_name, project_type, home, false)
+}
+
+/// Like [`init_at`], but skips the overlap check (intentional monorepo setups).
+/// Registering the exact same path twice is still an error.
+pub fn init_allow_overlap_at(
+    codebase_path: PathBuf,
+    project_name: ProjectName,
+    project_type: Option<ProjectType>,
+    home: &Path,
+) -> Result<Codebase, RegistryError> {
+    init_inner(codebase_path, project_name, project_type, home, true)
+}
+
+fn init_inner(
+    codebase_path: PathBuf,
+    project_name: ProjectName,
+    project_type: Option<ProjectType>,
+    home: &Path,
+    allow_overlap: bool,
 ) -> Result<Codebase, RegistryError> {
     let now = Utc::now();
+    let codebase_path = match codebase_path.canonicalize() {
+        Ok(path) => path,
+        Err(source) => {
+            return Err(RegistryError::PathNotFound {
+                path: codebase_path,
+                source,
+            })
+        }
+    };
     let codebase_name = CodebaseName::from(
         codebase_path
             .file_name()
@@ -228,12 +368,18 @@ pub fn init_at(
             .to_string_lossy()
             .into_owned(),
     );
+    validate_name(&project_name.0)?;
+    validate_name(&codebase_name.0)?;
 
     // Idempotent: return existing if already registered
     let yaml_path = codebase_path_at(home, &project_name, &codebase_name);
     if yaml_path.exists() {
         return load_codebase_at(home, &project_name, &codebase_name);
     }
+    check_duplicate(home, &codebase_path)?;
+    if !allow_overlap {
+        check_overlap(home, &codebase_path)?;
+    }
 
     let codebase = Codebase {
         name: codebase_name.clone(),
@@ -241,15 +387,29 @@ pub fn init_at(
         projects: vec![Project {
             name: ProjectName::from(codebase_name.0.clone()),
             project_type: project_type.unwrap_or_default(),
+            framework: None,
+            detection: None,
             tasks: vec![],
             agents: vec![],
+            commands: BTreeMap::new(),
+            extra: BTreeMap::new(),
         }],
         conventions: vec![],
         skills: vec![],
         notes: vec![],
         tracked_files: vec![],
+        max_rendered_tasks: None,
+        output_root: None,
+        manifest: None,
+        backups: None,
+        enforce_budget: None,
+        hooks: None,
+        extra_outputs: vec![],
+        agent_conventions: BTreeMap::new(),
+        schema_version: CURRENT_SCHEMA_VERSION,
         created_at: now,
         updated_at: now,
+        extra: BTreeMap::new(),
     };
 
     scaffold_project_index(home, &project_name)?;
@@ -266,14 +426,64 @@ pub fn init(
     init_at(codebase_path, project_name, project_type, &home()?)
 }
 
+/// `init_allow_overlap_at` convenience wrapper.
+pub fn init_allow_overlap(
+    codebase_path: PathBuf,
+    project_name: ProjectName,
+    project_type: Option<ProjectType>,
+) -> Result<Codebase, RegistryError> {
+    init_allow_overlap_at(codebase_path, project_name, project_type, &home()?)
+}
+
+/// Error if a codebase is already registered at the canonical `requested`
+/// path. Older entries may hold a symlinked path, so both sides are resolved.
+fn check_duplicate(home: &Path, requested: &Path) -> Result<(), RegistryError> {
+    for (project, codebase) in list_codebases_at(home)? {
+        if codebase.path.is_absolute() && normalize_path(&codebase.path) == requested {
+            return Err(RegistryError::DuplicatePath {
+                project: project.0,
+                codebase: codebase.name.0,
+                path: requested.to_path_buf(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Error if `requested` equals, contains, or lies inside a registered path.
+fn check_overlap(home: &Path, requested: &Path) -> Result<(), RegistryError> {
+    let requested_norm = normalize_path(requested);
+    for (_project, codebase) in list_codebases_at(home)? {
+        // `add_codebase_at` records the bare name as a placeholder path.
+        if !codebase.path.is_absolute() {
+            continue;
+        }
+        let existing = normalize_path(&codebase.path);
+        if requested_norm.starts_with(&existing) || existing.starts_with(&requested_norm) {
+            return Err(RegistryError::OverlappingPath {
+                codebase: codebase.name.0,
+                existing: codebase.path,
+                requested: requested.to_path_buf(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Resolve symlinks when the path exists, so `/tmp` and `/private/tmp` compare equal.
+fn normalize_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
 // ---------------------------------------------------------------------------
-// 6. Add codebase
+// 7. Add codebase
 // ---------------------------------------------------------------------------
 
 /// Register a new named codebase inside an existing project directory.
 ///
 /// Creates `<home>/.orchestra/projects/<project>/<codebase_name>.yaml`.
-/// Returns `RegistryError::RegistryNotFound` if the project directory doesn't exist.
+/// Returns `RegistryError::RegistryNotFound` if the project directory doesn't exist,
+/// or `RegistryError::InvalidName` if either name fails [`validate_name`].
 /// Idempotent: returns the existing file if already present.
 pub fn add_codebase_at(
     home: &Path,
@@ -281,7 +491,9 @@ pub fn add_codebase_at(
     codebase_name: CodebaseName,
     project_type: ProjectType,
 ) -> Result<Codebase, RegistryError> {
-    let project_dir = home.join(".orchestra").join("projects").join(&project.0);
+    validate_name(&project.0)?;
+    validate_name(&codebase_name.0)?;
+    let project_dir = paths::dirs_at(home).registry_root.join(&project.0);
     if !project_dir.exists() {
         return Err(RegistryError::RegistryNotFound { path: project_dir });
     }
@@ -298,15 +510,29 @@ pub fn add_codebase_at(
         projects: vec![Project {
             name: ProjectName::from(codebase_name.0.clone()),
             project_type,
+            framework: None,
+            detection: None,
             tasks: vec![],
             agents: vec![],
+            commands: BTreeMap::new(),
+            extra: BTreeMap::new(),
         }],
         conventions: vec![],
         skills: vec![],
         notes: vec![],
         tracked_files: vec![],
+        max_rendered_tasks: None,
+        output_root: None,
+        manifest: None,
+        backups: None,
+        enforce_budget: None,
+        hooks: None,
+        extra_outputs: vec![],
+        agent_conventions: BTreeMap::new(),
+        schema_version: CURRENT_SCHEMA_VERSION,
         created_at: now,
         updated_at: now,
+        extra: BTreeMap::new(),
     };
 
     save_codebase_at(home, project, &codebase)?;
@@ -323,7 +549,7 @@ pub fn add_codebase(
 }
 
 // ---------------------------------------------------------------------------
-// 7. Remove codebase
+// 8. Remove codebase
 // ---------------------------------------------------------------------------
 
 /// Remove a codebase from the registry by deleting its YAML file.
@@ -341,7 +567,7 @@ pub fn remove_codebase_at(
     }
 
     // Prune the project directory if it only contains project.yaml or is empty
-    let project_dir = home.join(".orchestra").join("projects").join(&project.0);
+    let project_dir = paths::dirs_at(home).registry_root.join(&project.0);
     if project_dir.exists() {
         let remaining: Vec<_> = std::fs::read_dir(&project_dir)?
             .filter_map(|e| e.ok())
@@ -368,10 +594,239 @@ pub fn remove_codebase(
     remove_codebase_at(&home()?, project, codebase)
 }
 
+// ---------------------------------------------------------------------------
+// 9. Remove project
+// ---------------------------------------------------------------------------
+
+/// What [`remove_project_at`] does when the project still has codebases.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RemoveProjectMode {
+    /// Fail with `RegistryError::ProjectNotEmpty` and change nothing.
+    #[default]
+    RefuseIfNotEmpty,
+    /// Remove every codebase file and its hash store along with the project.
+    Cascade,
+}
+
+/// Names of the codebases registered under `project`, sorted. Taken from the
+/// file names, so a codebase whose YAML no longer parses is still listed.
+///
+/// Returns `RegistryError::ProjectNotFound` if the project directory doesn't exist.
+pub fn project_codebase_names_at(
+    home: &Path,
+    project: &ProjectName,
+) -> Result<Vec<CodebaseName>, RegistryError> {
+    let project_dir = paths::dirs_at(home).registry_root.join(&project.0);
+    if !project_dir.is_dir() {
+        return Err(RegistryError::ProjectNotFound {
+            name: project.0.clone(),
+        });
+    }
+    let mut names: Vec<CodebaseName> = std::fs::read_dir(&project_dir)?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let fname = e.file_name().to_string_lossy().into_owned();
+            let stem = fname.strip_suffix(".yaml")?;
+            (stem != "project").then(|| CodebaseName::from(stem.to_string()))
+        })
+        .collect();
+    names.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(names)
+}
+
+/// Remove a project and its directory from the registry.
+///
+/// With [`RemoveProjectMode::RefuseIfNotEmpty`] a project that still has
+/// codebases is left untouched. With [`RemoveProjectMode::Cascade`] each
+/// codebase's YAML and hash store are deleted first. Files inside the
+/// codebases themselves are never touched here.
+///
+/// Returns the names of the codebases that were removed.
+pub fn remove_project_at(
+    home: &Path,
+    project: &ProjectName,
+    mode: RemoveProjectMode,
+) -> Result<Vec<CodebaseName>, RegistryError> {
+    let codebases = project_codebase_names_at(home, project)?;
+    if mode == RemoveProjectMode::RefuseIfNotEmpty && !codebases.is_empty() {
+        return Err(RegistryError::ProjectNotEmpty {
+            name: project.0.clone(),
+            codebases: codebases.iter().map(|name| name.0.clone()).collect(),
+        });
+    }
+
+    let dirs = paths::dirs_at(home);
+    for codebase in &codebases {
+        let path = codebase_path_at(home, project, codebase);
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        let store = dirs.hashes_dir.join(format!("{}.json", codebase.0));
+        if store.exists() {
+            std::fs::remove_file(&store)?;
+        }
+    }
+    std::fs::remove_dir_all(dirs.registry_root.join(&project.0))?;
+    Ok(codebases)
+}
+
+/// `remove_project_at` convenience wrapper.
+pub fn remove_project(
+    project: &ProjectName,
+    mode: RemoveProjectMode,
+) -> Result<Vec<CodebaseName>, RegistryError> {
+    remove_project_at(&home()?, project, mode)
+}
+
 // ---------------------------------------------------------------------------
 // Private helpers
 // ---------------------------------------------------------------------------
 
+/// Parse a codebase file, migrating and rewriting it if its schema is older.
+/// Tasks with a status this binary doesn't know are logged as warnings.
+fn read_codebase_file(path: &Path) -> Result<Codebase, RegistryError> {
+    let codebase = parse_codebase_file(path)?;
+    for warning in unknown_task_status_warnings(&codebase) {
+        tracing::warn!("{}: {warning}", path.display());
+    }
+    Ok(codebase)
+}
+
+/// One message per task whose status is [`TaskStatus::Unknown`].
+fn unknown_task_status_warnings(codebase: &Codebase) -> Vec<String> {
+    codebase
+        .projects
+        .iter()
+        .flat_map(|project| &project.tasks)
+        .filter_map(|task| match &task.status {
+            TaskStatus::Unknown(status) => Some(format!(
+                "task {} has unknown status '{status}'; it is kept as-is and not shown to agents",
+                task.id
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+fn parse_codebase_file(path: &Path) -> Result<Codebase, RegistryError> {
+    let contents = std::fs::read_to_string(path)?;
+    let doc: serde_yaml::Value =
+        serde_yaml::from_str(&contents).map_err(|e| parse_error(path, &contents, e))?;
+    if !doc.is_mapping() {
+        // Let serde report the shape mismatch as a normal parse error.
+        return serde_yaml::from_str(&contents).map_err(|e| parse_error(path, &contents, e));
+    }
+
+    let from = migrate::schema_version(&doc).map_err(|message| RegistryError::Migration {
+        path: path.to_path_buf(),
+        from: 1,
+        message,
+    })?;
+    if from > CURRENT_SCHEMA_VERSION {
+        return Err(RegistryError::UnsupportedSchema {
+            path: path.to_path_buf(),
+            found: from,
+            supported: CURRENT_SCHEMA_VERSION,
+        });
+    }
+    if from == CURRENT_SCHEMA_VERSION {
+        // Deserialize from the text rather than `doc` so errors keep their
+        // line and column.
+        return serde_yaml::from_str(&contents).map_err(|e| parse_error(path, &contents, e));
+    }
+
+    let doc = migrate::migrate(doc, from).map_err(|message| RegistryError::Migration {
+        path: path.to_path_buf(),
+        from,
+        message,
+    })?;
+    let codebase: Codebase = serde_yaml::from_value(doc).map_err(|source| RegistryError::Parse {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    rewrite_migrated(path, &codebase)?;
+    Ok(codebase)
+}
+
+/// Check that a codebase file parses, without migrating or rewriting it.
+///
+/// Returns the same errors as [`load_codebase_at`] for malformed YAML. Files
+/// on an older schema are only checked for YAML syntax.
+pub fn check_codebase_file(path: &Path) -> Result<(), RegistryError> {
+    let contents = std::fs::read_to_string(path)?;
+    let doc: serde_yaml::Value =
+        serde_yaml::from_str(&contents).map_err(|e| parse_error(path, &contents, e))?;
+    let current = !doc.is_mapping()
+        || migrate::schema_version(&doc).is_ok_and(|v| v == CURRENT_SCHEMA_VERSION);
+    if current {
+        serde_yaml::from_str::<Codebase>(&contents).map_err(|e| parse_error(path, &contents, e))?;
+    }
+    Ok(())
+}
+
+/// Build a parse error for `path`, with an annotated snippet of `contents`
+/// when serde_yaml reports a location.
+pub(crate) fn parse_error(path: &Path, contents: &str, source: serde_yaml::Error) -> RegistryError {
+    let Some(location) = source.location() else {
+        return RegistryError::Parse {
+            path: path.to_path_buf(),
+            source,
+        };
+    };
+    let (line, column) = (location.line(), location.column());
+    let mut message = source.to_string();
+    if let Some(index) = message.find(" at line ") {
+        message.truncate(index);
+    }
+    RegistryError::ParseDetailed {
+        path: path.to_path_buf(),
+        line,
+        column,
+        snippet: annotated_snippet(contents, line, column),
+        message,
+    }
+}
+
+/// The lines around `line` (1-based) with line numbers and a caret under
+/// `column` (1-based).
+///
+/// ```text
+///  2 | path: /code/api
+///  3 | projects: [unclosed
+///    |           ^
+///  4 | notes: []
+/// ```
+fn annotated_snippet(contents: &str, line: usize, column: usize) -> String {
+    let lines: Vec<&str> = contents.lines().collect();
+    let line = line.clamp(1, lines.len().max(1));
+    let first = line.saturating_sub(1).max(1);
+    let last = (line + 1).min(lines.len());
+    let width = last.max(line).to_string().len();
+
+    let mut snippet = String::new();
+    for number in first..=last.max(line) {
+        let text = lines.get(number - 1).copied().unwrap_or("");
+        snippet.push_str(&format!("{number:>width$} | {text}\n"));
+        if number == line {
+            let pad = " ".repeat(column.saturating_sub(1));
+            snippet.push_str(&format!("{:>width$} | {pad}^\n", ""));
+        }
+    }
+    snippet.truncate(snippet.trim_end().len());
+    snippet
+}
+
+/// Atomically replace `path` with the migrated `codebase`, keeping the
+/// original file's permissions.
+fn rewrite_migrated(path: &Path, codebase: &Codebase) -> Result<(), RegistryError> {
+    let permissions = std::fs::metadata(path)?.permissions();
+    let tmp_path = path.with_extension("yaml.tmp");
+    std::fs::write(&tmp_path, serde_yaml::to_string(codebase)?)?;
+    std::fs::set_permissions(&tmp_path, permissions)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
 fn home() -> Result<PathBuf, RegistryError> {
     dirs::home_dir().ok_or(RegistryError::HomeNotFound)
 }
@@ -448,15 +903,29 @@ mod tests {
             projects: vec![Project {
                 name: ProjectName::from("api"),
                 project_type: ProjectType::Backend,
+                framework: None,
+                detection: None,
                 tasks: vec![],
                 agents: vec![],
+                commands: BTreeMap::new(),
+                extra: BTreeMap::new(),
             }],
             conventions: vec![],
             skills: vec![],
             notes: vec![],
             tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: BTreeMap::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             created_at: now,
             updated_at: now,
+            extra: BTreeMap::new(),
         };
         save_codebase_at(home.path(), &proj(), &cb).expect("save");
         let loaded = load_codebase_at(home.path(), &proj(), &cb_name()).expect("load");
@@ -476,8 +945,18 @@ mod tests {
             skills: vec![],
             notes: vec![],
             tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: BTreeMap::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             created_at: now,
             updated_at: now,
+            extra: BTreeMap::new(),
         };
         save_codebase_at(home.path(), &proj(), &cb).expect("save");
         let tmp = codebase_path_at(home.path(), &proj(), &cb_name())
@@ -485,6 +964,73 @@ mod tests {
         assert!(!tmp.exists(), ".tmp must be gone after successful save");
     }
 
+    #[test]
+    fn future_task_status_loads_with_a_warning() {
+        use crate::types::{Task, TaskId};
+
+        let home = make_home();
+        let now = Utc::now();
+        let cb = Codebase {
+            name: cb_name(),
+            path: PathBuf::from("/code/copnow_api"),
+            projects: vec![Project {
+                name: ProjectName::from("api"),
+                project_type: ProjectType::Backend,
+                framework: None,
+                detection: None,
+                tasks: vec![Task {
+                    id: TaskId::from("t-001"),
+                    title: "Retire v1 endpoints".to_string(),
+                    status: TaskStatus::Pending,
+                    description: None,
+                    subtasks: vec![],
+                    notes: vec![],
+                    assigned_agent: None,
+                    created_at: now,
+                    updated_at: now,
+                    extra: BTreeMap::new(),
+                }],
+                agents: vec![],
+                commands: BTreeMap::new(),
+                extra: BTreeMap::new(),
+            }],
+            conventions: vec![],
+            skills: vec![],
+            notes: vec![],
+            tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: BTreeMap::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            created_at: now,
+            updated_at: now,
+            extra: BTreeMap::new(),
+        };
+        save_codebase_at(home.path(), &proj(), &cb).expect("save");
+        let path = codebase_path_at(home.path(), &proj(), &cb_name());
+        let yaml = std::fs::read_to_string(&path).unwrap();
+        assert!(yaml.contains("status: pending"), "{yaml}");
+        std::fs::write(&path, yaml.replace("status: pending", "status: archived")).unwrap();
+
+        let loaded = load_codebase_at(home.path(), &proj(), &cb_name()).expect("load");
+        assert_eq!(
+            loaded.projects[0].tasks[0].status,
+            TaskStatus::Unknown("archived".to_string())
+        );
+        let warnings = unknown_task_status_warnings(&loaded);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("t-001"), "{}", warnings[0]);
+        assert!(warnings[0].contains("'archived'"), "{}", warnings[0]);
+
+        save_codebase_at(home.path(), &proj(), &loaded).expect("save");
+        assert!(std::fs::read_to_string(&path).unwrap().contains("status: archived"));
+    }
+
     #[test]
     fn load_missing_codebase_returns_not_found() {
         let home = make_home();
@@ -492,6 +1038,74 @@ mod tests {
         assert!(matches!(err, RegistryError::RegistryNotFound { .. }));
     }
 
+    fn init_codebases(home: &TempDir, workspace: &TempDir, names: &[&str]) {
+        for name in names {
+            let dir = workspace.path().join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            init_at(dir, proj(), Some(ProjectType::Backend), home.path()).expect("init");
+        }
+    }
+
+    #[test]
+    fn remove_project_refuses_when_codebases_remain() {
+        let home = make_home();
+        let workspace = make_home();
+        init_codebases(&home, &workspace, &["api", "web"]);
+        let store = paths::dirs_at(home.path()).hashes_dir.join("api.json");
+        std::fs::create_dir_all(store.parent().unwrap()).unwrap();
+        std::fs::write(&store, "{}").unwrap();
+
+        let err = remove_project_at(home.path(), &proj(), RemoveProjectMode::default())
+            .unwrap_err();
+        assert!(matches!(err, RegistryError::ProjectNotEmpty { .. }), "got: {err}");
+        assert!(err.to_string().contains("2 codebase(s): api, web"));
+        assert_eq!(list_codebases_at(home.path()).unwrap().len(), 2);
+        assert!(store.exists());
+    }
+
+    #[test]
+    fn remove_project_cascade_removes_codebases_and_hash_stores() {
+        let home = make_home();
+        let workspace = make_home();
+        init_codebases(&home, &workspace, &["api", "web"]);
+        let store = paths::dirs_at(home.path()).hashes_dir.join("api.json");
+        std::fs::create_dir_all(store.parent().unwrap()).unwrap();
+        std::fs::write(&store, "{}").unwrap();
+        let other = ProjectName::from("tools");
+        add_codebase_at(home.path(), &proj(), CodebaseName::from("extra"), ProjectType::Backend)
+            .unwrap();
+        project_dir_at(home.path(), &other).unwrap();
+
+        let removed = remove_project_at(home.path(), &proj(), RemoveProjectMode::Cascade)
+            .expect("remove");
+        assert_eq!(
+            removed,
+            vec![
+                CodebaseName::from("api"),
+                CodebaseName::from("extra"),
+                CodebaseName::from("web")
+            ]
+        );
+        assert!(!store.exists());
+        assert!(list_codebases_at(home.path()).unwrap().is_empty());
+        assert_eq!(list_project_names_at(home.path()).unwrap(), vec![other]);
+        assert!(workspace.path().join("api").is_dir());
+    }
+
+    #[test]
+    fn remove_project_without_codebases_and_unknown_project() {
+        let home = make_home();
+        project_dir_at(home.path(), &proj()).unwrap();
+        assert!(remove_project_at(home.path(), &proj(), RemoveProjectMode::default())
+            .expect("remove empty")
+            .is_empty());
+        assert!(list_project_names_at(home.path()).unwrap().is_empty());
+
+        let err = remove_project_at(home.path(), &proj(), RemoveProjectMode::Cascade)
+            .unwrap_err();
+        assert!(matches!(err, RegistryError::ProjectNotFound { .. }), "got: {err}");
+    }
+
     #[test]
     fn list_codebases_empty_when_no_projects() {
         let home = make_home();
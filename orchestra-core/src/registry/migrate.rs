@@ -0,0 +1,130 @@
+//! Registry YAML schema migrations.
+//!
+//! Each codebase file carries a `schema_version` (absent means v1). When a
+//! file older than [`CURRENT_SCHEMA_VERSION`] is loaded, the migrations below
+//! run in sequence on the raw YAML value before it is deserialized, and the
+//! registry rewrites the upgraded file in place.
+//!
+//! # Adding a migration
+//!
+//! 1. Bump [`CURRENT_SCHEMA_VERSION`] in `types.rs`.
+//! 2. Append a `vN_to_vN+1` function to [`MIGRATIONS`]. It receives the
+//!    document at version N and returns it at version N+1; it must not touch
+//!    `schema_version` (the runner stamps it).
+
+use serde_yaml::{Mapping, Value};
+
+use crate::types::CURRENT_SCHEMA_VERSION;
+
+/// A single-step migration from version N to N+1.
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+/// `MIGRATIONS[i]` upgrades a document from version `i + 1` to `i + 2`.
+pub const MIGRATIONS: &[Migration] = &[v1_to_v2];
+
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Schema version recorded in `doc`, defaulting to 1 when the key is absent.
+pub fn schema_version(doc: &Value) -> Result<u32, String> {
+    match doc.get(SCHEMA_VERSION_KEY) {
+        None => Ok(1),
+        Some(v) => v
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .filter(|n| *n >= 1)
+            .ok_or_else(|| format!("invalid {SCHEMA_VERSION_KEY}: {v:?}")),
+    }
+}
+
+/// Upgrade `doc` from `from` to [`CURRENT_SCHEMA_VERSION`].
+///
+/// The caller is responsible for rejecting versions newer than the current one.
+pub fn migrate(mut doc: Value, from: u32) -> Result<Value, String> {
+    for version in from..CURRENT_SCHEMA_VERSION {
+        let step = MIGRATIONS
+            .get(version as usize - 1)
+            .ok_or_else(|| format!("no migration registered from v{version}"))?;
+        doc = step(doc).map_err(|e| format!("v{version} → v{}: {e}", version + 1))?;
+        mapping_mut(&mut doc)?.insert(
+            Value::from(SCHEMA_VERSION_KEY),
+            Value::from(version + 1),
+        );
+    }
+    Ok(doc)
+}
+
+/// v1 → v2: hoist the legacy `tracked` list into `tracked_files`.
+///
+/// Early writeback builds stored extra tracked paths under `tracked`. When
+/// both keys exist the entries are merged, keeping `tracked_files` first.
+fn v1_to_v2(mut doc: Value) -> Result<Value, String> {
+    let map = mapping_mut(&mut doc)?;
+    let Some(legacy) = map.remove("tracked") else {
+        return Ok(doc);
+    };
+    let Value::Sequence(legacy) = legacy else {
+        return Err("`tracked` must be a list".to_owned());
+    };
+    match map.get_mut("tracked_files") {
+        Some(Value::Sequence(current)) => {
+            for entry in legacy {
+                if !current.contains(&entry) {
+                    current.push(entry);
+                }
+            }
+        }
+        Some(_) => return Err("`tracked_files` must be a list".to_owned()),
+        None => {
+            map.insert(Value::from("tracked_files"), Value::Sequence(legacy));
+        }
+    }
+    Ok(doc)
+}
+
+fn mapping_mut(doc: &mut Value) -> Result<&mut Mapping, String> {
+    doc.as_mapping_mut()
+        .ok_or_else(|| "registry document is not a mapping".to_owned())
+}
+
+// ---------------------------------------------------------------------------
+// Unit tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(s: &str) -> Value {
+        serde_yaml::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn every_version_has_a_migration() {
+        assert_eq!(MIGRATIONS.len() as u32, CURRENT_SCHEMA_VERSION - 1);
+    }
+
+    #[test]
+    fn missing_version_is_v1() {
+        assert_eq!(schema_version(&yaml("name: api")), Ok(1));
+        assert_eq!(schema_version(&yaml("schema_version: 2")), Ok(2));
+        assert!(schema_version(&yaml("schema_version: zero")).is_err());
+    }
+
+    #[test]
+    fn v1_hoists_tracked_into_tracked_files() {
+        let doc = migrate(yaml("name: api\ntracked: [a.md, b.md]\n"), 1).unwrap();
+        assert_eq!(doc["tracked_files"], yaml("[a.md, b.md]"));
+        assert!(doc.get("tracked").is_none());
+        assert_eq!(schema_version(&doc), Ok(CURRENT_SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn v1_merges_tracked_without_duplicates() {
+        let doc = migrate(
+            yaml("tracked_files: [a.md]\ntracked: [a.md, b.md]\n"),
+            1,
+        )
+        .unwrap();
+        assert_eq!(doc["tracked_files"], yaml("[a.md, b.md]"));
+    }
+}
@@ -3,6 +3,7 @@
 //! All path fields use `PathBuf`; never `&str` or `String` for filesystem paths.
 //! All types are serializable/deserializable via serde + serde_yaml.
 
+use std::collections::BTreeMap;
 use std::fmt;
 use std::path::PathBuf;
 
@@ -92,6 +93,8 @@ pub enum ProjectType {
     Frontend,
     Mobile,
     Ml,
+    /// Infrastructure as code: Terraform, Pulumi, Ansible, Helm.
+    Infra,
 }
 
 impl fmt::Display for ProjectType {
@@ -101,19 +104,73 @@ impl fmt::Display for ProjectType {
             ProjectType::Frontend => write!(f, "frontend"),
             ProjectType::Mobile => write!(f, "mobile"),
             ProjectType::Ml => write!(f, "ml"),
+            ProjectType::Infra => write!(f, "infra"),
         }
     }
 }
 
 /// Status of a task.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
-#[serde(rename_all = "lowercase")]
+///
+/// Serialized lowercase. A status this binary doesn't know, such as one
+/// written by a newer orchestra, loads as [`TaskStatus::Unknown`] and is
+/// written back unchanged instead of failing the whole codebase.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum TaskStatus {
     #[default]
     Pending,
     InProgress,
     Blocked,
     Done,
+    /// Dropped without being finished. Hidden from agent files like `Done`.
+    Cancelled,
+    Unknown(String),
+}
+
+impl TaskStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            TaskStatus::Pending => "pending",
+            TaskStatus::InProgress => "inprogress",
+            TaskStatus::Blocked => "blocked",
+            TaskStatus::Done => "done",
+            TaskStatus::Cancelled => "cancelled",
+            TaskStatus::Unknown(status) => status,
+        }
+    }
+
+    /// Whether the task is still open work: pending, in progress or blocked.
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            TaskStatus::Pending | TaskStatus::InProgress | TaskStatus::Blocked
+        )
+    }
+}
+
+impl fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for TaskStatus {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskStatus {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let status = String::deserialize(deserializer)?;
+        Ok(match status.as_str() {
+            "pending" => TaskStatus::Pending,
+            "inprogress" => TaskStatus::InProgress,
+            "blocked" => TaskStatus::Blocked,
+            "done" => TaskStatus::Done,
+            "cancelled" => TaskStatus::Cancelled,
+            _ => TaskStatus::Unknown(status),
+        })
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -147,8 +204,16 @@ pub struct Task {
     /// Timestamped notes written back by agents via update blocks.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub notes: Vec<String>,
+    /// Agent this task is for, by lowercase agent name (e.g. `claude`).
+    /// Only that agent's files list it; unassigned tasks are listed in
+    /// every agent's files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assigned_agent: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Keys this version does not know; see [`Codebase::extra`].
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
 }
 
 /// Configuration for a specific AI agent assigned to a codebase.
@@ -166,15 +231,102 @@ pub struct AgentConfig {
 pub struct Project {
     pub name: ProjectName,
     pub project_type: ProjectType,
+    /// Framework reported by stack detection, e.g. `"FastAPI"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub framework: Option<String>,
+    /// How `orchestra init --detect` settled on `project_type`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detection: Option<StackDetection>,
     #[serde(default)]
     pub tasks: Vec<Task>,
     #[serde(default)]
     pub agents: Vec<AgentConfig>,
+    /// Named shell commands for working on the project (e.g. `test` →
+    /// `cargo test --workspace`), rendered into agent files.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub commands: BTreeMap<String, String>,
+    /// Keys this version does not know; see [`Codebase::extra`].
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+/// The outcome of stack detection, from `orchestra init --detect` or
+/// `orchestra detect --save`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StackDetection {
+    /// Detector confidence: `high` (framework identified) or `medium`
+    /// (indicator file only).
+    pub confidence: String,
+    /// Whether the user replaced the detected project type.
+    #[serde(default)]
+    pub overridden: bool,
+    /// When the stack was detected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_at: Option<DateTime<Utc>>,
+    /// Root indicator files present at detection time, e.g. `Cargo.toml`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub indicators: Vec<String>,
+    /// SHA-256 over the contents of `indicators` at detection time; a
+    /// mismatch means the stored stack may be outdated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indicators_sha256: Option<String>,
+    /// Primary language, e.g. `"Rust"`; with `framework` and
+    /// `package_manager` it picks the suggested commands.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Package manager, e.g. `"pnpm"` or `"poetry"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_manager: Option<String>,
+}
+
+/// Shell commands run around a codebase's sync, from the codebase root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct SyncHooks {
+    /// Runs before anything is rendered; a failure aborts the sync.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_sync: Option<String>,
+    /// Runs after a sync that wrote at least one file; never in dry-run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_sync: Option<String>,
+    /// Also run the hooks when the daemon starts the sync. Off by default so
+    /// a background process never runs commands by surprise.
+    #[serde(default)]
+    pub allow_in_daemon: bool,
+}
+
+impl SyncHooks {
+    /// Whether no hook command is set.
+    pub fn is_empty(&self) -> bool {
+        self.pre_sync.is_none() && self.post_sync.is_none()
+    }
+}
+
+/// An extra file rendered for a codebase from a user template.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtraOutput {
+    /// Template name under the user template directory, e.g.
+    /// `ai-context.md.tera`.
+    pub template: String,
+    /// Where the rendered file goes, relative to the codebase root (or
+    /// `output_root` in out-of-tree mode).
+    pub output_path: PathBuf,
+}
+
+/// Registry YAML schema version written by this build.
+///
+/// Older files are upgraded by `registry::migrate` when loaded.
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+fn implicit_schema_version() -> u32 {
+    1
 }
 
 /// A codebase managed by Orchestra.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Codebase {
+    /// On-disk schema version. Files written before versioning are v1.
+    #[serde(default = "implicit_schema_version")]
+    pub schema_version: u32,
     pub name: CodebaseName,
     /// Absolute path to the codebase root on disk.
     pub path: PathBuf,
@@ -192,8 +344,45 @@ pub struct Codebase {
     /// Additional tracked files mentioned via writeback protocol.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tracked_files: Vec<PathBuf>,
+    /// Maximum number of active tasks rendered into agent files. `None` uses
+    /// the renderer default; overflow is summarised as a single line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rendered_tasks: Option<usize>,
+    /// Directory that receives generated files instead of `path` (out-of-tree
+    /// mode). `None` writes into the codebase itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_root: Option<PathBuf>,
+    /// Whether sync writes `.orchestra-manifest.json` listing the managed
+    /// files. `None` writes it; `Some(false)` turns it off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<bool>,
+    /// Whether sync saves locally modified managed files under
+    /// `~/.orchestra/backups/` before overwriting them. `None` backs them up;
+    /// `Some(false)` turns it off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backups: Option<bool>,
+    /// Whether sync trims agent files that are over their token budget.
+    /// `None` only warns about them; `Some(true)` trims them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enforce_budget: Option<bool>,
+    /// Commands run before and after each sync. `None` runs nothing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<SyncHooks>,
+    /// Files rendered from user templates alongside the agent files.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_outputs: Vec<ExtraOutput>,
+    /// Per-agent replacements for the built-in convention bullets, keyed by
+    /// lowercase agent name (`claude`, `roo`, ...). Agents without an entry
+    /// keep their defaults.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub agent_conventions: BTreeMap<String, Vec<String>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Keys this version does not know, such as fields added by a newer
+    /// orchestra. They are written back after the known fields, in key
+    /// order, so saving the codebase does not drop them.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
 }
 
 /// Root of the Orchestra YAML registry.
@@ -247,6 +436,7 @@ mod tests {
     fn project_type_display() {
         assert_eq!(ProjectType::Mobile.to_string(), "mobile");
         assert_eq!(ProjectType::Ml.to_string(), "ml");
+        assert_eq!(ProjectType::Infra.to_string(), "infra");
     }
 
     #[test]
@@ -254,4 +444,23 @@ mod tests {
         let yaml = serde_yaml::to_string(&TaskStatus::Done).expect("serialize task status");
         assert_eq!(yaml.trim(), "done");
     }
+
+    #[test]
+    fn task_status_cancelled_round_trips() {
+        let yaml = serde_yaml::to_string(&TaskStatus::Cancelled).expect("serialize task status");
+        assert_eq!(yaml.trim(), "cancelled");
+        let status: TaskStatus = serde_yaml::from_str(&yaml).expect("deserialize task status");
+        assert_eq!(status, TaskStatus::Cancelled);
+        assert_eq!(TaskStatus::Cancelled.to_string(), "cancelled");
+        assert!(!TaskStatus::Cancelled.is_active());
+    }
+
+    #[test]
+    fn unknown_task_status_is_kept_verbatim() {
+        let status: TaskStatus = serde_yaml::from_str("archived").expect("deserialize task status");
+        assert_eq!(status, TaskStatus::Unknown("archived".to_string()));
+        assert!(!status.is_active());
+        let yaml = serde_yaml::to_string(&status).expect("serialize task status");
+        assert_eq!(yaml.trim(), "archived");
+    }
 }
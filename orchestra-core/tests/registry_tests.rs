@@ -1,19 +1,29 @@
 //! Registry error-message, atomic-write-safety, and init integration tests.
 //! Migrated for per-codebase storage: ~/.orchestra/projects/<project>/<codebase>.yaml
 
+use assert_fs::fixture::ChildPath;
 use assert_fs::prelude::*;
 use chrono::Utc;
 use orchestra_core::{
     registry,
-    types::{CodebaseName, ProjectName, ProjectType},
+    types::{CodebaseName, ProjectName, ProjectType, CURRENT_SCHEMA_VERSION},
     RegistryError,
 };
 use predicates::prelude::predicate;
+use std::collections::BTreeMap;
 use std::fs;
 
 fn proj() -> ProjectName { ProjectName::from("copnow") }
 fn cb() -> CodebaseName { CodebaseName::from("copnow_api") }
 
+/// Temp dirs are named `.tmpXXXX`, which `validate_name` rejects, so register
+/// a named directory inside one instead.
+fn named_dir(temp: &assert_fs::TempDir, name: &str) -> ChildPath {
+    let dir = temp.child(name);
+    dir.create_dir_all().expect("mkdir");
+    dir
+}
+
 // ---------------------------------------------------------------------------
 // 1. Load error messages
 // ---------------------------------------------------------------------------
@@ -36,14 +46,14 @@ fn load_corrupt_yaml_returns_parse_error_with_path() {
         .expect("write");
 
     let err = registry::load_codebase_at(home.path(), &proj(), &cb()).unwrap_err();
-    assert!(matches!(err, RegistryError::Parse { .. }), "got: {err}");
+    assert!(matches!(err, RegistryError::ParseDetailed { .. }), "got: {err}");
     let msg = err.to_string();
     assert!(msg.contains("copnow_api.yaml"), "must contain file path, got: {msg}");
-    let source_msg = match &err {
-        RegistryError::Parse { source, .. } => source.to_string(),
+    let message = match &err {
+        RegistryError::ParseDetailed { message, .. } => message.clone(),
         _ => unreachable!(),
     };
-    assert!(!source_msg.is_empty(), "serde_yaml must provide error context");
+    assert!(!message.is_empty(), "serde_yaml must provide error context");
 }
 
 #[test]
@@ -54,7 +64,55 @@ fn load_wrong_type_yaml_returns_parse_error() {
     fs::write(dir.join("copnow_api.yaml"), b"- this is a list, not a mapping\n").expect("write");
 
     let err = registry::load_codebase_at(home.path(), &proj(), &cb()).unwrap_err();
-    assert!(matches!(err, RegistryError::Parse { .. }), "got: {err}");
+    assert!(
+        matches!(err, RegistryError::Parse { .. } | RegistryError::ParseDetailed { .. }),
+        "got: {err}"
+    );
+}
+
+#[test]
+fn parse_error_reports_line_and_snippet() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let dir = home.path().join(".orchestra").join("projects").join("copnow");
+    fs::create_dir_all(&dir).expect("mkdir");
+    let yaml = "name: copnow_api\npath: /code/copnow_api\nprojects: [unclosed\nnotes: []\n";
+    fs::write(dir.join("copnow_api.yaml"), yaml).expect("write");
+
+    let err = registry::load_codebase_at(home.path(), &proj(), &cb()).unwrap_err();
+    let RegistryError::ParseDetailed { line, snippet, .. } = &err else {
+        panic!("expected ParseDetailed, got: {err}");
+    };
+    assert_eq!(*line, 4, "got: {err}");
+    assert!(snippet.contains("3 | projects: [unclosed"), "{snippet}");
+    assert!(snippet.contains("4 | notes: []"), "{snippet}");
+    assert!(snippet.contains('^'), "{snippet}");
+    assert!(err.to_string().contains("line 4"), "{err}");
+
+    let listed = registry::list_codebases_at(home.path()).unwrap_err();
+    assert!(
+        matches!(listed, RegistryError::ParseDetailed { line: 4, .. }),
+        "got: {listed}"
+    );
+}
+
+#[test]
+fn lossy_listing_keeps_good_codebases_and_reports_corrupt_files() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let cb_dir = named_dir(&workspace, "copnow_api");
+    registry::init_at(cb_dir.path().to_path_buf(), proj(), None, home.path()).expect("init");
+    let stray = home.path().join(".orchestra/projects/copnow/backup.yaml");
+    fs::write(&stray, "projects: [unclosed\n").expect("write");
+
+    let listing = registry::list_codebases_lossy_at(home.path()).expect("lossy list");
+    assert_eq!(listing.codebases.len(), 1);
+    assert_eq!(listing.codebases[0].1.name, cb());
+    assert_eq!(listing.errors.len(), 1);
+    assert_eq!(listing.errors[0].path, stray);
+    assert!(listing.errors[0].error.to_string().contains("backup.yaml"));
+
+    // The strict listing still fails on the same file.
+    assert!(registry::list_codebases_at(home.path()).is_err());
 }
 
 // ---------------------------------------------------------------------------
@@ -73,8 +131,18 @@ fn save_cleans_up_tmp_file() {
         skills: vec![],
         notes: vec![],
         tracked_files: vec![],
+        max_rendered_tasks: None,
+        output_root: None,
+        manifest: None,
+        backups: None,
+        enforce_budget: None,
+        hooks: None,
+        extra_outputs: vec![],
+        agent_conventions: BTreeMap::new(),
+        schema_version: CURRENT_SCHEMA_VERSION,
         created_at: now,
         updated_at: now,
+        extra: BTreeMap::new(),
     };
     registry::save_codebase_at(home.path(), &proj(), &codebase).expect("save");
 
@@ -95,8 +163,18 @@ fn mid_write_crash_leaves_original_intact() {
         skills: vec![],
         notes: vec![],
         tracked_files: vec![],
+        max_rendered_tasks: None,
+        output_root: None,
+        manifest: None,
+        backups: None,
+        enforce_budget: None,
+        hooks: None,
+        extra_outputs: vec![],
+        agent_conventions: BTreeMap::new(),
+        schema_version: CURRENT_SCHEMA_VERSION,
         created_at: now,
         updated_at: now,
+        extra: BTreeMap::new(),
     };
     registry::save_codebase_at(home.path(), &proj(), &codebase).expect("save");
 
@@ -119,7 +197,8 @@ fn mid_write_crash_leaves_original_intact() {
 #[test]
 fn init_creates_per_codebase_yaml() {
     let home = assert_fs::TempDir::new().expect("home tempdir");
-    let codebase_dir = assert_fs::TempDir::new().expect("codebase tempdir");
+    let workspace = assert_fs::TempDir::new().expect("codebase tempdir");
+    let codebase_dir = named_dir(&workspace, "copnow_api");
 
     let codebase = registry::init_at(
         codebase_dir.path().to_path_buf(),
@@ -156,7 +235,8 @@ fn init_creates_per_codebase_yaml() {
 #[test]
 fn project_index_created_on_init() {
     let home = assert_fs::TempDir::new().expect("tempdir");
-    let codebase_dir = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let codebase_dir = named_dir(&workspace, "copnow_api");
 
     registry::init_at(codebase_dir.path().to_path_buf(), proj(), None, home.path())
         .expect("init");
@@ -168,7 +248,8 @@ fn project_index_created_on_init() {
 #[test]
 fn init_is_idempotent() {
     let home = assert_fs::TempDir::new().expect("tempdir");
-    let cb_dir = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let cb_dir = named_dir(&workspace, "copnow_api");
 
     registry::init_at(cb_dir.path().to_path_buf(), proj(), Some(ProjectType::Backend), home.path())
         .expect("first init");
@@ -181,6 +262,148 @@ fn init_is_idempotent() {
     assert_eq!(list[0].1.projects[0].project_type, ProjectType::Backend);
 }
 
+#[test]
+fn init_rejects_path_inside_registered_codebase() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let repo = named_dir(&workspace, "repo");
+    let web = repo.child("apps/web");
+    web.create_dir_all().expect("mkdir");
+
+    let root = registry::init_at(repo.path().to_path_buf(), proj(), None, home.path())
+        .expect("init root");
+    let err = registry::init_at(web.path().to_path_buf(), proj(), None, home.path())
+        .unwrap_err();
+
+    assert!(matches!(err, RegistryError::OverlappingPath { .. }), "got: {err}");
+    assert!(err.to_string().contains(&format!("codebase '{}'", root.name)), "got: {err}");
+    assert_eq!(registry::list_codebases_at(home.path()).expect("list").len(), 1);
+}
+
+#[test]
+fn init_rejects_parent_of_registered_codebase() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let repo = named_dir(&workspace, "repo");
+    let web = repo.child("apps/web");
+    web.create_dir_all().expect("mkdir");
+
+    registry::init_at(web.path().to_path_buf(), proj(), None, home.path()).expect("init web");
+    let err = registry::init_at(repo.path().to_path_buf(), proj(), None, home.path())
+        .unwrap_err();
+
+    match err {
+        RegistryError::OverlappingPath { codebase, existing, requested } => {
+            assert_eq!(codebase, "web");
+            assert_eq!(existing, web.path());
+            assert_eq!(requested, repo.path());
+        }
+        other => panic!("expected OverlappingPath, got: {other}"),
+    }
+}
+
+#[test]
+fn init_rejects_identical_path_under_another_project() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let cb_dir = named_dir(&workspace, "copnow_api");
+
+    registry::init_at(cb_dir.path().to_path_buf(), proj(), None, home.path()).expect("init");
+    let err = registry::init_at(
+        cb_dir.path().to_path_buf(),
+        ProjectName::from("acme"),
+        None,
+        home.path(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, RegistryError::DuplicatePath { .. }), "got: {err}");
+    assert!(
+        err.to_string().contains("already registered as codebase 'copnow_api' in project 'copnow'"),
+        "got: {err}"
+    );
+}
+
+#[test]
+fn init_stores_relative_path_as_canonical_absolute_path() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let cb_dir = named_dir(&workspace, "copnow_api");
+
+    // Walk up from the test's working directory to `/`, then back down, so
+    // the path is relative without changing the process-wide cwd.
+    let cwd = std::env::current_dir().expect("cwd");
+    let mut relative = std::path::PathBuf::new();
+    for _ in cwd.components().skip(1) {
+        relative.push("..");
+    }
+    relative.push(cb_dir.path().strip_prefix("/").expect("absolute temp dir"));
+    relative.push("nested/..");
+    fs::create_dir_all(cb_dir.path().join("nested")).expect("mkdir nested");
+
+    let codebase = registry::init_at(relative, proj(), None, home.path()).expect("init");
+    assert!(codebase.path.is_absolute(), "got: {}", codebase.path.display());
+    assert_eq!(codebase.path, cb_dir.path().canonicalize().unwrap());
+    assert_eq!(codebase.name, cb());
+    let stored = registry::load_codebase_at(home.path(), &proj(), &cb()).expect("load");
+    assert_eq!(stored.path, codebase.path);
+}
+
+#[cfg(unix)]
+#[test]
+fn init_through_symlink_matches_init_through_real_path() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let real = named_dir(&workspace, "copnow_api");
+    let link = workspace.path().join("api_link");
+    std::os::unix::fs::symlink(real.path(), &link).expect("symlink");
+
+    let via_link = registry::init_at(link.clone(), proj(), None, home.path()).expect("init link");
+    assert_eq!(via_link.name, cb(), "named after the real directory");
+    assert_eq!(via_link.path, real.path().canonicalize().unwrap());
+
+    let via_real = registry::init_at(real.path().to_path_buf(), proj(), None, home.path())
+        .expect("init real");
+    assert_eq!(via_real, via_link, "the second init returns the existing codebase");
+    assert_eq!(registry::list_codebases_at(home.path()).expect("list").len(), 1);
+
+    let err = registry::init_allow_overlap_at(link, ProjectName::from("acme"), None, home.path())
+        .unwrap_err();
+    assert!(matches!(err, RegistryError::DuplicatePath { .. }), "got: {err}");
+}
+
+#[test]
+fn init_nonexistent_path_errors_before_writing() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let missing = workspace.path().join("copnow_api");
+
+    let err = registry::init_at(missing.clone(), proj(), None, home.path()).unwrap_err();
+    match &err {
+        RegistryError::PathNotFound { path, .. } => assert_eq!(path, &missing),
+        other => panic!("expected PathNotFound, got: {other}"),
+    }
+    assert!(err.to_string().contains("cannot resolve codebase path"), "got: {err}");
+    assert!(!home.path().join(".orchestra").exists(), "nothing may be written");
+}
+
+#[test]
+fn init_allows_sibling_paths_and_forced_overlap() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let repo = named_dir(&workspace, "repo");
+    let web = repo.child("apps/web");
+    let api = repo.child("apps/api");
+    web.create_dir_all().expect("mkdir");
+    api.create_dir_all().expect("mkdir");
+
+    registry::init_at(web.path().to_path_buf(), proj(), None, home.path()).expect("init web");
+    registry::init_at(api.path().to_path_buf(), proj(), None, home.path()).expect("init api");
+    registry::init_allow_overlap_at(repo.path().to_path_buf(), proj(), None, home.path())
+        .expect("forced overlap");
+
+    assert_eq!(registry::list_codebases_at(home.path()).expect("list").len(), 3);
+}
+
 // ---------------------------------------------------------------------------
 // 4. Multiple codebases and list
 // ---------------------------------------------------------------------------
@@ -188,8 +411,9 @@ fn init_is_idempotent() {
 #[test]
 fn list_codebases_groups_by_project() {
     let home = assert_fs::TempDir::new().expect("tempdir");
-    let api_dir = assert_fs::TempDir::new().expect("tempdir");
-    let mobile_dir = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let api_dir = named_dir(&workspace, "copnow_api");
+    let mobile_dir = named_dir(&workspace, "copnow_mobile");
 
     registry::init_at(api_dir.path().to_path_buf(), proj(), Some(ProjectType::Backend), home.path())
         .expect("init api");
@@ -204,7 +428,8 @@ fn list_codebases_groups_by_project() {
 #[test]
 fn add_codebase_creates_new_yaml() {
     let home = assert_fs::TempDir::new().expect("tempdir");
-    let cb_dir = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let cb_dir = named_dir(&workspace, "copnow_api");
 
     registry::init_at(cb_dir.path().to_path_buf(), proj(), Some(ProjectType::Backend), home.path())
         .expect("init");
@@ -240,8 +465,9 @@ fn add_codebase_to_nonexistent_project_errors() {
 #[test]
 fn list_is_sorted_and_deterministic() {
     let home = assert_fs::TempDir::new().expect("tempdir");
-    let alpha = assert_fs::TempDir::new().expect("tempdir");
-    let beta = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let alpha = named_dir(&workspace, "alpha");
+    let beta = named_dir(&workspace, "beta");
 
     let proj_b = ProjectName::from("beta_project");
     let proj_a = ProjectName::from("alpha_project");
@@ -256,3 +482,223 @@ fn list_is_sorted_and_deterministic() {
     assert_eq!(list[0].0, proj_a);
     assert_eq!(list[1].0, proj_b);
 }
+
+// ---------------------------------------------------------------------------
+// Schema versioning
+// ---------------------------------------------------------------------------
+
+const V1_CODEBASE: &str = "\
+name: copnow_api
+path: /code/copnow_api
+tracked:
+- docs/api.md
+created_at: 2025-01-01T00:00:00Z
+updated_at: 2025-01-01T00:00:00Z
+";
+
+fn write_registry_file(home: &std::path::Path, contents: &str) -> std::path::PathBuf {
+    let dir = home.join(".orchestra").join("projects").join("copnow");
+    fs::create_dir_all(&dir).expect("mkdir");
+    let path = dir.join("copnow_api.yaml");
+    fs::write(&path, contents).expect("write");
+    path
+}
+
+#[test]
+fn v1_file_is_migrated_and_rewritten() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let path = write_registry_file(home.path(), V1_CODEBASE);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).expect("chmod");
+    }
+
+    let loaded = registry::load_codebase_at(home.path(), &proj(), &cb()).expect("load v1");
+    assert_eq!(loaded.schema_version, CURRENT_SCHEMA_VERSION);
+    assert_eq!(loaded.tracked_files, vec![std::path::PathBuf::from("docs/api.md")]);
+
+    let on_disk = fs::read_to_string(&path).expect("read");
+    assert!(on_disk.contains(&format!("schema_version: {CURRENT_SCHEMA_VERSION}")));
+    assert!(on_disk.contains("tracked_files:"));
+    assert!(!on_disk.contains("\ntracked:"));
+    assert!(!path.with_extension("yaml.tmp").exists());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o640, "migration must preserve permissions");
+    }
+
+    // A second load sees the current schema and leaves the file untouched.
+    let again = registry::load_codebase_at(home.path(), &proj(), &cb()).expect("reload");
+    assert_eq!(again, loaded);
+    assert_eq!(fs::read_to_string(&path).expect("read"), on_disk);
+}
+
+#[test]
+fn list_codebases_migrates_v1_files() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    write_registry_file(home.path(), V1_CODEBASE);
+
+    let list = registry::list_codebases_at(home.path()).expect("list");
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].1.schema_version, CURRENT_SCHEMA_VERSION);
+}
+
+#[test]
+fn newer_schema_asks_user_to_upgrade() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let future = format!("schema_version: {}\n{V1_CODEBASE}", CURRENT_SCHEMA_VERSION + 1);
+    let path = write_registry_file(home.path(), &future);
+
+    let err = registry::load_codebase_at(home.path(), &proj(), &cb()).unwrap_err();
+    assert!(matches!(err, RegistryError::UnsupportedSchema { .. }), "got: {err}");
+    let msg = err.to_string();
+    assert!(msg.contains("upgrade orchestra"), "got: {msg}");
+    assert!(msg.contains("copnow_api.yaml"), "got: {msg}");
+    assert_eq!(fs::read_to_string(&path).expect("read"), future, "file must be left as-is");
+}
+
+// ---------------------------------------------------------------------------
+// Name validation
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_name_rejects_each_unsafe_shape() {
+    let long = "a".repeat(registry::MAX_NAME_LEN + 1);
+    for (name, reason) in [
+        ("", "empty"),
+        (long.as_str(), "longer than 100"),
+        ("a/b", "path separator"),
+        ("a\\b", "path separator"),
+        ("../../etc", "path separator"),
+        ("a\0b", "NUL"),
+        (".hidden", "start with a dot"),
+        (".", "start with a dot"),
+        ("..", "start with a dot"),
+        ("project", "reserved"),
+    ] {
+        match registry::validate_name(name) {
+            Err(RegistryError::InvalidName { reason: got, .. }) => {
+                assert!(got.contains(reason), "{name:?}: {got}")
+            }
+            other => panic!("{name:?}: expected InvalidName, got {other:?}"),
+        }
+    }
+
+    let max = "a".repeat(registry::MAX_NAME_LEN);
+    for name in ["copnow_api", "my-app.v2", "projects", max.as_str()] {
+        registry::validate_name(name).unwrap_or_else(|e| panic!("{name:?}: {e}"));
+    }
+}
+
+#[test]
+fn hostile_names_never_escape_the_projects_root() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let workspace = assert_fs::TempDir::new().expect("tempdir");
+    let cb_dir = named_dir(&workspace, "copnow_api");
+    registry::init_at(cb_dir.path().to_path_buf(), proj(), None, home.path()).expect("init");
+
+    for name in ["../../etc", "../escaped", "nested/child", "..", "project"] {
+        let err = registry::add_codebase_at(
+            home.path(),
+            &proj(),
+            CodebaseName::from(name),
+            ProjectType::Backend,
+        )
+        .unwrap_err();
+        assert!(matches!(err, RegistryError::InvalidName { .. }), "{name}: {err}");
+
+        let err = registry::init_at(
+            cb_dir.path().to_path_buf(),
+            ProjectName::from(name),
+            None,
+            home.path(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, RegistryError::InvalidName { .. }), "{name}: {err}");
+    }
+
+    let dot_dir = named_dir(&workspace, ".config");
+    let err = registry::init_at(dot_dir.path().to_path_buf(), proj(), None, home.path()).unwrap_err();
+    assert!(matches!(err, RegistryError::InvalidName { .. }), "got: {err}");
+
+    // Only the one valid entry exists, and nothing was written beside `projects/`.
+    assert_eq!(registry::list_codebases_at(home.path()).expect("list").len(), 1);
+    let orchestra: Vec<_> = fs::read_dir(home.path().join(".orchestra"))
+        .expect("read .orchestra")
+        .map(|e| e.expect("entry").file_name())
+        .collect();
+    assert_eq!(orchestra, vec!["projects"]);
+    assert!(!home.path().join("etc.yaml").exists());
+    assert!(!home.path().join(".orchestra/escaped.yaml").exists());
+}
+
+#[test]
+fn existing_entries_with_invalid_names_still_load() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let dir = home.path().join(".orchestra/projects/copnow");
+    fs::create_dir_all(&dir).expect("mkdir");
+    fs::write(dir.join(".legacy.yaml"), V1_CODEBASE.replace("copnow_api", ".legacy"))
+        .expect("write");
+
+    let list = registry::list_codebases_at(home.path()).expect("list");
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].1.name, CodebaseName::from(".legacy"));
+}
+
+// ---------------------------------------------------------------------------
+// Forward compatibility
+// ---------------------------------------------------------------------------
+
+/// A current-schema codebase with fields from an imagined newer version at
+/// the codebase, project and task level.
+fn future_fields_codebase() -> String {
+    format!(
+        "\
+schema_version: {CURRENT_SCHEMA_VERSION}
+name: copnow_api
+path: /code/copnow_api
+projects:
+- name: api
+  project_type: backend
+  tasks:
+  - id: T-1
+    title: Ship it
+    status: pending
+    created_at: 2025-01-01T00:00:00Z
+    updated_at: 2025-01-01T00:00:00Z
+    future_field: 42
+  agents: []
+  future_field: 42
+created_at: 2025-01-01T00:00:00Z
+updated_at: 2025-01-01T00:00:00Z
+future_block:
+  nested:
+  - 1
+  - two
+future_field: 42
+"
+    )
+}
+
+#[test]
+fn unknown_fields_survive_load_and_save() {
+    let home = assert_fs::TempDir::new().expect("tempdir");
+    let original = future_fields_codebase();
+    let path = write_registry_file(home.path(), &original);
+
+    let codebase = registry::load_codebase_at(home.path(), &proj(), &cb()).expect("load");
+    let future = serde_yaml::Value::from(42);
+    assert_eq!(codebase.extra["future_field"], future);
+    assert_eq!(codebase.projects[0].extra["future_field"], future);
+    assert_eq!(codebase.projects[0].tasks[0].extra["future_field"], future);
+
+    registry::save_codebase_at(home.path(), &proj(), &codebase).expect("save");
+    let saved = fs::read_to_string(&path).expect("read");
+    assert_eq!(saved, original, "unknown fields must be kept");
+
+    let reloaded = registry::load_codebase_at(home.path(), &proj(), &cb()).expect("reload");
+    assert_eq!(reloaded, codebase);
+}
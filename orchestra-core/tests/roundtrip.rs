@@ -5,9 +5,10 @@
 use chrono::Utc;
 use orchestra_core::types::{
     AgentConfig, Codebase, CodebaseName, Project, ProjectName, ProjectType, Registry, Task,
-    TaskId, TaskStatus,
+    TaskId, TaskStatus, CURRENT_SCHEMA_VERSION,
 };
 use rstest::rstest;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 // ---------------------------------------------------------------------------
@@ -29,6 +30,8 @@ fn full_registry() -> Registry {
             projects: vec![Project {
                 name: ProjectName::from("api"),
                 project_type: ProjectType::Backend,
+                framework: None,
+                detection: None,
                 tasks: vec![Task {
                     id: TaskId::from("t-001"),
                     title: "Implement auth".to_string(),
@@ -36,21 +39,38 @@ fn full_registry() -> Registry {
                     description: Some("JWT-based auth flow".to_string()),
                     subtasks: vec![],
                     notes: vec![],
+                    assigned_agent: None,
                     created_at: now,
                     updated_at: now,
+                    extra: BTreeMap::new(),
                 }],
                 agents: vec![AgentConfig {
                     agent_id: "claude".to_string(),
                     entry_point: PathBuf::from("AGENT/CLAUDE.md"),
                     skills: Some(vec!["registry-foundation".to_string()]),
                 }],
+                commands: BTreeMap::from([(
+                    "test".to_string(),
+                    "cargo test --workspace".to_string(),
+                )]),
+                extra: BTreeMap::new(),
             }],
             conventions: vec![],
             skills: vec![],
             notes: vec![],
             tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: BTreeMap::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             created_at: now,
             updated_at: now,
+            extra: BTreeMap::new(),
         }],
         created_at: now,
         updated_at: now,
@@ -67,6 +87,8 @@ fn unicode_registry() -> Registry {
             projects: vec![Project {
                 name: ProjectName::from("пользователь-api"),
                 project_type: ProjectType::Backend,
+                framework: None,
+                detection: None,
                 tasks: vec![Task {
                     id: TaskId::from("t-🚀"),
                     title: "Task with émojis & spéçïal chars: <>&\"'".to_string(),
@@ -74,17 +96,31 @@ fn unicode_registry() -> Registry {
                     description: Some("日本語・한국어・العربية".to_string()),
                     subtasks: vec![],
                     notes: vec![],
+                    assigned_agent: None,
                     created_at: now,
                     updated_at: now,
+                    extra: BTreeMap::new(),
                 }],
                 agents: vec![],
+                commands: BTreeMap::new(),
+                extra: BTreeMap::new(),
             }],
             conventions: vec![],
             skills: vec![],
             notes: vec![],
             tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: BTreeMap::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             created_at: now,
             updated_at: now,
+            extra: BTreeMap::new(),
         }],
         created_at: now,
         updated_at: now,
@@ -103,8 +139,18 @@ fn empty_vecs_registry() -> Registry {
             skills: vec![],
             notes: vec![],
             tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: BTreeMap::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             created_at: now,
             updated_at: now,
+            extra: BTreeMap::new(),
         }],
         created_at: now,
         updated_at: now,
@@ -152,14 +198,29 @@ fn registry_roundtrip(#[case] label: &str, #[case] registry: Registry) {
 #[case(ProjectType::Frontend)]
 #[case(ProjectType::Mobile)]
 #[case(ProjectType::Ml)]
+#[case(ProjectType::Infra)]
 fn project_type_roundtrip(#[case] pt: ProjectType) {
     let project = Project {
         name: ProjectName::from("test"),
         project_type: pt,
+        framework: None,
+        detection: None,
         tasks: vec![],
         agents: vec![],
+        commands: BTreeMap::new(),
+        extra: BTreeMap::new(),
     };
     let yaml = serde_yaml::to_string(&project).expect("serialize");
     let back: Project = serde_yaml::from_str(&yaml).expect("deserialize");
     assert_eq!(project.project_type, back.project_type);
 }
+
+#[test]
+fn project_without_commands_loads_and_omits_them() {
+    let yaml = "name: api\nproject_type: backend\n";
+    let project: Project = serde_yaml::from_str(yaml).expect("deserialize");
+    assert!(project.commands.is_empty());
+
+    let written = serde_yaml::to_string(&project).expect("serialize");
+    assert!(!written.contains("commands"), "empty commands written:\n{written}");
+}
@@ -0,0 +1,480 @@
+//! Transport-agnostic handling of daemon protocol requests.
+//!
+//! [`dispatch`] answers one [`DaemonRequest`] against a [`DispatchContext`].
+//! The daemon socket server dispatches with its live state, so `sync` goes
+//! through the sync queue. `orchestra serve --stdio` dispatches
+//! [`DispatchContext::in_process`], running each command synchronously
+//! against the caller's home with no daemon needed.
+//!
+//! | `cmd`    | Request fields | Response data                                   |
+//! |----------|----------------|-------------------------------------------------|
+//! | `status` | `detailed`?    | `running` plus one entry per codebase           |
+//! | `sync`   | `codebase`?    | sync summary (`written`, `unchanged`, ...)      |
+//! | `diff`   | `codebase`     | `codebase` and `diffs: [{path, diff}]`          |
+//! | `detect` | `path`         | detected language, framework and project type   |
+//! | `stop`   | —              | `stopping`, `drained` and `rejected` counts     |
+//!
+//! A `status` request with `detailed: true` is answered with the report that
+//! `orchestra status --json` prints instead.
+//!
+//! A daemon answers `stop` once its sync queue is drained, and the
+//! connection then ends. A `sync` it refuses while draining is answered
+//! with code [`SHUTTING_DOWN`].
+
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde_json::{json, Value};
+
+use orchestra_core::registry;
+use orchestra_detector::{detect_stack, DetectionReport};
+use orchestra_sync::{
+    diff_codebase, hash_store, pipeline, staleness, status, StalenessSignal, SyncSource,
+};
+
+use crate::error::{io_err, DaemonError};
+use crate::paths::projects_root;
+use crate::protocol::{
+    check_protocol_version, DaemonRequest, DaemonResponse, SHUTDOWN_TIMEOUT, SHUTTING_DOWN,
+};
+use crate::runtime::{build_status_payload, build_status_report, enqueue_sync, DaemonState};
+use crate::watch::{build_sync_summary, SyncTarget};
+
+/// What a request is answered against. See the [module docs](self).
+#[derive(Clone)]
+pub struct DispatchContext {
+    home: PathBuf,
+    pub(crate) daemon: Option<DaemonState>,
+}
+
+impl DispatchContext {
+    /// Answer requests in the calling process against `home`.
+    pub fn in_process(home: impl Into<PathBuf>) -> Self {
+        Self {
+            home: home.into(),
+            daemon: None,
+        }
+    }
+
+    /// Answer requests from a running daemon's state.
+    pub(crate) fn daemon(home: PathBuf, state: DaemonState) -> Self {
+        Self {
+            home,
+            daemon: Some(state),
+        }
+    }
+}
+
+/// Answer one protocol request. Failures become error responses, and a
+/// client on a newer protocol is refused before anything runs.
+pub async fn dispatch(request: &DaemonRequest, ctx: &DispatchContext) -> DaemonResponse {
+    if let Some(refusal) = check_protocol_version(request) {
+        return refusal;
+    }
+    let result = match request.cmd.as_str() {
+        "status" if request.detailed => status_report(ctx).await,
+        "status" => status(ctx).await,
+        "sync" => sync(ctx, request.codebase.clone()).await,
+        "diff" => match &request.codebase {
+            Some(codebase) => diff(&ctx.home, codebase.clone()).await,
+            None => Err(DaemonError::Protocol(
+                "diff requires a codebase".to_string(),
+            )),
+        },
+        "detect" => match &request.path {
+            Some(path) => detect(PathBuf::from(path)).await,
+            None => Err(DaemonError::Protocol("detect requires a path".to_string())),
+        },
+        "stop" => match &ctx.daemon {
+            Some(state) => stop(state).await,
+            None => Ok(json!({ "stopping": true })),
+        },
+        other => Err(DaemonError::Protocol(format!("unknown command '{other}'"))),
+    };
+
+    match result {
+        Ok(data) => DaemonResponse::ok(data),
+        Err(DaemonError::Protocol(message)) => DaemonResponse::error(message),
+        Err(err @ DaemonError::ShuttingDown) => {
+            DaemonResponse::error_with_code(SHUTTING_DOWN, err.to_string())
+        }
+        Err(err) => DaemonResponse::error(err.to_string()),
+    }
+}
+
+/// Signal shutdown, then wait for the sync processor to drain its queue so
+/// the caller learns how many sync requests were finished or refused. The
+/// counts are left out if the drain outlasts [`SHUTDOWN_TIMEOUT`].
+async fn stop(state: &DaemonState) -> Result<Value, DaemonError> {
+    let _ = state.shutdown_tx.send(());
+    let mut drain = state.drain.subscribe();
+    let report = match tokio::time::timeout(SHUTDOWN_TIMEOUT, drain.wait_for(Option::is_some)).await
+    {
+        Ok(Ok(report)) => *report,
+        _ => None,
+    };
+    let mut data = json!({ "stopping": true });
+    if let Some(report) = report {
+        data["drained"] = report.drained.into();
+        data["rejected"] = report.rejected.into();
+    }
+    Ok(data)
+}
+
+/// Parse one request line, or build the error response for a malformed one.
+pub(crate) fn decode_request(line: &str) -> Result<DaemonRequest, DaemonResponse> {
+    serde_json::from_str(line)
+        .map_err(|err| DaemonResponse::error(format!("invalid request JSON: {err}")))
+}
+
+/// Serve newline-delimited requests from `input`, writing one response line
+/// per request to `output`, until EOF or a `stop` request.
+///
+/// Malformed lines get an error response; the loop keeps going.
+pub fn serve_lines(
+    ctx: &DispatchContext,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<(), DaemonError> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| io_err("tokio-runtime", e))?;
+
+    for line in input.lines() {
+        let line = line.map_err(|e| io_err("stdin", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (response, stop) = match decode_request(&line) {
+            Ok(request) => {
+                let response = runtime.block_on(dispatch(&request, ctx));
+                let stop = request.cmd == "stop" && response.ok;
+                (response, stop)
+            }
+            Err(response) => (response, false),
+        };
+
+        let payload = serde_json::to_string(&response)?;
+        writeln!(output, "{payload}").map_err(|e| io_err("stdout", e))?;
+        output.flush().map_err(|e| io_err("stdout", e))?;
+        if stop {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Serve requests on stdin/stdout in-process against `home`.
+pub fn serve_stdio(home: &Path) -> Result<(), DaemonError> {
+    let ctx = DispatchContext::in_process(home);
+    serve_lines(&ctx, std::io::stdin().lock(), std::io::stdout().lock())
+}
+
+async fn status(ctx: &DispatchContext) -> Result<Value, DaemonError> {
+    match &ctx.daemon {
+        Some(state) => Ok(build_status_payload(
+            &ctx.home,
+            state.cache.clone(),
+            state.sync_states.clone(),
+            &state.metrics,
+            &state.last_staleness_scan,
+            &state.last_scheduled_resync,
+            &state.unwatched_dirs,
+            state.started_at_unix,
+            state.previous_started_at_unix,
+        )
+        .await),
+        None => {
+            let home = ctx.home.clone();
+            run_blocking("status", move || in_process_status(&home)).await?
+        }
+    }
+}
+
+async fn status_report(ctx: &DispatchContext) -> Result<Value, DaemonError> {
+    match &ctx.daemon {
+        Some(state) => {
+            build_status_report(&ctx.home, state.cache.clone(), &state.status_report).await
+        }
+        None => {
+            let home = ctx.home.clone();
+            run_blocking("status", move || -> Result<Value, DaemonError> {
+                let codebases = registry::list_codebases_lossy_at(&home)?.codebases;
+                let report = status::build_report(&home, &codebases, false, false)?;
+                Ok(serde_json::to_value(report)?)
+            })
+            .await?
+        }
+    }
+}
+
+async fn sync(ctx: &DispatchContext, codebase: Option<String>) -> Result<Value, DaemonError> {
+    let target = match codebase {
+        Some(codebase) => SyncTarget::Codebase(codebase),
+        None => SyncTarget::All,
+    };
+
+    let summary = match &ctx.daemon {
+        Some(state) => enqueue_sync(&state.sync_tx, target, "socket").await?,
+        None => {
+            let started = Instant::now();
+            let home = ctx.home.clone();
+            let scope = target.scope();
+            // Journaled and hook-gated like a daemon sync: the request comes
+            // from another program, not from someone at the terminal.
+            let results = run_blocking("sync", move || {
+                pipeline::run_from(&home, scope, false, None, SyncSource::Daemon)
+            })
+            .await??;
+            build_sync_summary(target, "in_process", results, started.elapsed())
+        }
+    };
+    Ok(json!(summary))
+}
+
+async fn diff(home: &Path, codebase: String) -> Result<Value, DaemonError> {
+    let home = home.to_path_buf();
+    let result = run_blocking("diff", move || diff_codebase(&codebase, &home)).await??;
+    let diffs: Vec<Value> = result
+        .diffs
+        .into_iter()
+        .map(|diff| {
+            json!({
+                "path": diff.path.display().to_string(),
+                "diff": diff.unified_diff,
+                "added": diff.added,
+                "removed": diff.removed,
+            })
+        })
+        .collect();
+    Ok(json!({ "codebase": result.codebase_name, "diffs": diffs }))
+}
+
+async fn detect(path: PathBuf) -> Result<Value, DaemonError> {
+    let target = path.clone();
+    let stack = run_blocking("detect", move || detect_stack(&target))
+        .await?
+        .map_err(|err| DaemonError::Protocol(format!("detect failed: {err}")))?;
+    Ok(serde_json::to_value(DetectionReport::new(&path, stack))?)
+}
+
+/// Status payload for in-process dispatch: staleness per registered codebase.
+fn in_process_status(home: &Path) -> Result<Value, DaemonError> {
+    let mut codebases = Vec::new();
+    for (project, codebase) in registry::list_codebases_lossy_at(home)?.codebases {
+        let report = staleness::check_report(home, &project, &codebase, false)?;
+        let last_sync_at_unix = match report.signal {
+            StalenessSignal::NeverSynced => 0,
+            _ => hash_store::load_at(home, &codebase.name.0)?
+                .synced_at
+                .timestamp()
+                .max(0) as u64,
+        };
+        let (status, files, reason) = match report.signal {
+            StalenessSignal::NeverSynced => ("never_synced", Vec::new(), None),
+            StalenessSignal::Current => ("current", Vec::new(), None),
+            StalenessSignal::Stale { reason } => ("stale", Vec::new(), Some(reason)),
+            StalenessSignal::Modified { files } => ("modified", files, None),
+            StalenessSignal::Orphan { files } => ("orphan", files, None),
+        };
+        codebases.push(json!({
+            "name": codebase.name.0,
+            "project": project.0,
+            "last_sync_at_unix": last_sync_at_unix,
+            "status": status,
+            "reason": reason,
+            "files": files,
+            "ignored": report.ignored,
+        }));
+    }
+    codebases.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+
+    Ok(json!({
+        "running": false,
+        "codebases": codebases,
+        "projects_root": projects_root(home).display().to_string(),
+    }))
+}
+
+async fn run_blocking<T, F>(task: &str, f: F) -> Result<T, DaemonError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|err| DaemonError::Protocol(format!("{task} join error: {err}")))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Cursor;
+
+    use crate::protocol::{PROTOCOL_TOO_NEW, PROTOCOL_VERSION};
+    use orchestra_core::types::{ProjectName, ProjectType};
+    use tempfile::TempDir;
+
+    fn serve(home: &Path, requests: &[&str]) -> Vec<Value> {
+        let ctx = DispatchContext::in_process(home);
+        let input = Cursor::new(requests.join("\n"));
+        let mut output = Vec::new();
+        serve_lines(&ctx, input, &mut output).expect("serve");
+        String::from_utf8(output)
+            .expect("utf8")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("response json"))
+            .collect()
+    }
+
+    fn init_codebase(home: &Path, workspace: &Path) -> PathBuf {
+        let dir = workspace.join("copnow_api");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"copnow_api\"\n").unwrap();
+        registry::init_at(
+            dir.clone(),
+            ProjectName::from("copnow"),
+            Some(ProjectType::Backend),
+            home,
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn stdio_requests_are_answered_in_order() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let dir = init_codebase(home.path(), workspace.path());
+        let detect = json!({ "cmd": "detect", "path": dir }).to_string();
+
+        let responses = serve(
+            home.path(),
+            &[
+                r#"{"cmd":"status"}"#,
+                r#"{"cmd":"sync","codebase":"copnow_api"}"#,
+                r#"{"cmd":"status"}"#,
+                r#"{"cmd":"diff","codebase":"copnow_api"}"#,
+                &detect,
+            ],
+        );
+        assert_eq!(responses.len(), 5);
+        assert!(responses.iter().all(|r| r["ok"] == true), "{responses:?}");
+
+        assert_eq!(responses[0]["data"]["running"], false);
+        assert_eq!(
+            responses[0]["data"]["codebases"][0]["status"],
+            "never_synced"
+        );
+        assert_eq!(responses[1]["data"]["source"], "in_process");
+        assert!(responses[1]["data"]["written"].as_u64().unwrap() > 0);
+        assert_eq!(responses[2]["data"]["codebases"][0]["status"], "current");
+        assert_eq!(responses[3]["data"]["codebase"], "copnow_api");
+        assert_eq!(responses[3]["data"]["diffs"], json!([]));
+        assert_eq!(responses[4]["data"]["primary_language"], "Rust");
+        assert_eq!(responses[4]["data"]["project_type"], "backend");
+        assert_eq!(responses[4]["data"]["indicators"], json!(["Cargo.toml"]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn in_process_sync_skips_hooks_unless_allowed_in_daemon() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let dir = init_codebase(home.path(), workspace.path());
+        let project = ProjectName::from("copnow");
+        let mut codebase =
+            registry::load_codebase_at(home.path(), &project, &"copnow_api".into()).unwrap();
+        codebase.hooks = Some(orchestra_core::types::SyncHooks {
+            pre_sync: Some("echo ran >> pre.txt".into()),
+            ..Default::default()
+        });
+        registry::save_codebase_at(home.path(), &project, &codebase).unwrap();
+
+        let responses = serve(home.path(), &[r#"{"cmd":"sync","codebase":"copnow_api"}"#]);
+        assert_eq!(responses[0]["ok"], true, "{responses:?}");
+        assert!(!dir.join("pre.txt").exists());
+    }
+
+    #[test]
+    fn detailed_status_is_the_status_json_report() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        init_codebase(home.path(), workspace.path());
+
+        let responses = serve(home.path(), &[r#"{"cmd":"status","detailed":true}"#]);
+        assert_eq!(responses[0]["ok"], true, "{responses:?}");
+
+        let codebases = registry::list_codebases_at(home.path()).unwrap();
+        let local = status::build_report(home.path(), &codebases, false, false).unwrap();
+        assert_eq!(responses[0]["data"], serde_json::to_value(&local).unwrap());
+        assert_eq!(responses[0]["data"]["summary"]["codebases"], 1);
+    }
+
+    #[test]
+    fn invalid_lines_get_error_responses_without_ending_the_loop() {
+        let home = TempDir::new().unwrap();
+        let responses = serve(
+            home.path(),
+            &[
+                "{not json",
+                "",
+                r#"{"cmd":"frobnicate"}"#,
+                r#"{"cmd":"diff"}"#,
+                r#"{"cmd":"stop"}"#,
+                r#"{"cmd":"status"}"#,
+            ],
+        );
+
+        assert_eq!(responses.len(), 4, "stop ends the loop: {responses:?}");
+        assert_eq!(responses[0]["ok"], false);
+        assert!(responses[0]["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid request JSON"));
+        assert_eq!(responses[1]["error"], "unknown command 'frobnicate'");
+        assert_eq!(responses[2]["error"], "diff requires a codebase");
+        assert_eq!(responses[3]["data"]["stopping"], true);
+    }
+
+    #[test]
+    fn newer_protocol_versions_are_refused() {
+        let home = TempDir::new().unwrap();
+        let future = json!({ "cmd": "stop", "protocol_version": PROTOCOL_VERSION + 1 }).to_string();
+        let current = json!({ "cmd": "status", "protocol_version": PROTOCOL_VERSION }).to_string();
+        let responses = serve(home.path(), &[&future, &current, r#"{"cmd":"status"}"#]);
+
+        assert_eq!(
+            responses.len(),
+            3,
+            "the refused stop must not run: {responses:?}"
+        );
+        assert_eq!(responses[0]["ok"], false);
+        assert_eq!(responses[0]["code"], PROTOCOL_TOO_NEW);
+        assert!(responses[0]["error"]
+            .as_str()
+            .unwrap()
+            .contains("orchestra daemon restart"));
+        assert_eq!(responses[1]["ok"], true, "{responses:?}");
+        assert_eq!(
+            responses[2]["ok"], true,
+            "requests without a version are served"
+        );
+    }
+
+    #[test]
+    fn missing_codebase_is_an_error_response() {
+        let home = TempDir::new().unwrap();
+        let responses = serve(home.path(), &[r#"{"cmd":"sync","codebase":"nope"}"#]);
+        assert_eq!(responses[0]["ok"], false);
+        assert!(responses[0]["error"].as_str().unwrap().contains("nope"));
+    }
+}
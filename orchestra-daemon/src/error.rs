@@ -1,8 +1,9 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use thiserror::Error;
 
-/// Error surface for daemon runtime, protocol, and launchd management.
+/// Error surface for daemon runtime, protocol, and launchd/systemd management.
 #[derive(Debug, Error)]
 pub enum DaemonError {
     #[error("I/O error at {path}: {source}")]
@@ -27,14 +28,23 @@ pub enum DaemonError {
     #[error("channel closed: {0}")]
     ChannelClosed(&'static str),
 
+    #[error("daemon is shutting down; the sync was not run")]
+    ShuttingDown,
+
     #[error("daemon protocol error: {0}")]
     Protocol(String),
 
     #[error("daemon is not running (socket missing: {socket})")]
     DaemonNotRunning { socket: PathBuf },
 
+    #[error("daemon did not shut down within {timeout:?} (socket still accepting: {socket})")]
+    ShutdownTimeout { socket: PathBuf, timeout: Duration },
+
     #[error("launchd error: {0}")]
     Launchd(String),
+
+    #[error("systemd error: {0}")]
+    Systemd(String),
 }
 
 pub(crate) fn io_err(path: impl Into<PathBuf>, source: std::io::Error) -> DaemonError {
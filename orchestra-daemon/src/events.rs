@@ -0,0 +1,255 @@
+//! Structured daemon event log at `~/.orchestra/logs/events.jsonl`.
+//!
+//! The runtime appends one JSON object per significant event (startup, the
+//! start and outcome of each sync, processed watcher events and post-sync
+//! staleness scans), so "when did this codebase last fail to sync, and why"
+//! is a filter rather than a grep through free-text tracing output.
+//!
+//! Emitting never blocks: events go through a bounded channel to a writer
+//! thread, and are dropped with a warning if it falls behind. Write failures
+//! are logged as warnings as well. The writer rotates the file with the same
+//! size cap as the other daemon logs, and [`read_at`] reads the rotated
+//! copies too, so a rotation never hides recent events.
+
+use std::fs;
+use std::io::{ErrorKind, Write as _};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::error::{io_err, DaemonError};
+use crate::log_rotation::{numbered_path, rotate_if_needed, MAX_LOG_BYTES, MAX_ROTATED_FILES};
+use crate::paths::events_log_path;
+
+/// Events buffered for the writer before new ones are dropped.
+const EVENT_BUFFER: usize = 1024;
+
+/// What an event log line records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    DaemonStarted,
+    SyncStarted,
+    /// `detail` is the sync's summary.
+    SyncCompleted,
+    SyncFailed,
+    WatcherEventProcessed,
+    /// `detail` is the post-sync staleness scan summary.
+    StalenessScan,
+    /// A socket request passed the rate limit; `detail` holds the command
+    /// and the client's `uid` and `pid` when the platform reports them.
+    SocketRequest,
+}
+
+/// One line of the event log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaemonEvent {
+    pub at_unix: u64,
+    pub event: EventKind,
+    /// Codebases the event concerns; empty for daemon-wide events.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub codebases: Vec<String>,
+    /// Event-specific fields, such as the sync target or the error.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub detail: Value,
+}
+
+impl DaemonEvent {
+    /// An event that happened now.
+    pub fn new(event: EventKind, codebases: Vec<String>, detail: Value) -> Self {
+        Self {
+            at_unix: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            event,
+            codebases,
+            detail,
+        }
+    }
+}
+
+/// Handle the runtime emits events through. Clones share one writer; the
+/// default handle discards every event.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EventLog {
+    tx: Option<mpsc::Sender<DaemonEvent>>,
+}
+
+impl EventLog {
+    /// Start the writer for the event log under `home`. It finishes once
+    /// every handle has been dropped and the buffered events are written.
+    pub(crate) fn spawn(home: &Path) -> (Self, JoinHandle<()>) {
+        let path = events_log_path(home);
+        let (tx, mut rx) = mpsc::channel::<DaemonEvent>(EVENT_BUFFER);
+        let writer = tokio::task::spawn_blocking(move || {
+            while let Some(event) = rx.blocking_recv() {
+                if let Err(err) = append(&path, &event, MAX_LOG_BYTES) {
+                    tracing::warn!(error = %err, "failed to write daemon event log");
+                }
+            }
+        });
+        (Self { tx: Some(tx) }, writer)
+    }
+
+    /// Queue an event for the writer without waiting.
+    pub(crate) fn emit(&self, event: EventKind, codebases: Vec<String>, detail: Value) {
+        let Some(tx) = &self.tx else {
+            return;
+        };
+        match tx.try_send(DaemonEvent::new(event, codebases, detail)) {
+            Ok(()) | Err(mpsc::error::TrySendError::Closed(_)) => {}
+            Err(mpsc::error::TrySendError::Full(event)) => {
+                tracing::warn!(event = ?event.event, "daemon event log is behind; dropping event");
+            }
+        }
+    }
+}
+
+/// Append `event` to the log at `path`, rotating it first once it reaches
+/// `max_bytes`.
+fn append(path: &Path, event: &DaemonEvent, max_bytes: u64) -> Result<(), DaemonError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| io_err(parent, e))?;
+    }
+    rotate_if_needed(path, max_bytes, MAX_ROTATED_FILES).map_err(|e| io_err(path, e))?;
+
+    let mut line = serde_json::to_string(event)?;
+    line.push('\n');
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| io_err(path, e))?;
+    file.write_all(line.as_bytes()).map_err(|e| io_err(path, e))
+}
+
+/// Load logged events, oldest first, including the rotated copies.
+///
+/// Filters by codebase and by `since_unix` (inclusive) when given. Lines
+/// that fail to parse are skipped. A missing log yields an empty list.
+pub fn read_at(
+    home: &Path,
+    codebase: Option<&str>,
+    since_unix: Option<u64>,
+) -> Result<Vec<DaemonEvent>, DaemonError> {
+    let path = events_log_path(home);
+    let mut files: Vec<PathBuf> = (1..=MAX_ROTATED_FILES)
+        .rev()
+        .map(|n| numbered_path(&path, n))
+        .collect();
+    files.push(path);
+
+    let mut events = Vec::new();
+    for file in files {
+        let contents = match fs::read_to_string(&file) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => return Err(io_err(&file, err)),
+        };
+        events.extend(
+            contents
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .filter_map(|line| match serde_json::from_str::<DaemonEvent>(line) {
+                    Ok(event) => Some(event),
+                    Err(err) => {
+                        tracing::debug!("skipping corrupt daemon event line: {err}");
+                        None
+                    }
+                })
+                .filter(|event| {
+                    codebase.is_none_or(|name| event.codebases.iter().any(|c| c == name))
+                })
+                .filter(|event| since_unix.is_none_or(|cutoff| event.at_unix >= cutoff)),
+        );
+    }
+    Ok(events)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn event(kind: EventKind, codebase: &str, at_unix: u64) -> DaemonEvent {
+        DaemonEvent {
+            at_unix,
+            event: kind,
+            codebases: vec![codebase.to_string()],
+            detail: json!({ "target": codebase }),
+        }
+    }
+
+    #[test]
+    fn read_filters_by_codebase_and_since() {
+        let home = TempDir::new().unwrap();
+        let path = events_log_path(home.path());
+        append(
+            &path,
+            &event(EventKind::SyncStarted, "api", 100),
+            MAX_LOG_BYTES,
+        )
+        .unwrap();
+        append(
+            &path,
+            &event(EventKind::SyncStarted, "web", 200),
+            MAX_LOG_BYTES,
+        )
+        .unwrap();
+        append(
+            &path,
+            &event(EventKind::SyncFailed, "api", 300),
+            MAX_LOG_BYTES,
+        )
+        .unwrap();
+        let started = DaemonEvent::new(EventKind::DaemonStarted, Vec::new(), Value::Null);
+        append(&path, &started, MAX_LOG_BYTES).unwrap();
+
+        let api = read_at(home.path(), Some("api"), None).unwrap();
+        assert_eq!(
+            api.iter().map(|e| (e.event, e.at_unix)).collect::<Vec<_>>(),
+            vec![(EventKind::SyncStarted, 100), (EventKind::SyncFailed, 300)]
+        );
+        let recent = read_at(home.path(), Some("api"), Some(300)).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(read_at(home.path(), None, None).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn rotation_keeps_every_event_readable_in_order() {
+        let home = TempDir::new().unwrap();
+        let path = events_log_path(home.path());
+        let line_bytes = serde_json::to_string(&event(EventKind::SyncStarted, "api", 0))
+            .unwrap()
+            .len() as u64;
+        // Rotate after every second line.
+        for at_unix in 0..8 {
+            let logged = event(EventKind::SyncStarted, "api", at_unix);
+            append(&path, &logged, 2 * line_bytes).unwrap();
+        }
+        assert!(numbered_path(&path, 3).exists(), "the log never rotated");
+
+        let times: Vec<u64> = read_at(home.path(), None, None)
+            .unwrap()
+            .iter()
+            .map(|e| e.at_unix)
+            .collect();
+        assert_eq!(times, (0..8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn read_missing_log_is_empty() {
+        let home = TempDir::new().unwrap();
+        assert!(read_at(home.path(), None, None).unwrap().is_empty());
+    }
+}
@@ -1,15 +1,98 @@
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use orchestra_core::paths::{dirs_at, ORCHESTRA_HOME_ENV};
 
 use crate::error::{io_err, DaemonError};
 use crate::paths::{launch_agents_dir, launchd_plist_path, socket_path, DAEMON_LABEL};
+use crate::service::current_binary;
+
+/// How long `install` waits for launchd to report the service running.
+const INSTALL_VERIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const INSTALL_VERIFY_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Whether launchd has the service loaded, and if so whether it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchdServiceState {
+    Running,
+    /// Loaded but not running: waiting to be (re)spawned, e.g. after a
+    /// crash while `KeepAlive` throttles restarts.
+    Waiting,
+    NotLoaded,
+}
+
+/// launchd's view of the Orchestra service, from `launchctl print`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LaunchdState {
+    pub state: LaunchdServiceState,
+    pub pid: Option<u32>,
+    /// Exit status of the last run; `None` if it never exited.
+    pub last_exit_status: Option<i32>,
+    /// Whether the agent plist is installed in `~/Library/LaunchAgents`.
+    pub installed: bool,
+}
+
+impl fmt::Display for LaunchdState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.state {
+            LaunchdServiceState::Running => write!(f, "running")?,
+            LaunchdServiceState::Waiting => write!(f, "waiting")?,
+            LaunchdServiceState::NotLoaded => write!(f, "not loaded")?,
+        }
+        if let Some(pid) = self.pid {
+            write!(f, ", pid {pid}")?;
+        }
+        if let Some(status) = self.last_exit_status {
+            write!(f, ", last exit status {status}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Output of one `launchctl` invocation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchctlOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs `launchctl` with the given arguments. Tests substitute canned output.
+pub type LaunchctlRunner = fn(&[String]) -> Result<LaunchctlOutput, DaemonError>;
 
 /// Generate a launchd plist for Orchestra daemon management.
-pub fn generate_plist(binary_path: &Path, log_dir: &Path) -> String {
+///
+/// `start_args` are appended to `orchestra daemon start` (e.g.
+/// `--no-startup-sync`). `orchestra_home` is exported as `ORCHESTRA_HOME` so
+/// the daemon finds a data directory that was chosen outside `~/.orchestra`.
+pub fn generate_plist(
+    binary_path: &Path,
+    start_args: &[&str],
+    log_dir: &Path,
+    orchestra_home: Option<&Path>,
+) -> String {
     let stdout = log_dir.join("daemon.log").display().to_string();
     let stderr = log_dir.join("daemon-err.log").display().to_string();
     let binary = binary_path.display().to_string();
+    let environment = match orchestra_home {
+        Some(root) => format!(
+            "  <key>EnvironmentVariables</key>\n  <dict>\n    <key>{ORCHESTRA_HOME_ENV}</key>\n    <string>{}</string>\n  </dict>\n",
+            root.display()
+        ),
+        None => String::new(),
+    };
+    let extra_args: String = start_args
+        .iter()
+        .map(|arg| format!("\n    <string>{arg}</string>"))
+        .collect();
 
     format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -22,7 +105,7 @@ pub fn generate_plist(binary_path: &Path, log_dir: &Path) -> String {
   <array>
     <string>{binary}</string>
     <string>daemon</string>
-    <string>start</string>
+    <string>start</string>{extra_args}
   </array>
   <key>RunAtLoad</key>
   <true/>
@@ -32,18 +115,21 @@ pub fn generate_plist(binary_path: &Path, log_dir: &Path) -> String {
   <string>{stdout}</string>
   <key>StandardErrorPath</key>
   <string>{stderr}</string>
-</dict>
+{environment}</dict>
 </plist>
 "#,
         label = DAEMON_LABEL,
         binary = binary,
         stdout = stdout,
-        stderr = stderr
+        stderr = stderr,
+        environment = environment,
+        extra_args = extra_args
     )
 }
 
-/// Install and bootstrap launchd service for the current user.
-pub fn install(home: &Path) -> Result<PathBuf, DaemonError> {
+/// Install and bootstrap launchd service for the current user, passing
+/// `start_args` through to `orchestra daemon start`.
+pub fn install(home: &Path, start_args: &[&str]) -> Result<PathBuf, DaemonError> {
     ensure_macos()?;
 
     let launch_agents = launch_agents_dir(home);
@@ -61,8 +147,11 @@ pub fn install(home: &Path) -> Result<PathBuf, DaemonError> {
     }
 
     let plist = launchd_plist_path(home);
-    let binary_path = Path::new("/usr/local/bin/orchestra");
-    fs::write(&plist, generate_plist(binary_path, &logs)).map_err(|e| io_err(&plist, e))?;
+    let binary_path = current_binary()?;
+    let dirs = dirs_at(home);
+    let orchestra_home = (!dirs.is_legacy(home)).then_some(dirs.data_root.as_path());
+    fs::write(&plist, generate_plist(&binary_path, start_args, &logs, orchestra_home))
+        .map_err(|e| io_err(&plist, e))?;
 
     let domain = launchctl_domain()?;
     let service = format!("{domain}/{DAEMON_LABEL}");
@@ -80,6 +169,7 @@ pub fn install(home: &Path) -> Result<PathBuf, DaemonError> {
         vec!["kickstart".to_string(), "-k".to_string(), service],
         false,
     )?;
+    wait_for_running(home, &domain, launchctl_command, INSTALL_VERIFY_TIMEOUT)?;
 
     Ok(plist)
 }
@@ -104,6 +194,103 @@ pub fn uninstall(home: &Path) -> Result<(), DaemonError> {
     Ok(())
 }
 
+/// Ask launchd whether the service is loaded and running.
+///
+/// The daemon socket only shows whether a daemon answers right now; launchd
+/// also knows when it is about to restart a crashed one, or has been booted
+/// out and will not.
+pub fn launchd_status(home: &Path) -> Result<LaunchdState, DaemonError> {
+    ensure_macos()?;
+    launchd_status_with(home, &launchctl_domain()?, launchctl_command)
+}
+
+fn launchd_status_with(
+    home: &Path,
+    domain: &str,
+    runner: LaunchctlRunner,
+) -> Result<LaunchdState, DaemonError> {
+    let installed = launchd_plist_path(home).exists();
+    let output = runner(&["print".to_string(), format!("{domain}/{DAEMON_LABEL}")])?;
+    if !output.success {
+        if output.stderr.contains("Could not find service") {
+            return Ok(LaunchdState {
+                state: LaunchdServiceState::NotLoaded,
+                pid: None,
+                last_exit_status: None,
+                installed,
+            });
+        }
+        return Err(DaemonError::Launchd(format!(
+            "launchctl print failed: {} {}",
+            output.stdout.trim(),
+            output.stderr.trim()
+        )));
+    }
+    Ok(parse_launchctl_print(&output.stdout, installed))
+}
+
+/// Read `state`, `pid` and `last exit code` from the service's own block of
+/// `launchctl print` output, ignoring nested blocks such as `environment`.
+fn parse_launchctl_print(output: &str, installed: bool) -> LaunchdState {
+    let mut parsed = LaunchdState {
+        state: LaunchdServiceState::Waiting,
+        pid: None,
+        last_exit_status: None,
+        installed,
+    };
+    let mut depth = 0usize;
+    for line in output.lines() {
+        let line = line.trim();
+        if line == "}" {
+            depth = depth.saturating_sub(1);
+            continue;
+        }
+        if depth == 1 {
+            if let Some((key, value)) = line.split_once(" = ") {
+                match key {
+                    "state" if value == "running" => parsed.state = LaunchdServiceState::Running,
+                    "pid" => parsed.pid = value.parse().ok(),
+                    // e.g. `78: EX_CONFIG`, or `(never exited)`.
+                    "last exit code" => {
+                        parsed.last_exit_status = value
+                            .split(':')
+                            .next()
+                            .and_then(|code| code.trim().parse().ok())
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if line.ends_with('{') {
+            depth += 1;
+        }
+    }
+    parsed
+}
+
+/// Poll launchd until it reports the service running, failing with its last
+/// reported state after `timeout`.
+fn wait_for_running(
+    home: &Path,
+    domain: &str,
+    runner: LaunchctlRunner,
+    timeout: Duration,
+) -> Result<LaunchdState, DaemonError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let state = launchd_status_with(home, domain, runner)?;
+        if state.state == LaunchdServiceState::Running {
+            return Ok(state);
+        }
+        if Instant::now() >= deadline {
+            return Err(DaemonError::Launchd(format!(
+                "launchd did not start {DAEMON_LABEL} ({state}); see `orchestra daemon logs --stderr-only`"
+            )));
+        }
+        sleep(INSTALL_VERIFY_POLL_INTERVAL);
+    }
+}
+
 #[cfg(target_os = "macos")]
 fn ensure_macos() -> Result<(), DaemonError> {
     Ok(())
@@ -134,6 +321,19 @@ fn run_launchctl(args: Vec<String>, ignore_failure: bool) -> Result<(), DaemonEr
     )))
 }
 
+/// The real [`LaunchctlRunner`].
+fn launchctl_command(args: &[String]) -> Result<LaunchctlOutput, DaemonError> {
+    let output = Command::new("launchctl")
+        .args(args)
+        .output()
+        .map_err(|e| io_err("launchctl", e))?;
+    Ok(LaunchctlOutput {
+        success: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
 fn launchctl_domain() -> Result<String, DaemonError> {
     let output = Command::new("id")
         .arg("-u")
@@ -160,11 +360,130 @@ mod tests {
     use super::*;
     use plist::Value;
 
+    const RUNNING: &str = "gui/501/dev.orchestra.daemon = {
+	active count = 1
+	path = /Users/tester/Library/LaunchAgents/dev.orchestra.daemon.plist
+	type = LaunchAgent
+	state = running
+
+	program = /usr/local/bin/orchestra
+	arguments = {
+		/usr/local/bin/orchestra
+		daemon
+		start
+	}
+
+	environment = {
+		state = not-a-service-field
+		pid = 1
+	}
+
+	pid = 4242
+	immediate reason = speculative
+	runs = 1
+	last exit code = (never exited)
+}
+";
+
+    const SPAWN_FAILED: &str = "gui/501/dev.orchestra.daemon = {
+	active count = 0
+	path = /Users/tester/Library/LaunchAgents/dev.orchestra.daemon.plist
+	type = LaunchAgent
+	state = spawn scheduled
+
+	program = /usr/local/bin/orchestra
+	runs = 14
+	last exit code = 78: EX_CONFIG
+	spawn type = daemon (3)
+}
+";
+
+    fn running(args: &[String]) -> Result<LaunchctlOutput, DaemonError> {
+        assert_eq!(args, ["print", "gui/501/dev.orchestra.daemon"]);
+        Ok(LaunchctlOutput {
+            success: true,
+            stdout: RUNNING.to_string(),
+            stderr: String::new(),
+        })
+    }
+
+    fn spawn_failed(_args: &[String]) -> Result<LaunchctlOutput, DaemonError> {
+        Ok(LaunchctlOutput {
+            success: true,
+            stdout: SPAWN_FAILED.to_string(),
+            stderr: String::new(),
+        })
+    }
+
+    fn not_loaded(_args: &[String]) -> Result<LaunchctlOutput, DaemonError> {
+        Ok(LaunchctlOutput {
+            success: false,
+            stdout: String::new(),
+            stderr:
+                "Bad request.\nCould not find service \"dev.orchestra.daemon\" in domain for port\n"
+                    .to_string(),
+        })
+    }
+
+    #[test]
+    fn launchd_status_parses_running_service() {
+        let home = tempfile::TempDir::new().unwrap();
+        let state = launchd_status_with(home.path(), "gui/501", running).unwrap();
+        assert_eq!(
+            state,
+            LaunchdState {
+                state: LaunchdServiceState::Running,
+                pid: Some(4242),
+                last_exit_status: None,
+                installed: false,
+            }
+        );
+        assert_eq!(state.to_string(), "running, pid 4242");
+    }
+
+    #[test]
+    fn launchd_status_parses_spawn_failure_as_waiting() {
+        let home = tempfile::TempDir::new().unwrap();
+        let plist = launchd_plist_path(home.path());
+        fs::create_dir_all(plist.parent().unwrap()).unwrap();
+        fs::write(&plist, "").unwrap();
+
+        let state = launchd_status_with(home.path(), "gui/501", spawn_failed).unwrap();
+        assert_eq!(state.state, LaunchdServiceState::Waiting);
+        assert_eq!(state.pid, None);
+        assert_eq!(state.last_exit_status, Some(78));
+        assert!(state.installed);
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["state"], "waiting");
+        assert_eq!(json["last_exit_status"], 78);
+    }
+
+    #[test]
+    fn launchd_status_reports_unknown_service_as_not_loaded() {
+        let home = tempfile::TempDir::new().unwrap();
+        let state = launchd_status_with(home.path(), "gui/501", not_loaded).unwrap();
+        assert_eq!(state.state, LaunchdServiceState::NotLoaded);
+        assert_eq!(serde_json::to_value(&state).unwrap()["state"], "not_loaded");
+    }
+
+    #[test]
+    fn install_verification_fails_loudly_unless_running() {
+        let home = tempfile::TempDir::new().unwrap();
+        let ok = wait_for_running(home.path(), "gui/501", running, Duration::ZERO).unwrap();
+        assert_eq!(ok.pid, Some(4242));
+
+        let err = wait_for_running(home.path(), "gui/501", spawn_failed, Duration::ZERO)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("did not start dev.orchestra.daemon"), "{err}");
+        assert!(err.contains("waiting, last exit status 78"), "{err}");
+    }
+
     #[test]
     fn plist_contains_required_launchd_fields() {
         let binary = Path::new("/usr/local/bin/orchestra");
         let log_dir = Path::new("/Users/tester/.orchestra/logs");
-        let plist = generate_plist(binary, log_dir);
+        let plist = generate_plist(binary, &[], log_dir, None);
 
         let value = Value::from_reader_xml(plist.as_bytes()).expect("parse plist");
         let dict = value.as_dictionary().expect("plist root dict");
@@ -195,4 +514,47 @@ mod tests {
             vec!["/usr/local/bin/orchestra", "daemon", "start"]
         );
     }
+
+    #[test]
+    fn plist_exports_custom_orchestra_home() {
+        let binary = Path::new("/usr/local/bin/orchestra");
+        let log_dir = Path::new("/Users/tester/Sync/orchestra/logs");
+        let root = Path::new("/Users/tester/Sync/orchestra");
+
+        let value = Value::from_reader_xml(generate_plist(binary, &[], log_dir, Some(root)).as_bytes())
+            .expect("parse plist");
+        let env = value
+            .as_dictionary()
+            .and_then(|dict| dict.get("EnvironmentVariables"))
+            .and_then(Value::as_dictionary)
+            .expect("EnvironmentVariables dict");
+        assert_eq!(
+            env.get("ORCHESTRA_HOME").and_then(Value::as_string),
+            Some("/Users/tester/Sync/orchestra")
+        );
+
+        let plain = generate_plist(binary, &[], log_dir, None);
+        assert!(!plain.contains("EnvironmentVariables"));
+    }
+
+    #[test]
+    fn plist_passes_start_args_through() {
+        let binary = Path::new("/usr/local/bin/orchestra");
+        let log_dir = Path::new("/Users/tester/.orchestra/logs");
+        let plist = generate_plist(binary, &["--no-startup-sync"], log_dir, None);
+
+        let value = Value::from_reader_xml(plist.as_bytes()).expect("parse plist");
+        let args: Vec<&str> = value
+            .as_dictionary()
+            .and_then(|dict| dict.get("ProgramArguments"))
+            .and_then(Value::as_array)
+            .expect("ProgramArguments array")
+            .iter()
+            .map(|v| v.as_string().expect("program arg as string"))
+            .collect();
+        assert_eq!(
+            args,
+            vec!["/usr/local/bin/orchestra", "daemon", "start", "--no-startup-sync"]
+        );
+    }
 }
@@ -1,21 +1,50 @@
 //! Phase 04 daemon runtime: watcher + sync processor + socket server.
 
+#[cfg(unix)]
+pub mod dispatch;
 mod error;
+pub mod events;
 pub mod launchd;
+pub mod limits;
 pub mod log_rotation;
+#[cfg(unix)]
+pub mod metrics;
 pub mod paths;
 pub mod protocol;
+pub mod rate_limit;
 #[cfg(unix)]
 mod runtime;
+pub mod service;
+pub mod state;
+pub mod systemd;
+#[cfg(unix)]
+pub mod watch;
 
 pub use error::DaemonError;
-pub use launchd::{generate_plist, install as install_launchd, uninstall as uninstall_launchd};
+pub use launchd::{
+    generate_plist, install as install_launchd, launchd_status, uninstall as uninstall_launchd,
+    LaunchdServiceState, LaunchdState,
+};
 pub use protocol::{
-    request_status, request_stop, request_sync, send_request, DaemonRequest, DaemonResponse,
+    check_protocol_version, daemon_version_warning, request_status, request_status_report,
+    request_stop, request_sync, send_request, wait_for_shutdown, DaemonRequest, DaemonResponse,
+    PROTOCOL_TOO_NEW, PROTOCOL_VERSION, RATE_LIMITED, SHUTDOWN_TIMEOUT, SHUTTING_DOWN,
+};
+pub use rate_limit::{RateDecision, RateLimit, RateLimiter};
+pub use service::{
+    install as install_service, installed_service, uninstall as uninstall_service,
+    SERVICE_MANAGER,
 };
+pub use systemd::generate_unit;
 
 #[cfg(unix)]
-pub use runtime::{run, start_blocking, RegistryCache, SyncSummary};
+pub use dispatch::{dispatch, serve_lines, serve_stdio, DispatchContext};
+#[cfg(unix)]
+pub use runtime::{
+    run, start_blocking, DaemonOptions, RegistryCache, SyncSummary, DEFAULT_RESYNC_INTERVAL,
+};
+#[cfg(unix)]
+pub use watch::{SyncEvent, Watch, WatchHandle};
 
 // ---------------------------------------------------------------------------
 // Windows stubs
@@ -34,19 +63,50 @@ pub struct SyncSummary {
     pub written: usize,
     pub unchanged: usize,
     pub duration_ms: u128,
+    pub coalesced: usize,
+}
+
+#[cfg(not(unix))]
+pub const DEFAULT_RESYNC_INTERVAL: std::time::Duration =
+    std::time::Duration::from_secs(24 * 60 * 60);
+
+#[cfg(not(unix))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaemonOptions {
+    pub startup_sync: bool,
+    pub resync_interval: Option<std::time::Duration>,
+}
+
+#[cfg(not(unix))]
+impl Default for DaemonOptions {
+    fn default() -> Self {
+        Self {
+            startup_sync: true,
+            resync_interval: Some(DEFAULT_RESYNC_INTERVAL),
+        }
+    }
 }
 
 #[cfg(not(unix))]
-pub fn start_blocking(_home: &std::path::Path) -> Result<(), DaemonError> {
+pub fn start_blocking(
+    _home: &std::path::Path,
+    _options: DaemonOptions,
+) -> Result<(), DaemonError> {
     Err(DaemonError::Protocol(
         "the Orchestra daemon is not supported on Windows".to_string(),
     ))
 }
 
 #[cfg(not(unix))]
-pub async fn run(_home: std::path::PathBuf) -> Result<(), DaemonError> {
+pub async fn run(_home: std::path::PathBuf, _options: DaemonOptions) -> Result<(), DaemonError> {
     Err(DaemonError::Protocol(
         "the Orchestra daemon is not supported on Windows".to_string(),
     ))
 }
 
+#[cfg(not(unix))]
+pub fn serve_stdio(_home: &std::path::Path) -> Result<(), DaemonError> {
+    Err(DaemonError::Protocol(
+        "orchestra serve is not supported on Windows".to_string(),
+    ))
+}
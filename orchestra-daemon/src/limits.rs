@@ -0,0 +1,19 @@
+//! Resource limits for daemon socket clients.
+//!
+//! Any local user process can connect to the socket, so one misbehaving
+//! client must not be able to pin daemon memory or tasks indefinitely.
+
+use std::time::Duration;
+
+/// Longest request line accepted, excluding the trailing newline. A longer
+/// line gets an `invalid request: line too long` response and the
+/// connection is closed.
+pub const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+
+/// A connection that does not complete a request within this window is
+/// dropped.
+pub const CLIENT_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Concurrent client connections served; further connections receive an
+/// immediate `busy` error response.
+pub const MAX_SOCKET_CLIENTS: usize = 32;
@@ -0,0 +1,166 @@
+//! In-process daemon counters surfaced through the `status` payload.
+//!
+//! Counters live for the lifetime of the daemon process: they start at zero
+//! on launch and are never reset while it runs.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Number of most recent sync durations kept for the status payload.
+pub const RECENT_SYNC_DURATIONS: usize = 10;
+
+/// Shared daemon counters. Cloning shares the same underlying counters.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    events_received: Arc<AtomicU64>,
+    events_debounced: Arc<AtomicU64>,
+    syncs_started: Arc<AtomicU64>,
+    syncs_failed: Arc<AtomicU64>,
+    files_written: Arc<AtomicU64>,
+    files_unchanged: Arc<AtomicU64>,
+    recent_sync_durations_ms: Arc<Mutex<VecDeque<u64>>>,
+}
+
+/// Point-in-time copy of [`Metrics`], serialized as the `metrics` object.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MetricsSnapshot {
+    pub events_received: u64,
+    pub events_debounced: u64,
+    pub syncs_started: u64,
+    pub syncs_failed: u64,
+    pub files_written: u64,
+    pub files_unchanged: u64,
+    /// Oldest first; at most [`RECENT_SYNC_DURATIONS`] entries.
+    pub recent_sync_durations_ms: Vec<u64>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A watcher event arrived for one path.
+    pub fn event_received(&self) {
+        self.events_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A watcher event was dropped by the debounce window.
+    pub fn event_debounced(&self) {
+        self.events_debounced.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn sync_started(&self) {
+        self.syncs_started.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn sync_failed(&self) {
+        self.syncs_failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Add one sync run's write outcome to the file counters.
+    pub fn files_synced(&self, written: usize, unchanged: usize) {
+        self.files_written.fetch_add(written as u64, Ordering::Relaxed);
+        self.files_unchanged
+            .fetch_add(unchanged as u64, Ordering::Relaxed);
+    }
+
+    /// Push a sync duration, evicting the oldest once the buffer is full.
+    pub fn sync_finished(&self, duration: Duration) {
+        let mut recent = self
+            .recent_sync_durations_ms
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if recent.len() == RECENT_SYNC_DURATIONS {
+            recent.pop_front();
+        }
+        recent.push_back(duration.as_millis() as u64);
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let recent = self
+            .recent_sync_durations_ms
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        MetricsSnapshot {
+            events_received: self.events_received.load(Ordering::Relaxed),
+            events_debounced: self.events_debounced.load(Ordering::Relaxed),
+            syncs_started: self.syncs_started.load(Ordering::Relaxed),
+            syncs_failed: self.syncs_failed.load(Ordering::Relaxed),
+            files_written: self.files_written.load(Ordering::Relaxed),
+            files_unchanged: self.files_unchanged.load(Ordering::Relaxed),
+            recent_sync_durations_ms: recent.iter().copied().collect(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_counters() {
+        let metrics = Metrics::new();
+        let watcher = metrics.clone();
+        watcher.event_received();
+        watcher.event_received();
+        watcher.event_debounced();
+        metrics.sync_started();
+        metrics.sync_failed();
+        metrics.files_synced(3, 7);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.events_received, 2);
+        assert_eq!(snapshot.events_debounced, 1);
+        assert_eq!(snapshot.syncs_started, 1);
+        assert_eq!(snapshot.syncs_failed, 1);
+        assert_eq!(snapshot.files_written, 3);
+        assert_eq!(snapshot.files_unchanged, 7);
+    }
+
+    #[test]
+    fn durations_keep_only_most_recent() {
+        let metrics = Metrics::new();
+        for ms in 1..=(RECENT_SYNC_DURATIONS as u64 + 3) {
+            metrics.sync_finished(Duration::from_millis(ms));
+        }
+        let recent = metrics.snapshot().recent_sync_durations_ms;
+        assert_eq!(recent.len(), RECENT_SYNC_DURATIONS);
+        assert_eq!(recent.first(), Some(&4));
+        assert_eq!(recent.last(), Some(&(RECENT_SYNC_DURATIONS as u64 + 3)));
+    }
+
+    #[test]
+    fn snapshot_json_shape() {
+        let metrics = Metrics::new();
+        metrics.sync_finished(Duration::from_millis(12));
+        let value = serde_json::to_value(metrics.snapshot()).unwrap();
+        let mut keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "events_debounced",
+                "events_received",
+                "files_unchanged",
+                "files_written",
+                "recent_sync_durations_ms",
+                "syncs_failed",
+                "syncs_started",
+            ]
+        );
+        assert_eq!(value["recent_sync_durations_ms"], serde_json::json!([12]));
+    }
+}
@@ -1,31 +1,53 @@
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+use orchestra_core::paths::dirs_at;
+
 pub const DAEMON_LABEL: &str = "dev.orchestra.daemon";
+pub const SYSTEMD_UNIT: &str = "orchestra-daemon.service";
 pub const DEBOUNCE_WINDOW: Duration = Duration::from_millis(500);
 
 pub const DAEMON_STDOUT_LOG: &str = "daemon.log";
 pub const DAEMON_STDERR_LOG: &str = "daemon-err.log";
+pub const DAEMON_EVENTS_LOG: &str = "events.jsonl";
 pub const DAEMON_SOCKET: &str = "daemon.sock";
+pub const DAEMON_META_FILE: &str = "daemon.meta.json";
+pub const DAEMON_STATE_FILE: &str = "daemon-state.json";
+pub const DAEMON_PID_FILE: &str = "daemon.pid";
 
 pub fn orchestra_root(home: &Path) -> PathBuf {
-    home.join(".orchestra")
+    dirs_at(home).data_root
 }
 
 pub fn projects_root(home: &Path) -> PathBuf {
-    orchestra_root(home).join("projects")
+    dirs_at(home).registry_root
 }
 
 pub fn run_dir(home: &Path) -> PathBuf {
-    orchestra_root(home).join("run")
+    dirs_at(home).run_dir
+}
+
+/// Remembers which orchestra version last ran the daemon.
+pub fn daemon_meta_path(home: &Path) -> PathBuf {
+    run_dir(home).join(DAEMON_META_FILE)
+}
+
+/// Sync timestamps kept across restarts, see [`crate::state`].
+pub fn daemon_state_path(home: &Path) -> PathBuf {
+    run_dir(home).join(DAEMON_STATE_FILE)
+}
+
+/// Pid of the daemon holding the socket, see [`crate::state`].
+pub fn daemon_pid_path(home: &Path) -> PathBuf {
+    run_dir(home).join(DAEMON_PID_FILE)
 }
 
 pub fn socket_path(home: &Path) -> PathBuf {
-    orchestra_root(home).join(DAEMON_SOCKET)
+    dirs_at(home).state_root.join(DAEMON_SOCKET)
 }
 
 pub fn logs_dir(home: &Path) -> PathBuf {
-    orchestra_root(home).join("logs")
+    dirs_at(home).logs_dir
 }
 
 pub fn stdout_log_path(home: &Path) -> PathBuf {
@@ -36,6 +58,11 @@ pub fn stderr_log_path(home: &Path) -> PathBuf {
     logs_dir(home).join(DAEMON_STDERR_LOG)
 }
 
+/// Structured JSONL event log, see [`crate::events`].
+pub fn events_log_path(home: &Path) -> PathBuf {
+    logs_dir(home).join(DAEMON_EVENTS_LOG)
+}
+
 pub fn launch_agents_dir(home: &Path) -> PathBuf {
     home.join("Library").join("LaunchAgents")
 }
@@ -43,3 +70,11 @@ pub fn launch_agents_dir(home: &Path) -> PathBuf {
 pub fn launchd_plist_path(home: &Path) -> PathBuf {
     launch_agents_dir(home).join(format!("{DAEMON_LABEL}.plist"))
 }
+
+pub fn systemd_user_dir(home: &Path) -> PathBuf {
+    home.join(".config").join("systemd").join("user")
+}
+
+pub fn systemd_unit_path(home: &Path) -> PathBuf {
+    systemd_user_dir(home).join(SYSTEMD_UNIT)
+}
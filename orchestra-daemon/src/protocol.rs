@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -10,19 +12,51 @@ use std::path::Path;
 #[cfg(unix)]
 use std::thread::sleep;
 #[cfg(unix)]
-use std::time::Duration;
+use std::time::Instant;
 
 #[cfg(unix)]
 use crate::error::{io_err, DaemonError};
 #[cfg(unix)]
 use crate::paths::socket_path;
 
+/// Version of the socket protocol this build speaks. Bump it whenever a
+/// change would make an older daemon misread or ignore part of a request.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// [`DaemonResponse::code`] of the error a daemon sends a client that
+/// speaks a newer protocol than it does.
+pub const PROTOCOL_TOO_NEW: &str = "protocol_too_new";
+
+/// [`DaemonResponse::code`] of the error a `sync` gets when the daemon began
+/// shutting down before running it.
+pub const SHUTTING_DOWN: &str = "shutting_down";
+
+/// [`DaemonResponse::code`] of the error a request gets when its command is
+/// over its rate limit; [`DaemonResponse::retry_after_ms`] says when to retry.
+pub const RATE_LIMITED: &str = "rate_limited";
+
+/// How long `daemon stop` and `daemon restart` wait for the daemon to exit.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[cfg(unix)]
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 /// JSON newline-delimited request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DaemonRequest {
     pub cmd: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub codebase: Option<String>,
+    /// Directory to inspect, for `detect`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// For `status`: answer with the full staleness report instead.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub detailed: bool,
+    /// [`PROTOCOL_VERSION`] of the client. [`send_request`] fills it in;
+    /// requests without it are answered as before.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<u32>,
 }
 
 /// JSON newline-delimited response.
@@ -33,6 +67,13 @@ pub struct DaemonResponse {
     pub data: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Machine-readable reason for some errors, e.g. [`PROTOCOL_TOO_NEW`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// For [`RATE_LIMITED`] errors: milliseconds until the request would be
+    /// accepted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
 }
 
 impl DaemonResponse {
@@ -41,6 +82,8 @@ impl DaemonResponse {
             ok: true,
             data: Some(data),
             error: None,
+            code: None,
+            retry_after_ms: None,
         }
     }
 
@@ -49,8 +92,71 @@ impl DaemonResponse {
             ok: false,
             data: None,
             error: Some(message.into()),
+            code: None,
+            retry_after_ms: None,
         }
     }
+
+    /// An error response carrying a machine-readable `code`.
+    pub fn error_with_code(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: Some(code.to_string()),
+            ..Self::error(message)
+        }
+    }
+
+    /// A [`RATE_LIMITED`] error for `cmd`, retryable after `retry_after`.
+    pub fn rate_limited(cmd: &str, retry_after: Duration) -> Self {
+        let seconds = retry_after.as_secs_f64().ceil().max(1.0);
+        Self {
+            retry_after_ms: Some(retry_after.as_millis().try_into().unwrap_or(u64::MAX)),
+            ..Self::error_with_code(
+                RATE_LIMITED,
+                format!("rate limited: too many '{cmd}' requests; retry in {seconds}s"),
+            )
+        }
+    }
+}
+
+/// The error response for a request from a client that speaks a newer
+/// protocol than [`PROTOCOL_VERSION`], or `None` when it can be served.
+pub fn check_protocol_version(request: &DaemonRequest) -> Option<DaemonResponse> {
+    let client = request.protocol_version.filter(|v| *v > PROTOCOL_VERSION)?;
+    Some(DaemonResponse::error_with_code(
+        PROTOCOL_TOO_NEW,
+        format!(
+            "the running daemon (v{}) speaks protocol v{PROTOCOL_VERSION} but this client needs v{client}; \
+             run `orchestra daemon restart` to start the installed version",
+            env!("CARGO_PKG_VERSION")
+        ),
+    ))
+}
+
+/// Warning for a `status` payload from a daemon built from an older
+/// release than this binary, e.g. one left running across an upgrade.
+/// Daemons from before the payload carried `version` count as older.
+pub fn daemon_version_warning(status: &Value) -> Option<String> {
+    let ours = env!("CARGO_PKG_VERSION");
+    let theirs = status.get("version").and_then(Value::as_str);
+    if theirs.is_some_and(|theirs| version_key(theirs) >= version_key(ours)) {
+        return None;
+    }
+    let running = theirs.map(|v| format!(" (v{v})")).unwrap_or_default();
+    Some(format!(
+        "daemon is running an older version{running} than this orchestra (v{ours}) — run `orchestra daemon restart`"
+    ))
+}
+
+/// Numeric `major.minor.patch` parts of a version; anything after a part's
+/// leading digits (such as `-beta.1`) is ignored.
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|part| {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().unwrap_or(0)
+        })
+        .collect()
 }
 
 /// Send one JSON request to the daemon socket and return one response.
@@ -76,7 +182,9 @@ pub fn send_request(home: &Path, request: &DaemonRequest) -> Result<DaemonRespon
         }
     })?;
 
-    let payload = serde_json::to_string(request)?;
+    let mut request = request.clone();
+    request.protocol_version.get_or_insert(PROTOCOL_VERSION);
+    let payload = serde_json::to_string(&request)?;
     stream
         .write_all(payload.as_bytes())
         .map_err(|e| io_err(&socket, e))?;
@@ -103,6 +211,9 @@ pub fn request_status(home: &Path) -> Result<Value, DaemonError> {
     let request = DaemonRequest {
         cmd: "status".to_string(),
         codebase: None,
+        path: None,
+        detailed: false,
+        protocol_version: None,
     };
 
     let mut last_not_running: Option<DaemonError> = None;
@@ -125,16 +236,43 @@ pub fn request_status(home: &Path) -> Result<Value, DaemonError> {
     }))
 }
 
+/// Ask the daemon to stop. It answers once its sync queue is drained, with
+/// `drained` and `rejected` counts of the sync requests it finished or
+/// refused on the way out.
 #[cfg(unix)]
-pub fn request_stop(home: &Path) -> Result<(), DaemonError> {
+pub fn request_stop(home: &Path) -> Result<Value, DaemonError> {
     let response = send_request(
         home,
         &DaemonRequest {
             cmd: "stop".to_string(),
             codebase: None,
+            path: None,
+            detailed: false,
+            protocol_version: None,
         },
     )?;
-    response_into_data(response).map(|_| ())
+    response_into_data(response)
+}
+
+/// Block until the daemon socket is gone or refuses connections.
+///
+/// The daemon acknowledges `stop` before its tasks have torn down, so a new
+/// daemon started right after the acknowledgement can find the socket still
+/// in use. Returns [`DaemonError::ShutdownTimeout`] if the socket still
+/// accepts connections after `timeout`.
+#[cfg(unix)]
+pub fn wait_for_shutdown(home: &Path, timeout: Duration) -> Result<(), DaemonError> {
+    let socket = socket_path(home);
+    let deadline = Instant::now() + timeout;
+    loop {
+        if !socket.exists() || UnixStream::connect(&socket).is_err() {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(DaemonError::ShutdownTimeout { socket, timeout });
+        }
+        sleep(SHUTDOWN_POLL_INTERVAL);
+    }
 }
 
 #[cfg(unix)]
@@ -144,6 +282,27 @@ pub fn request_sync(home: &Path, codebase: Option<String>) -> Result<Value, Daem
         &DaemonRequest {
             cmd: "sync".to_string(),
             codebase,
+            path: None,
+            detailed: false,
+            protocol_version: None,
+        },
+    )?;
+    response_into_data(response)
+}
+
+/// Ask the daemon for the staleness report that `orchestra status --json`
+/// prints. Unlike [`request_status`], a daemon that is not running is
+/// reported at once rather than retried.
+#[cfg(unix)]
+pub fn request_status_report(home: &Path) -> Result<Value, DaemonError> {
+    let response = send_request(
+        home,
+        &DaemonRequest {
+            cmd: "status".to_string(),
+            codebase: None,
+            path: None,
+            detailed: true,
+            protocol_version: None,
         },
     )?;
     response_into_data(response)
@@ -189,7 +348,22 @@ pub fn request_status(
 }
 
 #[cfg(not(unix))]
-pub fn request_stop(_home: &std::path::Path) -> Result<(), crate::error::DaemonError> {
+pub fn request_status_report(
+    _home: &std::path::Path,
+) -> Result<Value, crate::error::DaemonError> {
+    Err(not_supported())
+}
+
+#[cfg(not(unix))]
+pub fn request_stop(_home: &std::path::Path) -> Result<Value, crate::error::DaemonError> {
+    Err(not_supported())
+}
+
+#[cfg(not(unix))]
+pub fn wait_for_shutdown(
+    _home: &std::path::Path,
+    _timeout: Duration,
+) -> Result<(), crate::error::DaemonError> {
     Err(not_supported())
 }
 
@@ -201,3 +375,88 @@ pub fn request_sync(
     Err(not_supported())
 }
 
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+    use tempfile::TempDir;
+
+    fn bind_socket(home: &Path) -> UnixListener {
+        let socket = socket_path(home);
+        std::fs::create_dir_all(socket.parent().unwrap()).unwrap();
+        UnixListener::bind(&socket).expect("bind socket")
+    }
+
+    #[test]
+    fn version_warning_only_for_older_daemons() {
+        let ours = env!("CARGO_PKG_VERSION");
+        assert_eq!(
+            daemon_version_warning(&serde_json::json!({ "version": ours })),
+            None
+        );
+        assert_eq!(
+            daemon_version_warning(&serde_json::json!({ "version": "999.0.0" })),
+            None,
+            "a newer daemon understands this client"
+        );
+
+        let older = daemon_version_warning(&serde_json::json!({ "version": "0.0.1" }))
+            .expect("older daemon warns");
+        assert!(older.contains("older version (v0.0.1)"), "{older}");
+        assert!(older.contains("orchestra daemon restart"), "{older}");
+
+        let unversioned = daemon_version_warning(&serde_json::json!({ "running": true }))
+            .expect("daemon without a version warns");
+        assert!(unversioned.starts_with("daemon is running an older version than"));
+    }
+
+    #[test]
+    fn version_key_compares_numerically() {
+        assert!(version_key("0.1.10") > version_key("0.1.9"));
+        assert_eq!(version_key("0.2.0-beta.1"), vec![0, 2, 0, 1]);
+        assert!(version_key("1.0.0") > version_key("0.99.99"));
+    }
+
+    #[test]
+    fn wait_for_shutdown_returns_once_socket_is_removed() {
+        let home = TempDir::new().unwrap();
+        let listener = bind_socket(home.path());
+        let socket = socket_path(home.path());
+
+        let remover = thread::spawn(move || {
+            sleep(Duration::from_millis(200));
+            drop(listener);
+            std::fs::remove_file(socket).unwrap();
+        });
+
+        let started = Instant::now();
+        wait_for_shutdown(home.path(), Duration::from_secs(5)).expect("shutdown observed");
+        assert!(started.elapsed() >= Duration::from_millis(150));
+        remover.join().unwrap();
+    }
+
+    #[test]
+    fn wait_for_shutdown_accepts_a_stale_socket_file() {
+        let home = TempDir::new().unwrap();
+        drop(bind_socket(home.path()));
+        assert!(socket_path(home.path()).exists(), "socket file is left behind");
+
+        wait_for_shutdown(home.path(), Duration::from_millis(100))
+            .expect("refused connections count as shut down");
+    }
+
+    #[test]
+    fn wait_for_shutdown_times_out_while_socket_accepts() {
+        let home = TempDir::new().unwrap();
+        let _listener = bind_socket(home.path());
+
+        let err = wait_for_shutdown(home.path(), Duration::from_millis(100)).unwrap_err();
+        assert!(matches!(err, DaemonError::ShutdownTimeout { .. }), "got: {err}");
+    }
+}
@@ -0,0 +1,191 @@
+//! Per-command rate limits for daemon socket requests.
+//!
+//! Every job on a shared CI runner connects as the same user, so the socket
+//! permissions alone do not stop them from flooding the daemon with `sync`
+//! requests. Each limited command gets a token bucket shared by all clients:
+//! a request takes a token, tokens refill evenly over the limit's window,
+//! and a request that finds the bucket empty is refused with
+//! [`RATE_LIMITED`](crate::protocol::RATE_LIMITED) and how long until the
+//! next token. Commands without a limit, such as `status`, always pass.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// `sync` requests accepted per [`SYNC_RATE_WINDOW`].
+pub const SYNC_RATE_LIMIT: u32 = 10;
+
+/// Window the `sync` limit refills over.
+pub const SYNC_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// At most `requests` per `window`, all of which may arrive at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub window: Duration,
+}
+
+/// The outcome of [`RateLimiter::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateDecision {
+    Allow,
+    /// Refused; a token is available again after this long.
+    RetryAfter(Duration),
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token buckets for the limited commands.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limits: HashMap<String, RateLimit>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl Default for RateLimiter {
+    /// [`SYNC_RATE_LIMIT`] syncs per [`SYNC_RATE_WINDOW`]; nothing else is
+    /// limited.
+    fn default() -> Self {
+        Self::new([(
+            "sync",
+            RateLimit {
+                requests: SYNC_RATE_LIMIT,
+                window: SYNC_RATE_WINDOW,
+            },
+        )])
+    }
+}
+
+impl RateLimiter {
+    /// A limiter for the commands in `limits`; other commands are unlimited.
+    pub fn new<'a>(limits: impl IntoIterator<Item = (&'a str, RateLimit)>) -> Self {
+        Self {
+            limits: limits
+                .into_iter()
+                .map(|(cmd, limit)| (cmd.to_string(), limit))
+                .collect(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for `cmd`, if it is limited.
+    pub fn check(&self, cmd: &str) -> RateDecision {
+        self.check_at(cmd, Instant::now())
+    }
+
+    /// The limit on `cmd`, if any.
+    pub fn limit(&self, cmd: &str) -> Option<RateLimit> {
+        self.limits.get(cmd).copied()
+    }
+
+    fn check_at(&self, cmd: &str, now: Instant) -> RateDecision {
+        let Some(limit) = self.limit(cmd) else {
+            return RateDecision::Allow;
+        };
+        let capacity = f64::from(limit.requests);
+        let per_token = limit.window.as_secs_f64() / capacity.max(1.0);
+
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let bucket = buckets.entry(cmd.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            refilled_at: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() / per_token).min(capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            RateDecision::Allow
+        } else {
+            RateDecision::RetryAfter(Duration::from_secs_f64((1.0 - bucket.tokens) * per_token))
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn three_per_minute() -> RateLimiter {
+        RateLimiter::new([(
+            "sync",
+            RateLimit {
+                requests: 3,
+                window: Duration::from_secs(60),
+            },
+        )])
+    }
+
+    #[test]
+    fn burst_is_allowed_up_to_the_limit_then_refused() {
+        let limiter = three_per_minute();
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(limiter.check_at("sync", start), RateDecision::Allow);
+        }
+        assert_eq!(
+            limiter.check_at("sync", start),
+            RateDecision::RetryAfter(Duration::from_secs(20))
+        );
+        // Unlimited commands are never refused.
+        for _ in 0..100 {
+            assert_eq!(limiter.check_at("status", start), RateDecision::Allow);
+        }
+    }
+
+    #[test]
+    fn tokens_refill_over_the_window() {
+        let limiter = three_per_minute();
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.check_at("sync", start);
+        }
+
+        let later = start + Duration::from_secs(15);
+        assert_eq!(
+            limiter.check_at("sync", later),
+            RateDecision::RetryAfter(Duration::from_secs(5))
+        );
+        let refilled = start + Duration::from_secs(20);
+        assert_eq!(limiter.check_at("sync", refilled), RateDecision::Allow);
+        assert!(matches!(
+            limiter.check_at("sync", refilled),
+            RateDecision::RetryAfter(_)
+        ));
+
+        // A long pause refills to the burst size, not beyond it.
+        let idle = start + Duration::from_secs(3600);
+        for _ in 0..3 {
+            assert_eq!(limiter.check_at("sync", idle), RateDecision::Allow);
+        }
+        assert!(matches!(
+            limiter.check_at("sync", idle),
+            RateDecision::RetryAfter(_)
+        ));
+    }
+
+    #[test]
+    fn default_limits_only_sync() {
+        let limiter = RateLimiter::default();
+        assert_eq!(
+            limiter.limit("sync"),
+            Some(RateLimit {
+                requests: SYNC_RATE_LIMIT,
+                window: SYNC_RATE_WINDOW,
+            })
+        );
+        assert_eq!(limiter.limit("status"), None);
+    }
+}
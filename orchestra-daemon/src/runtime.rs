@@ -1,111 +1,337 @@
 #![cfg(unix)]
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::fs;
 use std::io::ErrorKind;
 use std::os::unix::net::UnixStream as StdUnixStream;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use notify::{recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
 use tokio::net::unix::OwnedWriteHalf;
 use tokio::net::{UnixListener, UnixStream};
-use tokio::sync::{broadcast, mpsc, oneshot, RwLock};
+use tokio::sync::broadcast::error::TryRecvError;
+use tokio::sync::{broadcast, mpsc, oneshot, watch, Mutex, RwLock, Semaphore};
+use tokio::task::JoinSet;
 use tokio::time::Instant;
 
 use orchestra_core::{
     registry,
-    types::{Codebase, CodebaseName},
-};
-use orchestra_sync::{
-    hash_store,
-    managed_agent_paths, pipeline::{self, SyncScope}, process_writeback,
-    staleness, SyncCodebaseResult, WriteResult,
+    types::{Codebase, CodebaseName, ProjectName},
 };
+use orchestra_sync::{staleness, status, StalenessSignal};
 
 use crate::error::{io_err, DaemonError};
-use crate::paths::{projects_root, run_dir, socket_path, DEBOUNCE_WINDOW};
-use crate::protocol::{DaemonRequest, DaemonResponse};
+use crate::events::{EventKind, EventLog};
+use crate::limits::{CLIENT_IDLE_TIMEOUT, MAX_REQUEST_BYTES, MAX_SOCKET_CLIENTS};
+use crate::dispatch::{decode_request, dispatch, DispatchContext};
+use crate::metrics::Metrics;
+use crate::paths::{daemon_meta_path, projects_root, run_dir, socket_path};
+use crate::protocol::{DaemonRequest, DaemonResponse, PROTOCOL_VERSION, SHUTDOWN_TIMEOUT};
+use crate::rate_limit::{RateDecision, RateLimiter};
+use crate::state;
+use crate::watch::{
+    build_sync_summary, daemon_sync_runner, mark_target_own_writes, notify_watcher,
+    run_sync_with_lock_retry, sync_target_for_path, OwnWrites, SyncEvent, SyncRunner, SyncTarget,
+    Syncer, Watch, WrapWatcher,
+};
+
+pub use crate::watch::SyncSummary;
 
 pub type RegistryCache = HashMap<CodebaseName, Codebase>;
 
-/// Per-codebase last-successful-sync timestamps (Unix seconds).
-/// Key: codebase name string. Value: unix seconds at last successful sync.
-pub type SyncTimestamps = HashMap<String, u64>;
-type OwnWrites = HashMap<PathBuf, Instant>;
-const OWN_WRITE_SUPPRESS_WINDOW: Duration = Duration::from_secs(1);
+/// How often the cache is rebuilt from the whole registry. Between passes,
+/// only the codebases a sync or registry event touched are reloaded.
+const CACHE_RECONCILE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// How long a computed staleness report answers further `status` requests.
+const STATUS_REPORT_TTL: Duration = Duration::from_secs(3);
+
+/// The last staleness report served, and when it was computed.
+pub(crate) type StatusReportCache = std::sync::Arc<Mutex<Option<(Instant, Value)>>>;
+
+/// The summary of the most recent post-sync staleness scan.
+pub(crate) type LastStalenessScan = std::sync::Arc<Mutex<Option<StalenessScanSummary>>>;
+
+/// The outcome of the most recent scheduled full re-sync.
+pub(crate) type LastScheduledResync = std::sync::Arc<Mutex<Option<ScheduledResync>>>;
+
+/// How many directories the watcher could not watch, see
+/// [`SyncEvent::WatchDegraded`].
+pub(crate) type UnwatchedDirs = std::sync::Arc<AtomicUsize>;
+
+/// Published once by the sync processor when it has emptied its queue after
+/// a shutdown; `stop` waits on it before answering.
+pub(crate) type DrainSignal = std::sync::Arc<watch::Sender<Option<DrainReport>>>;
+
+/// What the sync processor did with outstanding sync requests on shutdown.
+/// Both counts are requests, so coalesced duplicates count once each.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct DrainReport {
+    /// Requests answered by the sync that was running when shutdown began.
+    pub(crate) drained: usize,
+    /// Queued requests answered with [`SyncJobError::ShuttingDown`].
+    pub(crate) rejected: usize,
+}
+
+/// One full re-sync queued by the daemon itself rather than by a registry
+/// change, surfaced in the status payload as `last_scheduled_resync`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScheduledResync {
+    /// [`SCHEDULE_SYNC_SOURCE`] or [`UPGRADE_SYNC_SOURCE`].
+    pub source: String,
+    pub started_at_unix: u64,
+    pub finished_at_unix: u64,
+    pub written: usize,
+    pub unchanged: usize,
+    pub error: Option<String>,
+}
+
+/// Contents of [`daemon_meta_path`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct DaemonMeta {
+    version: String,
+}
 
-#[derive(Debug, Clone)]
-enum SyncTarget {
-    All,
-    Codebase(String),
+/// Signal counts from one post-sync staleness scan, logged as a single line
+/// and surfaced in the status payload as `last_staleness_scan`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StalenessScanSummary {
+    /// Label of the sync target whose completion triggered the scan.
+    pub target: String,
+    pub scanned_at_unix: u64,
+    pub current: usize,
+    pub never_synced: usize,
+    pub stale: usize,
+    pub modified: usize,
+    pub orphan: usize,
+    /// Codebases whose check failed.
+    pub failed: usize,
 }
 
-impl SyncTarget {
-    fn scope(&self) -> SyncScope {
-        match self {
-            SyncTarget::All => SyncScope::All,
-            SyncTarget::Codebase(name) => SyncScope::Codebase(name.clone()),
+impl StalenessScanSummary {
+    fn record(&mut self, signal: &StalenessSignal) {
+        match signal {
+            StalenessSignal::Current => self.current += 1,
+            StalenessSignal::NeverSynced => self.never_synced += 1,
+            StalenessSignal::Stale { .. } => self.stale += 1,
+            StalenessSignal::Modified { .. } => self.modified += 1,
+            StalenessSignal::Orphan { .. } => self.orphan += 1,
         }
     }
+}
 
-    fn label(&self) -> String {
-        match self {
-            SyncTarget::All => "all".to_string(),
-            SyncTarget::Codebase(name) => name.clone(),
-        }
+/// Last sync outcome for one codebase, surfaced in the status payload.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SyncState {
+    /// Unix seconds at the last successful sync (0 if none yet).
+    pub last_success_unix: u64,
+    /// Error from the most recent failed sync; cleared by the next success.
+    pub last_error: Option<String>,
+    /// Failed syncs since the last success.
+    pub consecutive_failures: u32,
+    /// `last_success_unix` was saved by a previous daemon, see
+    /// [`crate::state`]; cleared by the next success.
+    pub restored: bool,
+}
+
+impl SyncState {
+    fn record_success(&mut self, now_unix: u64) {
+        self.last_success_unix = now_unix;
+        self.last_error = None;
+        self.consecutive_failures = 0;
+        self.restored = false;
+    }
+
+    fn record_failure(&mut self, error: &str) {
+        self.last_error = Some(error.to_string());
+        self.consecutive_failures += 1;
     }
 }
 
-struct SyncJob {
+/// Per-codebase sync state. Key: codebase name string.
+pub type SyncStates = HashMap<String, SyncState>;
+type SyncResponder = oneshot::Sender<Result<SyncSummary, SyncJobError>>;
+
+/// Why a queued sync request did not produce a summary.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub(crate) enum SyncJobError {
+    /// The sync ran and failed.
+    #[error("{0}")]
+    Failed(String),
+    /// The daemon began shutting down before the sync ran.
+    #[error("daemon is shutting down; the sync was not run")]
+    ShuttingDown,
+}
+
+/// Live daemon state that socket requests are answered from.
+#[derive(Clone)]
+pub(crate) struct DaemonState {
+    pub(crate) cache: std::sync::Arc<RwLock<RegistryCache>>,
+    pub(crate) sync_states: std::sync::Arc<RwLock<SyncStates>>,
+    pub(crate) metrics: Metrics,
+    pub(crate) sync_tx: mpsc::Sender<SyncJob>,
+    pub(crate) shutdown_tx: broadcast::Sender<()>,
+    pub(crate) started_at_unix: u64,
+    /// When the daemon whose state was restored at startup had started.
+    pub(crate) previous_started_at_unix: Option<u64>,
+    pub(crate) status_report: StatusReportCache,
+    pub(crate) last_staleness_scan: LastStalenessScan,
+    pub(crate) last_scheduled_resync: LastScheduledResync,
+    pub(crate) unwatched_dirs: UnwatchedDirs,
+    pub(crate) drain: DrainSignal,
+}
+
+pub(crate) struct SyncJob {
     target: SyncTarget,
     source: &'static str,
-    respond_to: oneshot::Sender<Result<SyncSummary, String>>,
+    respond_to: SyncResponder,
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct SyncSummary {
-    pub target: String,
-    pub source: String,
-    pub codebases: Vec<String>,
-    pub written: usize,
-    pub unchanged: usize,
-    pub duration_ms: u128,
+/// A queued sync that one or more callers are waiting on.
+struct PendingSync {
+    target: SyncTarget,
+    source: &'static str,
+    waiters: Vec<SyncResponder>,
+}
+
+/// Merge `job` into `queue`, attaching it to an equivalent queued sync.
+///
+/// A codebase job joins a queued job for the same codebase or a queued `All`
+/// job. An `All` job absorbs every queued codebase job.
+fn coalesce_job(queue: &mut Vec<PendingSync>, job: SyncJob) {
+    let covering = queue.iter_mut().find(|pending| match (&pending.target, &job.target) {
+        (SyncTarget::All, _) => true,
+        (SyncTarget::Codebase(queued), SyncTarget::Codebase(name)) => queued == name,
+        (SyncTarget::Codebase(_), SyncTarget::All) => false,
+    });
+    if let Some(pending) = covering {
+        pending.waiters.push(job.respond_to);
+        return;
+    }
+
+    let mut pending = PendingSync {
+        target: job.target,
+        source: job.source,
+        waiters: vec![job.respond_to],
+    };
+    if matches!(pending.target, SyncTarget::All) {
+        // No `All` job is queued (it would have matched above), so every
+        // queued entry is a codebase job that this one subsumes.
+        for absorbed in queue.drain(..) {
+            pending.waiters.extend(absorbed.waiters);
+        }
+    }
+    queue.push(pending);
+}
+
+/// Source recorded for syncs queued by the startup reconciliation pass.
+const STARTUP_SYNC_SOURCE: &str = "startup";
+
+/// Source recorded for the periodic full re-sync.
+const SCHEDULE_SYNC_SOURCE: &str = "schedule";
+
+/// Source recorded for the full re-sync after the orchestra binary changed.
+const UPGRADE_SYNC_SOURCE: &str = "upgrade";
+
+/// How often the daemon re-syncs every codebase unless told otherwise.
+pub const DEFAULT_RESYNC_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Behaviour switches for a daemon run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaemonOptions {
+    /// Queue a sync for every never-synced or stale codebase at startup.
+    pub startup_sync: bool,
+    /// Re-sync every codebase this often, so template changes from a new
+    /// orchestra binary reach files whose registry never changed. `None`
+    /// disables the schedule.
+    pub resync_interval: Option<Duration>,
+}
+
+impl Default for DaemonOptions {
+    fn default() -> Self {
+        Self {
+            startup_sync: true,
+            resync_interval: Some(DEFAULT_RESYNC_INTERVAL),
+        }
+    }
 }
 
 /// Start the daemon runtime and block the current thread until it exits.
-pub fn start_blocking(home: &Path) -> Result<(), DaemonError> {
+pub fn start_blocking(home: &Path, options: DaemonOptions) -> Result<(), DaemonError> {
     init_tracing();
     let runtime = tokio::runtime::Builder::new_multi_thread()
         .enable_all()
         .build()
         .map_err(|e| io_err("tokio-runtime", e))?;
-    runtime.block_on(run(home.to_path_buf()))
+    runtime.block_on(run(home.to_path_buf(), options))
 }
 
 /// Run the daemon runtime.
-pub async fn run(home: PathBuf) -> Result<(), DaemonError> {
+pub async fn run(home: PathBuf, options: DaemonOptions) -> Result<(), DaemonError> {
     ensure_runtime_dirs(&home)?;
 
     let cache = std::sync::Arc::new(RwLock::new(load_registry_cache(&home)?));
-    let sync_timestamps: std::sync::Arc<RwLock<SyncTimestamps>> =
-        std::sync::Arc::new(RwLock::new(HashMap::new()));
+    let (restored_states, previous_started_at_unix) = restored_sync_states(&home);
+    let sync_states: std::sync::Arc<RwLock<SyncStates>> =
+        std::sync::Arc::new(RwLock::new(restored_states));
     let own_writes: std::sync::Arc<RwLock<OwnWrites>> =
         std::sync::Arc::new(RwLock::new(HashMap::new()));
     let started_at_unix = unix_seconds_now();
+    let metrics = Metrics::new();
+    let last_staleness_scan = LastStalenessScan::default();
+    let last_scheduled_resync = LastScheduledResync::default();
+    let unwatched_dirs = UnwatchedDirs::default();
+    // A changed version is recorded only once the upgrade re-sync succeeds,
+    // so a failed or interrupted one is retried on the next start.
+    let stored_version = stored_daemon_version(&home);
+    let version_changed = stored_version
+        .as_deref()
+        .is_some_and(|stored| stored != env!("CARGO_PKG_VERSION"));
+    if stored_version.is_none() {
+        if let Err(err) = record_daemon_version(&home, env!("CARGO_PKG_VERSION")) {
+            tracing::warn!(error = %err, "failed to record daemon version");
+        }
+    }
 
     let (sync_tx, sync_rx) = mpsc::channel::<SyncJob>(64);
     let (shutdown_tx, _) = broadcast::channel::<()>(16);
+    let drain = DrainSignal::default();
+    let (events, events_writer) = EventLog::spawn(&home);
+    events.emit(
+        EventKind::DaemonStarted,
+        Vec::new(),
+        json!({ "version": env!("CARGO_PKG_VERSION"), "pid": std::process::id() }),
+    );
 
     let watcher_handle = {
         let shutdown = shutdown_tx.clone();
         let home = home.clone();
+        let cache = cache.clone();
+        let sync_states = sync_states.clone();
         let sync_tx = sync_tx.clone();
         let own_writes = own_writes.clone();
+        let metrics = metrics.clone();
+        let last_staleness_scan = last_staleness_scan.clone();
+        let unwatched_dirs = unwatched_dirs.clone();
+        let events = events.clone();
         tokio::spawn(async move {
-            let result = watcher_task(home, sync_tx, own_writes, shutdown.subscribe()).await;
+            let result = watcher_task(
+                home,
+                cache,
+                sync_states,
+                sync_tx,
+                own_writes,
+                metrics,
+                last_staleness_scan,
+                unwatched_dirs,
+                events,
+                shutdown.subscribe(),
+                notify_watcher,
+            )
+            .await;
             let _ = shutdown.send(());
             result
         })
@@ -115,16 +341,23 @@ pub async fn run(home: PathBuf) -> Result<(), DaemonError> {
         let shutdown = shutdown_tx.clone();
         let home = home.clone();
         let cache = cache.clone();
-        let timestamps = sync_timestamps.clone();
+        let sync_states = sync_states.clone();
         let own_writes = own_writes.clone();
+        let metrics = metrics.clone();
+        let drain = drain.clone();
+        let events = events.clone();
         tokio::spawn(async move {
             let result = sync_processor_task(
                 home,
                 cache,
-                timestamps,
+                sync_states,
                 own_writes,
+                metrics,
+                events,
                 sync_rx,
                 shutdown.subscribe(),
+                drain,
+                daemon_sync_runner,
             )
             .await;
             let _ = shutdown.send(());
@@ -132,21 +365,47 @@ pub async fn run(home: PathBuf) -> Result<(), DaemonError> {
         })
     };
 
+    // Codebases registered while the daemon was down (or never synced at all)
+    // would otherwise wait for their next registry change.
+    if options.startup_sync {
+        tokio::spawn(startup_sync_task(home.clone(), cache.clone(), sync_tx.clone()));
+    }
+
+    // Outputs also go stale when the embedded templates change, which no
+    // registry event reports.
+    if version_changed || options.resync_interval.is_some() {
+        tokio::spawn(resync_schedule_task(
+            sync_tx.clone(),
+            options.resync_interval,
+            version_changed.then(|| home.clone()),
+            last_scheduled_resync.clone(),
+            shutdown_tx.subscribe(),
+        ));
+    }
+
     let socket_handle = {
         let shutdown = shutdown_tx.clone();
         let home = home.clone();
         let cache = cache.clone();
         let sync_tx = sync_tx.clone();
-        let timestamps = sync_timestamps.clone();
+        let sync_states = sync_states.clone();
+        let metrics = metrics.clone();
         tokio::spawn(async move {
             let result = socket_server_task(
                 home,
                 cache,
-                timestamps,
+                sync_states,
+                metrics,
+                last_staleness_scan,
+                last_scheduled_resync,
+                unwatched_dirs,
                 sync_tx,
                 shutdown.clone(),
                 shutdown.subscribe(),
+                drain,
+                events,
                 started_at_unix,
+                previous_started_at_unix,
             )
             .await;
             let _ = shutdown.send(());
@@ -198,212 +457,372 @@ pub async fn run(home: PathBuf) -> Result<(), DaemonError> {
     handle_join("socket_server", socket_result)?;
     handle_join("log_rotation", rotation_result)?;
     handle_join("signal_handler", signal_result)?;
+
+    // The writer ends once the tasks above have dropped their handles.
+    if tokio::time::timeout(SHUTDOWN_TIMEOUT, events_writer)
+        .await
+        .is_err()
+    {
+        tracing::warn!("daemon event log writer did not finish; recent events may be missing");
+    }
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn watcher_task(
     home: PathBuf,
+    cache: std::sync::Arc<RwLock<RegistryCache>>,
+    sync_states: std::sync::Arc<RwLock<SyncStates>>,
     sync_tx: mpsc::Sender<SyncJob>,
     own_writes: std::sync::Arc<RwLock<OwnWrites>>,
+    metrics: Metrics,
+    last_staleness_scan: LastStalenessScan,
+    unwatched_dirs: UnwatchedDirs,
+    events: EventLog,
     mut shutdown_rx: broadcast::Receiver<()>,
+    wrap_watcher: WrapWatcher,
 ) -> Result<(), DaemonError> {
-    let projects = projects_root(&home);
-    if !projects.exists() {
-        fs::create_dir_all(&projects).map_err(|e| io_err(&projects, e))?;
-    }
-
-    // Canonicalize so that FSEvents paths (which arrive as real paths, e.g.
-    // /private/var/... on macOS) match the `starts_with` checks below.
-    let projects = fs::canonicalize(&projects).unwrap_or(projects);
-
-    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<notify::Result<Event>>();
-    let mut _watcher: RecommendedWatcher = recommended_watcher(move |event| {
-        let _ = event_tx.send(event);
-    })?;
-
-    let mut watched_dirs = HashSet::new();
-    register_projects_tree(&mut _watcher, &mut watched_dirs, &projects)?;
+    let mut watch = Watch::new(&home)
+        .with_syncer(Syncer::queued(home.clone(), sync_tx, own_writes))
+        .with_metrics(metrics)
+        .with_watcher_wrapper(wrap_watcher)
+        .spawn()?;
 
-    // Also watch parent directories of all managed agent files.
-    register_managed_agent_dirs(&mut _watcher, &mut watched_dirs, &home)?;
-
-    let mut debounce = HashMap::<PathBuf, Instant>::new();
+    let mut reconcile = tokio::time::interval(CACHE_RECONCILE_INTERVAL);
+    reconcile.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    reconcile.tick().await; // the cache was just loaded at startup
 
     loop {
         tokio::select! {
             _ = shutdown_rx.recv() => break,
-            event = event_rx.recv() => {
+            _ = reconcile.tick() => {
+                reconcile_cache(&home, &cache, &sync_states).await;
+            }
+            event = watch.next() => {
                 let Some(event) = event else { break };
-                let event = match event {
-                    Ok(event) => event,
-                    Err(err) => {
-                        tracing::warn!(error = %err, "watcher event error");
-                        continue;
-                    }
-                };
-                if !is_relevant_event_kind(&event.kind) {
-                    continue;
-                }
-
-                let event_kind = format!("{:?}", event.kind);
-
-                for path in event.paths {
-                    let path_key = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
-                    tracing::trace!(
-                        event_kind = %event_kind,
-                        path = %path.display(),
-                        canonical_path = %path_key.display(),
-                        "watcher event observed",
-                    );
-
-                    // FSEvents is directory-based; always register parent directory.
-                    if let Some(watch_dir) = directory_to_watch(&path_key) {
-                        if watch_dir.starts_with(&projects) && watch_dir.exists() {
-                            register_projects_tree(&mut _watcher, &mut watched_dirs, &watch_dir)?;
-                        }
-                    }
-
-                    if is_recent_own_write(&own_writes, &path_key, Instant::now()).await {
-                        tracing::trace!(
-                            path = %path_key.display(),
-                            "skipping watcher event for recent orchestra-owned write"
-                        );
-                        continue;
+                let (codebases, detail) = watcher_event_detail(&event);
+                match event {
+                    SyncEvent::RegistryRemoved { .. } => {
+                        handle_registry_removal(&home, &cache, &sync_states).await;
                     }
-
-                    if !should_process_event(&mut debounce, &path_key, Instant::now()) {
-                        tracing::trace!(
-                            path = %path_key.display(),
-                            "watcher event suppressed by debounce"
-                        );
-                        continue;
+                    SyncEvent::RegistryInvalid { path, error } => {
+                        let target = sync_target_for_path(&path);
+                        record_sync_failure(&cache, &sync_states, &target, &error).await;
                     }
-
-                    if is_registry_yaml(&path_key, &projects) {
-                        // — Phase 04 path: registry YAML changed → reload + sync —
-                        let target = sync_target_for_path(&path_key);
-
-                        match enqueue_sync(&sync_tx, target, "watcher").await {
-                            Ok(summary) => {
-                                tracing::info!(
-                                    target = %summary.target,
-                                    written = summary.written,
-                                    unchanged = summary.unchanged,
-                                    duration_ms = summary.duration_ms,
-                                    "watcher-triggered sync completed",
-                                );
-                                if let Err(err) = run_staleness_scan(home.clone()).await {
-                                    tracing::warn!(error = %err, "staleness scan after sync failed");
-                                }
+                    SyncEvent::SyncCompleted(summary) => {
+                        let scanned = summary.codebases.clone();
+                        match run_staleness_scan(home.clone(), summary).await {
+                            Ok(scan) => {
+                                events.emit(EventKind::StalenessScan, scanned, json!(scan));
+                                *last_staleness_scan.lock().await = Some(scan);
                             }
                             Err(err) => {
-                                tracing::error!(error = %err, "watcher-triggered sync failed");
+                                tracing::warn!(error = %err, "staleness scan after sync failed");
                             }
                         }
-                    } else if is_managed_agent_file(&path_key, &home) {
-                        // — Phase 05 path: agent file changed → check for writeback block —
-                        let home_clone = home.clone();
-                        let path_clone = path_key.clone();
-                        // Run writeback in a blocking task to avoid blocking the async loop.
-                        tokio::task::spawn_blocking(move || {
-                            match process_writeback(&home_clone, &path_clone) {
-                                Ok(outcome) if outcome.block_found => {
-                                    tracing::info!(
-                                        path = %path_clone.display(),
-                                        commands_applied = outcome.apply_results.len(),
-                                        parse_errors = outcome.parse_errors.len(),
-                                        block_stripped = outcome.block_stripped,
-                                        "writeback processed",
-                                    );
-                                }
-                                Ok(_) => {
-                                    // No block — normal agent file edit, ignore.
-                                }
-                                Err(err) => {
-                                    tracing::warn!(error = %err, path = %path_clone.display(), "writeback failed");
-                                }
-                            }
-                        });
                     }
+                    SyncEvent::WatchDegraded { unwatched_dirs: count } => {
+                        unwatched_dirs.store(count, Ordering::Relaxed);
+                    }
+                    // The sync processor reloads the changed codebase and
+                    // records failures for queued syncs.
+                    SyncEvent::RegistryChanged { .. } | SyncEvent::SyncFailed { .. } => {}
                 }
+                events.emit(EventKind::WatcherEventProcessed, codebases, detail);
             }
         }
     }
 
-    Ok(())
+    watch.stop().await
+}
+
+/// The codebases a watcher event concerns and the fields it is logged with.
+fn watcher_event_detail(event: &SyncEvent) -> (Vec<String>, Value) {
+    let path_codebases = |path: &Path| match sync_target_for_path(path) {
+        SyncTarget::Codebase(name) => vec![name],
+        SyncTarget::All => Vec::new(),
+    };
+    match event {
+        SyncEvent::RegistryChanged { path } => (
+            path_codebases(path),
+            json!({ "kind": "registry_changed", "path": path }),
+        ),
+        SyncEvent::RegistryRemoved { path } => (
+            path_codebases(path),
+            json!({ "kind": "registry_removed", "path": path }),
+        ),
+        SyncEvent::RegistryInvalid { path, error } => (
+            path_codebases(path),
+            json!({ "kind": "registry_invalid", "path": path, "error": error }),
+        ),
+        SyncEvent::SyncCompleted(summary) => (
+            summary.codebases.clone(),
+            json!({ "kind": "sync_completed", "target": summary.target }),
+        ),
+        SyncEvent::SyncFailed { target, error } => (
+            if target == "all" {
+                Vec::new()
+            } else {
+                vec![target.clone()]
+            },
+            json!({ "kind": "sync_failed", "target": target, "error": error }),
+        ),
+        SyncEvent::WatchDegraded { unwatched_dirs } => (
+            Vec::new(),
+            json!({ "kind": "watch_degraded", "unwatched_dirs": unwatched_dirs }),
+        ),
+    }
 }
 
+/// Run queued syncs one at a time until shutdown.
+///
+/// A sync that is running when shutdown begins finishes normally; every
+/// request still queued then, or sent afterwards, is answered with
+/// [`SyncJobError::ShuttingDown`]. The resulting [`DrainReport`] is published
+/// on `drain` last, so `stop` can answer once nobody is left waiting.
+#[allow(clippy::too_many_arguments)]
 async fn sync_processor_task(
     home: PathBuf,
     cache: std::sync::Arc<RwLock<RegistryCache>>,
-    timestamps: std::sync::Arc<RwLock<SyncTimestamps>>,
+    sync_states: std::sync::Arc<RwLock<SyncStates>>,
     own_writes: std::sync::Arc<RwLock<OwnWrites>>,
+    metrics: Metrics,
+    events: EventLog,
     mut sync_rx: mpsc::Receiver<SyncJob>,
     mut shutdown_rx: broadcast::Receiver<()>,
+    drain: DrainSignal,
+    runner: SyncRunner,
 ) -> Result<(), DaemonError> {
+    let mut queue = Vec::<PendingSync>::new();
+    let mut drained = 0;
+    let mut failure = None;
     loop {
-        tokio::select! {
-            _ = shutdown_rx.recv() => break,
-            maybe_job = sync_rx.recv() => {
-                let Some(job) = maybe_job else { break };
-                let started = Instant::now();
-
-                let target = job.target.clone();
-                let source = job.source;
-                let home_for_sync = home.clone();
-
-                match managed_paths_for_target(&home, &target) {
-                    Ok(paths) => mark_own_writes(&own_writes, paths, Instant::now()).await,
-                    Err(err) => tracing::warn!(
-                        error = %err,
-                        target = %target.label(),
-                        "unable to pre-register own write paths before sync"
-                    ),
+        if queue.is_empty() {
+            tokio::select! {
+                biased;
+                _ = shutdown_rx.recv() => break,
+                maybe_job = sync_rx.recv() => {
+                    let Some(job) = maybe_job else { break };
+                    coalesce_job(&mut queue, job);
                 }
+            }
+        }
 
-                let sync_result = tokio::task::spawn_blocking(move || {
-                    pipeline::run(&home_for_sync, target.scope(), false)
-                })
+        // Fold in everything that queued up while we were idle or syncing so
+        // duplicate requests share a single pipeline run.
+        while let Ok(job) = sync_rx.try_recv() {
+            coalesce_job(&mut queue, job);
+        }
+
+        let pending = queue.remove(0);
+        let outcome = match run_pending_sync(
+            &home,
+            &cache,
+            &sync_states,
+            &own_writes,
+            &metrics,
+            &events,
+            &pending,
+            runner,
+        )
+        .await
+        {
+            Ok(outcome) => outcome.map_err(SyncJobError::Failed),
+            Err(err) => {
+                // The daemon stops with the processor; refuse this sync's
+                // callers along with the rest of the queue.
+                queue.insert(0, pending);
+                failure = Some(err);
+                break;
+            }
+        };
+        let waiters = pending.waiters.len();
+        for waiter in pending.waiters {
+            let _ = waiter.send(outcome.clone());
+        }
+        if shutdown_requested(&mut shutdown_rx) {
+            drained = waiters;
+            break;
+        }
+    }
+
+    let rejected = reject_queued_syncs(queue, &mut sync_rx);
+    if drained + rejected > 0 {
+        tracing::info!(drained, rejected, "drained sync queue for shutdown");
+    }
+    drain.send_replace(Some(DrainReport { drained, rejected }));
+    failure.map_or(Ok(()), Err)
+}
+
+/// Whether shutdown was signalled since `shutdown_rx` was last polled.
+fn shutdown_requested(shutdown_rx: &mut broadcast::Receiver<()>) -> bool {
+    !matches!(shutdown_rx.try_recv(), Err(TryRecvError::Empty))
+}
+
+/// Stop accepting sync requests and answer every queued one with
+/// [`SyncJobError::ShuttingDown`]. Returns how many requests were refused.
+fn reject_queued_syncs(queue: Vec<PendingSync>, sync_rx: &mut mpsc::Receiver<SyncJob>) -> usize {
+    sync_rx.close();
+    let mut waiters: Vec<SyncResponder> =
+        queue.into_iter().flat_map(|pending| pending.waiters).collect();
+    while let Ok(job) = sync_rx.try_recv() {
+        waiters.push(job.respond_to);
+    }
+    let rejected = waiters.len();
+    for waiter in waiters {
+        let _ = waiter.send(Err(SyncJobError::ShuttingDown));
+    }
+    rejected
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_pending_sync(
+    home: &Path,
+    cache: &std::sync::Arc<RwLock<RegistryCache>>,
+    sync_states: &std::sync::Arc<RwLock<SyncStates>>,
+    own_writes: &std::sync::Arc<RwLock<OwnWrites>>,
+    metrics: &Metrics,
+    events: &EventLog,
+    pending: &PendingSync,
+    runner: SyncRunner,
+) -> Result<Result<SyncSummary, String>, DaemonError> {
+    let started = Instant::now();
+    metrics.sync_started();
+    let target = pending.target.clone();
+    let codebases = match &target {
+        SyncTarget::Codebase(name) => vec![name.clone()],
+        SyncTarget::All => {
+            let mut names: Vec<String> = cache
+                .read()
                 .await
-                .map_err(|err| DaemonError::Protocol(format!("sync task join error: {err}")))?;
-
-                let outcome = match sync_result {
-                    Ok(results) => {
-                        let refreshed = refresh_cache(home.clone(), cache.clone()).await;
-                        match refreshed {
-                            Ok(()) => {
-                                // Record successful sync timestamp for each affected codebase.
-                                let now = unix_seconds_now();
-                                let mut ts = timestamps.write().await;
-                                for name in results.iter().map(|r| r.codebase_name.as_str()) {
-                                    ts.insert(name.to_string(), now);
-                                }
-                                // Drop write lock before building summary.
-                                drop(ts);
-                                Ok(build_sync_summary(job.target, source, results, started.elapsed()))
-                            }
-                            Err(err) => Err(err.to_string()),
+                .keys()
+                .map(|name| name.0.clone())
+                .collect();
+            names.sort();
+            names
+        }
+    };
+    let sync_detail = json!({ "target": target.label(), "source": pending.source });
+    events.emit(
+        EventKind::SyncStarted,
+        codebases.clone(),
+        sync_detail.clone(),
+    );
+
+    mark_target_own_writes(home, own_writes, &target).await;
+    let sync_result = run_sync_with_lock_retry(home.to_path_buf(), target, runner).await?;
+
+    let outcome = match sync_result {
+        Ok(results) => {
+            let synced: Vec<CodebaseName> = results
+                .iter()
+                .map(|r| CodebaseName::from(r.codebase_name.as_str()))
+                .collect();
+            let refreshed = reload_codebases(home, cache, sync_states, synced).await;
+            match refreshed {
+                Ok(()) => {
+                    // Record a successful sync for each affected codebase.
+                    let now = unix_seconds_now();
+                    let mut states = sync_states.write().await;
+                    for name in results.iter().map(|r| r.codebase_name.as_str()) {
+                        states.entry(name.to_string()).or_default().record_success(now);
+                    }
+                    // Drop write lock before building summary.
+                    drop(states);
+                    let names: Vec<String> =
+                        results.iter().map(|r| r.codebase_name.clone()).collect();
+                    let state_home = home.to_path_buf();
+                    let saved = tokio::task::spawn_blocking(move || {
+                        state::record_successes_at(&state_home, &names, now)
+                    })
+                    .await;
+                    match saved {
+                        Ok(Ok(())) => {}
+                        Ok(Err(err)) => {
+                            tracing::warn!(error = %err, "failed to save daemon state");
+                        }
+                        Err(err) => {
+                            tracing::warn!(error = %err, "daemon state save task failed");
                         }
                     }
-                    Err(err) => Err(err.to_string()),
-                };
-
-                let _ = job.respond_to.send(outcome);
+                    let mut summary = build_sync_summary(
+                        pending.target.clone(),
+                        pending.source,
+                        results,
+                        started.elapsed(),
+                    );
+                    summary.coalesced = pending.waiters.len().saturating_sub(1);
+                    if summary.coalesced > 0 {
+                        tracing::debug!(
+                            target = %summary.target,
+                            coalesced = summary.coalesced,
+                            "coalesced duplicate sync requests",
+                        );
+                    }
+                    Ok(summary)
+                }
+                Err(err) => Err(err.to_string()),
             }
         }
+        Err(err) => Err(err.to_string()),
+    };
+
+    match &outcome {
+        Ok(summary) => {
+            metrics.files_synced(summary.written, summary.unchanged);
+            events.emit(
+                EventKind::SyncCompleted,
+                summary.codebases.clone(),
+                json!(summary),
+            );
+        }
+        Err(err) => {
+            metrics.sync_failed();
+            record_sync_failure(cache, sync_states, &pending.target, err).await;
+            let mut detail = sync_detail;
+            detail["error"] = json!(err);
+            events.emit(EventKind::SyncFailed, codebases, detail);
+        }
     }
+    metrics.sync_finished(started.elapsed());
 
-    Ok(())
+    Ok(outcome)
+}
+
+/// Attribute a failed sync to its codebase, or to every cached codebase when
+/// the whole registry was being synced.
+async fn record_sync_failure(
+    cache: &std::sync::Arc<RwLock<RegistryCache>>,
+    sync_states: &std::sync::Arc<RwLock<SyncStates>>,
+    target: &SyncTarget,
+    error: &str,
+) {
+    let names: Vec<String> = match target {
+        SyncTarget::Codebase(name) => vec![name.clone()],
+        SyncTarget::All => cache.read().await.keys().map(|name| name.0.clone()).collect(),
+    };
+    let mut states = sync_states.write().await;
+    for name in names {
+        states.entry(name).or_default().record_failure(error);
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn socket_server_task(
     home: PathBuf,
     cache: std::sync::Arc<RwLock<RegistryCache>>,
-    timestamps: std::sync::Arc<RwLock<SyncTimestamps>>,
+    sync_states: std::sync::Arc<RwLock<SyncStates>>,
+    metrics: Metrics,
+    last_staleness_scan: LastStalenessScan,
+    last_scheduled_resync: LastScheduledResync,
+    unwatched_dirs: UnwatchedDirs,
     sync_tx: mpsc::Sender<SyncJob>,
     shutdown_tx: broadcast::Sender<()>,
     mut shutdown_rx: broadcast::Receiver<()>,
+    drain: DrainSignal,
+    events: EventLog,
     started_at_unix: u64,
+    previous_started_at_unix: Option<u64>,
 ) -> Result<(), DaemonError> {
     let run = run_dir(&home);
     if !run.exists() {
@@ -415,103 +834,343 @@ async fn socket_server_task(
 
     let listener = UnixListener::bind(&socket).map_err(|e| io_err(&socket, e))?;
     set_socket_permissions(&socket)?;
+    // Only the daemon that holds the socket owns the pid and state files.
+    match state::write_pid_file_at(&home) {
+        Ok(Some(pid)) => tracing::warn!(pid, "previous daemon exited without shutting down"),
+        Ok(None) => {}
+        Err(err) => tracing::warn!(error = %err, "failed to write daemon pid file"),
+    }
+    save_sync_states(&home, &sync_states, started_at_unix).await;
+
+    let ctx = DispatchContext::daemon(
+        home.clone(),
+        DaemonState {
+            cache,
+            sync_states: sync_states.clone(),
+            metrics,
+            sync_tx,
+            shutdown_tx,
+            started_at_unix,
+            previous_started_at_unix,
+            status_report: StatusReportCache::default(),
+            last_staleness_scan,
+            last_scheduled_resync,
+            unwatched_dirs,
+            drain,
+        },
+    );
+
+    let limiter = std::sync::Arc::new(RateLimiter::default());
+    accept_socket_clients(&listener, &socket, ctx, limiter, events, &mut shutdown_rx).await?;
+
+    save_sync_states(&home, &sync_states, started_at_unix).await;
+    if socket.exists() {
+        let _ = fs::remove_file(&socket);
+    }
+    if let Err(err) = state::remove_pid_file_at(&home) {
+        tracing::warn!(error = %err, "failed to remove daemon pid file");
+    }
+    Ok(())
+}
+
+/// Sync states seeded from the state a previous daemon saved, marked
+/// `restored`, and when that daemon had started. Empty without a usable
+/// state file.
+fn restored_sync_states(home: &Path) -> (SyncStates, Option<u64>) {
+    let Some(saved) = state::load_at(home) else {
+        return (SyncStates::new(), None);
+    };
+    let states = saved
+        .last_success_unix
+        .into_iter()
+        .map(|(name, last_success_unix)| {
+            let state = SyncState {
+                last_success_unix,
+                restored: true,
+                ..SyncState::default()
+            };
+            (name, state)
+        })
+        .collect();
+    (states, Some(saved.started_at_unix))
+}
+
+/// Save every codebase's last successful sync for the next daemon.
+async fn save_sync_states(
+    home: &Path,
+    sync_states: &std::sync::Arc<RwLock<SyncStates>>,
+    started_at_unix: u64,
+) {
+    let last_success_unix = sync_states
+        .read()
+        .await
+        .iter()
+        .filter(|(_, state)| state.last_success_unix > 0)
+        .map(|(name, state)| (name.clone(), state.last_success_unix))
+        .collect();
+    let saved = state::PersistedState {
+        started_at_unix,
+        last_success_unix,
+    };
+    if let Err(err) = state::save_at(home, &saved) {
+        tracing::warn!(error = %err, "failed to save daemon state");
+    }
+}
 
+/// Serve clients until shutdown, at most [`MAX_SOCKET_CLIENTS`] at a time.
+/// All clients share `limiter`, and their requests are audited to `events`.
+///
+/// Clients keep being served while the sync queue drains, so a `sync` sent
+/// after `stop` is refused with a shutdown error rather than left hanging.
+/// Clients still mid-request once the queue is drained get to finish,
+/// bounded by [`SHUTDOWN_TIMEOUT`].
+async fn accept_socket_clients(
+    listener: &UnixListener,
+    socket: &Path,
+    ctx: DispatchContext,
+    limiter: std::sync::Arc<RateLimiter>,
+    events: EventLog,
+    shutdown_rx: &mut broadcast::Receiver<()>,
+) -> Result<(), DaemonError> {
+    let permits = std::sync::Arc::new(Semaphore::new(MAX_SOCKET_CLIENTS));
+    let mut clients = JoinSet::new();
+    let mut drain_deadline = None;
     loop {
         tokio::select! {
-            _ = shutdown_rx.recv() => break,
+            _ = shutdown_rx.recv(), if drain_deadline.is_none() => {
+                if ctx.daemon.is_none() {
+                    break;
+                }
+                drain_deadline = Some(Instant::now() + SHUTDOWN_TIMEOUT);
+            }
+            _ = sync_queue_drained(&ctx), if drain_deadline.is_some() => break,
+            _ = sleep_until_deadline(drain_deadline) => {
+                tracing::warn!("sync queue did not drain in time; closing socket clients");
+                break;
+            }
+            Some(_) = clients.join_next(), if !clients.is_empty() => {}
             accepted = listener.accept() => {
-                let (stream, _) = accepted.map_err(|e| io_err(&socket, e))?;
-                let home = home.clone();
-                let cache = cache.clone();
-                let timestamps = timestamps.clone();
-                let sync_tx = sync_tx.clone();
-                let shutdown_tx = shutdown_tx.clone();
-                tokio::spawn(async move {
-                    if let Err(err) = handle_socket_client(
-                        stream,
-                        home,
-                        cache,
-                        timestamps,
-                        sync_tx,
-                        shutdown_tx,
-                        started_at_unix,
-                    ).await {
+                let (stream, _) = accepted.map_err(|e| io_err(socket, e))?;
+                let Ok(permit) = permits.clone().try_acquire_owned() else {
+                    tracing::warn!(limit = MAX_SOCKET_CLIENTS, "rejecting socket client: too many connections");
+                    tokio::spawn(reject_busy_client(stream));
+                    continue;
+                };
+                let ctx = ctx.clone();
+                let limiter = limiter.clone();
+                let events = events.clone();
+                clients.spawn(async move {
+                    if let Err(err) = handle_socket_client(stream, ctx, &limiter, &events).await {
                         tracing::error!(error = %err, "socket client error");
                     }
+                    drop(permit);
                 });
             }
         }
     }
 
-    if socket.exists() {
-        let _ = fs::remove_file(&socket);
+    let finished = async { while clients.join_next().await.is_some() {} };
+    if tokio::time::timeout(SHUTDOWN_TIMEOUT, finished).await.is_err() {
+        tracing::warn!("socket clients did not finish in time; disconnecting them");
+        clients.abort_all();
     }
     Ok(())
 }
 
+/// Resolves once the sync processor has published its [`DrainReport`].
+/// Never resolves for contexts without a running daemon.
+async fn sync_queue_drained(ctx: &DispatchContext) {
+    match &ctx.daemon {
+        Some(state) => {
+            let mut drain = state.drain.subscribe();
+            let _ = drain.wait_for(Option::is_some).await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Sleep until `deadline`, or forever when there is none.
+async fn sleep_until_deadline(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn reject_busy_client(stream: UnixStream) {
+    let (_, mut writer) = stream.into_split();
+    let response = DaemonResponse::error(format!(
+        "busy: the daemon is already serving {MAX_SOCKET_CLIENTS} clients; try again shortly"
+    ));
+    if let Err(err) = write_response(&mut writer, &response).await {
+        tracing::debug!(error = %err, "failed to send busy response");
+    }
+}
+
+/// One framed read from a socket client.
+enum RequestLine {
+    /// A request line, without its newline.
+    Complete(String),
+    /// More than [`MAX_REQUEST_BYTES`] arrived without a newline.
+    TooLong,
+    /// The client closed the connection.
+    Closed,
+}
+
+/// Read up to the next newline, buffering at most [`MAX_REQUEST_BYTES`].
+async fn read_request_line<R>(reader: &mut R) -> Result<RequestLine, DaemonError>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let limit = MAX_REQUEST_BYTES as u64 + 1;
+    let mut buf = Vec::new();
+    (&mut *reader)
+        .take(limit)
+        .read_until(b'\n', &mut buf)
+        .await
+        .map_err(|e| io_err("daemon socket read", e))?;
+
+    if buf.last() == Some(&b'\n') {
+        buf.pop();
+    } else if buf.len() as u64 == limit {
+        return Ok(RequestLine::TooLong);
+    } else if buf.is_empty() {
+        return Ok(RequestLine::Closed);
+    }
+    let line = String::from_utf8(buf).map_err(|e| {
+        io_err(
+            "daemon socket read",
+            std::io::Error::new(ErrorKind::InvalidData, e),
+        )
+    })?;
+    Ok(RequestLine::Complete(line))
+}
+
+/// The client on the other end of a socket connection, as the kernel
+/// reports it (`SO_PEERCRED` on Linux, `LOCAL_PEERCRED` on macOS and the
+/// BSDs). Either part is `None` where the platform does not provide it.
+#[derive(Debug, Clone, Copy, Default)]
+struct PeerCred {
+    uid: Option<u32>,
+    pid: Option<i32>,
+}
+
+impl PeerCred {
+    fn of(stream: &UnixStream) -> Self {
+        match stream.peer_cred() {
+            Ok(cred) => Self {
+                uid: Some(cred.uid()),
+                pid: cred.pid(),
+            },
+            Err(err) => {
+                tracing::debug!(error = %err, "socket peer credentials unavailable");
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Socket commands that change state, audited whether or not they are rate
+/// limited.
+const MUTATING_COMMANDS: &[&str] = &["sync", "stop"];
+
+/// Record a request that passed the rate limit, to the trace and the event
+/// log. Read-only, unlimited requests such as `status` arrive on every poll,
+/// so they are only traced at debug level.
+fn audit_request(
+    events: &EventLog,
+    limiter: &RateLimiter,
+    request: &DaemonRequest,
+    peer: PeerCred,
+) {
+    let cmd = request.cmd.as_str();
+    if !MUTATING_COMMANDS.contains(&cmd) && limiter.limit(cmd).is_none() {
+        tracing::debug!(
+            cmd,
+            codebase = request.codebase.as_deref().unwrap_or("-"),
+            uid = ?peer.uid,
+            pid = ?peer.pid,
+            "socket request"
+        );
+        return;
+    }
+    tracing::info!(
+        cmd = %request.cmd,
+        codebase = request.codebase.as_deref().unwrap_or("-"),
+        uid = ?peer.uid,
+        pid = ?peer.pid,
+        "socket request"
+    );
+    events.emit(
+        EventKind::SocketRequest,
+        request.codebase.iter().cloned().collect(),
+        json!({ "cmd": request.cmd, "uid": peer.uid, "pid": peer.pid }),
+    );
+}
+
 async fn handle_socket_client(
     stream: UnixStream,
-    home: PathBuf,
-    cache: std::sync::Arc<RwLock<RegistryCache>>,
-    timestamps: std::sync::Arc<RwLock<SyncTimestamps>>,
-    sync_tx: mpsc::Sender<SyncJob>,
-    shutdown_tx: broadcast::Sender<()>,
-    started_at_unix: u64,
+    ctx: DispatchContext,
+    limiter: &RateLimiter,
+    events: &EventLog,
 ) -> Result<(), DaemonError> {
+    let peer = PeerCred::of(&stream);
     let (reader, mut writer) = stream.into_split();
-    let mut lines = BufReader::new(reader).lines();
+    let mut reader = BufReader::new(reader);
 
-    while let Some(line) = lines
-        .next_line()
-        .await
-        .map_err(|e| io_err("daemon socket read", e))?
-    {
+    loop {
+        let read = tokio::select! {
+            read = tokio::time::timeout(CLIENT_IDLE_TIMEOUT, read_request_line(&mut reader)) => read,
+            // Nothing is left to answer a sync, so idle clients are let go.
+            _ = sync_queue_drained(&ctx) => break,
+        };
+        let Ok(read) = read else {
+            tracing::debug!("closing idle socket client");
+            break;
+        };
+        let line = match read? {
+            RequestLine::Complete(line) => line,
+            RequestLine::TooLong => {
+                let response = DaemonResponse::error(format!(
+                    "invalid request: line too long (limit {MAX_REQUEST_BYTES} bytes)"
+                ));
+                write_response(&mut writer, &response).await?;
+                break;
+            }
+            RequestLine::Closed => break,
+        };
         if line.trim().is_empty() {
             continue;
         }
 
-        let request: Result<DaemonRequest, _> = serde_json::from_str(&line);
-        let request = match request {
+        let request = match decode_request(&line) {
             Ok(request) => request,
-            Err(err) => {
-                write_response(
-                    &mut writer,
-                    &DaemonResponse::error(format!("invalid request JSON: {err}")),
-                )
-                .await?;
+            Err(response) => {
+                write_response(&mut writer, &response).await?;
                 continue;
             }
         };
 
-        let cmd = request.cmd.clone();
-        let codebase = request.codebase.clone();
-
-        let response = match cmd.as_str() {
-            "status" => {
-                let payload =
-                    build_status_payload(&home, cache.clone(), timestamps.clone(), started_at_unix)
-                        .await;
-                DaemonResponse::ok(payload)
-            }
-            "sync" => {
-                let target = match codebase {
-                    Some(codebase) => SyncTarget::Codebase(codebase),
-                    None => SyncTarget::All,
-                };
-                match enqueue_sync(&sync_tx, target, "socket").await {
-                    Ok(summary) => DaemonResponse::ok(json!(summary)),
-                    Err(err) => DaemonResponse::error(err.to_string()),
-                }
-            }
-            "stop" => {
-                let _ = shutdown_tx.send(());
-                DaemonResponse::ok(json!({ "stopping": true }))
-            }
-            other => DaemonResponse::error(format!("unknown command '{other}'")),
-        };
+        if let RateDecision::RetryAfter(wait) = limiter.check(&request.cmd) {
+            tracing::warn!(
+                cmd = %request.cmd,
+                uid = ?peer.uid,
+                pid = ?peer.pid,
+                retry_after_ms = wait.as_millis() as u64,
+                "rate limiting socket request"
+            );
+            write_response(
+                &mut writer,
+                &DaemonResponse::rate_limited(&request.cmd, wait),
+            )
+            .await?;
+            continue;
+        }
+        audit_request(events, limiter, &request, peer);
 
+        let response = dispatch(&request, &ctx).await;
         write_response(&mut writer, &response).await?;
-        if cmd == "stop" {
+        if request.cmd == "stop" && response.ok {
             break;
         }
     }
@@ -519,11 +1178,17 @@ async fn handle_socket_client(
     Ok(())
 }
 
-async fn build_status_payload(
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn build_status_payload(
     home: &Path,
     cache: std::sync::Arc<RwLock<RegistryCache>>,
-    timestamps: std::sync::Arc<RwLock<SyncTimestamps>>,
+    sync_states: std::sync::Arc<RwLock<SyncStates>>,
+    metrics: &Metrics,
+    last_staleness_scan: &LastStalenessScan,
+    last_scheduled_resync: &LastScheduledResync,
+    unwatched_dirs: &UnwatchedDirs,
     started_at_unix: u64,
+    previous_started_at_unix: Option<u64>,
 ) -> Value {
     // Collect codebase names from registry cache (read lock, dropped immediately).
     let names: Vec<String> = {
@@ -533,44 +1198,104 @@ async fn build_status_payload(
         v
     };
 
-    // Snapshot timestamps (read lock, dropped before JSON assembly).
-    let ts_snapshot: HashMap<String, u64> = {
-        let ts = timestamps.read().await;
-        ts.clone()
+    // Snapshot sync states (read lock, dropped before JSON assembly).
+    let states: SyncStates = {
+        let states = sync_states.read().await;
+        states.clone()
     };
 
-    // Build per-codebase objects with last sync time and task count.
+    // Build per-codebase objects with last sync time and last outcome.
     let codebases: Vec<Value> = names
         .iter()
         .map(|name| {
-            let last_sync = ts_snapshot.get(name).copied().unwrap_or(0);
+            let state = states.get(name).cloned().unwrap_or_default();
             json!({
                 "name": name,
-                "last_sync_at_unix": last_sync,
+                "last_sync_at_unix": state.last_success_unix,
+                "last_error": state.last_error,
+                "consecutive_failures": state.consecutive_failures,
+                "restored": state.restored,
             })
         })
         .collect();
 
     // Daemon-wide last sync = max of per-codebase timestamps (0 if none yet).
-    let last_sync_at_unix = ts_snapshot.values().copied().max().unwrap_or(0);
+    let last_sync_at_unix = states
+        .values()
+        .map(|state| state.last_success_unix)
+        .max()
+        .unwrap_or(0);
+
+    let unwatched_dirs = unwatched_dirs.load(Ordering::Relaxed);
 
     json!({
         "running": true,
         "label": crate::paths::DAEMON_LABEL,
+        "version": env!("CARGO_PKG_VERSION"),
+        "protocol_version": PROTOCOL_VERSION,
+        "pid": std::process::id(),
         "started_at_unix": started_at_unix,
+        "uptime_seconds": unix_seconds_now().saturating_sub(started_at_unix),
+        "previous_started_at_unix": previous_started_at_unix,
         "last_sync_at_unix": last_sync_at_unix,
         "codebases": codebases,
+        "metrics": metrics.snapshot(),
+        "last_staleness_scan": last_staleness_scan.lock().await.clone(),
+        "last_scheduled_resync": last_scheduled_resync.lock().await.clone(),
+        "watcher_degraded": unwatched_dirs > 0,
+        "watcher_unwatched_dirs": unwatched_dirs,
         "socket": socket_path(home).display().to_string(),
         "projects_root": projects_root(home).display().to_string(),
     })
 }
 
-async fn enqueue_sync(
+/// The `orchestra status --json` report for every cached codebase.
+///
+/// The checks run on a blocking task against the cached registry entries, so
+/// no registry file is re-read. A report younger than [`STATUS_REPORT_TTL`]
+/// is served as is, and concurrent requests wait for one shared scan.
+pub(crate) async fn build_status_report(
+    home: &Path,
+    cache: std::sync::Arc<RwLock<RegistryCache>>,
+    reports: &StatusReportCache,
+) -> Result<Value, DaemonError> {
+    let mut last = reports.lock().await;
+    if let Some((computed_at, report)) = last.as_ref() {
+        if computed_at.elapsed() < STATUS_REPORT_TTL {
+            return Ok(report.clone());
+        }
+    }
+
+    let codebases: Vec<Codebase> = cache.read().await.values().cloned().collect();
+    let home = home.to_path_buf();
+    let report = tokio::task::spawn_blocking(move || -> Result<Value, DaemonError> {
+        let mut listed = Vec::with_capacity(codebases.len());
+        for codebase in codebases {
+            if let Some(project) = find_codebase_project(&home, &codebase.name)? {
+                listed.push((project, codebase));
+            }
+        }
+        // Same order as `registry::list_codebases_at`: by project, then file.
+        listed.sort_by_cached_key(|(project, codebase)| {
+            registry::codebase_path_at(&home, project, &codebase.name)
+        });
+        let report = status::build_report(&home, &listed, false, false)?;
+        Ok(serde_json::to_value(report)?)
+    })
+    .await
+    .map_err(|err| DaemonError::Protocol(format!("status report join error: {err}")))??;
+
+    *last = Some((Instant::now(), report.clone()));
+    Ok(report)
+}
+
+pub(crate) async fn enqueue_sync(
     sync_tx: &mpsc::Sender<SyncJob>,
     target: SyncTarget,
     source: &'static str,
 ) -> Result<SyncSummary, DaemonError> {
     let (tx, rx) = oneshot::channel();
+    // The processor closes the queue once it starts draining for shutdown.
     sync_tx
         .send(SyncJob {
             target,
@@ -578,26 +1303,141 @@ async fn enqueue_sync(
             respond_to: tx,
         })
         .await
-        .map_err(|_| DaemonError::ChannelClosed("sync queue"))?;
+        .map_err(|_| DaemonError::ShuttingDown)?;
 
     let outcome = rx
         .await
         .map_err(|_| DaemonError::ChannelClosed("sync response"))?;
-    outcome.map_err(DaemonError::Protocol)
+    outcome.map_err(|err| match err {
+        SyncJobError::Failed(message) => DaemonError::Protocol(message),
+        SyncJobError::ShuttingDown => DaemonError::ShuttingDown,
+    })
 }
 
-async fn refresh_cache(
-    home: PathBuf,
-    cache: std::sync::Arc<RwLock<RegistryCache>>,
+/// Re-read the registry files of `names` and update their cache entries.
+///
+/// Codebases whose YAML is gone are dropped from the cache along with their
+/// sync state. No other registry file is read, and the cache lock is only
+/// taken once the files are parsed.
+async fn reload_codebases(
+    home: &Path,
+    cache: &std::sync::Arc<RwLock<RegistryCache>>,
+    sync_states: &std::sync::Arc<RwLock<SyncStates>>,
+    names: Vec<CodebaseName>,
 ) -> Result<(), DaemonError> {
-    let refreshed = tokio::task::spawn_blocking(move || load_registry_cache(&home))
+    let home = home.to_path_buf();
+    let loaded = tokio::task::spawn_blocking(move || load_codebases(&home, &names))
         .await
         .map_err(|err| DaemonError::Protocol(format!("cache refresh join error: {err}")))??;
-    let mut guard = cache.write().await;
-    *guard = refreshed;
+
+    let mut cache = cache.write().await;
+    let mut states = sync_states.write().await;
+    for (name, codebase) in loaded {
+        match codebase {
+            Some(codebase) => {
+                cache.insert(name, codebase);
+            }
+            None => {
+                if cache.remove(&name).is_some() {
+                    tracing::info!(codebase = %name.0, "codebase removed from registry");
+                }
+                states.remove(&name.0);
+            }
+        }
+    }
     Ok(())
 }
 
+/// Drop cached codebases whose registry file was deleted. Only checks which
+/// files still exist; nothing is re-parsed.
+async fn handle_registry_removal(
+    home: &Path,
+    cache: &std::sync::Arc<RwLock<RegistryCache>>,
+    sync_states: &std::sync::Arc<RwLock<SyncStates>>,
+) {
+    let cached: Vec<CodebaseName> = cache.read().await.keys().cloned().collect();
+    let lookup_home = home.to_path_buf();
+    let missing = tokio::task::spawn_blocking(move || {
+        cached
+            .into_iter()
+            .filter(|name| !matches!(find_codebase_project(&lookup_home, name), Ok(Some(_))))
+            .collect::<Vec<_>>()
+    })
+    .await;
+    let missing = match missing {
+        Ok(missing) => missing,
+        Err(err) => {
+            tracing::warn!(error = %err, "registry removal check join error");
+            return;
+        }
+    };
+    if missing.is_empty() {
+        return;
+    }
+    // The files are gone, so reloading them only removes the entries.
+    if let Err(err) = reload_codebases(home, cache, sync_states, missing).await {
+        tracing::warn!(error = %err, "cache update after registry removal failed");
+    }
+}
+
+/// Rebuild the cache from the whole registry, catching any change an
+/// incremental reload missed.
+async fn reconcile_cache(
+    home: &Path,
+    cache: &std::sync::Arc<RwLock<RegistryCache>>,
+    sync_states: &std::sync::Arc<RwLock<SyncStates>>,
+) {
+    let home = home.to_path_buf();
+    let (mut current, unreadable) =
+        match tokio::task::spawn_blocking(move || load_registry_cache_lossy(&home)).await {
+            Ok(Ok(loaded)) => loaded,
+            Ok(Err(err)) => {
+                tracing::warn!(error = %err, "registry cache reconciliation failed");
+                return;
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "registry cache reconciliation join error");
+                return;
+            }
+        };
+
+    let removed = {
+        let mut cache = cache.write().await;
+        // A file that stopped parsing (say, mid-edit) is not a removal; keep
+        // its last good entry until it reads again.
+        for name in unreadable {
+            if let Some(codebase) = cache.get(&name) {
+                current.entry(name).or_insert_with(|| codebase.clone());
+            }
+        }
+        let mut states = sync_states.write().await;
+        prune_removed_codebases(&mut cache, &mut states, current)
+    };
+    for name in removed {
+        tracing::info!(codebase = %name.0, "codebase removed from registry");
+    }
+}
+
+/// Replace `cache` with `current` and drop sync state for codebases that are
+/// no longer registered. Returns the removed names, sorted.
+fn prune_removed_codebases(
+    cache: &mut RegistryCache,
+    sync_states: &mut SyncStates,
+    current: RegistryCache,
+) -> Vec<CodebaseName> {
+    let mut removed: Vec<CodebaseName> = cache
+        .keys()
+        .filter(|name| !current.contains_key(*name))
+        .cloned()
+        .collect();
+    removed.sort_by(|a, b| a.0.cmp(&b.0));
+    for name in &removed {
+        sync_states.remove(&name.0);
+    }
+    *cache = current;
+    removed
+}
+
 async fn log_rotation_task(
     home: PathBuf,
     mut shutdown_rx: broadcast::Receiver<()>,
@@ -623,310 +1463,304 @@ async fn log_rotation_task(
     Ok(())
 }
 
-async fn run_staleness_scan(home: PathBuf) -> Result<(), DaemonError> {
-    tokio::task::spawn_blocking(move || run_staleness_scan_blocking(&home))
+async fn run_staleness_scan(
+    home: PathBuf,
+    summary: SyncSummary,
+) -> Result<StalenessScanSummary, DaemonError> {
+    tokio::task::spawn_blocking(move || run_staleness_scan_blocking(&home, &summary))
         .await
         .map_err(|err| DaemonError::Protocol(format!("staleness scan join error: {err}")))?
 }
 
-fn run_staleness_scan_blocking(home: &Path) -> Result<(), DaemonError> {
-    let codebases = registry::list_codebases_at(home)?;
-    for (project, codebase) in codebases {
-        let signal = staleness::check(home, &project, &codebase)?;
-        tracing::info!(
-            codebase = %codebase.name.0,
-            signal = ?signal,
-            "staleness signal after watcher sync",
-        );
-    }
-    Ok(())
-}
-
-fn build_sync_summary(
-    target: SyncTarget,
-    source: &'static str,
-    results: Vec<SyncCodebaseResult>,
-    duration: Duration,
-) -> SyncSummary {
-    let mut codebases = Vec::new();
-    let mut written = 0usize;
-    let mut unchanged = 0usize;
-
-    for result in results {
-        codebases.push(result.codebase_name);
-        for write in result.writes {
-            match write {
-                WriteResult::Written { .. } | WriteResult::WouldWrite { .. } => written += 1,
-                WriteResult::Unchanged { .. } => unchanged += 1,
+/// Queue a sync for each cached codebase that has never been synced or is
+/// stale, then log how each one went.
+async fn startup_sync_task(
+    home: PathBuf,
+    cache: std::sync::Arc<RwLock<RegistryCache>>,
+    sync_tx: mpsc::Sender<SyncJob>,
+) {
+    let codebases: Vec<Codebase> = cache.read().await.values().cloned().collect();
+    let targets =
+        match tokio::task::spawn_blocking(move || startup_sync_targets(&home, codebases)).await {
+            Ok(targets) => targets,
+            Err(err) => {
+                tracing::warn!(error = %err, "startup staleness check failed");
+                return;
             }
-        }
-    }
-
-    SyncSummary {
-        target: target.label(),
-        source: source.to_string(),
-        codebases,
-        written,
-        unchanged,
-        duration_ms: duration.as_millis(),
+        };
+    if targets.is_empty() {
+        return;
     }
-}
-
-fn load_registry_cache(home: &Path) -> Result<RegistryCache, DaemonError> {
-    let mut cache = HashMap::new();
-    for (_project, codebase) in registry::list_codebases_at(home)? {
-        cache.insert(codebase.name.clone(), codebase);
+    tracing::info!(count = targets.len(), "queueing startup sync for stale codebases");
+
+    let mut pending = Vec::with_capacity(targets.len());
+    for name in targets {
+        let (respond_to, response) = oneshot::channel();
+        let job = SyncJob {
+            target: SyncTarget::Codebase(name.0.clone()),
+            source: STARTUP_SYNC_SOURCE,
+            respond_to,
+        };
+        if sync_tx.send(job).await.is_err() {
+            return; // the processor has shut down
+        }
+        pending.push((name, response));
     }
-    Ok(cache)
-}
-
-#[cfg(test)]
-fn reload_codebase(
-    home: &Path,
-    cache: &mut RegistryCache,
-    codebase_name: &str,
-) -> Result<(), DaemonError> {
-    let target = CodebaseName::from(codebase_name);
-    for (_project, codebase) in registry::list_codebases_at(home)? {
-        if codebase.name == target {
-            cache.insert(codebase.name.clone(), codebase);
-            return Ok(());
+    for (name, response) in pending {
+        match response.await {
+            Ok(Ok(_)) => tracing::info!(codebase = %name.0, "startup sync completed"),
+            Ok(Err(SyncJobError::ShuttingDown)) | Err(_) => return,
+            Ok(Err(error)) => tracing::warn!(codebase = %name.0, %error, "startup sync failed"),
         }
     }
-    cache.remove(&target);
-    Ok(())
 }
 
-fn register_projects_tree(
-    watcher: &mut RecommendedWatcher,
-    watched_dirs: &mut HashSet<PathBuf>,
-    root: &Path,
-) -> Result<(), DaemonError> {
-    if !root.exists() {
-        fs::create_dir_all(root).map_err(|e| io_err(root, e))?;
-    }
-    for dir in collect_dirs(root)? {
-        let canonical = match fs::canonicalize(&dir) {
-            Ok(path) => path,
-            Err(err) if err.kind() == ErrorKind::NotFound => continue,
-            Err(err) => return Err(io_err(&dir, err)),
+/// Queue a full re-sync right away when `upgraded_home` is set (the binary
+/// changed), then every `interval`, recording each outcome in `last`. Only
+/// files whose rendered content differs are written. The new version is
+/// stored under `upgraded_home` once its re-sync succeeds.
+async fn resync_schedule_task(
+    sync_tx: mpsc::Sender<SyncJob>,
+    interval: Option<Duration>,
+    upgraded_home: Option<PathBuf>,
+    last: LastScheduledResync,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
+    if let Some(home) = upgraded_home {
+        tracing::info!("orchestra version changed; queueing a full re-sync");
+        let Some(resync) = scheduled_resync(&sync_tx, UPGRADE_SYNC_SOURCE).await else {
+            return;
         };
-        if watched_dirs.insert(canonical.clone()) {
-            watcher.watch(&canonical, RecursiveMode::NonRecursive)?;
-            tracing::debug!(path = %canonical.display(), "watching registry directory");
+        if resync.error.is_none() {
+            if let Err(err) = record_daemon_version(&home, env!("CARGO_PKG_VERSION")) {
+                tracing::warn!(error = %err, "failed to record daemon version");
+            }
         }
+        *last.lock().await = Some(resync);
     }
-    Ok(())
-}
+    let Some(interval) = interval else {
+        return;
+    };
 
-fn collect_dirs(root: &Path) -> Result<Vec<PathBuf>, DaemonError> {
-    let mut dirs = vec![root.to_path_buf()];
-    let mut cursor = 0;
-    while cursor < dirs.len() {
-        let current = dirs[cursor].clone();
-        cursor += 1;
-        let entries = match fs::read_dir(&current) {
-            Ok(entries) => entries,
-            Err(err) => {
-                if err.kind() == std::io::ErrorKind::NotFound {
-                    continue;
-                }
-                return Err(io_err(&current, err));
-            }
-        };
-        for entry in entries {
-            let entry = entry.map_err(|e| io_err(&current, e))?;
-            let ty = entry.file_type().map_err(|e| io_err(entry.path(), e))?;
-            if ty.is_dir() {
-                dirs.push(entry.path());
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    ticker.tick().await; // the first tick completes immediately
+    loop {
+        tokio::select! {
+            _ = shutdown_rx.recv() => break,
+            _ = ticker.tick() => {
+                let Some(resync) = scheduled_resync(&sync_tx, SCHEDULE_SYNC_SOURCE).await else {
+                    break;
+                };
+                *last.lock().await = Some(resync);
             }
         }
     }
-    dirs.sort();
-    dirs.dedup();
-    Ok(dirs)
 }
 
-/// Watch all parent directories of managed agent files for all codebases.
-///
-/// Uses `managed_agent_paths` from `orchestra-sync` to enumerate every output
-/// path (CLAUDE.md, AGENTS.md, .cursor/rules/orchestra.mdc, etc.) across all
-/// registered codebases, then watches each unique parent directory.
-fn register_managed_agent_dirs(
-    watcher: &mut RecommendedWatcher,
-    watched_dirs: &mut HashSet<PathBuf>,
-    home: &Path,
-) -> Result<(), DaemonError> {
-    let all = match registry::list_codebases_at(home) {
-        Ok(codebases) => codebases,
-        Err(err) => {
-            tracing::warn!(error = %err, "could not list codebases for agent file watcher");
-            return Ok(());
-        }
+/// Run one `SyncTarget::All` job from `source` and describe how it went.
+/// `None` once the sync processor has shut down.
+async fn scheduled_resync(
+    sync_tx: &mpsc::Sender<SyncJob>,
+    source: &'static str,
+) -> Option<ScheduledResync> {
+    let started_at_unix = unix_seconds_now();
+    let outcome = enqueue_sync(sync_tx, SyncTarget::All, source).await;
+    let mut resync = ScheduledResync {
+        source: source.to_string(),
+        started_at_unix,
+        finished_at_unix: unix_seconds_now(),
+        written: 0,
+        unchanged: 0,
+        error: None,
     };
-
-    let paths = managed_agent_paths(&all);
-    log_hash_path_alignment(home, &all, &paths);
-    let mut dirs_to_watch: Vec<PathBuf> = paths
-        .iter()
-        .filter_map(|p| p.parent().map(Path::to_path_buf))
-        .collect();
-    dirs_to_watch.sort();
-    dirs_to_watch.dedup();
-
-    for dir in dirs_to_watch {
-        if !dir.exists() {
-            // Directory may not exist yet if the codebase hasn't been synced.
-            continue;
+    match outcome {
+        Ok(summary) => {
+            tracing::info!(
+                source,
+                written = summary.written,
+                unchanged = summary.unchanged,
+                "scheduled re-sync completed",
+            );
+            resync.written = summary.written;
+            resync.unchanged = summary.unchanged;
         }
-        let canonical = match fs::canonicalize(&dir) {
-            Ok(path) => path,
-            Err(err) if err.kind() == ErrorKind::NotFound => continue,
-            Err(err) => return Err(io_err(&dir, err)),
-        };
-        if watched_dirs.insert(canonical.clone()) {
-            watcher
-                .watch(&canonical, RecursiveMode::NonRecursive)
-                .map_err(DaemonError::Notify)?;
-            tracing::debug!(path = %canonical.display(), "watching agent file directory");
+        Err(DaemonError::ChannelClosed(_) | DaemonError::ShuttingDown) => return None,
+        Err(err) => {
+            tracing::warn!(source, error = %err, "scheduled re-sync failed");
+            resync.error = Some(err.to_string());
         }
     }
-    Ok(())
-}
-
-fn managed_paths_for_target(home: &Path, target: &SyncTarget) -> Result<Vec<PathBuf>, DaemonError> {
-    let all = registry::list_codebases_at(home)?;
-    let selected: Vec<(orchestra_core::types::ProjectName, orchestra_core::types::Codebase)> =
-        match target {
-            SyncTarget::All => all,
-            SyncTarget::Codebase(name) => all
-                .into_iter()
-                .filter(|(_, codebase)| codebase.name.0 == *name)
-                .collect(),
-        };
-    Ok(managed_agent_paths(&selected))
+    Some(resync)
 }
 
-async fn mark_own_writes(
-    own_writes: &std::sync::Arc<RwLock<OwnWrites>>,
-    paths: Vec<PathBuf>,
-    now: Instant,
-) {
-    let mut guard = own_writes.write().await;
-    guard.retain(|_, seen_at| now.duration_since(*seen_at) <= OWN_WRITE_SUPPRESS_WINDOW);
-    for path in paths {
-        let canonical = fs::canonicalize(&path).unwrap_or(path);
-        guard.insert(canonical, now);
-    }
+/// Version stored in [`daemon_meta_path`] by an earlier daemon. A missing or
+/// unreadable file gives `None`, which counts as no change: codebases that
+/// were never synced get the startup sync instead.
+fn stored_daemon_version(home: &Path) -> Option<String> {
+    fs::read_to_string(daemon_meta_path(home))
+        .ok()
+        .and_then(|text| serde_json::from_str::<DaemonMeta>(&text).ok())
+        .map(|meta| meta.version)
 }
 
-async fn is_recent_own_write(
-    own_writes: &std::sync::Arc<RwLock<OwnWrites>>,
-    path: &Path,
-    now: Instant,
-) -> bool {
-    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
-    let mut guard = own_writes.write().await;
-    guard.retain(|_, seen_at| now.duration_since(*seen_at) <= OWN_WRITE_SUPPRESS_WINDOW);
-    guard.contains_key(&canonical)
+/// Store `version` in [`daemon_meta_path`].
+fn record_daemon_version(home: &Path, version: &str) -> Result<(), DaemonError> {
+    let path = daemon_meta_path(home);
+    let meta = DaemonMeta {
+        version: version.to_string(),
+    };
+    fs::write(&path, serde_json::to_string_pretty(&meta)?).map_err(|e| io_err(&path, e))
 }
 
-fn log_hash_path_alignment(
-    home: &Path,
-    all: &[(orchestra_core::types::ProjectName, orchestra_core::types::Codebase)],
-    managed_paths: &[PathBuf],
-) {
-    tracing::info!(
-        managed_paths = managed_paths.len(),
-        "registered managed agent output paths for watcher"
-    );
-
-    for managed_path in managed_paths {
-        tracing::trace!(path = %managed_path.display(), "managed agent output path");
-    }
-
-    for (_project, codebase) in all {
-        let store = match hash_store::load_at(home, &codebase.name.0) {
-            Ok(store) => store,
+/// The codebases among `codebases` whose staleness check reports
+/// `NeverSynced` or `Stale`, sorted by name.
+fn startup_sync_targets(home: &Path, codebases: Vec<Codebase>) -> Vec<CodebaseName> {
+    let mut targets = Vec::new();
+    for codebase in codebases {
+        let project = match find_codebase_project(home, &codebase.name) {
+            Ok(Some(project)) => project,
+            Ok(None) => continue,
             Err(err) => {
-                tracing::debug!(
-                    codebase = %codebase.name.0,
-                    error = %err,
-                    "no readable hash store yet for codebase"
-                );
+                tracing::warn!(codebase = %codebase.name.0, error = %err, "startup staleness check failed");
                 continue;
             }
         };
+        match staleness::check(home, &project, &codebase, false) {
+            Ok(StalenessSignal::NeverSynced | StalenessSignal::Stale { .. }) => {
+                targets.push(codebase.name);
+            }
+            Ok(_) => {}
+            Err(err) => {
+                tracing::warn!(codebase = %codebase.name.0, error = %err, "startup staleness check failed");
+            }
+        }
+    }
+    targets.sort_by(|a, b| a.0.cmp(&b.0));
+    targets
+}
 
-        for hash_path in store.files.keys() {
-            let matches_managed = managed_paths.iter().any(|managed| {
-                let raw = managed.to_string_lossy();
-                let canonical = fs::canonicalize(managed).unwrap_or_else(|_| managed.clone());
-                let canonical = canonical.to_string_lossy();
-                hash_path.as_str() == raw.as_ref() || hash_path.as_str() == canonical.as_ref()
-            });
-
-            if !matches_managed {
+/// Check the codebases the sync in `summary` touched and log one aggregate
+/// line. Per-codebase lines are only logged at info when something is off.
+fn run_staleness_scan_blocking(
+    home: &Path,
+    summary: &SyncSummary,
+) -> Result<StalenessScanSummary, DaemonError> {
+    let registered = registry::list_codebases_lossy_at(home)?.codebases;
+    let codebases = scan_targets(registered, &summary.codebases);
+    let mut scan = StalenessScanSummary {
+        target: summary.target.clone(),
+        scanned_at_unix: unix_seconds_now(),
+        ..StalenessScanSummary::default()
+    };
+    for (project, codebase) in codebases {
+        match staleness::check(home, &project, &codebase, false) {
+            Ok(signal) => {
+                if signal == StalenessSignal::Current {
+                    tracing::debug!(
+                        codebase = %codebase.name.0,
+                        "staleness signal after watcher sync: current",
+                    );
+                } else {
+                    tracing::info!(
+                        codebase = %codebase.name.0,
+                        signal = ?signal,
+                        "staleness signal after watcher sync",
+                    );
+                }
+                scan.record(&signal);
+            }
+            Err(err) => {
                 tracing::warn!(
                     codebase = %codebase.name.0,
-                    hash_path = %hash_path,
-                    "hash store entry does not match any managed watcher path"
+                    error = %err,
+                    "staleness check after sync failed",
                 );
+                scan.failed += 1;
             }
         }
     }
+    tracing::info!(
+        "staleness_scan target={} current={} never_synced={} stale={} modified={} orphan={} failed={}",
+        scan.target,
+        scan.current,
+        scan.never_synced,
+        scan.stale,
+        scan.modified,
+        scan.orphan,
+        scan.failed,
+    );
+    Ok(scan)
 }
 
-/// Returns `true` if `path` is a managed agent file output (CLAUDE.md,
-/// AGENTS.md, etc.) for any registered codebase in `home`.
-///
-/// Uses a canonical path comparison so symlinks/private/var paths match.
-fn is_managed_agent_file(path: &Path, home: &Path) -> bool {
-    let all = match registry::list_codebases_at(home) {
-        Ok(codebases) => codebases,
-        Err(_) => return false,
-    };
-    let canonical_path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
-    let managed = managed_agent_paths(&all);
-    for managed_path in &managed {
-        let canonical_managed =
-            fs::canonicalize(managed_path).unwrap_or_else(|_| managed_path.clone());
-        if canonical_path == canonical_managed {
-            return true;
-        }
-    }
-    false
+/// The registered codebases among `synced`, the names a sync just wrote.
+fn scan_targets(
+    registered: Vec<(ProjectName, Codebase)>,
+    synced: &[String],
+) -> Vec<(ProjectName, Codebase)> {
+    registered
+        .into_iter()
+        .filter(|(_, codebase)| synced.contains(&codebase.name.0))
+        .collect()
 }
 
-fn is_relevant_event_kind(kind: &EventKind) -> bool {
-    matches!(kind, EventKind::Create(_) | EventKind::Modify(_))
+fn load_registry_cache(home: &Path) -> Result<RegistryCache, DaemonError> {
+    load_registry_cache_lossy(home).map(|(cache, _)| cache)
 }
 
-fn is_registry_yaml(path: &Path, projects: &Path) -> bool {
-    path.starts_with(projects)
-        && path
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext.eq_ignore_ascii_case("yaml"))
-            .unwrap_or(false)
+/// Every readable codebase, plus the names of the registry files that could
+/// not be read. Each of those is logged, and the daemon carries on without it.
+fn load_registry_cache_lossy(
+    home: &Path,
+) -> Result<(RegistryCache, Vec<CodebaseName>), DaemonError> {
+    let listing = registry::list_codebases_lossy_at(home)?;
+    let mut unreadable = Vec::new();
+    for failed in &listing.errors {
+        tracing::warn!(path = %failed.path.display(), error = %failed, "skipping unreadable registry file");
+        if let Some(stem) = failed.path.file_stem() {
+            unreadable.push(CodebaseName::from(stem.to_string_lossy().into_owned()));
+        }
+    }
+    let cache = listing
+        .codebases
+        .into_iter()
+        .map(|(_project, codebase)| (codebase.name.clone(), codebase))
+        .collect();
+    Ok((cache, unreadable))
 }
 
-fn sync_target_for_path(path: &Path) -> SyncTarget {
-    if path.file_name().and_then(|name| name.to_str()) == Some("project.yaml") {
-        return SyncTarget::All;
+/// Load the registry entries for `names`, pairing each with `None` if the
+/// codebase is no longer registered.
+fn load_codebases(
+    home: &Path,
+    names: &[CodebaseName],
+) -> Result<Vec<(CodebaseName, Option<Codebase>)>, DaemonError> {
+    let mut loaded = Vec::with_capacity(names.len());
+    for name in names {
+        let codebase = match find_codebase_project(home, name)? {
+            Some(project) => Some(registry::load_codebase_at(home, &project, name)?),
+            None => None,
+        };
+        loaded.push((name.clone(), codebase));
     }
-    path.file_stem()
-        .and_then(|stem| stem.to_str())
-        .map(|name| SyncTarget::Codebase(name.to_string()))
-        .unwrap_or(SyncTarget::All)
+    Ok(loaded)
 }
 
-fn directory_to_watch(path: &Path) -> Option<PathBuf> {
-    if path.is_dir() {
-        Some(path.to_path_buf())
-    } else {
-        path.parent().map(Path::to_path_buf)
+/// The project directory holding `<name>.yaml`, found without reading any
+/// registry file.
+fn find_codebase_project(
+    home: &Path,
+    name: &CodebaseName,
+) -> Result<Option<ProjectName>, DaemonError> {
+    if name.0 == "project" {
+        return Ok(None);
+    }
+    for project in registry::list_project_names_at(home)? {
+        if registry::codebase_path_at(home, &project, name).is_file() {
+            return Ok(Some(project));
+        }
     }
+    Ok(None)
 }
 
 fn prepare_socket_for_bind(socket: &Path) -> Result<(), DaemonError> {
@@ -957,30 +1791,6 @@ fn prepare_socket_for_bind(socket: &Path) -> Result<(), DaemonError> {
     }
 }
 
-fn should_process_event(
-    debounce: &mut HashMap<PathBuf, Instant>,
-    path: &Path,
-    now: Instant,
-) -> bool {
-    should_process_event_with_threshold(debounce, path, now, DEBOUNCE_WINDOW)
-}
-
-fn should_process_event_with_threshold(
-    debounce: &mut HashMap<PathBuf, Instant>,
-    path: &Path,
-    now: Instant,
-    threshold: Duration,
-) -> bool {
-    debounce.retain(|_, seen_at| now.duration_since(*seen_at) <= Duration::from_secs(30));
-    match debounce.get(path) {
-        Some(last_seen) if now.duration_since(*last_seen) < threshold => false,
-        _ => {
-            debounce.insert(path.to_path_buf(), now);
-            true
-        }
-    }
-}
-
 fn ensure_runtime_dirs(home: &Path) -> Result<(), DaemonError> {
     let projects = projects_root(home);
     if !projects.exists() {
@@ -1059,127 +1869,820 @@ mod tests {
     use super::*;
     use std::fs;
 
+    use crate::paths::daemon_state_path;
+    use crate::protocol::DaemonRequest;
+    use crate::watch::{bump_generation, retry_watcher_sync, RetryGenerations};
+    use orchestra_sync::{pipeline::SyncScope, SyncCodebaseResult, SyncError};
+
     use orchestra_core::types::{ProjectName, ProjectType};
     use serde_json::json;
     use tempfile::TempDir;
     use tokio::sync::{broadcast, mpsc, RwLock};
-    use tokio::time::advance;
-
-    #[tokio::test(start_paused = true, flavor = "current_thread")]
-    async fn debounce_coalesces_rapid_events() {
-        let threshold = Duration::from_millis(100);
-        let mut debounce = HashMap::<PathBuf, Instant>::new();
-        let path = PathBuf::from("/tmp/copenow_api.yaml");
-        let mut sync_triggers = 0usize;
-
-        for _ in 0..5 {
-            if should_process_event_with_threshold(&mut debounce, &path, Instant::now(), threshold)
-            {
-                sync_triggers += 1;
-            }
-            advance(Duration::from_millis(10)).await;
-        }
-
-        advance(Duration::from_millis(150)).await;
-        assert_eq!(
-            sync_triggers, 1,
-            "rapid saves should collapse to one sync trigger"
-        );
-    }
-
-    #[test]
-    fn registry_cache_reload_updates_changed_codebase() {
-        let home = TempDir::new().expect("home");
-        let workspace = TempDir::new().expect("workspace");
-        let project = ProjectName::from("copnow");
 
-        for name in ["core_api", "copnow_api", "worker_api"] {
+    fn init_codebases(home: &TempDir, workspace: &TempDir, names: &[&str]) {
+        for name in names {
             let path = workspace.path().join(name);
             fs::create_dir_all(&path).expect("create codebase dir");
             registry::init_at(
                 path,
-                project.clone(),
+                ProjectName::from("copnow"),
                 Some(ProjectType::Backend),
                 home.path(),
             )
             .expect("init codebase");
         }
+    }
 
-        let mut cache = load_registry_cache(home.path()).expect("load cache");
-        assert_eq!(
-            cache.len(),
-            3,
-            "cache should contain all registered codebases"
+    /// Replace a codebase's YAML with content that fails to parse, so any
+    /// later read of it errors.
+    fn corrupt_codebase(home: &TempDir, name: &str) {
+        let path = registry::codebase_path_at(
+            home.path(),
+            &ProjectName::from("copnow"),
+            &CodebaseName::from(name),
         );
+        fs::write(path, "name: [unterminated\n").expect("corrupt codebase yaml");
+    }
 
+    fn rename_first_project(home: &TempDir, name: &str, renamed: &str) {
+        let project = ProjectName::from("copnow");
         let mut codebase =
-            registry::load_codebase_at(home.path(), &project, &CodebaseName::from("copnow_api"))
+            registry::load_codebase_at(home.path(), &project, &CodebaseName::from(name))
                 .expect("load codebase");
-        codebase.projects[0].name = ProjectName::from("phase04-cache-reload");
+        codebase.projects[0].name = ProjectName::from(renamed);
         registry::save_codebase_at(home.path(), &project, &codebase).expect("save codebase");
+    }
+
+    #[tokio::test]
+    async fn registry_cache_reload_reads_only_named_codebases() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_codebases(&home, &workspace, &["core_api", "copnow_api", "worker_api"]);
+
+        let cache = std::sync::Arc::new(RwLock::new(
+            load_registry_cache(home.path()).expect("load cache"),
+        ));
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
+        assert_eq!(cache.read().await.len(), 3);
+
+        rename_first_project(&home, "copnow_api", "phase04-cache-reload");
+        corrupt_codebase(&home, "worker_api");
+
+        reload_codebases(
+            home.path(),
+            &cache,
+            &sync_states,
+            vec![CodebaseName::from("copnow_api")],
+        )
+        .await
+        .expect("reloading copnow_api must not read worker_api");
 
-        reload_codebase(home.path(), &mut cache, "copnow_api").expect("reload cache entry");
+        let cache = cache.read().await;
         let reloaded = cache
             .get(&CodebaseName::from("copnow_api"))
             .expect("codebase in cache");
         assert_eq!(reloaded.projects[0].name.0, "phase04-cache-reload");
+        assert!(cache.contains_key(&CodebaseName::from("worker_api")));
     }
 
     #[tokio::test]
-    async fn socket_protocol_status_and_stop_over_in_memory_channels() {
-        let (request_tx, mut request_rx) = mpsc::channel::<Vec<u8>>(8);
-        let (response_tx, mut response_rx) = mpsc::channel::<Vec<u8>>(8);
-        let (shutdown_tx, mut shutdown_rx) = broadcast::channel::<()>(1);
-
-        tokio::spawn(async move {
-            while let Some(bytes) = request_rx.recv().await {
-                let line = String::from_utf8(bytes).expect("utf8");
-                let request: DaemonRequest = serde_json::from_str(line.trim()).expect("request");
-                let response = match request.cmd.as_str() {
-                    "status" => DaemonResponse::ok(json!({"running": true})),
-                    "stop" => {
-                        let _ = shutdown_tx.send(());
-                        DaemonResponse::ok(json!({"stopping": true}))
-                    }
-                    other => DaemonResponse::error(format!("unknown command '{other}'")),
-                };
-                let encoded = serde_json::to_vec(&response).expect("encode response");
-                if response_tx.send(encoded).await.is_err() {
-                    break;
-                }
-            }
-        });
+    async fn registry_cache_reload_drops_deleted_codebases() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_codebases(&home, &workspace, &["core_api", "worker_api"]);
 
-        request_tx
-            .send(br#"{"cmd":"status"}"#.to_vec())
-            .await
-            .expect("send status request");
-        let status_response = response_rx.recv().await.expect("status response");
-        let status_json: serde_json::Value =
-            serde_json::from_slice(&status_response).expect("decode status");
-        assert_eq!(status_json["ok"], serde_json::Value::Bool(true));
+        let cache = std::sync::Arc::new(RwLock::new(
+            load_registry_cache(home.path()).expect("load cache"),
+        ));
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
+        for name in ["core_api", "worker_api"] {
+            sync_states
+                .write()
+                .await
+                .insert(name.to_string(), SyncState::default());
+        }
 
-        request_tx
-            .send(br#"{"cmd":"stop"}"#.to_vec())
-            .await
-            .expect("send stop request");
-        let stop_response = response_rx.recv().await.expect("stop response");
-        let stop_json: serde_json::Value =
-            serde_json::from_slice(&stop_response).expect("decode stop");
-        assert_eq!(stop_json["ok"], serde_json::Value::Bool(true));
+        registry::remove_codebase_at(
+            home.path(),
+            &ProjectName::from("copnow"),
+            &CodebaseName::from("worker_api"),
+        )
+        .expect("remove codebase");
+        reload_codebases(
+            home.path(),
+            &cache,
+            &sync_states,
+            vec![CodebaseName::from("worker_api")],
+        )
+        .await
+        .expect("reload");
 
-        shutdown_rx.recv().await.expect("shutdown signal");
+        assert!(cache.read().await.contains_key(&CodebaseName::from("core_api")));
+        assert!(!cache.read().await.contains_key(&CodebaseName::from("worker_api")));
+        assert!(sync_states.read().await.contains_key("core_api"));
+        assert!(!sync_states.read().await.contains_key("worker_api"));
     }
 
-    // ─── Status payload tests ──────────────────────────────────────────────────
-
     #[tokio::test]
-    async fn status_payload_has_last_sync_at_unix_when_never_synced() {
+    async fn registry_removal_only_checks_which_files_exist() {
         let home = TempDir::new().expect("home");
-        let cache = std::sync::Arc::new(RwLock::new(RegistryCache::new()));
-        let timestamps = std::sync::Arc::new(RwLock::new(SyncTimestamps::new()));
+        let workspace = TempDir::new().expect("workspace");
+        init_codebases(&home, &workspace, &["core_api", "worker_api"]);
 
-        let payload = build_status_payload(home.path(), cache, timestamps, 1_000_000).await;
+        let cache = std::sync::Arc::new(RwLock::new(
+            load_registry_cache(home.path()).expect("load cache"),
+        ));
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
+
+        corrupt_codebase(&home, "core_api");
+        registry::remove_codebase_at(
+            home.path(),
+            &ProjectName::from("copnow"),
+            &CodebaseName::from("worker_api"),
+        )
+        .expect("remove codebase");
+        handle_registry_removal(home.path(), &cache, &sync_states).await;
+
+        let cache = cache.read().await;
+        assert!(cache.contains_key(&CodebaseName::from("core_api")));
+        assert!(!cache.contains_key(&CodebaseName::from("worker_api")));
+    }
+
+    #[tokio::test]
+    async fn sync_processor_reloads_only_synced_codebases() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_codebases(&home, &workspace, &["copnow_api", "worker_api"]);
+
+        let cache = std::sync::Arc::new(RwLock::new(
+            load_registry_cache(home.path()).expect("load cache"),
+        ));
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
+        let own_writes = std::sync::Arc::new(RwLock::new(OwnWrites::new()));
+        let (sync_tx, sync_rx) = mpsc::channel::<SyncJob>(8);
+        let (shutdown_tx, _) = broadcast::channel::<()>(1);
+        tokio::spawn(sync_processor_task(
+            home.path().to_path_buf(),
+            cache.clone(),
+            sync_states.clone(),
+            own_writes,
+            Metrics::new(),
+            EventLog::default(),
+            sync_rx,
+            shutdown_tx.subscribe(),
+            DrainSignal::default(),
+            |_home, scope| Ok(empty_results(scope)),
+        ));
+
+        rename_first_project(&home, "copnow_api", "incremental");
+        corrupt_codebase(&home, "worker_api");
+
+        enqueue_sync(&sync_tx, SyncTarget::Codebase("copnow_api".to_string()), "cli")
+            .await
+            .expect("sync must not re-read worker_api");
+
+        let cache = cache.read().await;
+        let reloaded = cache
+            .get(&CodebaseName::from("copnow_api"))
+            .expect("codebase in cache");
+        assert_eq!(reloaded.projects[0].name.0, "incremental");
+        assert!(cache.contains_key(&CodebaseName::from("worker_api")));
+        assert_eq!(
+            sync_states.read().await.get("copnow_api").map(|s| s.last_error.clone()),
+            Some(None)
+        );
+        drop(shutdown_tx);
+    }
+
+    #[test]
+    fn removed_codebases_are_pruned_from_cache_and_sync_states() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        let project = ProjectName::from("copnow");
+
+        for name in ["core_api", "worker_api"] {
+            let path = workspace.path().join(name);
+            fs::create_dir_all(&path).expect("create codebase dir");
+            registry::init_at(
+                path,
+                project.clone(),
+                Some(ProjectType::Backend),
+                home.path(),
+            )
+            .expect("init codebase");
+        }
+
+        let mut cache = load_registry_cache(home.path()).expect("load cache");
+        let mut states: SyncStates = HashMap::new();
+        for name in ["core_api", "worker_api"] {
+            states.insert(name.to_string(), SyncState::default());
+        }
+
+        registry::remove_codebase_at(home.path(), &project, &CodebaseName::from("worker_api"))
+            .expect("remove codebase");
+        let current = load_registry_cache(home.path()).expect("reload cache");
+        let removed = prune_removed_codebases(&mut cache, &mut states, current);
+
+        assert_eq!(removed, vec![CodebaseName::from("worker_api")]);
+        assert!(cache.contains_key(&CodebaseName::from("core_api")));
+        assert!(!cache.contains_key(&CodebaseName::from("worker_api")));
+        assert!(states.contains_key("core_api"));
+        assert!(!states.contains_key("worker_api"));
+    }
+
+    #[tokio::test]
+    async fn reconcile_skips_unreadable_files_and_keeps_their_last_entry() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_codebases(&home, &workspace, &["core_api", "worker_api"]);
+        let cache = std::sync::Arc::new(RwLock::new(
+            load_registry_cache(home.path()).expect("load cache"),
+        ));
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
+
+        init_codebases(&home, &workspace, &["copnow_api"]);
+        corrupt_codebase(&home, "worker_api");
+        let (loaded, unreadable) = load_registry_cache_lossy(home.path()).expect("lossy load");
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(unreadable, vec![CodebaseName::from("worker_api")]);
+
+        reconcile_cache(home.path(), &cache, &sync_states).await;
+        let cache = cache.read().await;
+        for name in ["copnow_api", "core_api", "worker_api"] {
+            assert!(
+                cache.contains_key(&CodebaseName::from(name)),
+                "{name} missing"
+            );
+        }
+    }
+
+    /// Refuses every watch the way inotify does at `max_user_watches`. Owns
+    /// the notify watcher so its event channel stays open.
+    struct ExhaustedWatcher {
+        _watcher: notify::RecommendedWatcher,
+    }
+
+    impl crate::watch::DirWatcher for ExhaustedWatcher {
+        fn watch_dir(&mut self, _dir: &Path) -> notify::Result<()> {
+            Err(notify::Error::new(notify::ErrorKind::MaxFilesWatch))
+        }
+
+        fn unwatch_dir(&mut self, _dir: &Path) -> notify::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn watcher_task_survives_watch_limit_and_status_reports_it() {
+        let home = TempDir::new().expect("home");
+        let cache = std::sync::Arc::new(RwLock::new(RegistryCache::new()));
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
+        let unwatched_dirs = UnwatchedDirs::default();
+        let (sync_tx, _sync_rx) = mpsc::channel::<SyncJob>(8);
+        let (shutdown_tx, _) = broadcast::channel::<()>(1);
+        let task = tokio::spawn(watcher_task(
+            home.path().to_path_buf(),
+            cache.clone(),
+            sync_states.clone(),
+            sync_tx,
+            std::sync::Arc::new(RwLock::new(HashMap::new())),
+            Metrics::new(),
+            LastStalenessScan::default(),
+            unwatched_dirs.clone(),
+            EventLog::default(),
+            shutdown_tx.subscribe(),
+            |watcher| Box::new(ExhaustedWatcher { _watcher: watcher }),
+        ));
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while unwatched_dirs.load(Ordering::Relaxed) == 0 {
+            assert!(Instant::now() < deadline, "watch limit never reported");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        // Retries keep failing without taking the task down.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!task.is_finished());
+
+        let payload = build_status_payload(
+            home.path(),
+            cache,
+            sync_states,
+            &Metrics::new(),
+            &LastStalenessScan::default(),
+            &LastScheduledResync::default(),
+            &unwatched_dirs,
+            1_000_000,
+            None,
+        )
+        .await;
+        assert_eq!(payload["watcher_degraded"], json!(true));
+        assert!(payload["watcher_unwatched_dirs"].as_u64().unwrap() > 0);
+
+        shutdown_tx.send(()).expect("signal shutdown");
+        task.await.expect("join").expect("watcher stops cleanly");
+    }
+
+    #[tokio::test]
+    async fn socket_protocol_status_and_stop_over_in_memory_channels() {
+        let home = TempDir::new().expect("home");
+        let (request_tx, mut request_rx) = mpsc::channel::<Vec<u8>>(8);
+        let (response_tx, mut response_rx) = mpsc::channel::<Vec<u8>>(8);
+        let (sync_tx, _sync_rx) = mpsc::channel::<SyncJob>(8);
+        let (shutdown_tx, mut shutdown_rx) = broadcast::channel::<()>(1);
+        let drain = DrainSignal::default();
+
+        // Stands in for the sync processor draining its queue on shutdown.
+        let mut processor_shutdown = shutdown_tx.subscribe();
+        let processor_drain = drain.clone();
+        tokio::spawn(async move {
+            let _ = processor_shutdown.recv().await;
+            processor_drain.send_replace(Some(DrainReport { drained: 1, rejected: 2 }));
+        });
+
+        let ctx = DispatchContext::daemon(
+            home.path().to_path_buf(),
+            DaemonState {
+                cache: std::sync::Arc::new(RwLock::new(HashMap::new())),
+                sync_states: std::sync::Arc::new(RwLock::new(HashMap::new())),
+                metrics: Metrics::new(),
+                sync_tx,
+                shutdown_tx,
+                started_at_unix: 1_000_000,
+                previous_started_at_unix: None,
+                status_report: StatusReportCache::default(),
+                last_staleness_scan: LastStalenessScan::default(),
+                last_scheduled_resync: LastScheduledResync::default(),
+                unwatched_dirs: UnwatchedDirs::default(),
+                drain,
+            },
+        );
+
+        tokio::spawn(async move {
+            while let Some(bytes) = request_rx.recv().await {
+                let line = String::from_utf8(bytes).expect("utf8");
+                let response = match decode_request(line.trim()) {
+                    Ok(request) => dispatch(&request, &ctx).await,
+                    Err(response) => response,
+                };
+                let encoded = serde_json::to_vec(&response).expect("encode response");
+                if response_tx.send(encoded).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        request_tx
+            .send(br#"{"cmd":"status"}"#.to_vec())
+            .await
+            .expect("send status request");
+        let status_response = response_rx.recv().await.expect("status response");
+        let status_json: serde_json::Value =
+            serde_json::from_slice(&status_response).expect("decode status");
+        assert_eq!(status_json["ok"], serde_json::Value::Bool(true));
+        assert_eq!(status_json["data"]["running"], serde_json::Value::Bool(true));
+
+        request_tx
+            .send(b"{not json".to_vec())
+            .await
+            .expect("send malformed request");
+        let error_response = response_rx.recv().await.expect("error response");
+        let error_json: serde_json::Value =
+            serde_json::from_slice(&error_response).expect("decode error");
+        assert_eq!(error_json["ok"], serde_json::Value::Bool(false));
+
+        request_tx
+            .send(br#"{"cmd":"stop"}"#.to_vec())
+            .await
+            .expect("send stop request");
+        let stop_response = response_rx.recv().await.expect("stop response");
+        let stop_json: serde_json::Value =
+            serde_json::from_slice(&stop_response).expect("decode stop");
+        assert_eq!(stop_json["ok"], serde_json::Value::Bool(true));
+        assert_eq!(stop_json["data"]["drained"], json!(1));
+        assert_eq!(stop_json["data"]["rejected"], json!(2));
+
+        shutdown_rx.recv().await.expect("shutdown signal");
+    }
+
+    // ─── Socket client limits ───────────────────────────────────────────────────
+
+    fn socket_test_ctx(home: &Path) -> DispatchContext {
+        let (sync_tx, _sync_rx) = mpsc::channel::<SyncJob>(8);
+        let (shutdown_tx, _shutdown_rx) = broadcast::channel::<()>(1);
+        DispatchContext::daemon(
+            home.to_path_buf(),
+            DaemonState {
+                cache: std::sync::Arc::new(RwLock::new(HashMap::new())),
+                sync_states: std::sync::Arc::new(RwLock::new(HashMap::new())),
+                metrics: Metrics::new(),
+                sync_tx,
+                shutdown_tx,
+                started_at_unix: 1_000_000,
+                previous_started_at_unix: None,
+                status_report: StatusReportCache::default(),
+                last_staleness_scan: LastStalenessScan::default(),
+                last_scheduled_resync: LastScheduledResync::default(),
+                unwatched_dirs: UnwatchedDirs::default(),
+                drain: DrainSignal::default(),
+            },
+        )
+    }
+
+    /// Serve `server` as the daemon would, without a rate limit on `sync`.
+    fn spawn_socket_client(
+        server: UnixStream,
+        home: &Path,
+    ) -> tokio::task::JoinHandle<Result<(), DaemonError>> {
+        let ctx = socket_test_ctx(home);
+        tokio::spawn(async move {
+            let limiter = RateLimiter::new([]);
+            handle_socket_client(server, ctx, &limiter, &EventLog::default()).await
+        })
+    }
+
+    async fn request_status(client: &mut UnixStream) -> serde_json::Value {
+        client
+            .write_all(b"{\"cmd\":\"status\"}\n")
+            .await
+            .expect("send status request");
+        let mut line = String::new();
+        BufReader::new(client)
+            .read_line(&mut line)
+            .await
+            .expect("read status response");
+        serde_json::from_str(&line).unwrap_or(serde_json::Value::Null)
+    }
+
+    #[tokio::test]
+    async fn oversized_request_line_is_rejected_and_connection_closed() {
+        let home = TempDir::new().expect("home");
+        let (client, server) = UnixStream::pair().expect("socketpair");
+        let handler = spawn_socket_client(server, home.path());
+
+        let (client_read, mut client_write) = client.into_split();
+        let sender = tokio::spawn(async move {
+            let oversized = vec![b'x'; MAX_REQUEST_BYTES + 1];
+            client_write.write_all(&oversized).await.expect("send oversized line");
+            client_write
+        });
+
+        let mut lines = BufReader::new(client_read).lines();
+        let response = lines.next_line().await.expect("read").expect("error response");
+        let json: serde_json::Value = serde_json::from_str(&response).expect("decode");
+        assert_eq!(json["ok"], serde_json::Value::Bool(false));
+        assert!(json["error"]
+            .as_str()
+            .unwrap_or_default()
+            .starts_with("invalid request: line too long"));
+        assert!(lines.next_line().await.expect("read").is_none(), "connection left open");
+        handler.await.expect("join").expect("handler");
+        sender.await.expect("join sender");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_client_is_dropped_after_timeout() {
+        let home = TempDir::new().expect("home");
+        let (mut client, server) = UnixStream::pair().expect("socketpair");
+        let started = Instant::now();
+        let handler = spawn_socket_client(server, home.path());
+
+        // Half a request that never gets its newline.
+        client.write_all(b"{\"cmd\":").await.expect("send partial request");
+        let mut rest = Vec::new();
+        client.read_to_end(&mut rest).await.expect("read until closed");
+
+        assert!(rest.is_empty(), "unexpected response: {rest:?}");
+        assert!(started.elapsed() >= CLIENT_IDLE_TIMEOUT);
+        handler.await.expect("join").expect("handler");
+    }
+
+    #[tokio::test]
+    async fn clients_beyond_the_limit_are_told_busy() {
+        let home = TempDir::new().expect("home");
+        let socket = home.path().join("test.sock");
+        let listener = UnixListener::bind(&socket).expect("bind socket");
+        let (shutdown_tx, mut shutdown_rx) = broadcast::channel::<()>(1);
+        let ctx = socket_test_ctx(home.path());
+        let server_socket = socket.clone();
+        let server = tokio::spawn(async move {
+            accept_socket_clients(
+                &listener,
+                &server_socket,
+                ctx,
+                std::sync::Arc::new(RateLimiter::default()),
+                EventLog::default(),
+                &mut shutdown_rx,
+            )
+            .await
+        });
+
+        let mut clients = Vec::new();
+        for _ in 0..MAX_SOCKET_CLIENTS {
+            let mut client = UnixStream::connect(&socket).await.expect("connect");
+            assert_eq!(request_status(&mut client).await["ok"], serde_json::Value::Bool(true));
+            clients.push(client);
+        }
+
+        let mut extra = UnixStream::connect(&socket).await.expect("connect extra client");
+        let mut line = String::new();
+        BufReader::new(&mut extra)
+            .read_line(&mut line)
+            .await
+            .expect("read busy response");
+        let json: serde_json::Value = serde_json::from_str(&line).expect("decode");
+        assert_eq!(json["ok"], serde_json::Value::Bool(false));
+        assert!(json["error"].as_str().unwrap_or_default().starts_with("busy"));
+
+        // Closing a served client frees its slot.
+        drop(clients.pop());
+        let mut admitted = false;
+        for _ in 0..50 {
+            let mut client = UnixStream::connect(&socket).await.expect("connect");
+            if request_status(&mut client).await["ok"] == serde_json::Value::Bool(true) {
+                admitted = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(admitted, "freed slot was never reused");
+
+        shutdown_tx.send(()).expect("shutdown");
+        server.await.expect("join").expect("server");
+    }
+
+    #[tokio::test]
+    async fn requests_over_the_rate_limit_are_refused_and_accepted_ones_audited() {
+        use std::os::unix::fs::MetadataExt;
+
+        let home = TempDir::new().expect("home");
+        let socket = home.path().join("test.sock");
+        let listener = UnixListener::bind(&socket).expect("bind socket");
+        let (_shutdown_tx, mut shutdown_rx) = broadcast::channel::<()>(1);
+        let ctx = socket_test_ctx(home.path());
+        let limiter = RateLimiter::new([(
+            "status",
+            crate::rate_limit::RateLimit {
+                requests: 1,
+                window: Duration::from_secs(60),
+            },
+        )]);
+        let (events, events_writer) = EventLog::spawn(home.path());
+        let server_socket = socket.clone();
+        let server_events = events.clone();
+        let server = tokio::spawn(async move {
+            accept_socket_clients(
+                &listener,
+                &server_socket,
+                ctx,
+                std::sync::Arc::new(limiter),
+                server_events,
+                &mut shutdown_rx,
+            )
+            .await
+        });
+
+        let mut client = UnixStream::connect(&socket).await.expect("connect");
+        assert_eq!(request_status(&mut client).await["ok"], json!(true));
+        let refused = request_status(&mut client).await;
+        assert_eq!(refused["ok"], json!(false));
+        assert_eq!(refused["code"], json!(crate::protocol::RATE_LIMITED));
+        let retry_after_ms = refused["retry_after_ms"].as_u64().expect("retry hint");
+        assert!(retry_after_ms > 0 && retry_after_ms <= 60_000, "{refused}");
+
+        server.abort();
+        let _ = server.await;
+        drop(events);
+        events_writer.await.expect("event writer");
+
+        let logged = crate::events::read_at(home.path(), None, None).expect("read events");
+        assert_eq!(
+            logged.len(),
+            1,
+            "only the accepted request is audited: {logged:?}"
+        );
+        assert_eq!(logged[0].event, EventKind::SocketRequest);
+        assert_eq!(logged[0].detail["cmd"], json!("status"));
+        let uid = fs::metadata(home.path()).expect("home metadata").uid();
+        assert_eq!(logged[0].detail["uid"], json!(uid));
+        assert_eq!(logged[0].detail["pid"], json!(std::process::id()));
+    }
+
+    #[tokio::test]
+    async fn read_only_requests_are_not_audited() {
+        let home = TempDir::new().expect("home");
+        let socket = home.path().join("test.sock");
+        let listener = UnixListener::bind(&socket).expect("bind socket");
+        let (_shutdown_tx, mut shutdown_rx) = broadcast::channel::<()>(1);
+        let ctx = socket_test_ctx(home.path());
+        let (events, events_writer) = EventLog::spawn(home.path());
+        let server_socket = socket.clone();
+        let server_events = events.clone();
+        let server = tokio::spawn(async move {
+            accept_socket_clients(
+                &listener,
+                &server_socket,
+                ctx,
+                std::sync::Arc::new(RateLimiter::default()),
+                server_events,
+                &mut shutdown_rx,
+            )
+            .await
+        });
+
+        let mut client = UnixStream::connect(&socket).await.expect("connect");
+        for _ in 0..3 {
+            assert_eq!(request_status(&mut client).await["ok"], json!(true));
+        }
+
+        server.abort();
+        let _ = server.await;
+        drop(events);
+        events_writer.await.expect("event writer");
+
+        let logged = crate::events::read_at(home.path(), None, None).expect("read events");
+        assert!(logged.is_empty(), "status is not audited: {logged:?}");
+    }
+
+    // ─── Startup sync ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn startup_sync_targets_only_never_synced_and_stale_codebases() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_codebases(&home, &workspace, &["api", "web"]);
+        orchestra_sync::sync_codebase("web", home.path(), false, None).expect("sync web");
+
+        let codebases = load_registry_cache(home.path())
+            .expect("load cache")
+            .into_values()
+            .collect();
+        let targets = startup_sync_targets(home.path(), codebases);
+        assert_eq!(targets, vec![CodebaseName::from("api")]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn daemon_startup_syncs_never_synced_codebase() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_codebases(&home, &workspace, &["api"]);
+        let codebase = load_registry_cache(home.path())
+            .expect("load cache")
+            .remove(&CodebaseName::from("api"))
+            .expect("cached codebase");
+        let managed: Vec<PathBuf> = orchestra_sync::managed_output_paths(&codebase)
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect();
+        assert!(managed.iter().all(|path| !path.exists()));
+
+        let daemon = tokio::spawn(run(home.path().to_path_buf(), DaemonOptions::default()));
+        let deadline = Instant::now() + Duration::from_secs(20);
+        while !managed.iter().all(|path| path.exists()) {
+            assert!(Instant::now() < deadline, "startup sync never wrote the managed files");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let stop_home = home.path().to_path_buf();
+        let stop = tokio::task::spawn_blocking(move || crate::protocol::request_stop(&stop_home))
+            .await
+            .expect("join stop request")
+            .expect("stop daemon");
+        assert_eq!(stop, json!({ "stopping": true, "drained": 0, "rejected": 0 }));
+        daemon.await.expect("join daemon").expect("daemon run");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn socket_sync_is_recorded_in_the_event_log_in_order() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_codebases(&home, &workspace, &["api"]);
+        let options = DaemonOptions {
+            startup_sync: false,
+            resync_interval: None,
+        };
+        let daemon = tokio::spawn(run(home.path().to_path_buf(), options));
+
+        let deadline = Instant::now() + Duration::from_secs(20);
+        while !socket_path(home.path()).exists() {
+            assert!(Instant::now() < deadline, "daemon never bound its socket");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let client_home = home.path().to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            crate::protocol::request_sync(&client_home, Some("api".to_string()))?;
+            crate::protocol::request_stop(&client_home)
+        })
+        .await
+        .expect("join client")
+        .expect("sync and stop over the socket");
+        daemon.await.expect("join daemon").expect("daemon run");
+
+        let logged: Vec<_> = crate::events::read_at(home.path(), None, None)
+            .expect("read event log")
+            .into_iter()
+            .filter(|event| event.event != EventKind::WatcherEventProcessed)
+            .collect();
+        let kinds: Vec<EventKind> = logged.iter().map(|event| event.event).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                EventKind::DaemonStarted,
+                EventKind::SocketRequest,
+                EventKind::SyncStarted,
+                EventKind::SyncCompleted,
+                EventKind::SocketRequest
+            ]
+        );
+        assert_eq!(logged[1].detail["cmd"], "sync");
+        assert_eq!(logged[1].codebases, vec!["api".to_string()]);
+        assert_eq!(logged[2].codebases, vec!["api".to_string()]);
+        assert_eq!(logged[2].detail["source"], "socket");
+        assert_eq!(logged[3].detail["codebases"], json!(["api"]));
+        assert!(logged[3].detail["written"].as_u64().unwrap() > 0);
+        assert_eq!(logged[4].detail["cmd"], "stop");
+    }
+
+    // ─── Post-sync staleness scan ──────────────────────────────────────────────
+
+    fn completed_sync(target: &str, codebases: &[&str]) -> SyncSummary {
+        SyncSummary {
+            target: target.to_string(),
+            source: "watcher".to_string(),
+            codebases: codebases.iter().map(|name| name.to_string()).collect(),
+            written: 0,
+            unchanged: 0,
+            duration_ms: 0,
+            coalesced: 0,
+        }
+    }
+
+    #[test]
+    fn staleness_scan_checks_only_the_synced_codebases() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_codebases(&home, &workspace, &["api", "web", "docs"]);
+        orchestra_sync::sync_codebase("web", home.path(), false, None).expect("sync web");
+
+        let registered = registry::list_codebases_at(home.path()).expect("list");
+        let synced = ["web".to_string(), "gone".to_string()];
+        let targets: Vec<String> = scan_targets(registered, &synced)
+            .into_iter()
+            .map(|(_, codebase)| codebase.name.0)
+            .collect();
+        assert_eq!(targets, vec!["web".to_string()]);
+
+        let scan = run_staleness_scan_blocking(home.path(), &completed_sync("web", &["web"]))
+            .expect("scan");
+        assert_eq!(scan.target, "web");
+        assert_eq!((scan.current, scan.never_synced), (1, 0));
+
+        let all = completed_sync("all", &["api", "web"]);
+        let scan = run_staleness_scan_blocking(home.path(), &all).expect("scan");
+        assert_eq!((scan.current, scan.never_synced), (1, 1), "docs was not synced");
+    }
+
+    #[test]
+    fn staleness_scan_summary_counts_each_signal() {
+        let mut scan = StalenessScanSummary::default();
+        for signal in [
+            StalenessSignal::Current,
+            StalenessSignal::Current,
+            StalenessSignal::NeverSynced,
+            StalenessSignal::Stale {
+                reason: "registry changed".to_string(),
+            },
+            StalenessSignal::Modified {
+                files: vec![PathBuf::from("CLAUDE.md")],
+            },
+            StalenessSignal::Orphan { files: vec![] },
+        ] {
+            scan.record(&signal);
+        }
+        assert_eq!(
+            scan,
+            StalenessScanSummary {
+                current: 2,
+                never_synced: 1,
+                stale: 1,
+                modified: 1,
+                orphan: 1,
+                ..StalenessScanSummary::default()
+            }
+        );
+        let value = serde_json::to_value(&scan).expect("json");
+        assert_eq!(value["current"], json!(2));
+        assert_eq!(value["failed"], json!(0));
+    }
+
+    // ─── Status payload tests ──────────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn status_payload_has_last_sync_at_unix_when_never_synced() {
+        let home = TempDir::new().expect("home");
+        let cache = std::sync::Arc::new(RwLock::new(RegistryCache::new()));
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
+
+        let payload = build_status_payload(home.path(), cache, sync_states, &Metrics::new(), &LastStalenessScan::default(), &LastScheduledResync::default(), &UnwatchedDirs::default(), 1_000_000, None).await;
 
         assert_eq!(payload["running"], json!(true));
         assert_eq!(payload["started_at_unix"], json!(1_000_000u64));
@@ -1190,6 +2693,40 @@ mod tests {
         );
         let codebases = payload["codebases"].as_array().expect("codebases array");
         assert!(codebases.is_empty(), "empty codebases when cache is empty");
+        assert_eq!(payload["metrics"]["syncs_started"], json!(0u64));
+        assert_eq!(payload["metrics"]["recent_sync_durations_ms"], json!([]));
+        assert!(payload["last_staleness_scan"].is_null());
+        assert!(payload["last_scheduled_resync"].is_null());
+        assert_eq!(payload["watcher_degraded"], json!(false));
+        assert_eq!(payload["watcher_unwatched_dirs"], json!(0));
+    }
+
+    #[tokio::test]
+    async fn status_payload_reports_version_pid_and_uptime() {
+        let home = TempDir::new().expect("home");
+        let cache = std::sync::Arc::new(RwLock::new(RegistryCache::new()));
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
+        let started_at_unix = unix_seconds_now() - 90;
+
+        let payload = build_status_payload(
+            home.path(),
+            cache,
+            sync_states,
+            &Metrics::new(),
+            &LastStalenessScan::default(),
+            &LastScheduledResync::default(),
+            &UnwatchedDirs::default(),
+            started_at_unix,
+            None,
+        )
+        .await;
+
+        assert_eq!(payload["version"], json!(env!("CARGO_PKG_VERSION")));
+        assert_eq!(payload["protocol_version"], json!(PROTOCOL_VERSION));
+        assert_eq!(payload["pid"], json!(std::process::id()));
+        let uptime = payload["uptime_seconds"].as_u64().expect("uptime_seconds");
+        assert!((90..100).contains(&uptime), "uptime {uptime}");
+        assert_eq!(crate::protocol::daemon_version_warning(&payload), None);
     }
 
     #[tokio::test]
@@ -1214,15 +2751,22 @@ mod tests {
             load_registry_cache(home.path()).expect("load cache"),
         ));
 
-        let ts_map: SyncTimestamps = [
+        let ts_map: SyncStates = [
             ("api".to_string(), 1_000_100u64),
             ("worker".to_string(), 1_000_200u64),
         ]
         .into_iter()
+        .map(|(name, last_success_unix)| {
+            let state = SyncState {
+                last_success_unix,
+                ..SyncState::default()
+            };
+            (name, state)
+        })
         .collect();
-        let timestamps = std::sync::Arc::new(RwLock::new(ts_map));
+        let sync_states = std::sync::Arc::new(RwLock::new(ts_map));
 
-        let payload = build_status_payload(home.path(), cache, timestamps, 1_000_000).await;
+        let payload = build_status_payload(home.path(), cache, sync_states, &Metrics::new(), &LastStalenessScan::default(), &LastScheduledResync::default(), &UnwatchedDirs::default(), 1_000_000, None).await;
 
         // Daemon-wide last sync = max of the two.
         assert_eq!(
@@ -1246,23 +2790,627 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn status_payload_reports_timestamps_restored_from_the_state_file() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_codebases(&home, &workspace, &["api", "worker"]);
+        ensure_runtime_dirs(home.path()).expect("runtime dirs");
+        let saved = state::PersistedState {
+            started_at_unix: 900_000,
+            last_success_unix: [("api".to_string(), 1_000_100)].into_iter().collect(),
+        };
+        state::save_at(home.path(), &saved).expect("save state");
+
+        let (states, previous_started_at_unix) = restored_sync_states(home.path());
+        assert_eq!(previous_started_at_unix, Some(900_000));
+        let cache = std::sync::Arc::new(RwLock::new(
+            load_registry_cache(home.path()).expect("load cache"),
+        ));
+        let sync_states = std::sync::Arc::new(RwLock::new(states));
+        let payload = build_status_payload(
+            home.path(),
+            cache,
+            sync_states.clone(),
+            &Metrics::new(),
+            &LastStalenessScan::default(),
+            &LastScheduledResync::default(),
+            &UnwatchedDirs::default(),
+            1_000_000,
+            previous_started_at_unix,
+        )
+        .await;
+
+        assert_eq!(payload["previous_started_at_unix"], json!(900_000u64));
+        assert_eq!(payload["last_sync_at_unix"], json!(1_000_100u64));
+        let api = &payload["codebases"][0];
+        assert_eq!(api["name"], json!("api"));
+        assert_eq!(api["last_sync_at_unix"], json!(1_000_100u64));
+        assert_eq!(api["restored"], json!(true));
+        let worker = &payload["codebases"][1];
+        assert_eq!(worker["last_sync_at_unix"], json!(0u64));
+        assert_eq!(worker["restored"], json!(false));
+
+        sync_states
+            .write()
+            .await
+            .get_mut("api")
+            .expect("api state")
+            .record_success(1_000_300);
+        save_sync_states(home.path(), &sync_states, 1_000_000).await;
+        let saved = state::load_at(home.path()).expect("saved state");
+        assert_eq!(saved.started_at_unix, 1_000_000);
+        assert_eq!(saved.last_success_unix["api"], 1_000_300);
+        assert!(!sync_states.read().await["api"].restored);
+
+        fs::write(daemon_state_path(home.path()), "not json").expect("corrupt state");
+        let (states, previous_started_at_unix) = restored_sync_states(home.path());
+        assert!(states.is_empty());
+        assert_eq!(previous_started_at_unix, None);
+    }
+
+    #[tokio::test]
+    async fn detailed_status_matches_local_report_and_is_reused_briefly() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_codebases(&home, &workspace, &["worker", "api"]);
+        let tools = workspace.path().join("tools");
+        fs::create_dir_all(&tools).expect("create codebase dir");
+        registry::init_at(tools, ProjectName::from("infra"), None, home.path())
+            .expect("init codebase");
+
+        let (sync_tx, _sync_rx) = mpsc::channel::<SyncJob>(8);
+        let (shutdown_tx, _shutdown_rx) = broadcast::channel::<()>(1);
+        let ctx = DispatchContext::daemon(
+            home.path().to_path_buf(),
+            DaemonState {
+                cache: std::sync::Arc::new(RwLock::new(
+                    load_registry_cache(home.path()).expect("load cache"),
+                )),
+                sync_states: std::sync::Arc::new(RwLock::new(HashMap::new())),
+                metrics: Metrics::new(),
+                sync_tx,
+                shutdown_tx,
+                started_at_unix: 1_000_000,
+                previous_started_at_unix: None,
+                status_report: StatusReportCache::default(),
+                last_staleness_scan: LastStalenessScan::default(),
+                last_scheduled_resync: LastScheduledResync::default(),
+                unwatched_dirs: UnwatchedDirs::default(),
+                drain: DrainSignal::default(),
+            },
+        );
+        let request = DaemonRequest {
+            cmd: "status".to_string(),
+            codebase: None,
+            path: None,
+            detailed: true,
+            protocol_version: None,
+        };
+
+        let first = dispatch(&request, &ctx).await;
+        assert!(first.ok, "{first:?}");
+        let local = status::build_report(
+            home.path(),
+            &registry::list_codebases_at(home.path()).expect("list codebases"),
+            false,
+            false,
+        )
+        .expect("local report");
+        assert_eq!(first.data, Some(serde_json::to_value(&local).expect("json")));
+
+        // Within the TTL, a sync does not change the served report.
+        orchestra_sync::pipeline::run_from(
+            home.path(),
+            SyncScope::Codebase("api".to_string()),
+            false,
+            None,
+            orchestra_sync::SyncSource::Cli,
+        )
+        .expect("sync api");
+        let second = dispatch(&request, &ctx).await;
+        assert_eq!(second.data, first.data);
+        assert_eq!(second.data.expect("data")["codebases"][0]["status"], "never_synced");
+    }
+
     #[tokio::test]
     async fn sync_processor_records_codebase_timestamps_on_success() {
         // Simulate what sync_processor_task does after a successful sync.
-        let timestamps = std::sync::Arc::new(RwLock::new(SyncTimestamps::new()));
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
 
         let before = unix_seconds_now();
         {
-            let mut ts = timestamps.write().await;
-            ts.insert("copnow_api".to_string(), unix_seconds_now());
+            let mut states = sync_states.write().await;
+            states
+                .entry("copnow_api".to_string())
+                .or_default()
+                .record_success(unix_seconds_now());
         }
         let after = unix_seconds_now();
 
-        let ts = timestamps.read().await;
-        let recorded = *ts.get("copnow_api").expect("timestamp recorded");
+        let states = sync_states.read().await;
+        let recorded = states
+            .get("copnow_api")
+            .expect("timestamp recorded")
+            .last_success_unix;
         assert!(
             recorded >= before && recorded <= after,
             "recorded timestamp {recorded} should be between {before} and {after}"
         );
     }
+
+    // ─── Sync coalescing tests ─────────────────────────────────────────────────
+
+    static RUNNER_CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    fn counting_runner(
+        _home: &Path,
+        scope: SyncScope,
+    ) -> Result<Vec<SyncCodebaseResult>, SyncError> {
+        RUNNER_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(empty_results(scope))
+    }
+
+    fn empty_results(scope: SyncScope) -> Vec<SyncCodebaseResult> {
+        let name = match scope {
+            SyncScope::All => "all".to_string(),
+            SyncScope::Codebase(name) => name,
+        };
+        vec![SyncCodebaseResult {
+            codebase_name: name,
+            writes: Vec::new(),
+            previous_locations: Vec::new(),
+            uncatalogued_skills: Vec::new(),
+            budget_overruns: Vec::new(),
+            unknown_assignees: Vec::new(),
+            post_sync_error: None,
+        }]
+    }
+
+    fn job(target: SyncTarget) -> (SyncJob, oneshot::Receiver<Result<SyncSummary, SyncJobError>>) {
+        let (tx, rx) = oneshot::channel();
+        let job = SyncJob {
+            target,
+            source: "test",
+            respond_to: tx,
+        };
+        (job, rx)
+    }
+
+    #[tokio::test]
+    async fn sync_processor_coalesces_duplicate_codebase_jobs() {
+        let home = TempDir::new().expect("home");
+        let cache = std::sync::Arc::new(RwLock::new(RegistryCache::new()));
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
+        let own_writes = std::sync::Arc::new(RwLock::new(OwnWrites::new()));
+        let (sync_tx, sync_rx) = mpsc::channel::<SyncJob>(8);
+        let (shutdown_tx, _) = broadcast::channel::<()>(1);
+        let metrics = Metrics::new();
+
+        let mut receivers = Vec::new();
+        for _ in 0..3 {
+            let (job, rx) = job(SyncTarget::Codebase("copnow_api".to_string()));
+            sync_tx.send(job).await.expect("enqueue");
+            receivers.push(rx);
+        }
+
+        let processor = tokio::spawn(sync_processor_task(
+            home.path().to_path_buf(),
+            cache,
+            sync_states,
+            own_writes,
+            metrics.clone(),
+            EventLog::default(),
+            sync_rx,
+            shutdown_tx.subscribe(),
+            DrainSignal::default(),
+            counting_runner,
+        ));
+
+        for rx in receivers {
+            let summary = rx.await.expect("response").expect("sync ok");
+            assert_eq!(summary.target, "copnow_api");
+            assert_eq!(summary.coalesced, 2, "all three callers share one run");
+        }
+        assert_eq!(
+            RUNNER_CALLS.load(std::sync::atomic::Ordering::SeqCst),
+            1,
+            "three queued jobs for one codebase should run the pipeline once"
+        );
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.syncs_started, 1);
+        assert_eq!(snapshot.syncs_failed, 0);
+        assert_eq!(snapshot.recent_sync_durations_ms.len(), 1);
+
+        drop(sync_tx);
+        processor.await.expect("join").expect("processor");
+    }
+
+    static GATE_ENTERED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+    static GATE_OPEN: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+    /// Blocks until [`GATE_OPEN`] is set, so a test can act mid-sync.
+    fn gated_runner(_home: &Path, scope: SyncScope) -> Result<Vec<SyncCodebaseResult>, SyncError> {
+        GATE_ENTERED.store(true, std::sync::atomic::Ordering::SeqCst);
+        while !GATE_OPEN.load(std::sync::atomic::Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        Ok(empty_results(scope))
+    }
+
+    #[tokio::test]
+    async fn shutdown_finishes_running_sync_and_refuses_queued_ones() {
+        let home = TempDir::new().expect("home");
+        let cache = std::sync::Arc::new(RwLock::new(RegistryCache::new()));
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
+        let own_writes = std::sync::Arc::new(RwLock::new(OwnWrites::new()));
+        let (sync_tx, sync_rx) = mpsc::channel::<SyncJob>(8);
+        let (shutdown_tx, _) = broadcast::channel::<()>(1);
+        let drain = DrainSignal::default();
+        let processor = tokio::spawn(sync_processor_task(
+            home.path().to_path_buf(),
+            cache,
+            sync_states,
+            own_writes,
+            Metrics::new(),
+            EventLog::default(),
+            sync_rx,
+            shutdown_tx.subscribe(),
+            drain.clone(),
+            gated_runner,
+        ));
+
+        let (running, running_rx) = job(SyncTarget::Codebase("api".to_string()));
+        sync_tx.send(running).await.expect("enqueue running job");
+        while !GATE_ENTERED.load(std::sync::atomic::Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let (queued, queued_rx) = job(SyncTarget::Codebase("worker".to_string()));
+        sync_tx.send(queued).await.expect("enqueue queued job");
+        shutdown_tx.send(()).expect("shutdown");
+        GATE_OPEN.store(true, std::sync::atomic::Ordering::SeqCst);
+
+        let summary = running_rx
+            .await
+            .expect("running job answered")
+            .expect("running job completes normally");
+        assert_eq!(summary.target, "api");
+        let refused = queued_rx.await.expect("queued job answered, not dropped");
+        assert!(matches!(refused, Err(SyncJobError::ShuttingDown)), "got {refused:?}");
+
+        processor.await.expect("join").expect("processor");
+        assert_eq!(*drain.borrow(), Some(DrainReport { drained: 1, rejected: 1 }));
+        let late = enqueue_sync(&sync_tx, SyncTarget::All, "cli").await;
+        assert!(matches!(late, Err(DaemonError::ShuttingDown)), "got {late:?}");
+    }
+
+    #[test]
+    fn all_job_subsumes_queued_codebase_jobs() {
+        let mut queue = Vec::new();
+        let (api, _api_rx) = job(SyncTarget::Codebase("api".to_string()));
+        let (worker, _worker_rx) = job(SyncTarget::Codebase("worker".to_string()));
+        let (all, _all_rx) = job(SyncTarget::All);
+        let (late, _late_rx) = job(SyncTarget::Codebase("api".to_string()));
+
+        coalesce_job(&mut queue, api);
+        coalesce_job(&mut queue, worker);
+        assert_eq!(queue.len(), 2, "distinct codebases stay separate");
+
+        coalesce_job(&mut queue, all);
+        coalesce_job(&mut queue, late);
+        assert_eq!(queue.len(), 1);
+        assert!(matches!(queue[0].target, SyncTarget::All));
+        assert_eq!(queue[0].waiters.len(), 4);
+    }
+    // ─── Sync failure state and retry tests ────────────────────────────────────
+
+    #[tokio::test]
+    async fn status_payload_reports_last_error_per_codebase() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        let path = workspace.path().join("api");
+        fs::create_dir_all(&path).expect("create codebase dir");
+        registry::init_at(path, ProjectName::from("copnow"), None, home.path())
+            .expect("init codebase");
+        let cache = std::sync::Arc::new(RwLock::new(
+            load_registry_cache(home.path()).expect("load cache"),
+        ));
+
+        let mut state = SyncState::default();
+        state.record_success(1_000_100);
+        state.record_failure("failed to parse registry");
+        state.record_failure("failed to parse registry");
+        let sync_states = std::sync::Arc::new(RwLock::new(
+            [("api".to_string(), state)].into_iter().collect::<SyncStates>(),
+        ));
+
+        let payload =
+            build_status_payload(home.path(), cache, sync_states, &Metrics::new(), &LastStalenessScan::default(), &LastScheduledResync::default(), &UnwatchedDirs::default(), 1_000_000, None)
+                .await;
+        let api = &payload["codebases"][0];
+        assert_eq!(api["name"], json!("api"));
+        assert_eq!(api["last_sync_at_unix"], json!(1_000_100u64));
+        assert_eq!(api["last_error"], json!("failed to parse registry"));
+        assert_eq!(api["consecutive_failures"], json!(2));
+    }
+
+    const TEST_BACKOFF: &[Duration] = &[
+        Duration::from_millis(10),
+        Duration::from_millis(10),
+        Duration::from_millis(10),
+    ];
+
+    static FLAKY_CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    /// Fails the first two runs, then succeeds.
+    fn flaky_runner(home: &Path, scope: SyncScope) -> Result<Vec<SyncCodebaseResult>, SyncError> {
+        if FLAKY_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst) < 2 {
+            return Err(SyncError::Registry(
+                orchestra_core::error::RegistryError::RegistryNotFound {
+                    path: home.to_path_buf(),
+                },
+            ));
+        }
+        Ok(empty_results(scope))
+    }
+
+    static FAILING_CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    fn failing_runner(home: &Path, _scope: SyncScope) -> Result<Vec<SyncCodebaseResult>, SyncError> {
+        FAILING_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Err(SyncError::Registry(
+            orchestra_core::error::RegistryError::RegistryNotFound {
+                path: home.to_path_buf(),
+            },
+        ))
+    }
+
+    static LOCKED_CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    /// Finds the codebase locked on the first run, then succeeds.
+    fn locked_once_runner(_home: &Path, scope: SyncScope) -> Result<Vec<SyncCodebaseResult>, SyncError> {
+        if LOCKED_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+            return Err(SyncError::Locked {
+                codebase: "copnow_api".to_string(),
+            });
+        }
+        Ok(empty_results(scope))
+    }
+
+    fn spawn_processor(
+        home: &TempDir,
+        sync_states: std::sync::Arc<RwLock<SyncStates>>,
+        runner: SyncRunner,
+    ) -> (mpsc::Sender<SyncJob>, broadcast::Sender<()>) {
+        let cache = std::sync::Arc::new(RwLock::new(RegistryCache::new()));
+        let own_writes = std::sync::Arc::new(RwLock::new(OwnWrites::new()));
+        let (sync_tx, sync_rx) = mpsc::channel::<SyncJob>(8);
+        let (shutdown_tx, _) = broadcast::channel::<()>(1);
+        let shutdown_rx = shutdown_tx.subscribe();
+        tokio::spawn(sync_processor_task(
+            home.path().to_path_buf(),
+            cache,
+            sync_states,
+            own_writes,
+            Metrics::new(),
+            EventLog::default(),
+            sync_rx,
+            shutdown_rx,
+            DrainSignal::default(),
+            runner,
+        ));
+        // The caller keeps the shutdown sender alive; dropping it stops the processor.
+        (sync_tx, shutdown_tx)
+    }
+
+    #[tokio::test]
+    async fn locked_codebase_is_retried_without_recording_failure() {
+        let home = TempDir::new().expect("home");
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
+        let (sync_tx, _shutdown) = spawn_processor(&home, sync_states.clone(), locked_once_runner);
+
+        let summary = enqueue_sync(&sync_tx, SyncTarget::Codebase("copnow_api".to_string()), "cli")
+            .await
+            .expect("lock conflict is retried, not surfaced");
+        assert_eq!(summary.codebases, vec!["copnow_api".to_string()]);
+        assert_eq!(LOCKED_CALLS.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        let states = sync_states.read().await;
+        let state = states.get("copnow_api").expect("state");
+        assert_eq!(state.last_error, None);
+        assert_eq!(state.consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn watcher_retry_stops_once_sync_succeeds() {
+        let home = TempDir::new().expect("home");
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
+        let (sync_tx, _shutdown) = spawn_processor(&home, sync_states.clone(), flaky_runner);
+        let target = SyncTarget::Codebase("copnow_api".to_string());
+        let generations = RetryGenerations::default();
+        let generation = bump_generation(&generations, &target);
+
+        enqueue_sync(&sync_tx, target.clone(), "watcher")
+            .await
+            .expect_err("first sync fails");
+        {
+            let states = sync_states.read().await;
+            let state = states.get("copnow_api").expect("failure recorded");
+            assert!(state.last_error.is_some());
+            assert_eq!(state.consecutive_failures, 1);
+        }
+
+        let syncer = Syncer::queued(home.path().to_path_buf(), sync_tx, Default::default());
+        let (events_tx, mut events) = mpsc::unbounded_channel();
+        let recovered = retry_watcher_sync(
+            syncer,
+            target,
+            generations,
+            generation,
+            TEST_BACKOFF,
+            events_tx,
+        )
+        .await;
+        assert!(recovered, "second retry should succeed");
+        assert_eq!(FLAKY_CALLS.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert!(matches!(events.try_recv(), Ok(SyncEvent::SyncFailed { .. })));
+        assert!(matches!(events.try_recv(), Ok(SyncEvent::SyncCompleted(_))));
+
+        let states = sync_states.read().await;
+        let state = states.get("copnow_api").expect("state");
+        assert_eq!(state.last_error, None, "success clears the last error");
+        assert_eq!(state.consecutive_failures, 0);
+        assert!(state.last_success_unix > 0);
+    }
+
+    #[tokio::test]
+    async fn watcher_retry_gives_up_after_backoff() {
+        let home = TempDir::new().expect("home");
+        let sync_states = std::sync::Arc::new(RwLock::new(SyncStates::new()));
+        let (sync_tx, _shutdown) = spawn_processor(&home, sync_states.clone(), failing_runner);
+        let target = SyncTarget::Codebase("copnow_api".to_string());
+        let generations = RetryGenerations::default();
+        let generation = bump_generation(&generations, &target);
+
+        let syncer = Syncer::queued(home.path().to_path_buf(), sync_tx, Default::default());
+        let (events_tx, _events) = mpsc::unbounded_channel();
+        let recovered = retry_watcher_sync(
+            syncer,
+            target,
+            generations,
+            generation,
+            TEST_BACKOFF,
+            events_tx,
+        )
+        .await;
+        assert!(!recovered);
+        assert_eq!(
+            FAILING_CALLS.load(std::sync::atomic::Ordering::SeqCst),
+            TEST_BACKOFF.len()
+        );
+        let states = sync_states.read().await;
+        assert_eq!(
+            states.get("copnow_api").expect("state").consecutive_failures,
+            TEST_BACKOFF.len() as u32
+        );
+    }
+
+    fn summary(source: &str, written: usize) -> SyncSummary {
+        SyncSummary {
+            target: "all".to_string(),
+            source: source.to_string(),
+            codebases: vec!["copnow_api".to_string()],
+            written,
+            unchanged: 3,
+            duration_ms: 1,
+            coalesced: 0,
+        }
+    }
+
+    async fn next_job(sync_rx: &mut mpsc::Receiver<SyncJob>) -> SyncJob {
+        tokio::time::timeout(Duration::from_secs(5), sync_rx.recv())
+            .await
+            .expect("job queued in time")
+            .expect("job")
+    }
+
+    #[tokio::test]
+    async fn resync_schedule_enqueues_full_sync_each_interval() {
+        let (sync_tx, mut sync_rx) = mpsc::channel::<SyncJob>(8);
+        let (shutdown_tx, _) = broadcast::channel::<()>(1);
+        let last = LastScheduledResync::default();
+        let task = tokio::spawn(resync_schedule_task(
+            sync_tx,
+            Some(Duration::from_millis(50)),
+            None,
+            last.clone(),
+            shutdown_tx.subscribe(),
+        ));
+
+        let job = next_job(&mut sync_rx).await;
+        assert!(matches!(job.target, SyncTarget::All));
+        assert_eq!(job.source, SCHEDULE_SYNC_SOURCE);
+        job.respond_to
+            .send(Ok(summary(SCHEDULE_SYNC_SOURCE, 2)))
+            .expect("respond");
+
+        let job = next_job(&mut sync_rx).await;
+        assert_eq!(job.source, SCHEDULE_SYNC_SOURCE);
+        let resync = last.lock().await.clone().expect("first run recorded");
+        assert_eq!(resync.source, SCHEDULE_SYNC_SOURCE);
+        assert_eq!((resync.written, resync.unchanged), (2, 3));
+        assert_eq!(resync.error, None);
+        assert!(resync.finished_at_unix >= resync.started_at_unix);
+
+        job.respond_to
+            .send(Err(SyncJobError::Failed("registry locked".to_string())))
+            .expect("respond");
+        let third = next_job(&mut sync_rx).await;
+        let resync = last.lock().await.clone().expect("second run recorded");
+        assert!(resync.error.expect("error").contains("registry locked"));
+
+        shutdown_tx.send(()).expect("shutdown");
+        drop(third);
+        tokio::time::timeout(Duration::from_secs(5), task)
+            .await
+            .expect("task stops")
+            .expect("join");
+    }
+
+    /// Run the upgrade re-sync against a home that last ran `0.0.1`,
+    /// answering its job with `response`.
+    async fn upgrade_resync(response: Result<SyncSummary, SyncJobError>) -> TempDir {
+        let home = TempDir::new().expect("home");
+        ensure_runtime_dirs(home.path()).expect("runtime dirs");
+        record_daemon_version(home.path(), "0.0.1").expect("old version");
+        let (sync_tx, mut sync_rx) = mpsc::channel::<SyncJob>(8);
+        let (shutdown_tx, _) = broadcast::channel::<()>(1);
+        let last = LastScheduledResync::default();
+        let task = tokio::spawn(resync_schedule_task(
+            sync_tx,
+            None,
+            Some(home.path().to_path_buf()),
+            last.clone(),
+            shutdown_tx.subscribe(),
+        ));
+
+        let job = next_job(&mut sync_rx).await;
+        assert!(matches!(job.target, SyncTarget::All));
+        assert_eq!(job.source, UPGRADE_SYNC_SOURCE);
+        job.respond_to.send(response).expect("respond");
+        task.await.expect("join");
+        let resync = last.lock().await.clone().expect("recorded");
+        assert_eq!(resync.source, UPGRADE_SYNC_SOURCE);
+        home
+    }
+
+    #[tokio::test]
+    async fn version_change_queues_an_immediate_resync() {
+        let home = upgrade_resync(Ok(summary(UPGRADE_SYNC_SOURCE, 4))).await;
+        assert_eq!(
+            stored_daemon_version(home.path()).as_deref(),
+            Some(env!("CARGO_PKG_VERSION"))
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_upgrade_resync_keeps_the_old_version() {
+        let home = upgrade_resync(Err(SyncJobError::Failed("registry locked".to_string()))).await;
+        assert_eq!(stored_daemon_version(home.path()).as_deref(), Some("0.0.1"));
+    }
+
+    #[test]
+    fn daemon_version_round_trips_through_meta_file() {
+        let home = TempDir::new().expect("home");
+        ensure_runtime_dirs(home.path()).expect("runtime dirs");
+
+        assert_eq!(stored_daemon_version(home.path()), None);
+        record_daemon_version(home.path(), "0.2.0").expect("record");
+        let meta: DaemonMeta = serde_json::from_str(
+            &fs::read_to_string(daemon_meta_path(home.path())).expect("meta file"),
+        )
+        .expect("meta json");
+        assert_eq!(meta.version, "0.2.0");
+        assert_eq!(stored_daemon_version(home.path()).as_deref(), Some("0.2.0"));
+    }
 }
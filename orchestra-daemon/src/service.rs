@@ -0,0 +1,72 @@
+//! Auto-start through the platform's service manager: a launchd agent on
+//! macOS, a systemd user unit on Linux.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::{io_err, DaemonError};
+use crate::paths::{launchd_plist_path, systemd_unit_path};
+
+/// Name of the service manager `install` uses on this platform.
+#[cfg(target_os = "macos")]
+pub const SERVICE_MANAGER: &str = "launchd";
+#[cfg(target_os = "linux")]
+pub const SERVICE_MANAGER: &str = "systemd";
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub const SERVICE_MANAGER: &str = "service manager";
+
+/// Install the daemon with the platform's service manager and start it,
+/// passing `start_args` through to `orchestra daemon start`. Returns the
+/// written plist or unit file.
+pub fn install(home: &Path, start_args: &[&str]) -> Result<PathBuf, DaemonError> {
+    #[cfg(target_os = "macos")]
+    {
+        crate::launchd::install(home, start_args)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        crate::systemd::install(home, start_args)
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        let _ = (home, start_args);
+        Err(unsupported())
+    }
+}
+
+/// Stop the managed daemon and remove what [`install`] wrote.
+pub fn uninstall(home: &Path) -> Result<(), DaemonError> {
+    #[cfg(target_os = "macos")]
+    {
+        crate::launchd::uninstall(home)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        crate::systemd::uninstall(home)
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        let _ = home;
+        Err(unsupported())
+    }
+}
+
+/// The installed plist or unit file, if a service manager has the daemon.
+pub fn installed_service(home: &Path) -> Option<PathBuf> {
+    [launchd_plist_path(home), systemd_unit_path(home)]
+        .into_iter()
+        .find(|path| path.exists())
+}
+
+/// The running orchestra binary, which the service manager should launch.
+/// Not canonicalized: a package manager's stable symlink must survive
+/// upgrades that replace the versioned file behind it.
+pub(crate) fn current_binary() -> Result<PathBuf, DaemonError> {
+    std::env::current_exe().map_err(|e| io_err("current executable", e))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn unsupported() -> DaemonError {
+    DaemonError::Protocol(
+        "daemon install is only supported on macOS (launchd) and Linux (systemd)".to_string(),
+    )
+}
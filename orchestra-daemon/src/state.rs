@@ -0,0 +1,282 @@
+//! Daemon state that outlives the process: sync timestamps and a pid file in
+//! the run directory.
+//!
+//! `daemon-state.json` holds the last successful sync of each codebase and
+//! when the daemon that wrote it started. It is rewritten after every
+//! successful sync and on shutdown, so a daemon that launchd or systemd
+//! restarts after a crash still knows when each codebase was last synced. A
+//! missing or unreadable file means starting with no timestamps.
+//!
+//! `daemon.pid` names the process serving the socket and is removed on a
+//! clean shutdown, so a daemon killed with `kill -9` is recognisable by the
+//! pid file and socket it leaves behind.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{io_err, DaemonError};
+use crate::paths::{daemon_pid_path, daemon_state_path, socket_path};
+
+/// Contents of [`daemon_state_path`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersistedState {
+    /// Unix seconds at which the daemon that wrote the file started.
+    pub started_at_unix: u64,
+    /// Unix seconds of the last successful sync, by codebase name.
+    #[serde(default)]
+    pub last_success_unix: BTreeMap<String, u64>,
+}
+
+/// The state the last daemon saved, or `None` when there is none or it
+/// cannot be read.
+pub fn load_at(home: &Path) -> Option<PersistedState> {
+    let path = daemon_state_path(home);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == ErrorKind::NotFound => return None,
+        Err(err) => {
+            tracing::warn!(
+                path = %path.display(),
+                error = %err,
+                "cannot read daemon state; starting without it",
+            );
+            return None;
+        }
+    };
+    match serde_json::from_str(&text) {
+        Ok(state) => Some(state),
+        Err(err) => {
+            tracing::warn!(
+                path = %path.display(),
+                error = %err,
+                "daemon state does not parse; starting without it",
+            );
+            None
+        }
+    }
+}
+
+/// Write `state` through a `.tmp` file that is flushed to disk before it is
+/// renamed into place, then flush the directory, so a crash or power loss
+/// leaves either the previous state or the new one.
+pub fn save_at(home: &Path, state: &PersistedState) -> Result<(), DaemonError> {
+    let path = daemon_state_path(home);
+    let tmp = path.with_extension("json.tmp");
+    let mut file = fs::File::create(&tmp).map_err(|e| io_err(&tmp, e))?;
+    file.write_all(serde_json::to_string_pretty(state)?.as_bytes())
+        .map_err(|e| io_err(&tmp, e))?;
+    file.sync_all().map_err(|e| io_err(&tmp, e))?;
+    fs::rename(&tmp, &path).map_err(|e| io_err(&path, e))?;
+    sync_parent_dir(&path)
+}
+
+/// Fsync the directory holding `path` so a rename into it survives a crash.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> Result<(), DaemonError> {
+    let Some(parent) = path.parent() else {
+        return Ok(());
+    };
+    let dir = fs::File::open(parent).map_err(|e| io_err(parent, e))?;
+    dir.sync_all().map_err(|e| io_err(parent, e))
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> Result<(), DaemonError> {
+    Ok(())
+}
+
+/// Record a successful sync of each of `codebases` at `now_unix`, keeping
+/// everything else in the saved state.
+pub fn record_successes_at(
+    home: &Path,
+    codebases: &[String],
+    now_unix: u64,
+) -> Result<(), DaemonError> {
+    let mut state = load_at(home).unwrap_or_default();
+    for name in codebases {
+        state.last_success_unix.insert(name.clone(), now_unix);
+    }
+    save_at(home, &state)
+}
+
+/// Record this process in [`daemon_pid_path`]. Returns the pid a previous
+/// daemon left there when that process is gone, meaning it did not shut
+/// down cleanly.
+pub fn write_pid_file_at(home: &Path) -> Result<Option<u32>, DaemonError> {
+    let own = std::process::id();
+    let crashed = read_pid_at(home).filter(|&pid| pid != own && !pid_is_alive(pid));
+    let path = daemon_pid_path(home);
+    fs::write(&path, format!("{own}\n")).map_err(|e| io_err(&path, e))?;
+    Ok(crashed)
+}
+
+/// Remove [`daemon_pid_path`] on a clean shutdown.
+pub fn remove_pid_file_at(home: &Path) -> Result<(), DaemonError> {
+    let path = daemon_pid_path(home);
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(io_err(&path, err)),
+    }
+}
+
+/// The pid recorded in [`daemon_pid_path`], if the file exists and parses.
+pub fn read_pid_at(home: &Path) -> Option<u32> {
+    fs::read_to_string(daemon_pid_path(home))
+        .ok()
+        .and_then(|text| text.trim().parse().ok())
+}
+
+/// Whether a process with this pid exists. Where `/proc` is mounted it is
+/// asked directly; elsewhere `kill -0` decides, and its "not permitted"
+/// failure (EPERM) means the process exists but belongs to another user.
+#[cfg(unix)]
+pub fn pid_is_alive(pid: u32) -> bool {
+    let proc = Path::new("/proc");
+    if proc.join("self").exists() {
+        return proc.join(pid.to_string()).exists();
+    }
+    std::process::Command::new("kill")
+        .arg("-0")
+        .arg(pid.to_string())
+        .stderr(std::process::Stdio::piped())
+        .output()
+        .is_ok_and(|output| {
+            output.status.success()
+                || String::from_utf8_lossy(&output.stderr).contains("not permitted")
+        })
+}
+
+#[cfg(not(unix))]
+pub fn pid_is_alive(_pid: u32) -> bool {
+    false
+}
+
+/// What is left of a daemon whose socket does not answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ProcessState {
+    /// No socket and no live process: the daemon is stopped.
+    Stopped,
+    /// The socket is still there but the process that bound it is dead,
+    /// as after a crash. `pid` is `None` when no pid file was left.
+    StaleSocket { pid: Option<u32> },
+    /// A pid file names a dead process; its socket was already removed.
+    StalePidFile { pid: u32 },
+    /// The recorded process is alive but not answering on the socket,
+    /// e.g. still starting or hung.
+    Unresponsive { pid: u32 },
+}
+
+/// Classify a daemon that did not answer on its socket from what it left in
+/// the run directory.
+pub fn classify(
+    socket_present: bool,
+    pid: Option<u32>,
+    pid_alive: impl Fn(u32) -> bool,
+) -> ProcessState {
+    match pid {
+        Some(pid) if pid_alive(pid) => ProcessState::Unresponsive { pid },
+        pid if socket_present => ProcessState::StaleSocket { pid },
+        Some(pid) => ProcessState::StalePidFile { pid },
+        None => ProcessState::Stopped,
+    }
+}
+
+/// [`classify`] the daemon under `home`.
+pub fn inspect_at(home: &Path) -> ProcessState {
+    classify(socket_path(home).exists(), read_pid_at(home), pid_is_alive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paths::run_dir;
+    use tempfile::TempDir;
+
+    fn home_with_run_dir() -> TempDir {
+        let home = TempDir::new().expect("home");
+        fs::create_dir_all(run_dir(home.path())).expect("run dir");
+        home
+    }
+
+    #[test]
+    fn saved_state_loads_back_and_keeps_other_codebases() {
+        let home = home_with_run_dir();
+        assert_eq!(load_at(home.path()), None);
+
+        let state = PersistedState {
+            started_at_unix: 1_000_000,
+            last_success_unix: BTreeMap::from([("api".to_string(), 1_000_100)]),
+        };
+        save_at(home.path(), &state).unwrap();
+        record_successes_at(home.path(), &["web".to_string()], 1_000_200).unwrap();
+        assert!(!daemon_state_path(home.path())
+            .with_extension("json.tmp")
+            .exists());
+
+        let loaded = load_at(home.path()).unwrap();
+        assert_eq!(loaded.started_at_unix, 1_000_000);
+        assert_eq!(loaded.last_success_unix["api"], 1_000_100);
+        assert_eq!(loaded.last_success_unix["web"], 1_000_200);
+    }
+
+    #[test]
+    fn corrupt_state_starts_empty() {
+        let home = home_with_run_dir();
+        fs::write(daemon_state_path(home.path()), "{\"started_at_unix\": ").unwrap();
+        assert_eq!(load_at(home.path()), None);
+    }
+
+    #[test]
+    fn dead_pid_is_told_apart_from_a_stopped_or_hung_daemon() {
+        let alive = |pid| pid == 42;
+        assert_eq!(classify(false, None, alive), ProcessState::Stopped);
+        assert_eq!(
+            classify(true, Some(7), alive),
+            ProcessState::StaleSocket { pid: Some(7) }
+        );
+        assert_eq!(
+            classify(true, None, alive),
+            ProcessState::StaleSocket { pid: None }
+        );
+        assert_eq!(
+            classify(false, Some(7), alive),
+            ProcessState::StalePidFile { pid: 7 }
+        );
+        assert_eq!(
+            classify(true, Some(42), alive),
+            ProcessState::Unresponsive { pid: 42 }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pid_file_reports_a_previous_daemon_that_died() {
+        let home = home_with_run_dir();
+        assert!(pid_is_alive(std::process::id()));
+        // Owned by root, so `kill -0` alone would fail with EPERM.
+        assert!(pid_is_alive(1));
+
+        // A finished child's pid is not in use until the system reuses it.
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let dead = child.id();
+        child.wait().unwrap();
+        fs::write(daemon_pid_path(home.path()), format!("{dead}\n")).unwrap();
+
+        assert_eq!(
+            inspect_at(home.path()),
+            ProcessState::StalePidFile { pid: dead }
+        );
+        assert_eq!(write_pid_file_at(home.path()).unwrap(), Some(dead));
+        assert_eq!(read_pid_at(home.path()), Some(std::process::id()));
+        assert_eq!(write_pid_file_at(home.path()).unwrap(), None);
+
+        remove_pid_file_at(home.path()).unwrap();
+        assert_eq!(inspect_at(home.path()), ProcessState::Stopped);
+    }
+}
@@ -0,0 +1,328 @@
+//! systemd user unit management, the Linux counterpart of [`crate::launchd`].
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use orchestra_core::paths::{dirs_at, ORCHESTRA_HOME_ENV};
+
+use crate::error::{io_err, DaemonError};
+use crate::paths::{socket_path, systemd_unit_path, systemd_user_dir, SYSTEMD_UNIT};
+use crate::service::current_binary;
+
+/// Output of one `systemctl --user` invocation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemctlOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs `systemctl --user` with the given arguments. Tests substitute a
+/// recorder.
+pub type SystemctlRunner = fn(&[&str]) -> Result<SystemctlOutput, DaemonError>;
+
+/// Generate a systemd user unit for the Orchestra daemon.
+///
+/// `start_args` are appended to `orchestra daemon start`. stdout and stderr
+/// are appended to the same log files the launchd agent uses, and
+/// `orchestra_home` is exported as `ORCHESTRA_HOME` when set.
+pub fn generate_unit(
+    binary_path: &Path,
+    start_args: &[&str],
+    log_dir: &Path,
+    orchestra_home: Option<&Path>,
+) -> String {
+    let binary = binary_path.display().to_string();
+    let exec_start = [binary.as_str(), "daemon", "start"]
+        .into_iter()
+        .chain(start_args.iter().copied())
+        .map(quote_arg)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let environment = match orchestra_home {
+        Some(root) => format!(
+            "Environment={}\n",
+            quote_arg(&format!("{ORCHESTRA_HOME_ENV}={}", root.display()))
+        ),
+        None => String::new(),
+    };
+    let stdout = escape_specifiers(&log_dir.join("daemon.log").display().to_string());
+    let stderr = escape_specifiers(&log_dir.join("daemon-err.log").display().to_string());
+
+    format!(
+        "[Unit]
+Description=Orchestra daemon
+
+[Service]
+Type=simple
+ExecStart={exec_start}
+Restart=always
+RestartSec=2
+{environment}StandardOutput=append:{stdout}
+StandardError=append:{stderr}
+
+[Install]
+WantedBy=default.target
+"
+    )
+}
+
+/// Write the user unit, reload systemd and enable and start the service,
+/// passing `start_args` through to `orchestra daemon start`.
+pub fn install(home: &Path, start_args: &[&str]) -> Result<PathBuf, DaemonError> {
+    install_with(home, &current_binary()?, start_args, systemctl_command)
+}
+
+fn install_with(
+    home: &Path,
+    binary_path: &Path,
+    start_args: &[&str],
+    runner: SystemctlRunner,
+) -> Result<PathBuf, DaemonError> {
+    let logs = crate::paths::logs_dir(home);
+    for dir in [
+        systemd_user_dir(home),
+        logs.clone(),
+        crate::paths::run_dir(home),
+    ] {
+        fs::create_dir_all(&dir).map_err(|e| io_err(&dir, e))?;
+    }
+
+    let unit = systemd_unit_path(home);
+    let reinstall = unit.exists();
+    let dirs = dirs_at(home);
+    let orchestra_home = (!dirs.is_legacy(home)).then_some(dirs.data_root.as_path());
+    fs::write(
+        &unit,
+        generate_unit(binary_path, start_args, &logs, orchestra_home),
+    )
+    .map_err(|e| io_err(&unit, e))?;
+
+    systemctl(runner, &["daemon-reload"])?;
+    systemctl(runner, &["enable", "--now", SYSTEMD_UNIT])?;
+    // `enable --now` leaves an already running daemon on the old unit.
+    if reinstall {
+        systemctl(runner, &["restart", SYSTEMD_UNIT])?;
+    }
+    Ok(unit)
+}
+
+/// Stop and disable the service and remove its unit file.
+pub fn uninstall(home: &Path) -> Result<(), DaemonError> {
+    uninstall_with(home, systemctl_command)
+}
+
+fn uninstall_with(home: &Path, runner: SystemctlRunner) -> Result<(), DaemonError> {
+    let unit = systemd_unit_path(home);
+    if unit.exists() {
+        let _ = runner(&["disable", "--now", SYSTEMD_UNIT]);
+        fs::remove_file(&unit).map_err(|e| io_err(&unit, e))?;
+        systemctl(runner, &["daemon-reload"])?;
+    }
+
+    let socket = socket_path(home);
+    if socket.exists() {
+        let _ = fs::remove_file(socket);
+    }
+
+    Ok(())
+}
+
+/// Run `systemctl --user args`, failing on a non-zero exit.
+fn systemctl(runner: SystemctlRunner, args: &[&str]) -> Result<(), DaemonError> {
+    let output = runner(args)?;
+    if output.success {
+        return Ok(());
+    }
+    Err(DaemonError::Systemd(format!(
+        "systemctl --user {} failed: {} {}",
+        args.join(" "),
+        output.stdout.trim(),
+        output.stderr.trim()
+    )))
+}
+
+/// The real [`SystemctlRunner`].
+fn systemctl_command(args: &[&str]) -> Result<SystemctlOutput, DaemonError> {
+    let output = Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .output()
+        .map_err(|e| io_err("systemctl", e))?;
+    Ok(SystemctlOutput {
+        success: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+/// One `ExecStart=`/`Environment=` word: specifiers escaped, and quoted when
+/// it holds whitespace, quotes or backslashes.
+fn quote_arg(arg: &str) -> String {
+    let escaped = escape_specifiers(arg);
+    if !escaped.contains(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '\\')) {
+        return escaped;
+    }
+    format!("\"{}\"", escaped.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// systemd expands `%` specifiers and `$` variables in unit values.
+fn escape_specifiers(value: &str) -> String {
+    value.replace('%', "%%").replace('$', "$$")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static CALLS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn recording(args: &[&str]) -> Result<SystemctlOutput, DaemonError> {
+        CALLS.with(|calls| calls.borrow_mut().push(args.join(" ")));
+        Ok(SystemctlOutput {
+            success: true,
+            ..SystemctlOutput::default()
+        })
+    }
+
+    fn failing(args: &[&str]) -> Result<SystemctlOutput, DaemonError> {
+        Ok(SystemctlOutput {
+            success: args[0] == "daemon-reload",
+            stdout: String::new(),
+            stderr: "Failed to connect to bus: No medium found".to_string(),
+        })
+    }
+
+    fn take_calls() -> Vec<String> {
+        CALLS.with(|calls| calls.take())
+    }
+
+    /// `key=value` lines of `unit`, in order.
+    fn entries(unit: &str) -> Vec<(&str, &str)> {
+        unit.lines()
+            .filter_map(|line| line.split_once('='))
+            .collect()
+    }
+
+    fn value<'a>(unit: &'a str, key: &str) -> Option<&'a str> {
+        entries(unit)
+            .into_iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v)
+    }
+
+    #[test]
+    fn unit_contains_required_service_fields() {
+        let binary = Path::new("/home/tester/.cargo/bin/orchestra");
+        let log_dir = Path::new("/home/tester/.orchestra/logs");
+        let unit = generate_unit(binary, &[], log_dir, None);
+
+        assert!(unit.starts_with("[Unit]\n"));
+        assert!(unit.contains("\n[Service]\n"));
+        assert!(unit.contains("\n[Install]\n"));
+        assert_eq!(
+            value(&unit, "ExecStart"),
+            Some("/home/tester/.cargo/bin/orchestra daemon start")
+        );
+        assert_eq!(value(&unit, "Restart"), Some("always"));
+        assert_eq!(
+            value(&unit, "StandardOutput"),
+            Some("append:/home/tester/.orchestra/logs/daemon.log")
+        );
+        assert_eq!(
+            value(&unit, "StandardError"),
+            Some("append:/home/tester/.orchestra/logs/daemon-err.log")
+        );
+        assert_eq!(value(&unit, "WantedBy"), Some("default.target"));
+        assert_eq!(value(&unit, "Environment"), None);
+    }
+
+    #[test]
+    fn unit_passes_start_args_and_custom_home() {
+        let binary = Path::new("/opt/my tools/orchestra");
+        let log_dir = Path::new("/home/tester/Sync/orchestra/logs");
+        let root = Path::new("/home/tester/Sync/orchestra");
+        let unit = generate_unit(binary, &["--no-startup-sync"], log_dir, Some(root));
+
+        assert_eq!(
+            value(&unit, "ExecStart"),
+            Some("\"/opt/my tools/orchestra\" daemon start --no-startup-sync")
+        );
+        assert_eq!(
+            value(&unit, "Environment"),
+            Some("ORCHESTRA_HOME=/home/tester/Sync/orchestra")
+        );
+    }
+
+    #[test]
+    fn unit_values_escape_systemd_specifiers() {
+        assert_eq!(quote_arg("/tmp/100%/orchestra"), "/tmp/100%%/orchestra");
+        assert_eq!(quote_arg("$HOME"), "$$HOME");
+        assert_eq!(quote_arg("a \"b\""), "\"a \\\"b\\\"\"");
+    }
+
+    #[test]
+    fn install_writes_unit_then_reloads_and_enables() {
+        let home = tempfile::TempDir::new().unwrap();
+        take_calls();
+
+        let binary = Path::new("/usr/bin/orchestra");
+        let unit = install_with(home.path(), binary, &[], recording).unwrap();
+        assert_eq!(
+            unit,
+            home.path()
+                .join(".config/systemd/user/orchestra-daemon.service")
+        );
+        assert!(fs::read_to_string(&unit)
+            .unwrap()
+            .contains("ExecStart=/usr/bin/orchestra"));
+        assert_eq!(
+            take_calls(),
+            ["daemon-reload", "enable --now orchestra-daemon.service"]
+        );
+
+        install_with(home.path(), binary, &[], recording).unwrap();
+        assert_eq!(
+            take_calls(),
+            [
+                "daemon-reload",
+                "enable --now orchestra-daemon.service",
+                "restart orchestra-daemon.service"
+            ]
+        );
+    }
+
+    #[test]
+    fn install_reports_systemctl_failures() {
+        let home = tempfile::TempDir::new().unwrap();
+        let err = install_with(home.path(), Path::new("/usr/bin/orchestra"), &[], failing)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("systemctl --user enable --now orchestra-daemon.service failed"),
+            "{err}"
+        );
+        assert!(err.contains("No medium found"), "{err}");
+    }
+
+    #[test]
+    fn uninstall_disables_and_removes_unit() {
+        let home = tempfile::TempDir::new().unwrap();
+        uninstall_with(home.path(), recording).unwrap();
+        take_calls();
+
+        let unit =
+            install_with(home.path(), Path::new("/usr/bin/orchestra"), &[], recording).unwrap();
+        take_calls();
+        uninstall_with(home.path(), recording).unwrap();
+        assert!(!unit.exists());
+        assert_eq!(
+            take_calls(),
+            ["disable --now orchestra-daemon.service", "daemon-reload"]
+        );
+    }
+}
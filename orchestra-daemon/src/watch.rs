@@ -0,0 +1,1736 @@
+//! Registry watcher: filesystem events → debounce → sync.
+//!
+//! [`Watch`] is the auto-sync loop the daemon runs, usable on its own by
+//! tools that want Orchestra's auto-sync without the socket server or
+//! launchd. It watches `~/.orchestra/projects/` and every managed agent file,
+//! syncs a codebase when its registry YAML changes, applies writeback blocks
+//! found in agent files, and reports what happened as [`SyncEvent`]s.
+//!
+//! ```rust,no_run
+//! use orchestra_daemon::watch::{SyncEvent, Watch};
+//! use std::time::Duration;
+//!
+//! # async fn embed() -> Result<(), orchestra_daemon::DaemonError> {
+//! let home = std::path::PathBuf::from("/home/ana");
+//! let mut watch = Watch::new(home)
+//!     .with_debounce(Duration::from_millis(200))
+//!     .spawn()?;
+//! while let Some(event) = watch.next().await {
+//!     match event {
+//!         SyncEvent::SyncCompleted(summary) => println!("synced {}", summary.target),
+//!         SyncEvent::SyncFailed { target, error } => eprintln!("{target}: {error}"),
+//!         _ => {}
+//!     }
+//! }
+//! watch.stop().await
+//! # }
+//! ```
+//!
+//! Syncs run in-process with the same hash-gated writes as `orchestra sync`
+//! and are journaled as daemon syncs. Don't run a `Watch` against a home
+//! whose daemon is also running: both would sync every change.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::future::Future;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::event::{ModifyKind, RenameMode};
+use notify::{recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tokio::sync::{mpsc, oneshot, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use orchestra_core::{
+    error::RegistryError,
+    registry,
+    types::{Codebase, ProjectName},
+};
+use orchestra_sync::{
+    hash_store, managed_agent_paths,
+    pipeline::{self, SyncProgress, SyncScope},
+    process_writeback, SyncCodebaseResult, SyncError, SyncSource, WriteResult,
+};
+
+use crate::error::{io_err, DaemonError};
+use crate::metrics::Metrics;
+use crate::paths::{projects_root, DEBOUNCE_WINDOW};
+use crate::runtime::{enqueue_sync, SyncJob};
+
+pub(crate) type OwnWrites = HashMap<PathBuf, Instant>;
+const OWN_WRITE_SUPPRESS_WINDOW: Duration = Duration::from_secs(1);
+
+/// Delays before each retry of a failed watcher-triggered sync. The watcher
+/// gives up once every delay has been used.
+pub(crate) const WATCHER_RETRY_BACKOFF: &[Duration] = &[
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+    Duration::from_secs(30),
+];
+
+/// Delays before re-running a sync that found its codebase locked by
+/// another process (usually a CLI sync). A lock conflict is transient, so it
+/// is retried in place instead of being recorded as a sync failure.
+#[cfg(not(test))]
+const LOCKED_RETRY_BACKOFF: &[Duration] = &[Duration::from_secs(1), Duration::from_secs(5)];
+#[cfg(test)]
+const LOCKED_RETRY_BACKOFF: &[Duration] = &[Duration::from_millis(10), Duration::from_millis(10)];
+
+/// Delays before retrying directories the OS refused to watch because its
+/// watch limit was reached. The last delay repeats until they are watched:
+/// the limit may be raised at any time.
+#[cfg(not(test))]
+const WATCH_LIMIT_RETRY_BACKOFF: &[Duration] = &[
+    Duration::from_secs(10),
+    Duration::from_secs(60),
+    Duration::from_secs(5 * 60),
+];
+#[cfg(test)]
+const WATCH_LIMIT_RETRY_BACKOFF: &[Duration] =
+    &[Duration::from_millis(10), Duration::from_millis(50)];
+
+/// Latest watcher event generation per sync target label. A pending retry
+/// is dropped once a newer event bumps its target's generation.
+pub(crate) type RetryGenerations = std::sync::Arc<std::sync::Mutex<HashMap<String, u64>>>;
+
+/// Blocking sync entrypoint; swappable in tests.
+pub(crate) type SyncRunner = fn(&Path, SyncScope) -> Result<Vec<SyncCodebaseResult>, SyncError>;
+
+/// Adds and removes non-recursive directory watches. Implemented for every
+/// notify watcher; tests wrap one to simulate an exhausted watch limit.
+pub(crate) trait DirWatcher: Send {
+    fn watch_dir(&mut self, dir: &Path) -> notify::Result<()>;
+    fn unwatch_dir(&mut self, dir: &Path) -> notify::Result<()>;
+}
+
+impl<W: Watcher + Send> DirWatcher for W {
+    fn watch_dir(&mut self, dir: &Path) -> notify::Result<()> {
+        self.watch(dir, RecursiveMode::NonRecursive)
+    }
+
+    fn unwatch_dir(&mut self, dir: &Path) -> notify::Result<()> {
+        self.unwatch(dir)
+    }
+}
+
+/// Turns the notify watcher into the [`DirWatcher`] the loop registers
+/// directories with; swappable in tests.
+pub(crate) type WrapWatcher = fn(RecommendedWatcher) -> Box<dyn DirWatcher>;
+
+/// The production [`WrapWatcher`]: the notify watcher itself.
+pub(crate) fn notify_watcher(watcher: RecommendedWatcher) -> Box<dyn DirWatcher> {
+    Box::new(watcher)
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum SyncTarget {
+    All,
+    Codebase(String),
+}
+
+impl SyncTarget {
+    pub(crate) fn scope(&self) -> SyncScope {
+        match self {
+            SyncTarget::All => SyncScope::All,
+            SyncTarget::Codebase(name) => SyncScope::Codebase(name.clone()),
+        }
+    }
+
+    pub(crate) fn label(&self) -> String {
+        match self {
+            SyncTarget::All => "all".to_string(),
+            SyncTarget::Codebase(name) => name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncSummary {
+    pub target: String,
+    pub source: String,
+    pub codebases: Vec<String>,
+    pub written: usize,
+    pub unchanged: usize,
+    pub duration_ms: u128,
+    /// Number of additional requests answered by this same sync run.
+    pub coalesced: usize,
+}
+
+/// Something the watcher noticed or did.
+#[derive(Debug, Clone)]
+pub enum SyncEvent {
+    /// A registry file changed and parses; a sync follows.
+    RegistryChanged { path: PathBuf },
+    /// A registry file or project directory was deleted. Nothing is synced.
+    RegistryRemoved { path: PathBuf },
+    /// A changed registry file does not parse. Nothing is synced until the
+    /// file changes again.
+    RegistryInvalid { path: PathBuf, error: String },
+    /// A watcher-triggered sync (or one of its retries) succeeded.
+    SyncCompleted(SyncSummary),
+    /// A watcher-triggered sync (or one of its retries) failed. Failed
+    /// syncs are retried with backoff until a newer change supersedes them.
+    SyncFailed { target: String, error: String },
+    /// The number of directories the OS refused to watch changed, usually
+    /// because the inotify watch limit is exhausted. Changes in them go
+    /// unnoticed until a retry succeeds; `0` means every directory is
+    /// watched again.
+    WatchDegraded { unwatched_dirs: usize },
+}
+
+/// Configuration for a registry watcher. See the [module docs](self).
+pub struct Watch {
+    home: PathBuf,
+    debounce: Duration,
+    syncer: Syncer,
+    metrics: Option<Metrics>,
+    only_codebase: Option<String>,
+    wrap_watcher: WrapWatcher,
+}
+
+impl Watch {
+    /// Watch the registry under `home` with the daemon's debounce window.
+    pub fn new(home: impl Into<PathBuf>) -> Self {
+        let home = home.into();
+        Self {
+            syncer: Syncer::direct(home.clone()),
+            home,
+            debounce: DEBOUNCE_WINDOW,
+            metrics: None,
+            only_codebase: None,
+            wrap_watcher: notify_watcher,
+        }
+    }
+
+    /// Ignore repeat events for the same path within `debounce`.
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Only react to changes to `codebase`'s registry YAML. Other codebases,
+    /// `project.yaml` and agent-file writeback are ignored.
+    pub fn only_codebase(mut self, codebase: impl Into<String>) -> Self {
+        self.only_codebase = Some(codebase.into());
+        self
+    }
+
+    /// Route syncs through the daemon's sync queue.
+    pub(crate) fn with_syncer(mut self, syncer: Syncer) -> Self {
+        self.syncer = syncer;
+        self
+    }
+
+    /// Count received and debounced events in the daemon's metrics.
+    pub(crate) fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Register directories through `wrap`'s [`DirWatcher`].
+    pub(crate) fn with_watcher_wrapper(mut self, wrap: WrapWatcher) -> Self {
+        self.wrap_watcher = wrap;
+        self
+    }
+
+    /// Watch until a watcher error, calling `callback` for every event.
+    pub async fn run(self, callback: impl FnMut(SyncEvent)) -> Result<(), DaemonError> {
+        self.run_until(std::future::pending(), callback).await
+    }
+
+    /// Watch until `shutdown` resolves, calling `callback` for every event.
+    pub async fn run_until(
+        self,
+        shutdown: impl Future<Output = ()>,
+        callback: impl FnMut(SyncEvent),
+    ) -> Result<(), DaemonError> {
+        WatchLoop::start(self)?.run(shutdown, callback).await
+    }
+
+    /// Start watching on a background task and receive events from the
+    /// returned handle. Must be called from within a tokio runtime.
+    ///
+    /// Setup errors (e.g. the projects directory cannot be watched) are
+    /// returned here; later errors are returned by [`WatchHandle::stop`].
+    pub fn spawn(self) -> Result<WatchHandle, DaemonError> {
+        let watch_loop = WatchLoop::start(self)?;
+        let (events_tx, events) = mpsc::unbounded_channel();
+        let (stop_tx, stop_rx) = oneshot::channel::<()>();
+        let task = tokio::spawn(watch_loop.run(
+            async {
+                let _ = stop_rx.await;
+            },
+            move |event| {
+                let _ = events_tx.send(event);
+            },
+        ));
+        Ok(WatchHandle {
+            events,
+            stop: Some(stop_tx),
+            task,
+        })
+    }
+}
+
+/// A [`Watch`] running on a background task.
+pub struct WatchHandle {
+    events: mpsc::UnboundedReceiver<SyncEvent>,
+    stop: Option<oneshot::Sender<()>>,
+    task: JoinHandle<Result<(), DaemonError>>,
+}
+
+impl WatchHandle {
+    /// The next event, or `None` once the watcher has stopped.
+    pub async fn next(&mut self) -> Option<SyncEvent> {
+        self.events.recv().await
+    }
+
+    /// Stop watching and return the watcher's result.
+    pub async fn stop(mut self) -> Result<(), DaemonError> {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        self.task
+            .await
+            .map_err(|err| DaemonError::Protocol(format!("watcher task join error: {err}")))?
+    }
+}
+
+/// Runs syncs for the watcher, either in-process or through the daemon's
+/// sync queue.
+#[derive(Clone)]
+pub(crate) struct Syncer {
+    home: PathBuf,
+    queue: Option<mpsc::Sender<SyncJob>>,
+    own_writes: std::sync::Arc<RwLock<OwnWrites>>,
+}
+
+impl Syncer {
+    fn direct(home: PathBuf) -> Self {
+        Self {
+            home,
+            queue: None,
+            own_writes: Default::default(),
+        }
+    }
+
+    /// Sync through the daemon's queue, which marks `own_writes` itself.
+    pub(crate) fn queued(
+        home: PathBuf,
+        queue: mpsc::Sender<SyncJob>,
+        own_writes: std::sync::Arc<RwLock<OwnWrites>>,
+    ) -> Self {
+        Self {
+            home,
+            queue: Some(queue),
+            own_writes,
+        }
+    }
+
+    async fn sync(
+        &self,
+        target: SyncTarget,
+        source: &'static str,
+    ) -> Result<SyncSummary, DaemonError> {
+        if let Some(queue) = &self.queue {
+            return enqueue_sync(queue, target, source).await;
+        }
+
+        let started = Instant::now();
+        mark_target_own_writes(&self.home, &self.own_writes, &target).await;
+        let results =
+            run_sync_with_lock_retry(self.home.clone(), target.clone(), daemon_sync_runner)
+                .await??;
+        Ok(build_sync_summary(
+            target,
+            source,
+            results,
+            started.elapsed(),
+        ))
+    }
+
+    async fn is_recent_own_write(&self, path: &Path) -> bool {
+        is_recent_own_write(&self.own_writes, path, Instant::now()).await
+    }
+}
+
+/// A started watcher: the notify watcher plus the loop's state.
+struct WatchLoop {
+    home: PathBuf,
+    projects: PathBuf,
+    debounce_window: Duration,
+    syncer: Syncer,
+    metrics: Option<Metrics>,
+    dirs: WatchedDirs,
+    /// When the next retry of `dirs.unwatched` is due.
+    watch_retry_at: Option<Instant>,
+    /// Retries of `dirs.unwatched` so far, indexing the backoff.
+    watch_retry_attempt: usize,
+    /// `dirs.unwatched.len()` as last reported.
+    reported_unwatched: usize,
+    fs_events: mpsc::UnboundedReceiver<notify::Result<Event>>,
+    debounce: HashMap<PathBuf, Instant>,
+    generations: RetryGenerations,
+    only_codebase: Option<String>,
+}
+
+impl WatchLoop {
+    fn start(watch: Watch) -> Result<Self, DaemonError> {
+        let Watch {
+            home,
+            debounce,
+            syncer,
+            metrics,
+            only_codebase,
+            wrap_watcher,
+        } = watch;
+        let projects = projects_root(&home);
+        if !projects.exists() {
+            fs::create_dir_all(&projects).map_err(|e| io_err(&projects, e))?;
+        }
+
+        // Canonicalize so that FSEvents paths (which arrive as real paths, e.g.
+        // /private/var/... on macOS) match the `starts_with` checks below.
+        let projects = fs::canonicalize(&projects).unwrap_or(projects);
+
+        let (event_tx, fs_events) = mpsc::unbounded_channel::<notify::Result<Event>>();
+        let watcher: RecommendedWatcher = recommended_watcher(move |event| {
+            let _ = event_tx.send(event);
+        })?;
+
+        let mut dirs = WatchedDirs {
+            watcher: wrap_watcher(watcher),
+            watched: HashSet::new(),
+            unwatched: BTreeSet::new(),
+        };
+        register_projects_tree(&mut dirs, &projects)?;
+
+        // Also watch parent directories of all managed agent files.
+        register_managed_agent_dirs(&mut dirs, &home)?;
+
+        Ok(Self {
+            home,
+            projects,
+            debounce_window: debounce,
+            syncer,
+            metrics,
+            dirs,
+            watch_retry_at: None,
+            watch_retry_attempt: 0,
+            reported_unwatched: 0,
+            fs_events,
+            debounce: HashMap::new(),
+            generations: RetryGenerations::default(),
+            only_codebase,
+        })
+    }
+
+    async fn run(
+        mut self,
+        shutdown: impl Future<Output = ()>,
+        mut emit: impl FnMut(SyncEvent),
+    ) -> Result<(), DaemonError> {
+        // Retries run on their own tasks and report back through here.
+        let (retry_tx, mut retry_events) = mpsc::unbounded_channel::<SyncEvent>();
+        tokio::pin!(shutdown);
+        self.report_unwatched(&mut emit);
+
+        loop {
+            let watch_retry_at = self.watch_retry_at.unwrap_or_else(Instant::now);
+            tokio::select! {
+                _ = &mut shutdown => break,
+                Some(event) = retry_events.recv() => emit(event),
+                _ = tokio::time::sleep_until(watch_retry_at), if self.watch_retry_at.is_some() => {
+                    self.watch_retry_at = None;
+                    self.watch_retry_attempt += 1;
+                    self.dirs.retry_unwatched()?;
+                    self.report_unwatched(&mut emit);
+                }
+                event = self.fs_events.recv() => {
+                    let Some(event) = event else { break };
+                    let event = match event {
+                        Ok(event) => event,
+                        Err(err) => {
+                            tracing::warn!(error = %err, "watcher event error");
+                            continue;
+                        }
+                    };
+                    self.handle_event(event, &retry_tx, &mut emit).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_event(
+        &mut self,
+        event: Event,
+        retry_tx: &mpsc::UnboundedSender<SyncEvent>,
+        emit: &mut impl FnMut(SyncEvent),
+    ) -> Result<(), DaemonError> {
+        let event_kind = format!("{:?}", event.kind);
+
+        for action in event_actions(&event.kind, &event.paths) {
+            let (path, removal) = match action {
+                EventAction::Changed(path) => (path, false),
+                EventAction::Removed(path) => (path, true),
+            };
+            let path_key = canonical_event_path(&path);
+            if let Some(metrics) = &self.metrics {
+                metrics.event_received();
+            }
+            tracing::trace!(
+                event_kind = %event_kind,
+                path = %path.display(),
+                canonical_path = %path_key.display(),
+                "watcher event observed",
+            );
+
+            // FSEvents is directory-based; always register parent directory.
+            if let Some(watch_dir) = directory_to_watch(&path_key) {
+                if watch_dir.starts_with(&self.projects) && watch_dir.exists() {
+                    register_projects_tree(&mut self.dirs, &watch_dir)?;
+                    self.report_unwatched(emit);
+                }
+            }
+            if !self.is_watched_path(&path_key) {
+                continue;
+            }
+
+            if removal && !path_key.exists() {
+                let registry_entry = is_registry_yaml(&path_key, &self.projects)
+                    || (path_key.starts_with(&self.projects) && path_key.extension().is_none());
+                if registry_entry {
+                    // A codebase YAML, project.yaml or a whole project
+                    // directory went away: forget it, but do not sync.
+                    self.dirs.prune();
+                    self.report_unwatched(emit);
+                    // Cancel any pending retry for the removed codebase.
+                    bump_generation(&self.generations, &sync_target_for_path(&path_key));
+                    cancel_unregistered_retries(&self.home, &self.generations);
+                    emit(SyncEvent::RegistryRemoved { path: path_key });
+                }
+                continue;
+            }
+
+            if self.syncer.is_recent_own_write(&path_key).await {
+                tracing::trace!(
+                    path = %path_key.display(),
+                    "skipping watcher event for recent orchestra-owned write"
+                );
+                continue;
+            }
+
+            if !should_process_event_with_threshold(
+                &mut self.debounce,
+                &path_key,
+                Instant::now(),
+                self.debounce_window,
+            ) {
+                if let Some(metrics) = &self.metrics {
+                    metrics.event_debounced();
+                }
+                tracing::trace!(
+                    path = %path_key.display(),
+                    "watcher event suppressed by debounce"
+                );
+                continue;
+            }
+
+            if is_registry_yaml(&path_key, &self.projects) {
+                self.handle_registry_change(path_key, retry_tx, emit).await;
+            } else if is_managed_agent_file(&path_key, &self.home) {
+                // — Phase 05 path: agent file changed → check for writeback block —
+                let home_clone = self.home.clone();
+                let path_clone = path_key.clone();
+                // Run writeback in a blocking task to avoid blocking the async loop.
+                tokio::task::spawn_blocking(move || {
+                    match process_writeback(&home_clone, &path_clone) {
+                        Ok(outcome) if outcome.block_found => {
+                            tracing::info!(
+                                path = %path_clone.display(),
+                                commands_applied = outcome.apply_results.len(),
+                                parse_errors = outcome.parse_errors.len(),
+                                block_stripped = outcome.block_stripped,
+                                "writeback processed",
+                            );
+                        }
+                        Ok(_) => {
+                            // No block — normal agent file edit, ignore.
+                        }
+                        Err(err) => {
+                            tracing::warn!(error = %err, path = %path_clone.display(), "writeback failed");
+                        }
+                    }
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Log and emit a change in how many directories are unwatched, and keep
+    /// a retry scheduled while any are.
+    fn report_unwatched(&mut self, emit: &mut impl FnMut(SyncEvent)) {
+        let unwatched = self.dirs.unwatched.len();
+        if unwatched == 0 {
+            self.watch_retry_at = None;
+            self.watch_retry_attempt = 0;
+        } else if self.watch_retry_at.is_none() {
+            let last = WATCH_LIMIT_RETRY_BACKOFF.len() - 1;
+            let delay = WATCH_LIMIT_RETRY_BACKOFF[self.watch_retry_attempt.min(last)];
+            self.watch_retry_at = Some(Instant::now() + delay);
+        }
+        if unwatched == self.reported_unwatched {
+            return;
+        }
+        if unwatched > 0 {
+            tracing::error!(
+                unwatched_dirs = unwatched,
+                "could not watch {unwatched} directories because the OS watch limit is \
+                 exhausted; changes in them are missed until a retry succeeds. On Linux, \
+                 raise the limit with `sudo sysctl fs.inotify.max_user_watches=524288` \
+                 (persist it in /etc/sysctl.d/) — retrying with backoff",
+            );
+        } else {
+            tracing::info!("every directory is watched again");
+        }
+        self.reported_unwatched = unwatched;
+        emit(SyncEvent::WatchDegraded {
+            unwatched_dirs: unwatched,
+        });
+    }
+
+    /// Whether events for `path` are handled; see [`Watch::only_codebase`].
+    fn is_watched_path(&self, path: &Path) -> bool {
+        let Some(only) = &self.only_codebase else {
+            return true;
+        };
+        is_registry_yaml(path, &self.projects)
+            && matches!(sync_target_for_path(path), SyncTarget::Codebase(name) if &name == only)
+    }
+
+    /// — Phase 04 path: registry YAML changed → reload + sync —
+    async fn handle_registry_change(
+        &mut self,
+        path: PathBuf,
+        retry_tx: &mpsc::UnboundedSender<SyncEvent>,
+        emit: &mut impl FnMut(SyncEvent),
+    ) {
+        let check_path = path.clone();
+        let invalid = tokio::task::spawn_blocking(move || invalid_codebase_file(&check_path))
+            .await
+            .ok()
+            .flatten();
+        let target = sync_target_for_path(&path);
+        let generation = bump_generation(&self.generations, &target);
+        if let Some(err) = invalid {
+            // Retrying cannot fix a malformed file; report it once and wait
+            // for the next edit, which must not be swallowed by the debounce
+            // window.
+            self.debounce.remove(&path);
+            tracing::error!(
+                path = %path.display(),
+                "registry file is invalid; syncing resumes once it is fixed\n{err}"
+            );
+            emit(SyncEvent::RegistryInvalid {
+                path,
+                error: err.to_string(),
+            });
+            return;
+        }
+
+        emit(SyncEvent::RegistryChanged { path });
+        match self.syncer.sync(target.clone(), "watcher").await {
+            Ok(summary) => {
+                tracing::info!(
+                    target = %summary.target,
+                    written = summary.written,
+                    unchanged = summary.unchanged,
+                    duration_ms = summary.duration_ms,
+                    "watcher-triggered sync completed",
+                );
+                emit(SyncEvent::SyncCompleted(summary));
+            }
+            Err(DaemonError::ShuttingDown) => {
+                tracing::debug!(target = %target.label(), "watcher-triggered sync skipped: daemon is shutting down");
+            }
+            Err(err) => {
+                tracing::error!(error = %err, "watcher-triggered sync failed");
+                emit(SyncEvent::SyncFailed {
+                    target: target.label(),
+                    error: err.to_string(),
+                });
+                tokio::spawn(retry_watcher_sync(
+                    self.syncer.clone(),
+                    target,
+                    self.generations.clone(),
+                    generation,
+                    WATCHER_RETRY_BACKOFF,
+                    retry_tx.clone(),
+                ));
+            }
+        }
+    }
+}
+
+pub(crate) fn daemon_sync_runner(
+    home: &Path,
+    scope: SyncScope,
+) -> Result<Vec<SyncCodebaseResult>, SyncError> {
+    pipeline::run_from_with_progress(home, scope, false, None, SyncSource::Daemon, |event| {
+        log_sync_progress(&event)
+    })
+}
+
+/// Log each step of a daemon sync with structured fields as it happens.
+fn log_sync_progress(event: &SyncProgress) {
+    match event {
+        SyncProgress::CodebaseStarted { name, index, total } => {
+            tracing::debug!(codebase = %name, index, total, "codebase sync started");
+        }
+        SyncProgress::FileWritten { path } => {
+            tracing::debug!(path = %path.display(), "file written");
+        }
+        SyncProgress::CodebaseFinished {
+            name,
+            written,
+            unchanged,
+        } => {
+            tracing::info!(codebase = %name, written, unchanged, "codebase synced");
+        }
+        SyncProgress::CodebaseFailed { name, error } => {
+            tracing::warn!(codebase = %name, error = %error, "codebase sync failed");
+        }
+    }
+}
+
+/// Run `runner` for `target`, re-running it while the codebase is locked by
+/// another sync.
+pub(crate) async fn run_sync_with_lock_retry(
+    home: PathBuf,
+    target: SyncTarget,
+    runner: SyncRunner,
+) -> Result<Result<Vec<SyncCodebaseResult>, SyncError>, DaemonError> {
+    let mut sync_result = run_blocking_sync(home.clone(), target.clone(), runner).await?;
+    for delay in LOCKED_RETRY_BACKOFF {
+        let Err(SyncError::Locked { codebase }) = &sync_result else {
+            break;
+        };
+        tracing::info!(
+            codebase = %codebase,
+            retry_in_ms = delay.as_millis() as u64,
+            "codebase locked by another sync; retrying",
+        );
+        tokio::time::sleep(*delay).await;
+        sync_result = run_blocking_sync(home.clone(), target.clone(), runner).await?;
+    }
+    Ok(sync_result)
+}
+
+async fn run_blocking_sync(
+    home: PathBuf,
+    target: SyncTarget,
+    runner: SyncRunner,
+) -> Result<Result<Vec<SyncCodebaseResult>, SyncError>, DaemonError> {
+    tokio::task::spawn_blocking(move || runner(&home, target.scope()))
+        .await
+        .map_err(|err| DaemonError::Protocol(format!("sync task join error: {err}")))
+}
+
+pub(crate) fn build_sync_summary(
+    target: SyncTarget,
+    source: &'static str,
+    results: Vec<SyncCodebaseResult>,
+    duration: Duration,
+) -> SyncSummary {
+    let mut codebases = Vec::new();
+    let mut written = 0usize;
+    let mut unchanged = 0usize;
+
+    for result in results {
+        if let Some(err) = &result.post_sync_error {
+            tracing::warn!("'{}': {err}", result.codebase_name);
+        }
+        codebases.push(result.codebase_name);
+        for write in result.writes {
+            match write {
+                WriteResult::Written { .. } | WriteResult::WouldWrite { .. } => written += 1,
+                WriteResult::Unchanged { .. } | WriteResult::Ignored { .. } => unchanged += 1,
+            }
+        }
+    }
+
+    SyncSummary {
+        target: target.label(),
+        source: source.to_string(),
+        codebases,
+        written,
+        unchanged,
+        duration_ms: duration.as_millis(),
+        coalesced: 0,
+    }
+}
+
+pub(crate) fn bump_generation(generations: &RetryGenerations, target: &SyncTarget) -> u64 {
+    let mut guard = generations
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let generation = guard.entry(target.label()).or_default();
+    *generation += 1;
+    *generation
+}
+
+/// Supersede pending retries for every codebase that is no longer
+/// registered, e.g. after its whole project directory was deleted.
+fn cancel_unregistered_retries(home: &Path, generations: &RetryGenerations) {
+    let Ok(registered) = registry::list_codebases_at(home) else {
+        return;
+    };
+    let mut guard = generations
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    for (label, generation) in guard.iter_mut() {
+        let known = label == "all"
+            || registered
+                .iter()
+                .any(|(_, codebase)| codebase.name.0 == *label);
+        if !known {
+            *generation += 1;
+        }
+    }
+}
+
+fn current_generation(generations: &RetryGenerations, target: &SyncTarget) -> u64 {
+    let guard = generations
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    guard.get(&target.label()).copied().unwrap_or_default()
+}
+
+/// Retry a failed watcher-triggered sync, waiting `backoff[i]` before the
+/// i-th attempt. Stops on the first success, after the last delay, or as soon
+/// as a newer watcher event for the same target supersedes `generation`.
+/// Each attempt's outcome is sent to `events`.
+///
+/// Returns `true` if a retry succeeded.
+pub(crate) async fn retry_watcher_sync(
+    syncer: Syncer,
+    target: SyncTarget,
+    generations: RetryGenerations,
+    generation: u64,
+    backoff: &'static [Duration],
+    events: mpsc::UnboundedSender<SyncEvent>,
+) -> bool {
+    for (attempt, delay) in backoff.iter().enumerate() {
+        tokio::time::sleep(*delay).await;
+        if current_generation(&generations, &target) != generation {
+            tracing::debug!(
+                target = %target.label(),
+                "newer watcher event superseded pending sync retry"
+            );
+            return false;
+        }
+        match syncer.sync(target.clone(), "watcher-retry").await {
+            Ok(summary) => {
+                tracing::info!(
+                    target = %summary.target,
+                    attempt = attempt + 1,
+                    "watcher sync retry succeeded",
+                );
+                let _ = events.send(SyncEvent::SyncCompleted(summary));
+                return true;
+            }
+            Err(err) => {
+                tracing::warn!(
+                    target = %target.label(),
+                    attempt = attempt + 1,
+                    error = %err,
+                    "watcher sync retry failed",
+                );
+                let _ = events.send(SyncEvent::SyncFailed {
+                    target: target.label(),
+                    error: err.to_string(),
+                });
+            }
+        }
+    }
+    tracing::error!(
+        target = %target.label(),
+        retries = backoff.len(),
+        "giving up on watcher-triggered sync after retries"
+    );
+    false
+}
+
+/// The directory watcher and the directories it has, or was refused.
+struct WatchedDirs {
+    watcher: Box<dyn DirWatcher>,
+    watched: HashSet<PathBuf>,
+    /// Refused because the OS watch limit was reached; retried on
+    /// [`WATCH_LIMIT_RETRY_BACKOFF`].
+    unwatched: BTreeSet<PathBuf>,
+}
+
+impl WatchedDirs {
+    /// Watch `dir` unless it already is. Hitting the OS watch limit parks
+    /// it in `unwatched` instead of failing; other errors are returned.
+    fn watch(&mut self, dir: PathBuf, what: &str) -> Result<(), DaemonError> {
+        if self.watched.contains(&dir) {
+            return Ok(());
+        }
+        match self.watcher.watch_dir(&dir) {
+            Ok(()) => {
+                tracing::debug!(path = %dir.display(), "watching {what} directory");
+                self.unwatched.remove(&dir);
+                self.watched.insert(dir);
+            }
+            Err(err) if is_watch_limit(&err) => {
+                tracing::debug!(path = %dir.display(), error = %err, "watch limit reached");
+                self.unwatched.insert(dir);
+            }
+            // Removed since it was listed.
+            Err(err) if matches!(err.kind, notify::ErrorKind::PathNotFound) => {
+                self.unwatched.remove(&dir);
+            }
+            Err(err) => return Err(err.into()),
+        }
+        Ok(())
+    }
+
+    /// Try the unwatched directories again, forgetting removed ones.
+    fn retry_unwatched(&mut self) -> Result<(), DaemonError> {
+        let pending = std::mem::take(&mut self.unwatched);
+        for dir in pending.into_iter().filter(|dir| dir.exists()) {
+            self.watch(dir, "previously unwatched")?;
+        }
+        Ok(())
+    }
+
+    /// Stop watching directories that no longer exist.
+    fn prune(&mut self) {
+        let watcher = &mut self.watcher;
+        self.watched.retain(|dir| {
+            if dir.exists() {
+                return true;
+            }
+            // The OS usually drops the watch itself when the directory goes.
+            let _ = watcher.unwatch_dir(dir);
+            tracing::debug!(path = %dir.display(), "stopped watching removed directory");
+            false
+        });
+        self.unwatched.retain(|dir| dir.exists());
+    }
+}
+
+/// Whether `err` means the OS has no watches left to give: inotify's
+/// `max_user_watches` (`ENOSPC`) or open-file limits (`EMFILE`).
+fn is_watch_limit(err: &notify::Error) -> bool {
+    match &err.kind {
+        notify::ErrorKind::MaxFilesWatch => true,
+        notify::ErrorKind::Io(io) => matches!(io.raw_os_error(), Some(28) | Some(24)),
+        _ => false,
+    }
+}
+
+fn register_projects_tree(dirs: &mut WatchedDirs, root: &Path) -> Result<(), DaemonError> {
+    if !root.exists() {
+        fs::create_dir_all(root).map_err(|e| io_err(root, e))?;
+    }
+    for dir in collect_dirs(root)? {
+        let canonical = match fs::canonicalize(&dir) {
+            Ok(path) => path,
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => return Err(io_err(&dir, err)),
+        };
+        dirs.watch(canonical, "registry")?;
+    }
+    Ok(())
+}
+
+fn collect_dirs(root: &Path) -> Result<Vec<PathBuf>, DaemonError> {
+    let mut dirs = vec![root.to_path_buf()];
+    let mut cursor = 0;
+    while cursor < dirs.len() {
+        let current = dirs[cursor].clone();
+        cursor += 1;
+        let entries = match fs::read_dir(&current) {
+            Ok(entries) => entries,
+            Err(err) => {
+                if err.kind() == std::io::ErrorKind::NotFound {
+                    continue;
+                }
+                return Err(io_err(&current, err));
+            }
+        };
+        for entry in entries {
+            let entry = entry.map_err(|e| io_err(&current, e))?;
+            let ty = entry.file_type().map_err(|e| io_err(entry.path(), e))?;
+            if ty.is_dir() {
+                dirs.push(entry.path());
+            }
+        }
+    }
+    dirs.sort();
+    dirs.dedup();
+    Ok(dirs)
+}
+
+/// Watch all parent directories of managed agent files for all codebases.
+///
+/// Uses `managed_agent_paths` from `orchestra-sync` to enumerate every output
+/// path (CLAUDE.md, AGENTS.md, .cursor/rules/orchestra.mdc, etc.) across all
+/// registered codebases, then watches each unique parent directory.
+fn register_managed_agent_dirs(dirs: &mut WatchedDirs, home: &Path) -> Result<(), DaemonError> {
+    let all = match registry::list_codebases_lossy_at(home) {
+        Ok(listing) => listing.codebases,
+        Err(err) => {
+            tracing::warn!(error = %err, "could not list codebases for agent file watcher");
+            return Ok(());
+        }
+    };
+
+    let paths = managed_agent_paths(&all);
+    log_hash_path_alignment(home, &all, &paths);
+    let mut dirs_to_watch: Vec<PathBuf> = paths
+        .iter()
+        .filter_map(|p| p.parent().map(Path::to_path_buf))
+        .collect();
+    dirs_to_watch.sort();
+    dirs_to_watch.dedup();
+
+    for dir in dirs_to_watch {
+        if !dir.exists() {
+            // Directory may not exist yet if the codebase hasn't been synced.
+            continue;
+        }
+        let canonical = match fs::canonicalize(&dir) {
+            Ok(path) => path,
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => return Err(io_err(&dir, err)),
+        };
+        dirs.watch(canonical, "agent file")?;
+    }
+    Ok(())
+}
+
+fn managed_paths_for_target(home: &Path, target: &SyncTarget) -> Result<Vec<PathBuf>, DaemonError> {
+    let all = registry::list_codebases_lossy_at(home)?.codebases;
+    let selected: Vec<(ProjectName, Codebase)> = match target {
+        SyncTarget::All => all,
+        SyncTarget::Codebase(name) => all
+            .into_iter()
+            .filter(|(_, codebase)| codebase.name.0 == *name)
+            .collect(),
+    };
+    Ok(managed_agent_paths(&selected))
+}
+
+/// Record the files a sync of `target` is about to write so their watcher
+/// events are not mistaken for user edits.
+pub(crate) async fn mark_target_own_writes(
+    home: &Path,
+    own_writes: &std::sync::Arc<RwLock<OwnWrites>>,
+    target: &SyncTarget,
+) {
+    match managed_paths_for_target(home, target) {
+        Ok(paths) => mark_own_writes(own_writes, paths, Instant::now()).await,
+        Err(err) => tracing::warn!(
+            error = %err,
+            target = %target.label(),
+            "unable to pre-register own write paths before sync"
+        ),
+    }
+}
+
+async fn mark_own_writes(
+    own_writes: &std::sync::Arc<RwLock<OwnWrites>>,
+    paths: Vec<PathBuf>,
+    now: Instant,
+) {
+    let mut guard = own_writes.write().await;
+    guard.retain(|_, seen_at| now.duration_since(*seen_at) <= OWN_WRITE_SUPPRESS_WINDOW);
+    for path in paths {
+        let canonical = fs::canonicalize(&path).unwrap_or(path);
+        guard.insert(canonical, now);
+    }
+}
+
+async fn is_recent_own_write(
+    own_writes: &std::sync::Arc<RwLock<OwnWrites>>,
+    path: &Path,
+    now: Instant,
+) -> bool {
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut guard = own_writes.write().await;
+    guard.retain(|_, seen_at| now.duration_since(*seen_at) <= OWN_WRITE_SUPPRESS_WINDOW);
+    guard.contains_key(&canonical)
+}
+
+fn log_hash_path_alignment(
+    home: &Path,
+    all: &[(ProjectName, Codebase)],
+    managed_paths: &[PathBuf],
+) {
+    tracing::info!(
+        managed_paths = managed_paths.len(),
+        "registered managed agent output paths for watcher"
+    );
+
+    for managed_path in managed_paths {
+        tracing::trace!(path = %managed_path.display(), "managed agent output path");
+    }
+
+    for (_project, codebase) in all {
+        let store = match hash_store::load_at(home, &codebase.name.0) {
+            Ok(store) => store,
+            Err(err) => {
+                tracing::debug!(
+                    codebase = %codebase.name.0,
+                    error = %err,
+                    "no readable hash store yet for codebase"
+                );
+                continue;
+            }
+        };
+
+        for hash_path in store.files.keys() {
+            let matches_managed = managed_paths.iter().any(|managed| {
+                let raw = managed.to_string_lossy();
+                let canonical = fs::canonicalize(managed).unwrap_or_else(|_| managed.clone());
+                let canonical = canonical.to_string_lossy();
+                hash_path.as_str() == raw.as_ref() || hash_path.as_str() == canonical.as_ref()
+            });
+
+            if !matches_managed {
+                tracing::warn!(
+                    codebase = %codebase.name.0,
+                    hash_path = %hash_path,
+                    "hash store entry does not match any managed watcher path"
+                );
+            }
+        }
+    }
+}
+
+/// Returns `true` if `path` is a managed agent file output (CLAUDE.md,
+/// AGENTS.md, etc.) for any registered codebase in `home`.
+///
+/// Uses a canonical path comparison so symlinks/private/var paths match.
+fn is_managed_agent_file(path: &Path, home: &Path) -> bool {
+    let all = match registry::list_codebases_lossy_at(home) {
+        Ok(listing) => listing.codebases,
+        Err(_) => return false,
+    };
+    let canonical_path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let managed = managed_agent_paths(&all);
+    for managed_path in &managed {
+        let canonical_managed =
+            fs::canonicalize(managed_path).unwrap_or_else(|_| managed_path.clone());
+        if canonical_path == canonical_managed {
+            return true;
+        }
+    }
+    false
+}
+
+/// How the watcher treats one path reported by an event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum EventAction {
+    /// The path was created or written.
+    Changed(PathBuf),
+    /// The path may be gone. One that still exists on disk (replaced in
+    /// place, or a macOS rename naming both ends) is handled as a change.
+    Removed(PathBuf),
+}
+
+/// The paths of `kind` worth acting on. Editors that save atomically write
+/// a temp or swap file and rename it over the original, so those names are
+/// dropped, and a rename that carries both ends maps to its destination.
+fn event_actions(kind: &EventKind, paths: &[PathBuf]) -> Vec<EventAction> {
+    if !is_relevant_event_kind(kind) {
+        return Vec::new();
+    }
+    let paths = match (kind, paths) {
+        (EventKind::Modify(ModifyKind::Name(RenameMode::Both)), [_, to]) => {
+            std::slice::from_ref(to)
+        }
+        _ => paths,
+    };
+    let removal = is_removal_event_kind(kind);
+    paths
+        .iter()
+        .filter(|path| !is_editor_temp_file(path))
+        .map(|path| {
+            if removal {
+                EventAction::Removed(path.clone())
+            } else {
+                EventAction::Changed(path.clone())
+            }
+        })
+        .collect()
+}
+
+/// Creates, modifications (renames included) and removals. `Any` is sent by
+/// backends that cannot tell what happened, so it counts too.
+fn is_relevant_event_kind(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Any | EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    )
+}
+
+/// Deletions and renames that may have moved a path away: the source half
+/// of a rename, and the unspecified renames and `Any` events FSEvents sends.
+fn is_removal_event_kind(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Any
+            | EventKind::Remove(_)
+            | EventKind::Modify(ModifyKind::Name(
+                RenameMode::From | RenameMode::Any | RenameMode::Other
+            ))
+    )
+}
+
+/// Editor scratch files: `x.yaml.tmp`, Vim's `.x.yaml.swp` and `x.yaml~`,
+/// and Emacs lock files `.#x.yaml`.
+fn is_editor_temp_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    name.starts_with(".#")
+        || name.ends_with('~')
+        || [".tmp", ".swp"].iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// Canonicalize an event path. Deleted paths cannot be canonicalized, so
+/// fall back to the canonical parent joined with the file name.
+fn canonical_event_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = fs::canonicalize(path) {
+        return canonical;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => fs::canonicalize(parent)
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn is_registry_yaml(path: &Path, projects: &Path) -> bool {
+    path.starts_with(projects)
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.eq_ignore_ascii_case("yaml"))
+            .unwrap_or(false)
+}
+
+/// The parse error for a changed codebase YAML, if it is malformed.
+///
+/// `project.yaml` and files that no longer exist are not checked.
+fn invalid_codebase_file(path: &Path) -> Option<RegistryError> {
+    if path.file_name().and_then(|name| name.to_str()) == Some("project.yaml") || !path.is_file() {
+        return None;
+    }
+    registry::check_codebase_file(path).err()
+}
+
+pub(crate) fn sync_target_for_path(path: &Path) -> SyncTarget {
+    if path.file_name().and_then(|name| name.to_str()) == Some("project.yaml") {
+        return SyncTarget::All;
+    }
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(|name| SyncTarget::Codebase(name.to_string()))
+        .unwrap_or(SyncTarget::All)
+}
+
+fn directory_to_watch(path: &Path) -> Option<PathBuf> {
+    if path.is_dir() {
+        Some(path.to_path_buf())
+    } else {
+        path.parent().map(Path::to_path_buf)
+    }
+}
+
+fn should_process_event_with_threshold(
+    debounce: &mut HashMap<PathBuf, Instant>,
+    path: &Path,
+    now: Instant,
+    threshold: Duration,
+) -> bool {
+    debounce.retain(|_, seen_at| now.duration_since(*seen_at) <= Duration::from_secs(30));
+    match debounce.get(path) {
+        Some(last_seen) if now.duration_since(*last_seen) < threshold => false,
+        _ => {
+            debounce.insert(path.to_path_buf(), now);
+            true
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use orchestra_core::types::{CodebaseName, ProjectType};
+    use tempfile::TempDir;
+    use tokio::time::advance;
+
+    #[tokio::test(start_paused = true, flavor = "current_thread")]
+    async fn debounce_coalesces_rapid_events() {
+        let threshold = Duration::from_millis(100);
+        let mut debounce = HashMap::<PathBuf, Instant>::new();
+        let path = PathBuf::from("/tmp/copenow_api.yaml");
+        let mut sync_triggers = 0usize;
+
+        for _ in 0..5 {
+            if should_process_event_with_threshold(&mut debounce, &path, Instant::now(), threshold)
+            {
+                sync_triggers += 1;
+            }
+            advance(Duration::from_millis(10)).await;
+        }
+
+        advance(Duration::from_millis(150)).await;
+        assert_eq!(
+            sync_triggers, 1,
+            "rapid saves should collapse to one sync trigger"
+        );
+    }
+
+    #[test]
+    fn event_shapes_map_to_expected_actions() {
+        use notify::event::{AccessKind, AccessMode, CreateKind, DataChange, RemoveKind};
+        use EventAction::{Changed, Removed};
+
+        let yaml = PathBuf::from("/home/u/.orchestra/projects/copnow/copnow_api.yaml");
+        let sibling = |name: &str| yaml.with_file_name(name);
+        let tmp = sibling("copnow_api.yaml.tmp");
+        let swp = sibling(".copnow_api.yaml.swp");
+        let backup = sibling("copnow_api.yaml~");
+        let lock = sibling(".#copnow_api.yaml");
+        let rename = |mode| EventKind::Modify(ModifyKind::Name(mode));
+
+        let cases: Vec<(&str, EventKind, Vec<PathBuf>, Vec<EventAction>)> = vec![
+            // Linux inotify: write a temp file, then rename it over the original.
+            (
+                "inotify create tmp",
+                EventKind::Create(CreateKind::File),
+                vec![tmp.clone()],
+                vec![],
+            ),
+            (
+                "inotify write tmp",
+                EventKind::Modify(ModifyKind::Data(DataChange::Any)),
+                vec![tmp.clone()],
+                vec![],
+            ),
+            (
+                "inotify rename from tmp",
+                rename(RenameMode::From),
+                vec![tmp.clone()],
+                vec![],
+            ),
+            (
+                "inotify rename to yaml",
+                rename(RenameMode::To),
+                vec![yaml.clone()],
+                vec![Changed(yaml.clone())],
+            ),
+            (
+                "inotify rename both",
+                rename(RenameMode::Both),
+                vec![tmp.clone(), yaml.clone()],
+                vec![Changed(yaml.clone())],
+            ),
+            (
+                "inotify rename yaml away",
+                rename(RenameMode::From),
+                vec![yaml.clone()],
+                vec![Removed(yaml.clone())],
+            ),
+            (
+                "inotify in-place write",
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                vec![yaml.clone()],
+                vec![Changed(yaml.clone())],
+            ),
+            (
+                "inotify close after write",
+                EventKind::Access(AccessKind::Close(AccessMode::Write)),
+                vec![yaml.clone()],
+                vec![],
+            ),
+            (
+                "inotify remove swap file",
+                EventKind::Remove(RemoveKind::File),
+                vec![swp.clone()],
+                vec![],
+            ),
+            // macOS FSEvents: renames name one end each, with no direction.
+            (
+                "fsevents rename tmp",
+                rename(RenameMode::Any),
+                vec![tmp.clone()],
+                vec![],
+            ),
+            (
+                "fsevents rename yaml",
+                rename(RenameMode::Any),
+                vec![yaml.clone()],
+                vec![Removed(yaml.clone())],
+            ),
+            (
+                "fsevents create yaml",
+                EventKind::Create(CreateKind::File),
+                vec![yaml.clone()],
+                vec![Changed(yaml.clone())],
+            ),
+            (
+                "fsevents unknown event",
+                EventKind::Any,
+                vec![yaml.clone()],
+                vec![Removed(yaml.clone())],
+            ),
+            (
+                "editor backup and lock files",
+                EventKind::Create(CreateKind::File),
+                vec![backup, lock],
+                vec![],
+            ),
+        ];
+
+        for (name, kind, paths, expected) in cases {
+            assert_eq!(event_actions(&kind, &paths), expected, "{name}");
+        }
+    }
+
+    fn init_codebase(home: &TempDir, workspace: &TempDir, name: &str) -> PathBuf {
+        let project = ProjectName::from("copnow");
+        let path = workspace.path().join(name);
+        fs::create_dir_all(&path).expect("create codebase dir");
+        registry::init_at(
+            path,
+            project.clone(),
+            Some(ProjectType::Backend),
+            home.path(),
+        )
+        .expect("init codebase");
+        registry::codebase_path_at(home.path(), &project, &CodebaseName::from(name))
+    }
+
+    #[test]
+    fn malformed_codebase_yaml_is_reported_before_sync() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        let yaml = init_codebase(&home, &workspace, "copnow_api");
+
+        assert!(invalid_codebase_file(&yaml).is_none());
+        fs::write(&yaml, "name: copnow_api\nprojects: [unclosed\nnotes: []\n").expect("corrupt");
+        let err = invalid_codebase_file(&yaml).expect("malformed file is reported");
+        assert!(
+            matches!(err, RegistryError::ParseDetailed { line: 3, .. }),
+            "got: {err}"
+        );
+        assert!(invalid_codebase_file(&yaml.with_file_name("project.yaml")).is_none());
+    }
+
+    #[tokio::test]
+    async fn newer_watcher_event_cancels_pending_retry() {
+        let home = TempDir::new().expect("home");
+        let (sync_tx, mut sync_rx) = mpsc::channel::<SyncJob>(8);
+        let syncer = Syncer::queued(home.path().to_path_buf(), sync_tx, Default::default());
+        let (events_tx, mut events) = mpsc::unbounded_channel();
+        let target = SyncTarget::Codebase("copnow_api".to_string());
+        let generations = RetryGenerations::default();
+        let stale = bump_generation(&generations, &target);
+        bump_generation(&generations, &target);
+
+        const BACKOFF: &[Duration] = &[Duration::from_millis(10)];
+        let recovered =
+            retry_watcher_sync(syncer, target, generations, stale, BACKOFF, events_tx).await;
+        assert!(!recovered);
+        assert!(
+            sync_rx.try_recv().is_err(),
+            "superseded retry must not enqueue"
+        );
+        assert!(events.try_recv().is_err(), "superseded retry emits nothing");
+    }
+
+    fn add_convention(home: &TempDir, codebase_name: &str, convention: &str) {
+        let project = ProjectName::from("copnow");
+        let mut codebase =
+            registry::load_codebase_at(home.path(), &project, &CodebaseName::from(codebase_name))
+                .expect("load codebase");
+        codebase.conventions.push(convention.to_string());
+        registry::save_codebase_at(home.path(), &project, &codebase).expect("save codebase");
+    }
+
+    #[test]
+    fn only_codebase_watch_syncs_that_codebase_until_shutdown() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_codebase(&home, &workspace, "copnow_api");
+        init_codebase(&home, &workspace, "copnow_web");
+
+        let (stop_tx, stop_rx) = oneshot::channel::<()>();
+        let (events_tx, events_rx) = std::sync::mpsc::channel::<SyncEvent>();
+        let watch = Watch::new(home.path())
+            .with_debounce(Duration::from_millis(50))
+            .only_codebase("copnow_api");
+        let thread = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("runtime");
+            runtime.block_on(watch.run_until(
+                async {
+                    let _ = stop_rx.await;
+                },
+                move |event| {
+                    let _ = events_tx.send(event);
+                },
+            ))
+        });
+        // Give the watcher time to register before editing.
+        std::thread::sleep(Duration::from_millis(300));
+
+        add_convention(&home, "copnow_web", "ignored-sentinel");
+        add_convention(&home, "copnow_api", "watched-sentinel");
+        let claude_md = |name: &str| {
+            workspace
+                .path()
+                .join(name)
+                .join("orchestra/controls/CLAUDE.md")
+        };
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while !fs::read_to_string(claude_md("copnow_api"))
+            .is_ok_and(|content| content.contains("watched-sentinel"))
+        {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "watched codebase was not re-synced"
+            );
+            std::thread::sleep(Duration::from_millis(50));
+        }
+
+        stop_tx.send(()).expect("signal shutdown");
+        thread
+            .join()
+            .expect("watch thread")
+            .expect("watch stops cleanly");
+
+        assert!(
+            !claude_md("copnow_web").exists(),
+            "other codebase untouched"
+        );
+        let events: Vec<SyncEvent> = events_rx.try_iter().collect();
+        assert!(
+            events
+                .iter()
+                .all(|event| !format!("{event:?}").contains("copnow_web")),
+            "{events:?}"
+        );
+        assert!(events.iter().any(
+            |event| matches!(event, SyncEvent::SyncCompleted(summary) if summary.target == "copnow_api")
+        ));
+    }
+
+    /// Collect events until `done` matches one, failing after `timeout`.
+    async fn collect_until(
+        watch: &mut WatchHandle,
+        timeout: Duration,
+        done: impl Fn(&SyncEvent) -> bool,
+    ) -> Vec<SyncEvent> {
+        let mut events = Vec::new();
+        let deadline = Instant::now() + timeout;
+        while let Ok(Some(event)) = tokio::time::timeout_at(deadline, watch.next()).await {
+            let finished = done(&event);
+            events.push(event);
+            if finished {
+                return events;
+            }
+        }
+        panic!("watcher did not emit the expected event; got {events:?}");
+    }
+
+    #[tokio::test]
+    async fn registry_edit_emits_change_then_completed_sync() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        let yaml = init_codebase(&home, &workspace, "copnow_api");
+        let mut watch = Watch::new(home.path())
+            .with_debounce(Duration::from_millis(50))
+            .spawn()
+            .expect("start watcher");
+
+        let mut codebase = registry::load_codebase_at(
+            home.path(),
+            &ProjectName::from("copnow"),
+            &CodebaseName::from("copnow_api"),
+        )
+        .expect("load codebase");
+        codebase.notes.push("watch-sentinel".to_string());
+        registry::save_codebase_at(home.path(), &ProjectName::from("copnow"), &codebase)
+            .expect("save codebase");
+
+        let events = collect_until(&mut watch, Duration::from_secs(10), |event| {
+            matches!(event, SyncEvent::SyncCompleted(_))
+        })
+        .await;
+        let changed = events
+            .iter()
+            .position(|e| matches!(e, SyncEvent::RegistryChanged { path } if path.ends_with("copnow_api.yaml")))
+            .expect("registry change reported");
+        let Some(SyncEvent::SyncCompleted(summary)) = events.last() else {
+            unreachable!()
+        };
+        assert!(
+            changed < events.len() - 1,
+            "change precedes the sync: {events:?}"
+        );
+        assert_eq!(summary.codebases, vec!["copnow_api".to_string()]);
+        assert_eq!(summary.source, "watcher");
+        assert!(summary.written > 0);
+        let claude_md = workspace
+            .path()
+            .join("copnow_api/orchestra/controls/CLAUDE.md");
+        assert!(claude_md.exists(), "sync wrote agent files");
+
+        // Wait out the debounce window, then replace the file atomically so
+        // the watcher never sees it half-written.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let tmp = yaml.with_extension("yaml.tmp");
+        fs::write(&tmp, "name: copnow_api\nprojects: [unclosed\nnotes: []\n")
+            .expect("corrupt yaml");
+        fs::rename(&tmp, &yaml).expect("replace yaml");
+        let events = collect_until(&mut watch, Duration::from_secs(10), |event| {
+            matches!(event, SyncEvent::RegistryInvalid { .. })
+        })
+        .await;
+        let Some(SyncEvent::RegistryInvalid { error, .. }) = events.last() else {
+            unreachable!()
+        };
+        assert!(error.contains("line 3"), "{error}");
+
+        fs::remove_file(&yaml).expect("remove yaml");
+        collect_until(&mut watch, Duration::from_secs(10), |event| {
+            matches!(event, SyncEvent::RegistryRemoved { path } if path.ends_with("copnow_api.yaml"))
+        })
+        .await;
+
+        watch.stop().await.expect("stop watcher");
+    }
+
+    #[tokio::test]
+    async fn atomic_save_triggers_one_sync_for_the_codebase() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        let yaml = init_codebase(&home, &workspace, "copnow_api");
+        let mut watch = Watch::new(home.path())
+            .with_debounce(Duration::from_millis(200))
+            .spawn()
+            .expect("start watcher");
+
+        // Save the way editors do: write a temp file, then rename it over
+        // the original.
+        let contents = fs::read_to_string(&yaml).expect("read yaml");
+        let tmp = yaml.with_extension("yaml.tmp");
+        fs::write(&tmp, contents.replace("notes: []", "notes:\n- saved")).expect("write tmp");
+        fs::rename(&tmp, &yaml).expect("rename over yaml");
+
+        let mut events = collect_until(&mut watch, Duration::from_secs(10), |event| {
+            matches!(
+                event,
+                SyncEvent::SyncCompleted(_) | SyncEvent::SyncFailed { .. }
+            )
+        })
+        .await;
+        // Anything else the save produced arrives well within this window.
+        let quiet_until = Instant::now() + Duration::from_millis(750);
+        while let Ok(Some(event)) = tokio::time::timeout_at(quiet_until, watch.next()).await {
+            events.push(event);
+        }
+        watch.stop().await.expect("stop watcher");
+
+        let syncs: Vec<&SyncSummary> = events
+            .iter()
+            .filter_map(|event| match event {
+                SyncEvent::SyncCompleted(summary) => Some(summary),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(syncs.len(), 1, "{events:?}");
+        assert_eq!(syncs[0].codebases, vec!["copnow_api".to_string()]);
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, SyncEvent::SyncFailed { .. })),
+            "{events:?}"
+        );
+    }
+    /// Refuses every watch with inotify's `ENOSPC` while
+    /// `WATCH_LIMIT_EXHAUSTED` is set.
+    struct LimitedWatcher(RecommendedWatcher);
+
+    static WATCH_LIMIT_EXHAUSTED: std::sync::atomic::AtomicBool =
+        std::sync::atomic::AtomicBool::new(true);
+
+    impl DirWatcher for LimitedWatcher {
+        fn watch_dir(&mut self, dir: &Path) -> notify::Result<()> {
+            if WATCH_LIMIT_EXHAUSTED.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err(notify::Error::new(notify::ErrorKind::MaxFilesWatch));
+            }
+            self.0.watch_dir(dir)
+        }
+
+        fn unwatch_dir(&mut self, dir: &Path) -> notify::Result<()> {
+            self.0.unwatch_dir(dir)
+        }
+    }
+
+    #[tokio::test]
+    async fn exhausted_watch_limit_degrades_then_recovers() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_codebase(&home, &workspace, "copnow_api");
+        let mut watch = Watch::new(home.path())
+            .with_debounce(Duration::from_millis(50))
+            .with_watcher_wrapper(|watcher| Box::new(LimitedWatcher(watcher)))
+            .spawn()
+            .expect("watcher starts despite the watch limit");
+
+        let events = collect_until(&mut watch, Duration::from_secs(10), |event| {
+            matches!(event, SyncEvent::WatchDegraded { .. })
+        })
+        .await;
+        let Some(SyncEvent::WatchDegraded { unwatched_dirs }) = events.last() else {
+            unreachable!()
+        };
+        assert!(*unwatched_dirs > 0, "{events:?}");
+
+        // The limit is raised: the next retry watches everything.
+        WATCH_LIMIT_EXHAUSTED.store(false, std::sync::atomic::Ordering::SeqCst);
+        collect_until(&mut watch, Duration::from_secs(10), |event| {
+            matches!(event, SyncEvent::WatchDegraded { unwatched_dirs: 0 })
+        })
+        .await;
+
+        add_convention(&home, "copnow_api", "after-recovery");
+        collect_until(&mut watch, Duration::from_secs(10), |event| {
+            matches!(event, SyncEvent::SyncCompleted(_))
+        })
+        .await;
+        watch.stop().await.expect("stop watcher");
+    }
+}
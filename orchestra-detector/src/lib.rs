@@ -3,12 +3,23 @@
 //! `detect_stack(path)` inspects indicator files in a codebase root and returns
 //! the primary language, framework, and project category. Checks are ordered by
 //! specificity: language-specific manifest files take priority over generic ones.
+//!
+//! `detect_stack_with(path, &options)` additionally descends into
+//! subdirectories up to `options.max_depth`, skipping vendored and build
+//! directories plus anything matched by the codebase's `.orchestraignore`.
+//!
+//! An indicator file that exists but cannot be read (bad permissions, a
+//! dangling symlink, invalid UTF-8) does not fail detection: it is skipped
+//! and reported by `detect_stack_detailed` as a [`DetectWarning`].
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use std::fs;
 
 use orchestra_core::types::ProjectType;
+use serde::Serialize;
 use thiserror::Error;
 
 // ---------------------------------------------------------------------------
@@ -16,7 +27,8 @@ use thiserror::Error;
 // ---------------------------------------------------------------------------
 
 /// Confidence level of a detected stack.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Confidence {
     /// Definitive indicator file with content match.
     High,
@@ -25,7 +37,7 @@ pub enum Confidence {
 }
 
 /// A detected technology stack for a codebase.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct DetectedStack {
     /// Primary programming language (e.g. `"Rust"`, `"TypeScript"`).
     pub primary_language: String,
@@ -35,6 +47,71 @@ pub struct DetectedStack {
     pub project_type: ProjectType,
     /// Detection confidence.
     pub confidence: Confidence,
+    /// Where the framework was found when it is not the root manifest, e.g.
+    /// the workspace member `"apps/web"`.
+    pub detail: Option<String>,
+    /// Flavour of the framework when it matters for guidance, e.g.
+    /// `"Rails (API-only)"` or `"Rails + Hotwire"`.
+    pub variant: Option<String>,
+    /// Package manager inferred from lock or requirements files (e.g.
+    /// `"poetry"`, `"uv"`, `"pip"`, `"pnpm"`, `"composer"`), for suggesting
+    /// install/run commands.
+    pub package_manager: Option<String>,
+}
+
+/// The machine-readable detection result: `orchestra detect --json` prints
+/// it and the `detect` protocol request answers with it.
+#[derive(Debug, Clone, Serialize)]
+pub struct DetectionReport {
+    /// The directory that was inspected.
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub stack: DetectedStack,
+    /// Root indicator files, as [`indicator_files`] lists them.
+    pub indicators: Vec<String>,
+}
+
+impl DetectionReport {
+    /// Report `stack`, detected at `path`, with the indicator files there.
+    pub fn new(path: &Path, stack: DetectedStack) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            indicators: indicator_files(path),
+            stack,
+        }
+    }
+}
+
+impl DetectedStack {
+    /// Conventional build/test/lint commands for the detected language, for
+    /// seeding a new codebase's project commands. Empty when there is no
+    /// obvious default.
+    pub fn default_commands(&self) -> BTreeMap<String, String> {
+        let commands: &[(&str, &str)] = match self.primary_language.as_str() {
+            "Rust" => &[
+                ("build", "cargo build --workspace"),
+                ("lint", "cargo clippy --workspace --all-targets"),
+                ("test", "cargo test --workspace"),
+            ],
+            "Go" => &[
+                ("build", "go build ./..."),
+                ("lint", "go vet ./..."),
+                ("test", "go test ./..."),
+            ],
+            "TypeScript" | "JavaScript" => &[("test", "npm test")],
+            "Python" => match self.package_manager.as_deref() {
+                Some("poetry") => &[("test", "poetry run pytest")],
+                Some("uv") => &[("test", "uv run pytest")],
+                _ => &[("test", "pytest")],
+            },
+            "Elixir" => &[("test", "mix test")],
+            _ => &[],
+        };
+        commands
+            .iter()
+            .map(|(name, run)| (name.to_string(), run.to_string()))
+            .collect()
+    }
 }
 
 /// A discovered agent file or directory inside a codebase.
@@ -50,6 +127,87 @@ pub struct AgentFileHit {
     pub is_subagent: bool,
 }
 
+/// An indicator file or directory that was skipped because it could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectWarning {
+    /// The unreadable path.
+    pub path: PathBuf,
+    /// Why it could not be used.
+    pub message: String,
+}
+
+impl fmt::Display for DetectWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "skipped {}: {}", self.path.display(), self.message)
+    }
+}
+
+/// Directory names that are never scanned, regardless of ignore patterns.
+pub const ALWAYS_IGNORED: &[&str] = &["node_modules", "vendor", "target", "dist", ".git"];
+
+/// Name of the optional ignore file read from the codebase root.
+pub const IGNORE_FILE: &str = ".orchestraignore";
+
+/// Root-level files whose contents decide what [`detect_stack`] reports.
+/// Files matched by extension are listed in [`INDICATOR_EXTENSIONS`].
+/// A detector that starts reading another root file adds it here, so that
+/// `detect` and the `detection_outdated` check see it too.
+pub const INDICATOR_FILES: &[&str] = &[
+    "Cargo.toml",
+    "Chart.yaml",
+    "Gemfile",
+    "Package.swift",
+    "Pipfile",
+    "Pulumi.yaml",
+    "ansible.cfg",
+    "build.gradle",
+    "build.gradle.kts",
+    "build.zig",
+    "build.zig.zon",
+    "composer.json",
+    "dune-project",
+    "go.mod",
+    "mix.exs",
+    "package.json",
+    "playbook.yml",
+    "pnpm-workspace.yaml",
+    "pom.xml",
+    "poetry.lock",
+    "pubspec.yaml",
+    "pyproject.toml",
+    "requirements.txt",
+    "settings.gradle",
+    "settings.gradle.kts",
+    "setup.py",
+    "terragrunt.hcl",
+    "tsconfig.json",
+    "uv.lock",
+];
+
+/// Extensions of root-level indicator files, e.g. Terraform's `*.tf`.
+pub const INDICATOR_EXTENSIONS: &[&str] = &["csproj", "fsproj", "nimble", "opam", "sln", "tf"];
+
+/// Tuning knobs for [`detect_stack_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectorOptions {
+    /// Number of directory levels to inspect. `1` checks only the codebase
+    /// root; `2` also checks its immediate subdirectories, and so on.
+    pub max_depth: usize,
+    /// Extra glob patterns (`*` and `?` wildcards) for paths to skip, matched
+    /// against both the entry name and its root-relative path. Combined with
+    /// [`ALWAYS_IGNORED`] and the lines of `.orchestraignore`.
+    pub ignore_patterns: Vec<String>,
+}
+
+impl Default for DetectorOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 1,
+            ignore_patterns: Vec::new(),
+        }
+    }
+}
+
 /// Errors from stack detection.
 #[derive(Debug, Error)]
 pub enum DetectError {
@@ -72,22 +230,105 @@ pub enum DetectError {
 /// Checks indicator files in priority order. Returns `DetectError::UnknownStack`
 /// if no known stack can be inferred.
 pub fn detect_stack(path: &Path) -> Result<DetectedStack, DetectError> {
-    // Priority: specific manifests first, generic (package.json, requirements) last.
-    if let Some(s) = detect_php(path)? { return Ok(s); }
-    if let Some(s) = detect_dart_flutter(path)? { return Ok(s); }
-    if let Some(s) = detect_rust_crate(path)? { return Ok(s); }
-    if let Some(s) = detect_go(path)? { return Ok(s); }
-    if let Some(s) = detect_elixir(path)? { return Ok(s); }
-    if let Some(s) = detect_jvm(path)? { return Ok(s); }
-    if let Some(s) = detect_dotnet(path)? { return Ok(s); }
-    if let Some(s) = detect_ruby(path)? { return Ok(s); }
-    if let Some(s) = detect_swift(path)? { return Ok(s); }
-    if let Some(s) = detect_javascript(path)? { return Ok(s); }
-    if let Some(s) = detect_python(path)? { return Ok(s); }
+    detect_stack_with(path, &DetectorOptions::default())
+}
+
+/// Detect the technology stack of the codebase at `path` using `options`.
+///
+/// Directories are visited breadth-first in name order, so a stack at the
+/// root always wins over one in a subdirectory. Ignored directories (see
+/// [`DetectorOptions::ignore_patterns`]) are never descended into.
+pub fn detect_stack_with(
+    path: &Path,
+    options: &DetectorOptions,
+) -> Result<DetectedStack, DetectError> {
+    detect_stack_detailed(path, options).map(|(stack, _)| stack)
+}
+
+/// Like [`detect_stack_with`], but also returns the indicator files that were
+/// skipped because they could not be read.
+///
+/// Only an unreadable `path` itself is an error; unreadable indicator files
+/// and subdirectories are skipped so the remaining detectors still run.
+pub fn detect_stack_detailed(
+    path: &Path,
+    options: &DetectorOptions,
+) -> Result<(DetectedStack, Vec<DetectWarning>), DetectError> {
+    fs::read_dir(path)?;
+    let mut warnings = Vec::new();
+    let ignore = IgnoreMatcher::load(path, options, &mut warnings);
+
+    let mut level = vec![path.to_path_buf()];
+    for depth in 1..=options.max_depth {
+        let mut next = Vec::new();
+        for dir in &level {
+            if let Some(s) = detect_in_dir(path, dir, &ignore, &mut warnings)? {
+                return Ok((s, warnings));
+            }
+            if depth < options.max_depth {
+                next.extend(child_dirs(path, dir, &ignore, &mut warnings));
+            }
+        }
+        level = next;
+    }
 
     Err(DetectError::UnknownStack { path: path.to_path_buf() })
 }
 
+/// Indicator files present directly under `path`, as sorted file names.
+///
+/// Recording these with a detection result lets a caller notice later that
+/// the manifests have changed and the stored stack may be outdated.
+pub fn indicator_files(path: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(path) else { return Vec::new() };
+    let mut files: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| {
+            INDICATOR_FILES.contains(&name.as_str())
+                || Path::new(name)
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| INDICATOR_EXTENSIONS.contains(&ext))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Run every language detector against a single directory. Detectors that
+/// look below `path` skip whatever `ignore` excludes under `root`.
+fn detect_in_dir(
+    root: &Path,
+    path: &Path,
+    ignore: &IgnoreMatcher,
+    warnings: &mut Vec<DetectWarning>,
+) -> Result<Option<DetectedStack>, DetectError> {
+    // Priority: specific manifests first, generic (package.json, requirements) last.
+    // A Pulumi program also carries its language's manifest, so it goes first.
+    if let Some(s) = detect_pulumi(path, warnings) { return Ok(Some(s)); }
+    if let Some(s) = detect_php(path, warnings) { return Ok(Some(s)); }
+    if let Some(s) = detect_dart_flutter(path, warnings) { return Ok(Some(s)); }
+    if let Some(s) = detect_rust_crate(path, warnings) { return Ok(Some(s)); }
+    if let Some(s) = detect_go(path, warnings) { return Ok(Some(s)); }
+    if let Some(s) = detect_elixir(path, warnings) { return Ok(Some(s)); }
+    if let Some(s) = detect_jvm(path, warnings) { return Ok(Some(s)); }
+    if let Some(s) = detect_dotnet(path, warnings) { return Ok(Some(s)); }
+    if let Some(s) = detect_ruby(root, path, ignore, warnings) { return Ok(Some(s)); }
+    if let Some(s) = detect_swift(path, warnings) { return Ok(Some(s)); }
+    if let Some(s) = detect_zig(path) { return Ok(Some(s)); }
+    if let Some(s) = detect_nim(path, warnings) { return Ok(Some(s)); }
+    if let Some(s) = detect_ocaml(path, warnings) { return Ok(Some(s)); }
+    if let Some(s) = detect_javascript(path, warnings)? { return Ok(Some(s)); }
+    if let Some(s) = detect_python(path, warnings)? { return Ok(Some(s)); }
+    // Terraform, Ansible and Helm files often sit beside an application's
+    // own manifest, which says more about the directory.
+    if let Some(s) = detect_infra(path) { return Ok(Some(s)); }
+
+    Ok(None)
+}
+
 /// Scan a codebase root for known agent instruction files/directories.
 ///
 /// The result is deterministic by path ordering and includes both primary
@@ -125,6 +366,11 @@ pub fn scan_agent_files(path: &Path) -> Result<Vec<AgentFileHit>, DetectError> {
         ("cline", ".agents/skills", true),
         // Antigravity
         ("antigravity", ".agent", false),
+        // Roo Code
+        ("roo", ".roo", false),
+        ("roo", ".roorules", false),
+        // Zed
+        ("zed", ".rules", false),
         // Generic
         ("generic", "rules", true),
     ];
@@ -167,14 +413,193 @@ pub fn scan_agent_files(path: &Path) -> Result<Vec<AgentFileHit>, DetectError> {
     Ok(hits)
 }
 
+// ---------------------------------------------------------------------------
+// Ignore handling
+// ---------------------------------------------------------------------------
+
+/// Compiled ignore rules for one codebase scan.
+struct IgnoreMatcher {
+    patterns: Vec<String>,
+}
+
+impl IgnoreMatcher {
+    /// Combine the built-in names, `options.ignore_patterns`, and the lines of
+    /// `<root>/.orchestraignore` (blank lines and `#` comments skipped).
+    fn load(root: &Path, options: &DetectorOptions, warnings: &mut Vec<DetectWarning>) -> Self {
+        let mut patterns: Vec<String> = ALWAYS_IGNORED.iter().map(|p| (*p).to_string()).collect();
+        patterns.extend(options.ignore_patterns.iter().map(|p| normalize_pattern(p)));
+
+        if let Some(contents) = read_indicator(&root.join(IGNORE_FILE), warnings) {
+            patterns.extend(
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(normalize_pattern),
+            );
+        }
+
+        patterns.retain(|p| !p.is_empty());
+        Self { patterns }
+    }
+
+    /// Whether the entry at root-relative `rel` (using `/` separators) with
+    /// file name `name` should be skipped.
+    fn is_ignored(&self, rel: &str, name: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|p| glob_match(p, name) || glob_match(p, rel))
+    }
+
+    /// Whether `path`, somewhere under `root`, should be skipped.
+    fn is_ignored_path(&self, root: &Path, path: &Path) -> bool {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let rel = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        self.is_ignored(&rel, &name)
+    }
+}
+
+fn normalize_pattern(raw: &str) -> String {
+    raw.trim().trim_matches('/').to_string()
+}
+
+/// Non-ignored subdirectories of `dir`, sorted by name. An unreadable `dir`
+/// is recorded in `warnings` and yields no children.
+fn child_dirs(
+    root: &Path,
+    dir: &Path,
+    ignore: &IgnoreMatcher,
+    warnings: &mut Vec<DetectWarning>,
+) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            warn(warnings, dir, &err);
+            return Vec::new();
+        }
+    };
+    let mut dirs = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            continue;
+        }
+        let p = entry.path();
+        if !ignore.is_ignored_path(root, &p) {
+            dirs.push(p);
+        }
+    }
+    dirs.sort();
+    dirs
+}
+
+/// Minimal glob matcher supporting `*` (any run of characters) and `?`
+/// (exactly one character). No character classes or `**` semantics.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star_pi, star_ti)) = backtrack {
+            pi = star_pi + 1;
+            ti = star_ti + 1;
+            backtrack = Some((star_pi, star_ti + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
 // ---------------------------------------------------------------------------
 // Language detectors
 // ---------------------------------------------------------------------------
 
-fn detect_php(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
-    let file = path.join("composer.json");
-    if !file.exists() { return Ok(None); }
-    let content = fs::read_to_string(&file)?;
+fn infra(language: &str, framework: &str, confidence: Confidence) -> DetectedStack {
+    DetectedStack {
+        primary_language: language.to_string(),
+        framework: Some(framework.to_string()),
+        project_type: ProjectType::Infra,
+        confidence,
+        detail: None,
+        variant: None,
+        package_manager: None,
+    }
+}
+
+/// Pulumi programs, reported in the language of their `runtime`.
+fn detect_pulumi(path: &Path, warnings: &mut Vec<DetectWarning>) -> Option<DetectedStack> {
+    let content = read_indicator(&path.join("Pulumi.yaml"), warnings)?;
+    Some(match pulumi_language(path, &content) {
+        Some(language) => infra(language, "Pulumi", Confidence::High),
+        None => infra("YAML", "Pulumi", Confidence::Medium),
+    })
+}
+
+/// Terraform/Terragrunt, Ansible and Helm repositories, reported in the
+/// configuration language they are written in.
+fn detect_infra(path: &Path) -> Option<DetectedStack> {
+    if path.join("terragrunt.hcl").is_file() {
+        return Some(infra("HCL", "Terragrunt", Confidence::High));
+    }
+    if has_file_with_extension(path, "tf") {
+        return Some(infra("HCL", "Terraform", Confidence::High));
+    }
+    if path.join("ansible.cfg").is_file() || path.join("playbook.yml").is_file() {
+        return Some(infra("YAML", "Ansible", Confidence::High));
+    }
+    if path.join("Chart.yaml").is_file() {
+        return Some(infra("YAML", "Helm", Confidence::High));
+    }
+    None
+}
+
+/// Language of a Pulumi program from `runtime` in `Pulumi.yaml`, given either
+/// as `runtime: python` or as `runtime: { name: python }`.
+fn pulumi_language(path: &Path, content: &str) -> Option<&'static str> {
+    let yaml: serde_yaml::Value = serde_yaml::from_str(content).ok()?;
+    let runtime = yaml.get("runtime")?;
+    let name = runtime
+        .as_str()
+        .or_else(|| runtime.get("name").and_then(|n| n.as_str()))?;
+    match name {
+        "python" => Some("Python"),
+        "nodejs" if path.join("tsconfig.json").exists() => Some("TypeScript"),
+        "nodejs" => Some("JavaScript"),
+        "go" => Some("Go"),
+        "dotnet" => Some("C#"),
+        "java" => Some("Java"),
+        "yaml" => Some("YAML"),
+        _ => None,
+    }
+}
+
+fn has_file_with_extension(path: &Path, extension: &str) -> bool {
+    let Ok(entries) = fs::read_dir(path) else { return false };
+    entries.filter_map(|e| e.ok()).any(|entry| {
+        let p = entry.path();
+        p.is_file() && p.extension().is_some_and(|ext| ext == extension)
+    })
+}
+
+fn detect_php(path: &Path, warnings: &mut Vec<DetectWarning>) -> Option<DetectedStack> {
+    let content = read_indicator(&path.join("composer.json"), warnings)?;
     let lower = content.to_lowercase();
 
     let framework = if lower.contains("laravel/framework") {
@@ -193,32 +618,34 @@ fn detect_php(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
         None
     };
 
-    Ok(Some(DetectedStack {
+    Some(DetectedStack {
         primary_language: "PHP".to_string(),
         framework: framework.map(str::to_string),
         project_type: ProjectType::Backend,
         confidence: if framework.is_some() { Confidence::High } else { Confidence::Medium },
-    }))
+        detail: None,
+        variant: None,
+        package_manager: Some("composer".to_string()),
+    })
 }
 
-fn detect_dart_flutter(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
-    let file = path.join("pubspec.yaml");
-    if !file.exists() { return Ok(None); }
-    let content = fs::read_to_string(&file)?;
+fn detect_dart_flutter(path: &Path, warnings: &mut Vec<DetectWarning>) -> Option<DetectedStack> {
+    let content = read_indicator(&path.join("pubspec.yaml"), warnings)?;
 
     let is_flutter = content.contains("flutter:") && content.contains("sdk: flutter");
-    Ok(Some(DetectedStack {
+    Some(DetectedStack {
         primary_language: "Dart".to_string(),
         framework: if is_flutter { Some("Flutter".to_string()) } else { None },
         project_type: if is_flutter { ProjectType::Mobile } else { ProjectType::Backend },
         confidence: if is_flutter { Confidence::High } else { Confidence::Medium },
-    }))
+        detail: None,
+        variant: None,
+        package_manager: None,
+    })
 }
 
-fn detect_rust_crate(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
-    let file = path.join("Cargo.toml");
-    if !file.exists() { return Ok(None); }
-    let content = fs::read_to_string(&file)?;
+fn detect_rust_crate(path: &Path, warnings: &mut Vec<DetectWarning>) -> Option<DetectedStack> {
+    let content = read_indicator(&path.join("Cargo.toml"), warnings)?;
     let lower = content.to_lowercase();
 
     let framework = if lower.contains("actix-web") || lower.contains("actix_web") {
@@ -243,18 +670,19 @@ fn detect_rust_crate(path: &Path) -> Result<Option<DetectedStack>, DetectError>
         _ => ProjectType::Backend,
     };
 
-    Ok(Some(DetectedStack {
+    Some(DetectedStack {
         primary_language: "Rust".to_string(),
         framework: framework.map(str::to_string),
         project_type,
         confidence: if framework.is_some() { Confidence::High } else { Confidence::Medium },
-    }))
+        detail: None,
+        variant: None,
+        package_manager: None,
+    })
 }
 
-fn detect_go(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
-    let file = path.join("go.mod");
-    if !file.exists() { return Ok(None); }
-    let content = fs::read_to_string(&file)?;
+fn detect_go(path: &Path, warnings: &mut Vec<DetectWarning>) -> Option<DetectedStack> {
+    let content = read_indicator(&path.join("go.mod"), warnings)?;
     let lower = content.to_lowercase();
 
     let framework = if lower.contains("gin-gonic/gin") {
@@ -271,18 +699,19 @@ fn detect_go(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
         None
     };
 
-    Ok(Some(DetectedStack {
+    Some(DetectedStack {
         primary_language: "Go".to_string(),
         framework: framework.map(str::to_string),
         project_type: ProjectType::Backend,
         confidence: if framework.is_some() { Confidence::High } else { Confidence::Medium },
-    }))
+        detail: None,
+        variant: None,
+        package_manager: None,
+    })
 }
 
-fn detect_elixir(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
-    let file = path.join("mix.exs");
-    if !file.exists() { return Ok(None); }
-    let content = fs::read_to_string(&file)?;
+fn detect_elixir(path: &Path, warnings: &mut Vec<DetectWarning>) -> Option<DetectedStack> {
+    let content = read_indicator(&path.join("mix.exs"), warnings)?;
     let lower = content.to_lowercase();
 
     let framework = if lower.contains(":phoenix") || lower.contains("\"phoenix\"") {
@@ -291,100 +720,379 @@ fn detect_elixir(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
         None
     };
 
-    Ok(Some(DetectedStack {
+    Some(DetectedStack {
         primary_language: "Elixir".to_string(),
         framework: framework.map(str::to_string),
         project_type: ProjectType::Backend,
         confidence: if framework.is_some() { Confidence::High } else { Confidence::Medium },
-    }))
+        detail: None,
+        variant: None,
+        package_manager: None,
+    })
 }
 
-fn detect_jvm(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
-    let gradle = path.join("build.gradle");
-    let gradle_kts = path.join("build.gradle.kts");
-    let pom = path.join("pom.xml");
-
-    let (content, is_kotlin) = if gradle_kts.exists() {
-        (fs::read_to_string(&gradle_kts)?, true)
-    } else if gradle.exists() {
-        (fs::read_to_string(&gradle)?, false)
-    } else if pom.exists() {
-        (fs::read_to_string(&pom)?, false)
-    } else {
-        return Ok(None);
+fn detect_jvm(path: &Path, warnings: &mut Vec<DetectWarning>) -> Option<DetectedStack> {
+    // Reads the first readable file of a group, remembering whether it is a
+    // Kotlin script.
+    let mut first_readable = |candidates: &[(&str, bool)]| {
+        candidates.iter().find_map(|(file, is_kotlin)| {
+            Some((read_indicator(&path.join(file), warnings)?, *is_kotlin))
+        })
     };
 
-    let lower = content.to_lowercase();
+    // The first readable root build file wins. Android projects often keep
+    // their configuration in settings.gradle(.kts) and app/build.gradle(.kts).
+    let root = first_readable(&[
+        ("build.gradle.kts", true),
+        ("build.gradle", false),
+        ("pom.xml", false),
+    ]);
+    let settings = first_readable(&[("settings.gradle.kts", true), ("settings.gradle", false)]);
+    if root.is_none() && settings.is_none() {
+        return None;
+    }
+    let app = first_readable(&[("app/build.gradle.kts", true), ("app/build.gradle", false)]);
+
+    let is_kotlin_script = [&root, &settings, &app]
+        .into_iter()
+        .flatten()
+        .next()
+        .is_some_and(|(_, is_kotlin)| *is_kotlin);
+    let lower = [root, settings, app]
+        .into_iter()
+        .flatten()
+        .map(|(content, _)| content.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let is_android_app = lower.contains("com.android.application")
+        || path.join("app/src/main/AndroidManifest.xml").is_file()
+        || path.join("src/main/AndroidManifest.xml").is_file();
+
+    // Server frameworks win so a backend that happens to share a build with
+    // Android tooling stays a backend.
     let framework = if lower.contains("spring-boot") || lower.contains("springframework") {
         Some("Spring Boot")
     } else if lower.contains("quarkus") {
         Some("Quarkus")
     } else if lower.contains("micronaut") {
         Some("Micronaut")
+    } else if lower.contains("kotlin(\"multiplatform\")")
+        || lower.contains("org.jetbrains.kotlin.multiplatform")
+    {
+        Some("Kotlin Multiplatform")
+    } else if is_android_app && lower.contains("androidx.compose") {
+        Some("Jetpack Compose")
+    } else if is_android_app {
+        Some("Android")
     } else {
         None
     };
 
-    let language = if is_kotlin { "Kotlin" } else { "Java" };
-    Ok(Some(DetectedStack {
+    let project_type = match framework {
+        Some("Kotlin Multiplatform" | "Jetpack Compose" | "Android") => ProjectType::Mobile,
+        _ => ProjectType::Backend,
+    };
+    let is_kotlin = is_kotlin_script
+        || lower.contains("org.jetbrains.kotlin")
+        || lower.contains("kotlin(\"");
+    let language = if is_kotlin {
+        "Kotlin"
+    } else {
+        "Java"
+    };
+    Some(DetectedStack {
         primary_language: language.to_string(),
         framework: framework.map(str::to_string),
-        project_type: ProjectType::Backend,
+        project_type,
         confidence: if framework.is_some() { Confidence::High } else { Confidence::Medium },
-    }))
+        detail: None,
+        variant: None,
+        package_manager: None,
+    })
 }
 
-fn detect_dotnet(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
-    // Look for any *.csproj or *.sln file
-    let found = fs::read_dir(path)?
-        .filter_map(|e| e.ok())
-        .any(|e| {
-            let name = e.file_name();
-            let s = name.to_string_lossy();
-            s.ends_with(".csproj") || s.ends_with(".sln") || s.ends_with(".fsproj")
+/// Most project files read per .NET solution.
+const MAX_DOTNET_PROJECTS: usize = 20;
+
+/// What a `.csproj` / `.fsproj` declares that matters for detection.
+#[derive(Debug, Default)]
+struct DotnetProject {
+    /// Root-relative path of the project file, `/`-separated.
+    rel: String,
+    fsharp: bool,
+    sdks: Vec<String>,
+    target_frameworks: Vec<String>,
+    packages: Vec<String>,
+    use_maui: bool,
+}
+
+impl DotnetProject {
+    fn framework(&self) -> Option<&'static str> {
+        let has_sdk = |name: &str| self.sdks.iter().any(|s| s.eq_ignore_ascii_case(name));
+        let has_package = |prefix: &str| {
+            self.packages
+                .iter()
+                .any(|p| p.to_ascii_lowercase().starts_with(prefix))
+        };
+        let mobile_target = self.target_frameworks.iter().any(|tf| {
+            let tf = tf.to_ascii_lowercase();
+            tf.contains("-android") || tf.contains("-ios") || tf.contains("-maccatalyst")
         });
-    if !found { return Ok(None); }
 
-    // Read all .csproj files to detect framework
-    let csproj = fs::read_dir(path)?
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_name().to_string_lossy().ends_with(".csproj"))
-        .filter_map(|e| fs::read_to_string(e.path()).ok())
-        .collect::<Vec<_>>()
-        .join("\n");
+        if self.use_maui || mobile_target || has_package("microsoft.maui") {
+            Some("MAUI")
+        } else if has_sdk("Microsoft.NET.Sdk.BlazorWebAssembly")
+            || has_package("microsoft.aspnetcore.components")
+        {
+            Some("Blazor")
+        } else if has_sdk("Microsoft.NET.Sdk.Web") || has_package("microsoft.aspnetcore") {
+            Some("ASP.NET Core")
+        } else {
+            None
+        }
+    }
+}
 
-    let lower = csproj.to_lowercase();
-    let framework = if lower.contains("microsoft.aspnetcore") {
-        Some("ASP.NET Core")
-    } else if lower.contains("maui") {
-        Some("MAUI")
-    } else if lower.contains("blazor") || lower.contains("microsoft.aspnetcore.components") {
-        Some("Blazor")
+fn detect_dotnet(path: &Path, warnings: &mut Vec<DetectWarning>) -> Option<DetectedStack> {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).collect::<Vec<_>>(),
+        Err(err) => {
+            warn(warnings, path, &err);
+            return None;
+        }
+    };
+
+    let unity = path.join("ProjectSettings").join("ProjectVersion.txt").is_file()
+        || entries.iter().any(|p| {
+            is_dotnet_project(p)
+                && p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("Assembly-CSharp"))
+        });
+
+    // Project files: at the root, listed by a root solution, or else nested
+    // under src/ (the usual `src/<Project>/<Project>.csproj` layout).
+    let mut project_files: Vec<PathBuf> =
+        entries.iter().filter(|p| is_dotnet_project(p)).cloned().collect();
+    let mut solutions: Vec<&PathBuf> = entries.iter().filter(|p| has_extension(p, "sln")).collect();
+    solutions.sort();
+    for sln in &solutions {
+        if let Some(content) = read_indicator(sln, warnings) {
+            project_files.extend(solution_projects(&content).into_iter().map(|rel| path.join(rel)));
+        }
+    }
+    if project_files.is_empty() {
+        project_files = src_dotnet_projects(&path.join("src"));
+    }
+    if project_files.is_empty() && solutions.is_empty() && !unity {
+        return None;
+    }
+    project_files.sort();
+    project_files.dedup();
+
+    let projects: Vec<DotnetProject> = project_files
+        .iter()
+        .filter(|p| p.is_file())
+        .take(MAX_DOTNET_PROJECTS)
+        .filter_map(|file| {
+            let content = read_indicator(file, warnings)?;
+            let rel = file.strip_prefix(path).unwrap_or(file);
+            parse_dotnet_project(&rel.to_string_lossy().replace('\\', "/"), &content, file, warnings)
+        })
+        .collect();
+
+    let primary_language = if !unity && !projects.is_empty() && projects.iter().all(|p| p.fsharp) {
+        "F#"
     } else {
-        None
+        "C#"
+    };
+
+    let (framework, detail) = if unity {
+        (Some("Unity"), None)
+    } else {
+        // One project's framework decides for the solution, by priority.
+        let found = ["MAUI", "Blazor", "ASP.NET Core"].into_iter().find_map(|framework| {
+            projects
+                .iter()
+                .find(|p| p.framework() == Some(framework))
+                .map(|p| (framework, p))
+        });
+        match found {
+            Some((framework, project)) => {
+                let nested = project.rel.contains('/');
+                (Some(framework), nested.then(|| project.rel.clone()))
+            }
+            None => (None, None),
+        }
     };
 
     let project_type = match framework {
         Some("MAUI") => ProjectType::Mobile,
-        Some("Blazor") => ProjectType::Frontend,
+        Some("Blazor") | Some("Unity") => ProjectType::Frontend,
         _ => ProjectType::Backend,
     };
 
-    Ok(Some(DetectedStack {
-        primary_language: "C#".to_string(),
+    Some(DetectedStack {
+        primary_language: primary_language.to_string(),
         framework: framework.map(str::to_string),
         project_type,
         confidence: if framework.is_some() { Confidence::High } else { Confidence::Medium },
-    }))
+        detail,
+        variant: None,
+        package_manager: None,
+    })
+}
+
+/// Files directly under `path` with extension `ext`, sorted by name.
+fn root_files_with_extension(path: &Path, ext: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(path) else { return Vec::new() };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| has_extension(p, ext) && p.is_file())
+        .collect();
+    files.sort();
+    files
+}
+
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some(ext)
+}
+
+fn is_dotnet_project(path: &Path) -> bool {
+    has_extension(path, "csproj") || has_extension(path, "fsproj")
+}
+
+/// Project paths listed in a `.sln`, from lines such as
+/// `Project("{GUID}") = "Api", "src\Api\Api.csproj", "{GUID}"`. Solution
+/// folders and non-C#/F# projects are skipped.
+fn solution_projects(sln: &str) -> Vec<PathBuf> {
+    sln.lines()
+        .filter(|line| line.trim_start().starts_with("Project("))
+        .filter_map(|line| {
+            let (_, rhs) = line.split_once('=')?;
+            let path = rhs.split(',').nth(1)?.trim().trim_matches('"');
+            Some(PathBuf::from(path.replace('\\', "/")))
+        })
+        .filter(|p| is_dotnet_project(p))
+        .collect()
+}
+
+/// `.csproj` / `.fsproj` files directly in `src/` or one level below it.
+fn src_dotnet_projects(src: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(src) else {
+        return Vec::new();
+    };
+    let mut found = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if entry.is_dir() {
+            if let Ok(nested) = fs::read_dir(&entry) {
+                found.extend(
+                    nested
+                        .filter_map(|e| e.ok())
+                        .map(|e| e.path())
+                        .filter(|p| is_dotnet_project(p)),
+                );
+            }
+        } else if is_dotnet_project(&entry) {
+            found.push(entry);
+        }
+    }
+    found
+}
+
+/// Parse an MSBuild project file. Malformed XML is reported as a warning and
+/// the project is skipped.
+fn parse_dotnet_project(
+    rel: &str,
+    content: &str,
+    file: &Path,
+    warnings: &mut Vec<DetectWarning>,
+) -> Option<DotnetProject> {
+    use quick_xml::events::Event;
+
+    let mut project = DotnetProject {
+        rel: rel.to_string(),
+        fsharp: has_extension(file, "fsproj"),
+        ..DotnetProject::default()
+    };
+    let mut reader = quick_xml::Reader::from_str(content);
+    // Name of the property element whose text comes next.
+    let mut property: Option<String> = None;
+    loop {
+        let event = match reader.read_event() {
+            Ok(event) => event,
+            Err(err) => {
+                warnings.push(DetectWarning {
+                    path: file.to_path_buf(),
+                    message: format!("invalid project XML: {err}"),
+                });
+                return None;
+            }
+        };
+        match event {
+            Event::Start(ref element) | Event::Empty(ref element) => {
+                let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+                let attribute = |key: &str| {
+                    element
+                        .attributes()
+                        .filter_map(|a| a.ok())
+                        .find(|a| a.key.local_name().as_ref() == key.as_bytes())
+                        .and_then(|a| a.unescape_value().ok().map(|v| v.into_owned()))
+                };
+                match name.as_str() {
+                    // `Sdk` may list several SDKs separated by `;`.
+                    "Project" | "Import" => project.sdks.extend(
+                        attribute("Sdk")
+                            .iter()
+                            .flat_map(|sdk| sdk.split(';'))
+                            .map(|sdk| sdk.split('/').next().unwrap_or(sdk).trim().to_string())
+                            .filter(|sdk| !sdk.is_empty()),
+                    ),
+                    "Sdk" => project.sdks.extend(attribute("Name")),
+                    "PackageReference" => project.packages.extend(attribute("Include")),
+                    _ => {}
+                }
+                property = matches!(event, Event::Start(_)).then_some(name);
+            }
+            Event::Text(text) => {
+                let Some(name) = property.as_deref() else { continue };
+                let Ok(value) = text.decode() else { continue };
+                match name {
+                    "TargetFramework" | "TargetFrameworks" => project.target_frameworks.extend(
+                        value
+                            .split(';')
+                            .map(str::trim)
+                            .filter(|tf| !tf.is_empty())
+                            .map(str::to_string),
+                    ),
+                    "UseMaui" => project.use_maui |= value.trim().eq_ignore_ascii_case("true"),
+                    _ => {}
+                }
+            }
+            Event::End(_) => property = None,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Some(project)
 }
 
-fn detect_ruby(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
-    let file = path.join("Gemfile");
-    if !file.exists() { return Ok(None); }
-    let content = fs::read_to_string(&file)?;
+fn detect_ruby(
+    root: &Path,
+    path: &Path,
+    ignore: &IgnoreMatcher,
+    warnings: &mut Vec<DetectWarning>,
+) -> Option<DetectedStack> {
+    let content = read_indicator(&path.join("Gemfile"), warnings)?;
     let lower = content.to_lowercase();
+    let has_gem =
+        |gem: &str| lower.contains(&format!("\"{gem}\"")) || lower.contains(&format!("'{gem}'"));
 
-    let framework = if lower.contains("\"rails\"") || lower.contains("'rails'") {
+    let framework = if has_gem("jekyll") {
+        Some("Jekyll")
+    } else if has_gem("rails") {
         Some("Rails")
     } else if lower.contains("sinatra") {
         Some("Sinatra")
@@ -394,15 +1102,58 @@ fn detect_ruby(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
         None
     };
 
-    Ok(Some(DetectedStack {
+    let variant = (framework == Some("Rails")).then(|| {
+        if rails_api_only(root, path, ignore, warnings) {
+            "Rails (API-only)"
+        } else if has_gem("turbo-rails") || has_gem("stimulus-rails") {
+            "Rails + Hotwire"
+        } else {
+            "Rails"
+        }
+    });
+
+    Some(DetectedStack {
         primary_language: "Ruby".to_string(),
         framework: framework.map(str::to_string),
-        project_type: ProjectType::Backend,
+        project_type: match framework {
+            Some("Jekyll") => ProjectType::Frontend,
+            _ => ProjectType::Backend,
+        },
         confidence: if framework.is_some() { Confidence::High } else { Confidence::Medium },
-    }))
+        detail: None,
+        variant: variant.map(str::to_string),
+        package_manager: None,
+    })
 }
 
-fn detect_swift(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
+/// A Rails app is API-only when `config/application.rb` sets
+/// `api_only = true` or it has no `app/views`. Ignored directories count as
+/// absent.
+fn rails_api_only(
+    root: &Path,
+    path: &Path,
+    ignore: &IgnoreMatcher,
+    warnings: &mut Vec<DetectWarning>,
+) -> bool {
+    let config = path.join("config");
+    if !ignore.is_ignored_path(root, &config) {
+        if let Some(application) = read_indicator(&config.join("application.rb"), warnings) {
+            let api_only = application.lines().any(|line| {
+                let code = line.split('#').next().unwrap_or_default();
+                code.split_whitespace().collect::<String>().contains("api_only=true")
+            });
+            if api_only {
+                return true;
+            }
+        }
+    }
+    let views = path.join("app").join("views");
+    ignore.is_ignored_path(root, &path.join("app"))
+        || ignore.is_ignored_path(root, &views)
+        || !views.is_dir()
+}
+
+fn detect_swift(path: &Path, warnings: &mut Vec<DetectWarning>) -> Option<DetectedStack> {
     let spm = path.join("Package.swift");
     let xcodeproj = fs::read_dir(path)
         .ok()
@@ -410,9 +1161,10 @@ fn detect_swift(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
             e.as_ref().ok().map(|e| e.file_name().to_string_lossy().ends_with(".xcodeproj")).unwrap_or(false)
         }));
 
-    if !spm.exists() && xcodeproj.is_none() { return Ok(None); }
+    let content = read_indicator(&spm, warnings);
+    if content.is_none() && xcodeproj.is_none() { return None; }
 
-    let content = if spm.exists() { fs::read_to_string(&spm)? } else { String::new() };
+    let content = content.unwrap_or_default();
     let lower = content.to_lowercase();
 
     let framework = if lower.contains("swiftui") {
@@ -428,133 +1180,450 @@ fn detect_swift(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
         _ => ProjectType::Mobile,
     };
 
-    Ok(Some(DetectedStack {
+    Some(DetectedStack {
         primary_language: "Swift".to_string(),
         framework: framework.map(str::to_string),
         project_type,
         confidence: Confidence::Medium,
-    }))
+        detail: None,
+        variant: None,
+        package_manager: None,
+    })
+}
+
+fn detect_zig(path: &Path) -> Option<DetectedStack> {
+    if !path.join("build.zig").is_file() && !path.join("build.zig.zon").is_file() { return None; }
+
+    Some(DetectedStack {
+        primary_language: "Zig".to_string(),
+        framework: None,
+        project_type: ProjectType::Backend,
+        confidence: Confidence::Medium,
+        detail: None,
+        variant: None,
+        package_manager: None,
+    })
+}
+
+fn detect_nim(path: &Path, warnings: &mut Vec<DetectWarning>) -> Option<DetectedStack> {
+    let nimble = root_files_with_extension(path, "nimble").into_iter().next()?;
+    let content = read_indicator(&nimble, warnings).unwrap_or_default();
+
+    let requires: Vec<String> = content
+        .lines()
+        .map(|line| line.trim_start().to_lowercase())
+        .filter(|line| line.starts_with("requires"))
+        .collect();
+    let framework = if requires.iter().any(|line| line.contains("jester")) {
+        Some("Jester")
+    } else if requires.iter().any(|line| line.contains("prologue")) {
+        Some("Prologue")
+    } else {
+        None
+    };
+
+    Some(DetectedStack {
+        primary_language: "Nim".to_string(),
+        framework: framework.map(str::to_string),
+        project_type: ProjectType::Backend,
+        confidence: if framework.is_some() { Confidence::High } else { Confidence::Medium },
+        detail: None,
+        variant: None,
+        package_manager: None,
+    })
+}
+
+fn detect_ocaml(path: &Path, warnings: &mut Vec<DetectWarning>) -> Option<DetectedStack> {
+    let dune_project = path.join("dune-project");
+    let opam = root_files_with_extension(path, "opam");
+    if !dune_project.is_file() && opam.is_empty() { return None; }
+
+    let mut content = read_indicator(&dune_project, warnings).unwrap_or_default();
+    for file in &opam {
+        content.extend(read_indicator(file, warnings));
+    }
+    let lower = content.to_lowercase();
+
+    let framework = if lower.contains("dream") {
+        Some("Dream")
+    } else if lower.contains("opium") {
+        Some("Opium")
+    } else {
+        None
+    };
+
+    Some(DetectedStack {
+        primary_language: "OCaml".to_string(),
+        framework: framework.map(str::to_string),
+        project_type: ProjectType::Backend,
+        confidence: if framework.is_some() { Confidence::High } else { Confidence::Medium },
+        detail: None,
+        variant: None,
+        package_manager: None,
+    })
 }
 
-fn detect_javascript(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
+/// JavaScript frameworks by dependency, most specific first.
+const JS_FRAMEWORKS: &[(&[&str], &str, ProjectType)] = &[
+    (&["next"], "Next.js", ProjectType::Frontend),
+    (&["nuxt", "nuxt3"], "Nuxt", ProjectType::Frontend),
+    (&["@remix-run/react", "@remix-run/node"], "Remix", ProjectType::Frontend),
+    (&["astro"], "Astro", ProjectType::Frontend),
+    (&["gatsby"], "Gatsby", ProjectType::Frontend),
+    (&["@angular/core"], "Angular", ProjectType::Frontend),
+    (&["@sveltejs/kit"], "SvelteKit", ProjectType::Frontend),
+    (&["@solidjs/start"], "SolidStart", ProjectType::Frontend),
+    (&["@builder.io/qwik-city"], "Qwik City", ProjectType::Frontend),
+    (&["@tanstack/start", "@tanstack/react-start"], "TanStack Start", ProjectType::Frontend),
+    (&["svelte"], "Svelte", ProjectType::Frontend),
+    (&["solid-js"], "Solid", ProjectType::Frontend),
+    (&["@builder.io/qwik"], "Qwik", ProjectType::Frontend),
+    (&["vue"], "Vue", ProjectType::Frontend),
+    (&["react"], "React", ProjectType::Frontend),
+    (&["@nestjs/core"], "NestJS", ProjectType::Backend),
+    (&["express"], "Express", ProjectType::Backend),
+    (&["fastify"], "Fastify", ProjectType::Backend),
+    (&["koa"], "Koa", ProjectType::Backend),
+    (&["hapi", "@hapi/hapi"], "Hapi", ProjectType::Backend),
+];
+
+/// Build tools that only bundle for the browser. A project using one but
+/// none of [`JS_FRAMEWORKS`] is a frontend; with Vite, it is a Vite app.
+const FRONTEND_BUILD_TOOLS: &[&str] = &["vite", "webpack", "parcel", "snowpack"];
+
+/// Node package managers by lock file, checked in order.
+const NODE_LOCK_FILES: &[(&str, &str)] = &[
+    ("pnpm-lock.yaml", "pnpm"),
+    ("yarn.lock", "yarn"),
+    ("bun.lock", "bun"),
+    ("bun.lockb", "bun"),
+    ("package-lock.json", "npm"),
+];
+
+/// The package manager named by `package.json`'s `packageManager` field
+/// (e.g. `"pnpm@9.1.0"`), or else by the lock file next to it.
+fn node_package_manager(path: &Path, package_json: &serde_json::Value) -> Option<&'static str> {
+    let declared = package_json
+        .get("packageManager")
+        .and_then(|value| value.as_str())
+        .and_then(|value| value.split('@').next());
+    NODE_LOCK_FILES
+        .iter()
+        .find(|(_, manager)| declared == Some(*manager))
+        .or_else(|| {
+            NODE_LOCK_FILES
+                .iter()
+                .find(|(lock, _)| path.join(lock).exists())
+        })
+        .map(|(_, manager)| *manager)
+}
+
+/// Index into [`JS_FRAMEWORKS`] of the most specific framework in `deps`.
+fn js_framework_rank(deps: &HashSet<String>) -> Option<usize> {
+    JS_FRAMEWORKS
+        .iter()
+        .position(|(names, _, _)| names.iter().any(|name| deps.contains(*name)))
+}
+
+fn detect_javascript(
+    path: &Path,
+    warnings: &mut Vec<DetectWarning>,
+) -> Result<Option<DetectedStack>, DetectError> {
     let file = path.join("package.json");
-    if !file.exists() { return Ok(None); }
-    let content = fs::read_to_string(&file)?;
+    let Some(content) = read_indicator(&file, warnings) else { return Ok(None) };
 
     let json: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
         DetectError::ParseError { path: file.clone(), message: e.to_string() }
     })?;
 
     let deps = collect_package_json_deps(&json);
-    let is_typescript = path.join("tsconfig.json").exists() || deps.contains("typescript");
-    let language = if is_typescript { "TypeScript" } else { "JavaScript" };
-
-    // Framework detection — precedence from most specific to most generic
-    let framework = if deps.contains("next") {
-        Some(("Next.js", ProjectType::Frontend))
-    } else if deps.contains("nuxt") || deps.contains("nuxt3") {
-        Some(("Nuxt", ProjectType::Frontend))
-    } else if deps.contains("@remix-run/react") || deps.contains("@remix-run/node") {
-        Some(("Remix", ProjectType::Frontend))
-    } else if deps.contains("astro") {
-        Some(("Astro", ProjectType::Frontend))
-    } else if deps.contains("gatsby") {
-        Some(("Gatsby", ProjectType::Frontend))
-    } else if deps.contains("@angular/core") {
-        Some(("Angular", ProjectType::Frontend))
-    } else if deps.contains("@sveltejs/kit") {
-        Some(("SvelteKit", ProjectType::Frontend))
-    } else if deps.contains("svelte") {
-        Some(("Svelte", ProjectType::Frontend))
-    } else if deps.contains("vue") {
-        Some(("Vue", ProjectType::Frontend))
-    } else if deps.contains("react") {
-        Some(("React", ProjectType::Frontend))
-    } else if deps.contains("@nestjs/core") {
-        Some(("NestJS", ProjectType::Backend))
-    } else if deps.contains("express") {
-        Some(("Express", ProjectType::Backend))
-    } else if deps.contains("fastify") {
-        Some(("Fastify", ProjectType::Backend))
-    } else if deps.contains("koa") {
-        Some(("Koa", ProjectType::Backend))
-    } else if deps.contains("hapi") || deps.contains("@hapi/hapi") {
-        Some(("Hapi", ProjectType::Backend))
-    } else {
-        None
-    };
+    let mut is_typescript = path.join("tsconfig.json").exists() || deps.contains("typescript");
+
+    // A framework declared at the root wins; otherwise aggregate members.
+    let mut found = js_framework_rank(&deps).map(|rank| (rank, None));
+    if found.is_none() {
+        for (member, member_deps) in workspace_members(path, &json) {
+            let Some(rank) = js_framework_rank(&member_deps) else { continue };
+            if found.as_ref().is_none_or(|(best, _)| rank < *best) {
+                is_typescript |= path.join(&member).join("tsconfig.json").exists()
+                    || member_deps.contains("typescript");
+                found = Some((rank, Some(member)));
+            }
+        }
+    }
 
-    let (fw_name, project_type) = match framework {
-        Some((name, ref pt)) => (Some(name.to_string()), pt.clone()),
-        None => (None, ProjectType::Backend),
+    let language = if is_typescript { "TypeScript" } else { "JavaScript" };
+    let (fw_name, project_type, detail, confidence) = match found {
+        Some((rank, detail)) => {
+            let (_, name, project_type) = &JS_FRAMEWORKS[rank];
+            (Some(name.to_string()), project_type.clone(), detail, Confidence::High)
+        }
+        None if deps.contains("vite") => {
+            (Some("Vite".to_string()), ProjectType::Frontend, None, Confidence::Medium)
+        }
+        None if FRONTEND_BUILD_TOOLS.iter().any(|tool| deps.contains(*tool)) => {
+            (None, ProjectType::Frontend, None, Confidence::Medium)
+        }
+        None => (None, ProjectType::Backend, None, Confidence::Medium),
     };
 
     Ok(Some(DetectedStack {
         primary_language: language.to_string(),
+        confidence,
         framework: fw_name,
         project_type,
-        confidence: if framework.is_some() { Confidence::High } else { Confidence::Medium },
+        detail,
+        variant: None,
+        package_manager: node_package_manager(path, &json).map(str::to_string),
     }))
 }
 
-fn detect_python(path: &Path) -> Result<Option<DetectedStack>, DetectError> {
+/// Workspace members declared by `package.json` `workspaces` (array or
+/// `{ packages: [] }`) or `pnpm-workspace.yaml`, with their dependencies.
+///
+/// Globs are expanded one level: `apps/*` lists the subdirectories of
+/// `apps`. Negated patterns and members without a readable `package.json`
+/// are skipped. Members are returned as `/`-separated relative paths, sorted.
+fn workspace_members(root: &Path, package_json: &serde_json::Value) -> Vec<(String, HashSet<String>)> {
+    let mut patterns: Vec<String> = Vec::new();
+    let declared = package_json.get("workspaces");
+    let list = declared
+        .and_then(|w| w.as_array())
+        .or_else(|| declared.and_then(|w| w.get("packages")).and_then(|p| p.as_array()));
+    if let Some(list) = list {
+        patterns.extend(list.iter().filter_map(|v| v.as_str()).map(str::to_owned));
+    }
+
+    if let Ok(content) = fs::read_to_string(root.join("pnpm-workspace.yaml")) {
+        if let Ok(yaml) = serde_yaml::from_str::<serde_yaml::Value>(&content) {
+            if let Some(list) = yaml.get("packages").and_then(|p| p.as_sequence()) {
+                patterns.extend(list.iter().filter_map(|v| v.as_str()).map(str::to_owned));
+            }
+        }
+    }
+
+    let mut members: Vec<String> = Vec::new();
+    for pattern in patterns.iter().filter(|p| !p.starts_with('!')) {
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        match pattern.split_once('*') {
+            Some((prefix, _)) => {
+                let parent = prefix.trim_end_matches('/');
+                let Ok(entries) = fs::read_dir(root.join(parent)) else { continue };
+                for entry in entries.filter_map(|e| e.ok()) {
+                    if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                        let name = entry.file_name().to_string_lossy().into_owned();
+                        members.push(if parent.is_empty() { name } else { format!("{parent}/{name}") });
+                    }
+                }
+            }
+            None => members.push(pattern.to_owned()),
+        }
+    }
+    members.sort();
+    members.dedup();
+
+    members
+        .into_iter()
+        .filter_map(|member| {
+            let content = fs::read_to_string(root.join(&member).join("package.json")).ok()?;
+            let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+            Some((member, collect_package_json_deps(&json)))
+        })
+        .collect()
+}
+
+/// Python frameworks by distribution name, most specific first. Names are
+/// compared after [`normalize_python_name`].
+const PY_FRAMEWORKS: &[(&[&str], &str, ProjectType)] = &[
+    (&["fastapi"], "FastAPI", ProjectType::Backend),
+    (&["django"], "Django", ProjectType::Backend),
+    (&["flask"], "Flask", ProjectType::Backend),
+    (&["starlette"], "Starlette", ProjectType::Backend),
+    (&["litestar"], "Litestar", ProjectType::Backend),
+    (&["streamlit"], "Streamlit", ProjectType::Ml),
+    (&["gradio"], "Gradio", ProjectType::Ml),
+    (&["torch", "pytorch"], "PyTorch", ProjectType::Ml),
+    (&["tensorflow", "keras"], "TensorFlow", ProjectType::Ml),
+    (&["scikit-learn", "sklearn"], "scikit-learn", ProjectType::Ml),
+    (&["pandas", "numpy"], "Data Science", ProjectType::Ml),
+];
+
+/// Detect a Python project from its dependency manifests.
+///
+/// The framework comes only from main (runtime) dependencies:
+/// `requirements.txt`, `[project.dependencies]`, `[tool.poetry.dependencies]`,
+/// Pipfile `[packages]` and `setup.py` string literals. Optional
+/// dependencies, `[dependency-groups]`, Poetry groups and Pipfile
+/// `[dev-packages]` are dev or extras and never decide the framework.
+fn detect_python(
+    path: &Path,
+    warnings: &mut Vec<DetectWarning>,
+) -> Result<Option<DetectedStack>, DetectError> {
     let req_txt = path.join("requirements.txt");
     let pyproject = path.join("pyproject.toml");
     let setup_py = path.join("setup.py");
     let pipfile = path.join("Pipfile");
 
-    let content = if req_txt.exists() {
-        fs::read_to_string(&req_txt)?
-    } else if pyproject.exists() {
-        fs::read_to_string(&pyproject)?
-    } else if pipfile.exists() {
-        fs::read_to_string(&pipfile)?
-    } else if setup_py.exists() {
-        fs::read_to_string(&setup_py)?
-    } else {
+    let req_content = read_indicator(&req_txt, warnings);
+    let pyproject_toml = read_toml(&pyproject, warnings)?;
+    let pipfile_toml = read_toml(&pipfile, warnings)?;
+    let setup_content = read_indicator(&setup_py, warnings);
+    if req_content.is_none()
+        && pyproject_toml.is_none()
+        && pipfile_toml.is_none()
+        && setup_content.is_none()
+    {
         return Ok(None);
-    };
+    }
 
-    let lower = content.to_lowercase();
+    let mut deps = HashSet::new();
+    if let Some(content) = &req_content {
+        deps.extend(content.lines().filter_map(requirement_name));
+    }
+    if let Some(toml) = &pyproject_toml {
+        let project_deps = toml
+            .get("project")
+            .and_then(|p| p.get("dependencies"))
+            .and_then(|d| d.as_array());
+        for dep in project_deps.into_iter().flatten() {
+            deps.extend(dep.as_str().and_then(requirement_name));
+        }
+        let poetry_deps = toml
+            .get("tool")
+            .and_then(|t| t.get("poetry"))
+            .and_then(|p| p.get("dependencies"))
+            .and_then(|d| d.as_table());
+        for name in poetry_deps.into_iter().flat_map(|t| t.keys()) {
+            if name != "python" {
+                deps.insert(normalize_python_name(name));
+            }
+        }
+    }
+    if let Some(toml) = &pipfile_toml {
+        let packages = toml.get("packages").and_then(|p| p.as_table());
+        deps.extend(packages.into_iter().flat_map(|t| t.keys()).map(|k| normalize_python_name(k)));
+    }
+    if let Some(content) = &setup_content {
+        deps.extend(python_string_literals(content).filter_map(requirement_name));
+    }
+
+    let found = PY_FRAMEWORKS
+        .iter()
+        .find(|(names, _, _)| names.iter().any(|name| deps.contains(*name)));
+    let (framework, project_type) = match found {
+        Some((_, name, project_type)) => (Some(name.to_string()), project_type.clone()),
+        None => (None, ProjectType::Backend),
+    };
 
-    let (framework, project_type) = if lower.contains("fastapi") {
-        (Some("FastAPI"), ProjectType::Backend)
-    } else if lower.contains("django") {
-        (Some("Django"), ProjectType::Backend)
-    } else if lower.contains("flask") {
-        (Some("Flask"), ProjectType::Backend)
-    } else if lower.contains("starlette") {
-        (Some("Starlette"), ProjectType::Backend)
-    } else if lower.contains("litestar") || lower.contains("litestar-api") {
-        (Some("Litestar"), ProjectType::Backend)
-    } else if lower.contains("streamlit") {
-        (Some("Streamlit"), ProjectType::Ml)
-    } else if lower.contains("gradio") {
-        (Some("Gradio"), ProjectType::Ml)
-    } else if lower.contains("torch") || lower.contains("pytorch") {
-        (Some("PyTorch"), ProjectType::Ml)
-    } else if lower.contains("tensorflow") || lower.contains("keras") {
-        (Some("TensorFlow"), ProjectType::Ml)
-    } else if lower.contains("scikit-learn") || lower.contains("sklearn") {
-        (Some("scikit-learn"), ProjectType::Ml)
-    } else if lower.contains("pandas") || lower.contains("numpy") {
-        (Some("Data Science"), ProjectType::Ml)
+    let package_manager = if path.join("poetry.lock").exists() {
+        Some("poetry")
+    } else if path.join("uv.lock").exists() {
+        Some("uv")
+    } else if req_content.is_some() {
+        Some("pip")
     } else {
-        (None, ProjectType::Backend)
+        None
     };
 
     Ok(Some(DetectedStack {
         primary_language: "Python".to_string(),
-        framework: framework.map(str::to_string),
-        project_type,
         confidence: if framework.is_some() { Confidence::High } else { Confidence::Medium },
+        framework,
+        project_type,
+        detail: None,
+        variant: None,
+        package_manager: package_manager.map(str::to_string),
     }))
 }
 
+/// Package name from one requirement line or PEP 508 string, e.g.
+/// `"Django>=4.2 ; python_version >= '3.10'"` → `django`.
+///
+/// Blank lines, comments and pip options (`-r`, `-e`, `--index-url`) yield `None`.
+fn requirement_name(line: &str) -> Option<String> {
+    let line = line.split('#').next().unwrap_or("").trim();
+    if line.is_empty() || line.starts_with('-') {
+        return None;
+    }
+    let end = line
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(line.len());
+    let name = &line[..end];
+    (!name.is_empty()).then(|| normalize_python_name(name))
+}
+
+/// PEP 503 normalisation: lowercase, with runs of `-`, `_` and `.` as `-`.
+fn normalize_python_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !out.ends_with('-') {
+                out.push('-');
+            }
+        } else {
+            out.push(c.to_ascii_lowercase());
+        }
+    }
+    out
+}
+
+/// Quoted string literals in Python source, e.g. the entries of
+/// `install_requires=[...]` in `setup.py`.
+fn python_string_literals(source: &str) -> impl Iterator<Item = &str> {
+    source
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .flat_map(|line| {
+            line.split(['"', '\''])
+                .skip(1)
+                .step_by(2)
+        })
+}
+
+/// Parse `file` as TOML. A missing or unreadable file yields `None`.
+fn read_toml(
+    file: &Path,
+    warnings: &mut Vec<DetectWarning>,
+) -> Result<Option<toml::Table>, DetectError> {
+    let Some(content) = read_indicator(file, warnings) else { return Ok(None) };
+    content
+        .parse::<toml::Table>()
+        .map(Some)
+        .map_err(|e| DetectError::ParseError {
+            path: file.to_path_buf(),
+            message: e.to_string(),
+        })
+}
+
 // ---------------------------------------------------------------------------
 // Utilities
 // ---------------------------------------------------------------------------
 
+/// Read an indicator file. Returns `None` when it does not exist, and also
+/// when it exists but cannot be read — a dangling symlink, missing
+/// permissions or invalid UTF-8 — after recording a warning.
+fn read_indicator(file: &Path, warnings: &mut Vec<DetectWarning>) -> Option<String> {
+    match fs::read_to_string(file) {
+        Ok(content) => Some(content),
+        Err(err) if err.kind() == ErrorKind::NotFound && fs::symlink_metadata(file).is_err() => {
+            None
+        }
+        Err(err) => {
+            warn(warnings, file, &err);
+            None
+        }
+    }
+}
+
+fn warn(warnings: &mut Vec<DetectWarning>, path: &Path, err: &std::io::Error) {
+    let message = match err.kind() {
+        ErrorKind::NotFound => "dangling symlink".to_string(),
+        ErrorKind::InvalidData => "not valid UTF-8".to_string(),
+        _ => err.to_string(),
+    };
+    warnings.push(DetectWarning { path: path.to_path_buf(), message });
+}
+
 fn collect_package_json_deps(json: &serde_json::Value) -> HashSet<String> {
     let mut deps = HashSet::new();
     for key in &["dependencies", "devDependencies", "peerDependencies"] {
@@ -3,7 +3,10 @@
 //! Each `#[case]` gets an isolated `TempDir` — no shared state.
 
 use orchestra_core::types::ProjectType;
-use orchestra_detector::{detect_stack, Confidence};
+use orchestra_detector::{
+    detect_stack, detect_stack_detailed, detect_stack_with, indicator_files, Confidence,
+    DetectError, DetectorOptions,
+};
 use rstest::rstest;
 use std::fs;
 use tempfile::TempDir;
@@ -17,7 +20,15 @@ fn make_dir() -> TempDir {
 }
 
 fn write(dir: &TempDir, filename: &str, content: &str) {
-    fs::write(dir.path().join(filename), content).expect("write fixture");
+    let path = dir.path().join(filename);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create fixture dir");
+    }
+    fs::write(path, content).expect("write fixture");
+}
+
+fn depth(max_depth: usize) -> DetectorOptions {
+    DetectorOptions { max_depth, ..DetectorOptions::default() }
 }
 
 // ---------------------------------------------------------------------------
@@ -194,6 +205,173 @@ fn kotlin_gradle_kts() {
     assert_eq!(s.primary_language, "Kotlin");
 }
 
+const COMPOSE_APP: &[(&str, &str)] = &[
+    ("settings.gradle.kts", "rootProject.name = \"notes\"\ninclude(\":app\")\n"),
+    (
+        "build.gradle.kts",
+        "plugins {\n    id(\"com.android.application\") version \"8.5.0\" apply false\n    id(\"org.jetbrains.kotlin.android\") version \"2.0.0\" apply false\n}\n",
+    ),
+    (
+        "app/build.gradle.kts",
+        "plugins { id(\"com.android.application\") }\nandroid { buildFeatures { compose = true } }\ndependencies { implementation(\"androidx.compose.ui:ui:1.6.8\") }\n",
+    ),
+    ("app/src/main/AndroidManifest.xml", "<manifest />\n"),
+];
+
+const KMP_PROJECT: &[(&str, &str)] = &[
+    ("settings.gradle.kts", "include(\":shared\")\n"),
+    (
+        "build.gradle.kts",
+        "plugins {\n    kotlin(\"multiplatform\") version \"2.0.0\"\n}\nkotlin { androidTarget()\n    iosArm64() }\n",
+    ),
+];
+
+const GROOVY_ANDROID_APP: &[(&str, &str)] = &[
+    ("settings.gradle", "include ':app'\n"),
+    ("app/build.gradle", "apply plugin: 'com.android.application'\n"),
+];
+
+const SPRING_KOTLIN: &[(&str, &str)] = &[
+    ("settings.gradle.kts", "rootProject.name = \"api\"\n"),
+    (
+        "build.gradle.kts",
+        "plugins {\n    id(\"org.springframework.boot\") version \"3.3.0\"\n    kotlin(\"jvm\") version \"2.0.0\"\n}\n",
+    ),
+];
+
+#[rstest]
+#[case::compose_android(COMPOSE_APP, "Kotlin", "Jetpack Compose", ProjectType::Mobile)]
+#[case::kotlin_multiplatform(KMP_PROJECT, "Kotlin", "Kotlin Multiplatform", ProjectType::Mobile)]
+#[case::groovy_android(GROOVY_ANDROID_APP, "Java", "Android", ProjectType::Mobile)]
+#[case::spring_kotlin(SPRING_KOTLIN, "Kotlin", "Spring Boot", ProjectType::Backend)]
+fn gradle_mobile_detection(
+    #[case] files: &[(&str, &str)],
+    #[case] lang: &str,
+    #[case] fw: &str,
+    #[case] pt: ProjectType,
+) {
+    let dir = make_dir();
+    for (file, content) in files {
+        write(&dir, file, content);
+    }
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, lang);
+    assert_eq!(s.framework.as_deref(), Some(fw));
+    assert_eq!(s.project_type, pt);
+    assert_eq!(s.confidence, Confidence::High);
+}
+
+// ---------------------------------------------------------------------------
+// .NET
+// ---------------------------------------------------------------------------
+
+const SLN: &str = r#"
+Microsoft Visual Studio Solution File, Format Version 12.00
+Project("{2150E333-8FDC-42A3-9474-1A3956D46DE8}") = "src", "src", "{11111111-1111-1111-1111-111111111111}"
+EndProject
+Project("{9A19103F-16F7-4668-BE54-9A1E7A4F7556}") = "Shop.Api", "src\Shop.Api\Shop.Api.csproj", "{22222222-2222-2222-2222-222222222222}"
+EndProject
+Project("{9A19103F-16F7-4668-BE54-9A1E7A4F7556}") = "Shop.Tests", "tests\Shop.Tests\Shop.Tests.csproj", "{33333333-3333-3333-3333-333333333333}"
+EndProject
+"#;
+
+const WEB_CSPROJ: &str = r#"<Project Sdk="Microsoft.NET.Sdk.Web">
+  <PropertyGroup>
+    <TargetFramework>net8.0</TargetFramework>
+  </PropertyGroup>
+</Project>
+"#;
+
+const TEST_CSPROJ: &str = r#"<Project Sdk="Microsoft.NET.Sdk">
+  <ItemGroup>
+    <PackageReference Include="xunit" Version="2.9.0" />
+  </ItemGroup>
+</Project>
+"#;
+
+#[test]
+fn dotnet_solution_reads_projects_under_src() {
+    let dir = make_dir();
+    write(&dir, "Shop.sln", SLN);
+    write(&dir, "src/Shop.Api/Shop.Api.csproj", WEB_CSPROJ);
+    write(&dir, "tests/Shop.Tests/Shop.Tests.csproj", TEST_CSPROJ);
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, "C#");
+    assert_eq!(s.framework.as_deref(), Some("ASP.NET Core"));
+    assert_eq!(s.project_type, ProjectType::Backend);
+    assert_eq!(s.confidence, Confidence::High);
+    assert_eq!(s.detail.as_deref(), Some("src/Shop.Api/Shop.Api.csproj"));
+}
+
+#[test]
+fn dotnet_src_projects_found_without_solution() {
+    let dir = make_dir();
+    write(&dir, "src/Shop.Api/Shop.Api.csproj", WEB_CSPROJ);
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.framework.as_deref(), Some("ASP.NET Core"));
+}
+
+#[test]
+fn fsproj_only_repo_is_fsharp() {
+    let dir = make_dir();
+    write(
+        &dir,
+        "Api.fsproj",
+        r#"<Project Sdk="Microsoft.NET.Sdk.Web"><ItemGroup><Compile Include="Program.fs" /></ItemGroup></Project>"#,
+    );
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, "F#");
+    assert_eq!(s.framework.as_deref(), Some("ASP.NET Core"));
+    assert_eq!(s.detail, None);
+}
+
+#[rstest]
+#[case::target_framework("<TargetFrameworks>net8.0-android;net8.0-ios</TargetFrameworks>")]
+#[case::use_maui("<TargetFramework>net8.0</TargetFramework><UseMaui>true</UseMaui>")]
+fn maui_detection(#[case] properties: &str) {
+    let dir = make_dir();
+    write(
+        &dir,
+        "App.csproj",
+        &format!(r#"<Project Sdk="Microsoft.NET.Sdk"><PropertyGroup>{properties}</PropertyGroup></Project>"#),
+    );
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.framework.as_deref(), Some("MAUI"));
+    assert_eq!(s.project_type, ProjectType::Mobile);
+}
+
+#[test]
+fn blazor_wasm_sdk_detection() {
+    let dir = make_dir();
+    write(
+        &dir,
+        "Client.csproj",
+        r#"<Project Sdk="Microsoft.NET.Sdk.BlazorWebAssembly"><PropertyGroup><TargetFramework>net8.0</TargetFramework></PropertyGroup></Project>"#,
+    );
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.framework.as_deref(), Some("Blazor"));
+    assert_eq!(s.project_type, ProjectType::Frontend);
+}
+
+#[test]
+fn unity_detected_from_project_settings() {
+    let dir = make_dir();
+    write(&dir, "ProjectSettings/ProjectVersion.txt", "m_EditorVersion: 2022.3.10f1\n");
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, "C#");
+    assert_eq!(s.framework.as_deref(), Some("Unity"));
+}
+
+#[test]
+fn plain_csproj_has_no_framework() {
+    let dir = make_dir();
+    write(&dir, "Tool.csproj", TEST_CSPROJ);
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, "C#");
+    assert_eq!(s.framework, None);
+    assert_eq!(s.confidence, Confidence::Medium);
+}
+
 // ---------------------------------------------------------------------------
 // Ruby
 // ---------------------------------------------------------------------------
@@ -210,6 +388,126 @@ fn ruby_detection(#[case] gemfile_line: &str, #[case] fw: &str) {
     assert_eq!(s.framework.as_deref(), Some(fw));
 }
 
+const RAILS_APP: &str = "module Shop\n  class Application < Rails::Application\n    config.load_defaults 7.1\n  end\nend\n";
+const RAILS_API: &str = "module Shop\n  class Application < Rails::Application\n    config.api_only = true\n  end\nend\n";
+
+#[rstest]
+#[case("gem \"jekyll\", \"~> 4.3\"", &[], "Jekyll", None, ProjectType::Frontend)]
+#[case("gem \"rails\"", &[("config/application.rb", RAILS_API), ("app/views/.keep", "")], "Rails", Some("Rails (API-only)"), ProjectType::Backend)]
+#[case("gem \"rails\"", &[("config/application.rb", RAILS_APP)], "Rails", Some("Rails (API-only)"), ProjectType::Backend)]
+#[case("gem \"rails\"\ngem \"turbo-rails\"", &[("config/application.rb", RAILS_APP), ("app/views/layouts/application.html.erb", "")], "Rails", Some("Rails + Hotwire"), ProjectType::Backend)]
+#[case("gem 'rails'", &[("app/views/home/index.html.erb", "")], "Rails", Some("Rails"), ProjectType::Backend)]
+fn ruby_variants(
+    #[case] gemfile: &str,
+    #[case] files: &[(&str, &str)],
+    #[case] fw: &str,
+    #[case] variant: Option<&str>,
+    #[case] pt: ProjectType,
+) {
+    let dir = make_dir();
+    write(&dir, "Gemfile", &format!("source 'https://rubygems.org'\n{gemfile}\n"));
+    for (file, content) in files {
+        write(&dir, file, content);
+    }
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.framework.as_deref(), Some(fw));
+    assert_eq!(s.variant.as_deref(), variant);
+    assert_eq!(s.project_type, pt);
+}
+
+#[test]
+fn rails_ignores_excluded_view_directories() {
+    let dir = make_dir();
+    write(&dir, "Gemfile", "gem \"rails\"\n");
+    write(&dir, "config/application.rb", RAILS_APP);
+    write(&dir, "app/views/home/index.html.erb", "");
+    let options = DetectorOptions {
+        ignore_patterns: vec!["app".to_string()],
+        ..DetectorOptions::default()
+    };
+    let s = detect_stack_with(dir.path(), &options).expect("detect");
+    assert_eq!(s.variant.as_deref(), Some("Rails (API-only)"));
+}
+
+// ---------------------------------------------------------------------------
+// Zig
+// ---------------------------------------------------------------------------
+
+const ZIG_ZON: &str = ".{\n    .name = .tool,\n    .dependencies = .{\n        .zap = .{ .url = \"https://example.com/zap.tar.gz\" },\n    },\n}\n";
+
+#[rstest]
+#[case::build_and_manifest(&[("build.zig", "const std = @import(\"std\");\n"), ("build.zig.zon", ZIG_ZON)])]
+#[case::build_only(&[("build.zig", "const std = @import(\"std\");\n")])]
+#[case::manifest_only(&[("build.zig.zon", ZIG_ZON)])]
+fn zig_detection(#[case] files: &[(&str, &str)]) {
+    let dir = make_dir();
+    for (name, content) in files {
+        write(&dir, name, content);
+    }
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, "Zig");
+    assert_eq!(s.framework, None);
+    assert_eq!(s.project_type, ProjectType::Backend);
+    assert_eq!(s.confidence, Confidence::Medium);
+    // `build.zig.zon` alone is enough, and is recorded as an indicator.
+    let names: Vec<&str> = files.iter().map(|(name, _)| *name).collect();
+    assert_eq!(indicator_files(dir.path()), names);
+}
+
+#[test]
+fn zig_takes_priority_over_package_json() {
+    let dir = make_dir();
+    write(&dir, "build.zig", "const std = @import(\"std\");\n");
+    write(&dir, "package.json", r#"{"devDependencies": {"prettier": "^3.0.0"}}"#);
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, "Zig");
+}
+
+// ---------------------------------------------------------------------------
+// Nim
+// ---------------------------------------------------------------------------
+
+#[rstest]
+#[case("requires \"jester >= 0.6.0\"", Some("Jester"), Confidence::High)]
+#[case("requires \"nim >= 2.0.0\", \"prologue\"", Some("Prologue"), Confidence::High)]
+#[case("requires \"nim >= 2.0.0\"", None, Confidence::Medium)]
+fn nim_detection(
+    #[case] requires: &str,
+    #[case] fw: Option<&str>,
+    #[case] confidence: Confidence,
+) {
+    let dir = make_dir();
+    write(&dir, "server.nimble", &format!("version = \"0.1.0\"\n# jester is not required below\n{requires}\n"));
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, "Nim");
+    assert_eq!(s.framework.as_deref(), fw);
+    assert_eq!(s.project_type, ProjectType::Backend);
+    assert_eq!(s.confidence, confidence);
+}
+
+// ---------------------------------------------------------------------------
+// OCaml
+// ---------------------------------------------------------------------------
+
+#[rstest]
+#[case::dream_in_dune_project("dune-project", "(lang dune 3.0)\n(package (name api) (depends dream))\n", Some("Dream"), Confidence::High)]
+#[case::opium_in_opam("api.opam", "opam-version: \"2.0\"\ndepends: [ \"opium\" ]\n", Some("Opium"), Confidence::High)]
+#[case::plain_dune_project("dune-project", "(lang dune 3.0)\n", None, Confidence::Medium)]
+fn ocaml_detection(
+    #[case] file: &str,
+    #[case] content: &str,
+    #[case] fw: Option<&str>,
+    #[case] confidence: Confidence,
+) {
+    let dir = make_dir();
+    write(&dir, file, content);
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, "OCaml");
+    assert_eq!(s.framework.as_deref(), fw);
+    assert_eq!(s.project_type, ProjectType::Backend);
+    assert_eq!(s.confidence, confidence);
+}
+
 // ---------------------------------------------------------------------------
 // JavaScript / TypeScript
 // ---------------------------------------------------------------------------
@@ -226,7 +524,14 @@ fn pkg_json(deps: &[(&str, &str)]) -> String {
 #[case("vue", "^3.0.0", "JavaScript", "Vue", ProjectType::Frontend)]
 #[case("@angular/core", "^17.0.0", "JavaScript", "Angular", ProjectType::Frontend)]
 #[case("svelte", "^4.0.0", "JavaScript", "Svelte", ProjectType::Frontend)]
+#[case("svelte", "^5.0.0", "JavaScript", "Svelte", ProjectType::Frontend)]
 #[case("@sveltejs/kit", "^2.0.0", "JavaScript", "SvelteKit", ProjectType::Frontend)]
+#[case("solid-js", "^1.8.0", "JavaScript", "Solid", ProjectType::Frontend)]
+#[case("@solidjs/start", "^1.0.0", "JavaScript", "SolidStart", ProjectType::Frontend)]
+#[case("@builder.io/qwik", "^1.5.0", "JavaScript", "Qwik", ProjectType::Frontend)]
+#[case("@builder.io/qwik-city", "^1.5.0", "JavaScript", "Qwik City", ProjectType::Frontend)]
+#[case("@tanstack/start", "^1.0.0", "JavaScript", "TanStack Start", ProjectType::Frontend)]
+#[case("@tanstack/react-start", "^1.0.0", "JavaScript", "TanStack Start", ProjectType::Frontend)]
 #[case("astro", "^4.0.0", "JavaScript", "Astro", ProjectType::Frontend)]
 #[case("gatsby", "^5.0.0", "JavaScript", "Gatsby", ProjectType::Frontend)]
 #[case("express", "^4.0.0", "JavaScript", "Express", ProjectType::Backend)]
@@ -249,6 +554,49 @@ fn js_framework_detection(
     assert_eq!(s.confidence, Confidence::High);
 }
 
+/// Meta-frameworks win over the library they build on, which their
+/// projects always depend on too.
+#[rstest]
+#[case::solid_start(&[("solid-js", "^1.8.0"), ("@solidjs/start", "^1.0.0"), ("vite", "^5.0.0")], "SolidStart")]
+#[case::qwik_city(&[("@builder.io/qwik", "^1.5.0"), ("@builder.io/qwik-city", "^1.5.0")], "Qwik City")]
+#[case::tanstack_start(&[("react", "^18.0.0"), ("@tanstack/react-start", "^1.0.0")], "TanStack Start")]
+#[case::svelte_5_with_vite(&[("svelte", "^5.0.0"), ("vite", "^5.0.0")], "Svelte")]
+#[case::react_with_vite(&[("react", "^18.0.0"), ("vite", "^5.0.0")], "React")]
+fn js_meta_framework_wins_over_its_library(#[case] deps: &[(&str, &str)], #[case] fw: &str) {
+    let dir = make_dir();
+    write(&dir, "package.json", &pkg_json(deps));
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.framework.as_deref(), Some(fw));
+    assert_eq!(s.project_type, ProjectType::Frontend);
+    assert_eq!(s.confidence, Confidence::High);
+}
+
+/// Frontend build tools without a framework: a bare Vite app, or only dev
+/// dependencies beside an empty `dependencies` object.
+#[rstest]
+#[case::bare_vite(r#"{"devDependencies": {"vite": "^5.0.0"}}"#, "JavaScript", Some("Vite"))]
+#[case::dev_deps_only(r#"{"dependencies": {}, "devDependencies": {"vite": "^5.0.0", "typescript": "^5.4.0"}}"#, "TypeScript", Some("Vite"))]
+#[case::webpack_only(r#"{"devDependencies": {"webpack": "^5.0.0"}}"#, "JavaScript", None)]
+fn js_build_tool_only_is_frontend(#[case] package_json: &str, #[case] lang: &str, #[case] fw: Option<&str>) {
+    let dir = make_dir();
+    write(&dir, "package.json", package_json);
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, lang);
+    assert_eq!(s.framework.as_deref(), fw);
+    assert_eq!(s.project_type, ProjectType::Frontend);
+    assert_eq!(s.confidence, Confidence::Medium);
+}
+
+#[test]
+fn js_without_framework_or_build_tool_stays_backend() {
+    let dir = make_dir();
+    write(&dir, "package.json", r#"{"dependencies": {"dotenv": "^16.0.0"}}"#);
+    let s = detect_stack(dir.path()).expect("detect");
+    assert!(s.framework.is_none());
+    assert_eq!(s.project_type, ProjectType::Backend);
+    assert_eq!(s.confidence, Confidence::Medium);
+}
+
 #[test]
 fn typescript_detected_via_tsconfig() {
     let dir = make_dir();
@@ -267,6 +615,49 @@ fn remix_detection() {
     assert_eq!(s.framework.as_deref(), Some("Remix"));
 }
 
+#[rstest]
+#[case::array(r#"{"private": true, "workspaces": ["apps/*", "packages/*"], "devDependencies": {"typescript": "^5.0.0", "turbo": "^2.0.0"}}"#)]
+#[case::packages_object(r#"{"private": true, "workspaces": {"packages": ["apps/*", "packages/*"]}, "devDependencies": {"typescript": "^5.0.0"}}"#)]
+fn js_workspace_picks_dominant_framework(#[case] root: &str) {
+    let dir = make_dir();
+    write(&dir, "package.json", root);
+    write(&dir, "apps/web/package.json", &pkg_json(&[("next", "^14.0.0"), ("react", "^18.0.0")]));
+    write(&dir, "packages/ui/package.json", &pkg_json(&[("react", "^18.0.0")]));
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, "TypeScript");
+    assert_eq!(s.framework.as_deref(), Some("Next.js"));
+    assert_eq!(s.project_type, ProjectType::Frontend);
+    assert_eq!(s.confidence, Confidence::High);
+    assert_eq!(s.detail.as_deref(), Some("apps/web"));
+}
+
+#[test]
+fn js_pnpm_workspace_members_are_read() {
+    let dir = make_dir();
+    write(&dir, "package.json", &pkg_json(&[("turbo", "^2.0.0")]));
+    write(&dir, "pnpm-workspace.yaml", "packages:\n  - 'services/*'\n  - '!services/legacy'\n");
+    write(&dir, "services/api/package.json", &pkg_json(&[("@nestjs/core", "^10.0.0")]));
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.framework.as_deref(), Some("NestJS"));
+    assert_eq!(s.project_type, ProjectType::Backend);
+    assert_eq!(s.detail.as_deref(), Some("services/api"));
+}
+
+#[test]
+fn js_root_framework_wins_over_workspace_members() {
+    let dir = make_dir();
+    write(
+        &dir,
+        "package.json",
+        r#"{"workspaces": ["apps/*"], "dependencies": {"express": "^4.0.0"}}"#,
+    );
+    write(&dir, "apps/web/package.json", &pkg_json(&[("next", "^14.0.0")]));
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.framework.as_deref(), Some("Express"));
+    assert_eq!(s.project_type, ProjectType::Backend);
+    assert!(s.detail.is_none());
+}
+
 // ---------------------------------------------------------------------------
 // Python
 // ---------------------------------------------------------------------------
@@ -303,6 +694,217 @@ fn python_pyproject_toml_detection() {
     assert_eq!(s.framework.as_deref(), Some("FastAPI"));
 }
 
+#[test]
+fn python_pep621_dependencies_detection() {
+    let dir = make_dir();
+    write(
+        &dir,
+        "pyproject.toml",
+        "[project]\nname = \"shop\"\ndependencies = [\"Django>=4.2,<5\", \"psycopg[binary]\"]\n\n[dependency-groups]\ndev = [\"pytest>=8\", \"pytest-django\"]\n",
+    );
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.framework.as_deref(), Some("Django"));
+    assert_eq!(s.project_type, ProjectType::Backend);
+}
+
+#[rstest]
+#[case::optional_dependencies("[project]\nname = \"svc\"\ndependencies = [\"httpx\"]\n\n[project.optional-dependencies]\ndev = [\"fastapi\"]\n")]
+#[case::dependency_groups("[project]\nname = \"svc\"\ndependencies = []\n\n[dependency-groups]\ndev = [\"fastapi\"]\n")]
+#[case::poetry_group("[tool.poetry.dependencies]\npython = \"^3.12\"\n\n[tool.poetry.group.dev.dependencies]\nfastapi = \"*\"\n")]
+fn python_dev_only_framework_is_ignored(#[case] pyproject: &str) {
+    let dir = make_dir();
+    write(&dir, "pyproject.toml", pyproject);
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, "Python");
+    assert_eq!(s.framework, None);
+    assert_eq!(s.confidence, Confidence::Medium);
+}
+
+#[test]
+fn python_requirements_ignore_comments_and_version_specifiers() {
+    let dir = make_dir();
+    write(
+        &dir,
+        "requirements.txt",
+        "# migrated off django last year\n-r base.txt\npytest==8.1 # tests\nrequests[socks]>=2.31\n",
+    );
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.framework, None, "comment mentioning django must not match");
+}
+
+#[rstest]
+#[case::poetry("poetry.lock", "poetry")]
+#[case::uv("uv.lock", "uv")]
+fn python_package_manager_from_lock_file(#[case] lock: &str, #[case] manager: &str) {
+    let dir = make_dir();
+    write(&dir, "pyproject.toml", "[project]\nname = \"svc\"\ndependencies = [\"flask\"]\n");
+    write(&dir, lock, "");
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.framework.as_deref(), Some("Flask"));
+    assert_eq!(s.package_manager.as_deref(), Some(manager));
+}
+
+#[rstest]
+#[case::pnpm("pnpm-lock.yaml", "pnpm")]
+#[case::yarn("yarn.lock", "yarn")]
+#[case::bun("bun.lockb", "bun")]
+#[case::npm("package-lock.json", "npm")]
+fn node_package_manager_from_lock_file(#[case] lock: &str, #[case] manager: &str) {
+    let dir = make_dir();
+    write(
+        &dir,
+        "package.json",
+        r#"{"dependencies": {"next": "14.0.0"}}"#,
+    );
+    write(&dir, lock, "");
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.framework.as_deref(), Some("Next.js"));
+    assert_eq!(s.package_manager.as_deref(), Some(manager));
+}
+
+#[test]
+fn node_package_manager_field_wins_over_lock_file() {
+    let dir = make_dir();
+    write(
+        &dir,
+        "package.json",
+        r#"{"packageManager": "yarn@4.1.0", "dependencies": {}}"#,
+    );
+    write(&dir, "package-lock.json", "{}");
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.package_manager.as_deref(), Some("yarn"));
+
+    let dir = make_dir();
+    write(&dir, "package.json", "{}");
+    assert_eq!(
+        detect_stack(dir.path()).expect("detect").package_manager,
+        None
+    );
+}
+
+#[test]
+fn python_package_manager_pip_from_requirements() {
+    let dir = make_dir();
+    write(&dir, "requirements.txt", "Django>=4.2\npytest\n");
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.framework.as_deref(), Some("Django"));
+    assert_eq!(s.package_manager.as_deref(), Some("pip"));
+}
+
+#[test]
+fn python_invalid_pyproject_is_parse_error() {
+    let dir = make_dir();
+    write(&dir, "pyproject.toml", "[project\nname = ");
+    let err = detect_stack(dir.path()).unwrap_err();
+    assert!(err.to_string().contains("pyproject.toml"), "got: {err}");
+}
+
+// ---------------------------------------------------------------------------
+// Infrastructure as code
+// ---------------------------------------------------------------------------
+
+#[rstest]
+#[case("main.tf", "resource \"null_resource\" \"x\" {}\n", "HCL", "Terraform")]
+#[case("terragrunt.hcl", "terraform {}\n", "HCL", "Terragrunt")]
+#[case("ansible.cfg", "[defaults]\n", "YAML", "Ansible")]
+#[case("playbook.yml", "- hosts: all\n", "YAML", "Ansible")]
+#[case("Chart.yaml", "apiVersion: v2\nname: api\n", "YAML", "Helm")]
+#[case("Pulumi.yaml", "name: infra\nruntime: go\n", "Go", "Pulumi")]
+#[case("Pulumi.yaml", "name: infra\nruntime:\n  name: nodejs\n", "JavaScript", "Pulumi")]
+fn infra_detection(
+    #[case] file: &str,
+    #[case] content: &str,
+    #[case] lang: &str,
+    #[case] fw: &str,
+) {
+    let dir = make_dir();
+    write(&dir, file, content);
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, lang);
+    assert_eq!(s.framework.as_deref(), Some(fw));
+    assert_eq!(s.project_type, ProjectType::Infra);
+    assert_eq!(s.confidence, Confidence::High);
+}
+
+#[test]
+fn pulumi_python_is_infra_not_python_backend() {
+    let dir = make_dir();
+    write(&dir, "Pulumi.yaml", "name: infra\nruntime:\n  name: python\n");
+    write(&dir, "requirements.txt", "pulumi>=3.0\npulumi-aws\n");
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, "Python");
+    assert_eq!(s.framework.as_deref(), Some("Pulumi"));
+    assert_eq!(s.project_type, ProjectType::Infra);
+}
+
+#[test]
+fn pulumi_unknown_runtime_is_medium_confidence() {
+    let dir = make_dir();
+    write(&dir, "Pulumi.yaml", "name: infra\n");
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.framework.as_deref(), Some("Pulumi"));
+    assert_eq!(s.confidence, Confidence::Medium);
+}
+
+#[test]
+fn app_manifest_wins_over_infra_files_beside_it() {
+    let dir = make_dir();
+    write(
+        &dir,
+        "package.json",
+        r#"{"dependencies":{"express":"^4.0.0"}}"#,
+    );
+    write(&dir, "Chart.yaml", "apiVersion: v2\nname: api\n");
+    write(&dir, "main.tf", "terraform {}\n");
+    let s = detect_stack(dir.path()).expect("detect");
+    assert_eq!(s.primary_language, "JavaScript");
+    assert_eq!(s.framework.as_deref(), Some("Express"));
+    assert_ne!(s.project_type, ProjectType::Infra);
+}
+
+#[test]
+fn terraform_in_subdirectory_needs_depth() {
+    let dir = make_dir();
+    write(&dir, "infra/main.tf", "terraform {}\n");
+    assert!(matches!(detect_stack(dir.path()), Err(DetectError::UnknownStack { .. })));
+    let s = detect_stack_with(dir.path(), &depth(2)).expect("detect");
+    assert_eq!(s.framework.as_deref(), Some("Terraform"));
+}
+
+// ---------------------------------------------------------------------------
+// Default commands
+// ---------------------------------------------------------------------------
+
+#[rstest]
+#[case::rust(&[("Cargo.toml", "[package]\nname = \"demo\"\n")], "cargo test --workspace")]
+#[case::node(&[("package.json", r#"{"name":"demo"}"#)], "npm test")]
+#[case::pip(&[("requirements.txt", "requests\n")], "pytest")]
+#[case::poetry(
+    &[("pyproject.toml", "[project]\nname = \"demo\"\n"), ("poetry.lock", "")],
+    "poetry run pytest"
+)]
+fn default_commands_follow_the_stack(
+    #[case] files: &[(&str, &str)],
+    #[case] expected_test: &str,
+) {
+    let dir = make_dir();
+    for (file, content) in files {
+        write(&dir, file, content);
+    }
+
+    let commands = detect_stack(dir.path()).unwrap().default_commands();
+
+    assert_eq!(commands.get("test").map(String::as_str), Some(expected_test));
+}
+
+#[test]
+fn default_commands_are_empty_for_unmapped_languages() {
+    let dir = make_dir();
+    write(&dir, "Gemfile", "source 'https://rubygems.org'\n");
+
+    assert!(detect_stack(dir.path()).unwrap().default_commands().is_empty());
+}
+
 // ---------------------------------------------------------------------------
 // Unknown stack
 // ---------------------------------------------------------------------------
@@ -316,3 +918,155 @@ fn empty_dir_returns_unknown_stack_error() {
         "got: {err}"
     );
 }
+
+// ---------------------------------------------------------------------------
+// Depth and ignore handling
+// ---------------------------------------------------------------------------
+
+#[test]
+fn default_depth_only_inspects_root() {
+    let dir = make_dir();
+    write(&dir, "api/Cargo.toml", "[package]\nname = \"api\"\n");
+    assert!(detect_stack(dir.path()).is_err());
+
+    let s = detect_stack_with(dir.path(), &depth(2)).expect("detect");
+    assert_eq!(s.primary_language, "Rust");
+}
+
+#[rstest]
+#[case("vendor")]
+#[case("node_modules")]
+#[case("target")]
+#[case("dist")]
+#[case(".git")]
+fn builtin_ignored_dirs_are_never_scanned(#[case] ignored: &str) {
+    let dir = make_dir();
+    write(&dir, &format!("{ignored}/Cargo.toml"), "[package]\nname = \"dep\"\n");
+    let err = detect_stack_with(dir.path(), &depth(3)).unwrap_err();
+    assert!(err.to_string().contains("could not determine stack"), "got: {err}");
+}
+
+#[test]
+fn root_stack_wins_over_vendored_manifest() {
+    let dir = make_dir();
+    write(&dir, "vendor/Cargo.toml", "[package]\nname = \"dep\"\n");
+    write(&dir, "go.mod", "module example.com/app\n");
+    let s = detect_stack_with(dir.path(), &depth(2)).expect("detect");
+    assert_eq!(s.primary_language, "Go");
+}
+
+#[test]
+fn orchestraignore_excludes_matching_subdir() {
+    let dir = make_dir();
+    write(&dir, "legacy/composer.json", r#"{"require": {"laravel/framework": "^10"}}"#);
+    write(&dir, "services/api/go.mod", "module example.com/api\n");
+
+    let s = detect_stack_with(dir.path(), &depth(3)).expect("detect");
+    assert_eq!(s.primary_language, "PHP", "legacy/ sorts first without an ignore");
+
+    write(&dir, ".orchestraignore", "# old code\n\nleg*/\n");
+    let s = detect_stack_with(dir.path(), &depth(3)).expect("detect");
+    assert_eq!(s.primary_language, "Go");
+}
+
+#[test]
+fn option_ignore_patterns_match_relative_paths() {
+    let dir = make_dir();
+    write(&dir, "apps/web/package.json", r#"{"dependencies": {"next": "14"}}"#);
+    write(&dir, "apps/worker/requirements.txt", "celery\n");
+
+    let options = DetectorOptions {
+        max_depth: 3,
+        ignore_patterns: vec!["apps/w?b".to_string()],
+    };
+    let s = detect_stack_with(dir.path(), &options).expect("detect");
+    assert_eq!(s.primary_language, "Python");
+}
+
+// ---------------------------------------------------------------------------
+// Unreadable indicator files
+// ---------------------------------------------------------------------------
+
+#[cfg(unix)]
+#[test]
+fn unreadable_composer_json_falls_through_to_cargo_toml() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = make_dir();
+    write(&dir, "composer.json", r#"{"require": {"laravel/framework": "^11.0"}}"#);
+    write(&dir, "Cargo.toml", "[package]\nname = \"app\"\n");
+    let composer = dir.path().join("composer.json");
+    fs::set_permissions(&composer, fs::Permissions::from_mode(0o000)).expect("chmod");
+    if fs::read(&composer).is_ok() {
+        // Running as root: permissions are not enforced.
+        return;
+    }
+
+    let (s, warnings) =
+        detect_stack_detailed(dir.path(), &DetectorOptions::default()).expect("detect");
+    assert_eq!(s.primary_language, "Rust");
+    assert_eq!(warnings.len(), 1, "warnings: {warnings:?}");
+    assert!(warnings[0].to_string().contains("composer.json"));
+    assert_eq!(detect_stack(dir.path()).expect("detect").primary_language, "Rust");
+}
+
+#[cfg(unix)]
+#[test]
+fn dangling_symlink_indicator_is_skipped_with_warning() {
+    let dir = make_dir();
+    std::os::unix::fs::symlink(dir.path().join("missing.json"), dir.path().join("composer.json"))
+        .expect("symlink");
+    write(&dir, "Cargo.toml", "[package]\nname = \"app\"\n");
+
+    let (s, warnings) =
+        detect_stack_detailed(dir.path(), &DetectorOptions::default()).expect("detect");
+    assert_eq!(s.primary_language, "Rust");
+    assert_eq!(warnings.len(), 1, "warnings: {warnings:?}");
+    assert!(warnings[0].path.ends_with("composer.json"));
+    assert!(warnings[0].message.contains("dangling symlink"));
+}
+
+#[test]
+fn non_utf8_indicator_is_skipped_with_warning() {
+    let dir = make_dir();
+    fs::write(dir.path().join("composer.json"), [0xff, 0xfe, 0x00]).expect("write bytes");
+    write(&dir, "go.mod", "module example.com/app\n");
+
+    let (s, warnings) =
+        detect_stack_detailed(dir.path(), &DetectorOptions::default()).expect("detect");
+    assert_eq!(s.primary_language, "Go");
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].to_string().contains("composer.json"));
+}
+
+#[test]
+fn missing_root_is_an_io_error() {
+    let dir = make_dir();
+    let err = detect_stack(&dir.path().join("gone")).unwrap_err();
+    assert!(matches!(err, DetectError::Io(_)), "got: {err}");
+}
+
+#[test]
+fn indicator_files_lists_root_manifests_only() {
+    let dir = make_dir();
+    write(&dir, "Cargo.toml", "[package]\nname = \"app\"\n");
+    write(&dir, "main.tf", "");
+    write(&dir, "README.md", "# app\n");
+    write(&dir, "web/package.json", "{}");
+
+    assert_eq!(indicator_files(dir.path()), ["Cargo.toml", "main.tf"]);
+    assert!(indicator_files(&dir.path().join("gone")).is_empty());
+}
+
+#[test]
+fn indicator_files_include_root_files_that_change_javascript_detection() {
+    let dir = make_dir();
+    write(&dir, "package.json", "{}");
+    write(&dir, "pnpm-workspace.yaml", "packages:\n  - 'apps/*'\n");
+    write(&dir, "tsconfig.json", "{}");
+
+    assert_eq!(
+        indicator_files(dir.path()),
+        ["package.json", "pnpm-workspace.yaml", "tsconfig.json"]
+    );
+}
@@ -0,0 +1,319 @@
+//! Token budgets for rendered agent files.
+//!
+//! Agent instruction files that grow past a few thousand tokens crowd out
+//! the work they are meant to support. [`render_within_budget`] estimates
+//! the size of each file an agent renders ([`estimate_tokens`], about four
+//! characters per token) and compares it to [`AgentKind::token_budget`].
+//! When enforcement is on, an agent over budget is rendered again with the
+//! steps of [`Trim::LADDER`] applied one at a time, until every file fits
+//! or the ladder runs out. The ladder is fixed, so the same registry always
+//! trims to the same files.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::context::TemplateContext;
+use crate::engine::{AgentKind, Renderer};
+use crate::error::RenderError;
+
+/// Tasks kept by [`Trim::TaskCap`].
+pub const TRIMMED_MAX_TASKS: usize = 5;
+
+/// What a trimmed task renders in place of its description. The writeback
+/// parser reads it as "description unchanged", so editing a trimmed file
+/// does not clear descriptions from the registry.
+pub const ELIDED_DESCRIPTION: &str = "…";
+
+/// Approximate token count of `text`: one token per four characters.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+impl AgentKind {
+    /// Estimated tokens one of this agent's files should stay under.
+    pub fn token_budget(&self) -> usize {
+        match self {
+            AgentKind::Claude | AgentKind::Codex | AgentKind::Gemini => 6000,
+            AgentKind::Copilot | AgentKind::Cline | AgentKind::Antigravity => 4000,
+            AgentKind::RooCode => 4000,
+            AgentKind::Cursor | AgentKind::Windsurf | AgentKind::Zed => 3000,
+        }
+    }
+}
+
+/// How much of the context is left out to meet a budget. Each step keeps
+/// the ones before it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trim {
+    /// Nothing left out.
+    #[default]
+    None,
+    /// Task descriptions render as [`ELIDED_DESCRIPTION`].
+    TaskDescriptions,
+    /// At most [`TRIMMED_MAX_TASKS`] tasks; the rest count as overflow.
+    TaskCap,
+    /// The skills section is dropped.
+    Skills,
+}
+
+impl Trim {
+    /// The steps enforcement tries, in order.
+    pub const LADDER: [Trim; 3] = [Trim::TaskDescriptions, Trim::TaskCap, Trim::Skills];
+
+    /// Label used in warnings, e.g. `task descriptions`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Trim::None => "nothing",
+            Trim::TaskDescriptions => "task descriptions",
+            Trim::TaskCap => "task descriptions and tasks past the first 5",
+            Trim::Skills => "task descriptions, tasks past the first 5 and skills",
+        }
+    }
+}
+
+/// An agent whose largest file was over its token budget.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BudgetOverrun {
+    #[serde(
+        serialize_with = "serialize_agent_name",
+        deserialize_with = "deserialize_agent_name"
+    )]
+    pub agent: AgentKind,
+    /// The largest file, untrimmed.
+    pub path: PathBuf,
+    /// Estimated tokens of that file, untrimmed.
+    pub tokens: usize,
+    pub budget: usize,
+    /// What enforcement left out; [`Trim::None`] when it was off.
+    pub trim: Trim,
+    /// Estimated tokens of the largest file as rendered, after `trim`.
+    pub rendered_tokens: usize,
+}
+
+impl BudgetOverrun {
+    /// Whether the rendered files fit the budget after trimming.
+    pub fn fits(&self) -> bool {
+        self.rendered_tokens <= self.budget
+    }
+}
+
+fn serialize_agent_name<S: serde::Serializer>(
+    agent: &AgentKind,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(agent.name())
+}
+
+fn deserialize_agent_name<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<AgentKind, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    name.parse().map_err(serde::de::Error::custom)
+}
+
+/// An agent's rendered files, and how they compare to its budget.
+#[derive(Debug, Clone)]
+pub struct BudgetedRender {
+    pub outputs: Vec<(PathBuf, String)>,
+    /// `None` when every file was within budget to begin with.
+    pub overrun: Option<BudgetOverrun>,
+}
+
+/// Render `agent` with `ctx` and check its files against the agent's
+/// budget. With `enforce`, an agent over budget is rendered again with each
+/// [`Trim::LADDER`] step until its files fit, keeping the last attempt when
+/// none does. Files within budget are rendered exactly once, untouched.
+pub fn render_within_budget(
+    renderer: &Renderer,
+    ctx: &TemplateContext,
+    agent: AgentKind,
+    enforce: bool,
+) -> Result<BudgetedRender, RenderError> {
+    let budget = agent.token_budget();
+    let outputs = renderer.render_with_context(ctx, agent)?;
+    let Some((path, tokens)) = largest(&outputs).filter(|(_, tokens)| *tokens > budget) else {
+        return Ok(BudgetedRender {
+            outputs,
+            overrun: None,
+        });
+    };
+
+    let mut overrun = BudgetOverrun {
+        agent,
+        path,
+        tokens,
+        budget,
+        trim: Trim::None,
+        rendered_tokens: tokens,
+    };
+    let mut outputs = outputs;
+    if enforce {
+        for trim in Trim::LADDER {
+            outputs = renderer.render_with_context(&ctx.trimmed(trim), agent)?;
+            overrun.trim = trim;
+            overrun.rendered_tokens = largest(&outputs).map_or(0, |(_, tokens)| tokens);
+            if overrun.fits() {
+                break;
+            }
+        }
+    }
+    Ok(BudgetedRender {
+        outputs,
+        overrun: Some(overrun),
+    })
+}
+
+/// The largest output and its estimated tokens; the first one on a tie.
+fn largest(outputs: &[(PathBuf, String)]) -> Option<(PathBuf, usize)> {
+    outputs
+        .iter()
+        .map(|(path, content)| (path, estimate_tokens(content)))
+        .rev()
+        .max_by_key(|(_, tokens)| *tokens)
+        .map(|(path, tokens)| (path.clone(), tokens))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::Utc;
+    use orchestra_core::types::{
+        Codebase, CodebaseName, Project, ProjectName, ProjectType, Task, TaskId, TaskStatus,
+        CURRENT_SCHEMA_VERSION,
+    };
+
+    use super::*;
+
+    /// A codebase with `count` active tasks, each described at length, all
+    /// of them rendered.
+    fn oversized_context(count: usize) -> TemplateContext {
+        let now = Utc::now();
+        let tasks = (0..count)
+            .map(|i| Task {
+                id: TaskId::from(format!("T-{i:03}")),
+                title: format!("Task number {i}"),
+                status: TaskStatus::Pending,
+                description: Some("Long description of the work. ".repeat(20)),
+                subtasks: Vec::new(),
+                notes: Vec::new(),
+                assigned_agent: None,
+                created_at: now,
+                updated_at: now,
+                extra: BTreeMap::new(),
+            })
+            .collect();
+        let codebase = Codebase {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            name: CodebaseName::from("api"),
+            path: PathBuf::from("/code/api"),
+            projects: vec![Project {
+                name: ProjectName::from("api"),
+                project_type: ProjectType::Backend,
+                framework: None,
+                detection: None,
+                tasks,
+                agents: Vec::new(),
+                commands: BTreeMap::new(),
+                extra: BTreeMap::new(),
+            }],
+            conventions: Vec::new(),
+            skills: Vec::new(),
+            notes: Vec::new(),
+            tracked_files: Vec::new(),
+            max_rendered_tasks: Some(count),
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: Vec::new(),
+            agent_conventions: BTreeMap::new(),
+            created_at: now,
+            updated_at: now,
+            extra: BTreeMap::new(),
+        };
+        TemplateContext::from_codebase(&codebase)
+    }
+
+    fn renderer() -> Renderer {
+        Renderer::with_user_templates(None).expect("renderer")
+    }
+
+    #[test]
+    fn tokens_are_estimated_from_characters() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        // Characters, not bytes.
+        assert_eq!(estimate_tokens("éééé"), 1);
+    }
+
+    #[test]
+    fn oversized_output_is_reported_but_kept_without_enforcement() {
+        let ctx = oversized_context(40);
+        let render =
+            render_within_budget(&renderer(), &ctx, AgentKind::Cursor, false).expect("render");
+        let overrun = render.overrun.expect("over budget");
+        assert_eq!(overrun.budget, 3000);
+        assert!(overrun.tokens > overrun.budget);
+        assert_eq!(overrun.trim, Trim::None);
+        assert!(!overrun.fits());
+        assert!(render.outputs[0]
+            .1
+            .contains("Long description of the work."));
+    }
+
+    #[test]
+    fn enforcement_trims_until_the_output_fits() {
+        let ctx = oversized_context(40);
+        let render =
+            render_within_budget(&renderer(), &ctx, AgentKind::Cursor, true).expect("render");
+        let overrun = render.overrun.expect("over budget");
+        assert!(overrun.fits(), "{overrun:?}");
+        assert!(overrun.trim >= Trim::TaskDescriptions);
+        let rules = &render.outputs[0].1;
+        assert!(estimate_tokens(rules) <= AgentKind::Cursor.token_budget());
+        assert!(!rules.contains("Long description of the work."));
+        assert!(rules.contains("trimmed to fit"));
+
+        // The ladder is deterministic.
+        let again =
+            render_within_budget(&renderer(), &ctx, AgentKind::Cursor, true).expect("render");
+        assert_eq!(again.outputs, render.outputs);
+    }
+
+    #[test]
+    fn small_output_is_untouched() {
+        let ctx = TemplateContext::example();
+        let renderer = renderer();
+        let plain = renderer
+            .render_with_context(&ctx, AgentKind::Claude)
+            .expect("render");
+        let render =
+            render_within_budget(&renderer, &ctx, AgentKind::Claude, true).expect("render");
+        assert!(render.overrun.is_none());
+        assert_eq!(render.outputs, plain);
+    }
+
+    #[test]
+    fn trimmed_context_keeps_its_invariants() {
+        let ctx = oversized_context(12);
+        let trimmed = ctx.trimmed(Trim::TaskCap);
+        assert!(trimmed.meta.truncation_applied);
+        assert_eq!(trimmed.tasks.len(), TRIMMED_MAX_TASKS);
+        assert_eq!(trimmed.active_task_count, 12);
+        assert_eq!(trimmed.tasks_overflow_count, 12 - TRIMMED_MAX_TASKS);
+        assert!(trimmed
+            .tasks
+            .iter()
+            .all(|task| task.description.as_deref() == Some(ELIDED_DESCRIPTION)));
+        assert!(trimmed.to_tera_context().is_ok());
+
+        let untouched = ctx.trimmed(Trim::None);
+        assert!(!untouched.meta.truncation_applied);
+        assert_eq!(untouched.tasks.len(), 12);
+    }
+}
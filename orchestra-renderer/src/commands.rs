@@ -0,0 +1,256 @@
+//! Idiomatic commands for a detected stack.
+//!
+//! [`suggest`] maps a language, framework and package manager, as recorded
+//! by stack detection, to the commands an agent should reach for: how to
+//! install dependencies, run the tests, lint, build or start a dev server.
+//! The templates render them after any commands the codebase defines
+//! itself, which always win on a name clash.
+
+use std::collections::BTreeMap;
+
+/// Suggested commands by name (`test`, `lint`, `dev`, ...) for a stack.
+///
+/// `language` and `framework` use the detector's names (`"TypeScript"`,
+/// `"Next.js"`); `package_manager` its lower-case ids (`"pnpm"`, `"uv"`).
+/// An unknown language yields an empty map.
+pub fn suggest(
+    language: &str,
+    framework: Option<&str>,
+    package_manager: Option<&str>,
+) -> BTreeMap<String, String> {
+    let commands = match language {
+        "Rust" => rust(),
+        "Go" => go(),
+        "TypeScript" | "JavaScript" => node(framework, package_manager),
+        "Python" => python(framework, package_manager),
+        "PHP" => php(framework),
+        _ => Vec::new(),
+    };
+    commands
+        .into_iter()
+        .map(|(name, run)| (name.to_string(), run))
+        .collect()
+}
+
+fn rust() -> Vec<(&'static str, String)> {
+    vec![
+        ("build", "cargo build --workspace".to_string()),
+        ("fmt", "cargo fmt --all".to_string()),
+        ("lint", "cargo clippy --workspace --all-targets".to_string()),
+        ("test", "cargo test --workspace".to_string()),
+    ]
+}
+
+fn go() -> Vec<(&'static str, String)> {
+    vec![
+        ("build", "go build ./...".to_string()),
+        ("fmt", "gofmt -w .".to_string()),
+        ("lint", "go vet ./...".to_string()),
+        ("test", "go test ./...".to_string()),
+    ]
+}
+
+/// Scripts from `package.json`, run through the package manager; npm when
+/// none was detected.
+fn node(framework: Option<&str>, package_manager: Option<&str>) -> Vec<(&'static str, String)> {
+    let (pm, run) = match package_manager {
+        Some("pnpm") => ("pnpm", "pnpm"),
+        Some("yarn") => ("yarn", "yarn"),
+        // `bun test` is bun's own test runner, not the `test` script.
+        Some("bun") => ("bun", "bun run"),
+        _ => ("npm", "npm run"),
+    };
+    let mut commands = vec![
+        ("build", format!("{run} build")),
+        ("install", format!("{pm} install")),
+        ("lint", format!("{run} lint")),
+        ("test", format!("{run} test")),
+    ];
+    let dev_script = match framework {
+        Some(
+            "Next.js" | "Nuxt" | "Remix" | "Astro" | "SvelteKit" | "Svelte" | "Vue" | "SolidStart"
+            | "Solid" | "Qwik City" | "Qwik" | "TanStack Start" | "Vite",
+        ) => Some("dev"),
+        Some("Gatsby") => Some("develop"),
+        Some("Angular") => Some("start"),
+        Some("NestJS") => Some("start:dev"),
+        _ => None,
+    };
+    if let Some(script) = dev_script {
+        commands.push(("dev", format!("{run} {script}")));
+    }
+    commands
+}
+
+/// pytest and the framework's dev server, inside the project environment
+/// of uv or poetry when one manages it.
+fn python(framework: Option<&str>, package_manager: Option<&str>) -> Vec<(&'static str, String)> {
+    let (install, prefix) = match package_manager {
+        Some("uv") => ("uv sync", "uv run "),
+        Some("poetry") => ("poetry install", "poetry run "),
+        _ => ("pip install -r requirements.txt", ""),
+    };
+    let mut commands = vec![
+        ("install", install.to_string()),
+        ("test", format!("{prefix}pytest")),
+    ];
+    let dev = match framework {
+        Some("Django") => Some("python manage.py runserver"),
+        Some("Flask") => Some("flask run --debug"),
+        Some("FastAPI") => Some("fastapi dev"),
+        _ => None,
+    };
+    if let Some(dev) = dev {
+        commands.push(("dev", format!("{prefix}{dev}")));
+    }
+    commands
+}
+
+fn php(framework: Option<&str>) -> Vec<(&'static str, String)> {
+    let mut commands = vec![("install", "composer install".to_string())];
+    match framework {
+        Some("Laravel") => commands.extend([
+            ("dev", "php artisan serve".to_string()),
+            ("test", "php artisan test".to_string()),
+        ]),
+        Some("Symfony") => commands.push(("test", "php bin/phpunit".to_string())),
+        _ => commands.push(("test", "vendor/bin/phpunit".to_string())),
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggested(
+        language: &str,
+        framework: Option<&str>,
+        package_manager: Option<&str>,
+    ) -> Vec<(String, String)> {
+        suggest(language, framework, package_manager)
+            .into_iter()
+            .collect()
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(name, run)| (name.to_string(), run.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn rust_and_go() {
+        assert_eq!(
+            suggested("Rust", None, Some("cargo")),
+            pairs(&[
+                ("build", "cargo build --workspace"),
+                ("fmt", "cargo fmt --all"),
+                ("lint", "cargo clippy --workspace --all-targets"),
+                ("test", "cargo test --workspace"),
+            ])
+        );
+        assert_eq!(
+            suggested("Go", None, None),
+            pairs(&[
+                ("build", "go build ./..."),
+                ("fmt", "gofmt -w ."),
+                ("lint", "go vet ./..."),
+                ("test", "go test ./..."),
+            ])
+        );
+    }
+
+    #[test]
+    fn node_follows_the_package_manager() {
+        assert_eq!(
+            suggested("TypeScript", Some("Next.js"), Some("pnpm")),
+            pairs(&[
+                ("build", "pnpm build"),
+                ("dev", "pnpm dev"),
+                ("install", "pnpm install"),
+                ("lint", "pnpm lint"),
+                ("test", "pnpm test"),
+            ])
+        );
+        assert_eq!(
+            suggest("JavaScript", Some("Gatsby"), Some("yarn"))["dev"],
+            "yarn develop"
+        );
+        assert_eq!(
+            suggested("TypeScript", Some("Vue"), Some("bun")),
+            pairs(&[
+                ("build", "bun run build"),
+                ("dev", "bun run dev"),
+                ("install", "bun install"),
+                ("lint", "bun run lint"),
+                ("test", "bun run test"),
+            ])
+        );
+        // npm without a framework that has a dev server.
+        assert_eq!(
+            suggested("JavaScript", Some("Express"), None),
+            pairs(&[
+                ("build", "npm run build"),
+                ("install", "npm install"),
+                ("lint", "npm run lint"),
+                ("test", "npm run test"),
+            ])
+        );
+    }
+
+    #[test]
+    fn python_runs_inside_the_managed_environment() {
+        assert_eq!(
+            suggested("Python", Some("FastAPI"), Some("uv")),
+            pairs(&[
+                ("dev", "uv run fastapi dev"),
+                ("install", "uv sync"),
+                ("test", "uv run pytest"),
+            ])
+        );
+        assert_eq!(
+            suggested("Python", Some("Django"), Some("poetry")),
+            pairs(&[
+                ("dev", "poetry run python manage.py runserver"),
+                ("install", "poetry install"),
+                ("test", "poetry run pytest"),
+            ])
+        );
+        assert_eq!(
+            suggested("Python", Some("Flask"), Some("pip")),
+            pairs(&[
+                ("dev", "flask run --debug"),
+                ("install", "pip install -r requirements.txt"),
+                ("test", "pytest"),
+            ])
+        );
+    }
+
+    #[test]
+    fn php_uses_composer_and_the_framework_runner() {
+        assert_eq!(
+            suggested("PHP", Some("Laravel"), Some("composer")),
+            pairs(&[
+                ("dev", "php artisan serve"),
+                ("install", "composer install"),
+                ("test", "php artisan test"),
+            ])
+        );
+        assert_eq!(
+            suggest("PHP", Some("Symfony"), Some("composer"))["test"],
+            "php bin/phpunit"
+        );
+        assert_eq!(
+            suggest("PHP", None, Some("composer"))["test"],
+            "vendor/bin/phpunit"
+        );
+    }
+
+    #[test]
+    fn unknown_stack_suggests_nothing() {
+        assert!(suggest("COBOL", None, None).is_empty());
+        assert!(suggest("Elixir", Some("Phoenix"), None).is_empty());
+    }
+}
@@ -1,17 +1,39 @@
 //! Template context — serializable rendering payload built from [`Codebase`].
 
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use orchestra_core::types::{Codebase, TaskStatus};
+use orchestra_core::global::GlobalContext;
+use orchestra_core::types::{
+    AgentConfig, Codebase, CodebaseName, Project, ProjectName, ProjectType, Skill, Subtask, Task,
+    TaskId, TaskStatus, CURRENT_SCHEMA_VERSION,
+};
 
+use crate::budget::{Trim, ELIDED_DESCRIPTION, TRIMMED_MAX_TASKS};
+use crate::engine::{default_conventions, AgentKind};
 use crate::error::RenderError;
+use crate::skills::SkillCatalog;
+
+/// Default cap on active tasks rendered into agent files when the codebase
+/// does not set `max_rendered_tasks`.
+pub const DEFAULT_MAX_RENDERED_TASKS: usize = 20;
 
 /// Flat + structured rendering payload.
 ///
 /// The FRD-aligned nested shape is exposed via `identity`, `stack`,
 /// `commands`, `architecture`, `skills`, `tasks`, `subagents`, and `meta`.
 /// Legacy flat fields are retained so existing templates keep working.
+///
+/// Build one with [`TemplateContext::builder`] rather than assigning fields:
+/// the legacy fields mirror `identity` and `stack.projects`, and
+/// `active_task_count` / `tasks_overflow_count` must agree with `tasks`.
+/// Direct field mutation is deprecated and [`to_tera_context`] checks these
+/// invariants in debug builds.
+///
+/// [`to_tera_context`]: TemplateContext::to_tera_context
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemplateContext {
     /// FRD identity context.
@@ -24,12 +46,21 @@ pub struct TemplateContext {
     pub architecture: ArchitectureCtx,
     /// Additional conventions to include.
     pub conventions: Vec<String>,
+    /// Per-agent replacements for the built-in convention bullets, keyed by
+    /// lowercase agent name. See [`TemplateContext::agent_conventions`].
+    #[serde(default)]
+    pub agent_convention_overrides: BTreeMap<String, Vec<String>>,
     /// Additional notes to include.
     pub notes: Vec<String>,
     /// FRD skill entries.
     pub skills: Vec<SkillCtx>,
-    /// FRD task entries (done tasks excluded).
+    /// FRD task entries (done tasks excluded), highest priority first and
+    /// truncated to `max_rendered_tasks`.
     pub tasks: Vec<TaskCtx>,
+    /// Cap applied to `tasks`.
+    pub max_rendered_tasks: usize,
+    /// Active tasks omitted from `tasks` because of the cap.
+    pub tasks_overflow_count: usize,
     /// FRD subagent entries.
     pub subagents: Vec<SubagentCtx>,
     /// FRD meta info.
@@ -56,6 +87,11 @@ pub struct IdentityCtx {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StackCtx {
     pub projects: Vec<ProjectSummary>,
+    /// Most common project type (`"backend"`, `"ml"`, ...); ties go to the
+    /// first project listed. `None` when the codebase has no projects.
+    pub primary_type: Option<String>,
+    /// Framework of the first project of `primary_type` that has one.
+    pub primary_framework: Option<String>,
 }
 
 /// FRD commands context.
@@ -63,12 +99,28 @@ pub struct StackCtx {
 pub struct CommandsCtx {
     pub sync: String,
     pub sync_dry_run: String,
+    /// Project commands (build, test, lint, ...) sorted by name. When two
+    /// projects define the same name, the first project's command is used.
+    pub project: Vec<ProjectCommandCtx>,
+    /// Idiomatic commands for the detected stack, by name, from
+    /// [`crate::commands::suggest`]. Names already in `project` are left out.
+    #[serde(default)]
+    pub suggested: BTreeMap<String, String>,
+}
+
+/// A named project command, e.g. `test` → `cargo test --workspace`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectCommandCtx {
+    pub name: String,
+    pub run: String,
 }
 
 /// FRD architecture context.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArchitectureCtx {
     pub summary: String,
+    /// `architecture_notes` from `~/.orchestra/global.yaml`, if any.
+    pub notes: Option<String>,
 }
 
 /// FRD skill context.
@@ -85,6 +137,19 @@ pub struct TaskCtx {
     pub title: String,
     pub status: String,
     pub description: Option<String>,
+    /// Lowercase agent name the task is assigned to; `None` for every agent.
+    #[serde(default)]
+    pub assigned_agent: Option<String>,
+}
+
+impl TaskCtx {
+    /// Whether `agent`'s files list this task: it is unassigned or assigned
+    /// to `agent`.
+    pub fn is_for(&self, agent: AgentKind) -> bool {
+        self.assigned_agent
+            .as_deref()
+            .is_none_or(|name| name.eq_ignore_ascii_case(agent.name()))
+    }
 }
 
 /// FRD subagent context.
@@ -100,43 +165,289 @@ pub struct SubagentCtx {
 pub struct MetaCtx {
     pub orchestra_version: String,
     pub last_synced: Option<DateTime<Utc>>,
+    /// Project the codebase is registered under, named in the provenance
+    /// header. `None` outside a sync, e.g. for template previews.
+    pub project_name: Option<String>,
+    /// Parts of the context were left out to fit the agent's token budget.
+    /// See [`TemplateContext::trimmed`].
+    #[serde(default)]
+    pub truncation_applied: bool,
 }
 
 /// Serializable summary of a project.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ProjectSummary {
     pub name: String,
     pub project_type: String,
+    pub framework: Option<String>,
 }
 
 /// Backward-compatible alias used by existing tests/imports.
 pub type TaskSummary = TaskCtx;
 
 impl TemplateContext {
-    /// Build a [`TemplateContext`] from a [`Codebase`].
+    /// Build a [`TemplateContext`] from a [`Codebase`] with no overrides.
     pub fn from_codebase(codebase: &Codebase) -> Self {
+        Self::builder(codebase).build()
+    }
+
+    /// Start a [`TemplateContextBuilder`] from a [`Codebase`].
+    pub fn builder(codebase: &Codebase) -> TemplateContextBuilder {
+        TemplateContextBuilder::new(codebase)
+    }
+
+    /// A synthetic context exercising every section the templates render:
+    /// a project with tasks, commands, and a subagent, conventions, skills,
+    /// and notes.
+    /// Used to check templates when no real codebase is at hand.
+    pub fn example() -> Self {
+        let now = Utc::now();
+        let task = |id: &str, title: &str, status: TaskStatus| Task {
+            id: TaskId::from(id),
+            title: title.to_string(),
+            status,
+            description: Some(format!("Example task: {title}")),
+            subtasks: vec![Subtask {
+                title: "Write tests".to_string(),
+                done: false,
+            }],
+            notes: vec!["Example note".to_string()],
+            assigned_agent: None,
+            created_at: now,
+            updated_at: now,
+            extra: BTreeMap::new(),
+        };
+        let codebase = Codebase {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            name: CodebaseName::from("example"),
+            path: PathBuf::from("/path/to/example"),
+            projects: vec![Project {
+                name: ProjectName::from("example"),
+                project_type: ProjectType::Backend,
+                framework: Some("Axum".to_string()),
+                detection: None,
+                tasks: vec![
+                    task("t-001", "Add login endpoint", TaskStatus::InProgress),
+                    task("t-002", "Document the API", TaskStatus::Pending),
+                ],
+                agents: vec![AgentConfig {
+                    agent_id: "reviewer".to_string(),
+                    entry_point: PathBuf::from("orchestra/agents/reviewer.md"),
+                    skills: Some(vec!["code-review".to_string()]),
+                }],
+                commands: BTreeMap::from([
+                    ("lint".to_string(), "cargo clippy --workspace".to_string()),
+                    ("test".to_string(), "cargo test --workspace".to_string()),
+                ]),
+                extra: BTreeMap::new(),
+            }],
+            conventions: vec!["Use snake_case for module names".to_string()],
+            skills: vec![Skill {
+                id: "code-review".to_string(),
+                description: "Review diffs before merging".to_string(),
+            }],
+            notes: vec!["Example codebase note".to_string()],
+            tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: BTreeMap::new(),
+            created_at: now,
+            updated_at: now,
+            extra: BTreeMap::new(),
+        };
+        Self::builder(&codebase)
+            .project(&ProjectName::from("example"))
+            .build()
+    }
+
+    /// Convert to a [`tera::Context`] for rendering.
+    pub fn to_tera_context(&self) -> Result<tera::Context, RenderError> {
+        debug_assert!(
+            self.mirrors_agree(),
+            "TemplateContext fields disagree; build it with TemplateContext::builder"
+        );
+        tera::Context::from_serialize(self).map_err(RenderError::from)
+    }
+
+    /// Whether the legacy fields mirror their nested counterparts and the
+    /// task counts match `tasks`.
+    fn mirrors_agree(&self) -> bool {
+        self.codebase_name == self.identity.codebase_name
+            && self.codebase_path == self.identity.codebase_path
+            && self.projects == self.stack.projects
+            && self.tasks.len() <= self.max_rendered_tasks
+            && self.active_task_count == self.tasks.len() + self.tasks_overflow_count
+    }
+
+    /// The convention bullets `agent`'s templates render as
+    /// `agent_conventions`: the codebase's override for that agent, or
+    /// [`default_conventions`] when it has none.
+    pub fn agent_conventions(&self, agent: AgentKind) -> Vec<String> {
+        match self.agent_convention_overrides.get(agent.name()) {
+            Some(overrides) => overrides.clone(),
+            None => default_conventions(agent)
+                .iter()
+                .map(|convention| convention.to_string())
+                .collect(),
+        }
+    }
+
+    /// The rendered tasks `agent`'s files list, which templates see as
+    /// `tasks_for_agent`. Tasks assigned to other agents only count towards
+    /// `tasks_assigned_elsewhere`.
+    pub fn tasks_for_agent(&self, agent: AgentKind) -> Vec<TaskCtx> {
+        self.tasks
+            .iter()
+            .filter(|task| task.is_for(agent))
+            .cloned()
+            .collect()
+    }
+
+    /// This context with the parts `trim` names left out, and
+    /// `meta.truncation_applied` set unless `trim` is [`Trim::None`]. Tasks
+    /// cut by [`Trim::TaskCap`] count towards `tasks_overflow_count`.
+    pub fn trimmed(&self, trim: Trim) -> Self {
+        let mut ctx = self.clone();
+        if trim == Trim::None {
+            return ctx;
+        }
+        ctx.meta.truncation_applied = true;
+        for task in &mut ctx.tasks {
+            if task.description.is_some() {
+                task.description = Some(ELIDED_DESCRIPTION.to_string());
+            }
+        }
+        if trim >= Trim::TaskCap && ctx.tasks.len() > TRIMMED_MAX_TASKS {
+            ctx.tasks_overflow_count += ctx.tasks.len() - TRIMMED_MAX_TASKS;
+            ctx.tasks.truncate(TRIMMED_MAX_TASKS);
+            ctx.max_rendered_tasks = TRIMMED_MAX_TASKS;
+        }
+        if trim >= Trim::Skills {
+            ctx.skills.clear();
+        }
+        ctx
+    }
+
+    /// Merge the org-wide instructions from `~/.orchestra/global.yaml`.
+    ///
+    /// Global conventions and skills come first; a codebase convention equal
+    /// to a global one, or a skill with a global skill's id, is dropped.
+    pub fn apply_global(&mut self, global: &GlobalContext) {
+        let mut conventions: Vec<String> = Vec::new();
+        for convention in global
+            .conventions
+            .iter()
+            .cloned()
+            .chain(self.conventions.drain(..))
+        {
+            if !conventions.contains(&convention) {
+                conventions.push(convention);
+            }
+        }
+        self.conventions = conventions;
+
+        let mut skills: Vec<SkillCtx> = Vec::new();
+        let global_skills = global.skills.iter().map(|skill| SkillCtx {
+            id: skill.id.clone(),
+            description: skill.description.clone(),
+        });
+        for skill in global_skills.chain(self.skills.drain(..)) {
+            if !skills.iter().any(|existing| existing.id == skill.id) {
+                skills.push(skill);
+            }
+        }
+        self.skills = skills;
+
+        self.architecture.notes = global
+            .architecture_notes
+            .as_deref()
+            .map(str::trim)
+            .filter(|notes| !notes.is_empty())
+            .map(str::to_string);
+    }
+
+    /// Fill skill descriptions from the skills catalog.
+    ///
+    /// Only bare skill ids (no description of their own) are looked up.
+    /// Returns the ids missing from the catalog; they keep rendering as the
+    /// id itself.
+    pub fn apply_skill_catalog(&mut self, catalog: &SkillCatalog) -> Vec<String> {
+        let mut missing = Vec::new();
+        for skill in &mut self.skills {
+            if !skill.description.is_empty() && skill.description != skill.id {
+                continue;
+            }
+            match catalog.get(&skill.id) {
+                Some(entry) => skill.description = entry.description.clone(),
+                None => {
+                    skill.description = skill.id.clone();
+                    missing.push(skill.id.clone());
+                }
+            }
+        }
+        missing
+    }
+}
+
+/// Builds a [`TemplateContext`] while keeping its mirrored fields in step.
+///
+/// Starts from everything [`TemplateContext::from_codebase`] would produce;
+/// each method replaces one part. Task counts and the legacy fields are
+/// derived in [`build`](Self::build), so they cannot drift from what is
+/// rendered.
+#[derive(Debug, Clone)]
+pub struct TemplateContextBuilder {
+    ctx: TemplateContext,
+    /// Every active task in render order, before the cap.
+    tasks: Vec<TaskCtx>,
+}
+
+impl TemplateContextBuilder {
+    /// Start from everything `codebase` provides.
+    pub fn new(codebase: &Codebase) -> Self {
         let projects: Vec<ProjectSummary> = codebase
             .projects
             .iter()
             .map(|p| ProjectSummary {
                 name: p.name.0.clone(),
                 project_type: p.project_type.to_string(),
+                framework: p.framework.clone(),
             })
             .collect();
+        let primary_type = primary_project_type(&projects);
+        let primary_framework = projects
+            .iter()
+            .filter(|p| Some(&p.project_type) == primary_type.as_ref())
+            .find_map(|p| p.framework.clone());
 
-        let tasks: Vec<TaskCtx> = codebase
+        let mut active: Vec<&Task> = codebase
             .projects
             .iter()
-            .flat_map(|p| {
-                p.tasks
-                    .iter()
-                    .filter(|t| !matches!(t.status, TaskStatus::Done))
-                    .map(|t| TaskCtx {
-                        id: t.id.0.clone(),
-                        title: t.title.clone(),
-                        status: format!("{:?}", t.status).to_lowercase(),
-                        description: t.description.clone(),
-                    })
+            .flat_map(|p| p.tasks.iter())
+            .filter(|t| t.status.is_active())
+            .collect();
+        active.sort_by(|a, b| {
+            status_priority(&a.status)
+                .cmp(&status_priority(&b.status))
+                .then_with(|| b.updated_at.cmp(&a.updated_at))
+        });
+
+        let max_rendered_tasks = codebase
+            .max_rendered_tasks
+            .unwrap_or(DEFAULT_MAX_RENDERED_TASKS);
+        let tasks: Vec<TaskCtx> = active
+            .into_iter()
+            .map(|t| TaskCtx {
+                id: t.id.0.clone(),
+                title: t.title.clone(),
+                status: t.status.to_string(),
+                description: t.description.clone(),
+                assigned_agent: t.assigned_agent.clone(),
             })
             .collect();
 
@@ -176,44 +487,179 @@ impl TemplateContext {
             })
             .collect();
 
+        let mut project_commands: BTreeMap<&str, &str> = BTreeMap::new();
+        for (name, run) in codebase.projects.iter().flat_map(|p| p.commands.iter()) {
+            project_commands.entry(name).or_insert(run);
+        }
+        let mut suggested_commands = BTreeMap::new();
+        for project in &codebase.projects {
+            let Some(detection) = &project.detection else {
+                continue;
+            };
+            let Some(language) = detection.language.as_deref() else {
+                continue;
+            };
+            let suggestions = crate::commands::suggest(
+                language,
+                project.framework.as_deref(),
+                detection.package_manager.as_deref(),
+            );
+            for (name, run) in suggestions {
+                if !project_commands.contains_key(name.as_str()) {
+                    suggested_commands.entry(name).or_insert(run);
+                }
+            }
+        }
+        let project_commands: Vec<ProjectCommandCtx> = project_commands
+            .into_iter()
+            .map(|(name, run)| ProjectCommandCtx {
+                name: name.to_string(),
+                run: run.to_string(),
+            })
+            .collect();
+
         let codebase_name = codebase.name.0.clone();
         let codebase_path = codebase.path.display().to_string();
-        let active_task_count = tasks.len();
 
-        TemplateContext {
+        let ctx = TemplateContext {
             identity: IdentityCtx {
                 codebase_name: codebase_name.clone(),
                 codebase_path: codebase_path.clone(),
             },
             stack: StackCtx {
                 projects: projects.clone(),
+                primary_type,
+                primary_framework,
             },
             commands: CommandsCtx {
                 sync: format!("orchestra sync {}", codebase_name),
                 sync_dry_run: format!("orchestra sync {} --dry-run", codebase_name),
+                project: project_commands,
+                suggested: suggested_commands,
             },
             architecture: ArchitectureCtx {
                 summary: "Refer to the project README and inline documentation.".to_string(),
+                notes: None,
             },
             conventions: codebase.conventions.clone(),
+            agent_convention_overrides: codebase.agent_conventions.clone(),
             notes: codebase.notes.clone(),
             skills,
-            tasks,
+            tasks: Vec::new(),
+            max_rendered_tasks,
+            tasks_overflow_count: 0,
             subagents,
             meta: MetaCtx {
                 orchestra_version: env!("CARGO_PKG_VERSION").to_string(),
                 last_synced: None,
+                project_name: None,
+                truncation_applied: false,
             },
             codebase_name,
             codebase_path,
             projects,
-            active_task_count,
+            active_task_count: 0,
+        };
+        Self { ctx, tasks }
+    }
+
+    /// Replace the conventions.
+    pub fn conventions(mut self, conventions: Vec<String>) -> Self {
+        self.ctx.conventions = conventions;
+        self
+    }
+
+    /// Replace the per-agent convention overrides.
+    pub fn agent_conventions(mut self, overrides: BTreeMap<String, Vec<String>>) -> Self {
+        self.ctx.agent_convention_overrides = overrides;
+        self
+    }
+
+    /// Replace the notes.
+    pub fn notes(mut self, notes: Vec<String>) -> Self {
+        self.ctx.notes = notes;
+        self
+    }
+
+    /// Replace the skills.
+    pub fn skills(mut self, skills: Vec<SkillCtx>) -> Self {
+        self.ctx.skills = skills;
+        self
+    }
+
+    /// Append skills whose id is not listed yet.
+    pub fn extra_skills(mut self, skills: impl IntoIterator<Item = SkillCtx>) -> Self {
+        for skill in skills {
+            if !self
+                .ctx
+                .skills
+                .iter()
+                .any(|existing| existing.id == skill.id)
+            {
+                self.ctx.skills.push(skill);
+            }
         }
+        self
     }
 
-    /// Convert to a [`tera::Context`] for rendering.
-    pub fn to_tera_context(&self) -> Result<tera::Context, RenderError> {
-        tera::Context::from_serialize(self).map_err(RenderError::from)
+    /// Replace the active tasks, given in render order. `build` applies the
+    /// cap and counts.
+    pub fn tasks(mut self, tasks: Vec<TaskCtx>) -> Self {
+        self.tasks = tasks;
+        self
+    }
+
+    /// Cap on the tasks rendered; the rest only count towards
+    /// `tasks_overflow_count`.
+    pub fn max_tasks(mut self, max: usize) -> Self {
+        self.ctx.max_rendered_tasks = max;
+        self
+    }
+
+    /// When the codebase was last synced.
+    pub fn last_synced(mut self, last_synced: Option<DateTime<Utc>>) -> Self {
+        self.ctx.meta.last_synced = last_synced;
+        self
+    }
+
+    /// The project the codebase is registered under.
+    pub fn project(mut self, project: &ProjectName) -> Self {
+        self.ctx.meta.project_name = Some(project.0.clone());
+        self
+    }
+
+    /// Finish the context, truncating the tasks to the cap and deriving the
+    /// task counts from them.
+    pub fn build(self) -> TemplateContext {
+        let Self { mut ctx, tasks } = self;
+        ctx.active_task_count = tasks.len();
+        ctx.tasks_overflow_count = tasks.len().saturating_sub(ctx.max_rendered_tasks);
+        ctx.tasks = tasks;
+        ctx.tasks.truncate(ctx.max_rendered_tasks);
+        ctx
+    }
+}
+
+/// The project type shared by the most projects, preferring the one listed
+/// first on a tie.
+fn primary_project_type(projects: &[ProjectSummary]) -> Option<String> {
+    let count = |ty: &str| projects.iter().filter(|p| p.project_type == ty).count();
+    projects
+        .iter()
+        .map(|p| p.project_type.as_str())
+        .rev()
+        .max_by_key(|ty| count(ty))
+        .map(str::to_string)
+}
+
+/// Render order for active tasks: in-progress work first, then blocked,
+/// then pending.
+fn status_priority(status: &TaskStatus) -> u8 {
+    match status {
+        TaskStatus::InProgress => 0,
+        TaskStatus::Blocked => 1,
+        TaskStatus::Pending => 2,
+        TaskStatus::Done | TaskStatus::Cancelled | TaskStatus::Unknown(_) => 3,
     }
 }
 
@@ -221,7 +667,8 @@ impl TemplateContext {
 mod tests {
     use super::*;
     use orchestra_core::types::{
-        AgentConfig, Codebase, CodebaseName, Project, ProjectName, ProjectType, Task, TaskId,
+        AgentConfig, Codebase, CodebaseName, Project, ProjectName, ProjectType, Skill, Task,
+        TaskId, CURRENT_SCHEMA_VERSION,
     };
     use std::path::PathBuf;
 
@@ -233,6 +680,8 @@ mod tests {
             projects: vec![Project {
                 name: ProjectName::from("api"),
                 project_type: ProjectType::Backend,
+                framework: None,
+                detection: None,
                 tasks: vec![
                     Task {
                         id: TaskId::from("t-001"),
@@ -241,8 +690,10 @@ mod tests {
                         description: None,
                         subtasks: vec![],
                         notes: vec![],
+                        assigned_agent: None,
                         created_at: now,
                         updated_at: now,
+                        extra: BTreeMap::new(),
                     },
                     Task {
                         id: TaskId::from("t-002"),
@@ -251,8 +702,10 @@ mod tests {
                         description: None,
                         subtasks: vec![],
                         notes: vec![],
+                        assigned_agent: None,
                         created_at: now,
                         updated_at: now,
+                        extra: BTreeMap::new(),
                     },
                 ],
                 agents: vec![AgentConfig {
@@ -260,13 +713,25 @@ mod tests {
                     entry_point: PathBuf::from("AGENT/coder.md"),
                     skills: Some(vec!["rust".to_string()]),
                 }],
+                commands: BTreeMap::new(),
+                extra: BTreeMap::new(),
             }],
             conventions: vec![],
             skills: vec![],
             notes: vec![],
             tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: BTreeMap::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             created_at: now,
             updated_at: now,
+            extra: BTreeMap::new(),
         }
     }
 
@@ -285,6 +750,133 @@ mod tests {
         assert!(ctx.meta.last_synced.is_none());
     }
 
+    #[test]
+    fn skill_catalog_fills_bare_skill_ids() {
+        let mut cb = make_codebase("myapp");
+        cb.skills.push(Skill {
+            id: "docs".to_string(),
+            description: "Keep the README current.".to_string(),
+        });
+        cb.projects[0].agents[0].skills = Some(vec!["rust".to_string(), "sql".to_string()]);
+        let mut ctx = TemplateContext::from_codebase(&cb);
+
+        let catalog = SkillCatalog::from([(
+            "rust".to_string(),
+            crate::skills::parse_entry("rust", "# Rust\n\nWrite idiomatic, clippy-clean Rust.\n"),
+        )]);
+        let missing = ctx.apply_skill_catalog(&catalog);
+
+        assert_eq!(missing, vec!["sql".to_string()]);
+        let descriptions: Vec<&str> = ctx.skills.iter().map(|s| s.description.as_str()).collect();
+        assert_eq!(
+            descriptions,
+            ["Keep the README current.", "Write idiomatic, clippy-clean Rust.", "sql"]
+        );
+    }
+
+    #[test]
+    fn global_context_comes_first_without_duplicates() {
+        let mut cb = make_codebase("myapp");
+        cb.conventions = vec![
+            "Never commit secrets".to_string(),
+            "Use snake_case".to_string(),
+        ];
+        cb.skills.push(Skill {
+            id: "security-review".to_string(),
+            description: "Codebase copy".to_string(),
+        });
+        let mut ctx = TemplateContext::from_codebase(&cb);
+
+        ctx.apply_global(&GlobalContext {
+            conventions: vec![
+                "Sign off commits".to_string(),
+                "Never commit secrets".to_string(),
+            ],
+            skills: vec![Skill {
+                id: "security-review".to_string(),
+                description: "Check the security checklist".to_string(),
+            }],
+            architecture_notes: Some("  Services talk over gRPC.\n".to_string()),
+        });
+
+        assert_eq!(
+            ctx.conventions,
+            ["Sign off commits", "Never commit secrets", "Use snake_case"]
+        );
+        let skills: Vec<(&str, &str)> = ctx
+            .skills
+            .iter()
+            .map(|s| (s.id.as_str(), s.description.as_str()))
+            .collect();
+        assert_eq!(
+            skills,
+            [
+                ("security-review", "Check the security checklist"),
+                ("rust", "rust")
+            ]
+        );
+        assert_eq!(
+            ctx.architecture.notes.as_deref(),
+            Some("Services talk over gRPC.")
+        );
+    }
+
+    #[test]
+    fn primary_type_is_the_most_common_project_type() {
+        let mut cb = make_codebase("mixed");
+        let mut web = cb.projects[0].clone();
+        web.name = ProjectName::from("web");
+        web.project_type = ProjectType::Frontend;
+        web.framework = Some("Next.js".to_string());
+        let mut admin = web.clone();
+        admin.name = ProjectName::from("admin");
+        admin.framework = None;
+        cb.projects.extend([admin, web]);
+
+        let ctx = TemplateContext::from_codebase(&cb);
+        assert_eq!(ctx.stack.primary_type.as_deref(), Some("frontend"));
+        assert_eq!(ctx.stack.primary_framework.as_deref(), Some("Next.js"));
+
+        cb.projects.truncate(2);
+        let ctx = TemplateContext::from_codebase(&cb);
+        assert_eq!(
+            ctx.stack.primary_type.as_deref(),
+            Some("backend"),
+            "a tie goes to the first project"
+        );
+        assert_eq!(ctx.stack.primary_framework, None);
+    }
+
+    #[test]
+    fn suggested_commands_skip_explicit_names() {
+        let mut cb = make_codebase("myapp");
+        cb.projects[0].detection = Some(orchestra_core::types::StackDetection {
+            confidence: "medium".to_string(),
+            overridden: false,
+            detected_at: None,
+            indicators: vec![],
+            indicators_sha256: None,
+            language: Some("Rust".to_string()),
+            package_manager: Some("cargo".to_string()),
+        });
+        cb.projects[0]
+            .commands
+            .insert("test".to_string(), "cargo nextest run".to_string());
+
+        let commands = TemplateContext::from_codebase(&cb).commands;
+        assert_eq!(commands.project[0].run, "cargo nextest run");
+        assert_eq!(
+            commands.suggested.keys().collect::<Vec<_>>(),
+            ["build", "fmt", "lint"]
+        );
+
+        cb.projects[0].detection = None;
+        assert!(TemplateContext::from_codebase(&cb)
+            .commands
+            .suggested
+            .is_empty());
+    }
+
     #[test]
     fn to_tera_context_succeeds() {
         let cb = make_codebase("tera_test");
@@ -292,4 +884,178 @@ mod tests {
         let tera_ctx = ctx.to_tera_context().expect("context conversion");
         let _ = tera_ctx;
     }
+
+    fn task(id: usize, status: TaskStatus, updated_at: DateTime<Utc>) -> Task {
+        Task {
+            id: TaskId::from(format!("t-{id:03}")),
+            title: format!("Task {id:03}"),
+            status,
+            description: None,
+            subtasks: vec![],
+            notes: vec![],
+            assigned_agent: None,
+            created_at: updated_at,
+            updated_at,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn tasks_are_capped_with_overflow_count() {
+        let mut cb = make_codebase("capped");
+        let now = Utc::now();
+        cb.projects[0].tasks = (0..30).map(|i| task(i, TaskStatus::Pending, now)).collect();
+        cb.max_rendered_tasks = Some(10);
+
+        let ctx = TemplateContext::from_codebase(&cb);
+        assert_eq!(ctx.tasks.len(), 10);
+        assert_eq!(ctx.tasks_overflow_count, 20);
+        assert_eq!(ctx.active_task_count, 30, "count reflects all active tasks");
+    }
+
+    #[test]
+    fn default_cap_applies_without_registry_setting() {
+        let mut cb = make_codebase("default_cap");
+        let now = Utc::now();
+        cb.projects[0].tasks = (0..25).map(|i| task(i, TaskStatus::Pending, now)).collect();
+
+        let ctx = TemplateContext::from_codebase(&cb);
+        assert_eq!(ctx.max_rendered_tasks, DEFAULT_MAX_RENDERED_TASKS);
+        assert_eq!(ctx.tasks.len(), DEFAULT_MAX_RENDERED_TASKS);
+        assert_eq!(ctx.tasks_overflow_count, 25 - DEFAULT_MAX_RENDERED_TASKS);
+    }
+
+    #[test]
+    fn tasks_sorted_by_status_then_most_recent() {
+        let mut cb = make_codebase("ordered");
+        let now = Utc::now();
+        let hour = chrono::Duration::hours(1);
+        cb.projects[0].tasks = vec![
+            task(1, TaskStatus::Pending, now),
+            task(2, TaskStatus::Blocked, now - hour),
+            task(3, TaskStatus::InProgress, now - hour * 2),
+            task(4, TaskStatus::InProgress, now),
+            task(5, TaskStatus::Done, now),
+        ];
+        cb.max_rendered_tasks = Some(3);
+
+        let ctx = TemplateContext::from_codebase(&cb);
+        let ids: Vec<&str> = ctx.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["t-004", "t-003", "t-002"], "pending dropped before in_progress");
+        assert_eq!(ctx.tasks_overflow_count, 1);
+    }
+
+    #[test]
+    fn cancelled_and_unknown_tasks_are_not_rendered() {
+        let mut cb = make_codebase("cancelled");
+        let now = Utc::now();
+        cb.projects[0].tasks = vec![
+            task(1, TaskStatus::Pending, now),
+            task(2, TaskStatus::Cancelled, now),
+            task(3, TaskStatus::Unknown("archived".to_string()), now),
+            task(4, TaskStatus::InProgress, now),
+        ];
+
+        let ctx = TemplateContext::from_codebase(&cb);
+        let rendered: Vec<(&str, &str)> = ctx
+            .tasks
+            .iter()
+            .map(|t| (t.id.as_str(), t.status.as_str()))
+            .collect();
+        assert_eq!(rendered, [("t-004", "inprogress"), ("t-001", "pending")]);
+        assert_eq!(ctx.active_task_count, 2);
+    }
+
+    fn task_ctx(id: usize) -> TaskCtx {
+        TaskCtx {
+            id: format!("x-{id}"),
+            title: format!("Extra {id}"),
+            status: "pending".to_string(),
+            description: None,
+            assigned_agent: None,
+        }
+    }
+
+    #[test]
+    fn every_builder_path_keeps_mirrors_in_agreement() {
+        let mut cb = make_codebase("built");
+        let now = Utc::now();
+        cb.projects[0].tasks = (0..5).map(|i| task(i, TaskStatus::Pending, now)).collect();
+        let skill = SkillCtx {
+            id: "sql".to_string(),
+            description: "Write portable SQL".to_string(),
+        };
+        let builders = [
+            TemplateContext::builder(&cb),
+            TemplateContext::builder(&cb).conventions(vec!["Use tabs".to_string()]),
+            TemplateContext::builder(&cb).notes(vec!["Frozen until Monday".to_string()]),
+            TemplateContext::builder(&cb).skills(vec![skill.clone()]),
+            TemplateContext::builder(&cb).extra_skills([skill.clone()]),
+            TemplateContext::builder(&cb).tasks((0..3).map(task_ctx).collect()),
+            TemplateContext::builder(&cb).tasks((0..40).map(task_ctx).collect()),
+            TemplateContext::builder(&cb).max_tasks(2),
+            TemplateContext::builder(&cb).max_tasks(0),
+            TemplateContext::builder(&cb).tasks(Vec::new()),
+            TemplateContext::builder(&cb).last_synced(Some(now)),
+            TemplateContext::builder(&cb).project(&ProjectName::from("copnow")),
+            // The cap applies whichever order the calls come in.
+            TemplateContext::builder(&cb)
+                .max_tasks(2)
+                .tasks((0..3).map(task_ctx).collect()),
+        ];
+        for builder in builders {
+            let ctx = builder.build();
+            assert!(ctx.mirrors_agree(), "{ctx:#?}");
+            ctx.to_tera_context().expect("tera context");
+        }
+    }
+
+    #[test]
+    fn builder_tasks_recompute_counts() {
+        let cb = make_codebase("counts");
+        let ctx = TemplateContext::builder(&cb)
+            .max_tasks(2)
+            .tasks((0..5).map(task_ctx).collect())
+            .build();
+        let ids: Vec<&str> = ctx.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["x-0", "x-1"]);
+        assert_eq!(ctx.active_task_count, 5);
+        assert_eq!(ctx.tasks_overflow_count, 3);
+        assert_eq!(ctx.max_rendered_tasks, 2);
+    }
+
+    #[test]
+    fn builder_extra_skills_skip_known_ids() {
+        let cb = make_codebase("skills");
+        let ctx = TemplateContext::builder(&cb)
+            .extra_skills([
+                SkillCtx {
+                    id: "rust".to_string(),
+                    description: "duplicate".to_string(),
+                },
+                SkillCtx {
+                    id: "sql".to_string(),
+                    description: "sql".to_string(),
+                },
+            ])
+            .build();
+        let ids: Vec<&str> = ctx.skills.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["rust", "sql"]);
+        assert_eq!(ctx.skills[0].description, "rust");
+    }
+
+    #[test]
+    fn hand_edited_counts_are_caught() {
+        let mut ctx = TemplateContext::from_codebase(&make_codebase("drift"));
+        assert!(ctx.mirrors_agree());
+        ctx.tasks.clear();
+        assert!(
+            !ctx.mirrors_agree(),
+            "active_task_count still counts the removed task"
+        );
+
+        let mut ctx = TemplateContext::from_codebase(&make_codebase("drift"));
+        ctx.identity.codebase_name = "renamed".to_string();
+        assert!(!ctx.mirrors_agree());
+    }
 }
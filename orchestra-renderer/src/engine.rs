@@ -12,12 +12,21 @@
 //! | Gemini      | `GEMINI.md`, `.gemini/settings.json`, `.gemini/styleguide.md`, `.gemini/skills/orchestra-sync/skill.md`|
 //! | Cline       | `.clinerules/orchestra.md`, `.agents/skills/orchestra-sync/skill.md` |
 //! | Antigravity | `.agent/rules/orchestra.md`, `.agent/skills/orchestra-sync/skill.md` |
+//! | Roo Code    | `.roo/rules/orchestra.md`                                     |
+//! | Zed         | `.rules`                                                      |
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::SystemTime;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 use tera::Tera;
 
-use orchestra_core::types::Codebase;
+use orchestra_core::{paths, types::Codebase};
 
 use crate::context::TemplateContext;
 use crate::error::RenderError;
@@ -28,6 +37,10 @@ use crate::error::RenderError;
 
 const TPLS: &[(&str, &str)] = &[
     ("shared/_header.tera", include_str!("templates/_partials/header.tera")),
+    (
+        "shared/_provenance.tera",
+        include_str!("templates/_partials/provenance.tera"),
+    ),
     ("shared/_tasks.tera", include_str!("templates/_partials/tasks.tera")),
     ("shared/_stack.tera", include_str!("templates/_partials/stack.tera")),
     (
@@ -39,6 +52,7 @@ const TPLS: &[(&str, &str)] = &[
         include_str!("templates/_partials/conventions_section.tera"),
     ),
     ("shared/_skills.tera", include_str!("templates/_partials/skills.tera")),
+    ("shared/_commands.tera", include_str!("templates/_partials/commands.tera")),
     (
         "shared/_orchestra_workflow.tera",
         include_str!("templates/_partials/orchestra_workflow.tera"),
@@ -51,6 +65,15 @@ const TPLS: &[(&str, &str)] = &[
         "shared/_worktree_instructions.tera",
         include_str!("templates/_partials/worktree_instructions.tera"),
     ),
+    (
+        "shared/_project_type.tera",
+        include_str!("templates/_partials/project_type.tera"),
+    ),
+    ("shared/_type_backend.tera", include_str!("templates/_partials/type_backend.tera")),
+    ("shared/_type_frontend.tera", include_str!("templates/_partials/type_frontend.tera")),
+    ("shared/_type_mobile.tera", include_str!("templates/_partials/type_mobile.tera")),
+    ("shared/_type_ml.tera", include_str!("templates/_partials/type_ml.tera")),
+    ("shared/_type_infra.tera", include_str!("templates/_partials/type_infra.tera")),
     ("claude/claude.md.tera", include_str!("templates/claude.md.tera")),
     (
         "claude/rules.md.tera",
@@ -116,6 +139,8 @@ const TPLS: &[(&str, &str)] = &[
         "antigravity/skill-orchestra-sync.md.tera",
         include_str!("templates/antigravity_skill_orchestra_sync.md.tera"),
     ),
+    ("roo/orchestra.md.tera", include_str!("templates/roo.md.tera")),
+    ("zed/rules.tera", include_str!("templates/zed_rules.tera")),
     (
         "pilot/pilot.md.tera",
         include_str!("templates/pilot.md.tera"),
@@ -124,6 +149,10 @@ const TPLS: &[(&str, &str)] = &[
         "guide/guide.md.tera",
         include_str!("templates/guide.md.tera"),
     ),
+    (
+        "subagent/entry.md.tera",
+        include_str!("templates/subagent_entry.md.tera"),
+    ),
 ];
 
 // ---------------------------------------------------------------------------
@@ -155,7 +184,29 @@ fn collect_template_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), Rend
     Ok(())
 }
 
-fn load_user_templates(dir: &Path) -> Result<Vec<(String, String)>, RenderError> {
+/// `<home>/.orchestra/templates/` — user overrides of the embedded templates,
+/// laid out like the embedded names (e.g. `claude/claude.md.tera`).
+pub fn user_template_dir_at(home: &Path) -> PathBuf {
+    paths::dirs_at(home).data_root.join("templates")
+}
+
+/// Where a loaded template's source came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateOrigin {
+    /// Baked into the binary.
+    Embedded,
+    /// A file in the user template directory that replaces (or adds to) the
+    /// embedded set.
+    Override(PathBuf),
+}
+
+/// A loaded template source, before Tera parses it.
+struct TemplateSource {
+    content: String,
+    origin: TemplateOrigin,
+}
+
+fn load_user_templates(dir: &Path) -> Result<Vec<(String, String, PathBuf)>, RenderError> {
     if !dir.exists() {
         return Ok(vec![]);
     }
@@ -171,29 +222,66 @@ fn load_user_templates(dir: &Path) -> Result<Vec<(String, String)>, RenderError>
             .unwrap_or(path.as_path());
         let name = normalize_template_name(rel);
         let contents = std::fs::read_to_string(&path).map_err(|e| io_err(&path, e))?;
-        templates.push((name, contents));
+        templates.push((name, contents, path));
     }
     Ok(templates)
 }
 
-fn build_tera(user_template_dir: Option<&Path>) -> Result<Tera, RenderError> {
-    let mut templates: HashMap<String, String> = HashMap::new();
+fn embedded_template(name: &str) -> Option<&'static str> {
+    TPLS.iter()
+        .find(|(tpl, _)| normalize_template_name(Path::new(tpl)) == name)
+        .map(|(_, content)| *content)
+}
+
+fn template_sources(
+    user_template_dir: Option<&Path>,
+) -> Result<BTreeMap<String, TemplateSource>, RenderError> {
+    let mut templates = BTreeMap::new();
     for (name, content) in TPLS {
         templates.insert(
             normalize_template_name(Path::new(name)),
-            (*content).to_string(),
+            TemplateSource {
+                content: (*content).to_string(),
+                origin: TemplateOrigin::Embedded,
+            },
         );
     }
     if let Some(dir) = user_template_dir {
-        for (name, content) in load_user_templates(dir)? {
-            templates.insert(name, content);
+        for (name, content, path) in load_user_templates(dir)? {
+            templates.insert(
+                name,
+                TemplateSource {
+                    content,
+                    origin: TemplateOrigin::Override(path),
+                },
+            );
         }
     }
+    Ok(templates)
+}
+
+/// A parsed template set, where each template came from, and the
+/// [`TemplateEngine::fingerprint`] of its sources.
+type BuiltTera = (Tera, HashMap<String, TemplateOrigin>, String);
+
+fn build_tera(user_template_dir: Option<&Path>) -> Result<BuiltTera, RenderError> {
+    let mut origins = HashMap::new();
+    let mut items = Vec::new();
+    let mut hasher = Sha256::new();
+    for (name, source) in template_sources(user_template_dir)? {
+        // Sources are visited in name order; the NULs keep `a` + `bc` apart
+        // from `ab` + `c`.
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+        hasher.update(source.content.as_bytes());
+        hasher.update([0]);
+        origins.insert(name.clone(), source.origin);
+        items.push((name, source.content));
+    }
 
     let mut tera = Tera::default();
-    let items: Vec<(String, String)> = templates.into_iter().collect();
     tera.add_raw_templates(items)?;
-    Ok(tera)
+    Ok((tera, origins, hex::encode(hasher.finalize())))
 }
 
 // ---------------------------------------------------------------------------
@@ -211,8 +299,55 @@ pub enum AgentKind {
     Gemini,
     Cline,
     Antigravity,
+    RooCode,
+    Zed,
+}
+
+impl fmt::Display for AgentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for AgentKind {
+    type Err = RenderError;
+
+    /// Parse a CLI-facing agent name (see [`AgentKind::name`]), ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AgentKind::all()
+            .iter()
+            .copied()
+            .find(|agent| agent.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| RenderError::UnknownAgent {
+                name: s.to_owned(),
+                expected: AgentKind::all()
+                    .iter()
+                    .map(|agent| agent.name())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            })
+    }
 }
 
+/// How a rendered output is reconciled with the file already on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputStrategy {
+    /// Rendered content replaces the existing file wholesale.
+    Replace,
+    /// Rendered JSON is deep-merged into the existing JSON document.
+    /// Orchestra-managed keys win; unknown user keys are preserved.
+    JsonMerge,
+    /// Only the text between [`MANAGED_REGION_BEGIN`] and
+    /// [`MANAGED_REGION_END`] is replaced; anything the user wrote outside the
+    /// markers is kept. Files without both markers are replaced wholesale.
+    ManagedRegion,
+}
+
+/// Opens the Orchestra-owned part of a [`OutputStrategy::ManagedRegion`] file.
+pub const MANAGED_REGION_BEGIN: &str = "<!-- orchestra:begin -->";
+/// Closes the Orchestra-owned part of a [`OutputStrategy::ManagedRegion`] file.
+pub const MANAGED_REGION_END: &str = "<!-- orchestra:end -->";
+
 pub const PROJECT_ORCHESTRA_DIR: &str = "orchestra";
 pub const LEGACY_PROJECT_ORCHESTRA_DIR: &str = ".orchestra";
 pub const CONTROL_DIR_NAME: &str = "controls";
@@ -256,9 +391,27 @@ impl AgentKind {
             AgentKind::Gemini,
             AgentKind::Cline,
             AgentKind::Antigravity,
+            AgentKind::RooCode,
+            AgentKind::Zed,
         ]
     }
 
+    /// Lowercase name used on the command line (`--agent <name>`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            AgentKind::Claude => "claude",
+            AgentKind::Cursor => "cursor",
+            AgentKind::Windsurf => "windsurf",
+            AgentKind::Copilot => "copilot",
+            AgentKind::Codex => "codex",
+            AgentKind::Gemini => "gemini",
+            AgentKind::Cline => "cline",
+            AgentKind::Antigravity => "antigravity",
+            AgentKind::RooCode => "roo",
+            AgentKind::Zed => "zed",
+        }
+    }
+
     /// Template name(s) to render for this agent.
     pub fn template_names(&self) -> &'static [&'static str] {
         match self {
@@ -298,6 +451,8 @@ impl AgentKind {
                 "antigravity/orchestra.md.tera",
                 "antigravity/skill-orchestra-sync.md.tera",
             ],
+            AgentKind::RooCode     => &["roo/orchestra.md.tera"],
+            AgentKind::Zed         => &["zed/rules.tera"],
         }
     }
 
@@ -375,6 +530,29 @@ impl AgentKind {
                     .join("orchestra-sync")
                     .join("skill.md"),
             ],
+            AgentKind::RooCode => vec![root.join(".roo").join("rules").join("orchestra.md")],
+            AgentKind::Zed => vec![root.join(".rules")],
+        }
+    }
+
+    /// Merge strategy for each managed output.
+    /// Returns one [`OutputStrategy`] per template (same order as `output_paths`).
+    pub fn output_strategies(&self) -> Vec<OutputStrategy> {
+        match self {
+            AgentKind::Gemini => vec![
+                OutputStrategy::ManagedRegion,
+                OutputStrategy::JsonMerge,
+                OutputStrategy::Replace,
+                OutputStrategy::Replace,
+            ],
+            // The top-level instruction files are where people add their own
+            // notes, so only Orchestra's marked region is rewritten.
+            AgentKind::Claude | AgentKind::Codex | AgentKind::Copilot => {
+                let mut strategies = vec![OutputStrategy::Replace; self.template_names().len()];
+                strategies[0] = OutputStrategy::ManagedRegion;
+                strategies
+            }
+            other => vec![OutputStrategy::Replace; other.template_names().len()],
         }
     }
 
@@ -565,6 +743,8 @@ impl AgentKind {
                     .join("orchestra-sync")
                     .join("skill.md"),
             ],
+            // Added after the move to `./orchestra/controls/` — no legacy layout.
+            AgentKind::RooCode | AgentKind::Zed => Vec::new(),
         };
         paths.sort();
         paths.dedup();
@@ -572,6 +752,98 @@ impl AgentKind {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Agent conventions
+// ---------------------------------------------------------------------------
+
+/// Built-in convention bullets for `agent`, exposed to its templates as
+/// `agent_conventions` unless the codebase replaces them through
+/// [`Codebase::agent_conventions`].
+pub fn default_conventions(agent: AgentKind) -> &'static [&'static str] {
+    match agent {
+        AgentKind::Claude => &[
+            "Follow existing code style — match indentation, naming, and module structure.",
+            "Never introduce new dependencies without confirming necessity.",
+            "Keep changes minimal and focused; avoid unrelated refactors.",
+            "Run the project's test suite before marking a task complete.",
+            "Prefer small, reviewable commits over large sweeping changes.",
+        ],
+        AgentKind::Cursor => &[
+            "Match existing code style exactly — do not reformat unrelated code.",
+            "Do not add dependencies without explicit instruction.",
+            "Prefer small, targeted edits over full-file rewrites.",
+            "Run tests before marking any task done.",
+            "Keep commits atomic and focused.",
+        ],
+        AgentKind::Windsurf => &[
+            "Match existing style — indentation, naming, file structure.",
+            "No new dependencies unless explicitly requested.",
+            "Minimal, focused changes — avoid scope creep.",
+            "All tests must pass before task completion.",
+            "Prefer incremental commits; avoid large sweeps.",
+        ],
+        // The "What to Generate" and "Anti-Patterns to Avoid" sections stay
+        // in the template; these are the "Coding Standards" bullets.
+        AgentKind::Copilot => &[
+            "Prefer the patterns already established in the codebase.",
+            "Keep functions focused and small.",
+            "All public APIs must have documentation comments.",
+            "Tests must be written for all new business logic.",
+        ],
+        AgentKind::Codex => &[
+            "**Style:** Match existing conventions in each file. Do not reformat unrelated code.",
+            "**Dependencies:** Do not add new libraries without explicit approval.",
+            "**Scope:** Keep changes minimal and targeted; avoid unrelated modifications.",
+            "**Tests:** All new code must have corresponding tests. Run the full test suite before marking a task done.",
+            "**Commits:** Write clear commit messages describing *what* changed and *why*.",
+            "**PRs:** Keep pull requests small and focused on a single concern.",
+            "**Security:** Never commit secrets, credentials, or API keys.",
+        ],
+        // The `.gemini/styleguide.md` review rules are a separate document
+        // and stay in their template.
+        AgentKind::Gemini => &[
+            "Follow existing code style — indentation, naming, module layout.",
+            "Do not add dependencies without explicit instruction.",
+            "Keep changes minimal and focused.",
+            "Run the project test suite before marking tasks done.",
+        ],
+        AgentKind::Cline => &[
+            "Match existing code style — indentation, naming, structure.",
+            "Do not add dependencies without explicit instruction.",
+            "Keep changes minimal and scoped to the task.",
+            "Do not modify files unrelated to the current task.",
+            "Run all tests before marking a task done.",
+            "Write documentation for every new public function or type.",
+            "Never commit secrets, credentials, or API keys.",
+            "Prefer small, focused PRs over large sweeping changes.",
+        ],
+        AgentKind::Antigravity => &[
+            "Match existing code style — do not reformat adjacent code.",
+            "Do not add dependencies without explicit instruction.",
+            "Keep edits minimal and targeted to the request.",
+            "All new code requires tests. Run the full test suite before completion.",
+            "Write clear commit messages explaining what changed and why.",
+            "Never commit secrets, credentials, or API keys.",
+            "When uncertain about approach, ask for clarification.",
+        ],
+        AgentKind::RooCode => &[
+            "Follow the code style already used in each file — naming, indentation, layout.",
+            "Do not add dependencies without explicit instruction.",
+            "Keep each change scoped to the current task.",
+            "Read the relevant files before editing; do not guess at structure.",
+            "Run the test suite before marking a task done.",
+            "Never commit secrets, credentials, or API keys.",
+        ],
+        AgentKind::Zed => &[
+            "Match the surrounding code style; do not reformat untouched lines.",
+            "Do not add dependencies unless explicitly asked.",
+            "Prefer small, targeted edits over whole-file rewrites.",
+            "Run tests before reporting a task as complete.",
+            "Never commit secrets, credentials, or API keys.",
+        ],
+    }
+}
+
 // ---------------------------------------------------------------------------
 // TemplateEngine
 // ---------------------------------------------------------------------------
@@ -582,14 +854,32 @@ impl AgentKind {
 /// Template names are normalised to lowercase and relative paths.
 pub struct TemplateEngine {
     tera: Tera,
+    origins: HashMap<String, TemplateOrigin>,
+    fingerprint: String,
 }
 
 impl TemplateEngine {
     /// Construct a new [`TemplateEngine`], loading embedded templates plus any
     /// overrides found in `user_template_dir`.
     pub fn new(user_template_dir: Option<&Path>) -> Result<Self, RenderError> {
-        let tera = build_tera(user_template_dir)?;
-        Ok(TemplateEngine { tera })
+        let (tera, origins, fingerprint) = build_tera(user_template_dir)?;
+        Ok(TemplateEngine {
+            tera,
+            origins,
+            fingerprint,
+        })
+    }
+
+    /// Where each loaded template came from, keyed by normalised name.
+    pub fn origins(&self) -> &HashMap<String, TemplateOrigin> {
+        &self.origins
+    }
+
+    /// SHA-256 hex digest of every loaded template's name and source,
+    /// embedded and overrides alike. Equal fingerprints render equal output
+    /// from equal context.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
     }
 
     /// Render all output files for a given `agent` using the supplied context.
@@ -600,7 +890,8 @@ impl TemplateEngine {
         ctx: &TemplateContext,
         agent: AgentKind,
     ) -> Result<Vec<(PathBuf, String)>, RenderError> {
-        let tera_ctx = ctx.to_tera_context()?;
+        let mut tera_ctx = ctx.to_tera_context()?;
+        insert_agent_vars(&mut tera_ctx, ctx, agent);
         let codebase_root = Path::new(&ctx.codebase_path);
         let names = agent.template_names();
         let paths = agent.output_paths(codebase_root);
@@ -613,29 +904,174 @@ impl TemplateEngine {
         );
 
         let mut results = Vec::with_capacity(names.len());
-        for (name, path) in names.iter().zip(paths.into_iter()) {
+        for (index, (name, path)) in names.iter().zip(paths).enumerate() {
+            let mut tera_ctx = tera_ctx.clone();
+            tera_ctx.insert(
+                "output",
+                &OutputVars::new(agent, index, names.len(), &path, codebase_root),
+            );
             let content = self.tera.render(name, &tera_ctx)?;
+            check_managed_markers(name, &content)?;
             results.push((path, content));
         }
         Ok(results)
     }
 }
 
+/// Add the variables that differ between agents: `agent_conventions`,
+/// `tasks_for_agent` and `tasks_assigned_elsewhere`.
+fn insert_agent_vars(tera_ctx: &mut tera::Context, ctx: &TemplateContext, agent: AgentKind) {
+    let tasks = ctx.tasks_for_agent(agent);
+    tera_ctx.insert("agent_conventions", &ctx.agent_conventions(agent));
+    tera_ctx.insert("tasks_assigned_elsewhere", &(ctx.tasks.len() - tasks.len()));
+    tera_ctx.insert("tasks_for_agent", &tasks);
+}
+
+/// Variables describing the file being rendered, exposed to agent templates
+/// as `output`. Pilot, guide and extra-output templates do not get them.
+#[derive(Debug, Clone, Serialize)]
+struct OutputVars {
+    /// Path relative to the controls directory, `/`-separated, e.g.
+    /// `.cursor/rules/orchestra.mdc`.
+    relative_path: String,
+    /// Lowercase agent name, as in `--agent`.
+    agent: &'static str,
+    /// 1-based position among the agent's outputs, like Tera's `loop.index`.
+    index: usize,
+    count: usize,
+}
+
+impl OutputVars {
+    fn new(
+        agent: AgentKind,
+        index: usize,
+        count: usize,
+        path: &Path,
+        codebase_root: &Path,
+    ) -> Self {
+        let relative = path
+            .strip_prefix(control_dir(codebase_root))
+            .unwrap_or(path);
+        OutputVars {
+            relative_path: relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            agent: agent.name(),
+            index: index + 1,
+            count,
+        }
+    }
+}
+
+/// Reject output that repeats a managed-region marker. The writer splices
+/// between the first begin and end marker it finds, so a second pair (say,
+/// from a note quoting the marker) would be treated as user text and the
+/// file would read as modified after every sync.
+fn check_managed_markers(template: &str, content: &str) -> Result<(), RenderError> {
+    for marker in [MANAGED_REGION_BEGIN, MANAGED_REGION_END] {
+        if content.matches(marker).count() > 1 {
+            return Err(RenderError::RepeatedManagedMarker {
+                template: template.to_string(),
+                marker,
+            });
+        }
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Renderer
 // ---------------------------------------------------------------------------
 
 /// Tera-based renderer for all agent kinds.
 ///
-/// Uses embedded templates only. Create once with [`Renderer::new`] and reuse.
+/// Uses embedded templates, plus user overrides when built with
+/// [`Renderer::with_user_templates`]. Parsing the templates is the expensive
+/// part, so prefer [`Renderer::shared`] and [`Renderer::cached`], which
+/// reuse one parsed engine per process and override directory.
 pub struct Renderer {
     engine: TemplateEngine,
+    generation: u64,
+    renders: AtomicU64,
 }
 
 impl Renderer {
     /// Construct a new [`Renderer`] with embedded templates.
     pub fn new() -> Result<Self, RenderError> {
-        Ok(Renderer { engine: TemplateEngine::new(None)? })
+        Self::with_user_templates(None)
+    }
+
+    /// Construct a [`Renderer`] whose embedded templates are overridden by any
+    /// `.tera` files under `user_template_dir` (missing directories are fine).
+    pub fn with_user_templates(user_template_dir: Option<&Path>) -> Result<Self, RenderError> {
+        Ok(Renderer {
+            engine: TemplateEngine::new(user_template_dir)?,
+            generation: NEXT_GENERATION.fetch_add(1, Ordering::Relaxed),
+            renders: AtomicU64::new(0),
+        })
+    }
+
+    /// The process-wide renderer for the embedded templates, parsed on first
+    /// use.
+    pub fn shared() -> &'static Renderer {
+        shared_arc()
+    }
+
+    /// A renderer for `user_template_dir`, reused until a `.tera` file under
+    /// it is added, removed or modified. Without override templates this is
+    /// [`Renderer::shared`].
+    pub fn cached(user_template_dir: Option<&Path>) -> Result<Arc<Renderer>, RenderError> {
+        let Some(dir) = user_template_dir else {
+            return Ok(Arc::clone(shared_arc()));
+        };
+        let Some(fingerprint) = override_fingerprint(dir)? else {
+            return Ok(Arc::clone(shared_arc()));
+        };
+
+        let mut cache = RENDERER_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(index) = cache.iter().position(|entry| entry.dir == dir) {
+            let entry = cache.remove(index);
+            if entry.fingerprint == fingerprint {
+                let renderer = Arc::clone(&entry.renderer);
+                cache.push(entry);
+                return Ok(renderer);
+            }
+        }
+        let renderer = Arc::new(Renderer::with_user_templates(Some(dir))?);
+        if cache.len() >= RENDERER_CACHE_CAPACITY {
+            cache.remove(0);
+        }
+        cache.push(CachedRenderer {
+            dir: dir.to_path_buf(),
+            fingerprint,
+            renderer: Arc::clone(&renderer),
+        });
+        Ok(renderer)
+    }
+
+    /// Distinct for every parsed engine, so callers can tell whether two
+    /// renderers share one.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// [`TemplateEngine::fingerprint`] of this renderer's templates.
+    pub fn fingerprint(&self) -> &str {
+        self.engine.fingerprint()
+    }
+
+    /// How many render calls this renderer has served, so callers and tests
+    /// can tell whether a cache skipped rendering.
+    pub fn render_count(&self) -> u64 {
+        self.renders.load(Ordering::Relaxed)
+    }
+
+    fn count_render(&self) {
+        self.renders.fetch_add(1, Ordering::Relaxed);
     }
 
     /// Render all output files for a given `agent` using data from `codebase`.
@@ -656,11 +1092,13 @@ impl Renderer {
         ctx: &TemplateContext,
         agent: AgentKind,
     ) -> Result<Vec<(PathBuf, String)>, RenderError> {
+        self.count_render();
         self.engine.render(ctx, agent)
     }
 
     /// Render Orchestra pilot entrypoint file.
     pub fn render_pilot(&self, ctx: &TemplateContext) -> Result<(PathBuf, String), RenderError> {
+        self.count_render();
         let tera_ctx = ctx.to_tera_context()?;
         let content = self.engine.tera.render("pilot/pilot.md.tera", &tera_ctx)?;
         let path = pilot_path(Path::new(&ctx.codebase_path));
@@ -669,11 +1107,232 @@ impl Renderer {
 
     /// Render Orchestra hidden context guide.
     pub fn render_guide(&self, ctx: &TemplateContext) -> Result<(PathBuf, String), RenderError> {
+        self.count_render();
         let tera_ctx = ctx.to_tera_context()?;
         let content = self.engine.tera.render("guide/guide.md.tera", &tera_ctx)?;
         let path = guide_path(Path::new(&ctx.codebase_path));
         Ok((path, content))
     }
+
+    /// Render the template called `name` (e.g. `ai-context.md.tera` from the
+    /// user template directory) with the shared codebase context.
+    pub fn render_template(
+        &self,
+        name: &str,
+        ctx: &TemplateContext,
+    ) -> Result<String, RenderError> {
+        let name = normalize_template_name(Path::new(name));
+        if !self.engine.origins.contains_key(&name) {
+            return Err(RenderError::UnknownTemplate { name });
+        }
+        self.count_render();
+        let tera_ctx = ctx.to_tera_context()?;
+        Ok(self.engine.tera.render(&name, &tera_ctx)?)
+    }
+
+    /// Render the starter entry-point file for a newly registered subagent.
+    ///
+    /// Unlike provider files this is written once and then left to the user.
+    pub fn render_subagent_entry(
+        &self,
+        ctx: &TemplateContext,
+        agent_id: &str,
+        skills: &[String],
+    ) -> Result<String, RenderError> {
+        self.count_render();
+        let mut tera_ctx = ctx.to_tera_context()?;
+        tera_ctx.insert("agent_id", agent_id);
+        tera_ctx.insert("agent_skills", skills);
+        Ok(self.engine.tera.render("subagent/entry.md.tera", &tera_ctx)?)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Renderer cache
+// ---------------------------------------------------------------------------
+
+/// Override directories [`Renderer::cached`] keeps parsed at once; the least
+/// recently used is dropped first.
+const RENDERER_CACHE_CAPACITY: usize = 8;
+
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+static SHARED_RENDERER: OnceLock<Arc<Renderer>> = OnceLock::new();
+static RENDERER_CACHE: Mutex<Vec<CachedRenderer>> = Mutex::new(Vec::new());
+
+struct CachedRenderer {
+    dir: PathBuf,
+    fingerprint: OverrideFingerprint,
+    renderer: Arc<Renderer>,
+}
+
+/// The state of an override directory's `.tera` files: editing one moves the
+/// newest mtime, adding or removing one changes the count or total size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct OverrideFingerprint {
+    templates: usize,
+    bytes: u64,
+    newest: SystemTime,
+}
+
+fn shared_arc() -> &'static Arc<Renderer> {
+    SHARED_RENDERER
+        .get_or_init(|| Arc::new(Renderer::new().expect("embedded templates always parse")))
+}
+
+/// `None` when `dir` holds no `.tera` files (or does not exist).
+fn override_fingerprint(dir: &Path) -> Result<Option<OverrideFingerprint>, RenderError> {
+    if !dir.exists() {
+        return Ok(None);
+    }
+    let mut files = Vec::new();
+    collect_template_files(dir, &mut files)?;
+    let mut fingerprint = OverrideFingerprint {
+        templates: 0,
+        bytes: 0,
+        newest: SystemTime::UNIX_EPOCH,
+    };
+    for path in files {
+        if path.extension().and_then(|s| s.to_str()) != Some("tera") {
+            continue;
+        }
+        let meta = std::fs::metadata(&path).map_err(|e| io_err(&path, e))?;
+        let modified = meta.modified().map_err(|e| io_err(&path, e))?;
+        fingerprint.templates += 1;
+        fingerprint.bytes += meta.len();
+        fingerprint.newest = fingerprint.newest.max(modified);
+    }
+    Ok((fingerprint.templates > 0).then_some(fingerprint))
+}
+
+// ---------------------------------------------------------------------------
+// Template checks
+// ---------------------------------------------------------------------------
+
+/// Outcome of checking one template with [`check_templates`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateCheck {
+    /// Normalised template name, e.g. `claude/claude.md.tera`.
+    pub name: String,
+    pub origin: TemplateOrigin,
+    /// Parse or render error, including the Tera location when known.
+    pub error: Option<String>,
+}
+
+impl TemplateCheck {
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Templates rendered directly (the rest are only reached via `include`).
+fn entry_templates() -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = AgentKind::all()
+        .iter()
+        .flat_map(|agent| agent.template_names().iter().copied())
+        .collect();
+    names.extend([
+        "pilot/pilot.md.tera",
+        "guide/guide.md.tera",
+        "subagent/entry.md.tera",
+    ]);
+    names
+}
+
+/// Tera nests the useful detail (line, column, missing variable) in the
+/// error's source chain rather than its top-level message.
+fn describe_tera_error(err: &tera::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(err);
+    while let Some(inner) = source {
+        message.push_str(": ");
+        message.push_str(&inner.to_string());
+        source = inner.source();
+    }
+    message
+}
+
+/// Parse every template (embedded plus overrides from `user_template_dir`)
+/// and render each entry template against `ctx`, without writing anything.
+///
+/// Each template is parsed on its own so that one broken override does not
+/// hide problems in the others; a template that fails to parse is replaced
+/// by its embedded default while the rest are rendered. Results are sorted
+/// by template name. Only reading the override directory can fail outright.
+pub fn check_templates(
+    user_template_dir: Option<&Path>,
+    ctx: &TemplateContext,
+) -> Result<Vec<TemplateCheck>, RenderError> {
+    let mut checks = BTreeMap::new();
+    let mut parsed = Vec::new();
+    for (name, source) in template_sources(user_template_dir)? {
+        let mut probe = Tera::default();
+        let error = match probe.add_raw_template(&name, &source.content) {
+            Ok(()) => {
+                parsed.push((name.clone(), source.content));
+                None
+            }
+            Err(err) => {
+                if let Some(default) = embedded_template(&name) {
+                    parsed.push((name.clone(), default.to_string()));
+                }
+                Some(describe_tera_error(&err))
+            }
+        };
+        checks.insert(
+            name.clone(),
+            TemplateCheck {
+                name,
+                origin: source.origin,
+                error,
+            },
+        );
+    }
+
+    let mut tera = Tera::default();
+    tera.add_raw_templates(parsed)?;
+    let mut tera_ctx = ctx.to_tera_context()?;
+    tera_ctx.insert("agent_id", "example-agent");
+    tera_ctx.insert("agent_skills", &["orchestra-sync".to_string()]);
+    let codebase_root = Path::new(&ctx.codebase_path);
+    let mut outputs = HashMap::new();
+    for agent in AgentKind::all() {
+        let names = agent.template_names();
+        for (index, (name, path)) in names
+            .iter()
+            .zip(agent.output_paths(codebase_root))
+            .enumerate()
+        {
+            outputs.insert(
+                *name,
+                (
+                    OutputVars::new(*agent, index, names.len(), &path, codebase_root),
+                    *agent,
+                ),
+            );
+        }
+    }
+    for name in entry_templates() {
+        let Some(check) = checks.get_mut(name) else {
+            continue;
+        };
+        if check.error.is_some() {
+            continue;
+        }
+        let mut tera_ctx = tera_ctx.clone();
+        if let Some((output, agent)) = outputs.get(name) {
+            tera_ctx.insert("output", output);
+            insert_agent_vars(&mut tera_ctx, ctx, *agent);
+        }
+        match tera.render(name, &tera_ctx) {
+            Ok(content) => {
+                if let Err(err) = check_managed_markers(name, &content) {
+                    check.error = Some(err.to_string());
+                }
+            }
+            Err(err) => check.error = Some(describe_tera_error(&err)),
+        }
+    }
+    Ok(checks.into_values().collect())
 }
 
 // ---------------------------------------------------------------------------
@@ -684,7 +1343,9 @@ impl Renderer {
 mod tests {
     use super::*;
     use chrono::Utc;
-    use orchestra_core::types::{Codebase, CodebaseName, Project, ProjectName, ProjectType};
+    use orchestra_core::types::{
+        Codebase, CodebaseName, Project, ProjectName, ProjectType, CURRENT_SCHEMA_VERSION,
+    };
     use std::path::PathBuf;
 
     fn make_codebase(name: &str) -> Codebase {
@@ -695,16 +1356,41 @@ mod tests {
             projects: vec![Project {
                 name: ProjectName::from("api"),
                 project_type: ProjectType::Backend,
+                framework: None,
+                detection: None,
                 tasks: vec![],
                 agents: vec![],
+                commands: BTreeMap::new(),
+                extra: BTreeMap::new(),
             }],
             conventions: vec![],
             skills: vec![],
             notes: vec![],
             tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: BTreeMap::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             created_at: now,
             updated_at: now,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn agent_names_round_trip() {
+        for agent in AgentKind::all() {
+            assert_eq!(agent.name().parse::<AgentKind>().unwrap(), *agent);
         }
+        assert_eq!("Claude".parse::<AgentKind>().unwrap(), AgentKind::Claude);
+        let err = "vim".parse::<AgentKind>().unwrap_err().to_string();
+        assert!(err.contains("unknown agent 'vim'"), "got: {err}");
+        assert!(err.contains("claude, cursor"), "got: {err}");
     }
 
     #[test]
@@ -750,12 +1436,54 @@ mod tests {
         assert_eq!(results.len(), 4, "Claude should produce 4 files");
     }
 
+    #[test]
+    fn registered_subagents_are_listed_in_claude_md() {
+        let renderer = Renderer::new().unwrap();
+        let mut cb = make_codebase("claudeapp");
+        let before = renderer.render(&cb, AgentKind::Claude).unwrap().remove(0).1;
+        assert!(!before.contains("Subagents registered"));
+
+        cb.projects[0].agents.push(orchestra_core::types::AgentConfig {
+            agent_id: "migrations".to_string(),
+            entry_point: PathBuf::from("AGENT/migrations.md"),
+            skills: Some(vec!["sql".to_string()]),
+        });
+        let claude_md = renderer.render(&cb, AgentKind::Claude).unwrap().remove(0).1;
+        assert!(
+            claude_md.contains("- **migrations** — `AGENT/migrations.md` (skills: sql)"),
+            "{claude_md}"
+        );
+    }
+
+    #[test]
+    fn subagent_entry_renders_codebase_context() {
+        let renderer = Renderer::new().unwrap();
+        let cb = make_codebase("entryapp");
+        let ctx = TemplateContext::from_codebase(&cb);
+        let entry = renderer
+            .render_subagent_entry(&ctx, "migrations", &["sql".to_string()])
+            .unwrap();
+        assert!(entry.contains("# migrations\n"));
+        assert!(entry.contains("Subagent for `entryapp`"));
+        assert!(entry.contains("- **api** — backend"));
+        assert!(entry.contains("- No active tasks."));
+        assert!(entry.contains("## Skills\n\n- sql\n"));
+    }
+
     #[test]
     fn copilot_produces_repo_and_path_instructions() {
         let renderer = Renderer::new().unwrap();
         let cb = make_codebase("copilotapp");
         let results = renderer.render(&cb, AgentKind::Copilot).unwrap();
         assert_eq!(results.len(), 2, "Copilot should produce 2 files");
+        let instructions = &results[0].1;
+        for heading in [
+            "## What to Generate",
+            "## Anti-Patterns to Avoid",
+            "## Coding Standards",
+        ] {
+            assert!(instructions.contains(heading), "missing {heading}");
+        }
     }
 
     #[test]
@@ -818,6 +1546,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn output_strategies_count_matches_template_count() {
+        for agent in AgentKind::all() {
+            assert_eq!(
+                agent.template_names().len(),
+                agent.output_strategies().len(),
+                "strategy/template count mismatch for {:?}",
+                agent
+            );
+        }
+    }
+
+    #[test]
+    fn gemini_settings_json_uses_json_merge() {
+        let root = PathBuf::from("/code/myapp");
+        let paths = AgentKind::Gemini.output_paths(&root);
+        let strategies = AgentKind::Gemini.output_strategies();
+        let settings = paths
+            .iter()
+            .position(|p| p.ends_with(".gemini/settings.json"))
+            .expect("settings.json output");
+        assert_eq!(strategies[settings], OutputStrategy::JsonMerge);
+        assert_eq!(
+            strategies.iter().filter(|s| **s == OutputStrategy::JsonMerge).count(),
+            1,
+            "only settings.json should be merged"
+        );
+    }
+
     #[test]
     fn claude_output_path_is_correct() {
         let root = PathBuf::from("/code/myapp");
@@ -835,6 +1592,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn roo_code_and_zed_output_paths_are_correct() {
+        let root = PathBuf::from("/code/myapp");
+        assert_eq!(
+            AgentKind::RooCode.output_paths(&root),
+            vec![PathBuf::from("/code/myapp/orchestra/controls/.roo/rules/orchestra.md")]
+        );
+        assert_eq!(
+            AgentKind::Zed.output_paths(&root),
+            vec![PathBuf::from("/code/myapp/orchestra/controls/.rules")]
+        );
+        assert!(AgentKind::RooCode.legacy_output_paths(&root).is_empty());
+        assert!(AgentKind::Zed.legacy_output_paths(&root).is_empty());
+    }
+
     #[test]
     fn legacy_output_paths_remain_available_for_cleanup() {
         let root = PathBuf::from("/code/myapp");
@@ -877,4 +1649,41 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn fingerprint_tracks_template_sources() {
+        let embedded = Renderer::new().unwrap();
+        assert_eq!(
+            embedded.fingerprint(),
+            Renderer::new().unwrap().fingerprint()
+        );
+        assert_eq!(embedded.fingerprint().len(), 64, "sha-256 hex");
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let overrides = dir.path().join("templates");
+        std::fs::create_dir_all(overrides.join("pilot")).unwrap();
+        std::fs::write(
+            overrides.join("pilot/pilot.md.tera"),
+            "# {{ codebase_name }}\n",
+        )
+        .unwrap();
+        let overridden = Renderer::with_user_templates(Some(&overrides)).unwrap();
+        assert_ne!(overridden.fingerprint(), embedded.fingerprint());
+
+        std::fs::write(overrides.join("pilot/pilot.md.tera"), "# pilot\n").unwrap();
+        let edited = Renderer::with_user_templates(Some(&overrides)).unwrap();
+        assert_ne!(edited.fingerprint(), overridden.fingerprint());
+    }
+
+    #[test]
+    fn render_count_counts_every_render_call() {
+        let renderer = Renderer::new().unwrap();
+        let cb = make_codebase("count_test");
+        let ctx = TemplateContext::from_codebase(&cb);
+        assert_eq!(renderer.render_count(), 0);
+        renderer.render(&cb, AgentKind::Claude).unwrap();
+        renderer.render_pilot(&ctx).unwrap();
+        renderer.render_guide(&ctx).unwrap();
+        assert_eq!(renderer.render_count(), 3);
+    }
 }
@@ -18,4 +18,23 @@ pub enum RenderError {
     /// Filesystem error while loading user templates.
     #[error("template io error at {path}: {source}")]
     Io { path: PathBuf, source: std::io::Error },
+
+    /// An agent name (e.g. from `--agent`) did not match any [`AgentKind`].
+    ///
+    /// [`AgentKind`]: crate::AgentKind
+    #[error("unknown agent '{name}' (expected one of: {expected})")]
+    UnknownAgent { name: String, expected: String },
+
+    /// A template requested by name (e.g. for an extra output) is neither
+    /// embedded nor in the user template directory.
+    #[error("no template named '{name}'")]
+    UnknownTemplate { name: String },
+
+    /// A rendered agent file repeats a managed-region marker, which would
+    /// make the writer splice the wrong region on every sync.
+    #[error("template '{template}' rendered `{marker}` more than once; remove it from the template or registry text")]
+    RepeatedManagedMarker {
+        template: String,
+        marker: &'static str,
+    },
 }
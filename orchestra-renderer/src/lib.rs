@@ -22,10 +22,17 @@
 //! }
 //! ```
 
+pub mod budget;
+pub mod commands;
 pub mod context;
 pub mod engine;
 pub mod error;
+pub mod skills;
 
-pub use context::TemplateContext;
-pub use engine::{AgentKind, Renderer, TemplateEngine};
+pub use budget::{estimate_tokens, render_within_budget, BudgetOverrun, BudgetedRender, Trim};
+pub use context::{TemplateContext, TemplateContextBuilder};
+pub use engine::{
+    check_templates, default_conventions, user_template_dir_at, AgentKind, OutputStrategy,
+    Renderer, TemplateCheck, TemplateEngine, TemplateOrigin,
+};
 pub use error::RenderError;
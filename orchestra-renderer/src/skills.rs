@@ -0,0 +1,186 @@
+//! Skills catalog — reusable skill descriptions shared by every codebase.
+//!
+//! Each skill lives in `~/.orchestra/skills/<id>.md`. The first heading is
+//! the skill's title and the first paragraph after it is the description
+//! rendered into agent files. Codebases refer to skills by id only, so one
+//! catalog entry describes a skill everywhere it is used.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use orchestra_core::paths;
+use serde::{Deserialize, Serialize};
+
+use crate::error::RenderError;
+
+/// One skill loaded from the catalog.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkillEntry {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+}
+
+/// Skill entries keyed by id.
+pub type SkillCatalog = BTreeMap<String, SkillEntry>;
+
+/// Directory holding catalog files.
+///
+/// `~/.orchestra/skills/`
+pub fn catalog_dir_at(home: &Path) -> PathBuf {
+    paths::dirs_at(home).data_root.join("skills")
+}
+
+/// Path to the catalog file for `id`.
+///
+/// `~/.orchestra/skills/<id>.md`
+pub fn entry_path_at(home: &Path, id: &str) -> PathBuf {
+    catalog_dir_at(home).join(format!("{id}.md"))
+}
+
+/// Load every `*.md` file in the catalog directory.
+///
+/// A missing directory is an empty catalog.
+pub fn load_catalog(home: &Path) -> Result<SkillCatalog, RenderError> {
+    let dir = catalog_dir_at(home);
+    let mut catalog = SkillCatalog::new();
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(catalog),
+        Err(e) => return Err(io_err(&dir, e)),
+    };
+
+    for entry in entries {
+        let path = entry.map_err(|e| io_err(&dir, e))?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+            continue;
+        }
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let content = fs::read_to_string(&path).map_err(|e| io_err(&path, e))?;
+        let skill = parse_entry(id, &content);
+        catalog.insert(skill.id.clone(), skill);
+    }
+    Ok(catalog)
+}
+
+/// Parse a catalog file's markdown.
+///
+/// The title falls back to `id` when the file has no heading, and the
+/// description falls back to the title when there is no paragraph.
+/// Paragraph lines are joined with single spaces.
+pub fn parse_entry(id: &str, content: &str) -> SkillEntry {
+    let mut title = None;
+    let mut paragraph: Vec<&str> = Vec::new();
+
+    for line in content.lines().map(str::trim) {
+        if let Some(heading) = line.strip_prefix('#') {
+            if !paragraph.is_empty() {
+                break;
+            }
+            if title.is_none() {
+                title = Some(heading.trim_start_matches('#').trim().to_string());
+            }
+            continue;
+        }
+        if line.is_empty() {
+            if !paragraph.is_empty() {
+                break;
+            }
+            continue;
+        }
+        paragraph.push(line);
+    }
+
+    let title = title
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| id.to_string());
+    let description = if paragraph.is_empty() {
+        title.clone()
+    } else {
+        paragraph.join(" ")
+    };
+    SkillEntry {
+        id: id.to_string(),
+        title,
+        description,
+    }
+}
+
+/// Markdown written for a new catalog entry.
+pub fn render_entry(entry: &SkillEntry) -> String {
+    format!("# {}\n\n{}\n", entry.title, entry.description)
+}
+
+/// Write `entry` to its catalog file, creating the directory if needed.
+///
+/// Returns the path written.
+pub fn save_entry_at(home: &Path, entry: &SkillEntry) -> Result<PathBuf, RenderError> {
+    let dir = catalog_dir_at(home);
+    fs::create_dir_all(&dir).map_err(|e| io_err(&dir, e))?;
+    let path = entry_path_at(home, &entry.id);
+    fs::write(&path, render_entry(entry)).map_err(|e| io_err(&path, e))?;
+    Ok(path)
+}
+
+fn io_err(path: &Path, source: std::io::Error) -> RenderError {
+    RenderError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn parse_takes_first_heading_and_first_paragraph() {
+        let content = "# API design\n\nDesign REST endpoints\nwith versioned routes.\n\nMore detail.\n";
+        let entry = parse_entry("api-design", content);
+        assert_eq!(entry.title, "API design");
+        assert_eq!(
+            entry.description,
+            "Design REST endpoints with versioned routes."
+        );
+    }
+
+    #[test]
+    fn parse_falls_back_to_id_and_title() {
+        let entry = parse_entry("rust", "");
+        assert_eq!(entry.title, "rust");
+        assert_eq!(entry.description, "rust");
+
+        let entry = parse_entry("rust", "## Rust\n");
+        assert_eq!(entry.description, "Rust");
+    }
+
+    #[test]
+    fn missing_catalog_dir_is_empty() {
+        let home = TempDir::new().unwrap();
+        assert!(load_catalog(home.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn saved_entries_round_trip_through_load() {
+        let home = TempDir::new().unwrap();
+        let entry = SkillEntry {
+            id: "sql".to_string(),
+            title: "SQL".to_string(),
+            description: "Write migrations that can be rolled back.".to_string(),
+        };
+        save_entry_at(home.path(), &entry).unwrap();
+        fs::write(catalog_dir_at(home.path()).join("notes.txt"), "ignored").unwrap();
+
+        let catalog = load_catalog(home.path()).unwrap();
+        assert_eq!(catalog.len(), 1);
+        assert_eq!(catalog["sql"], entry);
+    }
+}
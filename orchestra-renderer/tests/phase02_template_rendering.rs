@@ -1,9 +1,13 @@
+use std::collections::BTreeMap;
+
 use chrono::Utc;
 use orchestra_core::types::{
-    Codebase, CodebaseName, Project, ProjectName, ProjectType, Task, TaskId, TaskStatus,
+    Codebase, CodebaseName, Project, ProjectName, ProjectType, StackDetection, Task, TaskId,
+    TaskStatus, CURRENT_SCHEMA_VERSION,
 };
 use orchestra_renderer::{
-    AgentKind, TemplateContext, TemplateEngine,
+    check_templates, default_conventions, AgentKind, Renderer, TemplateContext, TemplateEngine,
+    TemplateOrigin,
     context::{SkillCtx, TaskSummary},
 };
 use tempfile::TempDir;
@@ -16,6 +20,8 @@ fn make_codebase() -> Codebase {
         projects: vec![Project {
             name: ProjectName::from("api"),
             project_type: ProjectType::Backend,
+            framework: None,
+            detection: None,
             tasks: vec![
                 Task {
                     id: TaskId::from("t-001"),
@@ -24,8 +30,10 @@ fn make_codebase() -> Codebase {
                     description: Some("Implement login + session handling".to_string()),
                     subtasks: vec![],
                     notes: vec![],
+                    assigned_agent: None,
                     created_at: now,
                     updated_at: now,
+                    extra: BTreeMap::new(),
                 },
                 Task {
                     id: TaskId::from("t-002"),
@@ -34,8 +42,10 @@ fn make_codebase() -> Codebase {
                     description: None,
                     subtasks: vec![],
                     notes: vec![],
+                    assigned_agent: None,
                     created_at: now,
                     updated_at: now,
+                    extra: BTreeMap::new(),
                 },
                 Task {
                     id: TaskId::from("t-003"),
@@ -44,8 +54,10 @@ fn make_codebase() -> Codebase {
                     description: Some("Waiting on infra change".to_string()),
                     subtasks: vec![],
                     notes: vec![],
+                    assigned_agent: None,
                     created_at: now,
                     updated_at: now,
+                    extra: BTreeMap::new(),
                 },
                 Task {
                     id: TaskId::from("t-004"),
@@ -54,111 +66,55 @@ fn make_codebase() -> Codebase {
                     description: Some("Delete deprecated endpoints".to_string()),
                     subtasks: vec![],
                     notes: vec![],
+                    assigned_agent: None,
                     created_at: now,
                     updated_at: now,
+                    extra: BTreeMap::new(),
                 },
             ],
             agents: vec![],
+            commands: BTreeMap::new(),
+            extra: BTreeMap::new(),
         }],
         conventions: vec![],
         skills: vec![],
         notes: vec![],
         tracked_files: vec![],
+        max_rendered_tasks: None,
+        output_root: None,
+        manifest: None,
+        backups: None,
+        enforce_budget: None,
+        hooks: None,
+        extra_outputs: vec![],
+        agent_conventions: BTreeMap::new(),
+        schema_version: CURRENT_SCHEMA_VERSION,
         created_at: now,
         updated_at: now,
+        extra: BTreeMap::new(),
     }
 }
 
-fn expected_conventions(agent: AgentKind) -> &'static [&'static str] {
-    match agent {
-        AgentKind::Claude => &[
-            "Follow existing code style — match indentation, naming, and module structure.",
-            "Never introduce new dependencies without confirming necessity.",
-            "Keep changes minimal and focused; avoid unrelated refactors.",
-            "Run the project's test suite before marking a task complete.",
-            "Prefer small, reviewable commits over large sweeping changes.",
-        ],
-        AgentKind::Cursor => &[
-            "Match existing code style exactly — do not reformat unrelated code.",
-            "Do not add dependencies without explicit instruction.",
-            "Prefer small, targeted edits over full-file rewrites.",
-            "Run tests before marking any task done.",
-            "Keep commits atomic and focused.",
-        ],
-        AgentKind::Windsurf => &[
-            "Match existing style — indentation, naming, file structure.",
-            "No new dependencies unless explicitly requested.",
-            "Minimal, focused changes — avoid scope creep.",
-            "All tests must pass before task completion.",
-            "Prefer incremental commits; avoid large sweeps.",
-        ],
-        AgentKind::Copilot => &[
-            "Follow the existing file structure and naming conventions already in the codebase.",
-            "Match the language and framework idioms present in each directory.",
-            "When creating new files, mirror the style of adjacent files.",
-            "Generate complete, working implementations — avoid TODO stubs unless explicitly asked.",
-            "Do not introduce dependencies not already in the project.",
-            "Do not reformat code outside the lines you are changing.",
-            "Do not generate overly verbose comments — match the existing comment density.",
-            "Do not change unrelated files when asked to modify a specific feature.",
-            "Prefer the patterns already established in the codebase.",
-            "Keep functions focused and small.",
-            "All public APIs must have documentation comments.",
-            "Tests must be written for all new business logic.",
-        ],
-        AgentKind::Codex => &[
-            "**Style:** Match existing conventions in each file. Do not reformat unrelated code.",
-            "**Dependencies:** Do not add new libraries without explicit approval.",
-            "**Scope:** Keep changes minimal and targeted; avoid unrelated modifications.",
-            "**Tests:** All new code must have corresponding tests. Run the full test suite before marking a task done.",
-            "**Commits:** Write clear commit messages describing *what* changed and *why*.",
-            "**PRs:** Keep pull requests small and focused on a single concern.",
-            "**Security:** Never commit secrets, credentials, or API keys.",
-        ],
-        AgentKind::Gemini => &[
-            "Follow existing code style — indentation, naming, module layout.",
-            "Do not add dependencies without explicit instruction.",
-            "Keep changes minimal and focused.",
-            "Run the project test suite before marking tasks done.",
-            "Match the style of adjacent files in every edit.",
-            "Preserve existing indentation — do not reformat beyond the changed lines.",
-            "Keep line length consistent with the rest of the file.",
-            "Use descriptive names — avoid single-letter variables outside short loop indices.",
-            "Follow the naming convention of the language in use (snake_case, camelCase, PascalCase, etc.).",
-            "All public functions and types must have documentation comments.",
-            "Comments should explain *why*, not *what* — the code shows what.",
-            "Every new business-logic function requires at least one unit test.",
-            "Test edge cases: empty inputs, boundary values, and error paths.",
-            "Never commit secrets, credentials, or API keys.",
-            "Validate all inputs on the server side.",
-            "Use parameterized queries — never string-interpolate user input into SQL.",
-        ],
-        AgentKind::Cline => &[
-            "Match existing code style — indentation, naming, structure.",
-            "Do not add dependencies without explicit instruction.",
-            "Keep changes minimal and scoped to the task.",
-            "Do not modify files unrelated to the current task.",
-            "Run all tests before marking a task done.",
-            "Write documentation for every new public function or type.",
-            "Never commit secrets, credentials, or API keys.",
-            "Prefer small, focused PRs over large sweeping changes.",
-        ],
-        AgentKind::Antigravity => &[
-            "Match existing code style — do not reformat adjacent code.",
-            "Do not add dependencies without explicit instruction.",
-            "Keep edits minimal and targeted to the request.",
-            "All new code requires tests. Run the full test suite before completion.",
-            "Write clear commit messages explaining what changed and why.",
-            "Never commit secrets, credentials, or API keys.",
-            "When uncertain about approach, ask for clarification.",
-        ],
-    }
-}
+/// Rules in `.gemini/styleguide.md`, which stays a fixed document rather
+/// than part of Gemini's `agent_conventions`.
+const GEMINI_STYLEGUIDE_RULES: &[&str] = &[
+    "Match the style of adjacent files in every edit.",
+    "Preserve existing indentation — do not reformat beyond the changed lines.",
+    "Keep line length consistent with the rest of the file.",
+    "Use descriptive names — avoid single-letter variables outside short loop indices.",
+    "Follow the naming convention of the language in use (snake_case, camelCase, PascalCase, etc.).",
+    "All public functions and types must have documentation comments.",
+    "Comments should explain *why*, not *what* — the code shows what.",
+    "Every new business-logic function requires at least one unit test.",
+    "Test edge cases: empty inputs, boundary values, and error paths.",
+    "Never commit secrets, credentials, or API keys.",
+    "Validate all inputs on the server side.",
+    "Use parameterized queries — never string-interpolate user input into SQL.",
+];
 
 #[test]
 fn template_rendering_correctness_all_agents() {
     let codebase = make_codebase();
-    let mut ctx = TemplateContext::from_codebase(&codebase);
     let extra_conventions = vec![
         "No tabs — spaces only.".to_string(),
         "Prefer explicit error handling over unwraps.".to_string(),
@@ -173,8 +129,10 @@ fn template_rendering_correctness_all_agents() {
             description: "template-sync-engineer".to_string(),
         },
     ];
-    ctx.conventions = extra_conventions.clone();
-    ctx.skills = extra_skills.clone();
+    let ctx = TemplateContext::builder(&codebase)
+        .conventions(extra_conventions.clone())
+        .skills(extra_skills.clone())
+        .build();
     let engine = TemplateEngine::new(None).expect("engine");
 
     let active_titles = [
@@ -216,9 +174,9 @@ fn template_rendering_correctness_all_agents() {
             "done task id leaked for {:?}",
             agent
         );
-        for conv in expected_conventions(*agent) {
+        for conv in default_conventions(*agent) {
             assert!(
-                combined.contains(conv),
+                combined.contains(&format!("- {conv}\n")),
                 "convention missing for {:?}: {conv}",
                 agent
             );
@@ -238,8 +196,30 @@ fn template_rendering_correctness_all_agents() {
                 skill.description
             );
         }
+        let (first_path, first) = &outputs[0];
+        let relative = first_path
+            .strip_prefix("/code/copnow_api/orchestra/controls")
+            .expect("output under controls");
+        assert!(
+            first.contains(&format!("<!-- File: {} -->", relative.display())),
+            "own path missing from header for {:?}",
+            agent
+        );
+        assert!(
+            combined.contains("## Backend Guidance"),
+            "backend guidance missing for {:?}",
+            agent
+        );
+        assert!(
+            !combined.contains("Primary framework:"),
+            "framework line rendered without a framework for {:?}",
+            agent
+        );
 
         if *agent == AgentKind::Gemini {
+            for rule in GEMINI_STYLEGUIDE_RULES {
+                assert!(combined.contains(rule), "styleguide rule missing: {rule}");
+            }
             let settings = outputs.iter()
                 .find(|(path, _)| path.ends_with("settings.json"))
                 .expect("gemini settings.json output missing");
@@ -251,6 +231,182 @@ fn template_rendering_correctness_all_agents() {
     }
 }
 
+#[test]
+fn agent_convention_override_replaces_only_that_agents_bullets() {
+    let mut codebase = make_codebase();
+    codebase.agent_conventions.insert(
+        "claude".to_string(),
+        vec![
+            "Add dependencies freely when they save real work.".to_string(),
+            "Squash commits before review.".to_string(),
+        ],
+    );
+    let ctx = TemplateContext::from_codebase(&codebase);
+    let engine = TemplateEngine::new(None).expect("engine");
+
+    let claude = engine.render(&ctx, AgentKind::Claude).expect("render claude");
+    let claude_md = &claude[0].1;
+    assert!(claude_md.contains("## Conventions\n\n- Add dependencies freely when they save real work.\n- Squash commits before review.\n"));
+    for default in default_conventions(AgentKind::Claude) {
+        assert!(!claude_md.contains(default), "default bullet kept: {default}");
+    }
+    assert!(claude_md.contains("## Project Overview"), "rest of the template is intact");
+
+    for agent in AgentKind::all().iter().filter(|agent| **agent != AgentKind::Claude) {
+        let combined: String = engine
+            .render(&ctx, *agent)
+            .expect("render")
+            .into_iter()
+            .map(|(_, content)| content)
+            .collect();
+        assert!(!combined.contains("Squash commits before review."), "override leaked into {agent:?}");
+        for default in default_conventions(*agent) {
+            assert!(combined.contains(default), "{agent:?} lost default bullet: {default}");
+        }
+    }
+}
+
+#[test]
+fn detected_stack_commands_render_under_explicit_ones() {
+    let mut codebase = make_codebase();
+    let engine = TemplateEngine::new(None).expect("engine");
+    let claude_md = |codebase: &Codebase| {
+        let ctx = TemplateContext::from_codebase(codebase);
+        let outputs = engine.render(&ctx, AgentKind::Claude).expect("render");
+        outputs[0].1.clone()
+    };
+    assert!(!claude_md(&codebase).contains("## Commands"));
+
+    let project = &mut codebase.projects[0];
+    project.framework = Some("Next.js".to_string());
+    project.detection = Some(StackDetection {
+        confidence: "high".to_string(),
+        overridden: false,
+        detected_at: None,
+        indicators: vec![],
+        indicators_sha256: None,
+        language: Some("TypeScript".to_string()),
+        package_manager: Some("pnpm".to_string()),
+    });
+    project
+        .commands
+        .insert("test".to_string(), "pnpm vitest run".to_string());
+
+    let rendered = claude_md(&codebase);
+    assert!(rendered.contains(
+        "## Commands\n\n- **test:** `pnpm vitest run`\n- **build:** `pnpm build` (suggested for this stack)\n- **dev:** `pnpm dev` (suggested for this stack)\n"
+    ));
+    assert!(
+        !rendered.contains("`pnpm test`"),
+        "explicit test command must win"
+    );
+
+    // A stack without suggestions renders no section.
+    let project = &mut codebase.projects[0];
+    project.commands.clear();
+    project.detection.as_mut().unwrap().language = Some("COBOL".to_string());
+    assert!(!claude_md(&codebase).contains("## Commands"));
+}
+
+#[test]
+fn task_cap_renders_overflow_line_and_keeps_in_progress() {
+    let mut codebase = make_codebase();
+    let now = Utc::now();
+    codebase.projects[0].tasks = (0..30)
+        .map(|i| Task {
+            id: TaskId::from(format!("cap-{i:02}")),
+            title: format!("Capped task {i:02}"),
+            // The last five tasks are in progress; they must survive the cap.
+            status: if i >= 25 { TaskStatus::InProgress } else { TaskStatus::Pending },
+            description: None,
+            subtasks: vec![],
+            notes: vec![],
+            assigned_agent: None,
+            created_at: now,
+            updated_at: now,
+            extra: BTreeMap::new(),
+        })
+        .collect();
+    codebase.max_rendered_tasks = Some(10);
+
+    let ctx = TemplateContext::from_codebase(&codebase);
+    let engine = TemplateEngine::new(None).expect("engine");
+    let outputs = engine.render(&ctx, AgentKind::Claude).expect("render");
+    let content = &outputs[0].1;
+
+    let rendered_titles = (0..30)
+        .filter(|i| content.contains(&format!("Capped task {i:02}")))
+        .count();
+    assert_eq!(rendered_titles, 10, "only the capped number of tasks is rendered");
+    for i in 25..30 {
+        assert!(
+            content.contains(&format!("Capped task {i:02}")),
+            "in_progress task {i} was dropped"
+        );
+    }
+    assert!(content.contains("…and 20 more tasks (see orchestra task list)"));
+}
+
+/// The Claude file `TemplateContext::example()` rendered to before tasks
+/// could be assigned to agents, with the version as `{version}`.
+const UNASSIGNED_CLAUDE_GOLDEN: &str = include_str!("fixtures/example_claude.md");
+
+#[test]
+fn unassigned_tasks_render_as_before_assignments() {
+    let engine = TemplateEngine::new(None).expect("engine");
+    let outputs = engine
+        .render(&TemplateContext::example(), AgentKind::Claude)
+        .expect("render");
+    assert_eq!(
+        outputs[0].1,
+        UNASSIGNED_CLAUDE_GOLDEN.replace("{version}", env!("CARGO_PKG_VERSION"))
+    );
+}
+
+#[test]
+fn assigned_tasks_render_only_into_their_agents_files() {
+    let mut codebase = make_codebase();
+    // t-001 stays unassigned; names match case-insensitively.
+    codebase.projects[0].tasks[1].assigned_agent = Some("claude".to_string());
+    codebase.projects[0].tasks[2].assigned_agent = Some("Cursor".to_string());
+    let ctx = TemplateContext::from_codebase(&codebase);
+    let renderer = Renderer::with_user_templates(None).expect("renderer");
+    let render = |agent| {
+        renderer
+            .render_with_context(&ctx, agent)
+            .expect("render")
+            .remove(0)
+            .1
+    };
+
+    let claude = render(AgentKind::Claude);
+    assert!(claude.contains("| t-001 |"));
+    assert!(claude.contains("| t-002 |"));
+    assert!(!claude.contains("| t-003 |"));
+    assert!(claude.contains("Tasks assigned to other agents: 1 (listed in their files)"));
+
+    let cursor = render(AgentKind::Cursor);
+    assert!(cursor.contains("| t-001 |"));
+    assert!(!cursor.contains("| t-002 |"));
+    assert!(cursor.contains("| t-003 |"));
+
+    let windsurf = render(AgentKind::Windsurf);
+    assert!(windsurf.contains("| t-001 |"));
+    assert!(!windsurf.contains("| t-002 |"));
+    assert!(!windsurf.contains("| t-003 |"));
+    assert!(windsurf.contains("Tasks assigned to other agents: 2"));
+
+    // The guide is not an agent's file and lists every task.
+    let (_, guide) = renderer.render_guide(&ctx).expect("guide");
+    for id in ["t-001", "t-002", "t-003"] {
+        assert!(
+            guide.contains(&format!("| {id} |")),
+            "{id} missing from guide"
+        );
+    }
+    assert!(!guide.contains("Tasks assigned to other agents"));
+}
+
 #[test]
 fn user_template_override_wins() {
     let codebase = make_codebase();
@@ -270,14 +426,174 @@ fn user_template_override_wins() {
     assert!(!content.contains("Project Overview"), "embedded template leaked through");
 }
 
+#[test]
+fn outputs_know_their_own_relative_path() {
+    let ctx = TemplateContext::from_codebase(&make_codebase());
+    let engine = TemplateEngine::new(None).expect("engine");
+
+    let cursor = engine.render(&ctx, AgentKind::Cursor).expect("render");
+    assert!(cursor[0].1.contains("<!-- File: .cursor/rules/orchestra.mdc -->"));
+    let claude = engine.render(&ctx, AgentKind::Claude).expect("render");
+    assert!(claude[0].1.contains("<!-- File: CLAUDE.md -->"));
+    assert!(claude[1].1.contains("<!-- File: .claude/rules/orchestra.md -->"));
+
+    // Pilot and guide are not agent outputs.
+    let renderer = Renderer::new().expect("renderer");
+    let (_, pilot) = renderer.render_pilot(&ctx).expect("pilot");
+    assert!(!pilot.contains("<!-- File:"));
+}
+
+#[test]
+fn gemini_outputs_each_get_their_own_output_vars() {
+    let ctx = TemplateContext::from_codebase(&make_codebase());
+    let dir = TempDir::new().expect("tempdir");
+    let probe =
+        "{{ output.agent }} {{ output.index }}/{{ output.count }} {{ output.relative_path }}";
+    for name in AgentKind::Gemini.template_names() {
+        let path = dir.path().join(name);
+        std::fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+        std::fs::write(path, probe).expect("write override");
+    }
+
+    let engine = TemplateEngine::new(Some(dir.path())).expect("engine");
+    let rendered: Vec<String> = engine
+        .render(&ctx, AgentKind::Gemini)
+        .expect("render")
+        .into_iter()
+        .map(|(_, content)| content)
+        .collect();
+    assert_eq!(
+        rendered,
+        vec![
+            "gemini 1/4 GEMINI.md",
+            "gemini 2/4 .gemini/settings.json",
+            "gemini 3/4 .gemini/styleguide.md",
+            "gemini 4/4 .gemini/skills/orchestra-sync/skill.md",
+        ]
+    );
+}
+
+#[test]
+fn repeated_managed_marker_is_rejected() {
+    let ctx = TemplateContext::builder(&make_codebase())
+        .conventions(vec!["Never write <!-- orchestra:end --> by hand.".to_string()])
+        .build();
+    let engine = TemplateEngine::new(None).expect("engine");
+
+    let err = engine.render(&ctx, AgentKind::Claude).unwrap_err().to_string();
+    assert!(err.contains("claude/claude.md.tera"), "got: {err}");
+    assert!(err.contains("more than once"), "got: {err}");
+    // Agents without a managed region are unaffected.
+    engine.render(&ctx, AgentKind::Cursor).expect("cursor render");
+}
+
+#[test]
+fn renderer_without_overrides_is_shared() {
+    let empty = TempDir::new().expect("tempdir");
+    let shared = Renderer::shared().generation();
+
+    assert_eq!(Renderer::shared().generation(), shared);
+    assert_eq!(Renderer::cached(None).expect("cached").generation(), shared);
+    assert_eq!(
+        Renderer::cached(Some(empty.path()))
+            .expect("cached")
+            .generation(),
+        shared
+    );
+    assert_eq!(
+        Renderer::cached(Some(&empty.path().join("missing")))
+            .expect("cached")
+            .generation(),
+        shared
+    );
+}
+
+#[test]
+fn cached_renderer_reloads_after_override_edit() {
+    let codebase = make_codebase();
+    let dir = TempDir::new().expect("tempdir");
+    let custom_path = dir.path().join("claude").join("claude.md.tera");
+    std::fs::create_dir_all(custom_path.parent().expect("parent")).expect("mkdir");
+    std::fs::write(&custom_path, "# First {{ codebase_name }}\n").expect("write template");
+
+    let first = Renderer::cached(Some(dir.path())).expect("cached");
+    let again = Renderer::cached(Some(dir.path())).expect("cached");
+    assert_eq!(first.generation(), again.generation(), "engine reused");
+    assert_ne!(first.generation(), Renderer::shared().generation());
+
+    std::fs::write(&custom_path, "# Second {{ codebase_name }}\n").expect("edit template");
+    let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+    std::fs::File::options()
+        .write(true)
+        .open(&custom_path)
+        .and_then(|file| file.set_modified(later))
+        .expect("bump mtime");
+
+    let edited = Renderer::cached(Some(dir.path())).expect("cached");
+    assert_ne!(edited.generation(), first.generation(), "edit invalidates");
+    let outputs = edited.render(&codebase, AgentKind::Claude).expect("render");
+    assert!(outputs[0].1.contains("# Second copnow_api"), "{}", outputs[0].1);
+}
+
+#[test]
+fn engine_records_template_origins() {
+    let dir = TempDir::new().expect("tempdir");
+    let custom_path = dir.path().join("claude").join("claude.md.tera");
+    std::fs::create_dir_all(custom_path.parent().expect("parent")).expect("mkdir");
+    std::fs::write(&custom_path, "# Custom\n").expect("write custom template");
+
+    let engine = TemplateEngine::new(Some(dir.path())).expect("engine");
+    let origins = engine.origins();
+
+    assert_eq!(
+        origins.get("claude/claude.md.tera"),
+        Some(&TemplateOrigin::Override(custom_path))
+    );
+    assert_eq!(
+        origins.get("codex/agents.md.tera"),
+        Some(&TemplateOrigin::Embedded)
+    );
+}
+
+#[test]
+fn template_check_passes_embedded_templates() {
+    let checks = check_templates(None, &TemplateContext::example()).expect("check");
+
+    assert!(!checks.is_empty());
+    let failures: Vec<_> = checks.iter().filter(|check| !check.passed()).collect();
+    assert!(failures.is_empty(), "embedded templates failed: {failures:?}");
+}
+
+#[test]
+fn template_check_reports_each_broken_override() {
+    let dir = TempDir::new().expect("tempdir");
+    let unparsable = dir.path().join("claude").join("claude.md.tera");
+    std::fs::create_dir_all(unparsable.parent().expect("parent")).expect("mkdir");
+    std::fs::write(&unparsable, "Hello {{ codebase_name\n").expect("write");
+    let unrenderable = dir.path().join("zed").join("rules.tera");
+    std::fs::create_dir_all(unrenderable.parent().expect("parent")).expect("mkdir");
+    std::fs::write(&unrenderable, "{{ missing.field }}\n").expect("write");
+
+    let checks = check_templates(Some(dir.path()), &TemplateContext::example()).expect("check");
+    let failures: Vec<_> = checks.iter().filter(|check| !check.passed()).collect();
+
+    assert_eq!(failures.len(), 2, "unexpected failures: {failures:?}");
+    assert_eq!(failures[0].name, "claude/claude.md.tera");
+    assert_eq!(failures[0].origin, TemplateOrigin::Override(unparsable));
+    assert!(failures[0].error.as_deref().unwrap().contains("-->"), "no location reported");
+    assert_eq!(failures[1].name, "zed/rules.tera");
+    assert!(failures[1].error.as_deref().unwrap().contains("missing.field"));
+}
+
 #[test]
 fn meta_last_synced_is_stable_without_sync() {
     let codebase = make_codebase();
-    let mut ctx = TemplateContext::from_codebase(&codebase);
     let last_synced = chrono::DateTime::parse_from_rfc3339("2026-02-27T15:30:00Z")
         .unwrap()
         .with_timezone(&Utc);
-    ctx.meta.last_synced = Some(last_synced);
+    let ctx = TemplateContext::builder(&codebase)
+        .last_synced(Some(last_synced))
+        .build();
 
     let dir = TempDir::new().expect("tempdir");
     let template_path = dir.path().join("claude").join("claude.md.tera");
@@ -293,8 +609,9 @@ fn meta_last_synced_is_stable_without_sync() {
     let second = engine.render(&ctx, AgentKind::Claude).expect("render #2");
     assert_eq!(first[0].1, second[0].1);
 
-    let mut changed = ctx.clone();
-    changed.meta.last_synced = Some(last_synced + chrono::Duration::seconds(1));
+    let changed = TemplateContext::builder(&codebase)
+        .last_synced(Some(last_synced + chrono::Duration::seconds(1)))
+        .build();
     let third = engine.render(&changed, AgentKind::Claude).expect("render #3");
     assert_ne!(first[0].1, third[0].1);
 }
@@ -316,20 +633,23 @@ fn rendering_handles_many_string_shapes() {
                         title: (*title).to_string(),
                         status: "pending".to_string(),
                         description: None,
+                        assigned_agent: None,
                     }
                 }).collect();
 
-                let mut ctx = TemplateContext::from_codebase(&make_codebase());
-                ctx.tasks = tasks;
-                ctx.conventions = conventions.iter().map(|s| (*s).to_string()).collect();
-                ctx.skills = skills
-                    .iter()
-                    .map(|s| SkillCtx {
-                        id: (*s).to_string(),
-                        description: (*s).to_string(),
-                    })
-                    .collect();
-                ctx.active_task_count = titles.len();
+                let ctx = TemplateContext::builder(&make_codebase())
+                    .tasks(tasks)
+                    .conventions(conventions.iter().map(|s| (*s).to_string()).collect())
+                    .skills(
+                        skills
+                            .iter()
+                            .map(|s| SkillCtx {
+                                id: (*s).to_string(),
+                                description: (*s).to_string(),
+                            })
+                            .collect(),
+                    )
+                    .build();
 
                 let dir = TempDir::new().expect("tempdir");
                 let tpl = r#"
@@ -348,3 +668,43 @@ fn rendering_handles_many_string_shapes() {
         }
     }
 }
+
+#[test]
+fn infra_project_renders_infra_guidance() {
+    let mut codebase = make_codebase();
+    codebase.projects[0].project_type = ProjectType::Infra;
+    codebase.projects[0].framework = Some("Terraform".to_string());
+    let ctx = TemplateContext::from_codebase(&codebase);
+    assert_eq!(ctx.stack.primary_type.as_deref(), Some("infra"));
+    let engine = TemplateEngine::new(None).expect("engine");
+
+    let outputs = engine.render(&ctx, AgentKind::Claude).expect("render");
+    let claude = &outputs[0].1;
+    assert!(claude.contains("## Infrastructure Guidance"));
+    assert!(claude.contains("Primary tool: Terraform"));
+    assert!(!claude.contains("## Backend Guidance"));
+}
+
+#[test]
+fn ml_project_renders_ml_guidance_only() {
+    let mut codebase = make_codebase();
+    codebase.projects[0].project_type = ProjectType::Ml;
+    codebase.projects[0].framework = Some("PyTorch".to_string());
+    let ctx = TemplateContext::from_codebase(&codebase);
+    assert_eq!(ctx.stack.primary_type.as_deref(), Some("ml"));
+    let engine = TemplateEngine::new(None).expect("engine");
+
+    for agent in AgentKind::all() {
+        let outputs = engine.render(&ctx, *agent).expect("render");
+        let combined: String = outputs.iter().map(|(_, content)| content.as_str()).collect();
+        assert!(combined.contains("## ML Guidance"), "ML block missing for {agent:?}");
+        assert!(
+            combined.contains("Primary framework: PyTorch"),
+            "framework line missing for {agent:?}"
+        );
+        assert!(
+            !combined.contains("## Backend Guidance"),
+            "backend block rendered for ML codebase ({agent:?})"
+        );
+    }
+}
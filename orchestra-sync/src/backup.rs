@@ -144,10 +144,11 @@ pub fn restore_from_backup(codebase_root: &Path) -> Result<usize, SyncError> {
             if fs::copy(&backup_file, &target_file).is_ok() {
                 restored_count += 1;
             }
-        } else if backup_file.exists() && backup_file.is_dir() {
-            if copy_dir_recursive(&backup_file, &target_file).is_ok() {
-                restored_count += 1;
-            }
+        } else if backup_file.exists()
+            && backup_file.is_dir()
+            && copy_dir_recursive(&backup_file, &target_file).is_ok()
+        {
+            restored_count += 1;
         }
     }
 
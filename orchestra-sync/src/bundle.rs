@@ -0,0 +1,270 @@
+//! Registry bundles — move a registry between machines.
+//!
+//! [`export_at`] gathers every registered codebase (and optionally its hash
+//! store) into a single [`RegistryBundle`], serialized as one YAML document.
+//! [`import_at`] writes a bundle into another home through
+//! `registry::save_codebase_at`, so files keep their `0600` permissions.
+//! Home directories usually differ between machines, so imports can rewrite
+//! path prefixes with [`PathRemap`].
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use orchestra_core::{
+    registry,
+    types::{Codebase, ProjectName},
+};
+
+use crate::error::SyncError;
+use crate::hash_store::{self, HashStoreFile};
+use crate::lockfile;
+
+/// Bundle format written by this build.
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Every registered codebase, grouped with its project and hash store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistryBundle {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub codebases: Vec<BundledCodebase>,
+}
+
+/// One codebase inside a [`RegistryBundle`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundledCodebase {
+    pub project: ProjectName,
+    pub codebase: Codebase,
+    /// Hash store contents, present when exported with hashes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hashes: Option<HashStoreFile>,
+}
+
+impl RegistryBundle {
+    pub fn to_yaml(&self) -> Result<String, SyncError> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    /// Parse a bundle, rejecting formats newer than [`BUNDLE_FORMAT_VERSION`].
+    pub fn from_yaml(yaml: &str) -> Result<Self, SyncError> {
+        let bundle: Self = serde_yaml::from_str(yaml)?;
+        if bundle.format_version > BUNDLE_FORMAT_VERSION {
+            return Err(SyncError::UnsupportedBundle {
+                found: bundle.format_version,
+                supported: BUNDLE_FORMAT_VERSION,
+            });
+        }
+        Ok(bundle)
+    }
+}
+
+/// A `from=to` path prefix rewrite applied on import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathRemap {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+impl FromStr for PathRemap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((from, to)) if !from.is_empty() && !to.is_empty() => Ok(Self {
+                from: PathBuf::from(from),
+                to: PathBuf::from(to),
+            }),
+            _ => Err(format!("invalid path remap '{s}'; expected OLD=NEW")),
+        }
+    }
+}
+
+/// Rewrite `path` with the first remap whose `from` is a prefix of it
+/// (compared by whole path components).
+pub fn remap_path(path: &Path, remaps: &[PathRemap]) -> PathBuf {
+    remaps
+        .iter()
+        .find_map(|remap| {
+            path.strip_prefix(&remap.from)
+                .ok()
+                .map(|rest| remap.to.join(rest))
+        })
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// How [`import_at`] treats codebases that are already registered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImportMode {
+    /// Refuse the whole import if any codebase already exists.
+    #[default]
+    Strict,
+    /// Keep whichever copy has the newer `updated_at`.
+    Merge,
+    /// Overwrite existing codebases with the bundle's copy.
+    Replace,
+}
+
+/// What [`import_at`] did with each bundled codebase.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Codebases that were not registered before.
+    pub added: Vec<String>,
+    /// Existing codebases overwritten by the bundle.
+    pub replaced: Vec<String>,
+    /// Existing codebases kept because the local copy is newer (merge mode).
+    pub kept: Vec<String>,
+}
+
+/// Bundle every registered codebase under `home`.
+///
+/// With `include_hashes`, each codebase's hash store is included when one
+/// exists, so the target machine does not treat every file as stale.
+pub fn export_at(home: &Path, include_hashes: bool) -> Result<RegistryBundle, SyncError> {
+    let mut codebases = Vec::new();
+    for (project, codebase) in registry::list_codebases_at(home)? {
+        let hashes = if include_hashes
+            && hash_store::store_path_at(home, &codebase.name.0).exists()
+        {
+            Some(hash_store::load_at(home, &codebase.name.0)?)
+        } else {
+            None
+        };
+        codebases.push(BundledCodebase {
+            project,
+            codebase,
+            hashes,
+        });
+    }
+    Ok(RegistryBundle {
+        format_version: BUNDLE_FORMAT_VERSION,
+        exported_at: Utc::now(),
+        codebases,
+    })
+}
+
+/// Write `bundle` into the registry under `home`.
+///
+/// `remaps` rewrite codebase paths, output roots and hash-store keys. In
+/// [`ImportMode::Strict`] nothing is written if any bundled codebase name is
+/// already registered.
+pub fn import_at(
+    home: &Path,
+    bundle: RegistryBundle,
+    remaps: &[PathRemap],
+    mode: ImportMode,
+) -> Result<ImportReport, SyncError> {
+    let existing = registry::list_codebases_at(home)?;
+    let find_existing = |name: &str| {
+        existing
+            .iter()
+            .find(|(_, codebase)| codebase.name.0 == name)
+    };
+
+    if mode == ImportMode::Strict {
+        let conflicts: Vec<String> = bundle
+            .codebases
+            .iter()
+            .filter(|entry| find_existing(&entry.codebase.name.0).is_some())
+            .map(|entry| entry.codebase.name.0.clone())
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(SyncError::ImportConflict { names: conflicts });
+        }
+    }
+
+    let mut report = ImportReport::default();
+    for entry in bundle.codebases {
+        let BundledCodebase {
+            project,
+            mut codebase,
+            hashes,
+        } = entry;
+        let name = codebase.name.0.clone();
+
+        match find_existing(&name) {
+            None => report.added.push(name.clone()),
+            Some((_, local))
+                if mode == ImportMode::Merge && local.updated_at >= codebase.updated_at =>
+            {
+                report.kept.push(name);
+                continue;
+            }
+            Some((local_project, _)) => {
+                if *local_project != project {
+                    registry::remove_codebase_at(home, local_project, &codebase.name)?;
+                }
+                report.replaced.push(name.clone());
+            }
+        }
+
+        codebase.path = remap_path(&codebase.path, remaps);
+        codebase.output_root = codebase
+            .output_root
+            .map(|root| remap_path(&root, remaps));
+        registry::save_codebase_at(home, &project, &codebase)?;
+
+        if let Some(mut store) = hashes {
+            // Sizes and mtimes describe the exporting machine's files.
+            store.files_meta.clear();
+            store.files = store
+                .files
+                .into_iter()
+                .map(|(key, digest)| {
+                    let key = remap_path(Path::new(&key), remaps)
+                        .to_string_lossy()
+                        .into_owned();
+                    (key, digest)
+                })
+                .collect();
+            let _lock = lockfile::lock_exclusive_at(home, &name)?;
+            hash_store::save_at(home, &name, &store)?;
+        }
+    }
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remap(s: &str) -> PathRemap {
+        s.parse().expect("remap")
+    }
+
+    #[test]
+    fn remap_parses_old_equals_new() {
+        let parsed = remap("/Users/ana=/home/ana");
+        assert_eq!(parsed.from, PathBuf::from("/Users/ana"));
+        assert_eq!(parsed.to, PathBuf::from("/home/ana"));
+        assert!("/Users/ana".parse::<PathRemap>().is_err());
+        assert!("=/home/ana".parse::<PathRemap>().is_err());
+    }
+
+    #[test]
+    fn remap_matches_whole_components_only() {
+        let remaps = [remap("/Users/ana=/home/ana")];
+        assert_eq!(
+            remap_path(Path::new("/Users/ana/code/api"), &remaps),
+            PathBuf::from("/home/ana/code/api")
+        );
+        assert_eq!(
+            remap_path(Path::new("/Users/anabel/code"), &remaps),
+            PathBuf::from("/Users/anabel/code"),
+            "a string prefix that splits a component must not match"
+        );
+    }
+
+    #[test]
+    fn newer_bundle_format_is_rejected() {
+        let yaml = "format_version: 99\nexported_at: 2026-01-01T00:00:00Z\ncodebases: []\n";
+        let err = RegistryBundle::from_yaml(yaml).unwrap_err();
+        assert!(matches!(err, SyncError::UnsupportedBundle { found: 99, .. }), "got: {err}");
+    }
+}
@@ -0,0 +1,97 @@
+//! Drift between a codebase and the stack recorded when it was detected.
+//!
+//! `orchestra init --detect` and `orchestra detect --save` store the root
+//! indicator files they saw (`Cargo.toml`, `package.json`, …) and a digest
+//! of their contents in [`StackDetection`]. Once the digest no longer
+//! matches, the stored project type and framework may be outdated.
+
+use std::io::ErrorKind;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use orchestra_core::types::{Codebase, StackDetection};
+
+/// SHA-256 over the name and content of each of `indicators` under `root`.
+/// A file that is missing or unreadable hashes as a distinct marker, so
+/// deleting an indicator changes the digest too.
+pub fn indicator_digest(root: &Path, indicators: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    for name in indicators {
+        hasher.update((name.len() as u64).to_le_bytes());
+        hasher.update(name.as_bytes());
+        match std::fs::read(root.join(name)) {
+            Ok(bytes) => {
+                hasher.update((bytes.len() as u64).to_le_bytes());
+                hasher.update(&bytes);
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                hasher.update(u64::MAX.to_le_bytes());
+            }
+            Err(_) => hasher.update((u64::MAX - 1).to_le_bytes()),
+        }
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Whether `detection`'s indicator files under `root` have changed since it
+/// was recorded. Detections without a digest are never outdated.
+pub fn detection_outdated(root: &Path, detection: &StackDetection) -> bool {
+    detection
+        .indicators_sha256
+        .as_deref()
+        .is_some_and(|recorded| recorded != indicator_digest(root, &detection.indicators))
+}
+
+/// Whether any project of `codebase` carries an outdated detection.
+pub fn codebase_detection_outdated(codebase: &Codebase) -> bool {
+    codebase
+        .projects
+        .iter()
+        .filter_map(|project| project.detection.as_ref())
+        .any(|detection| detection_outdated(&codebase.path, detection))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn detection(root: &Path, indicators: &[&str]) -> StackDetection {
+        let indicators: Vec<String> = indicators.iter().map(|name| name.to_string()).collect();
+        StackDetection {
+            confidence: "high".to_string(),
+            overridden: false,
+            detected_at: None,
+            indicators_sha256: Some(indicator_digest(root, &indicators)),
+            indicators,
+            language: None,
+            package_manager: None,
+        }
+    }
+
+    #[test]
+    fn changed_or_deleted_indicator_marks_detection_outdated() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\n").unwrap();
+        std::fs::write(dir.path().join("main.tf"), "").unwrap();
+        let recorded = detection(dir.path(), &["Cargo.toml", "main.tf"]);
+        assert!(!detection_outdated(dir.path(), &recorded));
+
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"x\"\n").unwrap();
+        assert!(detection_outdated(dir.path(), &recorded));
+
+        let recorded = detection(dir.path(), &["Cargo.toml", "main.tf"]);
+        std::fs::remove_file(dir.path().join("main.tf")).unwrap();
+        assert!(detection_outdated(dir.path(), &recorded));
+    }
+
+    #[test]
+    fn detection_without_digest_is_never_outdated() {
+        let dir = TempDir::new().unwrap();
+        let mut recorded = detection(dir.path(), &["Cargo.toml"]);
+        recorded.indicators_sha256 = None;
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\n").unwrap();
+        assert!(!detection_outdated(dir.path(), &recorded));
+    }
+}
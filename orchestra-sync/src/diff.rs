@@ -3,22 +3,44 @@
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 
-use similar::TextDiff;
+use chrono::Utc;
+use similar::{ChangeTag, TextDiff};
 
-use orchestra_renderer::{AgentKind, Renderer};
+use orchestra_core::{
+    global, registry,
+    types::{Codebase, ProjectName},
+};
+
+use orchestra_renderer::{
+    render_within_budget, skills, user_template_dir_at, AgentKind, BudgetOverrun, Renderer,
+};
 
 use crate::{
     error::io_err,
-    hash_store,
-    writer::{build_sync_context, find_codebase_at},
+    hash_store, ignore, layout, lockfile, manifest, render_cache,
+    writer::{
+        build_sync_context, content_digest, find_codebase_at, output_digest, render_extra_outputs,
+        resolve_output, validate_extra_outputs,
+    },
     SyncError,
 };
 
+/// Lines of unchanged context around each hunk unless a caller asks for
+/// another amount.
+pub const DEFAULT_CONTEXT_LINES: usize = 3;
+
 /// A single rendered file diff.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FileDiff {
     pub path: PathBuf,
+    /// `path` relative to the codebase (or its output root), as named in
+    /// the diff headers.
+    pub relative_path: PathBuf,
     pub unified_diff: String,
+    /// Lines sync would add to the file.
+    pub added: usize,
+    /// Lines sync would remove from the file.
+    pub removed: usize,
 }
 
 /// Diff result for a codebase.
@@ -26,62 +48,268 @@ pub struct FileDiff {
 pub struct DiffCodebaseResult {
     pub codebase_name: String,
     pub diffs: Vec<FileDiff>,
+    /// Agents whose files would be over their token budget. Empty when
+    /// nothing was rendered because nothing changed since the last sync.
+    pub budget_overruns: Vec<BudgetOverrun>,
+}
+
+impl DiffCodebaseResult {
+    /// Lines added across all files.
+    pub fn added(&self) -> usize {
+        self.diffs.iter().map(|diff| diff.added).sum()
+    }
+
+    /// Lines removed across all files.
+    pub fn removed(&self) -> usize {
+        self.diffs.iter().map(|diff| diff.removed).sum()
+    }
+}
+
+/// [`diff_codebase`] for every registered codebase, or only those in
+/// `project` when given, in registry order.
+pub fn diff_all(home: &Path, project: Option<&str>) -> Result<Vec<DiffCodebaseResult>, SyncError> {
+    diff_all_with_context(home, project, DEFAULT_CONTEXT_LINES)
+}
+
+/// [`diff_all`] with `context` lines of unchanged text around each hunk.
+pub fn diff_all_with_context(
+    home: &Path,
+    project: Option<&str>,
+    context: usize,
+) -> Result<Vec<DiffCodebaseResult>, SyncError> {
+    let mut results = Vec::new();
+    for (project_name, codebase) in registry::list_codebases_at(home)? {
+        if project.is_some_and(|project| project_name.0 != project) {
+            continue;
+        }
+        results.push(diff_codebase_with_context(&codebase.name.0, home, context)?);
+    }
+    Ok(results)
 }
 
 /// Render what `sync` would generate and compare it to current on-disk content.
 ///
-/// No files are written.
+/// No files are written. When nothing has changed since the last full sync
+/// this returns no diffs without rendering; see [`render_cache`].
 pub fn diff_codebase(codebase_name: &str, home: &Path) -> Result<DiffCodebaseResult, SyncError> {
-    let (_project, codebase) = find_codebase_at(home, codebase_name)?;
-    let renderer = Renderer::new()?;
+    diff_codebase_with_context(codebase_name, home, DEFAULT_CONTEXT_LINES)
+}
+
+/// [`diff_codebase`] with `context` lines of unchanged text around each hunk.
+pub fn diff_codebase_with_context(
+    codebase_name: &str,
+    home: &Path,
+    context: usize,
+) -> Result<DiffCodebaseResult, SyncError> {
+    let (project, codebase) = find_codebase_at(home, codebase_name)?;
+    layout::check_codebase_root(&codebase)?;
+    validate_extra_outputs(&codebase)?;
+    let renderer = Renderer::cached(Some(&user_template_dir_at(home)))?;
+    diff_with(home, &renderer, &project, &codebase, context)
+}
 
+fn diff_with(
+    home: &Path,
+    renderer: &Renderer,
+    project: &ProjectName,
+    codebase: &Codebase,
+    context: usize,
+) -> Result<DiffCodebaseResult, SyncError> {
+    let codebase_name = codebase.name.0.as_str();
+    let _lock = lockfile::lock_shared_at(home, codebase_name)?;
     let store_path = hash_store::store_path_at(home, codebase_name);
     let store_existed = store_path.exists();
     let store = hash_store::load_at(home, codebase_name)?;
-    let mut ctx = build_sync_context(&codebase, false, store_existed, store.synced_at);
-    ctx.meta.last_synced = None;
+    if render_cache::outputs_current(home, renderer, project, codebase, &store)? {
+        return Ok(DiffCodebaseResult {
+            codebase_name: codebase_name.to_string(),
+            diffs: Vec::new(),
+            budget_overruns: store.budget_overruns,
+        });
+    }
+    // Rendered like a dry run, which leaves `meta.last_synced` unset.
+    let mut ctx = build_sync_context(project, codebase, true, store_existed, store.synced_at);
+    ctx.apply_global(&global::load_at(home)?);
+    ctx.apply_skill_catalog(&skills::load_catalog(home)?);
 
     let mut diffs = Vec::new();
+    // Digests of every file sync would write, for the manifest.
+    let mut digests = Vec::new();
+    let mut changed = false;
+    let enforce_budget = codebase.enforce_budget == Some(true);
+    let mut budget_overruns = Vec::new();
     for agent in AgentKind::all() {
-        let outputs = renderer.render_with_context(&ctx, *agent)?;
-        for (path, rendered) in outputs {
-            let rendered = normalize_line_endings(&rendered);
-            let existing = read_existing_or_empty(&path)?;
-            if existing == rendered {
+        let render = render_within_budget(renderer, &ctx, *agent, enforce_budget)?;
+        budget_overruns.extend(render.overrun);
+        let outputs = render.outputs;
+        let targets = layout::agent_output_paths(codebase, *agent);
+        for (((_, rendered), path), strategy) in outputs
+            .into_iter()
+            .zip(targets)
+            .zip(agent.output_strategies())
+        {
+            // Sync never touches user-managed files, so there is nothing to show.
+            if ignore::is_ignored(&path) {
                 continue;
             }
+            let rendered = normalize_line_endings(&resolve_output(&path, &rendered, strategy));
+            digests.push((path.clone(), output_digest(&rendered, strategy)));
+            let existing = read_existing_or_empty(codebase, &path)?;
+            if let Some(diff) = diff_existing(path, &existing, &rendered, codebase, context) {
+                changed = true;
+                diffs.push(diff);
+            }
+        }
+    }
+
+    for (path, rendered) in render_extra_outputs(renderer, &ctx, codebase)? {
+        if ignore::is_ignored(&path) {
+            continue;
+        }
+        let rendered = normalize_line_endings(&rendered);
+        digests.push((path.clone(), content_digest(&rendered)));
+        let existing = read_existing_or_empty(codebase, &path)?;
+        if let Some(diff) = diff_existing(path, &existing, &rendered, codebase, context) {
+            changed = true;
+            diffs.push(diff);
+        }
+    }
+
+    if let Some(manifest_path) = layout::managed_manifest_path(codebase) {
+        if !ignore::is_ignored(&manifest_path) {
+            // The guide and pilot are not diffed, but the manifest lists them.
+            let shared = [
+                (layout::managed_guide_path(codebase), renderer.render_guide(&ctx)?.1),
+                (layout::managed_pilot_path(codebase), renderer.render_pilot(&ctx)?.1),
+            ];
+            for (path, rendered) in shared {
+                if ignore::is_ignored(&path) {
+                    continue;
+                }
+                let rendered = normalize_line_endings(&rendered);
+                changed |= !read_existing_or_empty(codebase, &path)?.matches(&rendered);
+                digests.push((path, content_digest(&rendered)));
+            }
 
-            let relative = path.strip_prefix(&codebase.path).unwrap_or(path.as_path());
-            let old_header = format!("a/{}", relative.display());
-            let new_header = format!("b/{}", relative.display());
-            let unified = TextDiff::from_lines(&existing, &rendered)
-                .unified_diff()
-                .header(&old_header, &new_header)
-                .context_radius(3)
-                .to_string();
-
-            diffs.push(FileDiff {
-                path,
-                unified_diff: unified,
-            });
+            let rendered =
+                manifest::render(codebase, &manifest_path, &digests, changed, Utc::now())?;
+            let existing = read_existing_or_empty(codebase, &manifest_path)?;
+            diffs.extend(diff_existing(
+                manifest_path,
+                &existing,
+                &rendered,
+                codebase,
+                context,
+            ));
         }
     }
 
     Ok(DiffCodebaseResult {
         codebase_name: codebase_name.to_string(),
         diffs,
+        budget_overruns,
     })
 }
 
-fn read_existing_or_empty(path: &Path) -> Result<String, SyncError> {
-    match std::fs::read_to_string(path) {
-        Ok(content) => Ok(normalize_line_endings(&content)),
-        Err(err) if err.kind() == ErrorKind::NotFound => Ok(String::new()),
+/// What is on disk where sync would write.
+pub(crate) enum Existing {
+    /// UTF-8 content with line endings normalised; empty if there is no file.
+    Text(String),
+    /// Content that is not UTF-8, which is not diffed line by line.
+    NonText { bytes: usize },
+}
+
+impl Existing {
+    fn matches(&self, rendered: &str) -> bool {
+        matches!(self, Existing::Text(text) if text == rendered)
+    }
+}
+
+/// The diff from `existing` to `rendered`, with `context` lines around each
+/// hunk, or `None` when they match.
+pub(crate) fn diff_existing(
+    path: PathBuf,
+    existing: &Existing,
+    rendered: &str,
+    codebase: &Codebase,
+    context: usize,
+) -> Option<FileDiff> {
+    match existing {
+        Existing::Text(text) if text == rendered => None,
+        Existing::Text(text) => Some(file_diff(path, text, rendered, codebase, context)),
+        Existing::NonText { bytes } => Some(non_text_diff(path, *bytes, rendered, codebase)),
+    }
+}
+
+/// Placeholder diff for a file whose current content is not text.
+fn non_text_diff(path: PathBuf, bytes: usize, rendered: &str, codebase: &Codebase) -> FileDiff {
+    let relative = layout::display_relative(&path, codebase);
+    FileDiff {
+        unified_diff: format!(
+            "--- a/{0}\n+++ b/{0}\n(binary or non-text file differs, {bytes} bytes)\n",
+            relative.display()
+        ),
+        path,
+        relative_path: relative,
+        added: rendered.lines().count(),
+        removed: 0,
+    }
+}
+
+/// Unified diff from `existing` to `rendered`, headed with the display path.
+fn file_diff(
+    path: PathBuf,
+    existing: &str,
+    rendered: &str,
+    codebase: &Codebase,
+    context: usize,
+) -> FileDiff {
+    let relative = layout::display_relative(&path, codebase);
+    let old_header = format!("a/{}", relative.display());
+    let new_header = format!("b/{}", relative.display());
+    let text_diff = TextDiff::from_lines(existing, rendered);
+    let unified = text_diff
+        .unified_diff()
+        .header(&old_header, &new_header)
+        .context_radius(context)
+        .to_string();
+    let (mut added, mut removed) = (0, 0);
+    for change in text_diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => added += 1,
+            ChangeTag::Delete => removed += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+
+    FileDiff {
+        path,
+        relative_path: relative,
+        unified_diff: unified,
+        added,
+        removed,
+    }
+}
+
+/// What is on disk at `path`, which must resolve inside the output root.
+pub(crate) fn read_existing_or_empty(
+    codebase: &Codebase,
+    path: &Path,
+) -> Result<Existing, SyncError> {
+    let path = &layout::contained_path(codebase, path)?;
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(match String::from_utf8(bytes) {
+            Ok(content) => Existing::Text(normalize_line_endings(&content)),
+            Err(err) => Existing::NonText {
+                bytes: err.as_bytes().len(),
+            },
+        }),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Existing::Text(String::new())),
         Err(err) => Err(io_err(path, err)),
     }
 }
 
-fn normalize_line_endings(content: &str) -> String {
+pub(crate) fn normalize_line_endings(content: &str) -> String {
     content.replace("\r\n", "\n")
 }
 
@@ -94,6 +322,7 @@ mod tests {
         registry,
         types::{ProjectName, ProjectType},
     };
+    use orchestra_renderer::engine::MANAGED_REGION_END;
     use tempfile::TempDir;
 
     use crate::{hash_store, sync_codebase};
@@ -113,7 +342,7 @@ mod tests {
             home.path(),
         )
         .expect("init");
-        sync_codebase("copnow_api", home.path(), false).expect("sync");
+        sync_codebase("copnow_api", home.path(), false, None).expect("sync");
 
         let diff = diff_codebase("copnow_api", home.path()).expect("diff");
         assert!(diff.diffs.is_empty(), "synced codebase should have no diff");
@@ -132,12 +361,14 @@ mod tests {
             home.path(),
         )
         .expect("init");
-        sync_codebase("copnow_api", home.path(), false).expect("sync");
+        sync_codebase("copnow_api", home.path(), false, None).expect("sync");
 
         let target = codebase_dir.join("orchestra/controls/CLAUDE.md");
-        let edited = format!(
-            "{}\nmanual tweak\n",
-            fs::read_to_string(&target).expect("read")
+        // Inside the managed region, so sync would overwrite it.
+        let edited = fs::read_to_string(&target).expect("read").replacen(
+            MANAGED_REGION_END,
+            &format!("manual tweak\n{MANAGED_REGION_END}"),
+            1,
         );
         fs::write(&target, edited).expect("write");
 
@@ -152,6 +383,42 @@ mod tests {
         assert!(claude_diff.unified_diff.contains("--- a/orchestra/controls/CLAUDE.md"));
         assert!(claude_diff.unified_diff.contains("+++ b/orchestra/controls/CLAUDE.md"));
         assert!(claude_diff.unified_diff.contains("@@"));
+
+        let body: Vec<&str> = claude_diff.unified_diff.lines().skip(2).collect();
+        let plus = body.iter().filter(|line| line.starts_with('+')).count();
+        let minus = body.iter().filter(|line| line.starts_with('-')).count();
+        assert_eq!((claude_diff.added, claude_diff.removed), (plus, minus));
+        assert!(claude_diff.removed >= 1, "edited lines not counted");
+    }
+
+    #[test]
+    fn non_utf8_existing_file_gets_a_placeholder_diff() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        let codebase_dir = workspace.path().join("copnow_api");
+        fs::create_dir_all(&codebase_dir).expect("mkdir");
+        registry::init_at(
+            codebase_dir.clone(),
+            ProjectName::from("copnow"),
+            Some(ProjectType::Backend),
+            home.path(),
+        )
+        .expect("init");
+        sync_codebase("copnow_api", home.path(), false, None).expect("sync");
+        let target = codebase_dir.join("orchestra/controls/CLAUDE.md");
+        fs::write(&target, b"\xff\xfe\x00\x01").expect("write binary");
+
+        let diff = diff_codebase("copnow_api", home.path()).expect("diff");
+        let claude_diff = diff
+            .diffs
+            .iter()
+            .find(|d| d.path == target)
+            .expect("CLAUDE diff");
+        assert!(claude_diff
+            .unified_diff
+            .contains("(binary or non-text file differs, 4 bytes)"));
+        assert!(claude_diff.added > 0);
+        assert_eq!(claude_diff.removed, 0);
     }
 
     #[test]
@@ -167,7 +434,7 @@ mod tests {
             home.path(),
         )
         .expect("init");
-        sync_codebase("copnow_api", home.path(), false).expect("sync");
+        sync_codebase("copnow_api", home.path(), false, None).expect("sync");
 
         let mut store = hash_store::load_at(home.path(), "copnow_api").expect("store");
         store.synced_at += ChronoDuration::hours(2);
@@ -179,4 +446,111 @@ mod tests {
             "last_synced metadata changes must not produce diff output"
         );
     }
+
+    fn init_copnow_api(home: &TempDir, workspace: &TempDir) -> PathBuf {
+        let codebase_dir = workspace.path().join("copnow_api");
+        fs::create_dir_all(&codebase_dir).expect("mkdir");
+        registry::init_at(
+            codebase_dir.clone(),
+            ProjectName::from("copnow"),
+            Some(ProjectType::Backend),
+            home.path(),
+        )
+        .expect("init");
+        codebase_dir
+    }
+
+    /// Diff with a renderer of its own and report how many renders it took.
+    fn counted_diff(home: &Path) -> (DiffCodebaseResult, u64) {
+        let (project, codebase) = find_codebase_at(home, "copnow_api").expect("find");
+        let renderer =
+            Renderer::with_user_templates(Some(&user_template_dir_at(home))).expect("renderer");
+        let diff =
+            diff_with(home, &renderer, &project, &codebase, DEFAULT_CONTEXT_LINES).expect("diff");
+        (diff, renderer.render_count())
+    }
+
+    fn forget_render_inputs(home: &Path) {
+        let mut store = hash_store::load_at(home, "copnow_api").expect("load store");
+        store.rendered_from = None;
+        hash_store::save_at(home, "copnow_api", &store).expect("save store");
+    }
+
+    #[test]
+    fn diff_after_clean_sync_renders_nothing_and_matches_full_render() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_copnow_api(&home, &workspace);
+        sync_codebase("copnow_api", home.path(), false, None).expect("sync");
+
+        let (cached, renders) = counted_diff(home.path());
+        assert_eq!(renders, 0, "nothing changed since the sync");
+
+        forget_render_inputs(home.path());
+        let (uncached, renders) = counted_diff(home.path());
+        assert!(renders > 0, "without recorded inputs diff renders");
+        assert_eq!(cached, uncached);
+    }
+
+    #[test]
+    fn single_agent_sync_records_no_render_inputs() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_copnow_api(&home, &workspace);
+        sync_codebase("copnow_api", home.path(), false, None).expect("sync");
+        let store = hash_store::load_at(home.path(), "copnow_api").expect("store");
+        assert!(store.rendered_from.is_some());
+
+        sync_codebase("copnow_api", home.path(), false, Some(AgentKind::Claude))
+            .expect("sync claude");
+        let store = hash_store::load_at(home.path(), "copnow_api").expect("store");
+        assert_eq!(store.rendered_from, None);
+        assert!(counted_diff(home.path()).1 > 0);
+    }
+
+    #[test]
+    fn registry_edit_forces_full_render() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_copnow_api(&home, &workspace);
+        sync_codebase("copnow_api", home.path(), false, None).expect("sync");
+
+        let (project, mut codebase) = find_codebase_at(home.path(), "copnow_api").expect("find");
+        codebase
+            .notes
+            .push("Run migrations before deploying.".to_string());
+        registry::save_codebase_at(home.path(), &project, &codebase).expect("save");
+
+        let (diff, renders) = counted_diff(home.path());
+        assert!(renders > 0);
+        // Notes render into the guide, which diff reports through the manifest.
+        assert!(
+            diff.diffs
+                .iter()
+                .any(|d| d.path.ends_with(".orchestra-manifest.json")),
+            "the changed guide shows up in the diff: {diff:?}"
+        );
+    }
+
+    #[test]
+    fn override_template_forces_full_render() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        let codebase_dir = init_copnow_api(&home, &workspace);
+        sync_codebase("copnow_api", home.path(), false, None).expect("sync");
+
+        let template = AgentKind::Cline.template_names()[0];
+        let override_path = user_template_dir_at(home.path()).join(template);
+        fs::create_dir_all(override_path.parent().unwrap()).expect("mkdir templates");
+        fs::write(&override_path, "custom cline rules\n").expect("write override");
+
+        let (diff, renders) = counted_diff(home.path());
+        assert!(renders > 0);
+        let cline = diff
+            .diffs
+            .iter()
+            .find(|d| d.path == codebase_dir.join("orchestra/controls/.clinerules/orchestra.md"))
+            .expect("cline diff");
+        assert!(cline.unified_diff.contains("+custom cline rules"));
+    }
 }
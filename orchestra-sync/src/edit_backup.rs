@@ -0,0 +1,266 @@
+//! Backups of local edits that a sync overwrote.
+//!
+//! When a managed file's content on disk no longer matches the digest in the
+//! hash store, someone edited it by hand. Before sync replaces it, the
+//! existing content is copied to
+//! `~/.orchestra/backups/<codebase>/<relative path>.<timestamp>`, where the
+//! relative path is the file's location under the output root. Only the
+//! [`MAX_BACKUPS_PER_FILE`] most recent copies of each file are kept.
+//!
+//! Set `backups: false` in the codebase registry file, or pass
+//! `orchestra sync --no-backup`, to turn this off.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use orchestra_core::{paths, types::Codebase};
+
+use crate::error::{io_err, SyncError};
+use crate::layout;
+use crate::manifest::slash_path;
+use crate::writer::find_codebase_at;
+
+/// Backups kept per managed file; older ones are pruned.
+pub const MAX_BACKUPS_PER_FILE: usize = 10;
+
+/// Suffix format appended to a backed-up file name. Sorts chronologically.
+const STAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+
+/// One saved copy of a managed file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditBackup {
+    /// Managed file, relative to the output root and `/`-separated.
+    pub file: String,
+    /// When the copy was taken.
+    pub taken_at: DateTime<Utc>,
+    /// Where the copy is stored.
+    pub path: PathBuf,
+}
+
+impl EditBackup {
+    /// The timestamp suffix identifying this copy, as accepted by
+    /// [`restore_at`].
+    pub fn stamp(&self) -> String {
+        self.taken_at.format(STAMP_FORMAT).to_string()
+    }
+}
+
+/// A backup written back over its managed file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoredBackup {
+    pub backup: EditBackup,
+    /// The managed file that now holds the backed-up content.
+    pub target: PathBuf,
+}
+
+/// Where sync saves backups for one codebase.
+pub(crate) struct BackupTarget<'a> {
+    pub(crate) home: &'a Path,
+    pub(crate) codebase: &'a Codebase,
+}
+
+impl BackupTarget<'_> {
+    /// Save `content`, the current contents of managed file `path`, and prune
+    /// older copies. Returns the backup's path.
+    pub(crate) fn save(&self, path: &Path, content: &str) -> Result<PathBuf, SyncError> {
+        save_at(self.home, self.codebase, path, content, Utc::now())
+    }
+}
+
+/// `<home>/.orchestra/backups/<codebase>/`
+pub fn backups_dir_at(home: &Path, codebase_name: &str) -> PathBuf {
+    paths::dirs_at(home)
+        .data_root
+        .join("backups")
+        .join(codebase_name)
+}
+
+fn save_at(
+    home: &Path,
+    codebase: &Codebase,
+    path: &Path,
+    content: &str,
+    now: DateTime<Utc>,
+) -> Result<PathBuf, SyncError> {
+    let relative = slash_path(&layout::display_relative(path, codebase));
+    let backup = backups_dir_at(home, &codebase.name.0).join(format!(
+        "{relative}.{}",
+        now.format(STAMP_FORMAT)
+    ));
+    if let Some(parent) = backup.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| io_err(parent, e))?;
+    }
+    std::fs::write(&backup, content).map_err(|e| io_err(&backup, e))?;
+    prune(home, &codebase.name.0, &relative)?;
+    Ok(backup)
+}
+
+/// Delete all but the newest [`MAX_BACKUPS_PER_FILE`] copies of `file`.
+fn prune(home: &Path, codebase_name: &str, file: &str) -> Result<(), SyncError> {
+    let copies: Vec<EditBackup> = list_at(home, codebase_name)?
+        .into_iter()
+        .filter(|backup| backup.file == file)
+        .collect();
+    let excess = copies.len().saturating_sub(MAX_BACKUPS_PER_FILE);
+    for stale in &copies[..excess] {
+        std::fs::remove_file(&stale.path).map_err(|e| io_err(&stale.path, e))?;
+    }
+    Ok(())
+}
+
+/// All backups for a codebase, sorted by file and then oldest first. A
+/// codebase without backups yields an empty list.
+pub fn list_at(home: &Path, codebase_name: &str) -> Result<Vec<EditBackup>, SyncError> {
+    let root = backups_dir_at(home, codebase_name);
+    let mut backups = Vec::new();
+    if root.is_dir() {
+        collect(&root, &root, &mut backups)?;
+    }
+    backups.sort_by(|a, b| a.file.cmp(&b.file).then(a.taken_at.cmp(&b.taken_at)));
+    Ok(backups)
+}
+
+fn collect(root: &Path, dir: &Path, backups: &mut Vec<EditBackup>) -> Result<(), SyncError> {
+    for entry in std::fs::read_dir(dir).map_err(|e| io_err(dir, e))? {
+        let path = entry.map_err(|e| io_err(dir, e))?.path();
+        if path.is_dir() {
+            collect(root, &path, backups)?;
+            continue;
+        }
+        let Some((file, stamp)) = path
+            .strip_prefix(root)
+            .ok()
+            .map(slash_path)
+            .and_then(|name| name.rsplit_once('.').map(|(f, s)| (f.to_string(), s.to_string())))
+        else {
+            continue;
+        };
+        let Ok(taken_at) = NaiveDateTime::parse_from_str(&stamp, STAMP_FORMAT) else {
+            continue;
+        };
+        backups.push(EditBackup {
+            file,
+            taken_at: taken_at.and_utc(),
+            path,
+        });
+    }
+    Ok(())
+}
+
+/// Write a backup of `file` back over the managed file.
+///
+/// `file` is the path relative to the output root or, when unambiguous, just
+/// the file name. Restores the newest copy unless `stamp` picks one from
+/// [`list_at`].
+pub fn restore_at(
+    home: &Path,
+    codebase_name: &str,
+    file: &str,
+    stamp: Option<&str>,
+) -> Result<RestoredBackup, SyncError> {
+    let (_, codebase) = find_codebase_at(home, codebase_name)?;
+    let file = file.replace('\\', "/");
+    let matching: Vec<EditBackup> = list_at(home, codebase_name)?
+        .into_iter()
+        .filter(|backup| backup.file == file || backup.file.ends_with(&format!("/{file}")))
+        .collect();
+
+    let mut files: Vec<&str> = matching.iter().map(|backup| backup.file.as_str()).collect();
+    files.dedup();
+    if files.len() > 1 {
+        return Err(SyncError::AmbiguousBackup {
+            file,
+            candidates: files.join(", "),
+        });
+    }
+
+    let backup = matching
+        .iter()
+        .rev()
+        .find(|backup| stamp.is_none_or(|stamp| backup.stamp() == stamp))
+        .cloned()
+        .ok_or_else(|| SyncError::BackupNotFound {
+            codebase: codebase_name.to_string(),
+            file: file.clone(),
+        })?;
+
+    let content = std::fs::read(&backup.path).map_err(|e| io_err(&backup.path, e))?;
+    let target = layout::output_root(&codebase).join(&backup.file);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| io_err(parent, e))?;
+    }
+    std::fs::write(&target, content).map_err(|e| io_err(&target, e))?;
+    Ok(RestoredBackup { backup, target })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use orchestra_core::{
+        registry,
+        types::{ProjectName, ProjectType},
+    };
+    use tempfile::TempDir;
+
+    fn registered(home: &TempDir, workspace: &TempDir) -> Codebase {
+        let path = workspace.path().join("copnow_api");
+        std::fs::create_dir_all(&path).unwrap();
+        registry::init_at(
+            path,
+            ProjectName::from("copnow"),
+            Some(ProjectType::Backend),
+            home.path(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn pruning_keeps_the_newest_copies() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase = registered(&home, &workspace);
+        let target = codebase.path.join("orchestra/controls/CLAUDE.md");
+        let start = Utc::now() - Duration::hours(1);
+
+        for i in 0..(MAX_BACKUPS_PER_FILE + 2) {
+            let at = start + Duration::seconds(i as i64);
+            save_at(home.path(), &codebase, &target, &format!("edit {i}\n"), at).unwrap();
+        }
+
+        let backups = list_at(home.path(), "copnow_api").unwrap();
+        assert_eq!(backups.len(), MAX_BACKUPS_PER_FILE);
+        assert!(backups
+            .iter()
+            .all(|backup| backup.file == "orchestra/controls/CLAUDE.md"));
+        let oldest = std::fs::read_to_string(&backups[0].path).unwrap();
+        assert_eq!(oldest, "edit 2\n");
+    }
+
+    #[test]
+    fn restore_picks_newest_or_named_copy() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase = registered(&home, &workspace);
+        let target = codebase.path.join("orchestra/controls/CLAUDE.md");
+        let first = Utc::now() - Duration::minutes(5);
+        save_at(home.path(), &codebase, &target, "first\n", first).unwrap();
+        save_at(home.path(), &codebase, &target, "second\n", Utc::now()).unwrap();
+
+        let restored = restore_at(home.path(), "copnow_api", "CLAUDE.md", None).unwrap();
+        assert_eq!(restored.target, target);
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "second\n");
+
+        let stamp = first.format(STAMP_FORMAT).to_string();
+        restore_at(home.path(), "copnow_api", "CLAUDE.md", Some(&stamp)).unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "first\n");
+
+        let missing = restore_at(home.path(), "copnow_api", "AGENTS.md", None).unwrap_err();
+        assert!(matches!(missing, SyncError::BackupNotFound { .. }));
+    }
+}
@@ -7,6 +7,8 @@ use thiserror::Error;
 use orchestra_core::error::RegistryError;
 use orchestra_renderer::RenderError;
 
+use crate::hooks::HookKind;
+
 /// All errors that can arise from sync operations.
 #[derive(Debug, Error)]
 pub enum SyncError {
@@ -26,9 +28,147 @@ pub enum SyncError {
         source: std::io::Error,
     },
 
+    /// Flushing a written file or its parent directory to disk failed.
+    #[error("fsync failed at {path}: {source}")]
+    Fsync {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
     /// JSON serialization/deserialization error (hash store).
     #[error("hash store JSON error: {0}")]
     Json(#[from] serde_json::Error),
+
+    /// YAML serialization/deserialization error (registry bundle).
+    #[error("registry bundle YAML error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    /// The registry bundle was written by a newer orchestra.
+    #[error(
+        "registry bundle uses format v{found}, but this orchestra reads up to v{supported}; upgrade orchestra to import it"
+    )]
+    UnsupportedBundle { found: u32, supported: u32 },
+
+    /// A strict import found codebases that are already registered.
+    #[error("codebases already registered: {}", names.join(", "))]
+    ImportConflict { names: Vec<String> },
+
+    /// Another sync held the codebase lock for longer than the lock timeout.
+    #[error("codebase '{codebase}' is locked by another sync; try again shortly")]
+    Locked { codebase: String },
+
+    /// No backup of the requested file exists.
+    #[error("no backup of '{file}' for codebase '{codebase}'")]
+    BackupNotFound { codebase: String, file: String },
+
+    /// A sync hook exited unsuccessfully.
+    #[error("{hook} hook failed ({status}){}", tail_suffix(stderr_tail))]
+    HookFailed {
+        hook: HookKind,
+        status: String,
+        stderr_tail: String,
+    },
+
+    /// An extra output's path is absolute or leaves the codebase root.
+    #[error("extra output '{}' for codebase '{codebase}' must be a relative path inside the codebase", output_path.display())]
+    InvalidExtraOutput {
+        codebase: String,
+        output_path: PathBuf,
+    },
+
+    /// A managed path resolves outside its codebase's output root, through
+    /// `..`, an absolute path or a symlinked directory.
+    #[error("'{}' resolves outside the codebase root {}; refusing to touch it", path.display(), root.display())]
+    PathEscape { root: PathBuf, path: PathBuf },
+
+    /// A codebase's path or output root is `/` or a home directory.
+    #[error("codebase '{codebase}' points at {}, which is the filesystem root or a home directory; register the project directory instead", path.display())]
+    UnsafeCodebaseRoot { codebase: String, path: PathBuf },
+
+    /// Rendering one of a codebase's extra outputs failed.
+    #[error("extra output '{}' (template '{template}') failed to render: {source}", output_path.display())]
+    ExtraOutput {
+        template: String,
+        output_path: PathBuf,
+        #[source]
+        source: RenderError,
+    },
+
+    /// A bare file name matched backups of several managed files.
+    #[error("'{file}' matches backups of several files ({candidates}); pass the full relative path")]
+    AmbiguousBackup { file: String, candidates: String },
+
+    /// No registered codebase has this name. `known` lists the ones that do
+    /// exist; the message suggests the closest of them.
+    #[error("codebase '{name}' is not registered{}", did_you_mean(name, known))]
+    CodebaseNotFound { name: String, known: Vec<String> },
+}
+
+fn tail_suffix(stderr_tail: &str) -> String {
+    if stderr_tail.is_empty() {
+        String::new()
+    } else {
+        format!(": {stderr_tail}")
+    }
+}
+
+/// `; did you mean 'payments'?` when some of `known` are a few edits away
+/// from `name`.
+fn did_you_mean(name: &str, known: &[String]) -> String {
+    let matches = closest_matches(name, known);
+    match matches.as_slice() {
+        [] => String::new(),
+        [only] => format!("; did you mean '{only}'?"),
+        several => format!(
+            "; did you mean one of {}?",
+            several
+                .iter()
+                .map(|name| format!("'{name}'"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Up to three of `known` within a third of `name`'s length in edits
+/// (at least one), closest first. Case is ignored.
+fn closest_matches<'a>(name: &str, known: &'a [String]) -> Vec<&'a str> {
+    let wanted = name.to_lowercase();
+    let max_distance = (wanted.chars().count() / 3).max(1);
+    let mut scored: Vec<(usize, &str)> = known
+        .iter()
+        .map(|candidate| {
+            (
+                levenshtein(&wanted, &candidate.to_lowercase()),
+                candidate.as_str(),
+            )
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    scored.sort();
+    scored.dedup_by_key(|(_, candidate)| *candidate);
+    scored
+        .into_iter()
+        .take(3)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Number of single-character insertions, deletions and substitutions
+/// that turn `a` into `b`.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
 }
 
 /// Convenience constructor for [`SyncError::Io`].
@@ -38,3 +178,60 @@ pub(crate) fn io_err(path: impl Into<PathBuf>, source: std::io::Error) -> SyncEr
         source,
     }
 }
+
+/// Convenience constructor for [`SyncError::Fsync`].
+pub(crate) fn fsync_err(path: impl Into<PathBuf>, source: std::io::Error) -> SyncError {
+    SyncError::Fsync {
+        path: path.into(),
+        source,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("payments", "payments"), 0);
+        assert_eq!(levenshtein("paymnets", "payments"), 2);
+        assert_eq!(levenshtein("api", "apis"), 1);
+        assert_eq!(levenshtein("", "web"), 3);
+    }
+
+    #[test]
+    fn typo_suggests_the_closest_codebase() {
+        let err = SyncError::CodebaseNotFound {
+            name: "paymnets".to_string(),
+            known: names(&["copnow_api", "payments", "web"]),
+        };
+        assert_eq!(
+            err.to_string(),
+            "codebase 'paymnets' is not registered; did you mean 'payments'?"
+        );
+    }
+
+    #[test]
+    fn several_near_matches_are_listed_closest_first() {
+        let known = names(&["api_v2", "apis", "api_v1", "web"]);
+        assert_eq!(closest_matches("API", &known), ["apis"]);
+        assert_eq!(closest_matches("api_v", &known), ["api_v1", "api_v2"]);
+    }
+
+    #[test]
+    fn unrelated_names_get_no_suggestion() {
+        let err = SyncError::CodebaseNotFound {
+            name: "billing".to_string(),
+            known: names(&["copnow_api", "web"]),
+        };
+        assert_eq!(err.to_string(), "codebase 'billing' is not registered");
+    }
+}
@@ -2,27 +2,100 @@
 //!
 //! Persists a `HashStoreFile` JSON document at
 //! `<home>/.orchestra/hashes/<codebase_name>.json`.
-//! Writes use the same atomic `.tmp` + rename pattern as the registry.
+//! Writes use the same fsynced `.tmp` + rename pattern as agent files, and
+//! are skipped when the file already holds the same store: each write costs
+//! an fsync, which is slow on network home directories.
+//!
+//! A store that no longer parses (say, truncated by a crash) is renamed to
+//! `<codebase_name>.json.corrupt-<timestamp>` on load and replaced by an empty
+//! store; the next sync rewrites every file and records fresh hashes.
+//! Read-only lookups such as `status`, `which` and the template upgrade plan
+//! use [`peek_at`], which treats it as empty and leaves it in place.
 
 use std::collections::HashMap;
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
 
 use chrono::{DateTime, Utc};
-use orchestra_core::registry;
+use orchestra_core::{paths, registry, types::Codebase};
 use orchestra_renderer::engine::{control_dir, guide_path, pilot_path, AgentKind};
+use orchestra_renderer::BudgetOverrun;
 use serde::{Deserialize, Serialize};
 
 use crate::error::{io_err, SyncError};
+use crate::writer::{file_digest, find_codebase_at, sync_parent_dir, write_synced_tmp};
+use crate::{ignore, layout};
+
+/// Timestamp appended to a corrupt store set aside by [`load_at`].
+const CORRUPT_STAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
 
 /// In-memory hash store: maps relative file path strings to their last
 /// synced SHA-256 hex digest.
 pub type HashStore = HashMap<String, String>;
 
+/// Size and modification time of a file as last written by sync, keyed like
+/// [`HashStore`]. Lets staleness checks skip hashing files that have not been
+/// touched since.
+pub type FileMetaStore = HashMap<String, FileMeta>;
+
 /// On-disk hash store payload.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct HashStoreFile {
     pub synced_at: DateTime<Utc>,
     pub files: HashStore,
+    /// Absent in stores written before metadata was recorded.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub files_meta: FileMetaStore,
+    /// What the last full sync rendered from; see [`crate::render_cache`].
+    /// Absent after a single-agent sync and in older stores.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rendered_from: Option<RenderInputs>,
+    /// Agents that were over their token budget when `rendered_from` was
+    /// recorded, reported by `diff` when it skips rendering.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub budget_overruns: Vec<BudgetOverrun>,
+    /// Template-set fingerprint of the last full sync or template upgrade;
+    /// see [`crate::upgrade`]. Absent in older stores.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub templates: Option<String>,
+}
+
+/// Fingerprints of everything a full sync renders from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RenderInputs {
+    /// [`Renderer::fingerprint`](orchestra_renderer::Renderer::fingerprint)
+    /// of the template set.
+    pub templates: String,
+    /// Digest of the codebase's registry file, the global context, the skills
+    /// catalog and the orchestra version.
+    pub registry: String,
+}
+
+/// File size and mtime (nanoseconds since the Unix epoch).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileMeta {
+    pub size: u64,
+    pub mtime_ns: u64,
+}
+
+impl FileMeta {
+    /// Read the current size and mtime of `path` without opening it.
+    pub fn read(path: &Path) -> std::io::Result<Self> {
+        Self::from_metadata(&std::fs::metadata(path)?)
+    }
+
+    pub fn from_metadata(metadata: &std::fs::Metadata) -> std::io::Result<Self> {
+        let mtime_ns = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|age| age.as_nanos() as u64)
+            .unwrap_or_default();
+        Ok(Self {
+            size: metadata.len(),
+            mtime_ns,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -36,37 +109,97 @@ enum HashStoreCompat {
 struct HashStoreStructuredCompat {
     pub synced_at: Option<DateTime<Utc>>,
     pub files: HashStore,
+    #[serde(default)]
+    pub files_meta: FileMetaStore,
+    #[serde(default)]
+    pub rendered_from: Option<RenderInputs>,
+    #[serde(default)]
+    pub budget_overruns: Vec<BudgetOverrun>,
+    #[serde(default)]
+    pub templates: Option<String>,
 }
 
 /// Path to the hash store JSON for a given codebase, rooted at `home`.
 ///
 /// `~/.orchestra/hashes/<codebase_name>.json`
 pub fn store_path_at(home: &Path, codebase_name: &str) -> PathBuf {
-    home.join(".orchestra")
-        .join("hashes")
+    paths::dirs_at(home)
+        .hashes_dir
         .join(format!("{codebase_name}.json"))
 }
 
+fn empty_store() -> HashStoreFile {
+    HashStoreFile {
+        synced_at: Utc::now(),
+        files: HashMap::new(),
+        files_meta: HashMap::new(),
+        rendered_from: None,
+        budget_overruns: Vec::new(),
+        templates: None,
+    }
+}
+
 /// Load the hash store for `codebase_name`.
 ///
-/// Returns an empty store if the file does not yet exist.
+/// Returns an empty store if the file does not yet exist. A file that does
+/// not parse is renamed to `<name>.json.corrupt-<timestamp>` and an empty
+/// store returned, so the next sync rebuilds it.
 pub fn load_at(home: &Path, codebase_name: &str) -> Result<HashStoreFile, SyncError> {
+    match read_at(home, codebase_name) {
+        Err(SyncError::Json(err)) => {
+            let path = store_path_at(home, codebase_name);
+            let kept = set_aside_corrupt(&path)?;
+            tracing::warn!(
+                "hash store {} does not parse ({err}); moved it to {} and starting from an empty store",
+                path.display(),
+                kept.display()
+            );
+            Ok(empty_store())
+        }
+        other => other,
+    }
+}
+
+/// Like [`load_at`], but a store that does not parse is left where it is:
+/// this logs a warning and returns an empty store. For read-only commands
+/// that the next sync's [`load_at`] can clean up after.
+pub fn peek_at(home: &Path, codebase_name: &str) -> Result<HashStoreFile, SyncError> {
+    match read_at(home, codebase_name) {
+        Err(SyncError::Json(err)) => {
+            tracing::warn!(
+                "hash store {} does not parse ({err}); treating it as empty",
+                store_path_at(home, codebase_name).display()
+            );
+            Ok(empty_store())
+        }
+        other => other,
+    }
+}
+
+/// Like [`load_at`], but a store that does not parse is an error and is left
+/// where it is. For diagnostics that must not change anything.
+pub fn read_at(home: &Path, codebase_name: &str) -> Result<HashStoreFile, SyncError> {
     let path = store_path_at(home, codebase_name);
     if !path.exists() {
-        return Ok(HashStoreFile {
-            synced_at: Utc::now(),
-            files: HashMap::new(),
-        });
+        return Ok(empty_store());
     }
-    let contents = std::fs::read_to_string(&path).map_err(|e| io_err(&path, e))?;
-    let mut store = match serde_json::from_str::<HashStoreCompat>(&contents)? {
+    let contents = std::fs::read(&path).map_err(|e| io_err(&path, e))?;
+    let mut store = match serde_json::from_slice::<HashStoreCompat>(&contents)? {
         HashStoreCompat::Structured(store) => HashStoreFile {
             synced_at: store.synced_at.unwrap_or_else(Utc::now),
             files: store.files,
+            files_meta: store.files_meta,
+            rendered_from: store.rendered_from,
+            budget_overruns: store.budget_overruns,
+            templates: store.templates,
         },
         HashStoreCompat::Legacy(files) => HashStoreFile {
             synced_at: Utc::now(),
             files,
+            files_meta: HashMap::new(),
+            rendered_from: None,
+            budget_overruns: Vec::new(),
+            templates: None,
         },
     };
 
@@ -75,11 +208,23 @@ pub fn load_at(home: &Path, codebase_name: &str) -> Result<HashStoreFile, SyncEr
         .and_then(|all| all.into_iter().find(|(_, cb)| cb.name.0 == codebase_name))
     {
         store.files = migrate_legacy_hash_keys(store.files, &codebase.path);
+        let files = &store.files;
+        store.files_meta.retain(|key, _| files.contains_key(key));
     }
 
     Ok(store)
 }
 
+/// Rename a corrupt store out of the way, keeping it for inspection.
+fn set_aside_corrupt(path: &Path) -> Result<PathBuf, SyncError> {
+    let stamp = Utc::now().format(CORRUPT_STAMP_FORMAT);
+    let mut kept = path.as_os_str().to_owned();
+    kept.push(format!(".corrupt-{stamp}"));
+    let kept = PathBuf::from(kept);
+    std::fs::rename(path, &kept).map_err(|e| io_err(path, e))?;
+    Ok(kept)
+}
+
 fn migrate_legacy_hash_keys(files: HashStore, codebase_root: &Path) -> HashStore {
     let legacy_paths = legacy_managed_paths(codebase_root);
     let mut migrated = HashMap::new();
@@ -150,9 +295,14 @@ fn legacy_managed_paths(codebase_root: &Path) -> Vec<PathBuf> {
 
 /// Save the hash store for `codebase_name` atomically.
 ///
-/// Writes to `<path>.tmp` then renames to `<path>`.
+/// Writes and fsyncs `<path>.tmp`, renames it to `<path>`, then fsyncs the
+/// hashes directory. Nothing is written when `<path>` already parses to
+/// `store`.
 pub fn save_at(home: &Path, codebase_name: &str, store: &HashStoreFile) -> Result<(), SyncError> {
     let path = store_path_at(home, codebase_name);
+    if on_disk_matches(&path, store) {
+        return Ok(());
+    }
     let Some(dir) = path.parent() else {
         return Err(io_err(
             path,
@@ -165,9 +315,66 @@ pub fn save_at(home: &Path, codebase_name: &str, store: &HashStoreFile) -> Resul
 
     let json = serde_json::to_string_pretty(store)?;
     let tmp = path.with_extension("json.tmp");
-    std::fs::write(&tmp, &json).map_err(|e| io_err(&tmp, e))?;
+    write_synced_tmp(&tmp, &json, &path)?;
     std::fs::rename(&tmp, &path).map_err(|e| io_err(&path, e))?;
-    Ok(())
+    sync_parent_dir(&path)
+}
+
+/// Whether the store at `path` equals `store`. Compared parsed rather than
+/// as text, since map order in the JSON differs between saves.
+fn on_disk_matches(path: &Path, store: &HashStoreFile) -> bool {
+    std::fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<HashStoreFile>(&bytes).ok())
+        .is_some_and(|on_disk| on_disk == *store)
+}
+
+/// A recorded hash that no longer matches the file on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashMismatch {
+    pub path: PathBuf,
+    pub expected: String,
+    /// `None` when the file no longer exists.
+    pub actual: Option<String>,
+}
+
+/// Re-hash every file recorded in `codebase_name`'s store and return those
+/// whose content no longer matches, sorted by path. User-managed files are
+/// skipped. Reads the store with [`read_at`], so a corrupt store is reported
+/// rather than set aside.
+pub fn verify_at(home: &Path, codebase_name: &str) -> Result<Vec<HashMismatch>, SyncError> {
+    let (_, codebase) = find_codebase_at(home, codebase_name)?;
+    let store = read_at(home, codebase_name)?;
+    mismatches(&codebase, &store)
+}
+
+/// The files in `store` whose content on disk no longer matches, sorted by
+/// path. User-managed files are skipped.
+pub(crate) fn mismatches(
+    codebase: &Codebase,
+    store: &HashStoreFile,
+) -> Result<Vec<HashMismatch>, SyncError> {
+    let mut mismatches = Vec::new();
+    for (key, expected) in &store.files {
+        let path = PathBuf::from(key);
+        if ignore::is_ignored(&path) {
+            continue;
+        }
+        let actual = match file_digest(&path, layout::output_strategy(codebase, &path)) {
+            Ok(digest) => Some(digest),
+            Err(err) if err.kind() == ErrorKind::NotFound => None,
+            Err(err) => return Err(io_err(&path, err)),
+        };
+        if actual.as_ref() != Some(expected) {
+            mismatches.push(HashMismatch {
+                path,
+                expected: expected.clone(),
+                actual,
+            });
+        }
+    }
+    mismatches.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(mismatches)
 }
 
 #[cfg(test)]
@@ -198,6 +405,10 @@ mod tests {
         let store = HashStoreFile {
             synced_at: Utc::now(),
             files,
+            files_meta: HashMap::new(),
+            rendered_from: None,
+            budget_overruns: Vec::new(),
+            templates: None,
         };
 
         save_at(tmp.path(), "myapp", &store).unwrap();
@@ -211,6 +422,10 @@ mod tests {
         let store = HashStoreFile {
             synced_at: Utc::now(),
             files: HashMap::new(),
+            files_meta: HashMap::new(),
+            rendered_from: None,
+            budget_overruns: Vec::new(),
+            templates: None,
         };
         save_at(tmp.path(), "clean_test", &store).unwrap();
         let tmp_path = store_path_at(tmp.path(), "clean_test").with_extension("json.tmp");
@@ -220,6 +435,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unchanged_store_is_not_rewritten() {
+        let tmp = TempDir::new().unwrap();
+        let mut files = HashMap::new();
+        for i in 0..8 {
+            files.insert(format!("file-{i}.md"), format!("digest-{i}"));
+        }
+        let mut store = HashStoreFile {
+            synced_at: Utc::now(),
+            files,
+            files_meta: HashMap::new(),
+            rendered_from: None,
+            budget_overruns: Vec::new(),
+            templates: None,
+        };
+        save_at(tmp.path(), "myapp", &store).unwrap();
+        let path = store_path_at(tmp.path(), "myapp");
+        let old = filetime::FileTime::from_unix_time(1_000_000_000, 0);
+        filetime::set_file_mtime(&path, old).unwrap();
+        let mtime = || filetime::FileTime::from_last_modification_time(&path.metadata().unwrap());
+
+        // A reloaded store has its map in another order but is still equal.
+        save_at(tmp.path(), "myapp", &load_at(tmp.path(), "myapp").unwrap()).unwrap();
+        assert_eq!(mtime(), old);
+
+        store.synced_at += chrono::Duration::seconds(1);
+        save_at(tmp.path(), "myapp", &store).unwrap();
+        assert_ne!(mtime(), old);
+        assert_eq!(load_at(tmp.path(), "myapp").unwrap(), store);
+    }
+
     #[test]
     fn load_legacy_flat_map_migrates_to_structured_store() {
         let tmp = TempDir::new().unwrap();
@@ -297,4 +543,101 @@ mod tests {
         assert_eq!(loaded.files.get(&migrated_key), Some(&"deadbeef".to_string()));
         assert!(!loaded.files.contains_key(&legacy_key));
     }
+
+    fn corrupt_copies(home: &Path, codebase_name: &str) -> Vec<PathBuf> {
+        let prefix = format!("{codebase_name}.json.corrupt-");
+        let dir = paths::dirs_at(home).hashes_dir;
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with(&prefix))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn truncated_store_is_set_aside_and_replaced_by_an_empty_one() {
+        let tmp = TempDir::new().unwrap();
+        let path = store_path_at(tmp.path(), "truncated");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let truncated = r#"{"synced_at":"2026-03-06T00:00:00Z","files":{"CLAUDE.md":"dead"#;
+        std::fs::write(&path, truncated).unwrap();
+
+        assert!(matches!(
+            read_at(tmp.path(), "truncated"),
+            Err(SyncError::Json(_))
+        ));
+        assert!(path.exists(), "read_at leaves a corrupt store in place");
+        assert!(peek_at(tmp.path(), "truncated").unwrap().files.is_empty());
+        assert!(path.exists(), "peek_at leaves a corrupt store in place");
+
+        let loaded = load_at(tmp.path(), "truncated").unwrap();
+        assert!(loaded.files.is_empty());
+        assert!(!path.exists(), "corrupt store is moved out of the way");
+        let kept = corrupt_copies(tmp.path(), "truncated");
+        assert_eq!(kept.len(), 1);
+        assert_eq!(std::fs::read_to_string(&kept[0]).unwrap(), truncated);
+
+        save_at(tmp.path(), "truncated", &loaded).unwrap();
+        assert!(load_at(tmp.path(), "truncated").is_ok());
+    }
+
+    #[test]
+    fn empty_store_file_is_recovered() {
+        let tmp = TempDir::new().unwrap();
+        let path = store_path_at(tmp.path(), "zero");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, b"").unwrap();
+
+        assert!(load_at(tmp.path(), "zero").unwrap().files.is_empty());
+        assert_eq!(corrupt_copies(tmp.path(), "zero").len(), 1);
+    }
+
+    #[test]
+    fn verify_flags_wrong_and_missing_hashes() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = workspace.path().join("atlas_api");
+        std::fs::create_dir_all(&codebase_dir).unwrap();
+        registry::init_at(
+            codebase_dir.clone(),
+            ProjectName::from("atlas"),
+            Some(ProjectType::Backend),
+            home.path(),
+        )
+        .unwrap();
+
+        let good = codebase_dir.join("good.md");
+        let bad = codebase_dir.join("bad.md");
+        let gone = codebase_dir.join("gone.md");
+        std::fs::write(&good, "good\n").unwrap();
+        std::fs::write(&bad, "bad\n").unwrap();
+        let key = |path: &Path| path.to_string_lossy().to_string();
+        let mut files = HashMap::new();
+        files.insert(key(&good), crate::writer::content_digest("good\n"));
+        files.insert(key(&bad), "0".repeat(64));
+        files.insert(key(&gone), crate::writer::content_digest("gone\n"));
+        let store = HashStoreFile {
+            synced_at: Utc::now(),
+            files,
+            files_meta: HashMap::new(),
+            rendered_from: None,
+            budget_overruns: Vec::new(),
+            templates: None,
+        };
+        save_at(home.path(), "atlas_api", &store).unwrap();
+
+        let mismatches = verify_at(home.path(), "atlas_api").unwrap();
+        assert_eq!(mismatches.len(), 2, "{mismatches:?}");
+        assert_eq!(mismatches[0].path, bad);
+        assert_eq!(mismatches[0].expected, "0".repeat(64));
+        assert_eq!(
+            mismatches[0].actual.as_deref(),
+            Some(crate::writer::content_digest("bad\n").as_str())
+        );
+        assert_eq!(mismatches[1].path, gone);
+        assert_eq!(mismatches[1].actual, None);
+    }
 }
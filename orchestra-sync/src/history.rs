@@ -0,0 +1,223 @@
+//! Sync history journal — one NDJSON line per non-dry-run sync.
+//!
+//! Persists entries at `<home>/.orchestra/logs/sync-history.jsonl`, rotated
+//! with the same size cap as the daemon logs. Journal writes are best-effort:
+//! a failure is logged as a warning and never fails the sync that produced it.
+
+use std::fmt;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use orchestra_core::paths;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{io_err, SyncError};
+use crate::log_rotation::{rotate_if_needed, MAX_LOG_BYTES, MAX_ROTATED_FILES};
+use crate::writer::{SyncCodebaseResult, WriteResult};
+
+/// What triggered a sync run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncSource {
+    /// An explicit `orchestra` CLI invocation.
+    Cli,
+    /// The background daemon (watcher or writeback), or a `sync` request
+    /// answered by `orchestra serve`.
+    Daemon,
+}
+
+impl fmt::Display for SyncSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cli => f.write_str("cli"),
+            Self::Daemon => f.write_str("daemon"),
+        }
+    }
+}
+
+/// A single journal line describing one completed sync run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Utc>,
+    pub codebase: String,
+    pub source: SyncSource,
+    pub written: usize,
+    /// Files left as they were, including user-managed (ignored) ones.
+    pub unchanged: usize,
+    /// Absolute paths of the files written during this run.
+    pub paths: Vec<PathBuf>,
+}
+
+impl HistoryEntry {
+    /// Summarise a sync result as a journal entry.
+    pub fn from_result(
+        result: &SyncCodebaseResult,
+        source: SyncSource,
+        timestamp: DateTime<Utc>,
+    ) -> Self {
+        let mut paths = Vec::new();
+        let mut unchanged = 0;
+        for write in &result.writes {
+            match write {
+                WriteResult::Written { path, .. } | WriteResult::WouldWrite { path } => {
+                    paths.push(path.clone())
+                }
+                WriteResult::Unchanged { .. } | WriteResult::Ignored { .. } => unchanged += 1,
+            }
+        }
+
+        Self {
+            timestamp,
+            codebase: result.codebase_name.clone(),
+            source,
+            written: paths.len(),
+            unchanged,
+            paths,
+        }
+    }
+}
+
+/// Path to the sync history journal, rooted at `home`.
+///
+/// `~/.orchestra/logs/sync-history.jsonl`
+pub fn history_path_at(home: &Path) -> PathBuf {
+    paths::dirs_at(home).logs_dir.join("sync-history.jsonl")
+}
+
+/// Append `entry` to the journal, rotating it first if it exceeds the cap.
+///
+/// Creates the log file (and parent directory) if absent.
+pub fn append_at(home: &Path, entry: &HistoryEntry) -> Result<(), SyncError> {
+    let path = history_path_at(home);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| io_err(parent, e))?;
+    }
+    rotate_if_needed(&path, MAX_LOG_BYTES, MAX_ROTATED_FILES).map_err(|e| io_err(&path, e))?;
+
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| io_err(&path, e))?;
+    file.write_all(line.as_bytes()).map_err(|e| io_err(&path, e))?;
+    Ok(())
+}
+
+/// Record a completed sync in the journal, logging (not returning) failures.
+pub(crate) fn record(home: &Path, result: &SyncCodebaseResult, source: SyncSource) {
+    let entry = HistoryEntry::from_result(result, source, Utc::now());
+    if let Err(err) = append_at(home, &entry) {
+        tracing::warn!(
+            "failed to append sync history for '{}': {err}",
+            result.codebase_name
+        );
+    }
+}
+
+/// Load journal entries, oldest first.
+///
+/// Filters by codebase name and by `since` (inclusive) when given. Lines
+/// that fail to parse are skipped. A missing journal yields an empty list.
+pub fn read_at(
+    home: &Path,
+    codebase: Option<&str>,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<HistoryEntry>, SyncError> {
+    let path = history_path_at(home);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = std::fs::read_to_string(&path).map_err(|e| io_err(&path, e))?;
+
+    let entries = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str::<HistoryEntry>(line) {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                tracing::debug!("skipping corrupt sync history line: {err}");
+                None
+            }
+        })
+        .filter(|entry| codebase.is_none_or(|name| entry.codebase == name))
+        .filter(|entry| since.is_none_or(|cutoff| entry.timestamp >= cutoff))
+        .collect();
+    Ok(entries)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    fn entry(codebase: &str, timestamp: DateTime<Utc>) -> HistoryEntry {
+        HistoryEntry {
+            timestamp,
+            codebase: codebase.to_owned(),
+            source: SyncSource::Cli,
+            written: 1,
+            unchanged: 0,
+            paths: vec![PathBuf::from(format!("/tmp/{codebase}/CLAUDE.md"))],
+        }
+    }
+
+    #[test]
+    fn read_missing_journal_is_empty() {
+        let home = TempDir::new().unwrap();
+        assert!(read_at(home.path(), None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn read_filters_by_codebase() {
+        let home = TempDir::new().unwrap();
+        let now = Utc::now();
+        append_at(home.path(), &entry("api", now)).unwrap();
+        append_at(home.path(), &entry("web", now)).unwrap();
+        append_at(home.path(), &entry("api", now)).unwrap();
+
+        let api = read_at(home.path(), Some("api"), None).unwrap();
+        assert_eq!(api.len(), 2);
+        assert!(api.iter().all(|e| e.codebase == "api"));
+    }
+
+    #[test]
+    fn read_filters_by_since() {
+        let home = TempDir::new().unwrap();
+        let now = Utc::now();
+        append_at(home.path(), &entry("api", now - Duration::days(10))).unwrap();
+        append_at(home.path(), &entry("api", now - Duration::days(1))).unwrap();
+
+        let recent = read_at(home.path(), Some("api"), Some(now - Duration::days(7))).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].timestamp, now - Duration::days(1));
+    }
+
+    #[test]
+    fn read_skips_corrupt_lines() {
+        let home = TempDir::new().unwrap();
+        append_at(home.path(), &entry("api", Utc::now())).unwrap();
+
+        let path = history_path_at(home.path());
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{not json\n\n{\"codebase\":\"api\"}\n").unwrap();
+        drop(file);
+        append_at(home.path(), &entry("api", Utc::now())).unwrap();
+
+        assert_eq!(read_at(home.path(), None, None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn entry_serializes_source_lowercase() {
+        let line = serde_json::to_string(&entry("api", Utc::now())).unwrap();
+        assert!(line.contains("\"source\":\"cli\""));
+    }
+}
@@ -0,0 +1,119 @@
+//! User-defined commands run before and after a codebase sync.
+//!
+//! Hooks come from the codebase's `hooks` registry entry and run through the
+//! platform shell with the codebase root as working directory. They see:
+//!
+//! - `ORCHESTRA_CODEBASE` — the codebase name;
+//! - `ORCHESTRA_WRITTEN_FILES` — paths written by this sync, separated by
+//!   newlines (empty for `pre_sync`). Paths inside the codebase are relative
+//!   to its root; files under an out-of-tree `output_root` stay absolute;
+//! - `ORCHESTRA_DRY_RUN` — `1` for a dry run, `0` otherwise.
+//!
+//! Syncs started by the daemon or by a `sync` request to `orchestra serve`
+//! skip hooks unless `allow_in_daemon` is set.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::process::Command;
+
+use orchestra_core::types::{Codebase, SyncHooks};
+
+use crate::{error::io_err, history::SyncSource, SyncError};
+
+/// Bytes of a failed hook's stderr kept in [`SyncError::HookFailed`].
+const STDERR_TAIL_BYTES: usize = 2048;
+
+/// Which hook ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    PreSync,
+    PostSync,
+}
+
+impl fmt::Display for HookKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PreSync => f.write_str("pre_sync"),
+            Self::PostSync => f.write_str("post_sync"),
+        }
+    }
+}
+
+/// The hooks to run for a sync started by `source`, or `None`.
+pub(crate) fn enabled(codebase: &Codebase, source: SyncSource) -> Option<&SyncHooks> {
+    let hooks = codebase.hooks.as_ref().filter(|hooks| !hooks.is_empty())?;
+    if source == SyncSource::Daemon && !hooks.allow_in_daemon {
+        tracing::debug!(
+            "skipping hooks for '{}': daemon sync without allow_in_daemon",
+            codebase.name
+        );
+        return None;
+    }
+    Some(hooks)
+}
+
+/// Run one hook command and wait for it. A non-zero exit becomes
+/// [`SyncError::HookFailed`] carrying the end of its stderr.
+pub(crate) fn run(
+    kind: HookKind,
+    command: &str,
+    codebase: &Codebase,
+    written: &[PathBuf],
+    dry_run: bool,
+) -> Result<(), SyncError> {
+    let written_files = written
+        .iter()
+        .map(|path| {
+            path.strip_prefix(&codebase.path)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let output = shell(command)
+        .current_dir(&codebase.path)
+        .env("ORCHESTRA_CODEBASE", &codebase.name.0)
+        .env("ORCHESTRA_WRITTEN_FILES", written_files)
+        .env("ORCHESTRA_DRY_RUN", if dry_run { "1" } else { "0" })
+        .output()
+        .map_err(|e| io_err(&codebase.path, e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !stdout.trim().is_empty() {
+        tracing::debug!("{kind} hook for '{}': {}", codebase.name, stdout.trim_end());
+    }
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(SyncError::HookFailed {
+        hook: kind,
+        status: output.status.to_string(),
+        stderr_tail: tail(&String::from_utf8_lossy(&output.stderr)),
+    })
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+/// The last [`STDERR_TAIL_BYTES`] of `stderr`, trimmed.
+fn tail(stderr: &str) -> String {
+    let trimmed = stderr.trim();
+    let mut start = trimmed.len().saturating_sub(STDERR_TAIL_BYTES);
+    while !trimmed.is_char_boundary(start) {
+        start += 1;
+    }
+    trimmed[start..].to_string()
+}
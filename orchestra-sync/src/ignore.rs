@@ -0,0 +1,81 @@
+//! Per-file opt-out marker for managed agent files.
+//!
+//! A managed file whose first [`MARKER_LINES`] lines contain
+//! [`IGNORE_MARKER`] is user-managed: sync never overwrites it, staleness
+//! checks leave it out of `Modified`/`Orphan`, and `diff` skips it. Removing
+//! the marker hands the file back to Orchestra on the next sync.
+//!
+//! ```markdown
+//! <!-- orchestra:ignore -->
+//! # AGENTS.md (maintained by hand)
+//! ```
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// Marker text that opts a managed file out of sync.
+pub const IGNORE_MARKER: &str = "orchestra:ignore";
+
+/// Number of leading lines searched for the marker.
+pub const MARKER_LINES: usize = 5;
+
+/// Upper bound on bytes read while looking for the marker, so a huge file
+/// with long lines is never read in full.
+const MARKER_PREFIX_BYTES: u64 = 4096;
+
+/// Returns `true` if `path` exists and carries the ignore marker in its
+/// first [`MARKER_LINES`] lines.
+///
+/// Missing or unreadable files are not ignored.
+pub fn is_ignored(path: &Path) -> bool {
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    let mut reader = BufReader::new(file.take(MARKER_PREFIX_BYTES));
+    let mut line = Vec::new();
+    for _ in 0..MARKER_LINES {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => return false,
+            Ok(_) => {
+                if String::from_utf8_lossy(&line).contains(IGNORE_MARKER) {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn marker_in_leading_lines_is_detected() {
+        let dir = TempDir::new().unwrap();
+        let html = dir.path().join("AGENTS.md");
+        fs::write(&html, "<!-- orchestra:ignore -->\n# Agents\n").unwrap();
+        assert!(is_ignored(&html));
+
+        let frontmatter = dir.path().join("rules.mdc");
+        fs::write(&frontmatter, "---\ndescription: x\norchestra:ignore: true\n---\n").unwrap();
+        assert!(is_ignored(&frontmatter));
+    }
+
+    #[test]
+    fn marker_after_the_prefix_or_missing_file_is_not_ignored() {
+        let dir = TempDir::new().unwrap();
+        let late = dir.path().join("CLAUDE.md");
+        fs::write(&late, "1\n2\n3\n4\n5\n<!-- orchestra:ignore -->\n").unwrap();
+        assert!(!is_ignored(&late));
+        assert!(!is_ignored(&dir.path().join("missing.md")));
+    }
+}
@@ -0,0 +1,374 @@
+//! Resolved on-disk locations for generated files.
+//!
+//! Generated files normally land inside the codebase. When the registry sets
+//! `output_root` (out-of-tree mode) they land under that directory instead.
+//! Sync, diff, staleness and writeback all resolve paths through this module
+//! so hash-store keys stay consistent across every caller.
+//!
+//! Codebase paths come from user-editable YAML, so before a file is read,
+//! written or deleted it goes through [`resolve_managed_path`], which refuses
+//! anything that would land outside the output root.
+
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+use orchestra_core::types::{Codebase, ExtraOutput};
+use orchestra_renderer::engine::{guide_path, pilot_path, AgentKind, OutputStrategy};
+
+use crate::error::{io_err, SyncError};
+use crate::manifest::MANIFEST_FILE;
+
+/// Directory generated files are written under for `codebase`.
+pub fn output_root(codebase: &Codebase) -> &Path {
+    codebase.output_root.as_deref().unwrap_or(&codebase.path)
+}
+
+/// Output paths for one agent, in the same order as `AgentKind::output_paths`.
+pub fn agent_output_paths(codebase: &Codebase, agent: AgentKind) -> Vec<PathBuf> {
+    agent.output_paths(output_root(codebase))
+}
+
+/// Every agent output path for `codebase`, tagged with the agent that owns it.
+pub fn managed_output_paths(codebase: &Codebase) -> Vec<(AgentKind, PathBuf)> {
+    AgentKind::all()
+        .iter()
+        .flat_map(|agent| {
+            agent_output_paths(codebase, *agent)
+                .into_iter()
+                .map(move |path| (*agent, path))
+        })
+        .collect()
+}
+
+/// Write strategy of the agent output at `path`. The guide, pilot, manifest
+/// and anything else outside the agent outputs are replaced wholesale.
+pub fn output_strategy(codebase: &Codebase, path: &Path) -> OutputStrategy {
+    AgentKind::all()
+        .iter()
+        .find_map(|agent| {
+            agent_output_paths(codebase, *agent)
+                .iter()
+                .zip(agent.output_strategies())
+                .find(|(candidate, _)| candidate.as_path() == path)
+                .map(|(_, strategy)| strategy)
+        })
+        .unwrap_or(OutputStrategy::Replace)
+}
+
+/// Resolved location of the generated `.guide.md`.
+pub fn managed_guide_path(codebase: &Codebase) -> PathBuf {
+    guide_path(output_root(codebase))
+}
+
+/// Resolved location of the generated `pilot.md`.
+pub fn managed_pilot_path(codebase: &Codebase) -> PathBuf {
+    pilot_path(output_root(codebase))
+}
+
+/// Resolved location of `.orchestra-manifest.json`, or `None` when the
+/// registry turns the manifest off.
+pub fn managed_manifest_path(codebase: &Codebase) -> Option<PathBuf> {
+    (codebase.manifest != Some(false)).then(|| manifest_path(output_root(codebase)))
+}
+
+/// `.orchestra-manifest.json` under an output root, whether or not the
+/// registry turns the manifest off.
+pub fn manifest_path(root: &Path) -> PathBuf {
+    root.join(MANIFEST_FILE)
+}
+
+/// Resolved location of an extra output, or `None` when its `output_path`
+/// is absolute, empty or contains `..`, any of which could leave the
+/// output root.
+pub fn extra_output_path(codebase: &Codebase, output: &ExtraOutput) -> Option<PathBuf> {
+    let relative = &output.output_path;
+    let contained = relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    let names_a_file = relative
+        .components()
+        .any(|component| matches!(component, Component::Normal(_)));
+    (contained && names_a_file).then(|| output_root(codebase).join(relative))
+}
+
+/// Resolved locations of the codebase's extra outputs. Invalid entries are
+/// skipped here; sync reports them.
+pub fn extra_output_paths(codebase: &Codebase) -> Vec<PathBuf> {
+    codebase
+        .extra_outputs
+        .iter()
+        .filter_map(|output| extra_output_path(codebase, output))
+        .collect()
+}
+
+/// Agent outputs plus the guide, pilot, extra outputs and manifest — every
+/// file a sync writes.
+pub fn all_managed_paths(codebase: &Codebase) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = managed_output_paths(codebase)
+        .into_iter()
+        .map(|(_, path)| path)
+        .collect();
+    paths.push(managed_guide_path(codebase));
+    paths.push(managed_pilot_path(codebase));
+    paths.extend(extra_output_paths(codebase));
+    paths.extend(managed_manifest_path(codebase));
+    paths
+}
+
+/// Resolve `relative` under `codebase_root` for a managed read, write or
+/// delete. `relative` may also be an absolute path inside `codebase_root`,
+/// as the other functions in this module return.
+///
+/// `.` and `..` are resolved lexically, so the target need not exist. The
+/// result must stay inside the canonicalized root, and the deepest existing
+/// directory on the way must too, which rejects a symlinked subdirectory
+/// pointing out of the tree. The returned path keeps `codebase_root` as
+/// given, so hash-store keys do not change.
+pub fn resolve_managed_path(codebase_root: &Path, relative: &Path) -> Result<PathBuf, SyncError> {
+    let escape = || SyncError::PathEscape {
+        root: codebase_root.to_path_buf(),
+        path: relative.to_path_buf(),
+    };
+    let root = match codebase_root.canonicalize() {
+        Ok(root) => root,
+        // Nothing under a missing root can be a symlink yet.
+        Err(err) if err.kind() == ErrorKind::NotFound => codebase_root.to_path_buf(),
+        Err(err) => return Err(io_err(codebase_root, err)),
+    };
+
+    let mut resolved = root.clone();
+    for component in relative
+        .strip_prefix(codebase_root)
+        .unwrap_or(relative)
+        .components()
+    {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::CurDir => {}
+            Component::ParentDir if resolved != root => {
+                resolved.pop();
+            }
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(escape())
+            }
+        }
+    }
+    if resolved == root {
+        return Err(escape());
+    }
+
+    let mut existing = resolved.parent();
+    while let Some(dir) = existing {
+        if dir == root || dir.exists() {
+            break;
+        }
+        existing = dir.parent();
+    }
+    if let Some(dir) = existing.filter(|dir| *dir != root) {
+        let real = dir.canonicalize().map_err(|e| io_err(dir, e))?;
+        if !real.starts_with(&root) {
+            return Err(escape());
+        }
+    }
+
+    let inside = resolved.strip_prefix(&root).map_err(|_| escape())?;
+    Ok(codebase_root.join(inside))
+}
+
+/// [`resolve_managed_path`] against the codebase's output root.
+pub fn contained_path(codebase: &Codebase, path: &Path) -> Result<PathBuf, SyncError> {
+    resolve_managed_path(output_root(codebase), path)
+}
+
+/// Refuse a codebase whose path or output root is the filesystem root or a
+/// home directory, where generated files would scatter over unrelated data.
+pub fn check_codebase_root(codebase: &Codebase) -> Result<(), SyncError> {
+    let home = dirs::home_dir().and_then(|home| home.canonicalize().ok());
+    for root in [codebase.path.as_path(), output_root(codebase)] {
+        let real = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        if real.parent().is_none() || home.as_deref() == Some(real.as_path()) {
+            return Err(SyncError::UnsafeCodebaseRoot {
+                codebase: codebase.name.0.clone(),
+                path: root.to_path_buf(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Strip the output root (or codebase root) prefix for display.
+pub fn display_relative(path: &Path, codebase: &Codebase) -> PathBuf {
+    path.strip_prefix(output_root(codebase))
+        .or_else(|_| path.strip_prefix(&codebase.path))
+        .unwrap_or(path)
+        .to_path_buf()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use orchestra_core::types::{CodebaseName, CURRENT_SCHEMA_VERSION};
+
+    fn codebase(output_root: Option<&str>) -> Codebase {
+        let now = Utc::now();
+        Codebase {
+            name: CodebaseName::from("api"),
+            path: PathBuf::from("/work/api"),
+            projects: vec![],
+            conventions: vec![],
+            skills: vec![],
+            notes: vec![],
+            tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: output_root.map(PathBuf::from),
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: std::collections::BTreeMap::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            created_at: now,
+            updated_at: now,
+            extra: std::collections::BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn in_tree_paths_match_agent_output_paths() {
+        let cb = codebase(None);
+        for (agent, path) in managed_output_paths(&cb) {
+            assert!(agent.output_paths(&cb.path).contains(&path));
+        }
+        assert_eq!(managed_pilot_path(&cb), pilot_path(&cb.path));
+    }
+
+    #[test]
+    fn output_root_relocates_every_managed_path() {
+        let cb = codebase(Some("/build/agents/api"));
+        let paths = all_managed_paths(&cb);
+        assert!(!paths.is_empty());
+        assert!(paths.iter().all(|p| p.starts_with("/build/agents/api")));
+        assert_eq!(
+            display_relative(&managed_guide_path(&cb), &cb),
+            guide_path(Path::new(""))
+        );
+    }
+
+    #[test]
+    fn manifest_path_follows_registry_setting() {
+        let mut cb = codebase(Some("/build/agents/api"));
+        assert_eq!(
+            managed_manifest_path(&cb),
+            Some(PathBuf::from("/build/agents/api/.orchestra-manifest.json"))
+        );
+        assert!(all_managed_paths(&cb).contains(&managed_manifest_path(&cb).unwrap()));
+
+        cb.manifest = Some(false);
+        assert_eq!(managed_manifest_path(&cb), None);
+        assert!(!all_managed_paths(&cb)
+            .iter()
+            .any(|p| p.ends_with(MANIFEST_FILE)));
+    }
+
+    #[test]
+    fn managed_paths_cannot_climb_out_of_the_root() {
+        let root = tempfile::TempDir::new().unwrap();
+        let root = root.path();
+        assert_eq!(
+            resolve_managed_path(root, Path::new("docs/./../CLAUDE.md")).unwrap(),
+            root.join("CLAUDE.md")
+        );
+        assert_eq!(
+            resolve_managed_path(root, &root.join(".cursor/rules/a.mdc")).unwrap(),
+            root.join(".cursor/rules/a.mdc")
+        );
+        for escape in [
+            "../outside.md",
+            "docs/../../outside.md",
+            "/etc/passwd",
+            "",
+            ".",
+        ] {
+            assert!(
+                matches!(
+                    resolve_managed_path(root, Path::new(escape)),
+                    Err(SyncError::PathEscape { .. })
+                ),
+                "{escape:?} was accepted"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_directories_must_stay_inside_the_root() {
+        let root = tempfile::TempDir::new().unwrap();
+        let outside = tempfile::TempDir::new().unwrap();
+        let root = root.path();
+        std::os::unix::fs::symlink(outside.path(), root.join(".claude")).unwrap();
+        std::fs::create_dir(root.join("real")).unwrap();
+        std::os::unix::fs::symlink(root.join("real"), root.join("docs")).unwrap();
+
+        for escape in [".claude/settings.json", ".claude/agents/new/reviewer.md"] {
+            assert!(
+                matches!(
+                    resolve_managed_path(root, Path::new(escape)),
+                    Err(SyncError::PathEscape { .. })
+                ),
+                "{escape:?} was accepted"
+            );
+        }
+        assert_eq!(
+            resolve_managed_path(root, Path::new("docs/ai-context.md")).unwrap(),
+            root.join("docs/ai-context.md")
+        );
+    }
+
+    #[test]
+    fn filesystem_root_is_not_a_codebase_root() {
+        let mut cb = codebase(None);
+        assert!(check_codebase_root(&cb).is_ok());
+        cb.path = PathBuf::from("/");
+        assert!(matches!(
+            check_codebase_root(&cb),
+            Err(SyncError::UnsafeCodebaseRoot { .. })
+        ));
+        let mut cb = codebase(Some("/"));
+        cb.path = PathBuf::from("/work/api");
+        assert!(check_codebase_root(&cb).is_err());
+    }
+
+    #[test]
+    fn extra_outputs_stay_inside_the_output_root() {
+        let mut cb = codebase(Some("/build/agents/api"));
+        let extra = |path: &str| ExtraOutput {
+            template: "ai-context.md.tera".to_string(),
+            output_path: PathBuf::from(path),
+        };
+        cb.extra_outputs = vec![
+            extra("docs/ai-context.md"),
+            extra("../escape.md"),
+            extra("docs/../../escape.md"),
+            extra("/etc/passwd"),
+            extra(""),
+        ];
+
+        assert_eq!(
+            extra_output_path(&cb, &cb.extra_outputs[0]),
+            Some(PathBuf::from("/build/agents/api/docs/ai-context.md"))
+        );
+        assert_eq!(
+            extra_output_paths(&cb),
+            vec![PathBuf::from("/build/agents/api/docs/ai-context.md")]
+        );
+        assert!(
+            all_managed_paths(&cb).contains(&PathBuf::from("/build/agents/api/docs/ai-context.md"))
+        );
+    }
+}
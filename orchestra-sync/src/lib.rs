@@ -6,18 +6,39 @@
 //! registered codebase, or [`sync_all`] to process every registered codebase.
 
 pub mod backup;
+pub mod bundle;
+pub mod detection;
 pub mod diff;
+pub mod edit_backup;
 pub mod error;
 pub mod hash_store;
+pub mod history;
+pub mod hooks;
+pub mod ignore;
+pub mod layout;
+pub mod lockfile;
+pub mod log_rotation;
+pub mod manifest;
 pub mod pipeline;
+pub mod render_cache;
+pub mod report;
 pub mod staleness;
+pub mod status;
+pub mod upgrade;
+pub mod which;
 pub mod writeback;
 pub mod writer;
 
-pub use diff::{diff_codebase, DiffCodebaseResult, FileDiff};
+pub use diff::{
+    diff_all, diff_all_with_context, diff_codebase, diff_codebase_with_context, DiffCodebaseResult,
+    FileDiff, DEFAULT_CONTEXT_LINES,
+};
 pub use error::SyncError;
-pub use pipeline::SyncScope;
-pub use staleness::StalenessSignal;
+pub use history::{HistoryEntry, SyncSource};
+pub use layout::managed_output_paths;
+pub use pipeline::{CodebaseOutcome, SyncProgress, SyncScope};
+pub use report::SyncReport;
+pub use staleness::{AgentStaleness, StalenessReport, StalenessSignal};
 pub use backup::{
 	backup_agent_files,
 	load_backup_manifest,
@@ -27,6 +48,10 @@ pub use backup::{
 	BackupItem,
 	BackupManifest,
 };
-pub use writer::{sync_all, sync_codebase, SyncCodebaseResult, WriteResult};
+pub use which::{find_managed_at, ManagedFile};
+pub use writer::{
+    sync_all, sync_codebase, sync_codebase_with, unknown_assignees, SyncCodebaseResult,
+    SyncOptions, UnknownAssignee, WriteFilter, WriteResult,
+};
 pub use writeback::{managed_agent_paths, process_writeback, WritebackOutcome};
 
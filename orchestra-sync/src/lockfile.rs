@@ -0,0 +1,131 @@
+//! Advisory per-codebase locks.
+//!
+//! A sync loads the hash store, writes files, then saves the store. Two
+//! processes doing that for the same codebase at once (the daemon and the
+//! CLI, say) would each save their own copy and lose the other's updates, so
+//! the whole load-to-save section runs under an exclusive lock on
+//! `~/.orchestra/locks/<codebase>.lock`. Readers that must not observe a
+//! half-finished sync (`diff`) take a shared lock.
+//!
+//! Locks are OS advisory locks (`flock` on unix) and are released when the
+//! [`CodebaseLock`] is dropped or the process exits.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use orchestra_core::paths;
+
+use crate::error::{io_err, SyncError};
+
+/// How long to wait for another sync to release a codebase lock.
+pub const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// A held codebase lock. Dropping it releases the lock.
+#[derive(Debug)]
+pub struct CodebaseLock {
+    file: File,
+}
+
+impl Drop for CodebaseLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+/// Path to the lock file for `codebase_name`.
+///
+/// `~/.orchestra/locks/<codebase_name>.lock`
+pub fn lock_path_at(home: &Path, codebase_name: &str) -> PathBuf {
+    paths::dirs_at(home)
+        .state_root
+        .join("locks")
+        .join(format!("{codebase_name}.lock"))
+}
+
+/// Take the exclusive lock used by anything that rewrites the hash store.
+pub fn lock_exclusive_at(home: &Path, codebase_name: &str) -> Result<CodebaseLock, SyncError> {
+    acquire(home, codebase_name, true, LOCK_TIMEOUT)
+}
+
+/// Take a shared lock, which only waits for exclusive holders.
+pub fn lock_shared_at(home: &Path, codebase_name: &str) -> Result<CodebaseLock, SyncError> {
+    acquire(home, codebase_name, false, LOCK_TIMEOUT)
+}
+
+fn acquire(
+    home: &Path,
+    codebase_name: &str,
+    exclusive: bool,
+    timeout: Duration,
+) -> Result<CodebaseLock, SyncError> {
+    let path = lock_path_at(home, codebase_name);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| io_err(dir, e))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(|e| io_err(&path, e))?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        let attempt = if exclusive {
+            file.try_lock()
+        } else {
+            file.try_lock_shared()
+        };
+        match attempt {
+            Ok(()) => return Ok(CodebaseLock { file }),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                thread::sleep(POLL_INTERVAL);
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(SyncError::Locked {
+                    codebase: codebase_name.to_string(),
+                });
+            }
+            Err(TryLockError::Error(e)) => return Err(io_err(&path, e)),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SHORT: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn exclusive_lock_times_out_while_held() {
+        let home = TempDir::new().unwrap();
+        let held = acquire(home.path(), "api", true, SHORT).expect("first lock");
+
+        let err = acquire(home.path(), "api", true, SHORT).unwrap_err();
+        assert!(matches!(err, SyncError::Locked { ref codebase } if codebase == "api"));
+        let err = acquire(home.path(), "api", false, SHORT).unwrap_err();
+        assert!(matches!(err, SyncError::Locked { .. }));
+
+        drop(held);
+        acquire(home.path(), "api", true, SHORT).expect("lock after release");
+    }
+
+    #[test]
+    fn shared_locks_coexist_but_block_writers() {
+        let home = TempDir::new().unwrap();
+        let _a = acquire(home.path(), "api", false, SHORT).expect("shared a");
+        let _b = acquire(home.path(), "api", false, SHORT).expect("shared b");
+        assert!(acquire(home.path(), "api", true, SHORT).is_err());
+        acquire(home.path(), "web", true, SHORT).expect("other codebase is independent");
+    }
+}
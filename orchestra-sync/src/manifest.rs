@@ -0,0 +1,162 @@
+//! Repo-local manifest of the files Orchestra manages.
+//!
+//! Sync writes `.orchestra-manifest.json` at the output root, listing every
+//! managed file (relative to that root) with its SHA-256 digest — the same
+//! digest the hash store records. Teams can build a `.gitignore` from it, and
+//! tools can tell Orchestra's files apart without reading
+//! `~/.orchestra/hashes/`.
+//!
+//! The manifest goes through the same hash-gated writer as every other
+//! output. It never lists itself, and `synced_at` only advances when another
+//! managed file changed, so a no-op sync leaves it byte-identical. Set
+//! `manifest: false` in the codebase registry file to turn it off.
+
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use orchestra_core::types::Codebase;
+
+use crate::{layout, SyncError};
+
+/// File name of the manifest, written at the output root.
+pub const MANIFEST_FILE: &str = ".orchestra-manifest.json";
+
+/// On-disk manifest payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Version of the orchestra build that wrote the manifest.
+    pub orchestra_version: String,
+    /// When a managed file last changed.
+    pub synced_at: DateTime<Utc>,
+    /// Managed files, sorted by path.
+    pub files: Vec<ManifestEntry>,
+}
+
+/// One managed file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Path relative to the output root, `/`-separated.
+    pub path: String,
+    /// SHA-256 hex digest of the LF-normalised content.
+    pub sha256: String,
+}
+
+/// Read the manifest at `path`. Returns `None` if it is missing or does not
+/// parse.
+pub fn load(path: &Path) -> Option<Manifest> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Render manifest content listing `files` (absolute path and digest).
+///
+/// `changed` says whether any of `files` is being written by this run. If
+/// not, the previous manifest's `synced_at` is kept so the content only
+/// changes when the file list or a digest does.
+pub(crate) fn render(
+    codebase: &Codebase,
+    manifest_path: &Path,
+    files: &[(PathBuf, String)],
+    changed: bool,
+    now: DateTime<Utc>,
+) -> Result<String, SyncError> {
+    let synced_at = match load(manifest_path) {
+        Some(previous) if !changed => previous.synced_at,
+        _ => now,
+    };
+
+    let mut entries: Vec<ManifestEntry> = files
+        .iter()
+        .filter(|(path, _)| path != manifest_path)
+        .map(|(path, digest)| ManifestEntry {
+            path: slash_path(&layout::display_relative(path, codebase)),
+            sha256: digest.clone(),
+        })
+        .collect();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    entries.dedup_by(|a, b| a.path == b.path);
+
+    let manifest = Manifest {
+        orchestra_version: env!("CARGO_PKG_VERSION").to_string(),
+        synced_at,
+        files: entries,
+    };
+    let mut json = serde_json::to_string_pretty(&manifest)?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// `/`-separated form of a relative path, identical on every platform.
+pub(crate) fn slash_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use orchestra_core::types::{CodebaseName, CURRENT_SCHEMA_VERSION};
+    use tempfile::TempDir;
+
+    fn codebase(root: &Path) -> Codebase {
+        let now = Utc::now();
+        Codebase {
+            name: CodebaseName::from("api"),
+            path: root.to_path_buf(),
+            projects: vec![],
+            conventions: vec![],
+            skills: vec![],
+            notes: vec![],
+            tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: std::collections::BTreeMap::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            created_at: now,
+            updated_at: now,
+            extra: std::collections::BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn unchanged_files_keep_the_previous_timestamp() {
+        let dir = TempDir::new().unwrap();
+        let cb = codebase(dir.path());
+        let manifest_path = dir.path().join(MANIFEST_FILE);
+        let files = vec![
+            (dir.path().join("orchestra/pilot.md"), "bb".to_string()),
+            (dir.path().join("orchestra/controls/CLAUDE.md"), "aa".to_string()),
+            (manifest_path.clone(), "ff".to_string()),
+        ];
+        let first_sync = Utc::now() - Duration::hours(1);
+
+        let first = render(&cb, &manifest_path, &files, true, first_sync).unwrap();
+        std::fs::write(&manifest_path, &first).unwrap();
+        let parsed = load(&manifest_path).unwrap();
+        let paths: Vec<&str> = parsed.files.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["orchestra/controls/CLAUDE.md", "orchestra/pilot.md"]);
+
+        let noop = render(&cb, &manifest_path, &files, false, Utc::now()).unwrap();
+        assert_eq!(noop, first);
+
+        let changed = render(&cb, &manifest_path, &files, true, Utc::now()).unwrap();
+        assert_ne!(changed, first);
+    }
+}
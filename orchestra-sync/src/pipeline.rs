@@ -1,11 +1,19 @@
 //! Shared sync pipeline entrypoint used by CLI and daemon.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::{sync_all, sync_codebase, SyncCodebaseResult, SyncError};
+use orchestra_core::registry;
+use orchestra_renderer::AgentKind;
+use serde::Serialize;
 
-/// Scope for a sync pipeline run.
-#[derive(Debug, Clone, PartialEq, Eq)]
+use crate::history::SyncSource;
+use crate::writer::{sync_codebase_from, SyncOptions, WriteFilter};
+use crate::{SyncCodebaseResult, SyncError, WriteResult};
+
+/// Scope for a sync pipeline run. Serializes as `{"kind": "all"}` or
+/// `{"kind": "codebase", "name": "..."}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "name", rename_all = "snake_case")]
 pub enum SyncScope {
     /// Sync every registered codebase.
     All,
@@ -13,18 +21,276 @@ pub enum SyncScope {
     Codebase(String),
 }
 
+/// Progress reported while a sync pipeline run is under way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncProgress {
+    /// A codebase is about to sync; `index` counts from 1 up to `total`.
+    CodebaseStarted {
+        name: String,
+        index: usize,
+        total: usize,
+    },
+    /// A file was written to disk (never sent for `--dry-run`).
+    FileWritten { path: PathBuf },
+    /// A codebase finished. `written` includes files a dry run would write.
+    CodebaseFinished {
+        name: String,
+        written: usize,
+        unchanged: usize,
+    },
+    /// A codebase failed; the run stops after this event unless it was
+    /// started with [`run_each`].
+    CodebaseFailed { name: String, error: String },
+}
+
 /// Run the sync pipeline for a scope.
 ///
 /// This is the canonical sync entrypoint for both `orchestra sync` and the
-/// Phase 04 daemon processor.
+/// Phase 04 daemon processor. Runs are journaled as [`SyncSource::Cli`];
+/// use [`run_from`] to attribute them elsewhere.
+///
+/// `agent` restricts the run to one agent's outputs; `None` syncs every agent.
+/// Every pipeline run uses the default [`SyncOptions`], so a no-op sync
+/// still advances `synced_at`; use [`sync_codebase_with`] to change that.
+///
+/// [`sync_codebase_with`]: crate::sync_codebase_with
 pub fn run(
     home: &Path,
     scope: SyncScope,
     dry_run: bool,
+    agent: Option<AgentKind>,
+) -> Result<Vec<SyncCodebaseResult>, SyncError> {
+    run_with_progress(home, scope, dry_run, agent, |_| {})
+}
+
+/// [`run`], reporting each step to `on_event` as it happens.
+pub fn run_with_progress(
+    home: &Path,
+    scope: SyncScope,
+    dry_run: bool,
+    agent: Option<AgentKind>,
+    on_event: impl FnMut(SyncProgress),
+) -> Result<Vec<SyncCodebaseResult>, SyncError> {
+    run_from_with_progress(home, scope, dry_run, agent, SyncSource::Cli, on_event)
+}
+
+/// Run the sync pipeline for a scope, recording `source` in the history journal.
+pub fn run_from(
+    home: &Path,
+    scope: SyncScope,
+    dry_run: bool,
+    agent: Option<AgentKind>,
+    source: SyncSource,
+) -> Result<Vec<SyncCodebaseResult>, SyncError> {
+    run_from_with_progress(home, scope, dry_run, agent, source, |_| {})
+}
+
+/// [`run_from`], reporting each step to `on_event` as it happens.
+pub fn run_from_with_progress(
+    home: &Path,
+    scope: SyncScope,
+    dry_run: bool,
+    agent: Option<AgentKind>,
+    source: SyncSource,
+    mut on_event: impl FnMut(SyncProgress),
+) -> Result<Vec<SyncCodebaseResult>, SyncError> {
+    run_inner(home, scope, dry_run, agent, source, true, &mut on_event)
+}
+
+/// [`run_with_progress`] without saving local edits to
+/// `~/.orchestra/backups/` before overwriting them (`orchestra sync --no-backup`).
+pub fn run_without_backups(
+    home: &Path,
+    scope: SyncScope,
+    dry_run: bool,
+    agent: Option<AgentKind>,
+    mut on_event: impl FnMut(SyncProgress),
+) -> Result<Vec<SyncCodebaseResult>, SyncError> {
+    run_inner(
+        home,
+        scope,
+        dry_run,
+        agent,
+        SyncSource::Cli,
+        false,
+        &mut on_event,
+    )
+}
+
+/// One codebase's result from [`run_each`].
+#[derive(Debug)]
+pub struct CodebaseOutcome {
+    pub name: String,
+    pub result: Result<SyncCodebaseResult, SyncError>,
+}
+
+/// [`run_with_progress`] that keeps going when a codebase fails, returning
+/// every codebase's outcome in order. A registry file that cannot be read
+/// is the failed outcome of the codebase it is named after; only failing to
+/// list the registry fails the whole run. With `backups` off this is [`run_without_backups`].
+/// `enforce_budget` trims agent files over their token budget in every
+/// codebase, not only those whose registry file asks for it.
+pub fn run_each(
+    home: &Path,
+    scope: SyncScope,
+    dry_run: bool,
+    agent: Option<AgentKind>,
+    backups: bool,
+    enforce_budget: bool,
+    mut on_event: impl FnMut(SyncProgress),
+) -> Result<Vec<CodebaseOutcome>, SyncError> {
+    run_codebases(
+        home,
+        scope,
+        dry_run,
+        agent,
+        SyncSource::Cli,
+        backups,
+        enforce_budget,
+        WriteFilter::All,
+        true,
+        &mut on_event,
+    )
+}
+
+/// [`run_each`] that writes only the files a template change rewrites (see
+/// [`WriteFilter::TemplateChanges`]), for `orchestra upgrade-templates --apply`.
+/// Files with local edits, or missing from disk, are left alone.
+pub fn run_template_upgrade(
+    home: &Path,
+    scope: SyncScope,
+    mut on_event: impl FnMut(SyncProgress),
+) -> Result<Vec<CodebaseOutcome>, SyncError> {
+    run_codebases(
+        home,
+        scope,
+        false,
+        None,
+        SyncSource::Cli,
+        true,
+        false,
+        WriteFilter::TemplateChanges,
+        true,
+        &mut on_event,
+    )
+}
+
+fn run_inner(
+    home: &Path,
+    scope: SyncScope,
+    dry_run: bool,
+    agent: Option<AgentKind>,
+    source: SyncSource,
+    backups: bool,
+    on_event: &mut dyn FnMut(SyncProgress),
 ) -> Result<Vec<SyncCodebaseResult>, SyncError> {
-    match scope {
-        SyncScope::All => sync_all(home, dry_run),
-        SyncScope::Codebase(name) => Ok(vec![sync_codebase(&name, home, dry_run)?]),
+    run_codebases(
+        home,
+        scope,
+        dry_run,
+        agent,
+        source,
+        backups,
+        false,
+        WriteFilter::All,
+        false,
+        on_event,
+    )?
+    .into_iter()
+    .map(|outcome| outcome.result)
+    .collect()
+}
+
+/// Sync each codebase in `scope`. Without `keep_going` the run stops after
+/// the first failure, which is then the last outcome.
+#[allow(clippy::too_many_arguments)]
+fn run_codebases(
+    home: &Path,
+    scope: SyncScope,
+    dry_run: bool,
+    agent: Option<AgentKind>,
+    source: SyncSource,
+    backups: bool,
+    enforce_budget: bool,
+    filter: WriteFilter,
+    keep_going: bool,
+    on_event: &mut dyn FnMut(SyncProgress),
+) -> Result<Vec<CodebaseOutcome>, SyncError> {
+    let names = match scope {
+        SyncScope::All => {
+            // Unreadable files are kept by name, so each one fails on its own.
+            let listing = registry::list_codebases_lossy_at(home)?;
+            let failed = listing
+                .errors
+                .iter()
+                .filter_map(|failed| failed.path.file_stem())
+                .map(|stem| stem.to_string_lossy().into_owned());
+            listing
+                .codebases
+                .into_iter()
+                .map(|(_, codebase)| codebase.name.0)
+                .chain(failed)
+                .collect()
+        }
+        SyncScope::Codebase(name) => vec![name],
+    };
+
+    let total = names.len();
+    let mut outcomes = Vec::with_capacity(total);
+    for (index, name) in names.into_iter().enumerate() {
+        on_event(SyncProgress::CodebaseStarted {
+            name: name.clone(),
+            index: index + 1,
+            total,
+        });
+        let result = sync_codebase_from(
+            &name,
+            home,
+            dry_run,
+            agent,
+            source,
+            backups,
+            enforce_budget,
+            filter,
+            // Not configurable per run; see `SyncOptions`.
+            SyncOptions::default(),
+            on_event,
+        );
+        let failed = match &result {
+            Ok(result) => {
+                on_event(finished(result));
+                false
+            }
+            Err(err) => {
+                on_event(SyncProgress::CodebaseFailed {
+                    name: name.clone(),
+                    error: err.to_string(),
+                });
+                true
+            }
+        };
+        outcomes.push(CodebaseOutcome { name, result });
+        if failed && !keep_going {
+            break;
+        }
+    }
+    Ok(outcomes)
+}
+
+fn finished(result: &SyncCodebaseResult) -> SyncProgress {
+    let mut written = 0;
+    let mut unchanged = 0;
+    for write in &result.writes {
+        match write {
+            WriteResult::Written { .. } | WriteResult::WouldWrite { .. } => written += 1,
+            WriteResult::Unchanged { .. } => unchanged += 1,
+            WriteResult::Ignored { .. } => {}
+        }
+    }
+    SyncProgress::CodebaseFinished {
+        name: result.codebase_name.clone(),
+        written,
+        unchanged,
     }
 }
 
@@ -43,7 +309,7 @@ mod tests {
     #[test]
     fn run_all_empty_registry_returns_empty_vec() {
         let home = TempDir::new().expect("home");
-        let result = run(home.path(), SyncScope::All, true).expect("run");
+        let result = run(home.path(), SyncScope::All, true, None).expect("run");
         assert!(result.is_empty());
     }
 
@@ -65,9 +331,110 @@ mod tests {
             home.path(),
             SyncScope::Codebase("copnow_api".to_string()),
             true,
+            None,
         )
         .expect("run");
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].codebase_name, "copnow_api");
     }
+
+    #[test]
+    fn progress_events_follow_each_codebase_in_order() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        for name in ["alpha", "beta", "gamma"] {
+            let codebase_dir = workspace.path().join(name);
+            fs::create_dir_all(&codebase_dir).expect("mkdir");
+            registry::init_at(
+                codebase_dir,
+                ProjectName::from("copnow"),
+                Some(ProjectType::Backend),
+                home.path(),
+            )
+            .expect("init");
+        }
+
+        let mut events = Vec::new();
+        let results = run_with_progress(home.path(), SyncScope::All, false, None, |event| {
+            events.push(event)
+        })
+        .expect("run");
+        assert_eq!(results.len(), 3);
+
+        let mut events = events.into_iter();
+        for (i, result) in results.iter().enumerate() {
+            assert_eq!(
+                events.next(),
+                Some(SyncProgress::CodebaseStarted {
+                    name: result.codebase_name.clone(),
+                    index: i + 1,
+                    total: 3,
+                })
+            );
+            let written: Vec<_> = result
+                .writes
+                .iter()
+                .filter_map(|write| match write {
+                    WriteResult::Written { path, .. } => Some(path.clone()),
+                    _ => None,
+                })
+                .collect();
+            assert!(!written.is_empty());
+            for path in &written {
+                assert_eq!(
+                    events.next(),
+                    Some(SyncProgress::FileWritten { path: path.clone() })
+                );
+            }
+            assert_eq!(
+                events.next(),
+                Some(SyncProgress::CodebaseFinished {
+                    name: result.codebase_name.clone(),
+                    written: written.len(),
+                    unchanged: 0,
+                })
+            );
+        }
+        assert_eq!(events.next(), None);
+
+        let mut events = Vec::new();
+        run_with_progress(home.path(), SyncScope::All, false, None, |event| {
+            events.push(event)
+        })
+        .expect("second run");
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, SyncProgress::FileWritten { .. })));
+    }
+
+    #[test]
+    fn unknown_codebase_reports_failure() {
+        let home = TempDir::new().expect("home");
+        let mut events = Vec::new();
+        let err = run_with_progress(
+            home.path(),
+            SyncScope::Codebase("missing".to_string()),
+            true,
+            None,
+            |event| events.push(event),
+        )
+        .expect_err("unknown codebase");
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            SyncProgress::CodebaseStarted {
+                index: 1,
+                total: 1,
+                ..
+            }
+        ));
+        assert_eq!(
+            events[1],
+            SyncProgress::CodebaseFailed {
+                name: "missing".to_string(),
+                error: err.to_string(),
+            }
+        );
+    }
 }
@@ -0,0 +1,104 @@
+//! Render cache — lets `diff` skip rendering a codebase nothing has changed
+//! for.
+//!
+//! A full sync records [`RenderInputs`] in the hash store: the template-set
+//! fingerprint and a digest of the registry inputs. While both still match
+//! and every recorded file on disk still has its recorded hash, rendering
+//! again would reproduce what is on disk, so [`diff_codebase`] reports no
+//! diffs without rendering, and the budget overruns that sync recorded
+//! alongside the inputs. Any mismatch falls back to the full render.
+//!
+//! [`diff_codebase`]: crate::diff_codebase
+
+use std::io::ErrorKind;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use orchestra_core::{
+    global, registry,
+    types::{Codebase, ProjectName},
+};
+use orchestra_renderer::{skills, Renderer};
+
+use crate::error::{io_err, SyncError};
+use crate::hash_store::{self, HashStoreFile, RenderInputs};
+
+/// The inputs rendering `codebase` with `renderer` would use right now.
+pub fn render_inputs(
+    home: &Path,
+    renderer: &Renderer,
+    project: &ProjectName,
+    codebase: &Codebase,
+) -> Result<RenderInputs, SyncError> {
+    Ok(RenderInputs {
+        templates: renderer.fingerprint().to_string(),
+        registry: registry_digest(home, project, codebase)?,
+    })
+}
+
+/// `true` when `store` was written by a full sync from the current inputs
+/// and none of its files has changed on disk since.
+pub(crate) fn outputs_current(
+    home: &Path,
+    renderer: &Renderer,
+    project: &ProjectName,
+    codebase: &Codebase,
+    store: &HashStoreFile,
+) -> Result<bool, SyncError> {
+    let Some(recorded) = &store.rendered_from else {
+        return Ok(false);
+    };
+    if store.files.is_empty() || *recorded != render_inputs(home, renderer, project, codebase)? {
+        return Ok(false);
+    }
+    Ok(hash_store::mismatches(codebase, store)?.is_empty())
+}
+
+/// SHA-256 over the raw files rendering reads besides the templates. The
+/// orchestra version covers changes to how the context is built from them.
+fn registry_digest(
+    home: &Path,
+    project: &ProjectName,
+    codebase: &Codebase,
+) -> Result<String, SyncError> {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hash_file(
+        &mut hasher,
+        &registry::codebase_path_at(home, project, &codebase.name),
+    )?;
+    hash_file(&mut hasher, &global::path_at(home))?;
+
+    let catalog = skills::catalog_dir_at(home);
+    let mut entries = match std::fs::read_dir(&catalog) {
+        Ok(entries) => entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| io_err(&catalog, e))?,
+        Err(err) if err.kind() == ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(io_err(&catalog, err)),
+    };
+    entries.sort();
+    for path in entries {
+        hasher.update(path.to_string_lossy().as_bytes());
+        hash_file(&mut hasher, &path)?;
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Feed `path`'s length-prefixed content to `hasher`; a missing file (or a
+/// directory) hashes as a distinct marker.
+fn hash_file(hasher: &mut Sha256, path: &Path) -> Result<(), SyncError> {
+    match std::fs::read(path) {
+        Ok(bytes) => {
+            hasher.update((bytes.len() as u64).to_le_bytes());
+            hasher.update(&bytes);
+        }
+        Err(err) if matches!(err.kind(), ErrorKind::NotFound | ErrorKind::IsADirectory) => {
+            hasher.update(u64::MAX.to_le_bytes());
+        }
+        Err(err) => return Err(io_err(path, err)),
+    }
+    Ok(())
+}
@@ -0,0 +1,219 @@
+//! Machine-readable record of one sync run, for `orchestra sync --report`.
+//!
+//! The report lists every codebase in the run with the state of each file it
+//! wrote, or the error that stopped it, so CI can attach it to a build.
+//! Paths are relative to the codebase's output root and `/`-separated.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use orchestra_core::{registry, types::Codebase};
+
+use crate::layout;
+use crate::manifest::slash_path;
+use crate::pipeline::{CodebaseOutcome, SyncScope};
+
+/// The whole run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SyncReport {
+    pub orchestra_version: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub scope: SyncScope,
+    pub dry_run: bool,
+    /// The agent the run was restricted to, if any.
+    pub agent: Option<String>,
+    pub codebases: Vec<CodebaseReport>,
+}
+
+/// One codebase, tagged by `status`: `synced` or `failed`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CodebaseReport {
+    Synced {
+        codebase: String,
+        files: Vec<FileReport>,
+        /// Why the `post_sync` hook failed, if it did.
+        post_sync_error: Option<String>,
+    },
+    Failed {
+        codebase: String,
+        error: String,
+    },
+}
+
+/// One file a synced codebase wrote or skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileReport {
+    pub path: String,
+    /// A [`WriteResult`](crate::WriteResult) state, e.g. `written`.
+    pub state: &'static str,
+}
+
+impl SyncReport {
+    /// Build the report for `outcomes`. Codebases are looked up in the
+    /// registry to make paths relative; any that cannot be found keep
+    /// absolute paths.
+    pub fn new(
+        home: &Path,
+        scope: SyncScope,
+        dry_run: bool,
+        agent: Option<String>,
+        started_at: DateTime<Utc>,
+        duration: Duration,
+        outcomes: &[CodebaseOutcome],
+    ) -> Self {
+        let codebases: HashMap<String, Codebase> = registry::list_codebases_at(home)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(_, codebase)| (codebase.name.0.clone(), codebase))
+            .collect();
+        SyncReport {
+            orchestra_version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at,
+            duration_ms: duration.as_millis().try_into().unwrap_or(u64::MAX),
+            scope,
+            dry_run,
+            agent,
+            codebases: outcomes
+                .iter()
+                .map(|outcome| codebase_report(outcome, codebases.get(&outcome.name)))
+                .collect(),
+        }
+    }
+
+    /// Codebases that failed.
+    pub fn failed(&self) -> usize {
+        self.codebases
+            .iter()
+            .filter(|codebase| matches!(codebase, CodebaseReport::Failed { .. }))
+            .count()
+    }
+}
+
+fn codebase_report(outcome: &CodebaseOutcome, codebase: Option<&Codebase>) -> CodebaseReport {
+    match &outcome.result {
+        Ok(result) => CodebaseReport::Synced {
+            codebase: outcome.name.clone(),
+            files: result
+                .writes
+                .iter()
+                .map(|write| FileReport {
+                    path: match codebase {
+                        Some(codebase) => {
+                            slash_path(&layout::display_relative(write.path(), codebase))
+                        }
+                        None => write.path().display().to_string(),
+                    },
+                    state: write.state(),
+                })
+                .collect(),
+            post_sync_error: result.post_sync_error.as_ref().map(ToString::to_string),
+        },
+        Err(err) => CodebaseReport::Failed {
+            codebase: outcome.name.clone(),
+            error: err.to_string(),
+        },
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use orchestra_core::types::{ProjectName, ProjectType};
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::pipeline;
+
+    #[test]
+    fn report_covers_synced_and_failed_codebases() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        for name in ["api", "web"] {
+            let dir = workspace.path().join(name);
+            fs::create_dir_all(&dir).unwrap();
+            registry::init_at(
+                dir,
+                ProjectName::from("copnow"),
+                Some(ProjectType::Backend),
+                home.path(),
+            )
+            .unwrap();
+        }
+        // A codebase whose directory is now a file cannot be written into.
+        let broken = workspace.path().join("api");
+        fs::remove_dir_all(&broken).unwrap();
+        fs::write(&broken, "not a directory").unwrap();
+
+        let outcomes = pipeline::run_each(
+            home.path(),
+            SyncScope::All,
+            false,
+            None,
+            true,
+            false,
+            |_| {},
+        )
+        .unwrap();
+        let report = SyncReport::new(
+            home.path(),
+            SyncScope::All,
+            false,
+            None,
+            Utc::now(),
+            Duration::from_millis(1500),
+            &outcomes,
+        );
+        assert_eq!(report.failed(), 1);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["orchestra_version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["duration_ms"], 1500);
+        assert_eq!(json["scope"], serde_json::json!({"kind": "all"}));
+        assert_eq!(json["dry_run"], false);
+        assert!(json["agent"].is_null());
+
+        let codebases = json["codebases"].as_array().unwrap();
+        assert_eq!(codebases.len(), 2);
+        assert_eq!(codebases[0]["codebase"], "api");
+        assert_eq!(codebases[0]["status"], "failed");
+        assert!(codebases[0]["error"]
+            .as_str()
+            .unwrap()
+            .contains("I/O error"));
+
+        assert_eq!(codebases[1]["codebase"], "web");
+        assert_eq!(codebases[1]["status"], "synced");
+        assert!(codebases[1]["post_sync_error"].is_null());
+        let files = codebases[1]["files"].as_array().unwrap();
+        assert!(files.iter().all(|file| file["state"] == "written"));
+        assert!(files
+            .iter()
+            .any(|file| file["path"] == "orchestra/controls/CLAUDE.md"));
+    }
+
+    #[test]
+    fn scope_and_write_results_use_snake_case_tags() {
+        assert_eq!(
+            serde_json::to_value(SyncScope::Codebase("api".to_string())).unwrap(),
+            serde_json::json!({"kind": "codebase", "name": "api"})
+        );
+        assert_eq!(
+            serde_json::to_value(crate::WriteResult::WouldWrite {
+                path: "/work/api/AGENTS.md".into()
+            })
+            .unwrap(),
+            serde_json::json!({"state": "would_write", "path": "/work/api/AGENTS.md"})
+        );
+    }
+}
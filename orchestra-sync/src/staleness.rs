@@ -2,10 +2,18 @@
 //!
 //! Signal precedence:
 //! 1. `NeverSynced` (hash store missing or empty)
-//! 2. `Stale` (registry changed after `synced_at`, or managed files missing)
+//! 2. `Stale` (registry or `global.yaml` changed after `synced_at`, or
+//!    managed files missing)
 //! 3. `Modified` (rendered files changed since last sync hash)
 //! 4. `Orphan` (managed files present but not tracked in hash store)
 //! 5. `Current`
+//!
+//! [`check_detailed`] applies the same precedence to each agent's own output
+//! files; [`check`] merges those results, so the worst agent wins.
+//!
+//! Files carrying the [`ignore`](crate::ignore) marker are left out of the
+//! `Modified` and `Orphan` calculations and listed separately in
+//! [`StalenessReport::ignored`].
 
 use std::collections::BTreeSet;
 use std::io::ErrorKind;
@@ -13,16 +21,17 @@ use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use chrono::{DateTime, Utc};
-use sha2::{Digest, Sha256};
+use orchestra_renderer::{AgentKind, OutputStrategy};
 
 use orchestra_core::{
-    registry,
+    global, registry,
     types::{Codebase, ProjectName},
 };
-use orchestra_renderer::engine::{guide_path, pilot_path};
-use orchestra_renderer::AgentKind;
-
-use crate::{error::io_err, hash_store, SyncError};
+use crate::{
+    error::io_err,
+    hash_store::{self, FileMeta, HashStoreFile},
+    ignore, layout, writer, SyncError,
+};
 
 /// Phase 03 staleness classification for a codebase.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -34,48 +43,195 @@ pub enum StalenessSignal {
     Orphan { files: Vec<PathBuf> },
 }
 
+/// Staleness signal plus the managed files the user opted out of sync.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StalenessReport {
+    pub signal: StalenessSignal,
+    /// User-managed files (relative to the codebase), informational only.
+    pub ignored: Vec<PathBuf>,
+}
+
+/// Staleness of the files one agent owns, from [`check_detailed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentStaleness {
+    pub agent: AgentKind,
+    pub signal: StalenessSignal,
+}
+
 /// Check a codebase for staleness against registry metadata, hash store, and
 /// managed file presence.
+///
+/// Files whose size and mtime still match what sync recorded are assumed
+/// unmodified and are not read. Pass `deep` to hash every file regardless,
+/// which also catches edits that preserved the mtime.
 pub fn check(
     home: &Path,
     project: &ProjectName,
     codebase: &Codebase,
+    deep: bool,
 ) -> Result<StalenessSignal, SyncError> {
-    let managed = managed_paths(codebase);
-    let mut managed_keys = BTreeSet::new();
-    for path in &managed {
-        managed_keys.insert(path.to_string_lossy().to_string());
+    check_report(home, project, codebase, deep).map(|report| report.signal)
+}
+
+/// Like [`check`], also listing the user-managed files it skipped.
+pub fn check_report(
+    home: &Path,
+    project: &ProjectName,
+    codebase: &Codebase,
+    deep: bool,
+) -> Result<StalenessReport, SyncError> {
+    let managed = layout::all_managed_paths(codebase);
+    let ignored: BTreeSet<PathBuf> = managed
+        .iter()
+        .filter(|path| ignore::is_ignored(path))
+        .cloned()
+        .collect();
+
+    // The aggregate merges every agent's findings with those for the shared
+    // files, so the worst signal across agents wins.
+    let signal = match scan(home, project, codebase, deep, &ignored)? {
+        Scan::NeverSynced => StalenessSignal::NeverSynced,
+        Scan::Synced {
+            agents,
+            mut shared,
+            registry_changed,
+        } => {
+            for (_, findings) in agents {
+                shared.merge(findings);
+            }
+            shared.into_signal(registry_changed)
+        }
+    };
+
+    let mut ignored: Vec<PathBuf> = ignored
+        .iter()
+        .map(|path| relative_to_codebase(path, codebase))
+        .collect();
+    sort_and_dedup_paths(&mut ignored);
+    Ok(StalenessReport { signal, ignored })
+}
+
+/// Staleness per agent, each judged only on its own output files and their
+/// hash entries. The guide, pilot, extra outputs and manifest belong to no
+/// agent and only count towards [`check`].
+pub fn check_detailed(
+    home: &Path,
+    project: &ProjectName,
+    codebase: &Codebase,
+    deep: bool,
+) -> Result<Vec<AgentStaleness>, SyncError> {
+    let ignored: BTreeSet<PathBuf> = layout::all_managed_paths(codebase)
+        .into_iter()
+        .filter(|path| ignore::is_ignored(path))
+        .collect();
+    Ok(match scan(home, project, codebase, deep, &ignored)? {
+        Scan::NeverSynced => AgentKind::all()
+            .iter()
+            .map(|agent| AgentStaleness {
+                agent: *agent,
+                signal: StalenessSignal::NeverSynced,
+            })
+            .collect(),
+        Scan::Synced {
+            agents,
+            registry_changed,
+            ..
+        } => agents
+            .into_iter()
+            .map(|(agent, findings)| AgentStaleness {
+                agent,
+                signal: findings.into_signal(registry_changed),
+            })
+            .collect(),
+    })
+}
+
+/// Raw results of comparing managed files with the hash store.
+enum Scan {
+    NeverSynced,
+    Synced {
+        agents: Vec<(AgentKind, Findings)>,
+        /// The guide, pilot, extra outputs and manifest, plus stored paths no
+        /// longer managed.
+        shared: Findings,
+        /// The registry or global file, when it is newer than the last sync.
+        registry_changed: Option<RegistryChange>,
+    },
+}
+
+/// A registry input modified after the last sync.
+#[derive(Debug, Clone, Copy)]
+struct RegistryChange {
+    /// Named in the stale reason: `registry` or `global.yaml`.
+    what: &'static str,
+    mtime: SystemTime,
+}
+
+/// What a scan found for one group of files, before precedence is applied.
+#[derive(Default)]
+struct Findings {
+    missing: Vec<PathBuf>,
+    modified: Vec<PathBuf>,
+    orphan: Vec<PathBuf>,
+}
+
+impl Findings {
+    fn merge(&mut self, other: Findings) {
+        self.missing.extend(other.missing);
+        self.modified.extend(other.modified);
+        self.orphan.extend(other.orphan);
+    }
+
+    /// Apply the signal precedence from the module docs.
+    fn into_signal(mut self, registry_changed: Option<RegistryChange>) -> StalenessSignal {
+        if !self.missing.is_empty() {
+            sort_and_dedup_paths(&mut self.missing);
+            return StalenessSignal::Stale {
+                reason: format!(
+                    "missing {} managed file(s): {}",
+                    self.missing.len(),
+                    preview_files(&self.missing),
+                ),
+            };
+        }
+        if let Some(change) = registry_changed {
+            return StalenessSignal::Stale {
+                reason: format!(
+                    "{} changed {} ago",
+                    change.what,
+                    format_system_time_age(change.mtime)
+                ),
+            };
+        }
+        if !self.modified.is_empty() {
+            sort_and_dedup_paths(&mut self.modified);
+            return StalenessSignal::Modified {
+                files: self.modified,
+            };
+        }
+        if !self.orphan.is_empty() {
+            sort_and_dedup_paths(&mut self.orphan);
+            return StalenessSignal::Orphan { files: self.orphan };
+        }
+        StalenessSignal::Current
     }
+}
 
+fn scan(
+    home: &Path,
+    project: &ProjectName,
+    codebase: &Codebase,
+    deep: bool,
+    ignored: &BTreeSet<PathBuf>,
+) -> Result<Scan, SyncError> {
+    layout::check_codebase_root(codebase)?;
     // First-run handling: no hash file or no tracked hashes is "never synced",
     // not "stale".
     let store_path = hash_store::store_path_at(home, &codebase.name.0);
     let store_exists = store_path.exists();
-    let store = hash_store::load_at(home, &codebase.name.0)?;
+    let store = hash_store::peek_at(home, &codebase.name.0)?;
     if !store_exists || store.files.is_empty() {
-        return Ok(StalenessSignal::NeverSynced);
-    }
-
-    let mut missing = Vec::new();
-    for path in &managed {
-        match std::fs::metadata(path) {
-            Ok(_) => {}
-            Err(err) if err.kind() == ErrorKind::NotFound => {
-                missing.push(relative_to_codebase(path, codebase));
-            }
-            Err(err) => return Err(io_err(path, err)),
-        }
-    }
-
-    if !missing.is_empty() {
-        sort_and_dedup_paths(&mut missing);
-        return Ok(StalenessSignal::Stale {
-            reason: format!(
-                "missing {} managed file(s): {}",
-                missing.len(),
-                preview_files(&missing),
-            ),
-        });
+        return Ok(Scan::NeverSynced);
     }
 
     // Freshness is based on hash-store sync time, not rendered file mtimes.
@@ -84,55 +240,111 @@ pub fn check(
     let registry_mtime = registry_meta
         .modified()
         .map_err(|e| io_err(&registry_path, e))?;
-    let registry_ts = unix_duration(registry_mtime);
-    let synced_ts = datetime_to_unix_duration(store.synced_at);
-    if registry_ts > synced_ts {
-        return Ok(StalenessSignal::Stale {
-            reason: format!(
-                "registry changed {} ago",
-                format_system_time_age(registry_mtime)
-            ),
-        });
-    }
+    // global.yaml is rendered into every codebase, so editing it counts too.
+    let synced_at = datetime_to_unix_duration(store.synced_at);
+    let newer = |what, mtime: SystemTime| {
+        (unix_duration(mtime) > synced_at).then_some(RegistryChange { what, mtime })
+    };
+    let registry_changed = match newer("registry", registry_mtime) {
+        Some(change) => Some(change),
+        None => global_mtime(home)?.and_then(|mtime| newer(global::GLOBAL_FILE, mtime)),
+    };
 
-    let mut modified = Vec::new();
-    for path in &managed {
-        let key = path.to_string_lossy().to_string();
-        let Some(expected_hash) = store.files.get(&key) else {
-            continue;
-        };
-        let current_hash = hash_file(path)?;
-        if &current_hash != expected_hash {
-            modified.push(relative_to_codebase(path, codebase));
-        }
-    }
-    if !modified.is_empty() {
-        sort_and_dedup_paths(&mut modified);
-        return Ok(StalenessSignal::Modified { files: modified });
+    let mut agents = Vec::new();
+    for agent in AgentKind::all() {
+        let paths = contained_paths(codebase, layout::agent_output_paths(codebase, *agent))?;
+        agents.push((*agent, scan_paths(&store, codebase, deep, &paths, ignored)?));
     }
 
-    let mut orphan = Vec::new();
-    for path in &managed {
-        let key = path.to_string_lossy().to_string();
-        if path.exists() && !store.files.contains_key(&key) {
-            orphan.push(relative_to_codebase(path, codebase));
-        }
-    }
+    let mut shared_paths = vec![
+        layout::managed_guide_path(codebase),
+        layout::managed_pilot_path(codebase),
+    ];
+    shared_paths.extend(layout::extra_output_paths(codebase));
+    shared_paths.extend(layout::managed_manifest_path(codebase));
+    let shared_paths = contained_paths(codebase, shared_paths)?;
+    let mut shared = scan_paths(&store, codebase, deep, &shared_paths, ignored)?;
+
+    let managed_keys: BTreeSet<String> = layout::all_managed_paths(codebase)
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
     for key in store.files.keys() {
         if managed_keys.contains(key) {
             continue;
         }
         let path = PathBuf::from(key);
         if path.exists() {
-            orphan.push(relative_to_codebase(&path, codebase));
+            shared.orphan.push(relative_to_codebase(&path, codebase));
         }
     }
-    if !orphan.is_empty() {
-        sort_and_dedup_paths(&mut orphan);
-        return Ok(StalenessSignal::Orphan { files: orphan });
+
+    Ok(Scan::Synced {
+        agents,
+        shared,
+        registry_changed,
+    })
+}
+
+/// `paths` checked to stay inside the output root before they are read.
+fn contained_paths(codebase: &Codebase, paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, SyncError> {
+    paths
+        .iter()
+        .map(|path| layout::contained_path(codebase, path))
+        .collect()
+}
+
+/// Modification time of `~/.orchestra/global.yaml`, if it exists.
+fn global_mtime(home: &Path) -> Result<Option<SystemTime>, SyncError> {
+    let path = global::path_at(home);
+    match std::fs::metadata(&path) {
+        Ok(metadata) => metadata.modified().map(Some).map_err(|e| io_err(&path, e)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(io_err(&path, err)),
     }
+}
 
-    Ok(StalenessSignal::Current)
+/// Compare `paths` with their hash-store entries. User-managed files are
+/// neither modified nor orphaned.
+fn scan_paths(
+    store: &HashStoreFile,
+    codebase: &Codebase,
+    deep: bool,
+    paths: &[PathBuf],
+    ignored: &BTreeSet<PathBuf>,
+) -> Result<Findings, SyncError> {
+    let mut findings = Findings::default();
+    for path in paths {
+        let on_disk = match std::fs::metadata(path) {
+            Ok(metadata) => FileMeta::from_metadata(&metadata).ok(),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                findings.missing.push(relative_to_codebase(path, codebase));
+                continue;
+            }
+            Err(err) => return Err(io_err(path, err)),
+        };
+        if ignored.contains(path) {
+            continue;
+        }
+
+        let key = path.to_string_lossy().to_string();
+        let Some(expected_hash) = store.files.get(&key) else {
+            findings.orphan.push(relative_to_codebase(path, codebase));
+            continue;
+        };
+        let meta_unchanged = store
+            .files_meta
+            .get(&key)
+            .is_some_and(|recorded| on_disk.as_ref() == Some(recorded));
+        if !deep && meta_unchanged {
+            continue;
+        }
+        let current_hash = hash_file(path, layout::output_strategy(codebase, path))?;
+        if &current_hash != expected_hash {
+            findings.modified.push(relative_to_codebase(path, codebase));
+        }
+    }
+    Ok(findings)
 }
 
 /// Format age from a filesystem timestamp.
@@ -150,22 +362,9 @@ pub fn format_datetime_age(timestamp: DateTime<Utc>) -> String {
     format_seconds(age)
 }
 
-fn managed_paths(codebase: &Codebase) -> Vec<PathBuf> {
-    let mut paths = Vec::new();
-    for agent in AgentKind::all() {
-        paths.extend(agent.output_paths(&codebase.path));
-    }
-    paths.push(guide_path(&codebase.path));
-    paths.push(pilot_path(&codebase.path));
-    paths
-}
-
-fn hash_file(path: &Path) -> Result<String, SyncError> {
-    let content = std::fs::read_to_string(path).map_err(|e| io_err(path, e))?;
-    let normalized = content.replace("\r\n", "\n");
-    let mut hasher = Sha256::new();
-    hasher.update(normalized.as_bytes());
-    Ok(hex::encode(hasher.finalize()))
+/// Non-text content hashes without error and so reports as `Modified`.
+fn hash_file(path: &Path, strategy: OutputStrategy) -> Result<String, SyncError> {
+    writer::file_digest(path, strategy).map_err(|e| io_err(path, e))
 }
 
 fn unix_duration(timestamp: SystemTime) -> Duration {
@@ -178,9 +377,7 @@ fn datetime_to_unix_duration(timestamp: DateTime<Utc>) -> Duration {
 }
 
 fn relative_to_codebase(path: &Path, codebase: &Codebase) -> PathBuf {
-    path.strip_prefix(&codebase.path)
-        .unwrap_or(path)
-        .to_path_buf()
+    layout::display_relative(path, codebase)
 }
 
 fn format_duration(duration: Duration) -> String {
@@ -251,7 +448,7 @@ mod tests {
         )
         .expect("init");
 
-        sync_codebase("copnow_api", home.path(), false).expect("sync");
+        sync_codebase("copnow_api", home.path(), false, None).expect("sync");
         let (actual_project, codebase) = registry::list_codebases_at(home.path())
             .expect("list")
             .into_iter()
@@ -269,7 +466,7 @@ mod tests {
     #[test]
     fn returns_current_after_sync() {
         let (home, _workspace, _name, project, codebase) = setup_codebase();
-        let signal = check(home.path(), &project, &codebase).expect("check");
+        let signal = check(home.path(), &project, &codebase, false).expect("check");
         assert_eq!(signal, StalenessSignal::Current);
     }
 
@@ -293,7 +490,7 @@ mod tests {
             .into_iter()
             .next()
             .expect("codebase");
-        let signal = check(home.path(), &project, &codebase).expect("check");
+        let signal = check(home.path(), &project, &codebase, false).expect("check");
         assert_eq!(signal, StalenessSignal::NeverSynced);
     }
 
@@ -306,7 +503,7 @@ mod tests {
         let yaml = fs::read_to_string(&registry_path).expect("read");
         fs::write(&registry_path, yaml).expect("touch registry");
 
-        let signal = check(home.path(), &project, &codebase).expect("check");
+        let signal = check(home.path(), &project, &codebase, false).expect("check");
         match signal {
             StalenessSignal::Stale { reason } => assert!(reason.contains("registry")),
             other => panic!("expected stale, got {other:?}"),
@@ -319,7 +516,7 @@ mod tests {
         let target = codebase.path.join("orchestra/controls/CLAUDE.md");
         fs::write(&target, "manually edited\n").expect("edit");
 
-        let signal = check(home.path(), &project, &codebase).expect("check");
+        let signal = check(home.path(), &project, &codebase, false).expect("check");
         match signal {
             StalenessSignal::Modified { files } => {
                 assert!(files.iter().any(|p| p == &PathBuf::from("orchestra/controls/CLAUDE.md")));
@@ -328,6 +525,115 @@ mod tests {
         }
     }
 
+    #[test]
+    fn detailed_check_flags_only_the_edited_agent() {
+        let (home, _workspace, _name, project, codebase) = setup_codebase();
+        let cursor = layout::agent_output_paths(&codebase, AgentKind::Cursor)
+            .into_iter()
+            .next()
+            .expect("cursor output");
+        fs::write(&cursor, "hand edited rules\n").expect("edit");
+
+        let detailed = check_detailed(home.path(), &project, &codebase, false).expect("check");
+        assert_eq!(detailed.len(), AgentKind::all().len());
+        for entry in &detailed {
+            if entry.agent == AgentKind::Cursor {
+                assert_eq!(
+                    entry.signal,
+                    StalenessSignal::Modified {
+                        files: vec![layout::display_relative(&cursor, &codebase)],
+                    }
+                );
+            } else {
+                assert_eq!(entry.signal, StalenessSignal::Current, "{:?}", entry.agent);
+            }
+        }
+
+        let aggregate = check(home.path(), &project, &codebase, false).expect("check");
+        assert!(
+            matches!(aggregate, StalenessSignal::Modified { .. }),
+            "got {aggregate:?}"
+        );
+    }
+
+    #[test]
+    fn mtime_preserving_edit_is_caught_only_by_deep_check() {
+        let (home, _workspace, _name, project, codebase) = setup_codebase();
+        let target = codebase.path.join("orchestra/controls/CLAUDE.md");
+        let original = fs::read_to_string(&target).expect("read");
+        let mtime = filetime::FileTime::from_last_modification_time(
+            &fs::metadata(&target).expect("metadata"),
+        );
+
+        // Same length, different bytes, original mtime restored.
+        let edited: String = original
+            .chars()
+            .map(|c| if c == 'a' { 'b' } else { c })
+            .collect();
+        assert_ne!(edited, original);
+        fs::write(&target, &edited).expect("edit");
+        filetime::set_file_mtime(&target, mtime).expect("restore mtime");
+
+        let fast = check(home.path(), &project, &codebase, false).expect("check");
+        assert_eq!(fast, StalenessSignal::Current);
+        let deep = check(home.path(), &project, &codebase, true).expect("deep check");
+        assert!(matches!(deep, StalenessSignal::Modified { .. }), "got {deep:?}");
+    }
+
+    #[test]
+    fn non_utf8_managed_file_is_modified_not_an_error() {
+        let (home, _workspace, _name, project, codebase) = setup_codebase();
+        let target = codebase.path.join("orchestra/controls/CLAUDE.md");
+        fs::write(&target, b"\xff\xfe binary \x00 content").expect("write binary");
+
+        let signal = check(home.path(), &project, &codebase, false).expect("check");
+        match signal {
+            StalenessSignal::Modified { files } => {
+                assert_eq!(files, vec![PathBuf::from("orchestra/controls/CLAUDE.md")]);
+            }
+            other => panic!("expected Modified, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn very_large_managed_file_is_hashed() {
+        use std::io::Write as _;
+
+        let (home, _workspace, _name, project, codebase) = setup_codebase();
+        let target = layout::managed_pilot_path(&codebase);
+        let mut file = std::io::BufWriter::new(fs::File::create(&target).expect("create"));
+        let block = "orchestra pilot line\r\n".repeat(1024 * 1024 / 22);
+        for _ in 0..50 {
+            file.write_all(block.as_bytes()).expect("write block");
+        }
+        file.flush().expect("flush");
+        drop(file);
+        assert!(fs::metadata(&target).expect("metadata").len() >= 50 * 1_000_000);
+
+        let signal = check(home.path(), &project, &codebase, true).expect("deep check");
+        match signal {
+            StalenessSignal::Modified { files } => {
+                assert_eq!(files, vec![PathBuf::from("orchestra/pilot.md")]);
+            }
+            other => panic!("expected Modified, got {other:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fast_check_does_not_read_unchanged_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (home, _workspace, _name, project, codebase) = setup_codebase();
+        let target = codebase.path.join("orchestra/controls/CLAUDE.md");
+        // Unreadable, but size and mtime are untouched, so no read is needed.
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o000)).expect("chmod");
+
+        let signal = check(home.path(), &project, &codebase, false);
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o644)).expect("chmod back");
+        assert_eq!(signal.expect("check via metadata"), StalenessSignal::Current);
+    }
+
     #[test]
     fn returns_orphan_when_hash_entry_missing_for_existing_managed_file() {
         let (home, _workspace, name, project, codebase) = setup_codebase();
@@ -341,7 +647,7 @@ mod tests {
         );
         hash_store::save_at(home.path(), &name, &store).expect("save store");
 
-        let signal = check(home.path(), &project, &codebase).expect("check");
+        let signal = check(home.path(), &project, &codebase, false).expect("check");
         match signal {
             StalenessSignal::Orphan { files } => {
                 assert!(files.iter().any(|p| p == &PathBuf::from("orchestra/controls/CLAUDE.md")));
@@ -365,7 +671,7 @@ mod tests {
         let target = codebase.path.join("orchestra/controls/CLAUDE.md");
         fs::remove_file(&target).expect("remove");
 
-        let signal = check(home.path(), &project, &codebase).expect("check");
+        let signal = check(home.path(), &project, &codebase, false).expect("check");
         match signal {
             StalenessSignal::Stale { reason } => {
                 assert!(reason.contains("missing"));
@@ -387,7 +693,7 @@ mod tests {
             .insert(extra.to_string_lossy().to_string(), "deadbeef".to_string());
         hash_store::save_at(home.path(), &name, &store).expect("save");
 
-        let signal = check(home.path(), &project, &codebase).expect("check");
+        let signal = check(home.path(), &project, &codebase, false).expect("check");
         match signal {
             StalenessSignal::Orphan { files } => {
                 assert!(files.iter().any(|p| p == &PathBuf::from("legacy_agent.md")));
@@ -402,7 +708,7 @@ mod tests {
         let path = hash_store::store_path_at(home.path(), &name);
         fs::remove_file(&path).expect("delete store");
 
-        let signal = check(home.path(), &project, &codebase).expect("check");
+        let signal = check(home.path(), &project, &codebase, false).expect("check");
         assert_eq!(signal, StalenessSignal::NeverSynced);
     }
 
@@ -413,6 +719,10 @@ mod tests {
         let legacy = HashStoreFile {
             synced_at: Utc::now(),
             files: std::collections::HashMap::new(),
+            files_meta: std::collections::HashMap::new(),
+            rendered_from: None,
+            budget_overruns: Vec::new(),
+            templates: None,
         };
         fs::write(
             store_path,
@@ -420,7 +730,7 @@ mod tests {
         )
         .expect("write");
 
-        let signal = check(home.path(), &project, &codebase).expect("check");
+        let signal = check(home.path(), &project, &codebase, false).expect("check");
         assert_eq!(signal, StalenessSignal::NeverSynced);
     }
 }
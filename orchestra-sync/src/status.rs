@@ -0,0 +1,596 @@
+//! The `orchestra status` report.
+//!
+//! [`build_report`] runs the staleness checks for a set of registered
+//! codebases and summarises them. The report serializes to the schema that
+//! `orchestra status --json` prints, and deserializes back from it, so the
+//! daemon can compute a report that the CLI renders unchanged. Only the
+//! changed-file lists behind the markdown output are left out of the JSON.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use orchestra_core::types::{Codebase, ProjectName, Task, TaskStatus};
+
+use crate::{
+    detection::codebase_detection_outdated,
+    hash_store,
+    staleness::{check_detailed, check_report, format_datetime_age},
+    StalenessSignal, SyncError,
+};
+
+/// A [`StalenessSignal`] without its payload, keyed like the JSON `status`
+/// field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalKind {
+    NeverSynced,
+    Current,
+    Stale,
+    Modified,
+    Orphan,
+}
+
+impl SignalKind {
+    const ALL: [SignalKind; 5] = [
+        SignalKind::NeverSynced,
+        SignalKind::Stale,
+        SignalKind::Modified,
+        SignalKind::Orphan,
+        SignalKind::Current,
+    ];
+
+    /// Every signal, in the precedence order [`staleness`](crate::staleness)
+    /// applies when more than one holds.
+    pub fn all() -> &'static [SignalKind] {
+        const {
+            // `precedence` matches exhaustively, so a new variant needs a
+            // rank there; `Current` ranks last, so `ALL` must grow with it,
+            // and each entry must sit at its rank.
+            assert!(SignalKind::ALL.len() == SignalKind::Current.precedence() + 1);
+            let mut i = 0;
+            while i < SignalKind::ALL.len() {
+                assert!(SignalKind::ALL[i].precedence() == i);
+                i += 1;
+            }
+        }
+        &Self::ALL
+    }
+
+    /// Position in [`SignalKind::all`]. `Current` holds only when no other
+    /// signal does, so it always ranks last.
+    const fn precedence(self) -> usize {
+        match self {
+            SignalKind::NeverSynced => 0,
+            SignalKind::Stale => 1,
+            SignalKind::Modified => 2,
+            SignalKind::Orphan => 3,
+            SignalKind::Current => 4,
+        }
+    }
+
+    pub fn of(signal: &StalenessSignal) -> Self {
+        match signal {
+            StalenessSignal::NeverSynced => Self::NeverSynced,
+            StalenessSignal::Current => Self::Current,
+            StalenessSignal::Stale { .. } => Self::Stale,
+            StalenessSignal::Modified { .. } => Self::Modified,
+            StalenessSignal::Orphan { .. } => Self::Orphan,
+        }
+    }
+
+    /// The JSON `status` value, e.g. `never_synced`.
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::NeverSynced => "never_synced",
+            Self::Current => "current",
+            Self::Stale => "stale",
+            Self::Modified => "modified",
+            Self::Orphan => "orphan",
+        }
+    }
+
+    /// The table label, e.g. `NEVER SYNCED`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::NeverSynced => "NEVER SYNCED",
+            Self::Current => "CURRENT",
+            Self::Stale => "STALE",
+            Self::Modified => "MODIFIED",
+            Self::Orphan => "ORPHAN",
+        }
+    }
+
+    /// One sentence on what the signal means, for `orchestra help signals`.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::NeverSynced => "The codebase is registered but has never been synced.",
+            Self::Current => "Every generated file matches the registry and the last sync.",
+            Self::Stale => {
+                "The registry or `global.yaml` changed since the last sync, \
+                 or a managed file is missing."
+            }
+            Self::Modified => "A generated file was edited outside Orchestra since the last sync.",
+            Self::Orphan => "Files in the agent directories are not tracked by the last sync.",
+        }
+    }
+}
+
+/// Tasks per [`TaskStatus`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskCounts {
+    pub pending: usize,
+    pub in_progress: usize,
+    pub blocked: usize,
+    pub done: usize,
+    pub cancelled: usize,
+}
+
+impl TaskCounts {
+    pub fn of(codebase: &Codebase) -> Self {
+        Self::of_tasks(codebase.projects.iter().flat_map(|project| &project.tasks))
+    }
+
+    pub fn of_tasks<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> Self {
+        let mut counts = Self::default();
+        for task in tasks {
+            match task.status {
+                TaskStatus::Pending => counts.pending += 1,
+                TaskStatus::InProgress => counts.in_progress += 1,
+                TaskStatus::Blocked => counts.blocked += 1,
+                TaskStatus::Done => counts.done += 1,
+                TaskStatus::Cancelled => counts.cancelled += 1,
+                // Written by a newer orchestra; neither open nor closed here.
+                TaskStatus::Unknown(_) => {}
+            }
+        }
+        counts
+    }
+
+    pub fn add(&mut self, other: TaskCounts) {
+        self.pending += other.pending;
+        self.in_progress += other.in_progress;
+        self.blocked += other.blocked;
+        self.done += other.done;
+        self.cancelled += other.cancelled;
+    }
+
+    /// Tasks that are neither done nor cancelled.
+    pub fn active(&self) -> usize {
+        self.pending + self.in_progress + self.blocked
+    }
+}
+
+/// Codebases per staleness signal, keyed like the JSON `status` field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignalCounts {
+    pub current: usize,
+    pub stale: usize,
+    pub modified: usize,
+    pub orphan: usize,
+    pub never_synced: usize,
+}
+
+impl SignalCounts {
+    pub fn add(&mut self, kind: SignalKind) {
+        match kind {
+            SignalKind::Current => self.current += 1,
+            SignalKind::Stale => self.stale += 1,
+            SignalKind::Modified => self.modified += 1,
+            SignalKind::Orphan => self.orphan += 1,
+            SignalKind::NeverSynced => self.never_synced += 1,
+        }
+    }
+}
+
+/// One project's codebases and tasks, summed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectRollup {
+    pub project: String,
+    pub codebases: usize,
+    pub status: SignalCounts,
+    pub tasks: TaskCounts,
+}
+
+impl ProjectRollup {
+    /// e.g. `4 codebases · 1 stale · 12 active tasks (3 blocked)`.
+    pub fn summary_line(&self) -> String {
+        let mut line = format!(
+            "{} · {} stale · {}",
+            plural(self.codebases, "codebase"),
+            self.status.stale,
+            plural(self.tasks.active(), "active task")
+        );
+        if self.tasks.blocked > 0 {
+            line.push_str(&format!(" ({} blocked)", self.tasks.blocked));
+        }
+        line
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{count} {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+/// Report-wide counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusSummary {
+    pub projects: usize,
+    pub codebases: usize,
+    pub stale: usize,
+}
+
+/// Status of one codebase.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodebaseStatus {
+    pub project: String,
+    pub codebase: String,
+    pub status: SignalKind,
+    pub detail: String,
+    /// Modified or orphaned files behind the status, relative to the
+    /// codebase. Not part of the JSON schema.
+    #[serde(skip)]
+    pub files: Vec<String>,
+    /// User-managed files skipped by sync; omitted when there are none.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignored: Vec<String>,
+    pub last_sync_age: String,
+    pub last_sync_at: Option<String>,
+    pub active_tasks: usize,
+    /// Whether a project's detected stack may be outdated because its
+    /// indicator files changed since detection; omitted when not.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub detection_outdated: bool,
+    /// Per-agent breakdown; only present for detailed reports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agents: Option<Vec<AgentStatus>>,
+}
+
+/// Status of the files one agent owns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentStatus {
+    pub agent: String,
+    pub status: SignalKind,
+    pub detail: String,
+}
+
+/// The whole report, in the shape `orchestra status --json` prints.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusReport {
+    pub summary: StatusSummary,
+    pub codebases: Vec<CodebaseStatus>,
+    /// Per-project rollups; omitted when no codebases are listed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<ProjectRollup>,
+}
+
+impl StatusReport {
+    /// Codebases whose status is anything but current.
+    pub fn needs_sync_count(&self) -> usize {
+        self.codebases
+            .iter()
+            .filter(|row| row.status != SignalKind::Current)
+            .count()
+    }
+
+    /// Keep only the codebases of `project`, recounting the summary.
+    pub fn retain_project(&mut self, project: &str) {
+        self.codebases.retain(|row| row.project == project);
+        self.projects.retain(|rollup| rollup.project == project);
+        self.summary = summarize(&self.codebases);
+    }
+}
+
+/// Check every codebase in `codebases` and build the report, in the order
+/// given. `deep` hashes every managed file; `detailed` adds the per-agent
+/// breakdown.
+pub fn build_report(
+    home: &Path,
+    codebases: &[(ProjectName, Codebase)],
+    deep: bool,
+    detailed: bool,
+) -> Result<StatusReport, SyncError> {
+    let mut rows = Vec::new();
+    let mut tasks = Vec::new();
+    for (project, codebase) in codebases {
+        let report = check_report(home, project, codebase, deep)?;
+        let signal = report.signal;
+        let counts = TaskCounts::of(codebase);
+        let (last_sync_at, last_sync_age) = load_last_sync(home, &codebase.name.0)?;
+        let agents = if detailed {
+            let agents = check_detailed(home, project, codebase, deep)?;
+            Some(
+                agents
+                    .iter()
+                    .map(|entry| AgentStatus {
+                        agent: entry.agent.to_string(),
+                        status: SignalKind::of(&entry.signal),
+                        detail: signal_detail(&entry.signal),
+                    })
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        rows.push(CodebaseStatus {
+            project: project.0.clone(),
+            codebase: codebase.name.0.clone(),
+            status: SignalKind::of(&signal),
+            detail: with_ignored_note(signal_detail(&signal), &report.ignored),
+            files: display_paths(signal_files(&signal)),
+            ignored: display_paths(&report.ignored),
+            last_sync_age,
+            last_sync_at,
+            active_tasks: counts.active(),
+            detection_outdated: codebase_detection_outdated(codebase),
+            agents,
+        });
+        tasks.push(counts);
+    }
+
+    Ok(StatusReport {
+        summary: summarize(&rows),
+        projects: project_rollups(rows.iter().zip(tasks)),
+        codebases: rows,
+    })
+}
+
+fn summarize(rows: &[CodebaseStatus]) -> StatusSummary {
+    StatusSummary {
+        projects: rows
+            .iter()
+            .map(|row| row.project.as_str())
+            .collect::<BTreeSet<_>>()
+            .len(),
+        codebases: rows.len(),
+        stale: rows
+            .iter()
+            .filter(|row| row.status == SignalKind::Stale)
+            .count(),
+    }
+}
+
+/// Roll rows up per project, in project name order.
+fn project_rollups<'a>(
+    rows: impl IntoIterator<Item = (&'a CodebaseStatus, TaskCounts)>,
+) -> Vec<ProjectRollup> {
+    let mut rollups = BTreeMap::<&str, ProjectRollup>::new();
+    for (row, tasks) in rows {
+        let rollup = rollups
+            .entry(row.project.as_str())
+            .or_insert_with(|| ProjectRollup {
+                project: row.project.clone(),
+                codebases: 0,
+                status: SignalCounts::default(),
+                tasks: TaskCounts::default(),
+            });
+        rollup.codebases += 1;
+        rollup.status.add(row.status);
+        rollup.tasks.add(tasks);
+    }
+    rollups.into_values().collect()
+}
+
+fn load_last_sync(home: &Path, codebase_name: &str) -> Result<(Option<String>, String), SyncError> {
+    let path = hash_store::store_path_at(home, codebase_name);
+    if !path.exists() {
+        return Ok((None, "never".to_string()));
+    }
+    let store = hash_store::load_at(home, codebase_name)?;
+    if store.files.is_empty() {
+        return Ok((None, "never".to_string()));
+    }
+    let iso = Some(store.synced_at.to_rfc3339());
+    let age = format_datetime_age(store.synced_at);
+    Ok((iso, age))
+}
+
+/// One-line description of a signal, e.g. `AGENTS.md edited`.
+pub fn signal_detail(signal: &StalenessSignal) -> String {
+    match signal {
+        StalenessSignal::NeverSynced => "no hash store entries".to_string(),
+        StalenessSignal::Current => "up to date".to_string(),
+        StalenessSignal::Stale { reason } => reason.clone(),
+        StalenessSignal::Modified { files } => format!("{} edited", summarize_files(files)),
+        StalenessSignal::Orphan { files } => format!("{} untracked", summarize_files(files)),
+    }
+}
+
+/// The modified or orphaned files behind a signal.
+pub fn signal_files(signal: &StalenessSignal) -> &[PathBuf] {
+    match signal {
+        StalenessSignal::Modified { files } | StalenessSignal::Orphan { files } => files,
+        _ => &[],
+    }
+}
+
+/// Append the user-managed files to a status detail.
+fn with_ignored_note(detail: String, ignored: &[PathBuf]) -> String {
+    if ignored.is_empty() {
+        return detail;
+    }
+    format!(
+        "{detail}; {} ignored (user-managed)",
+        summarize_files(ignored)
+    )
+}
+
+fn summarize_files(files: &[PathBuf]) -> String {
+    if files.is_empty() {
+        return "unknown file".to_string();
+    }
+
+    let mut names: Vec<String> = files
+        .iter()
+        .take(2)
+        .map(|path| path.display().to_string())
+        .collect();
+    if files.len() > names.len() {
+        names.push(format!("+{} more", files.len() - names.len()));
+    }
+    names.join(", ")
+}
+
+fn display_paths(paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(project: &str, codebase: &str, status: SignalKind) -> CodebaseStatus {
+        CodebaseStatus {
+            project: project.to_string(),
+            codebase: codebase.to_string(),
+            status,
+            detail: String::new(),
+            files: Vec::new(),
+            ignored: Vec::new(),
+            last_sync_age: "never".to_string(),
+            last_sync_at: None,
+            active_tasks: 0,
+            detection_outdated: false,
+            agents: None,
+        }
+    }
+
+    #[test]
+    fn project_rollups_sum_signals_and_task_statuses() {
+        let counts = |pending, in_progress, blocked, done| TaskCounts {
+            pending,
+            in_progress,
+            blocked,
+            done,
+            ..Default::default()
+        };
+        let api = row("copnow", "api", SignalKind::Current);
+        let web = row("copnow", "web", SignalKind::Stale);
+        let tools = row("infra", "tools", SignalKind::NeverSynced);
+
+        let rollups = project_rollups([
+            (&web, counts(0, 3, 2, 1)),
+            (&tools, counts(1, 0, 0, 0)),
+            (&api, counts(2, 1, 1, 4)),
+        ]);
+        assert_eq!(
+            rollups,
+            vec![
+                ProjectRollup {
+                    project: "copnow".to_string(),
+                    codebases: 2,
+                    status: SignalCounts {
+                        current: 1,
+                        stale: 1,
+                        ..Default::default()
+                    },
+                    tasks: counts(2, 4, 3, 5),
+                },
+                ProjectRollup {
+                    project: "infra".to_string(),
+                    codebases: 1,
+                    status: SignalCounts {
+                        never_synced: 1,
+                        ..Default::default()
+                    },
+                    tasks: counts(1, 0, 0, 0),
+                },
+            ]
+        );
+        assert_eq!(
+            rollups[0].summary_line(),
+            "2 codebases · 1 stale · 9 active tasks (3 blocked)"
+        );
+        assert_eq!(
+            rollups[1].summary_line(),
+            "1 codebase · 0 stale · 1 active task"
+        );
+
+        let json = serde_json::to_value(&rollups[0]).expect("serialize rollup");
+        assert_eq!(json["status"]["stale"], 1);
+        assert_eq!(json["tasks"]["in_progress"], 4);
+    }
+
+    #[test]
+    fn task_counts_report_cancelled_separately_from_active() {
+        let codebase: Codebase = serde_yaml::from_str(
+            "name: api
+path: /code/api
+projects:
+  - name: api
+    project_type: backend
+    tasks:
+      - { id: t-1, title: Open, status: pending, created_at: 2026-01-01T00:00:00Z, updated_at: 2026-01-01T00:00:00Z }
+      - { id: t-2, title: Dropped, status: cancelled, created_at: 2026-01-01T00:00:00Z, updated_at: 2026-01-01T00:00:00Z }
+      - { id: t-3, title: Future, status: archived, created_at: 2026-01-01T00:00:00Z, updated_at: 2026-01-01T00:00:00Z }
+created_at: 2026-01-01T00:00:00Z
+updated_at: 2026-01-01T00:00:00Z
+",
+        )
+        .expect("parse codebase");
+
+        let counts = TaskCounts::of(&codebase);
+        assert_eq!(counts.pending, 1);
+        assert_eq!(counts.cancelled, 1);
+        assert_eq!(counts.active(), 1);
+        let json = serde_json::to_value(counts).expect("serialize counts");
+        assert_eq!(json["cancelled"], 1);
+    }
+
+    #[test]
+    fn report_round_trips_through_json_and_filters_by_project() {
+        let mut modified = row("copnow", "web", SignalKind::Modified);
+        modified.files = vec!["AGENTS.md".to_string()];
+        let rows = vec![
+            row("copnow", "api", SignalKind::Stale),
+            modified,
+            row("infra", "tools", SignalKind::Current),
+        ];
+        let mut report = StatusReport {
+            summary: summarize(&rows),
+            projects: project_rollups(rows.iter().map(|row| (row, TaskCounts::default()))),
+            codebases: rows,
+        };
+        assert_eq!(
+            report.summary,
+            StatusSummary {
+                projects: 2,
+                codebases: 3,
+                stale: 1,
+            }
+        );
+        assert_eq!(report.needs_sync_count(), 2);
+
+        let json = serde_json::to_value(&report).expect("serialize report");
+        assert_eq!(json["codebases"][0]["status"], "stale");
+        assert!(json["codebases"][1].get("files").is_none());
+        let decoded: StatusReport = serde_json::from_value(json).expect("decode report");
+        report.codebases[1].files.clear();
+        assert_eq!(decoded, report);
+
+        report.retain_project("infra");
+        assert_eq!(report.codebases.len(), 1);
+        assert_eq!(report.projects.len(), 1);
+        assert_eq!(
+            report.summary,
+            StatusSummary {
+                projects: 1,
+                codebases: 1,
+                stale: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,290 @@
+//! Template upgrades — the changes a new template set alone would make.
+//!
+//! After an Orchestra upgrade brings new embedded templates, or an override
+//! in `~/.orchestra/templates/` changes, [`plan_codebase`] lists the
+//! generated files that still hold what the last sync wrote (their content
+//! matches the hash store) but render differently now. Files with local
+//! edits, missing files and files sync never wrote are left out, since their
+//! differences are not the templates' doing.
+//! [`pipeline::run_template_upgrade`](crate::pipeline::run_template_upgrade)
+//! writes exactly those files, plus the manifest listing them.
+//!
+//! Every full sync records the template-set fingerprint in the hash store,
+//! so a codebase last synced with the current templates is reported
+//! unchanged without rendering.
+
+use std::path::{Path, PathBuf};
+
+use orchestra_core::{
+    global, registry,
+    types::{Codebase, ProjectName},
+};
+use orchestra_renderer::{
+    render_within_budget, skills, user_template_dir_at, AgentKind, OutputStrategy, Renderer,
+};
+
+use crate::diff::{
+    diff_existing, normalize_line_endings, read_existing_or_empty, DEFAULT_CONTEXT_LINES,
+};
+use crate::writer::{
+    build_sync_context, find_codebase_at, matches_stored, render_extra_outputs, resolve_output,
+    validate_extra_outputs,
+};
+use crate::{hash_store, ignore, layout, lockfile, FileDiff, SyncError};
+
+/// One file a template upgrade would rewrite.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateChange {
+    /// The agent the file belongs to; `None` for the shared guide, pilot and
+    /// extra outputs.
+    pub agent: Option<AgentKind>,
+    pub diff: FileDiff,
+}
+
+/// What a template upgrade would change in a codebase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateUpgrade {
+    pub codebase_name: String,
+    /// The last sync used the current template set, so nothing was rendered.
+    pub templates_unchanged: bool,
+    pub changes: Vec<TemplateChange>,
+}
+
+impl TemplateUpgrade {
+    /// Agents with at least one changed file, in [`AgentKind::all`] order.
+    pub fn agents(&self) -> Vec<AgentKind> {
+        AgentKind::all()
+            .iter()
+            .copied()
+            .filter(|agent| self.changes.iter().any(|c| c.agent == Some(*agent)))
+            .collect()
+    }
+
+    /// Whether the shared guide, pilot or an extra output would change.
+    pub fn shared_changed(&self) -> bool {
+        self.changes.iter().any(|change| change.agent.is_none())
+    }
+}
+
+/// [`plan_codebase`] for every registered codebase, in registry order.
+pub fn plan_all(home: &Path) -> Result<Vec<TemplateUpgrade>, SyncError> {
+    registry::list_codebases_at(home)?
+        .into_iter()
+        .map(|(_, codebase)| plan_codebase(&codebase.name.0, home))
+        .collect()
+}
+
+/// The files in `codebase_name` that a template upgrade would rewrite, with
+/// their diffs. No files are written.
+pub fn plan_codebase(codebase_name: &str, home: &Path) -> Result<TemplateUpgrade, SyncError> {
+    let (project, codebase) = find_codebase_at(home, codebase_name)?;
+    layout::check_codebase_root(&codebase)?;
+    validate_extra_outputs(&codebase)?;
+    let renderer = Renderer::cached(Some(&user_template_dir_at(home)))?;
+    plan_with(home, &renderer, &project, &codebase)
+}
+
+fn plan_with(
+    home: &Path,
+    renderer: &Renderer,
+    project: &ProjectName,
+    codebase: &Codebase,
+) -> Result<TemplateUpgrade, SyncError> {
+    let codebase_name = codebase.name.0.as_str();
+    let _lock = lockfile::lock_shared_at(home, codebase_name)?;
+    let store_existed = hash_store::store_path_at(home, codebase_name).exists();
+    let store = hash_store::peek_at(home, codebase_name)?;
+    let mut upgrade = TemplateUpgrade {
+        codebase_name: codebase_name.to_string(),
+        templates_unchanged: false,
+        changes: Vec::new(),
+    };
+    if store.templates.as_deref() == Some(renderer.fingerprint()) {
+        upgrade.templates_unchanged = true;
+        return Ok(upgrade);
+    }
+
+    // Rendered as the next sync would, with `meta.last_synced` from the store.
+    let mut ctx = build_sync_context(project, codebase, false, store_existed, store.synced_at);
+    ctx.apply_global(&global::load_at(home)?);
+    ctx.apply_skill_catalog(&skills::load_catalog(home)?);
+
+    let mut outputs: Vec<(Option<AgentKind>, PathBuf, String, OutputStrategy)> = Vec::new();
+    let enforce_budget = codebase.enforce_budget == Some(true);
+    for agent in AgentKind::all() {
+        let rendered = render_within_budget(renderer, &ctx, *agent, enforce_budget)?.outputs;
+        let targets = layout::agent_output_paths(codebase, *agent);
+        for (((_, content), path), strategy) in rendered
+            .into_iter()
+            .zip(targets)
+            .zip(agent.output_strategies())
+        {
+            outputs.push((Some(*agent), path, content, strategy));
+        }
+    }
+    let shared = [
+        (
+            layout::managed_guide_path(codebase),
+            renderer.render_guide(&ctx)?.1,
+        ),
+        (
+            layout::managed_pilot_path(codebase),
+            renderer.render_pilot(&ctx)?.1,
+        ),
+    ];
+    for (path, content) in shared
+        .into_iter()
+        .chain(render_extra_outputs(renderer, &ctx, codebase)?)
+    {
+        outputs.push((None, path, content, OutputStrategy::Replace));
+    }
+
+    for (agent, path, rendered, strategy) in outputs {
+        let path = layout::contained_path(codebase, &path)?;
+        if ignore::is_ignored(&path) || !matches_stored(&path, strategy, &store)? {
+            continue;
+        }
+        let rendered = normalize_line_endings(&resolve_output(&path, &rendered, strategy));
+        let existing = read_existing_or_empty(codebase, &path)?;
+        if let Some(diff) =
+            diff_existing(path, &existing, &rendered, codebase, DEFAULT_CONTEXT_LINES)
+        {
+            upgrade.changes.push(TemplateChange { agent, diff });
+        }
+    }
+    Ok(upgrade)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use orchestra_core::types::ProjectType;
+    use tempfile::TempDir;
+
+    use crate::pipeline::{run_template_upgrade, SyncScope};
+    use crate::sync_codebase;
+
+    use super::*;
+
+    fn init_copnow_api(home: &TempDir, workspace: &TempDir) -> PathBuf {
+        let codebase_dir = workspace.path().join("copnow_api");
+        fs::create_dir_all(&codebase_dir).expect("mkdir");
+        registry::init_at(
+            codebase_dir.clone(),
+            ProjectName::from("copnow"),
+            Some(ProjectType::Backend),
+            home.path(),
+        )
+        .expect("init");
+        sync_codebase("copnow_api", home.path(), false, None).expect("sync");
+        codebase_dir
+    }
+
+    fn override_cline_template(home: &Path) {
+        let template = AgentKind::Cline.template_names()[0];
+        let path = user_template_dir_at(home).join(template);
+        fs::create_dir_all(path.parent().unwrap()).expect("mkdir templates");
+        fs::write(&path, "upgraded cline rules\n").expect("write override");
+    }
+
+    /// Plan with a renderer of its own and report how many renders it took.
+    fn counted_plan(home: &Path) -> (TemplateUpgrade, u64) {
+        let (project, codebase) = find_codebase_at(home, "copnow_api").expect("find");
+        let renderer =
+            Renderer::with_user_templates(Some(&user_template_dir_at(home))).expect("renderer");
+        let plan = plan_with(home, &renderer, &project, &codebase).expect("plan");
+        (plan, renderer.render_count())
+    }
+
+    fn upgrade(home: &Path) {
+        let outcomes =
+            run_template_upgrade(home, SyncScope::Codebase("copnow_api".to_string()), |_| {})
+                .expect("upgrade");
+        outcomes.into_iter().for_each(|o| {
+            o.result.expect("upgrade codebase");
+        });
+    }
+
+    #[test]
+    fn unchanged_templates_are_reported_without_rendering() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_copnow_api(&home, &workspace);
+
+        let (plan, renders) = counted_plan(home.path());
+        assert!(plan.templates_unchanged);
+        assert_eq!(renders, 0);
+
+        // A store from before the fingerprint was recorded renders, but
+        // finds nothing to change.
+        let mut store = hash_store::load_at(home.path(), "copnow_api").expect("store");
+        store.templates = None;
+        hash_store::save_at(home.path(), "copnow_api", &store).expect("save");
+        let (plan, renders) = counted_plan(home.path());
+        assert!(!plan.templates_unchanged && plan.changes.is_empty());
+        assert!(renders > 0);
+    }
+
+    #[test]
+    fn planning_leaves_a_corrupt_hash_store_in_place() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        init_copnow_api(&home, &workspace);
+        let store = hash_store::store_path_at(home.path(), "copnow_api");
+        fs::write(&store, "{").expect("corrupt store");
+
+        // With no recorded hashes nothing counts as safe to upgrade.
+        let plan = plan_codebase("copnow_api", home.path()).expect("plan");
+        assert!(plan.changes.is_empty(), "{plan:?}");
+        assert_eq!(fs::read_to_string(&store).expect("store"), "{");
+    }
+
+    #[test]
+    fn template_change_is_planned_and_applied() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        let codebase_dir = init_copnow_api(&home, &workspace);
+        let cline = codebase_dir.join("orchestra/controls/.clinerules/orchestra.md");
+        override_cline_template(home.path());
+
+        let plan = plan_codebase("copnow_api", home.path()).expect("plan");
+        assert_eq!(plan.agents(), vec![AgentKind::Cline]);
+        assert!(!plan.shared_changed());
+        assert_eq!(plan.changes[0].diff.path, cline);
+        assert!(plan.changes[0]
+            .diff
+            .unified_diff
+            .contains("+upgraded cline rules"));
+
+        upgrade(home.path());
+        assert_eq!(
+            fs::read_to_string(&cline).expect("read cline"),
+            "upgraded cline rules\n"
+        );
+        assert!(
+            plan_codebase("copnow_api", home.path())
+                .expect("plan")
+                .templates_unchanged
+        );
+    }
+
+    #[test]
+    fn locally_edited_files_are_left_alone() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        let codebase_dir = init_copnow_api(&home, &workspace);
+        let cline = codebase_dir.join("orchestra/controls/.clinerules/orchestra.md");
+        fs::write(&cline, "hand-written rules\n").expect("edit cline");
+        override_cline_template(home.path());
+
+        let plan = plan_codebase("copnow_api", home.path()).expect("plan");
+        assert!(plan.changes.is_empty(), "{plan:?}");
+
+        upgrade(home.path());
+        assert_eq!(
+            fs::read_to_string(&cline).expect("read cline"),
+            "hand-written rules\n"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Reverse lookup from a file to the codebase that generates it.
+//!
+//! [`find_managed_at`] compares a path with every registered codebase's
+//! [`layout::all_managed_paths`], so it recognises exactly the files a sync
+//! writes, whether or not they exist on disk yet.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use orchestra_core::{
+    registry,
+    types::{Codebase, ProjectName},
+};
+use orchestra_renderer::AgentKind;
+
+use crate::{error::io_err, hash_store, layout, staleness, StalenessSignal, SyncError};
+
+/// A file some registered codebase generates.
+#[derive(Debug, Clone)]
+pub struct ManagedFile {
+    pub project: ProjectName,
+    pub codebase: Codebase,
+    /// The agent the file is written for; `None` for the guide, pilot,
+    /// extra outputs and manifest.
+    pub agent: Option<AgentKind>,
+    /// The resolved path that matched.
+    pub path: PathBuf,
+    /// Staleness of the agent's files, or of the whole codebase for files
+    /// that belong to no agent.
+    pub signal: StalenessSignal,
+    /// When the codebase was last synced; `None` if it never was.
+    pub last_synced: Option<DateTime<Utc>>,
+}
+
+/// The codebase that generates `path`, or `None` when no registered
+/// codebase writes it. A relative `path` is resolved against the current
+/// directory and symlinks are followed, like the paths `init` registers.
+pub fn find_managed_at(home: &Path, path: &Path) -> Result<Option<ManagedFile>, SyncError> {
+    let candidates = resolve(path)?;
+    for (project, codebase) in registry::list_codebases_at(home)? {
+        let Some(path) = layout::all_managed_paths(&codebase)
+            .into_iter()
+            .find(|managed| candidates.contains(managed))
+        else {
+            continue;
+        };
+        let agent = layout::managed_output_paths(&codebase)
+            .into_iter()
+            .find(|(_, managed)| *managed == path)
+            .map(|(agent, _)| agent);
+        let signal = match agent {
+            Some(agent) => staleness::check_detailed(home, &project, &codebase, false)?
+                .into_iter()
+                .find(|staleness| staleness.agent == agent)
+                .map_or(StalenessSignal::NeverSynced, |staleness| staleness.signal),
+            None => staleness::check(home, &project, &codebase, false)?,
+        };
+        let last_synced = last_synced(home, &codebase)?;
+        return Ok(Some(ManagedFile {
+            project,
+            codebase,
+            agent,
+            path,
+            signal,
+            last_synced,
+        }));
+    }
+    Ok(None)
+}
+
+/// `path` made absolute, plus its symlink-free form. A file that does not
+/// exist yet is resolved through its parent directory.
+fn resolve(path: &Path) -> Result<Vec<PathBuf>, SyncError> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map_err(|e| io_err(".", e))?
+            .join(path)
+    };
+    let canonical = absolute.canonicalize().ok().or_else(|| {
+        let parent = absolute.parent()?.canonicalize().ok()?;
+        Some(parent.join(absolute.file_name()?))
+    });
+    Ok(canonical.into_iter().chain([absolute]).collect())
+}
+
+fn last_synced(home: &Path, codebase: &Codebase) -> Result<Option<DateTime<Utc>>, SyncError> {
+    if !hash_store::store_path_at(home, &codebase.name.0).exists() {
+        return Ok(None);
+    }
+    let store = hash_store::peek_at(home, &codebase.name.0)?;
+    Ok((!store.files.is_empty()).then_some(store.synced_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use orchestra_core::types::ProjectType;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::sync_codebase;
+
+    fn synced_codebase(home: &TempDir, workspace: &TempDir) -> PathBuf {
+        let dir = workspace.path().join("copnow_api");
+        fs::create_dir_all(&dir).expect("mkdir");
+        registry::init_at(
+            dir.clone(),
+            ProjectName::from("copnow"),
+            Some(ProjectType::Backend),
+            home.path(),
+        )
+        .expect("init");
+        sync_codebase("copnow_api", home.path(), false, None).expect("sync");
+        dir.canonicalize().expect("canonical")
+    }
+
+    #[test]
+    fn resolves_agent_outputs_and_shared_files() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        let dir = synced_codebase(&home, &workspace);
+
+        let claude = layout::agent_output_paths(
+            &registry::list_codebases_at(home.path()).expect("list")[0].1,
+            AgentKind::Claude,
+        )[0]
+        .clone();
+        let found = find_managed_at(home.path(), &claude)
+            .expect("lookup")
+            .expect("managed");
+        assert_eq!(found.project, ProjectName::from("copnow"));
+        assert_eq!(found.codebase.name.0, "copnow_api");
+        assert_eq!(found.agent, Some(AgentKind::Claude));
+        assert_eq!(found.signal, StalenessSignal::Current);
+        assert!(found.last_synced.is_some());
+
+        let pilot = find_managed_at(home.path(), &dir.join("orchestra/pilot.md"))
+            .expect("lookup")
+            .expect("managed");
+        assert_eq!(pilot.agent, None);
+    }
+
+    #[test]
+    fn reports_edits_and_rejects_unmanaged_files() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        let dir = synced_codebase(&home, &workspace);
+
+        let agents_md = dir.join("orchestra/controls/AGENTS.md");
+        fs::write(&agents_md, "hand edit\n").expect("edit");
+        let found = find_managed_at(home.path(), &agents_md)
+            .expect("lookup")
+            .expect("managed");
+        assert_eq!(found.agent, Some(AgentKind::Codex));
+        assert!(
+            matches!(found.signal, StalenessSignal::Modified { .. }),
+            "{:?}",
+            found.signal
+        );
+
+        fs::write(dir.join("README.md"), "# api\n").expect("readme");
+        for unmanaged in [dir.join("README.md"), dir.join("missing.md")] {
+            assert!(find_managed_at(home.path(), &unmanaged)
+                .expect("lookup")
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn lookup_leaves_a_corrupt_hash_store_in_place() {
+        let home = TempDir::new().expect("home");
+        let workspace = TempDir::new().expect("workspace");
+        let dir = synced_codebase(&home, &workspace);
+        let store = hash_store::store_path_at(home.path(), "copnow_api");
+        fs::write(&store, "{").expect("corrupt store");
+
+        let found = find_managed_at(home.path(), &dir.join("orchestra/controls/AGENTS.md"))
+            .expect("lookup")
+            .expect("managed");
+        assert_eq!(found.signal, StalenessSignal::NeverSynced);
+        assert_eq!(found.last_synced, None);
+        assert_eq!(fs::read_to_string(&store).expect("store"), "{");
+    }
+}
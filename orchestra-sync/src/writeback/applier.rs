@@ -1,5 +1,7 @@
 //! Applier — applies parsed [`WritebackCommand`]s to a [`Codebase`] struct.
 
+use std::collections::BTreeMap;
+
 use chrono::Utc;
 use orchestra_core::types::{Codebase, Project, ProjectName, ProjectType, Skill, Task, TaskId, TaskStatus};
 
@@ -45,7 +47,7 @@ pub fn reconcile_task_snapshot(codebase: &mut Codebase, tasks: &[TaskSnapshot])
                 task.status = snapshot.status.clone();
                 changed = true;
             }
-            if task.description != snapshot.description {
+            if !snapshot.description_elided && task.description != snapshot.description {
                 task.description = snapshot.description.clone();
                 changed = true;
             }
@@ -63,8 +65,10 @@ pub fn reconcile_task_snapshot(codebase: &mut Codebase, tasks: &[TaskSnapshot])
             description: snapshot.description.clone(),
             subtasks: vec![],
             notes: vec![],
+            assigned_agent: None,
             created_at: now,
             updated_at: now,
+            extra: BTreeMap::new(),
         });
         changed = true;
     }
@@ -274,8 +278,12 @@ fn ensure_default_project(codebase: &mut Codebase) {
         codebase.projects.push(Project {
             name: ProjectName::from(codebase.name.0.clone()),
             project_type: ProjectType::Backend,
+            framework: None,
+            detection: None,
             tasks: vec![],
             agents: vec![],
+            commands: BTreeMap::new(),
+            extra: BTreeMap::new(),
         });
     }
 }
@@ -297,7 +305,7 @@ mod tests {
     use chrono::Utc;
     use orchestra_core::types::{
         Codebase, CodebaseName, Project, ProjectName, ProjectType, Subtask, Task, TaskId,
-        TaskStatus,
+        TaskStatus, CURRENT_SCHEMA_VERSION,
     };
     use std::path::PathBuf;
 
@@ -313,8 +321,10 @@ mod tests {
                 done: false,
             }],
             notes: vec![],
+            assigned_agent: None,
             created_at: now,
             updated_at: now,
+            extra: BTreeMap::new(),
         }
     }
 
@@ -326,15 +336,29 @@ mod tests {
             projects: vec![Project {
                 name: ProjectName::from("default"),
                 project_type: ProjectType::Backend,
+                framework: None,
+                detection: None,
                 tasks: vec![make_task("T-1")],
                 agents: vec![],
+                commands: BTreeMap::new(),
+                extra: BTreeMap::new(),
             }],
             conventions: vec![],
             skills: vec![],
             notes: vec![],
             tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: BTreeMap::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             created_at: now,
             updated_at: now,
+            extra: BTreeMap::new(),
         }
     }
 
@@ -376,12 +400,14 @@ mod tests {
                     title: "Rename task".to_owned(),
                     status: TaskStatus::InProgress,
                     description: Some("updated".to_owned()),
+                    description_elided: false,
                 },
                 TaskSnapshot {
                     task_id: "T-2".to_owned(),
                     title: "New task".to_owned(),
                     status: TaskStatus::Pending,
                     description: None,
+                    description_elided: false,
                 },
             ],
         );
@@ -393,6 +419,31 @@ mod tests {
         assert_eq!(codebase.projects[0].tasks[1].id.0, "T-2");
     }
 
+    #[test]
+    fn reconcile_keeps_descriptions_elided_by_a_trimmed_file() {
+        let mut codebase = make_codebase();
+        codebase.projects[0].tasks[0].description = Some("full write-up".to_owned());
+        let snapshot = |status| TaskSnapshot {
+            task_id: "T-1".to_owned(),
+            title: "Task T-1".to_owned(),
+            status,
+            description: None,
+            description_elided: true,
+        };
+
+        assert!(!reconcile_task_snapshot(
+            &mut codebase,
+            &[snapshot(TaskStatus::Pending)]
+        ));
+        assert!(reconcile_task_snapshot(
+            &mut codebase,
+            &[snapshot(TaskStatus::Blocked)]
+        ));
+        let task = &codebase.projects[0].tasks[0];
+        assert_eq!(task.status, TaskStatus::Blocked);
+        assert_eq!(task.description.as_deref(), Some("full write-up"));
+    }
+
     #[test]
     fn applies_subtask_done() {
         let mut codebase = make_codebase();
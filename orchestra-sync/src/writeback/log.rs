@@ -6,6 +6,8 @@
 use std::io::Write as _;
 use std::path::Path;
 
+use orchestra_core::paths;
+
 use crate::error::{io_err, SyncError};
 
 /// A loggable event from the writeback pipeline.
@@ -35,10 +37,7 @@ pub struct WritebackEvent<'a> {
 ///
 /// Creates the log file (and parent directory) if absent.
 pub fn log_event(home: &Path, event: &WritebackEvent<'_>) -> Result<(), SyncError> {
-    let log_path = home
-        .join(".orchestra")
-        .join("logs")
-        .join("sync-events.log");
+    let log_path = paths::dirs_at(home).logs_dir.join("sync-events.log");
 
     // Ensure logs directory exists.
     if let Some(parent) = log_path.parent() {
@@ -13,12 +13,13 @@ use std::path::Path;
 
 use chrono::Utc;
 use orchestra_core::registry;
-use orchestra_renderer::engine::{guide_path, pilot_path};
-use orchestra_renderer::AgentKind;
 
 use crate::{
     error::SyncError,
     hash_store,
+    history::SyncSource,
+    layout,
+    lockfile,
     pipeline,
     pipeline::SyncScope,
     writeback::types::WritebackCommand,
@@ -153,8 +154,16 @@ pub fn process_writeback(home: &Path, agent_file: &Path) -> Result<WritebackOutc
         apply_hash_store_lifecycle(home, &codebase_name, &parse_result.commands, &apply_results)?;
     }
 
-    // 6. Strip update block atomically.
-    let block_stripped = if update_block.is_some() {
+    // 6. Strip update block atomically, unless the file cannot be safely
+    // rewritten for this codebase.
+    let writable = match check_agent_file(&codebase, agent_file) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!("writeback: not rewriting {}: {}", agent_file.display(), e);
+            false
+        }
+    };
+    let block_stripped = if writable && update_block.is_some() {
         match strip_update_block(agent_file) {
             Ok(()) => true,
             Err(e) => {
@@ -168,7 +177,7 @@ pub fn process_writeback(home: &Path, agent_file: &Path) -> Result<WritebackOutc
 
     // 7. Inject error block atomically when needed.
     let all_parse_errors = merge_parse_errors(parse_result.errors.clone(), task_parse_result.errors.clone());
-    let error_block_written = if !all_parse_errors.is_empty() {
+    let error_block_written = if writable && !all_parse_errors.is_empty() {
         match write_error_block_messages(
             agent_file,
             &all_parse_errors.iter().map(|error| error.to_string()).collect::<Vec<_>>(),
@@ -185,7 +194,13 @@ pub fn process_writeback(home: &Path, agent_file: &Path) -> Result<WritebackOutc
 
     // 8. Run full sync pipeline.
     if should_save_registry {
-        if let Err(e) = pipeline::run(home, SyncScope::Codebase(codebase_name.clone()), false) {
+        if let Err(e) = pipeline::run_from(
+            home,
+            SyncScope::Codebase(codebase_name.clone()),
+            false,
+            None,
+            SyncSource::Daemon,
+        ) {
         tracing::warn!(
             "writeback: sync pipeline failed after apply for {}: {}",
             codebase_name,
@@ -250,6 +265,7 @@ fn apply_hash_store_lifecycle(
     commands: &[WritebackCommand],
     apply_results: &[self::types::ApplyResult],
 ) -> Result<(), SyncError> {
+    let _lock = lockfile::lock_exclusive_at(home, codebase_name)?;
     let mut store = hash_store::load_at(home, codebase_name)?;
     let mut changed = false;
 
@@ -270,6 +286,7 @@ fn apply_hash_store_lifecycle(
             }
             WritebackCommand::FileDeleted { path } => {
                 let key = path.to_string_lossy().to_string();
+                store.files_meta.remove(&key);
                 if store.files.remove(&key).is_some() {
                     changed = true;
                 }
@@ -289,6 +306,20 @@ fn apply_hash_store_lifecycle(
 // Internal: codebase lookup
 // ---------------------------------------------------------------------------
 
+/// Refuse to rewrite `agent_file` for a codebase rooted at `/` or a home
+/// directory, or through a symlinked directory that leads out of the output
+/// root. Files a `codebase_hint` maps from elsewhere are rewritten in place.
+fn check_agent_file(
+    codebase: &orchestra_core::types::Codebase,
+    agent_file: &Path,
+) -> Result<(), SyncError> {
+    layout::check_codebase_root(codebase)?;
+    if agent_file.starts_with(layout::output_root(codebase)) {
+        layout::contained_path(codebase, agent_file)?;
+    }
+    Ok(())
+}
+
 /// Find which registered codebase owns `agent_file` by checking
 /// `AgentKind::output_paths` for each codebase.
 fn find_owning_codebase(
@@ -313,14 +344,11 @@ fn find_owning_codebase(
     }
 
     for (project_name, codebase) in all {
-        let codebase_root = &codebase.path;
-        for agent_kind in AgentKind::all() {
-            for output_path in agent_kind.output_paths(codebase_root) {
-                let canonical_output = std::fs::canonicalize(&output_path)
-                    .unwrap_or_else(|_| output_path.clone());
-                if canonical_agent == canonical_output {
-                    return Some((project_name.clone(), codebase.clone()));
-                }
+        for (_, output_path) in layout::managed_output_paths(codebase) {
+            let canonical_output = std::fs::canonicalize(&output_path)
+                .unwrap_or_else(|_| output_path.clone());
+            if canonical_agent == canonical_output {
+                return Some((project_name.clone(), codebase.clone()));
             }
         }
     }
@@ -334,13 +362,13 @@ pub fn managed_agent_paths(
 ) -> Vec<std::path::PathBuf> {
     let mut paths = Vec::new();
     for (_project, codebase) in all {
-        for agent_kind in AgentKind::all() {
-            for output_path in agent_kind.output_paths(&codebase.path) {
-                paths.push(output_path);
-            }
-        }
-        paths.push(guide_path(&codebase.path));
-        paths.push(pilot_path(&codebase.path));
+        // The manifest carries no writeback blocks, so it is not watched.
+        let manifest = layout::managed_manifest_path(codebase);
+        paths.extend(
+            layout::all_managed_paths(codebase)
+                .into_iter()
+                .filter(|path| Some(path) != manifest.as_ref()),
+        );
     }
     paths
 }
@@ -355,7 +383,10 @@ mod tests {
     use chrono::Utc;
     use orchestra_core::{
         registry,
-        types::{CodebaseName, ProjectName, ProjectType, Task, TaskId, TaskStatus},
+        types::{
+            CodebaseName, ProjectName, ProjectType, Task, TaskId, TaskStatus,
+            CURRENT_SCHEMA_VERSION,
+        },
     };
     use std::fs;
     use tempfile::TempDir;
@@ -379,7 +410,7 @@ mod tests {
         setup(&home, &workspace, "test_cb");
 
         // Run initial sync so agent files exist
-        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false)
+        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false, None)
             .expect("initial sync");
 
         let agent_file = workspace.path().join("test_cb").join("orchestra/controls/CLAUDE.md");
@@ -395,7 +426,7 @@ mod tests {
         let workspace = TempDir::new().unwrap();
         setup(&home, &workspace, "test_cb");
 
-        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false)
+        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false, None)
             .expect("initial sync");
 
         let agent_file = workspace.path().join("test_cb").join("orchestra/controls/CLAUDE.md");
@@ -429,7 +460,7 @@ mod tests {
         let workspace = TempDir::new().unwrap();
         setup(&home, &workspace, "test_cb");
 
-        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false)
+        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false, None)
             .expect("initial sync");
 
         let agent_file = workspace.path().join("test_cb").join("orchestra/controls/CLAUDE.md");
@@ -456,7 +487,7 @@ mod tests {
         let workspace = TempDir::new().unwrap();
         setup(&home, &workspace, "test_cb");
 
-        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false)
+        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false, None)
             .expect("initial sync");
 
         let agent_file = workspace.path().join("test_cb").join("orchestra/controls/CLAUDE.md");
@@ -495,14 +526,20 @@ mod tests {
             description: None,
             subtasks: vec![],
             notes: vec![],
+            assigned_agent: None,
             created_at: now,
             updated_at: now,
+            extra: std::collections::BTreeMap::new(),
         };
         let project = orchestra_core::types::Project {
             name: orchestra_core::types::ProjectName::from("test_cb"),
             project_type: ProjectType::Backend,
+            framework: None,
+            detection: None,
             tasks: vec![task],
             agents: vec![],
+            commands: std::collections::BTreeMap::new(),
+            extra: std::collections::BTreeMap::new(),
         };
         let codebase = orchestra_core::types::Codebase {
             name: CodebaseName::from("test_cb"),
@@ -512,8 +549,18 @@ mod tests {
             skills: vec![],
             notes: vec![],
             tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: std::collections::BTreeMap::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             created_at: now,
             updated_at: now,
+            extra: std::collections::BTreeMap::new(),
         };
         registry::save_codebase_at(home.path(), &ProjectName::from("copnow"), &codebase)
             .expect("save");
@@ -523,7 +570,7 @@ mod tests {
         registry::save_codebase_at(home.path(), &ProjectName::from("copnow"), &codebase)
             .expect("save again");
 
-        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false)
+        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false, None)
             .expect("initial sync");
 
         let agent_file = cb_dir.join("orchestra/controls/CLAUDE.md");
@@ -560,7 +607,7 @@ mod tests {
         )
         .expect("init");
 
-        pipeline::run(home.path(), SyncScope::Codebase("task_block_cb".to_owned()), false)
+        pipeline::run(home.path(), SyncScope::Codebase("task_block_cb".to_owned()), false, None)
             .expect("initial sync");
 
         let claude_file = cb_dir.join("orchestra/controls/CLAUDE.md");
@@ -594,7 +641,7 @@ mod tests {
         let workspace = TempDir::new().unwrap();
         setup(&home, &workspace, "test_cb");
 
-        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false)
+        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false, None)
             .expect("initial sync");
 
         let agent_file = workspace.path().join("test_cb").join("orchestra/controls/CLAUDE.md");
@@ -647,7 +694,7 @@ mod tests {
         let workspace = TempDir::new().unwrap();
         setup(&home, &workspace, "test_cb");
 
-        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false)
+        pipeline::run(home.path(), SyncScope::Codebase("test_cb".to_owned()), false, None)
             .expect("initial sync");
 
         let file = home.path().join("delegated-output.md");
@@ -674,4 +721,33 @@ mod tests {
             .iter()
             .any(|entry| entry == "Delegated writeback convention"));
     }
+
+    #[test]
+    fn process_writeback_leaves_files_of_an_unsafe_codebase_untouched() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        setup(&home, &workspace, "test_cb");
+        let project = ProjectName::from("copnow");
+        let name = CodebaseName::from("test_cb");
+        let mut codebase = registry::load_codebase_at(home.path(), &project, &name).unwrap();
+        codebase.path = std::path::PathBuf::from("/");
+        registry::save_codebase_at(home.path(), &project, &codebase).unwrap();
+
+        let file = workspace.path().join("delegated-output.md");
+        let content = [
+            "Header",
+            "<!-- orchestra:update -->",
+            "codebase_hint: test_cb",
+            "bogus_command: nope",
+            "<!-- /orchestra:update -->",
+        ]
+        .join("\n");
+        fs::write(&file, &content).expect("write delegated file");
+
+        let outcome = process_writeback(home.path(), &file).expect("process");
+        assert!(outcome.block_found);
+        assert!(!outcome.block_stripped);
+        assert!(!outcome.error_block_written);
+        assert_eq!(fs::read_to_string(&file).unwrap(), content);
+    }
 }
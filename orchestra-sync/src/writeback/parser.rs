@@ -17,6 +17,7 @@
 use std::path::PathBuf;
 
 use orchestra_core::types::TaskStatus;
+use orchestra_renderer::budget;
 
 use crate::writeback::types::{ParseError, ParseResult, TaskParseResult, TaskSnapshot, WritebackCommand};
 
@@ -159,10 +160,14 @@ fn parse_task_line(line: &str) -> Result<TaskSnapshot, String> {
     let task_id = cells[0].trim();
     let title = cells[1].trim();
     let status_raw = cells[2].trim();
+    let description_elided = cells
+        .get(3)
+        .is_some_and(|cell| cell.trim() == budget::ELIDED_DESCRIPTION);
     let description = cells
         .get(3)
         .map(|cell| cell.trim())
         .filter(|cell| !cell.is_empty() && *cell != "-" && !cell.eq_ignore_ascii_case("none"))
+        .filter(|_| !description_elided)
         .map(|cell| cell.to_string());
 
     if task_id.is_empty() {
@@ -178,6 +183,7 @@ fn parse_task_line(line: &str) -> Result<TaskSnapshot, String> {
         title: title.to_owned(),
         status,
         description,
+        description_elided,
     })
 }
 
@@ -189,8 +195,9 @@ fn parse_task_status(value: &str) -> Result<TaskStatus, String> {
         }
         "blocked" => Ok(TaskStatus::Blocked),
         "done" | "complete" | "completed" => Ok(TaskStatus::Done),
+        "cancelled" | "canceled" => Ok(TaskStatus::Cancelled),
         other => Err(format!(
-            "unknown task status '{other}'. Supported: pending, in_progress, blocked, done"
+            "unknown task status '{other}'. Supported: pending, in_progress, blocked, done, cancelled"
         )),
     }
 }
@@ -398,15 +405,19 @@ mod tests {
             "|---|---|---|---|",
             "| T-1 | Ship onboarding | pending | polish prompt |",
             "| T-2 | Review writeback | in_progress | - |",
+            "| T-3 | Trimmed | pending | … |",
         ]
         .join("\n");
 
         let result = parse_task_block(&block);
         assert!(result.errors.is_empty());
-        assert_eq!(result.tasks.len(), 2);
+        assert_eq!(result.tasks.len(), 3);
         assert_eq!(result.tasks[0].task_id, "T-1");
         assert_eq!(result.tasks[1].status, TaskStatus::InProgress);
         assert_eq!(result.tasks[0].description.as_deref(), Some("polish prompt"));
+        assert!(!result.tasks[1].description_elided);
+        assert_eq!(result.tasks[2].description, None);
+        assert!(result.tasks[2].description_elided);
     }
 
     #[test]
@@ -45,6 +45,9 @@ pub struct TaskSnapshot {
     pub title: String,
     pub status: TaskStatus,
     pub description: Option<String>,
+    /// The description cell held the placeholder of a file trimmed to its
+    /// token budget, so the registry's description is kept.
+    pub description_elided: bool,
 }
 
 // ---------------------------------------------------------------------------
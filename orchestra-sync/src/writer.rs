@@ -2,41 +2,124 @@
 //!
 //! ## `atomic_write` — 7-step protocol
 //!
+//! Files carrying the [`ignore`](crate::ignore) marker are skipped before
+//! step 2.
+//!
 //! 1. Render content (already done by caller).
 //! 2. SHA-256 hash the rendered content.
 //! 3. Load the hash store.
 //! 4. Compare with stored hash → skip if identical.
-//! 5. Write to `<path>.orchestra.tmp`.
-//! 6. Rename to final path (atomic on POSIX).
-//! 7. Update hash store entry + save store.
+//! 5. Write to `<path>.orchestra.tmp`, copying the destination's permission
+//!    bits onto it, and fsync it.
+//! 6. Rename to final path (atomic on POSIX), then fsync the parent directory.
+//! 7. Update hash store entry (digest plus size and mtime) + save store.
+//!
+//! Before step 5, a file whose on-disk content no longer matches the stored
+//! digest is copied aside by [`edit_backup`](crate::edit_backup).
 
+use std::io::{Read, Write as _};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 use chrono::Utc;
+use serde::Serialize;
 use sha2::{Digest, Sha256};
 
 use orchestra_core::{
-    registry,
+    global, registry,
     types::{Codebase, CodebaseName, ProjectName},
 };
-use orchestra_renderer::{AgentKind, Renderer, TemplateContext};
+use orchestra_renderer::engine::{MANAGED_REGION_BEGIN, MANAGED_REGION_END};
+use orchestra_renderer::{
+    render_within_budget, skills, user_template_dir_at, AgentKind, BudgetOverrun, OutputStrategy,
+    Renderer, TemplateContext,
+};
 
-use crate::error::{io_err, SyncError};
-use crate::hash_store;
+use crate::edit_backup::BackupTarget;
+use crate::error::{fsync_err, io_err, SyncError};
+use crate::hash_store::{self, FileMeta, HashStore, HashStoreFile};
+use crate::history::{self, SyncSource};
+use crate::hooks::{self, HookKind};
+use crate::ignore;
+use crate::layout;
+use crate::lockfile;
+use crate::manifest;
+use crate::pipeline::{self, SyncProgress, SyncScope};
+use crate::render_cache;
 
 // ---------------------------------------------------------------------------
 // Write result
 // ---------------------------------------------------------------------------
 
-/// Outcome of an individual file write.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Outcome of an individual file write. Serializes with a snake_case
+/// `state` tag, e.g. `{"state": "unchanged", "path": "..."}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
 pub enum WriteResult {
     /// File was written (content changed or did not previously exist).
-    Written { path: PathBuf },
+    /// `backed_up` holds the copy of local edits it replaced, if any.
+    Written {
+        path: PathBuf,
+        backed_up: Option<PathBuf>,
+    },
     /// File was skipped — rendered content matches the stored hash.
     Unchanged { path: PathBuf },
     /// `--dry-run` mode: the file *would* have been written.
     WouldWrite { path: PathBuf },
+    /// File was skipped — it carries the `orchestra:ignore` marker and is
+    /// maintained by the user.
+    Ignored { path: PathBuf },
+}
+
+impl WriteResult {
+    /// The file the write was for.
+    pub fn path(&self) -> &Path {
+        match self {
+            WriteResult::Written { path, .. }
+            | WriteResult::Unchanged { path }
+            | WriteResult::WouldWrite { path }
+            | WriteResult::Ignored { path } => path,
+        }
+    }
+
+    /// The serialized `state` tag: `written`, `unchanged`, `would_write` or
+    /// `ignored`.
+    pub fn state(&self) -> &'static str {
+        match self {
+            WriteResult::Written { .. } => "written",
+            WriteResult::Unchanged { .. } => "unchanged",
+            WriteResult::WouldWrite { .. } => "would_write",
+            WriteResult::Ignored { .. } => "ignored",
+        }
+    }
+}
+
+/// Which rendered files a sync may write.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WriteFilter {
+    /// Every file whose rendered content differs from its stored hash.
+    #[default]
+    All,
+    /// Only files still holding what the last sync wrote (their content
+    /// matches the stored hash), which a changed template set would rewrite.
+    /// Any other file is reported [`WriteResult::Unchanged`] and keeps its
+    /// hash-store entry.
+    TemplateChanges,
+}
+
+impl WriteFilter {
+    /// Whether a sync with this filter may write `path`.
+    fn allows(
+        self,
+        path: &Path,
+        strategy: OutputStrategy,
+        store: &HashStoreFile,
+    ) -> Result<bool, SyncError> {
+        match self {
+            WriteFilter::All => Ok(true),
+            WriteFilter::TemplateChanges => matches_stored(path, strategy, store),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -48,40 +131,53 @@ pub enum WriteResult {
 /// The hash store is loaded before the call; the caller is responsible for
 /// saving it after all files for a codebase are processed.
 ///
+/// With `backups` set, local edits are saved there before being overwritten.
+/// `strategy` decides which part of the file is hashed (see [`output_digest`]).
+///
 /// Returns [`WriteResult`] indicating whether the file was written or skipped.
 pub(crate) fn atomic_write(
     path: &Path,
     content: &str,
-    hash_store: &mut hash_store::HashStore,
+    strategy: OutputStrategy,
+    store: &mut HashStoreFile,
     dry_run: bool,
+    backups: Option<&BackupTarget>,
 ) -> Result<WriteResult, SyncError> {
     let tmp = PathBuf::from(format!("{}.orchestra.tmp", path.display()));
-    atomic_write_with_tmp(path, content, hash_store, dry_run, &tmp)
+    atomic_write_with_tmp(path, content, strategy, store, dry_run, backups, &tmp)
 }
 
 fn atomic_write_with_tmp(
     path: &Path,
     content: &str,
-    hash_store: &mut hash_store::HashStore,
+    strategy: OutputStrategy,
+    store: &mut HashStoreFile,
     dry_run: bool,
+    backups: Option<&BackupTarget>,
     tmp: &Path,
 ) -> Result<WriteResult, SyncError> {
+    if ignore::is_ignored(path) {
+        tracing::debug!("ignored (user-managed): {}", path.display());
+        return Ok(WriteResult::Ignored {
+            path: path.to_path_buf(),
+        });
+    }
+
     // Normalise line endings to LF before hashing and writing.
     let normalized = content.replace("\r\n", "\n");
     let content = normalized.as_str();
 
-    // Step 2: hash the normalised content.
-    let digest = {
-        let mut h = Sha256::new();
-        h.update(content.as_bytes());
-        hex::encode(h.finalize())
-    };
+    // Step 2: hash the normalised content (only the managed region, if any).
+    let digest = output_digest(content, strategy);
 
     // Step 4: compare with stored hash.
     let key = path.to_string_lossy().to_string();
-    if let Some(stored) = hash_store.get(&key) {
-        if stored == &digest && disk_content_matches_digest(path, &digest)? {
+    if let Some(stored) = store.files.get(&key) {
+        if stored == &digest && disk_content_matches_digest(path, &digest, strategy)? {
             tracing::debug!("unchanged: {}", path.display());
+            if !dry_run {
+                record_file_meta(store, path, key)?;
+            }
             return Ok(WriteResult::Unchanged {
                 path: path.to_path_buf(),
             });
@@ -95,6 +191,11 @@ fn atomic_write_with_tmp(
         });
     }
 
+    let backed_up = match backups {
+        Some(backups) => back_up_local_edits(path, &key, strategy, store, backups)?,
+        None => None,
+    };
+
     // Step 5: ensure parent directory exists, write to .tmp.
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| io_err(parent, e))?;
@@ -103,31 +204,310 @@ fn atomic_write_with_tmp(
     if let Some(tmp_parent) = tmp.parent() {
         std::fs::create_dir_all(tmp_parent).map_err(|e| io_err(tmp_parent, e))?;
     }
-    std::fs::write(tmp, content).map_err(|e| io_err(tmp, e))?;
+    if let Err(e) = write_synced_tmp(tmp, content, path) {
+        let _ = std::fs::remove_file(tmp);
+        return Err(e);
+    }
 
-    // Step 6: atomic rename to final path.
+    // Step 6: atomic rename to final path, then persist the directory entry.
     if let Err(e) = std::fs::rename(tmp, path) {
         let _ = std::fs::remove_file(tmp);
         return Err(io_err(path, e));
     }
+    sync_parent_dir(path)?;
 
     // Step 7: update hash store entry (caller saves the store).
-    hash_store.insert(key, digest);
+    store.files.insert(key.clone(), digest);
+    record_file_meta(store, path, key)?;
 
     tracing::info!("wrote: {}", path.display());
     Ok(WriteResult::Written {
         path: path.to_path_buf(),
+        backed_up,
     })
 }
 
-fn disk_content_matches_digest(path: &Path, expected_digest: &str) -> Result<bool, SyncError> {
+/// Copy `path` aside if its content differs from the digest recorded when
+/// Orchestra last wrote it. Writeback-tracked files (empty digest) and files
+/// Orchestra never wrote are not backed up.
+fn back_up_local_edits(
+    path: &Path,
+    key: &str,
+    strategy: OutputStrategy,
+    store: &HashStoreFile,
+    backups: &BackupTarget,
+) -> Result<Option<PathBuf>, SyncError> {
+    let Some(stored) = store.files.get(key).filter(|digest| !digest.is_empty()) else {
+        return Ok(None);
+    };
+    let Ok(existing) = std::fs::read_to_string(path) else {
+        return Ok(None);
+    };
+    if &output_digest(&existing, strategy) == stored {
+        return Ok(None);
+    }
+    let backup = backups.save(path, &existing)?;
+    tracing::warn!(
+        "overwriting locally-modified {}; previous version saved to {}",
+        path.display(),
+        backup.display()
+    );
+    Ok(Some(backup))
+}
+
+/// SHA-256 hex digest of `content` with line endings normalised to LF, as
+/// recorded in the hash store.
+pub(crate) fn content_digest(content: &str) -> String {
+    let mut h = Sha256::new();
+    h.update(content.replace("\r\n", "\n").as_bytes());
+    hex::encode(h.finalize())
+}
+
+/// Digest recorded for a file written with `strategy`: for
+/// [`OutputStrategy::ManagedRegion`] only the marked region counts, so notes
+/// the user keeps outside it never look like local edits.
+pub(crate) fn output_digest(content: &str, strategy: OutputStrategy) -> String {
+    match (strategy, find_managed_region(content)) {
+        (OutputStrategy::ManagedRegion, ManagedRegion::Found(region)) => {
+            content_digest(&content[region])
+        }
+        _ => content_digest(content),
+    }
+}
+
+/// [`output_digest`] of the file at `path`, for files that may not be text.
+///
+/// Content that is not UTF-8 is hashed as raw bytes, with CRLF still folded
+/// to LF, so it never matches a recorded digest. Whole-file digests are
+/// computed while streaming; only [`OutputStrategy::ManagedRegion`] files
+/// are read into memory to find the region.
+pub(crate) fn file_digest(path: &Path, strategy: OutputStrategy) -> std::io::Result<String> {
+    if strategy != OutputStrategy::ManagedRegion {
+        return stream_digest(std::fs::File::open(path)?);
+    }
+    let bytes = std::fs::read(path)?;
+    match std::str::from_utf8(&bytes) {
+        Ok(content) => Ok(output_digest(content, strategy)),
+        Err(_) => stream_digest(bytes.as_slice()),
+    }
+}
+
+/// [`content_digest`] over bytes read from `reader` in chunks.
+fn stream_digest(mut reader: impl Read) -> std::io::Result<String> {
+    let mut h = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    // A `\r` ending the previous chunk, held back in case `\n` starts this one.
+    let mut pending_cr = false;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let chunk = &buf[..n];
+        if std::mem::take(&mut pending_cr) && chunk[0] != b'\n' {
+            h.update(b"\r");
+        }
+        let mut start = 0;
+        for (i, &byte) in chunk.iter().enumerate() {
+            if byte != b'\r' {
+                continue;
+            }
+            if i + 1 == n {
+                h.update(&chunk[start..i]);
+                start = n;
+                pending_cr = true;
+            } else if chunk[i + 1] == b'\n' {
+                h.update(&chunk[start..i]);
+                start = i + 1;
+            }
+        }
+        h.update(&chunk[start..]);
+    }
+    if pending_cr {
+        h.update(b"\r");
+    }
+    Ok(hex::encode(h.finalize()))
+}
+
+/// Remember `path`'s size and mtime so staleness checks can skip hashing it.
+fn record_file_meta(store: &mut HashStoreFile, path: &Path, key: String) -> Result<(), SyncError> {
+    let meta = FileMeta::read(path).map_err(|e| io_err(path, e))?;
+    store.files_meta.insert(key, meta);
+    Ok(())
+}
+
+/// Write `content` to `tmp` and flush it to disk before it is renamed over
+/// `dest`. If `dest` already exists its permission bits are carried over so a
+/// rewrite does not reset them to the process default.
+pub(crate) fn write_synced_tmp(tmp: &Path, content: &str, dest: &Path) -> Result<(), SyncError> {
+    let mut file = std::fs::File::create(tmp).map_err(|e| io_err(tmp, e))?;
+    file.write_all(content.as_bytes()).map_err(|e| io_err(tmp, e))?;
+    if let Ok(existing) = std::fs::metadata(dest) {
+        file.set_permissions(existing.permissions())
+            .map_err(|e| io_err(tmp, e))?;
+    }
+    file.sync_all().map_err(|e| fsync_err(tmp, e))
+}
+
+/// Fsync the directory containing `path` so the rename itself survives a
+/// crash. Directories cannot be opened for syncing on Windows; skip there.
+#[cfg(unix)]
+pub(crate) fn sync_parent_dir(path: &Path) -> Result<(), SyncError> {
+    let Some(parent) = path.parent() else {
+        return Ok(());
+    };
+    let dir = std::fs::File::open(parent).map_err(|e| io_err(parent, e))?;
+    dir.sync_all().map_err(|e| fsync_err(parent, e))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn sync_parent_dir(_path: &Path) -> Result<(), SyncError> {
+    Ok(())
+}
+
+/// `true` when `path` still holds what sync last wrote there: it has a
+/// recorded digest (not a writeback-tracked empty one) that its content on
+/// disk matches.
+pub(crate) fn matches_stored(
+    path: &Path,
+    strategy: OutputStrategy,
+    store: &HashStoreFile,
+) -> Result<bool, SyncError> {
+    match store.files.get(path.to_string_lossy().as_ref()) {
+        Some(digest) if !digest.is_empty() => disk_content_matches_digest(path, digest, strategy),
+        _ => Ok(false),
+    }
+}
+
+fn disk_content_matches_digest(
+    path: &Path,
+    expected_digest: &str,
+    strategy: OutputStrategy,
+) -> Result<bool, SyncError> {
     let Ok(existing) = std::fs::read_to_string(path) else {
         return Ok(false);
     };
-    let normalized = existing.replace("\r\n", "\n");
-    let mut hasher = Sha256::new();
-    hasher.update(normalized.as_bytes());
-    Ok(hex::encode(hasher.finalize()) == expected_digest)
+    Ok(output_digest(&existing, strategy) == expected_digest)
+}
+
+// ---------------------------------------------------------------------------
+// Output strategies
+// ---------------------------------------------------------------------------
+
+/// Reconcile rendered content with the file currently on disk.
+///
+/// For [`OutputStrategy::JsonMerge`] the rendered JSON is deep-merged into the
+/// existing document so user-written keys survive. The merged document is what
+/// gets hashed and written, which keeps repeated syncs idempotent.
+///
+/// For [`OutputStrategy::ManagedRegion`] the rendered region is spliced into
+/// the existing file between its markers.
+pub(crate) fn resolve_output(path: &Path, rendered: &str, strategy: OutputStrategy) -> String {
+    match strategy {
+        OutputStrategy::Replace => rendered.to_string(),
+        OutputStrategy::JsonMerge => merge_json_output(path, rendered),
+        OutputStrategy::ManagedRegion => merge_managed_region(path, rendered),
+    }
+}
+
+/// Where the Orchestra-owned region sits in a file.
+enum ManagedRegion {
+    /// Byte range from the start of the begin marker to the end of the end marker.
+    Found(Range<usize>),
+    /// No begin marker.
+    Missing,
+    /// A begin marker with no end marker after it.
+    Unterminated,
+}
+
+fn find_managed_region(text: &str) -> ManagedRegion {
+    let Some(start) = text.find(MANAGED_REGION_BEGIN) else {
+        return ManagedRegion::Missing;
+    };
+    match text[start..].find(MANAGED_REGION_END) {
+        Some(offset) => ManagedRegion::Found(start..start + offset + MANAGED_REGION_END.len()),
+        None => ManagedRegion::Unterminated,
+    }
+}
+
+fn merge_managed_region(path: &Path, rendered: &str) -> String {
+    // A user template override may drop the markers; then there is nothing
+    // to splice and the file is replaced as before.
+    let ManagedRegion::Found(managed) = find_managed_region(rendered) else {
+        return rendered.to_string();
+    };
+    let Ok(existing) = std::fs::read_to_string(path) else {
+        return rendered.to_string();
+    };
+
+    match find_managed_region(&existing) {
+        ManagedRegion::Found(region) => format!(
+            "{}{}{}",
+            &existing[..region.start],
+            &rendered[managed],
+            &existing[region.end..]
+        ),
+        ManagedRegion::Unterminated => {
+            tracing::warn!(
+                "{} has `{MANAGED_REGION_BEGIN}` but no `{MANAGED_REGION_END}`, replacing the whole file",
+                path.display()
+            );
+            rendered.to_string()
+        }
+        ManagedRegion::Missing => rendered.to_string(),
+    }
+}
+
+fn merge_json_output(path: &Path, rendered: &str) -> String {
+    let Ok(managed) = serde_json::from_str::<serde_json::Value>(rendered) else {
+        tracing::warn!(
+            "rendered output is not valid JSON, replacing wholesale: {}",
+            path.display()
+        );
+        return rendered.to_string();
+    };
+
+    let merged = match std::fs::read_to_string(path) {
+        Ok(existing) => match serde_json::from_str::<serde_json::Value>(&existing) {
+            Ok(mut existing) if existing.is_object() => {
+                deep_merge_json(&mut existing, managed);
+                existing
+            }
+            _ => {
+                tracing::warn!(
+                    "existing JSON is corrupt or not an object, replacing: {}",
+                    path.display()
+                );
+                managed
+            }
+        },
+        Err(_) => managed,
+    };
+
+    match serde_json::to_string_pretty(&merged) {
+        Ok(json) => format!("{json}\n"),
+        Err(_) => rendered.to_string(),
+    }
+}
+
+/// Recursively merge `managed` into `target`. Objects merge key-by-key;
+/// any other value in `managed` overwrites the corresponding `target` value.
+fn deep_merge_json(target: &mut serde_json::Value, managed: serde_json::Value) {
+    match (target, managed) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(managed)) => {
+            for (key, value) in managed {
+                match target.get_mut(&key) {
+                    Some(existing) => deep_merge_json(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, managed) => *target = managed,
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -135,107 +515,604 @@ fn disk_content_matches_digest(path: &Path, expected_digest: &str) -> Result<boo
 // ---------------------------------------------------------------------------
 
 pub(crate) fn build_sync_context(
+    project: &ProjectName,
     codebase: &Codebase,
     dry_run: bool,
     store_existed: bool,
     store_synced_at: chrono::DateTime<Utc>,
 ) -> TemplateContext {
-    let mut ctx = TemplateContext::from_codebase(codebase);
-    ctx.meta.last_synced = if dry_run {
-        None
-    } else if store_existed {
-        Some(store_synced_at)
-    } else {
-        None
-    };
-    ctx
+    TemplateContext::builder(codebase)
+        .project(project)
+        .last_synced((!dry_run && store_existed).then_some(store_synced_at))
+        .build()
 }
 
+/// Look up a registered codebase by name. Other codebases' registry files
+/// that cannot be read do not stop a codebase that loads from being found.
+/// Otherwise the lookup fails with the error of its own file, or of any
+/// unreadable file, since that may be the one it is in; with every file
+/// readable a missing name fails with [`SyncError::CodebaseNotFound`].
 pub(crate) fn find_codebase_at(
     home: &Path,
     codebase_name: &str,
 ) -> Result<(ProjectName, Codebase), SyncError> {
     let name = CodebaseName::from(codebase_name);
-    let all = registry::list_codebases_at(home)?;
-    all.into_iter()
+    let listing = registry::list_codebases_lossy_at(home)?;
+    let known: Vec<String> = listing
+        .codebases
+        .iter()
+        .map(|(_, cb)| cb.name.0.clone())
+        .collect();
+    if let Some(found) = listing
+        .codebases
+        .into_iter()
         .find(|(_, cb)| cb.name == name)
-        .ok_or_else(|| {
-            SyncError::Registry(orchestra_core::error::RegistryError::RegistryNotFound {
-                path: home.join(".orchestra").join("projects").join(codebase_name),
-            })
+    {
+        return Ok(found);
+    }
+    let mut errors = listing.errors;
+    let own = errors
+        .iter()
+        .position(|failed| failed.path.file_stem() == Some(codebase_name.as_ref()))
+        .unwrap_or(0);
+    match (!errors.is_empty()).then(|| errors.swap_remove(own)) {
+        Some(failed) => Err(failed.error.into()),
+        None => Err(SyncError::CodebaseNotFound {
+            name: codebase_name.to_string(),
+            known,
+        }),
+    }
+}
+
+/// Check that every extra output stays inside the codebase.
+pub(crate) fn validate_extra_outputs(codebase: &Codebase) -> Result<(), SyncError> {
+    match codebase
+        .extra_outputs
+        .iter()
+        .find(|output| layout::extra_output_path(codebase, output).is_none())
+    {
+        Some(output) => Err(SyncError::InvalidExtraOutput {
+            codebase: codebase.name.0.clone(),
+            output_path: output.output_path.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Render the codebase's extra outputs with the shared context, paired with
+/// where each is written. Call [`validate_extra_outputs`] first.
+pub(crate) fn render_extra_outputs(
+    renderer: &Renderer,
+    ctx: &TemplateContext,
+    codebase: &Codebase,
+) -> Result<Vec<(PathBuf, String)>, SyncError> {
+    codebase
+        .extra_outputs
+        .iter()
+        .filter_map(|output| {
+            let path = layout::extra_output_path(codebase, output)?;
+            Some(
+                renderer
+                    .render_template(&output.template, ctx)
+                    .map(|content| (path, content))
+                    .map_err(|source| SyncError::ExtraOutput {
+                        template: output.template.clone(),
+                        output_path: output.output_path.clone(),
+                        source,
+                    }),
+            )
         })
+        .collect()
 }
 
 /// Outcome of syncing a single codebase.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct SyncCodebaseResult {
     pub codebase_name: String,
     pub writes: Vec<WriteResult>,
+    /// Generated files still present at a previous output location, e.g.
+    /// after `output_root` changed. They are left in place for the user.
+    pub previous_locations: Vec<PathBuf>,
+    /// Skill ids with no entry in the skills catalog. They render as the
+    /// bare id.
+    pub uncatalogued_skills: Vec<String>,
+    /// Agents whose files were over their token budget, and how far
+    /// enforcement trimmed them.
+    pub budget_overruns: Vec<BudgetOverrun>,
+    /// Active tasks assigned to a name that is no agent, so no agent file
+    /// lists them.
+    pub unknown_assignees: Vec<UnknownAssignee>,
+    /// Why the `post_sync` hook failed. The writes above stand regardless.
+    #[serde(serialize_with = "serialize_error_message")]
+    pub post_sync_error: Option<SyncError>,
+}
+
+/// An active task whose `assigned_agent` names no agent Orchestra renders.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnknownAssignee {
+    pub task_id: String,
+    pub assigned_agent: String,
+}
+
+/// The active tasks of `codebase` assigned to a name that does not parse as
+/// an [`AgentKind`].
+pub fn unknown_assignees(codebase: &Codebase) -> Vec<UnknownAssignee> {
+    codebase
+        .projects
+        .iter()
+        .flat_map(|project| &project.tasks)
+        .filter(|task| task.status.is_active())
+        .filter_map(|task| {
+            let name = task.assigned_agent.as_deref()?;
+            name.parse::<AgentKind>().is_err().then(|| UnknownAssignee {
+                task_id: task.id.0.clone(),
+                assigned_agent: name.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Errors serialize as their message.
+fn serialize_error_message<S: serde::Serializer>(
+    error: &Option<SyncError>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match error {
+        Some(error) => serializer.serialize_some(&error.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// How [`sync_codebase_with`] records a run in the hash store.
+///
+/// The [`pipeline`] runs behind `orchestra sync` and the daemon always use
+/// [`SyncOptions::default`]; only callers of [`sync_codebase_with`] can
+/// change them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncOptions {
+    /// Advance `synced_at` even when no file was written. Staleness compares
+    /// registry and `global.yaml` mtimes against it, so with this off a
+    /// registry saved without any rendered change stays reported as stale;
+    /// in exchange a sync that changes nothing leaves the hash store
+    /// untouched. On by default.
+    pub bump_synced_at_on_noop: bool,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            bump_synced_at_on_noop: true,
+        }
+    }
 }
 
 /// Sync all agent files for the named codebase.
 ///
-/// Renders every agent kind and writes with hash-gated atomic writes.
-/// Returns a summary of what was written / unchanged.
+/// Renders every agent kind, plus the codebase's extra outputs, and writes
+/// with hash-gated atomic writes. Returns a summary of what was written /
+/// unchanged.
+///
+/// With `agent` set, only that agent's outputs are rendered and written; the
+/// shared guide, pilot and extra outputs are skipped and hash-store entries
+/// for other agents are left untouched. `synced_at` still advances, so staleness may
+/// then report the skipped agents' files as stale — that is expected.
+///
+/// The codebase's [`hooks`](crate::hooks) run around the sync: a failing
+/// `pre_sync` aborts it, a failing `post_sync` is reported in
+/// [`SyncCodebaseResult::post_sync_error`].
 pub fn sync_codebase(
     codebase_name: &str,
     home: &Path,
     dry_run: bool,
+    agent: Option<AgentKind>,
+) -> Result<SyncCodebaseResult, SyncError> {
+    sync_codebase_with(codebase_name, home, dry_run, agent, SyncOptions::default())
+}
+
+/// [`sync_codebase`] with explicit [`SyncOptions`].
+pub fn sync_codebase_with(
+    codebase_name: &str,
+    home: &Path,
+    dry_run: bool,
+    agent: Option<AgentKind>,
+    options: SyncOptions,
+) -> Result<SyncCodebaseResult, SyncError> {
+    sync_codebase_from(
+        codebase_name,
+        home,
+        dry_run,
+        agent,
+        SyncSource::Cli,
+        true,
+        false,
+        WriteFilter::All,
+        options,
+        &mut |_| {},
+    )
+}
+
+/// [`sync_codebase`] with an explicit [`SyncSource`] for the history journal.
+///
+/// `backups` allows saving local edits before they are overwritten; the
+/// codebase's `backups` registry setting can still turn it off.
+/// `enforce_budget` trims agent files that are over their token budget, as
+/// the codebase's `enforce_budget` registry setting also does. `filter`
+/// narrows which files may be written, and `options` how the run is recorded.
+/// Each file written is reported to `on_event` as
+/// [`SyncProgress::FileWritten`].
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn sync_codebase_from(
+    codebase_name: &str,
+    home: &Path,
+    dry_run: bool,
+    agent: Option<AgentKind>,
+    source: SyncSource,
+    backups: bool,
+    enforce_budget: bool,
+    filter: WriteFilter,
+    options: SyncOptions,
+    on_event: &mut dyn FnMut(SyncProgress),
 ) -> Result<SyncCodebaseResult, SyncError> {
     let sync_started_at = Utc::now();
 
     // Find the codebase in the registry by scanning all projects.
-    let (_, codebase) = find_codebase_at(home, codebase_name)?;
+    let (project, codebase) = find_codebase_at(home, codebase_name)?;
+    layout::check_codebase_root(&codebase)?;
+    validate_extra_outputs(&codebase)?;
+    let hooks = hooks::enabled(&codebase, source);
+    if let Some(command) = hooks.and_then(|h| h.pre_sync.as_deref()) {
+        hooks::run(HookKind::PreSync, command, &codebase, &[], dry_run)?;
+    }
 
-    let renderer = Renderer::new()?;
+    let renderer = Renderer::cached(Some(&user_template_dir_at(home)))?;
+    // Held until the hash store is saved so concurrent syncs of the same
+    // codebase cannot overwrite each other's hash updates.
+    let _lock = lockfile::lock_exclusive_at(home, codebase_name)?;
     let store_path = hash_store::store_path_at(home, codebase_name);
     let store_existed = store_path.exists();
     let mut store = hash_store::load_at(home, codebase_name)?;
-    let ctx = build_sync_context(&codebase, dry_run, store_existed, store.synced_at);
+    // Taken before rendering: inputs edited mid-sync then fail to match.
+    let rendered_from = match agent {
+        None if !dry_run => Some(render_cache::render_inputs(
+            home, &renderer, &project, &codebase,
+        )?),
+        _ => None,
+    };
+    let mut ctx = build_sync_context(&project, &codebase, dry_run, store_existed, store.synced_at);
+    ctx.apply_global(&global::load_at(home)?);
+    let uncatalogued_skills = ctx.apply_skill_catalog(&skills::load_catalog(home)?);
+    if !uncatalogued_skills.is_empty() {
+        tracing::warn!(
+            "'{}' uses skills missing from the skills catalog: {}",
+            codebase_name,
+            uncatalogued_skills.join(", ")
+        );
+    }
+    let unknown_assignees = unknown_assignees(&codebase);
+    for unknown in &unknown_assignees {
+        tracing::warn!(
+            "'{}' task {} is assigned to '{}', which is not an agent; no agent file lists it",
+            codebase_name,
+            unknown.task_id,
+            unknown.assigned_agent
+        );
+    }
+    let backup_target = BackupTarget {
+        home,
+        codebase: &codebase,
+    };
+    let backups = (backups && codebase.backups != Some(false)).then_some(&backup_target);
     let mut writes = Vec::new();
-
-    for agent in AgentKind::all() {
-        let outputs = renderer.render_with_context(&ctx, *agent)?;
-        for (path, content) in outputs {
-            let result = atomic_write(&path, &content, &mut store.files, dry_run)?;
-            writes.push(result);
+    // Digests of the files this run manages, listed in the manifest.
+    let mut digests = Vec::new();
+    let enforce_budget = enforce_budget || codebase.enforce_budget == Some(true);
+    let mut budget_overruns = Vec::new();
+
+    let agents = match agent {
+        Some(agent) => vec![agent],
+        None => AgentKind::all().to_vec(),
+    };
+    for agent in &agents {
+        let render = render_within_budget(&renderer, &ctx, *agent, enforce_budget)?;
+        budget_overruns.extend(render.overrun);
+        let outputs = render.outputs;
+        let targets = layout::agent_output_paths(&codebase, *agent);
+        for (((_, content), path), strategy) in outputs
+            .into_iter()
+            .zip(targets)
+            .zip(agent.output_strategies())
+        {
+            let path = layout::contained_path(&codebase, &path)?;
+            let content = resolve_output(&path, &content, strategy);
+            let result = write_output(
+                &path,
+                &content,
+                strategy,
+                &mut store,
+                dry_run,
+                backups,
+                filter,
+                &mut digests,
+            )?;
+            push_write(&mut writes, result, on_event);
         }
     }
 
-    let (guide_path, guide_content) = renderer.render_guide(&ctx)?;
-    let guide_result = atomic_write(&guide_path, &guide_content, &mut store.files, dry_run)?;
-    writes.push(guide_result);
+    if agent.is_none() {
+        let (_, guide_content) = renderer.render_guide(&ctx)?;
+        let guide_path = layout::contained_path(&codebase, &layout::managed_guide_path(&codebase))?;
+        let guide_result = write_output(
+            &guide_path,
+            &guide_content,
+            OutputStrategy::Replace,
+            &mut store,
+            dry_run,
+            backups,
+            filter,
+            &mut digests,
+        )?;
+        push_write(&mut writes, guide_result, on_event);
+
+        let (_, pilot_content) = renderer.render_pilot(&ctx)?;
+        let pilot_path = layout::contained_path(&codebase, &layout::managed_pilot_path(&codebase))?;
+        let pilot_result = write_output(
+            &pilot_path,
+            &pilot_content,
+            OutputStrategy::Replace,
+            &mut store,
+            dry_run,
+            backups,
+            filter,
+            &mut digests,
+        )?;
+        push_write(&mut writes, pilot_result, on_event);
+
+        for (path, content) in render_extra_outputs(&renderer, &ctx, &codebase)? {
+            let path = layout::contained_path(&codebase, &path)?;
+            let result = write_output(
+                &path,
+                &content,
+                OutputStrategy::Replace,
+                &mut store,
+                dry_run,
+                backups,
+                filter,
+                &mut digests,
+            )?;
+            push_write(&mut writes, result, on_event);
+        }
 
-    let (pilot_path, pilot_content) = renderer.render_pilot(&ctx)?;
-    let pilot_result = atomic_write(&pilot_path, &pilot_content, &mut store.files, dry_run)?;
-    writes.push(pilot_result);
+        match layout::managed_manifest_path(&codebase) {
+            Some(manifest_path) => {
+                let manifest_path = layout::contained_path(&codebase, &manifest_path)?;
+                let changed = writes.iter().any(|write| {
+                    matches!(
+                        write,
+                        WriteResult::Written { .. } | WriteResult::WouldWrite { .. }
+                    )
+                });
+                let content = manifest::render(
+                    &codebase,
+                    &manifest_path,
+                    &digests,
+                    changed,
+                    sync_started_at,
+                )?;
+                let manifest_result = write_output(
+                    &manifest_path,
+                    &content,
+                    OutputStrategy::Replace,
+                    &mut store,
+                    dry_run,
+                    backups,
+                    filter,
+                    &mut Vec::new(),
+                )?;
+                push_write(&mut writes, manifest_result, on_event);
+            }
+            None if !dry_run => remove_disabled_manifest(&codebase, &mut store)?,
+            None => {}
+        }
+    }
+
+    let previous_locations = reconcile_previous_locations(&codebase, &mut store.files);
+    let files = &store.files;
+    store.files_meta.retain(|key, _| files.contains_key(key));
+    for overrun in &budget_overruns {
+        tracing::warn!(
+            "'{}': {} is ~{} tokens, over {}'s {}-token budget",
+            codebase_name,
+            overrun.path.display(),
+            overrun.tokens,
+            overrun.agent,
+            overrun.budget
+        );
+    }
+    if !previous_locations.is_empty() {
+        tracing::warn!(
+            "'{}' has generated files at a previous output location: {}",
+            codebase_name,
+            previous_locations
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
 
     // Save the updated hash store (skip in dry-run — no filesystem changes).
     if !dry_run {
-        store.synced_at = sync_started_at;
+        let wrote_any = writes
+            .iter()
+            .any(|write| matches!(write, WriteResult::Written { .. }));
+        if wrote_any || options.bump_synced_at_on_noop {
+            store.synced_at = sync_started_at;
+        }
+        // An ignored file has no recorded hash to vouch for it, so a sync
+        // that skipped one leaves `diff` nothing to short-circuit on.
+        let ignored_any = writes
+            .iter()
+            .any(|write| matches!(write, WriteResult::Ignored { .. }));
+        // A filtered sync leaves files behind the current inputs.
+        store.rendered_from = rendered_from.filter(|_| !ignored_any && filter == WriteFilter::All);
+        store.budget_overruns = match store.rendered_from {
+            Some(_) => budget_overruns.clone(),
+            None => Vec::new(),
+        };
+        if agent.is_none() {
+            store.templates = Some(renderer.fingerprint().to_string());
+        }
         hash_store::save_at(home, codebase_name, &store)?;
     }
 
-    Ok(SyncCodebaseResult {
+    let mut result = SyncCodebaseResult {
         codebase_name: codebase_name.to_string(),
         writes,
-    })
+        previous_locations,
+        uncatalogued_skills,
+        budget_overruns,
+        unknown_assignees,
+        post_sync_error: None,
+    };
+    if !dry_run {
+        history::record(home, &result, source);
+    }
+
+    let written: Vec<PathBuf> = result
+        .writes
+        .iter()
+        .filter_map(|write| match write {
+            WriteResult::Written { path, .. } => Some(path.clone()),
+            _ => None,
+        })
+        .collect();
+    // Released first so a hook that syncs again does not wait on this run.
+    drop(_lock);
+    if let Some(command) = hooks.and_then(|h| h.post_sync.as_deref()) {
+        if !dry_run && !written.is_empty() {
+            result.post_sync_error =
+                hooks::run(HookKind::PostSync, command, &codebase, &written, dry_run).err();
+        }
+    }
+    Ok(result)
+}
+
+/// Add `result` to `writes`, reporting it to `on_event` if it hit the disk.
+fn push_write(
+    writes: &mut Vec<WriteResult>,
+    result: WriteResult,
+    on_event: &mut dyn FnMut(SyncProgress),
+) {
+    if let WriteResult::Written { path, .. } = &result {
+        on_event(SyncProgress::FileWritten { path: path.clone() });
+    }
+    writes.push(result);
+}
+
+/// [`atomic_write`] unless `filter` leaves the file alone, remembering its
+/// digest for the manifest. A file left alone is listed with the digest last
+/// recorded for it.
+#[allow(clippy::too_many_arguments)]
+fn write_output(
+    path: &Path,
+    content: &str,
+    strategy: OutputStrategy,
+    store: &mut HashStoreFile,
+    dry_run: bool,
+    backups: Option<&BackupTarget>,
+    filter: WriteFilter,
+    digests: &mut Vec<(PathBuf, String)>,
+) -> Result<WriteResult, SyncError> {
+    if !filter.allows(path, strategy, store)? && !ignore::is_ignored(path) {
+        let key = path.to_string_lossy();
+        if let Some(digest) = store.files.get(key.as_ref()).filter(|d| !d.is_empty()) {
+            digests.push((path.to_path_buf(), digest.clone()));
+        }
+        return Ok(WriteResult::Unchanged {
+            path: path.to_path_buf(),
+        });
+    }
+    let result = atomic_write(path, content, strategy, store, dry_run, backups)?;
+    record_digest(&result, content, strategy, digests);
+    Ok(result)
+}
+
+/// Remember the digest of `content` unless the file was left to the user.
+fn record_digest(
+    result: &WriteResult,
+    content: &str,
+    strategy: OutputStrategy,
+    digests: &mut Vec<(PathBuf, String)>,
+) {
+    match result {
+        WriteResult::Written { path, .. }
+        | WriteResult::Unchanged { path }
+        | WriteResult::WouldWrite { path } => {
+            digests.push((path.clone(), output_digest(content, strategy)))
+        }
+        WriteResult::Ignored { .. } => {}
+    }
+}
+
+/// Delete a manifest written before the registry turned it off, along with
+/// its hash-store entry. A manifest the user edited since is left alone.
+fn remove_disabled_manifest(codebase: &Codebase, store: &mut HashStoreFile) -> Result<(), SyncError> {
+    let path = layout::contained_path(codebase, Path::new(manifest::MANIFEST_FILE))?;
+    let key = path.to_string_lossy().to_string();
+    let Some(digest) = store.files.remove(&key) else {
+        return Ok(());
+    };
+    store.files_meta.remove(&key);
+    if disk_content_matches_digest(&path, &digest, OutputStrategy::Replace)? {
+        std::fs::remove_file(&path).map_err(|e| io_err(&path, e))?;
+    }
+    Ok(())
+}
+
+/// Find hash-store entries for managed files at a location other than the
+/// current layout (e.g. before `output_root` changed).
+///
+/// Entries whose file is gone are dropped; entries whose file still exists
+/// are kept (so staleness keeps reporting them) and returned for a warning.
+/// Writeback-tracked files (empty digest) are never touched.
+fn reconcile_previous_locations(codebase: &Codebase, store: &mut HashStore) -> Vec<PathBuf> {
+    let current = layout::all_managed_paths(codebase);
+    let suffixes: Vec<PathBuf> = current
+        .iter()
+        .map(|path| layout::display_relative(path, codebase))
+        .collect();
+
+    let mut previous = Vec::new();
+    store.retain(|key, digest| {
+        let path = Path::new(key);
+        let relocated = !digest.is_empty()
+            && !current.iter().any(|p| p == path)
+            && suffixes.iter().any(|suffix| path.ends_with(suffix));
+        if !relocated {
+            return true;
+        }
+        if path.exists() {
+            previous.push(path.to_path_buf());
+            true
+        } else {
+            false
+        }
+    });
+    previous.sort();
+    previous
 }
 
 // ---------------------------------------------------------------------------
 // sync_all
 // ---------------------------------------------------------------------------
 
-/// Sync all registered codebases.
-pub fn sync_all(home: &Path, dry_run: bool) -> Result<Vec<SyncCodebaseResult>, SyncError> {
-    let all = registry::list_codebases_at(home)?;
-    let mut results = Vec::new();
-    for (_project, codebase) in all {
-        let name = codebase.name.0.clone();
-        let r = sync_codebase(&name, home, dry_run)?;
-        results.push(r);
-    }
-    Ok(results)
+/// Sync all registered codebases, optionally restricted to one `agent`.
+pub fn sync_all(
+    home: &Path,
+    dry_run: bool,
+    agent: Option<AgentKind>,
+) -> Result<Vec<SyncCodebaseResult>, SyncError> {
+    pipeline::run(home, SyncScope::All, dry_run, agent)
 }
 
 // ---------------------------------------------------------------------------
@@ -247,18 +1124,40 @@ mod tests {
     use super::*;
     use chrono::Duration as ChronoDuration;
     use orchestra_core::{
+        error::RegistryError,
         registry,
-        types::{Codebase, CodebaseName, Project, ProjectName, ProjectType},
+        types::{
+            Codebase, CodebaseName, Project, ProjectName, ProjectType, CURRENT_SCHEMA_VERSION,
+        },
     };
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
     use std::fs;
     use std::thread::sleep;
     use std::time::Duration;
     use tempfile::TempDir;
 
+    fn empty_store() -> HashStoreFile {
+        HashStoreFile {
+            synced_at: Utc::now(),
+            files: HashMap::new(),
+            files_meta: HashMap::new(),
+            rendered_from: None,
+            budget_overruns: Vec::new(),
+            templates: None,
+        }
+    }
+
     fn write_content(path: &Path, content: &str) -> WriteResult {
-        let mut store = HashMap::new();
-        atomic_write(path, content, &mut store, false).unwrap()
+        let mut store = empty_store();
+        atomic_write(
+            path,
+            content,
+            OutputStrategy::Replace,
+            &mut store,
+            false,
+            None,
+        )
+        .unwrap()
     }
 
     fn make_codebase_for_context(name: &str) -> Codebase {
@@ -269,15 +1168,29 @@ mod tests {
             projects: vec![Project {
                 name: ProjectName::from("api"),
                 project_type: ProjectType::Backend,
+                framework: None,
+                detection: None,
                 tasks: vec![],
                 agents: vec![],
+                commands: BTreeMap::new(),
+                extra: BTreeMap::new(),
             }],
             conventions: vec![],
             skills: vec![],
             notes: vec![],
             tracked_files: vec![],
+            max_rendered_tasks: None,
+            output_root: None,
+            manifest: None,
+            backups: None,
+            enforce_budget: None,
+            hooks: None,
+            extra_outputs: vec![],
+            agent_conventions: BTreeMap::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             created_at: now,
             updated_at: now,
+            extra: BTreeMap::new(),
         }
     }
 
@@ -294,11 +1207,27 @@ mod tests {
     fn second_write_same_content_returns_unchanged() {
         let tmp = TempDir::new().unwrap();
         let path = tmp.path().join("file.md");
-        let mut store = HashMap::new();
+        let mut store = empty_store();
         // First write.
-        atomic_write(&path, "same content", &mut store, false).unwrap();
+        atomic_write(
+            &path,
+            "same content",
+            OutputStrategy::Replace,
+            &mut store,
+            false,
+            None,
+        )
+        .unwrap();
         // Second write with same content.
-        let result = atomic_write(&path, "same content", &mut store, false).unwrap();
+        let result = atomic_write(
+            &path,
+            "same content",
+            OutputStrategy::Replace,
+            &mut store,
+            false,
+            None,
+        )
+        .unwrap();
         assert!(matches!(result, WriteResult::Unchanged { .. }));
     }
 
@@ -306,9 +1235,25 @@ mod tests {
     fn changed_content_returns_written() {
         let tmp = TempDir::new().unwrap();
         let path = tmp.path().join("file.md");
-        let mut store = HashMap::new();
-        atomic_write(&path, "v1", &mut store, false).unwrap();
-        let result = atomic_write(&path, "v2", &mut store, false).unwrap();
+        let mut store = empty_store();
+        atomic_write(
+            &path,
+            "v1",
+            OutputStrategy::Replace,
+            &mut store,
+            false,
+            None,
+        )
+        .unwrap();
+        let result = atomic_write(
+            &path,
+            "v2",
+            OutputStrategy::Replace,
+            &mut store,
+            false,
+            None,
+        )
+        .unwrap();
         assert!(matches!(result, WriteResult::Written { .. }));
     }
 
@@ -316,12 +1261,28 @@ mod tests {
     fn rewrites_drifted_file_even_when_hash_store_matches_target_content() {
         let tmp = TempDir::new().unwrap();
         let path = tmp.path().join("file.md");
-        let mut store = HashMap::new();
-
-        atomic_write(&path, "expected\n", &mut store, false).unwrap();
+        let mut store = empty_store();
+
+        atomic_write(
+            &path,
+            "expected\n",
+            OutputStrategy::Replace,
+            &mut store,
+            false,
+            None,
+        )
+        .unwrap();
         fs::write(&path, "manual drift\n").unwrap();
 
-        let result = atomic_write(&path, "expected\n", &mut store, false).unwrap();
+        let result = atomic_write(
+            &path,
+            "expected\n",
+            OutputStrategy::Replace,
+            &mut store,
+            false,
+            None,
+        )
+        .unwrap();
         assert!(matches!(result, WriteResult::Written { .. }));
         assert_eq!(fs::read_to_string(&path).unwrap(), "expected\n");
     }
@@ -330,8 +1291,16 @@ mod tests {
     fn dry_run_does_not_write_file() {
         let tmp = TempDir::new().unwrap();
         let path = tmp.path().join("nope.md");
-        let mut store = HashMap::new();
-        let result = atomic_write(&path, "content", &mut store, true).unwrap();
+        let mut store = empty_store();
+        let result = atomic_write(
+            &path,
+            "content",
+            OutputStrategy::Replace,
+            &mut store,
+            true,
+            None,
+        )
+        .unwrap();
         assert!(matches!(result, WriteResult::WouldWrite { .. }));
         assert!(!path.exists(), "dry-run must not create files");
     }
@@ -372,7 +1341,7 @@ mod tests {
         )
         .expect("init");
 
-        sync_codebase("copnow_api", home.path(), false).expect("first sync");
+        sync_codebase("copnow_api", home.path(), false, None).expect("first sync");
 
         let target = codebase_dir.join("orchestra/controls/CLAUDE.md");
         let mtime_1 = fs::metadata(&target).unwrap().modified().unwrap();
@@ -381,7 +1350,7 @@ mod tests {
         let hash_1 = store_1.files.get(&key).expect("hash entry").clone();
 
         sleep(Duration::from_millis(1100));
-        sync_codebase("copnow_api", home.path(), false).expect("second sync");
+        sync_codebase("copnow_api", home.path(), false, None).expect("second sync");
 
         let mtime_2 = fs::metadata(&target).unwrap().modified().unwrap();
         let store_2 = hash_store::load_at(home.path(), "copnow_api").unwrap();
@@ -406,47 +1375,659 @@ mod tests {
         )
         .expect("init");
 
-        sync_codebase("copnow_api", home.path(), false).expect("first sync");
+        sync_codebase("copnow_api", home.path(), false, None).expect("first sync");
         let first = hash_store::load_at(home.path(), "copnow_api")
             .unwrap()
             .synced_at;
 
         sleep(Duration::from_millis(1100));
-        sync_codebase("copnow_api", home.path(), true).expect("dry-run sync");
+        sync_codebase("copnow_api", home.path(), true, None).expect("dry-run sync");
         let after_dry_run = hash_store::load_at(home.path(), "copnow_api")
             .unwrap()
             .synced_at;
         assert_eq!(after_dry_run, first, "dry-run must not advance synced_at");
 
         sleep(Duration::from_millis(1100));
-        sync_codebase("copnow_api", home.path(), false).expect("second real sync");
+        sync_codebase("copnow_api", home.path(), false, None).expect("second real sync");
         let second = hash_store::load_at(home.path(), "copnow_api")
             .unwrap()
             .synced_at;
         assert!(second > first, "real sync should advance synced_at");
     }
 
+    #[test]
+    fn noop_sync_without_synced_at_bump_leaves_hash_store_alone() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        setup_synced_codebase(&home, &workspace);
+        let store_path = hash_store::store_path_at(home.path(), "copnow_api");
+        let old = filetime::FileTime::from_unix_time(1_000_000_000, 0);
+        filetime::set_file_mtime(&store_path, old).unwrap();
+
+        let options = SyncOptions {
+            bump_synced_at_on_noop: false,
+        };
+        let result = sync_codebase_with("copnow_api", home.path(), false, None, options).unwrap();
+        assert!(result
+            .writes
+            .iter()
+            .all(|write| matches!(write, WriteResult::Unchanged { .. })));
+        let mtime =
+            filetime::FileTime::from_last_modification_time(&fs::metadata(&store_path).unwrap());
+        assert_eq!(mtime, old);
+    }
+
+    #[test]
+    fn noop_sync_clears_registry_staleness_by_default() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        setup_synced_codebase(&home, &workspace);
+        sleep(Duration::from_millis(1100));
+        // Saved without a change that renders differently.
+        let project = ProjectName::from("copnow");
+        let name = CodebaseName::from("copnow_api");
+        let codebase = registry::load_codebase_at(home.path(), &project, &name).unwrap();
+        registry::save_codebase_at(home.path(), &project, &codebase).unwrap();
+        assert!(matches!(
+            claude_signal(&home),
+            crate::staleness::StalenessSignal::Stale { .. }
+        ));
+
+        let options = SyncOptions {
+            bump_synced_at_on_noop: false,
+        };
+        sync_codebase_with("copnow_api", home.path(), false, None, options).unwrap();
+        assert!(matches!(
+            claude_signal(&home),
+            crate::staleness::StalenessSignal::Stale { .. }
+        ));
+
+        sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        assert_eq!(
+            claude_signal(&home),
+            crate::staleness::StalenessSignal::Current
+        );
+    }
+
     #[test]
     fn crlf_and_lf_content_share_the_same_hash() {
         let tmp = TempDir::new().unwrap();
         let path = tmp.path().join("normalize.md");
-        let mut store = HashMap::new();
-
-        let first = atomic_write(&path, "line1\r\nline2\r\n", &mut store, false).unwrap();
+        let mut store = empty_store();
+
+        let first = atomic_write(
+            &path,
+            "line1\r\nline2\r\n",
+            OutputStrategy::Replace,
+            &mut store,
+            false,
+            None,
+        )
+        .unwrap();
         assert!(matches!(first, WriteResult::Written { .. }));
 
-        let second = atomic_write(&path, "line1\nline2\n", &mut store, false).unwrap();
+        let second = atomic_write(
+            &path,
+            "line1\nline2\n",
+            OutputStrategy::Replace,
+            &mut store,
+            false,
+            None,
+        )
+        .unwrap();
         assert!(matches!(second, WriteResult::Unchanged { .. }));
 
         let disk = fs::read_to_string(&path).unwrap();
         assert_eq!(disk, "line1\nline2\n");
     }
 
+    #[test]
+    fn file_digest_streams_text_to_the_content_digest() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("streamed.md");
+        // CRLF split across the 64 KiB read boundary, plus lone CRs.
+        let mut content = "a".repeat(64 * 1024 - 1);
+        content.push_str("\r\nb\rc\r\r\nend\r");
+        fs::write(&path, &content).unwrap();
+
+        for strategy in [OutputStrategy::Replace, OutputStrategy::ManagedRegion] {
+            assert_eq!(
+                file_digest(&path, strategy).unwrap(),
+                output_digest(&content, strategy)
+            );
+        }
+    }
+
+    #[test]
+    fn file_digest_hashes_non_utf8_content_without_error() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("binary.md");
+        fs::write(&path, b"\xff\xfe\r\nnot text").unwrap();
+
+        let digest = file_digest(&path, OutputStrategy::ManagedRegion).unwrap();
+        assert_eq!(digest, file_digest(&path, OutputStrategy::Replace).unwrap());
+        assert_ne!(digest, content_digest(""));
+    }
+
+    fn setup_synced_codebase(home: &TempDir, workspace: &TempDir) -> PathBuf {
+        let codebase_dir = workspace.path().join("copnow_api");
+        fs::create_dir_all(&codebase_dir).unwrap();
+        registry::init_at(
+            codebase_dir.clone(),
+            ProjectName::from("copnow"),
+            Some(ProjectType::Backend),
+            home.path(),
+        )
+        .expect("init");
+        sync_codebase("copnow_api", home.path(), false, None).expect("first sync");
+        codebase_dir
+    }
+
+    fn settings_write(result: &SyncCodebaseResult) -> &WriteResult {
+        result
+            .writes
+            .iter()
+            .find(|w| match w {
+                WriteResult::Written { path, .. }
+                | WriteResult::Unchanged { path }
+                | WriteResult::WouldWrite { path }
+                | WriteResult::Ignored { path } => path.ends_with(".gemini/settings.json"),
+            })
+            .expect("settings.json write result")
+    }
+
+    #[test]
+    fn gemini_settings_user_key_survives_sync() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let settings = codebase_dir.join("orchestra/controls/.gemini/settings.json");
+
+        let mut value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&settings).unwrap()).unwrap();
+        value["mcpServers"] = serde_json::json!({ "local": { "command": "mcp-local" } });
+        fs::write(&settings, serde_json::to_string(&value).unwrap()).unwrap();
+
+        sync_codebase("copnow_api", home.path(), false, None).expect("second sync");
+
+        let merged: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&settings).unwrap()).unwrap();
+        assert_eq!(merged["mcpServers"]["local"]["command"], "mcp-local");
+        assert_eq!(merged["codebase"], "copnow_api");
+    }
+
+    #[test]
+    fn gemini_settings_orchestra_key_is_updated() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let settings = codebase_dir.join("orchestra/controls/.gemini/settings.json");
+
+        fs::write(
+            &settings,
+            r#"{"codebase":"stale","context":{"managed_by":"someone","theme":"dark"}}"#,
+        )
+        .unwrap();
+
+        sync_codebase("copnow_api", home.path(), false, None).expect("second sync");
+
+        let merged: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&settings).unwrap()).unwrap();
+        assert_eq!(merged["codebase"], "copnow_api");
+        assert_eq!(merged["context"]["managed_by"], "orchestra");
+        assert_eq!(merged["context"]["theme"], "dark", "nested user key preserved");
+    }
+
+    #[test]
+    fn gemini_settings_merge_is_idempotent() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let settings = codebase_dir.join("orchestra/controls/.gemini/settings.json");
+
+        let unchanged = sync_codebase("copnow_api", home.path(), false, None).expect("no-op sync");
+        assert!(matches!(settings_write(&unchanged), WriteResult::Unchanged { .. }));
+
+        fs::write(&settings, r#"{"auth":{"mode":"oauth"}}"#).unwrap();
+        let merged = sync_codebase("copnow_api", home.path(), false, None).expect("merge sync");
+        assert!(matches!(settings_write(&merged), WriteResult::Written { .. }));
+
+        let again = sync_codebase("copnow_api", home.path(), false, None).expect("repeat sync");
+        assert!(matches!(settings_write(&again), WriteResult::Unchanged { .. }));
+    }
+
+    #[test]
+    fn gemini_settings_corrupt_json_falls_back_to_replace() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let settings = codebase_dir.join("orchestra/controls/.gemini/settings.json");
+
+        fs::write(&settings, "{ not json").unwrap();
+        sync_codebase("copnow_api", home.path(), false, None).expect("sync");
+
+        let replaced: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&settings).unwrap()).unwrap();
+        assert_eq!(replaced["codebase"], "copnow_api");
+    }
+
+    fn claude_signal(home: &TempDir) -> crate::staleness::StalenessSignal {
+        let project = ProjectName::from("copnow");
+        let name = CodebaseName::from("copnow_api");
+        let codebase = registry::load_codebase_at(home.path(), &project, &name).unwrap();
+        crate::staleness::check(home.path(), &project, &codebase, true).unwrap()
+    }
+
+    #[test]
+    fn notes_outside_managed_region_survive_repeated_syncs() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let claude = codebase_dir.join("orchestra/controls/CLAUDE.md");
+        let generated = fs::read_to_string(&claude).unwrap();
+        assert!(generated.trim_start().starts_with(MANAGED_REGION_BEGIN));
+        assert!(generated.trim_end().ends_with(MANAGED_REGION_END));
+
+        let edited = format!("# My notes\n\n{generated}\n## Local rules\nkeep me\n");
+        fs::write(&claude, &edited).unwrap();
+        assert_eq!(
+            claude_signal(&home),
+            crate::staleness::StalenessSignal::Current
+        );
+
+        for _ in 0..2 {
+            let result = sync_codebase("copnow_api", home.path(), false, None).unwrap();
+            assert!(result.writes.contains(&WriteResult::Unchanged {
+                path: claude.clone()
+            }));
+            assert_eq!(fs::read_to_string(&claude).unwrap(), edited);
+        }
+    }
+
+    #[test]
+    fn edit_inside_managed_region_is_modified_and_overwritten() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let claude = codebase_dir.join("orchestra/controls/CLAUDE.md");
+        let generated = fs::read_to_string(&claude).unwrap();
+
+        let edited = format!(
+            "# My notes\n{}",
+            generated.replacen(
+                MANAGED_REGION_END,
+                &format!("hand edit\n{MANAGED_REGION_END}"),
+                1
+            )
+        );
+        fs::write(&claude, &edited).unwrap();
+        assert!(matches!(
+            claude_signal(&home),
+            crate::staleness::StalenessSignal::Modified { .. }
+        ));
+
+        sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(&claude).unwrap(),
+            format!("# My notes\n{generated}")
+        );
+    }
+
+    #[test]
+    fn missing_end_marker_falls_back_to_full_replace() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let claude = codebase_dir.join("orchestra/controls/CLAUDE.md");
+        let generated = fs::read_to_string(&claude).unwrap();
+
+        let broken = format!(
+            "# My notes\n{}",
+            generated.replacen(MANAGED_REGION_END, "", 1)
+        );
+        fs::write(&claude, broken).unwrap();
+        sync_codebase("copnow_api", home.path(), false, None).unwrap();
+
+        assert_eq!(fs::read_to_string(&claude).unwrap(), generated);
+    }
+
+    #[test]
+    fn syncs_reuse_renderer_until_an_override_changes() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let templates = user_template_dir_at(home.path());
+        let override_path = templates.join("claude").join("claude.md.tera");
+        fs::create_dir_all(override_path.parent().unwrap()).unwrap();
+        fs::write(&override_path, "# Team rules v1\n").unwrap();
+
+        sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        let first = Renderer::cached(Some(&templates)).unwrap().generation();
+        sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        assert_eq!(
+            Renderer::cached(Some(&templates)).unwrap().generation(),
+            first
+        );
+
+        fs::write(&override_path, "# Team rules v2\n").unwrap();
+        let later = std::time::SystemTime::now() + Duration::from_secs(5);
+        fs::File::options()
+            .write(true)
+            .open(&override_path)
+            .and_then(|file| file.set_modified(later))
+            .unwrap();
+        sync_codebase("copnow_api", home.path(), false, None).unwrap();
+
+        let claude = fs::read_to_string(codebase_dir.join("orchestra/controls/CLAUDE.md")).unwrap();
+        assert!(claude.contains("# Team rules v2"), "{claude}");
+        assert_ne!(
+            Renderer::cached(Some(&templates)).unwrap().generation(),
+            first
+        );
+    }
+
+    #[test]
+    fn manifest_lists_managed_files_and_survives_noop_sync() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let manifest_path = codebase_dir.join(manifest::MANIFEST_FILE);
+        let first = fs::read(&manifest_path).expect("manifest written");
+
+        let parsed = manifest::load(&manifest_path).expect("manifest parses");
+        let claude = codebase_dir.join("orchestra/controls/CLAUDE.md");
+        let claude_digest = output_digest(
+            &fs::read_to_string(&claude).unwrap(),
+            OutputStrategy::ManagedRegion,
+        );
+        let entry = parsed
+            .files
+            .iter()
+            .find(|e| e.path == "orchestra/controls/CLAUDE.md")
+            .expect("CLAUDE.md listed");
+        assert_eq!(entry.sha256, claude_digest);
+        let store = hash_store::load_at(home.path(), "copnow_api").unwrap();
+        assert_eq!(store.files[&claude.to_string_lossy().to_string()], claude_digest);
+        assert!(parsed.files.iter().all(|e| e.path != manifest::MANIFEST_FILE));
+
+        let second = sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        assert!(second.writes.contains(&WriteResult::Unchanged {
+            path: manifest_path.clone()
+        }));
+        assert_eq!(fs::read(&manifest_path).unwrap(), first);
+    }
+
+    #[test]
+    fn disabling_the_manifest_removes_it() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let manifest_path = codebase_dir.join(manifest::MANIFEST_FILE);
+        assert!(manifest_path.exists());
+
+        let project = ProjectName::from("copnow");
+        let name = CodebaseName::from("copnow_api");
+        let mut codebase = registry::load_codebase_at(home.path(), &project, &name).unwrap();
+        codebase.manifest = Some(false);
+        registry::save_codebase_at(home.path(), &project, &codebase).unwrap();
+
+        sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        assert!(!manifest_path.exists());
+        let store = hash_store::load_at(home.path(), "copnow_api").unwrap();
+        assert!(!store
+            .files
+            .contains_key(&manifest_path.to_string_lossy().to_string()));
+    }
+
+    fn add_extra_output(home: &TempDir, template: &str, output_path: &str) {
+        let project = ProjectName::from("copnow");
+        let name = CodebaseName::from("copnow_api");
+        let mut codebase = registry::load_codebase_at(home.path(), &project, &name).unwrap();
+        codebase.extra_outputs.push(orchestra_core::types::ExtraOutput {
+            template: template.to_string(),
+            output_path: PathBuf::from(output_path),
+        });
+        registry::save_codebase_at(home.path(), &project, &codebase).unwrap();
+    }
+
+    #[test]
+    fn extra_output_renders_shared_context_and_is_hash_gated() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let template = user_template_dir_at(home.path()).join("ai-context.md.tera");
+        fs::create_dir_all(template.parent().unwrap()).unwrap();
+        fs::write(&template, "# {{ identity.codebase_name }} context\n").unwrap();
+        add_extra_output(&home, "ai-context.md.tera", "docs/ai-context.md");
+
+        let extra = codebase_dir.join("docs/ai-context.md");
+        let first = sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        assert!(first.writes.contains(&WriteResult::Written {
+            path: extra.clone(),
+            backed_up: None,
+        }));
+        assert_eq!(fs::read_to_string(&extra).unwrap(), "# copnow_api context\n");
+        let manifest = manifest::load(&codebase_dir.join(manifest::MANIFEST_FILE)).unwrap();
+        assert!(manifest.files.iter().any(|e| e.path == "docs/ai-context.md"));
+
+        let second = sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        assert!(second.writes.contains(&WriteResult::Unchanged {
+            path: extra.clone()
+        }));
+
+        let project = ProjectName::from("copnow");
+        let name = CodebaseName::from("copnow_api");
+        let codebase = registry::load_codebase_at(home.path(), &project, &name).unwrap();
+        fs::remove_file(&extra).unwrap();
+        match crate::staleness::check(home.path(), &project, &codebase, false).unwrap() {
+            crate::staleness::StalenessSignal::Stale { reason } => {
+                assert!(reason.contains("docs/ai-context.md"), "{reason}")
+            }
+            other => panic!("expected stale, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn extra_output_errors_name_the_failing_entry() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        add_extra_output(&home, "missing.md.tera", "docs/missing.md");
+
+        let err = sync_codebase("copnow_api", home.path(), false, None).unwrap_err();
+        assert!(
+            matches!(&err, SyncError::ExtraOutput { template, .. } if template == "missing.md.tera"),
+            "{err}"
+        );
+        assert!(err.to_string().contains("docs/missing.md"), "{err}");
+
+        let project = ProjectName::from("copnow");
+        let name = CodebaseName::from("copnow_api");
+        let mut codebase = registry::load_codebase_at(home.path(), &project, &name).unwrap();
+        codebase.extra_outputs[0].output_path = PathBuf::from("../outside.md");
+        registry::save_codebase_at(home.path(), &project, &codebase).unwrap();
+        let err = sync_codebase("copnow_api", home.path(), false, None).unwrap_err();
+        assert!(matches!(err, SyncError::InvalidExtraOutput { .. }), "{err}");
+        assert!(!codebase_dir.parent().unwrap().join("outside.md").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_output_directory_leaving_the_tree_is_refused() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let controls = codebase_dir.join("orchestra/controls");
+        fs::remove_dir_all(&controls).unwrap();
+        std::os::unix::fs::symlink(outside.path(), &controls).unwrap();
+
+        let err = sync_codebase("copnow_api", home.path(), false, None).unwrap_err();
+        assert!(matches!(err, SyncError::PathEscape { .. }), "{err}");
+        assert_eq!(fs::read_dir(outside.path()).unwrap().count(), 0);
+        let err = crate::diff::diff_codebase("copnow_api", home.path()).unwrap_err();
+        assert!(matches!(err, SyncError::PathEscape { .. }), "{err}");
+    }
+
+    #[test]
+    fn codebase_at_the_filesystem_root_is_refused() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        setup_synced_codebase(&home, &workspace);
+        let project = ProjectName::from("copnow");
+        let name = CodebaseName::from("copnow_api");
+        let mut codebase = registry::load_codebase_at(home.path(), &project, &name).unwrap();
+        codebase.path = PathBuf::from("/");
+        registry::save_codebase_at(home.path(), &project, &codebase).unwrap();
+
+        let err = sync_codebase("copnow_api", home.path(), false, None).unwrap_err();
+        assert!(matches!(err, SyncError::UnsafeCodebaseRoot { .. }), "{err}");
+        assert!(!Path::new("/CLAUDE.md").exists());
+    }
+
+    #[test]
+    fn locally_modified_file_is_backed_up_before_overwrite() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let claude = codebase_dir.join("orchestra/controls/CLAUDE.md");
+        fs::write(&claude, "# hand edit\n").unwrap();
+
+        let result = sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        let mut backed_up = result.writes.iter().filter_map(|write| match write {
+            WriteResult::Written {
+                path,
+                backed_up: Some(backup),
+            } => Some((path, backup)),
+            _ => None,
+        });
+        let (path, backup) = backed_up.next().expect("CLAUDE.md backed up");
+        assert_eq!(path, &claude);
+        assert!(backed_up.next().is_none(), "unchanged files are not backed up");
+        assert_eq!(fs::read_to_string(backup).unwrap(), "# hand edit\n");
+        assert_eq!(
+            crate::edit_backup::list_at(home.path(), "copnow_api").unwrap()[0].file,
+            "orchestra/controls/CLAUDE.md"
+        );
+
+        sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        assert_eq!(crate::edit_backup::list_at(home.path(), "copnow_api").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn backups_can_be_turned_off() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_synced_codebase(&home, &workspace);
+        let claude = codebase_dir.join("orchestra/controls/CLAUDE.md");
+
+        fs::write(&claude, "# hand edit\n").unwrap();
+        crate::pipeline::run_without_backups(
+            home.path(),
+            crate::pipeline::SyncScope::Codebase("copnow_api".to_string()),
+            false,
+            None,
+            |_| {},
+        )
+        .unwrap();
+
+        let project = ProjectName::from("copnow");
+        let name = CodebaseName::from("copnow_api");
+        let mut codebase = registry::load_codebase_at(home.path(), &project, &name).unwrap();
+        codebase.backups = Some(false);
+        registry::save_codebase_at(home.path(), &project, &codebase).unwrap();
+        fs::write(&claude, "# another edit\n").unwrap();
+        sync_codebase("copnow_api", home.path(), false, None).unwrap();
+
+        assert_ne!(fs::read_to_string(&claude).unwrap(), "# another edit\n");
+        assert!(crate::edit_backup::list_at(home.path(), "copnow_api")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn each_sync_appends_one_history_line() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        setup_synced_codebase(&home, &workspace);
+        sync_codebase("copnow_api", home.path(), false, None).expect("second sync");
+        sync_codebase("copnow_api", home.path(), true, None).expect("dry run");
+
+        let entries = history::read_at(home.path(), None, None).unwrap();
+        assert_eq!(entries.len(), 2, "dry runs must not be journaled");
+        assert!(entries[0].written > 0);
+        assert_eq!(entries[0].written, entries[0].paths.len());
+        assert_eq!(entries[1].written, 0, "second sync is a no-op");
+        assert!(entries.iter().all(|e| e.source == SyncSource::Cli));
+    }
+
+    #[test]
+    fn history_write_failure_does_not_fail_sync() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        setup_synced_codebase(&home, &workspace);
+
+        // A directory where the journal file should be makes every append fail.
+        let journal = history::history_path_at(home.path());
+        fs::remove_file(&journal).unwrap();
+        fs::create_dir_all(&journal).unwrap();
+
+        sync_codebase("copnow_api", home.path(), false, None).expect("sync despite journal failure");
+    }
+
+    #[test]
+    fn concurrent_syncs_of_one_codebase_keep_every_hash_entry() {
+        for _ in 0..5 {
+            let home = TempDir::new().unwrap();
+            let workspace = TempDir::new().unwrap();
+            let codebase_dir = workspace.path().join("copnow_api");
+            fs::create_dir_all(&codebase_dir).unwrap();
+            registry::init_at(
+                codebase_dir,
+                ProjectName::from("copnow"),
+                Some(ProjectType::Backend),
+                home.path(),
+            )
+            .expect("init");
+
+            // Each thread writes a different agent's files, so a lost hash
+            // store update would drop one agent's entries.
+            let handles: Vec<_> = [AgentKind::Claude, AgentKind::Codex]
+                .into_iter()
+                .map(|agent| {
+                    let home = home.path().to_path_buf();
+                    std::thread::spawn(move || {
+                        sync_codebase("copnow_api", &home, false, Some(agent))
+                    })
+                })
+                .collect();
+            let mut written = Vec::new();
+            for handle in handles {
+                let result = handle.join().unwrap().expect("sync");
+                written.extend(result.writes.into_iter().map(|w| match w {
+                    WriteResult::Written { path, .. }
+                    | WriteResult::Unchanged { path }
+                    | WriteResult::WouldWrite { path }
+                    | WriteResult::Ignored { path } => path,
+                }));
+            }
+
+            let store = hash_store::load_at(home.path(), "copnow_api").unwrap();
+            for path in &written {
+                assert!(
+                    store.files.contains_key(&path.to_string_lossy().to_string()),
+                    "missing hash entry for {}",
+                    path.display()
+                );
+            }
+        }
+    }
+
     #[test]
     fn dry_run_context_has_no_last_synced() {
         let codebase = make_codebase_for_context("ctx");
         let synced_at = Utc::now();
-        let ctx = build_sync_context(&codebase, true, true, synced_at);
+        let ctx = build_sync_context(&ProjectName::from("copnow"), &codebase, true, true, synced_at);
         assert!(ctx.meta.last_synced.is_none());
     }
 
@@ -455,13 +2036,45 @@ mod tests {
         let codebase = make_codebase_for_context("ctx");
         let synced_at = Utc::now() - ChronoDuration::hours(1);
 
-        let with_store = build_sync_context(&codebase, false, true, synced_at);
+        let with_store = build_sync_context(&ProjectName::from("copnow"), &codebase, false, true, synced_at);
         assert_eq!(with_store.meta.last_synced, Some(synced_at));
 
-        let without_store = build_sync_context(&codebase, false, false, synced_at);
+        let without_store = build_sync_context(&ProjectName::from("copnow"), &codebase, false, false, synced_at);
         assert!(without_store.meta.last_synced.is_none());
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn rewrite_preserves_existing_permission_bits() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        for mode in [0o600, 0o644, 0o755] {
+            let path = dir.path().join(format!("file-{mode:o}.md"));
+            fs::write(&path, "original").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+
+            let result = write_content(&path, "rewritten");
+            assert!(matches!(result, WriteResult::Written { .. }));
+            assert_eq!(fs::read_to_string(&path).unwrap(), "rewritten");
+
+            let actual = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(actual, mode, "mode {mode:o} should survive a rewrite");
+        }
+    }
+
+    #[test]
+    fn fresh_write_is_synced_and_leaves_no_tmp() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested").join("fresh.md");
+        let tmp = PathBuf::from(format!("{}.orchestra.tmp", path.display()));
+
+        let result = write_content(&path, "synced content");
+        assert!(matches!(result, WriteResult::Written { .. }));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "synced content");
+        assert!(!tmp.exists(), "tmp file should be renamed away");
+    }
+
     #[test]
     #[cfg(unix)]
     fn rename_failure_leaves_original_and_cleans_tmp() {
@@ -481,9 +2094,17 @@ mod tests {
         let tmp_dir = TempDir::new().unwrap();
         let tmp_path = tmp_dir.path().join("file.md.orchestra.tmp");
 
-        let mut store = HashMap::new();
-        let err = atomic_write_with_tmp(&path, "new content", &mut store, false, &tmp_path)
-            .expect_err("rename should fail on readonly dir");
+        let mut store = empty_store();
+        let err = atomic_write_with_tmp(
+            &path,
+            "new content",
+            OutputStrategy::Replace,
+            &mut store,
+            false,
+            None,
+            &tmp_path,
+        )
+        .expect_err("rename should fail on readonly dir");
         let _ = err;
 
         let current = fs::read_to_string(&path).unwrap();
@@ -494,4 +2115,251 @@ mod tests {
         perms.set_mode(0o755);
         fs::set_permissions(&readonly_dir, perms).unwrap();
     }
+
+    fn setup_codebase_with_hooks(
+        home: &TempDir,
+        workspace: &TempDir,
+        hooks: orchestra_core::types::SyncHooks,
+    ) -> PathBuf {
+        let codebase_dir = workspace.path().join("copnow_api");
+        fs::create_dir_all(&codebase_dir).unwrap();
+        registry::init_at(
+            codebase_dir.clone(),
+            ProjectName::from("copnow"),
+            Some(ProjectType::Backend),
+            home.path(),
+        )
+        .expect("init");
+        let project = ProjectName::from("copnow");
+        let name = CodebaseName::from("copnow_api");
+        let mut codebase = registry::load_codebase_at(home.path(), &project, &name).unwrap();
+        codebase.hooks = Some(hooks);
+        registry::save_codebase_at(home.path(), &project, &codebase).unwrap();
+        codebase_dir
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hooks_see_codebase_written_files_and_dry_run_flag() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_codebase_with_hooks(
+            &home,
+            &workspace,
+            orchestra_core::types::SyncHooks {
+                pre_sync: Some(
+                    "printf '%s|%s' \"$ORCHESTRA_CODEBASE\" \"$ORCHESTRA_DRY_RUN\" > pre.txt"
+                        .into(),
+                ),
+                post_sync: Some("printf '%s' \"$ORCHESTRA_WRITTEN_FILES\" > post.txt".into()),
+                allow_in_daemon: false,
+            },
+        );
+
+        let result = sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        assert!(result.post_sync_error.is_none());
+        assert_eq!(
+            fs::read_to_string(codebase_dir.join("pre.txt")).unwrap(),
+            "copnow_api|0"
+        );
+        let written = fs::read_to_string(codebase_dir.join("post.txt")).unwrap();
+        let written: Vec<&str> = written.lines().collect();
+        assert!(
+            written.contains(&"orchestra/controls/CLAUDE.md"),
+            "{written:?}"
+        );
+        assert!(
+            written.iter().all(|path| !path.starts_with('/')),
+            "{written:?}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dry_run_and_unchanged_syncs_skip_post_sync_hook() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_codebase_with_hooks(
+            &home,
+            &workspace,
+            orchestra_core::types::SyncHooks {
+                pre_sync: Some("printf '%s' \"$ORCHESTRA_DRY_RUN\" > pre.txt".into()),
+                post_sync: Some("echo ran >> post.txt".into()),
+                allow_in_daemon: false,
+            },
+        );
+        let post = codebase_dir.join("post.txt");
+
+        sync_codebase("copnow_api", home.path(), true, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(codebase_dir.join("pre.txt")).unwrap(),
+            "1"
+        );
+        assert!(!post.exists());
+
+        sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        assert_eq!(fs::read_to_string(&post).unwrap(), "ran\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_pre_sync_hook_aborts_before_writing() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_codebase_with_hooks(
+            &home,
+            &workspace,
+            orchestra_core::types::SyncHooks {
+                pre_sync: Some("echo 'lint failed' >&2; exit 3".into()),
+                ..Default::default()
+            },
+        );
+
+        let err = sync_codebase("copnow_api", home.path(), false, None).unwrap_err();
+        match err {
+            SyncError::HookFailed {
+                hook, stderr_tail, ..
+            } => {
+                assert_eq!(hook, HookKind::PreSync);
+                assert_eq!(stderr_tail, "lint failed");
+            }
+            other => panic!("expected HookFailed, got {other:?}"),
+        }
+        assert!(!codebase_dir.join("orchestra/controls/CLAUDE.md").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_post_sync_hook_is_reported_and_keeps_writes() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let codebase_dir = setup_codebase_with_hooks(
+            &home,
+            &workspace,
+            orchestra_core::types::SyncHooks {
+                post_sync: Some("exit 1".into()),
+                ..Default::default()
+            },
+        );
+
+        let result = sync_codebase("copnow_api", home.path(), false, None).unwrap();
+        assert!(matches!(
+            result.post_sync_error,
+            Some(SyncError::HookFailed {
+                hook: HookKind::PostSync,
+                ..
+            })
+        ));
+        assert!(codebase_dir.join("orchestra/controls/CLAUDE.md").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn daemon_syncs_skip_hooks_unless_allowed() {
+        let home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let mut hooks = orchestra_core::types::SyncHooks {
+            pre_sync: Some("echo ran >> pre.txt".into()),
+            ..Default::default()
+        };
+        let codebase_dir = setup_codebase_with_hooks(&home, &workspace, hooks.clone());
+        let pre = codebase_dir.join("pre.txt");
+
+        sync_codebase_from(
+            "copnow_api",
+            home.path(),
+            false,
+            None,
+            SyncSource::Daemon,
+            true,
+            false,
+            WriteFilter::All,
+            SyncOptions::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+        assert!(!pre.exists());
+
+        hooks.allow_in_daemon = true;
+        setup_codebase_with_hooks(&home, &workspace, hooks);
+        sync_codebase_from(
+            "copnow_api",
+            home.path(),
+            false,
+            None,
+            SyncSource::Daemon,
+            true,
+            false,
+            WriteFilter::All,
+            SyncOptions::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+        assert!(pre.exists());
+    }
+
+    #[test]
+    fn unknown_codebase_lists_the_registered_ones() {
+        let home = TempDir::new().unwrap();
+        let codebase_root = TempDir::new().unwrap();
+        let codebase_dir = codebase_root.path().join("payments");
+        fs::create_dir_all(&codebase_dir).unwrap();
+        registry::init_at(codebase_dir, ProjectName::from("copnow"), None, home.path())
+            .expect("init");
+
+        let err = find_codebase_at(home.path(), "paymnets").unwrap_err();
+        assert!(
+            matches!(&err, SyncError::CodebaseNotFound { name, known }
+                if name == "paymnets" && known == &["payments".to_string()]),
+            "got: {err:?}"
+        );
+        assert!(
+            err.to_string().contains("did you mean 'payments'?"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn unreadable_registry_is_not_reported_as_unknown_codebase() {
+        let home = TempDir::new().unwrap();
+        let codebase_root = TempDir::new().unwrap();
+        let codebase_dir = codebase_root.path().join("payments");
+        fs::create_dir_all(&codebase_dir).unwrap();
+        registry::init_at(codebase_dir, ProjectName::from("copnow"), None, home.path())
+            .expect("init");
+        let yaml = orchestra_core::paths::dirs_at(home.path())
+            .registry_root
+            .join("copnow/payments.yaml");
+        fs::write(&yaml, "name: [unclosed").unwrap();
+
+        let err = find_codebase_at(home.path(), "paymnets").unwrap_err();
+        assert!(
+            matches!(
+                err,
+                SyncError::Registry(
+                    RegistryError::Parse { .. } | RegistryError::ParseDetailed { .. }
+                )
+            ),
+            "got: {err:?}"
+        );
+    }
+
+    #[test]
+    fn another_codebases_unreadable_file_does_not_block_lookup() {
+        let home = TempDir::new().unwrap();
+        let codebase_root = TempDir::new().unwrap();
+        let codebase_dir = codebase_root.path().join("payments");
+        fs::create_dir_all(&codebase_dir).unwrap();
+        registry::init_at(codebase_dir, ProjectName::from("copnow"), None, home.path())
+            .expect("init");
+        let stray = orchestra_core::paths::dirs_at(home.path())
+            .registry_root
+            .join("copnow/backup.yaml");
+        fs::write(&stray, "name: [unclosed").unwrap();
+
+        let (_, codebase) = find_codebase_at(home.path(), "payments").expect("find");
+        assert_eq!(codebase.name.0, "payments");
+        assert!(find_codebase_at(home.path(), "backup").is_err());
+    }
 }
@@ -32,7 +32,7 @@ fn init_codebase(
     .expect("init");
 
     if do_sync {
-        sync_codebase(codebase_name, home.path(), false).expect("sync");
+        sync_codebase(codebase_name, home.path(), false, None).expect("sync");
     }
 
     registry::list_codebases_at(home.path())
@@ -61,7 +61,7 @@ fn stale_when_registry_is_newer_than_managed_files() {
     let new = FileTime::from_system_time(SystemTime::now() + Duration::from_secs(120));
     set_file_mtime(&registry_path, new).expect("touch registry mtime");
 
-    let signal = check(home.path(), &project, &codebase).expect("check");
+    let signal = check(home.path(), &project, &codebase, false).expect("check");
     match signal {
         StalenessSignal::Stale { .. } => {}
         other => panic!("expected stale, got {other:?}"),
@@ -78,7 +78,7 @@ fn modified_when_hash_mismatch_detected() {
     let claude = codebase.path.join("orchestra/controls/CLAUDE.md");
     fs::write(&claude, "manually changed content\n").expect("edit CLAUDE.md");
 
-    let signal = check(home.path(), &project, &codebase).expect("check");
+    let signal = check(home.path(), &project, &codebase, false).expect("check");
     match signal {
         StalenessSignal::Modified { files } => {
             assert!(
@@ -97,7 +97,7 @@ fn current_immediately_after_sync() {
     let project = ProjectName::from("copnow");
     let codebase = init_codebase(&home, &workspace, &project, "copnow_api", true);
 
-    let signal = check(home.path(), &project, &codebase).expect("check");
+    let signal = check(home.path(), &project, &codebase, false).expect("check");
     assert_eq!(signal, StalenessSignal::Current);
 }
 
@@ -108,6 +108,6 @@ fn never_synced_when_registry_exists_but_no_hash_store() {
     let project = ProjectName::from("copnow");
     let codebase = init_codebase(&home, &workspace, &project, "copnow_api", false);
 
-    let signal = check(home.path(), &project, &codebase).expect("check");
+    let signal = check(home.path(), &project, &codebase, false).expect("check");
     assert_eq!(signal, StalenessSignal::NeverSynced);
 }